@@ -0,0 +1,145 @@
+use bitcoin::base58;
+use bitcoin::bech32::{segwit, Hrp};
+use bitcoin::{Amount, CompressedPublicKey, FeeRate, Network, Script, ScriptBuf};
+
+use crate::{Error, Result};
+
+/// Address, fee and dust parameters of a Bitcoin-derived UTXO chain
+///
+/// Chains without segwit lock the HTLC in P2SH, fund it from P2PKH outputs and sign with the
+/// legacy sighash; segwit chains use P2WSH, P2WPKH and BIP143 as on Bitcoin.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UtxoChain {
+    pub name: &'static str,
+    /// Bech32 prefix of segwit addresses, or `None` for chains without segwit
+    pub segwit_hrp: Option<&'static str>,
+    pub p2pkh_prefix: u8,
+    pub p2sh_prefix: u8,
+    /// Lowest fee rate the chain's nodes relay
+    pub min_fee_rate: FeeRate,
+    /// Smallest output the chain's nodes relay, on top of the script-based dust rule
+    pub dust_limit: Amount,
+}
+
+impl UtxoChain {
+    pub const BITCOIN: Self = Self {
+        name: "bitcoin",
+        segwit_hrp: Some("bc"),
+        p2pkh_prefix: 0x00,
+        p2sh_prefix: 0x05,
+        min_fee_rate: FeeRate::from_sat_per_vb_u32(1),
+        dust_limit: Amount::ZERO,
+    };
+
+    /// Testnet and signet share address formats
+    pub const BITCOIN_TESTNET: Self = Self {
+        name: "bitcoin-testnet",
+        segwit_hrp: Some("tb"),
+        p2pkh_prefix: 0x6f,
+        p2sh_prefix: 0xc4,
+        ..Self::BITCOIN
+    };
+
+    pub const BITCOIN_REGTEST: Self = Self {
+        name: "bitcoin-regtest",
+        segwit_hrp: Some("bcrt"),
+        ..Self::BITCOIN_TESTNET
+    };
+
+    pub const LITECOIN: Self = Self {
+        name: "litecoin",
+        segwit_hrp: Some("ltc"),
+        p2pkh_prefix: 0x30,
+        p2sh_prefix: 0x32,
+        min_fee_rate: FeeRate::from_sat_per_vb_u32(10),
+        dust_limit: Amount::ZERO,
+    };
+
+    pub const LITECOIN_TESTNET: Self = Self {
+        name: "litecoin-testnet",
+        segwit_hrp: Some("tltc"),
+        p2pkh_prefix: 0x6f,
+        p2sh_prefix: 0x3a,
+        ..Self::LITECOIN
+    };
+
+    /// Fees are in koinu per byte; Dogecoin Core recommends 0.01 DOGE per kB and treats
+    /// outputs under 0.01 DOGE as dust
+    pub const DOGECOIN: Self = Self {
+        name: "dogecoin",
+        segwit_hrp: None,
+        p2pkh_prefix: 0x1e,
+        p2sh_prefix: 0x16,
+        min_fee_rate: FeeRate::from_sat_per_vb_u32(1_000),
+        dust_limit: Amount::from_sat(1_000_000),
+    };
+
+    pub const DOGECOIN_TESTNET: Self = Self {
+        name: "dogecoin-testnet",
+        p2pkh_prefix: 0x71,
+        p2sh_prefix: 0xc4,
+        ..Self::DOGECOIN
+    };
+
+    pub fn bitcoin(network: Network) -> Self {
+        match network {
+            Network::Bitcoin => Self::BITCOIN,
+            Network::Regtest => Self::BITCOIN_REGTEST,
+            _ => Self::BITCOIN_TESTNET,
+        }
+    }
+
+    pub fn is_segwit(&self) -> bool {
+        self.segwit_hrp.is_some()
+    }
+
+    /// Output script locking funds to `script`: P2WSH on segwit chains, P2SH otherwise
+    pub fn script_hash_output(&self, script: &Script) -> ScriptBuf {
+        if self.is_segwit() {
+            ScriptBuf::new_p2wsh(&script.wscript_hash())
+        } else {
+            ScriptBuf::new_p2sh(&script.script_hash())
+        }
+    }
+
+    /// Output script paying `key`: P2WPKH on segwit chains, P2PKH otherwise
+    pub fn key_hash_output(&self, key: &CompressedPublicKey) -> ScriptBuf {
+        if self.is_segwit() {
+            ScriptBuf::new_p2wpkh(&key.wpubkey_hash())
+        } else {
+            ScriptBuf::new_p2pkh(&key.pubkey_hash())
+        }
+    }
+
+    /// Address of a P2PKH, P2SH, P2WPKH or P2WSH output script in this chain's format
+    pub fn address(&self, script_pubkey: &Script) -> Result<String> {
+        let base58 = |prefix: u8, hash: &[u8]| {
+            let mut payload = vec![prefix];
+            payload.extend_from_slice(hash);
+            base58::encode_check(&payload)
+        };
+        let bytes = script_pubkey.as_bytes();
+        if script_pubkey.is_p2pkh() {
+            Ok(base58(self.p2pkh_prefix, &bytes[3..23]))
+        } else if script_pubkey.is_p2sh() {
+            Ok(base58(self.p2sh_prefix, &bytes[2..22]))
+        } else if let (Some(version), Some(hrp)) =
+            (script_pubkey.witness_version(), self.segwit_hrp)
+        {
+            let hrp = Hrp::parse(hrp).map_err(|_| Error::UnsupportedScript)?;
+            segwit::encode(hrp, version.to_fe(), &bytes[2..]).map_err(|_| Error::UnsupportedScript)
+        } else {
+            Err(Error::UnsupportedScript)
+        }
+    }
+
+    /// `estimate` raised to what the chain's nodes relay
+    pub fn fee_rate(&self, estimate: FeeRate) -> FeeRate {
+        estimate.max(self.min_fee_rate)
+    }
+
+    /// Smallest relayable value of an output paying `script_pubkey`
+    pub fn min_output(&self, script_pubkey: &Script) -> Amount {
+        script_pubkey.minimal_non_dust().max(self.dust_limit)
+    }
+}
@@ -0,0 +1,159 @@
+use bitcoin::consensus::encode::{deserialize_hex, serialize_hex};
+use std::collections::HashMap;
+
+use bitcoin::{Amount, FeeRate, OutPoint, ScriptBuf, Transaction, Txid};
+use serde::Deserialize;
+
+use crate::chain::UtxoChain;
+use crate::tx::Utxo;
+use crate::{Error, Result};
+
+/// Minimal client for an Esplora REST API (Blockstream, mempool.space, litecoinspace.org or a
+/// self-hosted one); addresses are passed in the chain's own format
+#[derive(Clone, Debug)]
+pub struct EsploraClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+/// Confirmation status of a transaction
+#[derive(Clone, Debug, Deserialize)]
+pub struct TxStatus {
+    pub confirmed: bool,
+    pub block_height: Option<u32>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EsploraTxOut {
+    pub scriptpubkey: ScriptBuf,
+    pub value: u64,
+}
+
+/// A transaction as listed by the address endpoints, without witnesses
+#[derive(Clone, Debug, Deserialize)]
+pub struct EsploraTx {
+    pub txid: Txid,
+    pub vout: Vec<EsploraTxOut>,
+    pub status: TxStatus,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct EsploraUtxo {
+    pub txid: Txid,
+    pub vout: u32,
+    pub value: u64,
+    pub status: TxStatus,
+}
+
+impl From<&EsploraUtxo> for Utxo {
+    fn from(utxo: &EsploraUtxo) -> Self {
+        Utxo {
+            outpoint: OutPoint::new(utxo.txid, utxo.vout),
+            value: Amount::from_sat(utxo.value),
+        }
+    }
+}
+
+/// Whether an output has been spent, and by which transaction
+#[derive(Clone, Debug, Deserialize)]
+pub struct Outspend {
+    pub spent: bool,
+    pub txid: Option<Txid>,
+    pub status: Option<TxStatus>,
+}
+
+impl EsploraClient {
+    /// `base_url` is the API root, e.g. `https://blockstream.info/testnet/api`
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn tip_height(&self) -> Result<u32> {
+        let body = self.get_text("/blocks/tip/height").await?;
+        body.trim()
+            .parse()
+            .map_err(|_| Error::Esplora(format!("invalid tip height: {body}")))
+    }
+
+    pub async fn address_utxos(&self, address: &str) -> Result<Vec<EsploraUtxo>> {
+        self.get_json(&format!("/address/{address}/utxo")).await
+    }
+
+    /// Most recent transactions touching an address, newest first
+    pub async fn address_txs(&self, address: &str) -> Result<Vec<EsploraTx>> {
+        self.get_json(&format!("/address/{address}/txs")).await
+    }
+
+    /// Fee rate to confirm within `target_blocks`, raised to the chain's relay minimum; falls
+    /// back to that minimum on backends without estimates
+    pub async fn fee_rate(&self, chain: &UtxoChain, target_blocks: u16) -> Result<FeeRate> {
+        let estimates: HashMap<String, f64> = match self.get_json("/fee-estimates").await {
+            Ok(estimates) => estimates,
+            Err(Error::Http(error)) if error.is_status() => return Ok(chain.min_fee_rate),
+            Err(error) => return Err(error),
+        };
+        // Targets are keyed by block count; take the nearest one no slower than asked for
+        let sat_per_vb = estimates
+            .iter()
+            .filter_map(|(target, rate)| Some((target.parse::<u16>().ok()?, *rate)))
+            .filter(|(target, _)| *target <= target_blocks)
+            .max_by_key(|(target, _)| *target)
+            .map_or(0.0, |(_, rate)| rate);
+        let estimate = FeeRate::from_sat_per_kwu((sat_per_vb * 250.0).ceil() as u64);
+        Ok(chain.fee_rate(estimate))
+    }
+
+    pub async fn outspend(&self, outpoint: &OutPoint) -> Result<Outspend> {
+        self.get_json(&format!("/tx/{}/outspend/{}", outpoint.txid, outpoint.vout))
+            .await
+    }
+
+    /// Full transaction, witnesses included
+    pub async fn transaction(&self, txid: &Txid) -> Result<Transaction> {
+        let hex = self.get_text(&format!("/tx/{txid}/hex")).await?;
+        Ok(deserialize_hex(hex.trim())?)
+    }
+
+    /// Submit a signed transaction to the mempool
+    pub async fn broadcast(&self, tx: &Transaction) -> Result<Txid> {
+        let response = self
+            .http
+            .post(format!("{}/tx", self.base_url))
+            .body(serialize_hex(tx))
+            .send()
+            .await?;
+        let status = response.status();
+        let body = response.text().await?;
+        if !status.is_success() {
+            return Err(Error::Esplora(format!("broadcast rejected: {body}")));
+        }
+        body.trim()
+            .parse()
+            .map_err(|_| Error::Esplora(format!("invalid txid: {body}")))
+    }
+
+    async fn get_text(&self, path: &str) -> Result<String> {
+        Ok(self
+            .http
+            .get(format!("{}{path}", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?)
+    }
+
+    async fn get_json<T: serde::de::DeserializeOwned>(&self, path: &str) -> Result<T> {
+        Ok(self
+            .http
+            .get(format!("{}{path}", self.base_url))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
@@ -0,0 +1,48 @@
+// Bitcoin HTLC Leg
+// Locks native BTC in a P2WSH script with the same hashlock and timelock terms as the escrow
+// contracts on the other chains. Bitcoin script cannot hash with keccak256, so orders with a
+// BTC leg need a sha256 hashlock on the counterpart too: a Stellar escrow with
+// `LockType::Sha256`, a classic Stellar hash(x) escrow or a TON escrow, but no EVM escrow,
+// which checks keccak256 only. The receiver claims with the secret, which
+// lands in the claim transaction's witness where the watcher picks it up for the counterpart
+// escrow; the sender refunds once the CHECKLOCKTIMEVERIFY timelock has passed.
+// The same HTLC runs on other Bitcoin-derived chains through `UtxoChain`: Litecoin uses P2WSH
+// like Bitcoin, while Dogecoin has no segwit and locks it in P2SH with legacy signatures.
+
+pub mod chain;
+pub mod esplora;
+pub mod script;
+pub mod tx;
+pub mod watcher;
+
+pub use chain::UtxoChain;
+pub use esplora::EsploraClient;
+pub use script::{sha256, HtlcParams};
+pub use tx::{build_claim, build_funding, build_refund, Utxo};
+pub use watcher::{extract_secret, HtlcStatus, HtlcWatcher};
+
+use bitcoin::Amount;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("secret does not match the hashlock")]
+    InvalidSecret,
+    #[error("signing key does not match the HTLC")]
+    KeyMismatch,
+    #[error("script is not an HTLC")]
+    InvalidScript,
+    #[error("output script has no address")]
+    UnsupportedScript,
+    #[error("insufficient funds: need {needed}, have {available}")]
+    InsufficientFunds { needed: Amount, available: Amount },
+    #[error("output would be dust after paying {fee} in fees")]
+    Dust { fee: Amount },
+    #[error("esplora: {0}")]
+    Esplora(String),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Decode(#[from] bitcoin::consensus::encode::FromHexError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
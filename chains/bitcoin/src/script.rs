@@ -0,0 +1,107 @@
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::opcodes::all::{
+    OP_CHECKSIG, OP_CLTV, OP_DROP, OP_ELSE, OP_ENDIF, OP_EQUALVERIFY, OP_IF, OP_SHA256, OP_SIZE,
+};
+use bitcoin::script::{Builder, Instruction};
+use bitcoin::{CompressedPublicKey, ScriptBuf};
+
+use crate::chain::UtxoChain;
+use crate::{Error, Result};
+
+/// Length of the secret; the script rejects any other size so it matches the 32-byte secrets
+/// the escrow contracts accept
+pub const SECRET_LEN: usize = 32;
+
+/// Terms of an HTLC on Bitcoin or another UTXO chain
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HtlcParams {
+    /// sha256 of the 32-byte secret
+    pub secret_hash: [u8; 32],
+    /// Key that claims with the secret
+    pub receiver: CompressedPublicKey,
+    /// Key that refunds once the timelock has passed
+    pub sender: CompressedPublicKey,
+    /// Block height or unix time from which the sender may refund
+    pub timelock: LockTime,
+}
+
+impl HtlcParams {
+    /// The witness script:
+    /// `OP_IF OP_SIZE 32 OP_EQUALVERIFY OP_SHA256 <hash> OP_EQUALVERIFY <receiver>
+    ///  OP_ELSE <timelock> OP_CLTV OP_DROP <sender> OP_ENDIF OP_CHECKSIG`
+    pub fn script(&self) -> ScriptBuf {
+        Builder::new()
+            .push_opcode(OP_IF)
+            .push_opcode(OP_SIZE)
+            .push_int(SECRET_LEN as i64)
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_SHA256)
+            .push_slice(self.secret_hash)
+            .push_opcode(OP_EQUALVERIFY)
+            .push_slice(self.receiver.to_bytes())
+            .push_opcode(OP_ELSE)
+            .push_lock_time(self.timelock)
+            .push_opcode(OP_CLTV)
+            .push_opcode(OP_DROP)
+            .push_slice(self.sender.to_bytes())
+            .push_opcode(OP_ENDIF)
+            .push_opcode(OP_CHECKSIG)
+            .into_script()
+    }
+
+    /// Output script funding transactions pay to, P2WSH or P2SH depending on the chain
+    pub fn script_pubkey(&self, chain: &UtxoChain) -> ScriptBuf {
+        chain.script_hash_output(&self.script())
+    }
+
+    /// Address of the HTLC in the chain's format
+    pub fn address(&self, chain: &UtxoChain) -> String {
+        chain
+            .address(&self.script_pubkey(chain))
+            .expect("P2WSH and P2SH outputs have addresses")
+    }
+
+    /// Recover the terms from a witness script, e.g. one handed over by the counterparty,
+    /// failing unless it is exactly the script `script()` would build
+    pub fn from_script(script: &ScriptBuf) -> Result<Self> {
+        let pushes = script
+            .instructions()
+            .map(|instruction| match instruction {
+                Ok(Instruction::PushBytes(bytes)) => Ok(Some(bytes.as_bytes().to_vec())),
+                Ok(Instruction::Op(_)) => Ok(None),
+                Err(_) => Err(Error::InvalidScript),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if pushes.len() != 15 {
+            return Err(Error::InvalidScript);
+        }
+        let push = |index: usize| pushes[index].as_deref().ok_or(Error::InvalidScript);
+
+        let params = HtlcParams {
+            secret_hash: push(5)?.try_into().map_err(|_| Error::InvalidScript)?,
+            receiver: CompressedPublicKey::from_slice(push(7)?)
+                .map_err(|_| Error::InvalidScript)?,
+            timelock: bitcoin::script::read_scriptint(push(9)?)
+                .ok()
+                .and_then(|n| u32::try_from(n).ok())
+                .map(LockTime::from_consensus)
+                .ok_or(Error::InvalidScript)?,
+            sender: CompressedPublicKey::from_slice(push(12)?).map_err(|_| Error::InvalidScript)?,
+        };
+        if params.script() != *script {
+            return Err(Error::InvalidScript);
+        }
+        Ok(params)
+    }
+
+    /// Whether a secret opens this HTLC
+    pub fn verify_secret(&self, secret: &[u8; 32]) -> bool {
+        sha256(secret) == self.secret_hash
+    }
+}
+
+/// Hash a secret into the hashlock used by the HTLC
+pub fn sha256(secret: &[u8; 32]) -> [u8; 32] {
+    sha256::Hash::hash(secret).to_byte_array()
+}
@@ -0,0 +1,268 @@
+use bitcoin::absolute::LockTime;
+use bitcoin::hashes::Hash;
+use bitcoin::script::{Builder, PushBytesBuf};
+use bitcoin::secp256k1::{Message, Secp256k1, SecretKey};
+use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+use bitcoin::transaction::Version;
+use bitcoin::{
+    ecdsa, Amount, CompressedPublicKey, FeeRate, OutPoint, ScriptBuf, Sequence, Transaction, TxIn,
+    TxOut, Witness,
+};
+
+use crate::chain::UtxoChain;
+use crate::script::HtlcParams;
+use crate::{Error, Result};
+
+/// Stand-in for a DER signature plus sighash byte, the largest a low-R signature gets,
+/// used to size transactions before they are signed
+const MAX_SIGNATURE_LEN: usize = 72;
+
+/// An output paying the funding key: P2WPKH on segwit chains, P2PKH otherwise
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Utxo {
+    pub outpoint: OutPoint,
+    pub value: Amount,
+}
+
+/// Pay `amount` into the HTLC from the sender's `utxos`, returning change to `change`
+/// Change too small to relay is left to the fee
+pub fn build_funding(
+    chain: &UtxoChain,
+    params: &HtlcParams,
+    amount: Amount,
+    utxos: &[Utxo],
+    key: &SecretKey,
+    change: ScriptBuf,
+    fee_rate: FeeRate,
+) -> Result<Transaction> {
+    let secp = Secp256k1::new();
+    let pubkey = CompressedPublicKey(key.public_key(&secp));
+    let input_script = chain.key_hash_output(&pubkey);
+    let placeholder = vec![vec![0; MAX_SIGNATURE_LEN], pubkey.to_bytes().to_vec()];
+
+    let mut tx = Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: utxos
+            .iter()
+            .map(|utxo| {
+                let mut input = TxIn {
+                    previous_output: utxo.outpoint,
+                    sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+                    ..Default::default()
+                };
+                unlock(chain, &mut input, &placeholder);
+                input
+            })
+            .collect(),
+        output: vec![
+            TxOut {
+                value: amount,
+                script_pubkey: params.script_pubkey(chain),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: change,
+            },
+        ],
+    };
+
+    let available = utxos.iter().map(|utxo| utxo.value).sum::<Amount>();
+    let fee_with_change = estimate_fee(&tx, fee_rate);
+    let change_value = available
+        .checked_sub(amount + fee_with_change)
+        .unwrap_or(Amount::ZERO);
+    if change_value >= chain.min_output(&tx.output[1].script_pubkey) {
+        tx.output[1].value = change_value;
+    } else {
+        tx.output.pop();
+        let needed = amount + estimate_fee(&tx, fee_rate);
+        if available < needed {
+            return Err(Error::InsufficientFunds { needed, available });
+        }
+    }
+
+    let mut cache = SighashCache::new(&tx);
+    let signatures = utxos
+        .iter()
+        .enumerate()
+        .map(|(index, utxo)| {
+            let sighash = if chain.is_segwit() {
+                cache
+                    .p2wpkh_signature_hash(index, &input_script, utxo.value, EcdsaSighashType::All)
+                    .expect("input exists and its script is P2WPKH")
+                    .to_byte_array()
+            } else {
+                legacy_sighash(&cache, index, &input_script)
+            };
+            sign(&secp, key, sighash).to_vec()
+        })
+        .collect::<Vec<_>>();
+    for (input, signature) in tx.input.iter_mut().zip(signatures) {
+        unlock(chain, input, &[signature, pubkey.to_bytes().to_vec()]);
+    }
+    Ok(tx)
+}
+
+/// Spend the HTLC output to `destination` by revealing the secret, signed by the receiver
+pub fn build_claim(
+    chain: &UtxoChain,
+    params: &HtlcParams,
+    funding: Utxo,
+    destination: ScriptBuf,
+    fee_rate: FeeRate,
+    secret: &[u8; 32],
+    key: &SecretKey,
+) -> Result<Transaction> {
+    if !params.verify_secret(secret) {
+        return Err(Error::InvalidSecret);
+    }
+    spend(
+        chain,
+        params,
+        &params.receiver,
+        LockTime::ZERO,
+        funding,
+        destination,
+        fee_rate,
+        key,
+        // Selects the OP_IF branch
+        vec![secret.to_vec(), vec![1]],
+    )
+}
+
+/// Return the HTLC output to `destination` once the timelock has passed, signed by the sender
+/// The transaction is only valid for mining from `params.timelock`
+pub fn build_refund(
+    chain: &UtxoChain,
+    params: &HtlcParams,
+    funding: Utxo,
+    destination: ScriptBuf,
+    fee_rate: FeeRate,
+    key: &SecretKey,
+) -> Result<Transaction> {
+    spend(
+        chain,
+        params,
+        &params.sender,
+        params.timelock,
+        funding,
+        destination,
+        fee_rate,
+        key,
+        // Selects the OP_ELSE branch
+        vec![vec![]],
+    )
+}
+
+/// Spend the HTLC output with `key`, which must be `signer`, pushing `branch` between the
+/// signature and the HTLC script
+#[allow(clippy::too_many_arguments)]
+fn spend(
+    chain: &UtxoChain,
+    params: &HtlcParams,
+    signer: &CompressedPublicKey,
+    lock_time: LockTime,
+    funding: Utxo,
+    destination: ScriptBuf,
+    fee_rate: FeeRate,
+    key: &SecretKey,
+    branch: Vec<Vec<u8>>,
+) -> Result<Transaction> {
+    let secp = Secp256k1::new();
+    if CompressedPublicKey(key.public_key(&secp)) != *signer {
+        return Err(Error::KeyMismatch);
+    }
+    let script = params.script();
+    let items = |signature: Vec<u8>| {
+        let mut items = vec![signature];
+        items.extend(branch.iter().cloned());
+        items.push(script.to_bytes());
+        items
+    };
+
+    let mut tx = Transaction {
+        version: Version::TWO,
+        lock_time,
+        input: vec![TxIn {
+            previous_output: funding.outpoint,
+            // Anything below final so CHECKLOCKTIMEVERIFY accepts the lock time
+            sequence: Sequence::ENABLE_RBF_NO_LOCKTIME,
+            ..Default::default()
+        }],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: destination,
+        }],
+    };
+
+    unlock(chain, &mut tx.input[0], &items(vec![0; MAX_SIGNATURE_LEN]));
+
+    let fee = estimate_fee(&tx, fee_rate);
+    let value = funding.value.checked_sub(fee).unwrap_or(Amount::ZERO);
+    if value < chain.min_output(&tx.output[0].script_pubkey) {
+        return Err(Error::Dust { fee });
+    }
+    tx.output[0].value = value;
+
+    let mut cache = SighashCache::new(&tx);
+    let sighash = if chain.is_segwit() {
+        cache
+            .p2wsh_signature_hash(0, &script, funding.value, EcdsaSighashType::All)
+            .expect("input exists")
+            .to_byte_array()
+    } else {
+        legacy_sighash(&cache, 0, &script)
+    };
+    let signature = sign(&secp, key, sighash);
+    unlock(chain, &mut tx.input[0], &items(signature.to_vec()));
+    Ok(tx)
+}
+
+fn sign(
+    secp: &Secp256k1<bitcoin::secp256k1::All>,
+    key: &SecretKey,
+    sighash: [u8; 32],
+) -> ecdsa::Signature {
+    ecdsa::Signature {
+        signature: secp.sign_ecdsa_low_r(&Message::from_digest(sighash), key),
+        sighash_type: EcdsaSighashType::All,
+    }
+}
+
+/// Put `items` in the input's witness on segwit chains, or push them in its script sig
+fn unlock(chain: &UtxoChain, input: &mut TxIn, items: &[Vec<u8>]) {
+    if chain.is_segwit() {
+        input.witness = Witness::from_slice(items);
+        return;
+    }
+    let script_sig = items
+        .iter()
+        .fold(Builder::new(), |builder, item| match item.as_slice() {
+            // Standardness requires the minimal push, so branch selectors become opcodes
+            [] => builder.push_int(0),
+            [n @ 1..=16] => builder.push_int(i64::from(*n)),
+            _ => builder
+                .push_slice(PushBytesBuf::try_from(item.clone()).expect("push fits a script")),
+        });
+    input.script_sig = script_sig.into_script();
+}
+
+/// Pre-segwit sighash of input `index`, signing `script_code`
+fn legacy_sighash(
+    cache: &SighashCache<&Transaction>,
+    index: usize,
+    script_code: &ScriptBuf,
+) -> [u8; 32] {
+    cache
+        .legacy_signature_hash(index, script_code, EcdsaSighashType::All.to_u32())
+        .expect("input exists")
+        .to_byte_array()
+}
+
+/// Fee for `tx` at `fee_rate`, sized with its placeholder signatures
+fn estimate_fee(tx: &Transaction, fee_rate: FeeRate) -> Amount {
+    fee_rate
+        .fee_vb(tx.vsize() as u64)
+        .expect("fee for a standard-size transaction fits in an Amount")
+}
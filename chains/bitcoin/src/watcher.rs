@@ -0,0 +1,144 @@
+use std::time::Duration;
+
+use bitcoin::opcodes::all::OP_PUSHNUM_1;
+use bitcoin::script::Instruction;
+use bitcoin::{Amount, OutPoint, Transaction, Txid};
+
+use crate::chain::UtxoChain;
+use crate::esplora::EsploraClient;
+use crate::script::HtlcParams;
+use crate::Result;
+
+/// Where an HTLC is in its lifecycle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HtlcStatus {
+    /// No output pays the HTLC yet
+    Unfunded,
+    /// Funded and unspent; `confirmations` is 0 while in the mempool
+    Funded {
+        outpoint: OutPoint,
+        value: Amount,
+        confirmations: u32,
+    },
+    /// Claimed by the receiver, revealing the secret
+    Claimed {
+        outpoint: OutPoint,
+        spending_txid: Txid,
+        secret: [u8; 32],
+    },
+    /// Refunded to the sender after the timelock
+    Refunded {
+        outpoint: OutPoint,
+        spending_txid: Txid,
+    },
+}
+
+/// Polls an Esplora backend for the funding and spending of one HTLC
+#[derive(Clone, Debug)]
+pub struct HtlcWatcher {
+    client: EsploraClient,
+    params: HtlcParams,
+    chain: UtxoChain,
+}
+
+impl HtlcWatcher {
+    pub fn new(client: EsploraClient, params: HtlcParams, chain: UtxoChain) -> Self {
+        Self {
+            client,
+            params,
+            chain,
+        }
+    }
+
+    pub fn params(&self) -> &HtlcParams {
+        &self.params
+    }
+
+    /// Current state of the HTLC, following the earliest output that funded it
+    pub async fn status(&self) -> Result<HtlcStatus> {
+        let script_pubkey = self.params.script_pubkey(&self.chain);
+        let txs = self
+            .client
+            .address_txs(&self.params.address(&self.chain))
+            .await?;
+        let funding = txs.iter().rev().find_map(|tx| {
+            tx.vout
+                .iter()
+                .position(|out| out.scriptpubkey == script_pubkey)
+                .map(|vout| (tx, vout))
+        });
+        let Some((funding_tx, vout)) = funding else {
+            return Ok(HtlcStatus::Unfunded);
+        };
+        let outpoint = OutPoint::new(funding_tx.txid, vout as u32);
+
+        let outspend = self.client.outspend(&outpoint).await?;
+        if let (true, Some(spending_txid)) = (outspend.spent, outspend.txid) {
+            let spending_tx = self.client.transaction(&spending_txid).await?;
+            return Ok(match extract_secret(&spending_tx, &self.params) {
+                Some(secret) => HtlcStatus::Claimed {
+                    outpoint,
+                    spending_txid,
+                    secret,
+                },
+                None => HtlcStatus::Refunded {
+                    outpoint,
+                    spending_txid,
+                },
+            });
+        }
+
+        let confirmations = match funding_tx.status.block_height {
+            Some(height) if funding_tx.status.confirmed => {
+                self.client.tip_height().await?.saturating_sub(height) + 1
+            }
+            _ => 0,
+        };
+        Ok(HtlcStatus::Funded {
+            outpoint,
+            value: Amount::from_sat(funding_tx.vout[vout].value),
+            confirmations,
+        })
+    }
+
+    /// Poll until the HTLC is spent, returning the secret if it was claimed or `None` if it
+    /// was refunded
+    pub async fn wait_for_secret(&self, poll_interval: Duration) -> Result<Option<[u8; 32]>> {
+        loop {
+            match self.status().await? {
+                HtlcStatus::Claimed { secret, .. } => return Ok(Some(secret)),
+                HtlcStatus::Refunded { .. } => return Ok(None),
+                HtlcStatus::Unfunded | HtlcStatus::Funded { .. } => {
+                    tokio::time::sleep(poll_interval).await
+                }
+            }
+        }
+    }
+}
+
+/// Secret revealed by a transaction claiming the HTLC, if it spends one
+/// Claims push `<signature> <secret> 1 <HTLC script>`, in the witness on segwit chains and in
+/// the script sig on the others
+pub fn extract_secret(tx: &Transaction, params: &HtlcParams) -> Option<[u8; 32]> {
+    let script = params.script().to_bytes();
+    tx.input.iter().find_map(|input| {
+        let items: Vec<Vec<u8>> = if input.witness.is_empty() {
+            input
+                .script_sig
+                .instructions()
+                .map(|instruction| match instruction.ok()? {
+                    Instruction::PushBytes(bytes) => Some(bytes.as_bytes().to_vec()),
+                    Instruction::Op(OP_PUSHNUM_1) => Some(vec![1]),
+                    Instruction::Op(_) => None,
+                })
+                .collect::<Option<_>>()?
+        } else {
+            input.witness.iter().map(<[u8]>::to_vec).collect()
+        };
+        if items.len() != 4 || items[2] != [1] || items[3] != script {
+            return None;
+        }
+        let secret: [u8; 32] = items[1].as_slice().try_into().ok()?;
+        params.verify_secret(&secret).then_some(secret)
+    })
+}
@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+
+use crate::types::{ChainEvent, EscrowId, EscrowParams, EscrowStatus, FeeEstimate, Secret, TxId};
+use crate::Result;
+
+/// Events of one escrow, ending after its claim or refund
+pub type EventStream = BoxStream<'static, Result<ChainEvent>>;
+
+/// Everything the relayer needs from a chain to run one leg of a swap
+/// Object safe, so the relayer can hold adapters for every chain as `Box<dyn ChainAdapter>`
+#[async_trait]
+pub trait ChainAdapter: Send + Sync {
+    /// Chain name used in orders and logs, e.g. `stellar` or `ethereum`
+    fn chain(&self) -> &str;
+
+    /// Lock funds for `params`, returning the escrow ID and transaction
+    async fn create_escrow(&self, params: &EscrowParams) -> Result<(EscrowId, TxId)>;
+
+    /// Follow an escrow from `from_height`, replaying events already past and then
+    /// following new ones until the escrow is claimed or refunded
+    async fn watch_events(&self, escrow_id: EscrowId, from_height: u64) -> Result<EventStream>;
+
+    /// Secret published by the escrow's claim, or `None` while it is unclaimed
+    async fn reveal_secret(&self, escrow_id: &EscrowId) -> Result<Option<Secret>>;
+
+    /// Current state of an escrow, read directly rather than from events, so a caller can
+    /// check a claim or refund has not already landed before sending it again
+    async fn escrow_status(&self, escrow_id: &EscrowId) -> Result<EscrowStatus>;
+
+    /// Release an escrow to its receiver with `secret`; signs as the receiver
+    async fn claim(&self, escrow_id: &EscrowId, secret: &[u8]) -> Result<TxId>;
+
+    /// Return an expired escrow to its sender; signs as the sender
+    async fn refund(&self, escrow_id: &EscrowId) -> Result<TxId>;
+
+    /// Blocks or ledgers, including the one it landed in, before an event is final
+    fn finality_depth(&self) -> u64;
+
+    /// Newest block or ledger the chain's RPC has seen; a cheap call that shows the RPC is up
+    async fn head(&self) -> Result<u64>;
+
+    async fn fee_estimate(&self) -> Result<FeeEstimate>;
+
+    /// The signer's balance of `token`, or of the native currency without one, in its
+    /// smallest unit; `None` where the adapter cannot read it
+    async fn balance(&self, _token: Option<&str>) -> Result<Option<u128>> {
+        Ok(None)
+    }
+
+    /// Fee a landed transaction paid, in the smallest unit of the native currency; `None`
+    /// while it is pending or where the adapter cannot read it
+    async fn tx_fee(&self, _tx: &TxId) -> Result<Option<u128>> {
+        Ok(None)
+    }
+}
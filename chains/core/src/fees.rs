@@ -0,0 +1,121 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Inclusion times kept for the latency percentile
+const LATENCY_SAMPLES: usize = 32;
+
+/// When and how far to raise the fee of a transaction that has not landed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BumpPolicy {
+    /// Least time to wait for inclusion before bumping; longer while recent transactions
+    /// took longer to land
+    pub wait: Duration,
+    /// Percent each bump adds to the previous bid; EVM nodes only replace a pending
+    /// transaction for at least 10
+    pub percent: u32,
+    /// Bumps before giving up and leaving the retry to the caller
+    pub max_bumps: u32,
+    /// Highest bid per unit a bump may reach, in the chain's `FeeEstimate::max_fee` unit
+    pub max_fee: Option<u128>,
+    /// Once a deadline is this close, bids go straight to `max_fee`, or double without one
+    pub urgent: Duration,
+}
+
+impl Default for BumpPolicy {
+    fn default() -> Self {
+        Self {
+            wait: Duration::from_secs(30),
+            percent: 25,
+            max_bumps: 4,
+            max_fee: None,
+            urgent: Duration::from_secs(120),
+        }
+    }
+}
+
+/// Prices transactions and their bumps, learning from how long recent ones took to land
+/// Clones share their latency samples
+#[derive(Clone, Debug, Default)]
+pub struct FeeManager {
+    policy: BumpPolicy,
+    latency: Arc<Mutex<VecDeque<Duration>>>,
+}
+
+impl FeeManager {
+    pub fn new(policy: BumpPolicy) -> Self {
+        Self {
+            policy,
+            latency: Arc::default(),
+        }
+    }
+
+    pub fn policy(&self) -> &BumpPolicy {
+        &self.policy
+    }
+
+    /// Record how long a transaction took from its first send to inclusion
+    pub fn landed(&self, elapsed: Duration) {
+        let mut latency = self.latency.lock().unwrap();
+        if latency.len() == LATENCY_SAMPLES {
+            latency.pop_front();
+        }
+        latency.push_back(elapsed);
+    }
+
+    /// 90th percentile of recent inclusion times, `None` before any landed
+    pub fn latency(&self) -> Option<Duration> {
+        let mut samples = Vec::from(self.latency.lock().unwrap().clone());
+        samples.sort();
+        let index = (samples.len() * 9 / 10).min(samples.len().checked_sub(1)?);
+        Some(samples[index])
+    }
+
+    /// How long to wait for inclusion before bumping: twice the recent latency, at least
+    /// `wait`, and never past `deadline` (unix seconds)
+    pub fn patience(&self, deadline: Option<u64>, now: u64) -> Duration {
+        let patience = self.latency().map_or(self.policy.wait, |latency| {
+            (latency * 2).max(self.policy.wait)
+        });
+        match deadline {
+            Some(deadline) => patience.min(Duration::from_secs(deadline.saturating_sub(now))),
+            None => patience,
+        }
+    }
+
+    /// Bid for the next try: the current `estimate`, or once there is a `previous` bid, that
+    /// raised by `percent` if more; near `deadline` (unix seconds) bids jump instead
+    ///
+    /// `None` when `max_fee` leaves no room above `previous`.
+    pub fn price(
+        &self,
+        estimate: u128,
+        previous: Option<u128>,
+        deadline: Option<u64>,
+        now: u64,
+    ) -> Option<u128> {
+        let urgent = deadline
+            .is_some_and(|deadline| deadline.saturating_sub(now) <= self.policy.urgent.as_secs());
+        let bid = match (previous, urgent) {
+            (None, false) => estimate,
+            (Some(previous), false) => estimate.max(self.bump(previous)),
+            (previous, true) => match self.policy.max_fee {
+                Some(max_fee) => max_fee,
+                None => estimate.max(previous.unwrap_or(estimate)).saturating_mul(2),
+            },
+        };
+        let bid = self.policy.max_fee.map_or(bid, |max_fee| bid.min(max_fee));
+        match previous {
+            Some(previous) if bid <= previous => None,
+            _ => Some(bid),
+        }
+    }
+
+    /// `fee` raised by `percent`, rounding up so a small fee still rises
+    pub fn bump(&self, fee: u128) -> u128 {
+        let raise = fee
+            .saturating_mul(u128::from(self.policy.percent))
+            .div_ceil(100);
+        fee.saturating_add(raise.max(1))
+    }
+}
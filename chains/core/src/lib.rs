@@ -0,0 +1,51 @@
+// Chain Adapter Core
+// The relayer coordinates swaps across chains through the `ChainAdapter` trait alone: lock
+// funds, follow an escrow's lifecycle, learn a revealed secret, claim or refund. Each chain
+// crate implements it for its client, so a new chain plugs in by providing an adapter without
+// changes to the coordination logic. Chain-specific values (addresses, tokens, transaction
+// hashes) cross the trait as strings the adapter parses and formats itself.
+
+pub mod adapter;
+pub mod fees;
+pub mod pool;
+pub mod signer;
+pub mod types;
+
+pub use adapter::{ChainAdapter, EventStream};
+pub use fees::{BumpPolicy, FeeManager};
+pub use pool::{Fault, Pool};
+pub use signer::RemoteSigner;
+pub use types::{
+    ChainEvent, EscrowEvent, EscrowId, EscrowParams, EscrowStatus, FeeEstimate, Hashlock, Secret,
+    TxId,
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("invalid {field}: {value}")]
+    InvalidParam { field: &'static str, value: String },
+    #[error("secret is not accepted by this chain: {0}")]
+    InvalidSecret(String),
+    #[error("remote signer: {0}")]
+    Signing(String),
+    #[error("{chain}: {source}")]
+    Chain {
+        chain: &'static str,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl Error {
+    /// Wrap a chain client's own error
+    pub fn chain(
+        chain: &'static str,
+        source: impl std::error::Error + Send + Sync + 'static,
+    ) -> Self {
+        Self::Chain {
+            chain,
+            source: Box::new(source),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
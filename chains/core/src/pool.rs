@@ -0,0 +1,242 @@
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use futures::stream::{FuturesUnordered, StreamExt};
+
+/// Weight of the newest sample in the running latency and error rate
+const SMOOTHING: f64 = 0.2;
+
+/// Consecutive faults after which an endpoint is only tried once the others have failed
+const FAULTS_BEFORE_COOLDOWN: u32 = 3;
+
+/// How long an endpoint stays at the back of the line after `FAULTS_BEFORE_COOLDOWN` faults
+const COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Whether an error is the endpoint's fault, so another endpoint may do better: a dropped
+/// connection or an overloaded node is, a refused transaction or a failed simulation is not
+pub trait Fault {
+    fn is_fault(&self) -> bool;
+}
+
+/// Interchangeable endpoints of one service, tried best first
+///
+/// Endpoints are ranked by latency weighted by their recent error rate; one that faults
+/// `FAULTS_BEFORE_COOLDOWN` times in a row drops to the back for `COOLDOWN`. Clones share
+/// their scores.
+#[derive(Debug)]
+pub struct Pool<E> {
+    endpoints: Arc<Vec<Scored<E>>>,
+}
+
+impl<E> Clone for Pool<E> {
+    fn clone(&self) -> Self {
+        Self {
+            endpoints: self.endpoints.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Scored<E> {
+    endpoint: E,
+    stats: Mutex<Stats>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Stats {
+    /// Seconds, smoothed; `None` until the first call, which ranks an endpoint behind those
+    /// already answering
+    latency: Option<f64>,
+    /// Share of recent calls that faulted, smoothed
+    errors: f64,
+    faults: u32,
+    cooling_until: Option<Instant>,
+}
+
+impl Stats {
+    fn score(&self) -> f64 {
+        self.latency
+            .map_or(f64::INFINITY, |latency| latency * (1.0 + 4.0 * self.errors))
+    }
+
+    fn is_cooling(&self, now: Instant) -> bool {
+        self.cooling_until.is_some_and(|until| now < until)
+    }
+}
+
+impl<E> Pool<E> {
+    /// `None` without endpoints; the first is preferred until scores say otherwise
+    pub fn new(endpoints: Vec<E>) -> Option<Self> {
+        if endpoints.is_empty() {
+            return None;
+        }
+        Some(Self {
+            endpoints: Arc::new(
+                endpoints
+                    .into_iter()
+                    .map(|endpoint| Scored {
+                        endpoint,
+                        stats: Mutex::default(),
+                    })
+                    .collect(),
+            ),
+        })
+    }
+
+    pub fn single(endpoint: E) -> Self {
+        Self {
+            endpoints: Arc::new(vec![Scored {
+                endpoint,
+                stats: Mutex::default(),
+            }]),
+        }
+    }
+
+    /// This pool's endpoints followed by `more`; scores start over
+    pub fn extend(self, more: impl IntoIterator<Item = E>) -> Self
+    where
+        E: Clone,
+    {
+        let endpoints = self
+            .endpoints()
+            .cloned()
+            .chain(more)
+            .map(|endpoint| Scored {
+                endpoint,
+                stats: Mutex::default(),
+            })
+            .collect();
+        Self {
+            endpoints: Arc::new(endpoints),
+        }
+    }
+
+    /// Every endpoint in configured order
+    pub fn endpoints(&self) -> impl Iterator<Item = &E> {
+        self.endpoints.iter().map(|scored| &scored.endpoint)
+    }
+
+    pub fn len(&self) -> usize {
+        self.endpoints.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.endpoints.is_empty()
+    }
+
+    /// Every endpoint, best first
+    pub fn ranked(&self) -> Vec<&E> {
+        self.order()
+            .into_iter()
+            .map(|index| &self.endpoints[index].endpoint)
+            .collect()
+    }
+
+    /// Call endpoints best first until one answers without a fault, returning its answer
+    /// or, when all fault, the last fault; endpoints are cloned into `call`, so keep them
+    /// cheap to clone
+    pub async fn call<T, Er, F, Fut>(&self, mut call: F) -> Result<T, Er>
+    where
+        E: Clone,
+        F: FnMut(E) -> Fut,
+        Fut: Future<Output = Result<T, Er>>,
+        Er: Fault,
+    {
+        let mut last = None;
+        for index in self.order() {
+            match self
+                .timed(index, call(self.endpoints[index].endpoint.clone()))
+                .await
+            {
+                Err(error) if error.is_fault() => last = Some(error),
+                result => return result,
+            }
+        }
+        Err(last.expect("a pool has at least one endpoint"))
+    }
+
+    /// Call the best `width` endpoints at once and return the first success; for
+    /// time-critical requests that are safe to repeat, such as submitting a signed transaction
+    ///
+    /// When none succeeds, the first error that is not a fault wins over faults, since it is
+    /// the chain's answer rather than an endpoint's failure.
+    pub async fn hedge<T, Er, F, Fut>(&self, width: usize, mut call: F) -> Result<T, Er>
+    where
+        E: Clone,
+        F: FnMut(E) -> Fut,
+        Fut: Future<Output = Result<T, Er>>,
+        Er: Fault,
+    {
+        let mut calls: FuturesUnordered<_> = self
+            .order()
+            .into_iter()
+            .take(width.max(1))
+            .map(|index| self.timed(index, call(self.endpoints[index].endpoint.clone())))
+            .collect();
+        let mut refused = None;
+        let mut fault = None;
+        while let Some(result) = calls.next().await {
+            match result {
+                Ok(value) => return Ok(value),
+                Err(error) if error.is_fault() => fault = Some(error),
+                Err(error) => {
+                    refused.get_or_insert(error);
+                }
+            }
+        }
+        Err(refused.or(fault).expect("a pool has at least one endpoint"))
+    }
+
+    /// Run `call` against endpoint `index`, scoring it by the outcome
+    async fn timed<T, Er: Fault>(
+        &self,
+        index: usize,
+        call: impl Future<Output = Result<T, Er>>,
+    ) -> Result<T, Er> {
+        let started = Instant::now();
+        let result = call.await;
+        let faulted = matches!(&result, Err(error) if error.is_fault());
+        self.record(index, started.elapsed(), faulted);
+        result
+    }
+
+    fn record(&self, index: usize, elapsed: Duration, faulted: bool) {
+        let mut stats = self.endpoints[index].stats.lock().unwrap();
+        let sample = if faulted { 1.0 } else { 0.0 };
+        stats.errors += SMOOTHING * (sample - stats.errors);
+        let elapsed = elapsed.as_secs_f64();
+        stats.latency = Some(
+            stats
+                .latency
+                .map_or(elapsed, |latency| latency + SMOOTHING * (elapsed - latency)),
+        );
+        if faulted {
+            stats.faults += 1;
+            if stats.faults >= FAULTS_BEFORE_COOLDOWN {
+                stats.cooling_until = Some(Instant::now() + COOLDOWN);
+            }
+        } else {
+            stats.faults = 0;
+            stats.cooling_until = None;
+        }
+    }
+
+    /// Indices of the endpoints, those cooling down last, each group by score and then by
+    /// configured order
+    fn order(&self) -> Vec<usize> {
+        let now = Instant::now();
+        let stats: Vec<Stats> = self
+            .endpoints
+            .iter()
+            .map(|scored| *scored.stats.lock().unwrap())
+            .collect();
+        let mut order: Vec<usize> = (0..self.endpoints.len()).collect();
+        order.sort_by(|&a, &b| {
+            (stats[a].is_cooling(now), stats[a].score())
+                .partial_cmp(&(stats[b].is_cooling(now), stats[b].score()))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        order
+    }
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::Result;
+
+/// Ed25519 key held outside the process, e.g. split across threshold signer nodes
+/// Signatures are plain Ed25519 signatures over `message`, verifiable with `public_key`
+#[async_trait]
+pub trait RemoteSigner: Send + Sync {
+    fn public_key(&self) -> [u8; 32];
+
+    async fn sign(&self, message: &[u8]) -> Result<[u8; 64]>;
+}
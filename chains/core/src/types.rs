@@ -0,0 +1,76 @@
+/// Escrow identifier; escrow contracts derive it as `keccak256(order_id)`, Stellar claimable
+/// balances use their balance ID and TON escrows their contract address hash
+pub type EscrowId = [u8; 32];
+
+/// Hash or point the escrow is locked to
+pub type Hashlock = [u8; 32];
+
+/// Preimage that unlocks an escrow, in the encoding its chain hashes
+/// Stellar takes 32 raw bytes; the EVM escrow takes a UTF-8 string
+pub type Secret = Vec<u8>;
+
+/// Transaction hash as the chain's explorers display it
+pub type TxId = String;
+
+/// Terms of an escrow to lock on one chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowParams {
+    pub order_id: String,
+    pub hashlock: Hashlock,
+    /// Chain-native address of the party that can claim with the secret
+    pub receiver: String,
+    /// Token contract or asset, or `None` for the chain's native currency
+    pub token: Option<String>,
+    /// In the token's smallest unit
+    pub amount: u128,
+    /// Unix seconds after which the sender can refund
+    pub timelock: u64,
+}
+
+/// Lifecycle step of one escrow
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowEvent {
+    Created {
+        amount: u128,
+        hashlock: Hashlock,
+        timelock: u64,
+    },
+    /// `secret` is `None` when the claim could not be decoded, e.g. a call through another
+    /// contract; `ChainAdapter::reveal_secret` may still find it
+    Claimed {
+        secret: Option<Secret>,
+    },
+    Refunded,
+}
+
+/// Where an escrow stands on chain right now, as opposed to the events that led there
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowStatus {
+    /// Not created, dropped by a reorg, or settled in a way the chain can no longer show
+    Missing,
+    Open,
+    Claimed,
+    Refunded,
+}
+
+/// An escrow event with where it happened
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainEvent {
+    pub escrow_id: EscrowId,
+    pub event: EscrowEvent,
+    /// Block or ledger the event was observed at
+    pub height: u64,
+    pub tx: Option<TxId>,
+    /// Set when a reorg drops the block of a previously delivered event
+    pub removed: bool,
+}
+
+/// Fee level for the next transaction, per unit of work in the chain's smallest native unit
+/// (wei per gas on EVM chains, stroops per operation on Stellar)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeEstimate {
+    /// Most a transaction should pay per unit
+    pub max_fee: u128,
+    /// Part of `max_fee` that buys priority over other transactions
+    pub priority_fee: u128,
+}
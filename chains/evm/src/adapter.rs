@@ -0,0 +1,241 @@
+use std::collections::HashSet;
+use std::future::ready;
+
+use alloy::primitives::{Address, B256, U256};
+use alloy::providers::Provider;
+use alloy::rpc::types::Log;
+use alloy::sol_types::SolEvent;
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use unite_core::{
+    ChainAdapter, ChainEvent, EscrowId, EscrowParams, EscrowStatus, EventStream, FeeEstimate,
+    Secret, TxId,
+};
+
+use crate::bindings::HTLCEscrow::EscrowWithdrawn;
+use crate::client::EscrowClient;
+use crate::events::{EscrowEvent, EscrowLog};
+use crate::Error;
+
+impl From<Error> for unite_core::Error {
+    fn from(error: Error) -> Self {
+        unite_core::Error::chain("evm", error)
+    }
+}
+
+#[async_trait]
+impl<P: Provider + Clone + 'static> ChainAdapter for EscrowClient<P> {
+    fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    async fn create_escrow(&self, params: &EscrowParams) -> unite_core::Result<(EscrowId, TxId)> {
+        let receiver = parse_address("receiver", &params.receiver)?;
+        let secret_hash = B256::from(params.hashlock);
+        let amount = U256::from(params.amount);
+        let order_id = params.order_id.clone();
+        let (escrow_id, tx_hash) = match &params.token {
+            None => {
+                self.create_native(secret_hash, params.timelock, receiver, order_id, amount)
+                    .await?
+            }
+            Some(token) => {
+                let token = parse_address("token", token)?;
+                self.create_erc20(
+                    token,
+                    amount,
+                    secret_hash,
+                    params.timelock,
+                    receiver,
+                    order_id,
+                )
+                .await?
+            }
+        };
+        Ok((escrow_id.0, tx_hash.to_string()))
+    }
+
+    async fn watch_events(
+        &self,
+        escrow_id: EscrowId,
+        from_height: u64,
+    ) -> unite_core::Result<EventStream> {
+        let filter = self.filter().topic1(B256::from(escrow_id));
+        let (live, history) = if self.provider().client().pubsub_frontend().is_some() {
+            // Subscribe before reading history so nothing emitted in between is missed
+            let live = self
+                .provider()
+                .subscribe_logs(&filter)
+                .await
+                .map_err(Error::from)?
+                .into_stream()
+                .map(Ok)
+                .boxed();
+            let history = self
+                .provider()
+                .get_logs(&filter.clone().from_block(from_height))
+                .await
+                .map_err(Error::from)?;
+            (live, history)
+        } else {
+            // Without subscriptions, poll on from the head the history was read up to
+            let head = self
+                .provider()
+                .get_block_number()
+                .await
+                .map_err(Error::from)?;
+            let history = self
+                .provider()
+                .get_logs(&filter.clone().from_block(from_height).to_block(head))
+                .await
+                .map_err(Error::from)?;
+            (self.poll_logs(filter, head + 1).boxed(), history)
+        };
+        let replayed: HashSet<_> = history.iter().map(log_position).collect();
+
+        let client = self.clone();
+        let mut settled = false;
+        let events = stream::iter(history.into_iter().map(Ok))
+            .chain(live.filter(move |log| {
+                ready(match log {
+                    Ok(log) => log.removed || !replayed.contains(&log_position(log)),
+                    Err(_) => true,
+                })
+            }))
+            .filter_map(|log| {
+                ready(match log {
+                    Ok(log) => EscrowLog::decode(&log).map(Ok),
+                    Err(error) => Some(Err(error)),
+                })
+            })
+            .then(move |log| {
+                let client = client.clone();
+                async move { Ok(client.chain_event(escrow_id, log?).await?) }
+            })
+            .take_while(move |event| {
+                let more = !settled;
+                if let Ok(event) = event {
+                    settled = !event.removed
+                        && !matches!(event.event, unite_core::EscrowEvent::Created { .. });
+                }
+                ready(more)
+            });
+        Ok(events.boxed())
+    }
+
+    async fn reveal_secret(&self, escrow_id: &EscrowId) -> unite_core::Result<Option<Secret>> {
+        let filter = self
+            .filter()
+            .event_signature(EscrowWithdrawn::SIGNATURE_HASH)
+            .topic1(B256::from(*escrow_id))
+            .from_block(self.deploy_block);
+        let logs = self
+            .provider()
+            .get_logs(&filter)
+            .await
+            .map_err(Error::from)?;
+        let Some(tx_hash) = logs.last().and_then(|log| log.transaction_hash) else {
+            return Ok(None);
+        };
+        Ok(self.revealed_secret(tx_hash).await?.map(String::into_bytes))
+    }
+
+    async fn escrow_status(&self, escrow_id: &EscrowId) -> unite_core::Result<EscrowStatus> {
+        Ok(match self.get_escrow(B256::from(*escrow_id)).await? {
+            None => EscrowStatus::Missing,
+            Some(escrow) if escrow.withdrawn => EscrowStatus::Claimed,
+            Some(escrow) if escrow.cancelled => EscrowStatus::Refunded,
+            Some(_) => EscrowStatus::Open,
+        })
+    }
+
+    async fn claim(&self, escrow_id: &EscrowId, secret: &[u8]) -> unite_core::Result<TxId> {
+        let secret = String::from_utf8(secret.to_vec()).map_err(|_| {
+            unite_core::Error::InvalidSecret("the EVM escrow takes a UTF-8 string".to_string())
+        })?;
+        let tx_hash = self.withdraw(B256::from(*escrow_id), secret).await?;
+        Ok(tx_hash.to_string())
+    }
+
+    async fn refund(&self, escrow_id: &EscrowId) -> unite_core::Result<TxId> {
+        let tx_hash = self.cancel(B256::from(*escrow_id)).await?;
+        Ok(tx_hash.to_string())
+    }
+
+    fn finality_depth(&self) -> u64 {
+        self.confirmations
+    }
+
+    async fn head(&self) -> unite_core::Result<u64> {
+        Ok(self
+            .provider()
+            .get_block_number()
+            .await
+            .map_err(Error::from)?)
+    }
+
+    async fn fee_estimate(&self) -> unite_core::Result<FeeEstimate> {
+        let (max_fee, priority_fee) = self.fees().await?;
+        Ok(FeeEstimate {
+            max_fee,
+            priority_fee,
+        })
+    }
+
+    async fn balance(&self, token: Option<&str>) -> unite_core::Result<Option<u128>> {
+        let token = token
+            .map(|token| parse_address("token", token))
+            .transpose()?;
+        Ok(Some(EscrowClient::balance(self, token).await?.saturating_to()))
+    }
+
+    async fn tx_fee(&self, tx: &TxId) -> unite_core::Result<Option<u128>> {
+        let tx_hash: B256 = tx.parse().map_err(|_| unite_core::Error::InvalidParam {
+            field: "tx",
+            value: tx.clone(),
+        })?;
+        Ok(self.fee_paid(tx_hash).await?)
+    }
+}
+
+impl<P: Provider> EscrowClient<P> {
+    /// Convert a decoded log, reading the secret from the call data of withdrawals
+    async fn chain_event(&self, escrow_id: EscrowId, log: EscrowLog) -> crate::Result<ChainEvent> {
+        let event = match log.event {
+            EscrowEvent::Created(created) => unite_core::EscrowEvent::Created {
+                amount: created.amount.saturating_to(),
+                hashlock: created.secretHash.0,
+                timelock: created.timelock.saturating_to(),
+            },
+            EscrowEvent::Withdrawn(_) => {
+                let secret = match log.tx_hash {
+                    Some(tx_hash) => self.revealed_secret(tx_hash).await?,
+                    None => None,
+                };
+                unite_core::EscrowEvent::Claimed {
+                    secret: secret.map(String::into_bytes),
+                }
+            }
+            EscrowEvent::Cancelled(_) => unite_core::EscrowEvent::Refunded,
+        };
+        Ok(ChainEvent {
+            escrow_id,
+            event,
+            height: log.block_number.unwrap_or_default(),
+            tx: log.tx_hash.map(|tx_hash| tx_hash.to_string()),
+            removed: log.removed,
+        })
+    }
+}
+
+/// Identifies a log across the history query and the subscription
+fn log_position(log: &Log) -> (Option<B256>, Option<u64>) {
+    (log.transaction_hash, log.log_index)
+}
+
+fn parse_address(field: &'static str, value: &str) -> unite_core::Result<Address> {
+    value.parse().map_err(|_| unite_core::Error::InvalidParam {
+        field,
+        value: value.to_string(),
+    })
+}
@@ -0,0 +1,20 @@
+// The generated partial-fill constructors take more arguments than clippy allows
+#![allow(clippy::too_many_arguments)]
+
+// Generated from the ABI checked in next to the contract, so the bindings follow it
+alloy::sol!(
+    #[sol(rpc)]
+    #[derive(Debug, PartialEq, Eq)]
+    HTLCEscrow,
+    "../../contracts/src/ABI.json"
+);
+
+alloy::sol! {
+    /// The ERC-20 calls needed to fund token escrows and read balances
+    #[sol(rpc)]
+    interface IERC20 {
+        function allowance(address owner, address spender) external view returns (uint256);
+        function approve(address spender, uint256 amount) external returns (bool);
+        function balanceOf(address owner) external view returns (uint256);
+    }
+}
@@ -0,0 +1,452 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use alloy::consensus::Transaction as _;
+use alloy::contract::{CallBuilder, CallDecoder};
+use alloy::network::EthereumWallet;
+use alloy::primitives::{Address, TxHash, B256, U256};
+use alloy::providers::{DynProvider, Provider, ProviderBuilder, WsConnect};
+use alloy::rpc::client::RpcClient;
+use alloy::rpc::types::TransactionReceipt;
+use alloy::signers::local::PrivateKeySigner;
+use alloy::sol_types::{SolCall, SolEvent};
+use alloy::transports::TransportErrorKind;
+use unite_core::{BumpPolicy, FeeManager};
+
+use crate::bindings::HTLCEscrow::{self, HTLCEscrowInstance};
+use crate::bindings::IERC20;
+use crate::pool::PoolTransport;
+use crate::{Error, Result};
+
+/// How often receipts of sent transactions are polled
+const RECEIPT_POLL: Duration = Duration::from_secs(2);
+
+/// EIP-1559 pricing applied to every transaction the client sends
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeePolicy {
+    /// Refuse to send when the estimated max fee per gas exceeds this many wei
+    pub max_fee_cap: Option<u128>,
+    /// Lowest priority fee per gas to offer, in wei
+    pub min_priority_fee: u128,
+}
+
+/// An escrow as stored by the contract
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowDetails {
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount: U256,
+    pub secret_hash: B256,
+    pub timelock: U256,
+    pub withdrawn: bool,
+    pub cancelled: bool,
+    /// `Address::ZERO` for the native token
+    pub token: Address,
+    pub order_id: String,
+    pub created_at: U256,
+}
+
+/// Client for one deployed escrow contract, sending as `sender`
+#[derive(Clone, Debug)]
+pub struct EscrowClient<P> {
+    pub(crate) contract: HTLCEscrowInstance<P>,
+    sender: Address,
+    fees: FeePolicy,
+    bump: FeeManager,
+    pub(crate) confirmations: u64,
+    pub(crate) chain: String,
+    pub(crate) deploy_block: u64,
+    pub(crate) poll_interval: Duration,
+}
+
+impl EscrowClient<DynProvider> {
+    /// Connect over WebSocket, signing with `signer`
+    pub async fn connect(
+        ws_url: &str,
+        contract: Address,
+        signer: PrivateKeySigner,
+        confirmations: u64,
+    ) -> Result<Self> {
+        let sender = signer.address();
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(signer))
+            .connect_ws(WsConnect::new(ws_url))
+            .await?;
+        Ok(Self::new(
+            provider.erased(),
+            contract,
+            sender,
+            confirmations,
+        ))
+    }
+
+    /// Connect to several RPC endpoints of the same chain through a `PoolTransport`, sending
+    /// each transaction to the best `hedge` at once; event watches poll for logs
+    pub async fn connect_pool(
+        urls: &[String],
+        hedge: usize,
+        contract: Address,
+        signer: PrivateKeySigner,
+        confirmations: u64,
+    ) -> Result<Self> {
+        let transport = PoolTransport::connect(urls, hedge)
+            .await?
+            .ok_or_else(|| TransportErrorKind::custom_str("no RPC endpoints to connect to"))?;
+        let sender = signer.address();
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(signer))
+            .connect_client(RpcClient::new(transport, false));
+        Ok(Self::new(
+            provider.erased(),
+            contract,
+            sender,
+            confirmations,
+        ))
+    }
+}
+
+impl<P: Provider> EscrowClient<P> {
+    /// `provider` must be able to sign for `sender`
+    /// Sends return once their transaction has `confirmations` blocks (1 = mined)
+    pub fn new(provider: P, contract: Address, sender: Address, confirmations: u64) -> Self {
+        Self {
+            contract: HTLCEscrow::new(contract, provider),
+            sender,
+            fees: FeePolicy::default(),
+            bump: FeeManager::default(),
+            confirmations,
+            chain: "evm".to_string(),
+            deploy_block: 0,
+            poll_interval: Duration::from_secs(4),
+        }
+    }
+
+    pub fn with_fee_policy(mut self, fees: FeePolicy) -> Self {
+        self.fees = fees;
+        self
+    }
+
+    /// How transactions not mined in time are replaced; `max_fee` caps the bumped max fee per
+    /// gas, in wei, under the fee policy's `max_fee_cap`
+    pub fn with_fee_bump(mut self, policy: BumpPolicy) -> Self {
+        self.bump = FeeManager::new(policy);
+        self
+    }
+
+    /// Fee bids and recent inclusion latency
+    pub fn fee_manager(&self) -> &FeeManager {
+        &self.bump
+    }
+
+    /// Name the adapter reports, e.g. `ethereum` or `monad` (defaults to `evm`)
+    pub fn with_chain(mut self, chain: impl Into<String>) -> Self {
+        self.chain = chain.into();
+        self
+    }
+
+    /// Block the contract was deployed at, where log searches without a start begin
+    pub fn with_deploy_block(mut self, deploy_block: u64) -> Self {
+        self.deploy_block = deploy_block;
+        self
+    }
+
+    /// How often event watches poll for new logs when the provider cannot subscribe
+    /// (defaults to 4 seconds)
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn address(&self) -> Address {
+        *self.contract.address()
+    }
+
+    pub fn provider(&self) -> &P {
+        self.contract.provider()
+    }
+
+    /// The sender's balance of an ERC-20 token, or of the native token without one
+    pub async fn balance(&self, token: Option<Address>) -> Result<U256> {
+        Ok(match token {
+            Some(token) => {
+                IERC20::new(token, self.provider())
+                    .balanceOf(self.sender)
+                    .call()
+                    .await?
+            }
+            None => self.provider().get_balance(self.sender).await?,
+        })
+    }
+
+    /// Lock `value` of the native token, returning the escrow ID and transaction hash
+    pub async fn create_native(
+        &self,
+        secret_hash: B256,
+        timelock: u64,
+        receiver: Address,
+        order_id: String,
+        value: U256,
+    ) -> Result<(B256, TxHash)> {
+        let call = self
+            .contract
+            .createHTLCEscrowNative(secret_hash, U256::from(timelock), receiver, order_id)
+            .value(value);
+        let receipt = self.send(call, None).await?;
+        self.created_escrow_id(&receipt)
+    }
+
+    /// Lock `amount` of an ERC-20 token, approving the escrow contract first if its
+    /// allowance is short
+    pub async fn create_erc20(
+        &self,
+        token: Address,
+        amount: U256,
+        secret_hash: B256,
+        timelock: u64,
+        receiver: Address,
+        order_id: String,
+    ) -> Result<(B256, TxHash)> {
+        let erc20 = IERC20::new(token, self.provider());
+        let allowance = erc20.allowance(self.sender, self.address()).call().await?;
+        if allowance < amount {
+            self.send(erc20.approve(self.address(), amount), None)
+                .await?;
+        }
+
+        let call = self.contract.createHTLCEscrowERC20(
+            token,
+            amount,
+            secret_hash,
+            U256::from(timelock),
+            receiver,
+            order_id,
+        );
+        let receipt = self.send(call, None).await?;
+        self.created_escrow_id(&receipt)
+    }
+
+    /// Release an escrow to its receiver; only the receiver may call. Bids rise as its
+    /// timelock nears, since it cannot be withdrawn after
+    pub async fn withdraw(&self, escrow_id: B256, secret: String) -> Result<TxHash> {
+        let deadline = self
+            .get_escrow(escrow_id)
+            .await?
+            .map(|escrow| escrow.timelock.saturating_to());
+        let receipt = self
+            .send(self.contract.withdraw(escrow_id, secret), deadline)
+            .await?;
+        Ok(receipt.transaction_hash)
+    }
+
+    /// Refund an escrow after its timelock; only the sender may call
+    pub async fn cancel(&self, escrow_id: B256) -> Result<TxHash> {
+        let receipt = self.send(self.contract.cancel(escrow_id), None).await?;
+        Ok(receipt.transaction_hash)
+    }
+
+    /// Escrow details, or `None` if no escrow has this ID
+    pub async fn get_escrow(&self, escrow_id: B256) -> Result<Option<EscrowDetails>> {
+        let escrow = self.contract.getEscrow(escrow_id).call().await?;
+        if escrow.amount.is_zero() {
+            return Ok(None);
+        }
+        Ok(Some(EscrowDetails {
+            sender: escrow.sender,
+            receiver: escrow.receiver,
+            amount: escrow.amount,
+            secret_hash: escrow.secretHash,
+            timelock: escrow.timelock,
+            withdrawn: escrow.withdrawn,
+            cancelled: escrow.cancelled,
+            token: escrow.tokenAddress,
+            order_id: escrow.orderId,
+            created_at: escrow.createdAt,
+        }))
+    }
+
+    pub async fn can_withdraw(&self, escrow_id: B256) -> Result<bool> {
+        Ok(self.contract.canWithdraw(escrow_id).call().await?)
+    }
+
+    pub async fn can_cancel(&self, escrow_id: B256) -> Result<bool> {
+        Ok(self.contract.canCancel(escrow_id).call().await?)
+    }
+
+    /// Secret revealed by a withdrawal transaction
+    /// `EscrowWithdrawn` only carries the secret's hash, so this reads the call data; it
+    /// returns `None` unless the transaction called `withdraw` on the escrow directly
+    pub async fn revealed_secret(&self, tx_hash: TxHash) -> Result<Option<String>> {
+        let Some(tx) = self.provider().get_transaction_by_hash(tx_hash).await? else {
+            return Ok(None);
+        };
+        if tx.to() != Some(self.address()) {
+            return Ok(None);
+        }
+        Ok(HTLCEscrow::withdrawCall::abi_decode(tx.input())
+            .ok()
+            .map(|call| call.secret))
+    }
+
+    /// Blocks on top of (and including) the one that mined a transaction, or `None` while
+    /// it is pending
+    pub async fn confirmations(&self, tx_hash: TxHash) -> Result<Option<u64>> {
+        let Some(receipt) = self.provider().get_transaction_receipt(tx_hash).await? else {
+            return Ok(None);
+        };
+        let Some(mined_at) = receipt.block_number else {
+            return Ok(None);
+        };
+        let head = self.provider().get_block_number().await?;
+        Ok(Some(head.saturating_sub(mined_at) + 1))
+    }
+
+    /// Wei a mined transaction paid for gas, or `None` while it is pending; an L2's data fee
+    /// is not included
+    pub async fn fee_paid(&self, tx_hash: TxHash) -> Result<Option<u128>> {
+        let receipt = self.provider().get_transaction_receipt(tx_hash).await?;
+        Ok(receipt.map(|receipt| {
+            u128::from(receipt.gas_used).saturating_mul(receipt.effective_gas_price)
+        }))
+    }
+
+    /// Price `call` with the fee policy, send it from `sender` and wait for the configured
+    /// confirmation depth
+    ///
+    /// A transaction not mined within the fee manager's patience is replaced at the same
+    /// nonce with both fees bumped, up to `max_bumps` times, bidding harder as `deadline`
+    /// (unix seconds) nears; whichever of the sends is mined counts.
+    async fn send<Q: Provider + Clone, D: CallDecoder + Clone>(
+        &self,
+        call: CallBuilder<Q, D>,
+        deadline: Option<u64>,
+    ) -> Result<TransactionReceipt> {
+        let started = Instant::now();
+        let nonce = self
+            .provider()
+            .get_transaction_count(self.sender)
+            .pending()
+            .await?;
+        let call = call.from(self.sender).nonce(nonce);
+        let mut bid: Option<(u128, u128)> = None;
+        let mut sent = Vec::new();
+        for _ in 0..=self.bump.policy().max_bumps {
+            let now = now();
+            let (estimate, priority_estimate) = match bid {
+                None => self.fees().await?,
+                Some(_) => self.estimate().await?,
+            };
+            let previous = bid.map(|(max_fee, _)| max_fee);
+            let max_fee = self
+                .bump
+                .price(estimate, previous, deadline, now)
+                .map(|max_fee| {
+                    self.fees
+                        .max_fee_cap
+                        .map_or(max_fee, |cap| max_fee.min(cap))
+                })
+                .filter(|max_fee| previous.is_none_or(|previous| *max_fee > previous));
+            if let Some(max_fee) = max_fee {
+                let priority_fee = match bid {
+                    Some((_, previous)) => priority_estimate.max(self.bump.bump(previous)),
+                    None => priority_estimate,
+                }
+                .min(max_fee);
+                match call
+                    .clone()
+                    .max_fee_per_gas(max_fee)
+                    .max_priority_fee_per_gas(priority_fee)
+                    .send()
+                    .await
+                {
+                    Ok(pending) => {
+                        sent.push(*pending.tx_hash());
+                        bid = Some((max_fee, priority_fee));
+                    }
+                    // An earlier send was mined or the node refused the replacement; either
+                    // way the sends so far are still worth waiting on
+                    Err(_) if !sent.is_empty() => {}
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            let patience = self.bump.patience(deadline, now).max(RECEIPT_POLL);
+            if let Some(receipt) = self.mined(&sent, patience).await? {
+                self.bump.landed(started.elapsed());
+                return self.confirmed(receipt).await;
+            }
+        }
+        Err(Error::NotMined(sent.last().copied().unwrap_or_default()))
+    }
+
+    /// Receipt of whichever of `sent` is mined first, polling until `patience` runs out
+    async fn mined(
+        &self,
+        sent: &[TxHash],
+        patience: Duration,
+    ) -> Result<Option<TransactionReceipt>> {
+        let until = Instant::now() + patience;
+        loop {
+            for tx_hash in sent {
+                if let Some(receipt) = self.provider().get_transaction_receipt(*tx_hash).await? {
+                    return Ok(Some(receipt));
+                }
+            }
+            if Instant::now() >= until {
+                return Ok(None);
+            }
+            tokio::time::sleep(RECEIPT_POLL).await;
+        }
+    }
+
+    /// Wait until `receipt`'s transaction is the configured confirmation depth deep
+    async fn confirmed(&self, receipt: TransactionReceipt) -> Result<TransactionReceipt> {
+        if !receipt.status() {
+            return Err(Error::Reverted(receipt.transaction_hash));
+        }
+        while self
+            .confirmations(receipt.transaction_hash)
+            .await?
+            .unwrap_or_default()
+            < self.confirmations
+        {
+            tokio::time::sleep(RECEIPT_POLL).await;
+        }
+        Ok(receipt)
+    }
+
+    /// Current max fee and priority fee per gas under the fee policy
+    pub(crate) async fn fees(&self) -> Result<(u128, u128)> {
+        let (max_fee, priority_fee) = self.estimate().await?;
+        if let Some(cap) = self.fees.max_fee_cap {
+            if max_fee > cap {
+                return Err(Error::FeeTooHigh { max_fee, cap });
+            }
+        }
+        Ok((max_fee, priority_fee))
+    }
+
+    /// Estimated max fee and priority fee per gas, with the policy's priority fee floor
+    async fn estimate(&self) -> Result<(u128, u128)> {
+        let estimate = self.provider().estimate_eip1559_fees().await?;
+        let priority_fee = estimate
+            .max_priority_fee_per_gas
+            .max(self.fees.min_priority_fee);
+        Ok((estimate.max_fee_per_gas.max(priority_fee), priority_fee))
+    }
+
+    fn created_escrow_id(&self, receipt: &TransactionReceipt) -> Result<(B256, TxHash)> {
+        let tx_hash = receipt.transaction_hash;
+        receipt
+            .inner
+            .logs()
+            .iter()
+            .filter(|log| log.address() == self.address())
+            .find_map(|log| HTLCEscrow::EscrowCreated::decode_log(&log.inner).ok())
+            .map(|event| (event.escrowId, tx_hash))
+            .ok_or(Error::MissingEvent(tx_hash))
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
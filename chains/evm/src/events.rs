@@ -0,0 +1,125 @@
+use alloy::primitives::TxHash;
+use alloy::providers::Provider;
+use alloy::rpc::types::{Filter, Log};
+use alloy::sol_types::SolEvent;
+use futures::stream::{self, Stream, StreamExt};
+
+use crate::bindings::HTLCEscrow::{EscrowCancelled, EscrowCreated, EscrowWithdrawn};
+use crate::client::EscrowClient;
+use crate::Result;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowEvent {
+    Created(EscrowCreated),
+    /// Carries the secret's hash only; see `EscrowClient::revealed_secret` for the secret
+    Withdrawn(EscrowWithdrawn),
+    Cancelled(EscrowCancelled),
+}
+
+impl EscrowEvent {
+    /// Decode an escrow contract log, or `None` for other events
+    pub fn decode(log: &Log) -> Option<Self> {
+        match *log.topic0()? {
+            EscrowCreated::SIGNATURE_HASH => EscrowCreated::decode_log(&log.inner)
+                .ok()
+                .map(|event| Self::Created(event.data)),
+            EscrowWithdrawn::SIGNATURE_HASH => EscrowWithdrawn::decode_log(&log.inner)
+                .ok()
+                .map(|event| Self::Withdrawn(event.data)),
+            EscrowCancelled::SIGNATURE_HASH => EscrowCancelled::decode_log(&log.inner)
+                .ok()
+                .map(|event| Self::Cancelled(event.data)),
+            _ => None,
+        }
+    }
+}
+
+/// An escrow event with where it was emitted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowLog {
+    pub event: EscrowEvent,
+    pub block_number: Option<u64>,
+    pub tx_hash: Option<TxHash>,
+    /// Position of the log in its block
+    pub log_index: Option<u64>,
+    /// Set when a reorg drops the block that emitted a previously delivered log
+    pub removed: bool,
+}
+
+impl EscrowLog {
+    pub(crate) fn decode(log: &Log) -> Option<Self> {
+        Some(Self {
+            event: EscrowEvent::decode(log)?,
+            block_number: log.block_number,
+            tx_hash: log.transaction_hash,
+            log_index: log.log_index,
+            removed: log.removed,
+        })
+    }
+}
+
+impl<P: Provider> EscrowClient<P> {
+    /// Stream escrow events as they are emitted; needs a WebSocket or IPC provider
+    pub async fn subscribe(&self) -> Result<impl Stream<Item = EscrowLog>> {
+        let subscription = self.provider().subscribe_logs(&self.filter()).await?;
+        Ok(subscription
+            .into_stream()
+            .filter_map(|log| async move { EscrowLog::decode(&log) }))
+    }
+
+    /// Escrow events in a block range, for catching up after a restart or dropped subscription
+    pub async fn events(&self, from_block: u64, to_block: u64) -> Result<Vec<EscrowLog>> {
+        let filter = self.filter().from_block(from_block).to_block(to_block);
+        let logs = self.provider().get_logs(&filter).await?;
+        Ok(logs.iter().filter_map(EscrowLog::decode).collect())
+    }
+
+    /// Logs matching `filter` from `from_block` on, polled every `poll_interval`, for
+    /// providers that cannot subscribe; a failed poll is yielded and retried on the next
+    pub(crate) fn poll_logs(
+        &self,
+        filter: Filter,
+        from_block: u64,
+    ) -> impl Stream<Item = Result<Log>> + Send + 'static
+    where
+        P: Clone + 'static,
+    {
+        let client = self.clone();
+        stream::unfold(from_block, move |next| {
+            let client = client.clone();
+            let filter = filter.clone();
+            async move {
+                loop {
+                    tokio::time::sleep(client.poll_interval).await;
+                    let logs = match client.provider().get_block_number().await {
+                        Ok(head) if head < next => continue,
+                        Ok(head) => client
+                            .provider()
+                            .get_logs(&filter.from_block(next).to_block(head))
+                            .await
+                            .map(|logs| (logs, head + 1)),
+                        Err(error) => Err(error),
+                    };
+                    return Some(match logs {
+                        Ok((logs, next)) => {
+                            (stream::iter(logs.into_iter().map(Ok)).left_stream(), next)
+                        }
+                        Err(error) => (
+                            stream::once(async { Err(error.into()) }).right_stream(),
+                            next,
+                        ),
+                    });
+                }
+            }
+        })
+        .flatten()
+    }
+
+    pub(crate) fn filter(&self) -> Filter {
+        Filter::new().address(self.address()).event_signature(vec![
+            EscrowCreated::SIGNATURE_HASH,
+            EscrowWithdrawn::SIGNATURE_HASH,
+            EscrowCancelled::SIGNATURE_HASH,
+        ])
+    }
+}
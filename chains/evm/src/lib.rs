@@ -0,0 +1,42 @@
+// EVM Escrow Client
+// Typed bindings and a client for the Solidity HTLC escrow (`contracts/src/HTLCEscrowFixed.sol`,
+// deployed on Ethereum, Monad and other EVM chains), so the relayer can drive the EVM leg from
+// Rust. Transactions are priced with EIP-1559 fees under a configurable cap, replaced with
+// bumped fees when they are not mined in time, and wait for a configurable confirmation depth;
+// escrow events can be streamed over WebSocket or fetched by block range to catch up after a
+// reconnect. Several RPC endpoints can be pooled with `PoolTransport`, failing over between them
+// and sending each transaction to several. `EscrowClient` also implements the relayer's
+// `ChainAdapter` trait from `unite-core`.
+
+pub mod adapter;
+pub mod bindings;
+pub mod client;
+pub mod events;
+pub mod pool;
+
+pub use bindings::HTLCEscrow;
+pub use client::{EscrowClient, EscrowDetails, FeePolicy};
+pub use events::{EscrowEvent, EscrowLog};
+pub use pool::PoolTransport;
+
+use alloy::primitives::TxHash;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Transport(#[from] alloy::transports::TransportError),
+    #[error(transparent)]
+    Contract(#[from] alloy::contract::Error),
+    #[error(transparent)]
+    Pending(#[from] alloy::providers::PendingTransactionError),
+    #[error("transaction {0} reverted")]
+    Reverted(TxHash),
+    #[error("transaction {0} emitted no EscrowCreated event")]
+    MissingEvent(TxHash),
+    #[error("transaction {0} was not mined after every fee bump")]
+    NotMined(TxHash),
+    #[error("max fee of {max_fee} wei per gas exceeds the {cap} wei cap")]
+    FeeTooHigh { max_fee: u128, cap: u128 },
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,97 @@
+use std::task::{Context, Poll};
+
+use alloy::rpc::client::BuiltInConnectionString;
+use alloy::rpc::json_rpc::{RequestPacket, ResponsePacket};
+use alloy::transports::{BoxTransport, TransportError, TransportFut, TransportResult};
+use tower::{Service, ServiceExt};
+use unite_core::{Fault, Pool};
+
+/// JSON-RPC transport over several RPC endpoints of one chain
+///
+/// Requests go to the best-scoring endpoint and fail over to the next when one cannot be
+/// reached, rate-limits or is overloaded; raw transaction submissions go to the best `hedge`
+/// at once. Subscriptions are not supported, so event watches poll for logs instead.
+#[derive(Clone, Debug)]
+pub struct PoolTransport {
+    endpoints: Pool<BoxTransport>,
+    hedge: usize,
+}
+
+/// Why an endpoint's answer is not used as is
+enum Miss {
+    /// Unreachable, or asking to be retried later; the answer if there was one
+    Fault(TransportResult<ResponsePacket>),
+    /// A JSON-RPC error any node would repeat, such as a reverted call or a known nonce
+    Refused(ResponsePacket),
+}
+
+impl Fault for Miss {
+    fn is_fault(&self) -> bool {
+        matches!(self, Miss::Fault(_))
+    }
+}
+
+impl PoolTransport {
+    /// Connect to every `urls` (HTTP or WebSocket), preferring the first until scores say
+    /// otherwise; `None` without URLs
+    pub async fn connect(urls: &[String], hedge: usize) -> TransportResult<Option<Self>> {
+        let mut endpoints = Vec::with_capacity(urls.len());
+        for url in urls {
+            endpoints.push(BuiltInConnectionString::connect(url).await?);
+        }
+        Ok(Pool::new(endpoints).map(|endpoints| Self { endpoints, hedge }))
+    }
+
+    pub fn endpoints(&self) -> &Pool<BoxTransport> {
+        &self.endpoints
+    }
+
+    async fn request(self, request: RequestPacket) -> TransportResult<ResponsePacket> {
+        let send = |endpoint: BoxTransport| {
+            let request = request.clone();
+            async move { answer(endpoint.oneshot(request).await) }
+        };
+        let result = if is_submission(&request) {
+            self.endpoints.hedge(self.hedge, send).await
+        } else {
+            self.endpoints.call(send).await
+        };
+        match result {
+            Ok(response) | Err(Miss::Refused(response)) => Ok(response),
+            Err(Miss::Fault(result)) => result,
+        }
+    }
+}
+
+impl Service<RequestPacket> for PoolTransport {
+    type Response = ResponsePacket;
+    type Error = TransportError;
+    type Future = TransportFut<'static>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<TransportResult<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: RequestPacket) -> Self::Future {
+        Box::pin(self.clone().request(request))
+    }
+}
+
+/// Sort an endpoint's answer into a success, a fault worth failing over and a refusal
+fn answer(result: TransportResult<ResponsePacket>) -> Result<ResponsePacket, Miss> {
+    match result {
+        Ok(response) if !response.is_error() => Ok(response),
+        Ok(response) if response.iter_errors().any(|error| error.is_retry_err()) => {
+            Err(Miss::Fault(Ok(response)))
+        }
+        Ok(response) => Err(Miss::Refused(response)),
+        Err(error) => Err(Miss::Fault(Err(error))),
+    }
+}
+
+/// Whether `request` sends a signed transaction, which is safe to send to several nodes
+fn is_submission(request: &RequestPacket) -> bool {
+    request
+        .method_names()
+        .any(|method| method == "eth_sendRawTransaction")
+}
@@ -0,0 +1,252 @@
+use alloy::primitives::{Address, Bytes, FixedBytes, B256, U256};
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer};
+
+use crate::spec::{EXECUTION_PAYLOAD_GINDEX, SYNC_COMMITTEE_SIZE};
+use crate::ssz::{self, Chunk};
+use crate::{Error, Result};
+
+pub type BlsPublicKey = FixedBytes<48>;
+pub type BlsSignature = FixedBytes<96>;
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct BeaconBlockHeader {
+    #[serde(deserialize_with = "quoted")]
+    pub slot: u64,
+    #[serde(deserialize_with = "quoted")]
+    pub proposer_index: u64,
+    pub parent_root: B256,
+    pub state_root: B256,
+    pub body_root: B256,
+}
+
+impl BeaconBlockHeader {
+    pub fn hash_tree_root(&self) -> B256 {
+        let fields = [
+            ssz::uint64(self.slot),
+            ssz::uint64(self.proposer_index),
+            self.parent_root.0,
+            self.state_root.0,
+            self.body_root.0,
+        ];
+        B256::from(ssz::merkleize(&fields, 0))
+    }
+}
+
+/// Capella-or-later execution payload header; the blob fields are only present from Deneb
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct ExecutionPayloadHeader {
+    pub parent_hash: B256,
+    pub fee_recipient: Address,
+    pub state_root: B256,
+    pub receipts_root: B256,
+    pub logs_bloom: FixedBytes<256>,
+    pub prev_randao: B256,
+    #[serde(deserialize_with = "quoted")]
+    pub block_number: u64,
+    #[serde(deserialize_with = "quoted")]
+    pub gas_limit: u64,
+    #[serde(deserialize_with = "quoted")]
+    pub gas_used: u64,
+    #[serde(deserialize_with = "quoted")]
+    pub timestamp: u64,
+    pub extra_data: Bytes,
+    #[serde(deserialize_with = "quoted_u256")]
+    pub base_fee_per_gas: U256,
+    pub block_hash: B256,
+    pub transactions_root: B256,
+    pub withdrawals_root: B256,
+    #[serde(default, deserialize_with = "quoted_option")]
+    pub blob_gas_used: Option<u64>,
+    #[serde(default, deserialize_with = "quoted_option")]
+    pub excess_blob_gas: Option<u64>,
+}
+
+impl ExecutionPayloadHeader {
+    pub fn hash_tree_root(&self) -> B256 {
+        let mut fields: Vec<Chunk> = vec![
+            self.parent_hash.0,
+            ssz::pack(self.fee_recipient.as_slice())[0],
+            self.state_root.0,
+            self.receipts_root.0,
+            ssz::byte_vector(self.logs_bloom.as_slice()),
+            self.prev_randao.0,
+            ssz::uint64(self.block_number),
+            ssz::uint64(self.gas_limit),
+            ssz::uint64(self.gas_used),
+            ssz::uint64(self.timestamp),
+            ssz::byte_list(&self.extra_data, 32),
+            self.base_fee_per_gas.to_le_bytes(),
+            self.block_hash.0,
+            self.transactions_root.0,
+            self.withdrawals_root.0,
+        ];
+        if let (Some(blob_gas_used), Some(excess_blob_gas)) =
+            (self.blob_gas_used, self.excess_blob_gas)
+        {
+            fields.push(ssz::uint64(blob_gas_used));
+            fields.push(ssz::uint64(excess_blob_gas));
+        }
+        B256::from(ssz::merkleize(&fields, 0))
+    }
+}
+
+/// A beacon header with the execution block it carries
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct LightClientHeader {
+    pub beacon: BeaconBlockHeader,
+    pub execution: ExecutionPayloadHeader,
+    pub execution_branch: Vec<B256>,
+}
+
+impl LightClientHeader {
+    /// Whether `execution` is the payload of the beacon block's body
+    pub fn is_valid(&self) -> bool {
+        ssz::is_valid_merkle_branch(
+            &self.execution.hash_tree_root(),
+            &self.execution_branch,
+            EXECUTION_PAYLOAD_GINDEX,
+            &self.beacon.body_root,
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct SyncCommittee {
+    pub pubkeys: Vec<BlsPublicKey>,
+    pub aggregate_pubkey: BlsPublicKey,
+}
+
+impl SyncCommittee {
+    pub fn hash_tree_root(&self) -> B256 {
+        let pubkeys: Vec<Chunk> = self
+            .pubkeys
+            .iter()
+            .map(|pubkey| ssz::byte_vector(pubkey.as_slice()))
+            .collect();
+        let fields = [
+            ssz::merkleize(&pubkeys, SYNC_COMMITTEE_SIZE),
+            ssz::byte_vector(self.aggregate_pubkey.as_slice()),
+        ];
+        B256::from(ssz::merkleize(&fields, 0))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct SyncAggregate {
+    /// One bit per committee member, least significant bit first
+    pub sync_committee_bits: FixedBytes<64>,
+    pub sync_committee_signature: BlsSignature,
+}
+
+impl SyncAggregate {
+    pub fn participants(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..SYNC_COMMITTEE_SIZE)
+            .filter(|&index| self.sync_committee_bits[index / 8] >> (index % 8) & 1 == 1)
+    }
+}
+
+/// Starting point of a light client, proven against a trusted beacon block root
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct LightClientBootstrap {
+    pub header: LightClientHeader,
+    pub current_sync_committee: SyncCommittee,
+    pub current_sync_committee_branch: Vec<B256>,
+}
+
+/// A sync committee's signature over `attested_header`, which proves `finalized_header` and,
+/// in period updates, the next sync committee
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+pub struct LightClientUpdate {
+    pub attested_header: LightClientHeader,
+    /// Absent from finality updates
+    #[serde(default)]
+    pub next_sync_committee: Option<SyncCommittee>,
+    #[serde(default)]
+    pub next_sync_committee_branch: Vec<B256>,
+    pub finalized_header: LightClientHeader,
+    pub finality_branch: Vec<B256>,
+    pub sync_aggregate: SyncAggregate,
+    #[serde(deserialize_with = "quoted")]
+    pub signature_slot: u64,
+}
+
+#[derive(Deserialize)]
+struct Versioned<T> {
+    data: T,
+}
+
+/// Beacon node light client API; its answers are verified, never trusted
+#[derive(Clone, Debug)]
+pub struct BeaconApi {
+    url: String,
+    http: reqwest::Client,
+}
+
+impl BeaconApi {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn bootstrap(&self, block_root: &B256) -> Result<LightClientBootstrap> {
+        let response: Versioned<_> = self
+            .get(&format!("light_client/bootstrap/{block_root}"), &[])
+            .await?;
+        Ok(response.data)
+    }
+
+    /// Best update of each sync committee period from `start_period`, at most `count`
+    pub async fn updates(&self, start_period: u64, count: u64) -> Result<Vec<LightClientUpdate>> {
+        let query = [
+            ("start_period", start_period.to_string()),
+            ("count", count.to_string()),
+        ];
+        let responses: Vec<Versioned<_>> = self.get("light_client/updates", &query).await?;
+        Ok(responses
+            .into_iter()
+            .map(|response| response.data)
+            .collect())
+    }
+
+    pub async fn finality_update(&self) -> Result<LightClientUpdate> {
+        let response: Versioned<_> = self.get("light_client/finality_update", &[]).await?;
+        Ok(response.data)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str, query: &[(&str, String)]) -> Result<T> {
+        let response = self
+            .http
+            .get(format!("{}/eth/v1/beacon/{path}", self.url))
+            .query(query)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Beacon(format!("{status}: {body}")));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+/// The beacon API sends integers as decimal strings
+fn quoted<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<u64, D::Error> {
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(D::Error::custom)
+}
+
+fn quoted_option<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<u64>, D::Error> {
+    Option::<String>::deserialize(deserializer)?
+        .map(|value| value.parse().map_err(D::Error::custom))
+        .transpose()
+}
+
+fn quoted_u256<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<U256, D::Error> {
+    U256::from_str_radix(&String::deserialize(deserializer)?, 10).map_err(D::Error::custom)
+}
@@ -0,0 +1,76 @@
+use std::collections::BTreeMap;
+
+use alloy::consensus::Header;
+use alloy::primitives::B256;
+use alloy::providers::Provider;
+
+use crate::{Error, Result};
+
+/// Execution headers verified by hash back from a finalized block
+///
+/// Headers come from an untrusted RPC provider; each is only kept if it hashes to the parent
+/// hash of a header already verified, starting from the light client's finalized block.
+#[derive(Clone, Debug, Default)]
+pub struct HeaderChain {
+    anchor: Option<(u64, B256)>,
+    headers: BTreeMap<u64, Header>,
+    max_depth: u64,
+}
+
+impl HeaderChain {
+    /// Keep headers up to `max_depth` blocks below the anchor
+    pub fn new(max_depth: u64) -> Self {
+        Self {
+            max_depth,
+            ..Self::default()
+        }
+    }
+
+    /// Move the anchor to a newer finalized execution block
+    pub fn set_anchor(&mut self, number: u64, hash: B256) {
+        if self.anchor.is_some_and(|(anchor, _)| anchor >= number) {
+            return;
+        }
+        self.anchor = Some((number, hash));
+        let oldest = number.saturating_sub(self.max_depth);
+        self.headers = self.headers.split_off(&oldest);
+    }
+
+    pub fn anchor(&self) -> Option<(u64, B256)> {
+        self.anchor
+    }
+
+    /// Verified header `number`, fetching and linking the headers between it and the nearest
+    /// verified header above it
+    pub async fn header<P: Provider>(&mut self, provider: &P, number: u64) -> Result<&Header> {
+        let (anchor, anchor_hash) = self.anchor.ok_or(Error::NotFinalized(number))?;
+        if number > anchor {
+            return Err(Error::NotFinalized(number));
+        }
+        if anchor - number > self.max_depth {
+            return Err(Error::TooOld(number));
+        }
+        let (mut next, mut expected) = match self.headers.range(number..).next() {
+            Some((&verified, header)) => (verified, header.parent_hash),
+            None => (anchor + 1, anchor_hash),
+        };
+        if next == number {
+            return Ok(&self.headers[&number]);
+        }
+        while next > number {
+            next -= 1;
+            let header = provider
+                .get_block_by_number(next.into())
+                .await?
+                .ok_or(Error::MissingBlock(next))?
+                .header
+                .inner;
+            if header.number != next || header.hash_slow() != expected {
+                return Err(Error::HeaderMismatch(next));
+            }
+            expected = header.parent_hash;
+            self.headers.insert(next, header);
+        }
+        Ok(&self.headers[&number])
+    }
+}
@@ -0,0 +1,54 @@
+// Ethereum Light Client
+// Verifies counterpart EVM escrow creations without trusting a single RPC provider. A
+// sync-committee light client (Altair light client protocol, Capella-or-later headers) follows
+// the finalized beacon chain from a trusted checkpoint, checking BLS signatures and SSZ Merkle
+// branches. The finalized execution block anchors a chain of execution headers linked by hash,
+// and an `EscrowCreated` log is accepted only through a receipt trie proof against one of
+// those headers' receipts roots.
+
+pub mod beacon;
+pub mod headers;
+pub mod receipt;
+pub mod spec;
+pub mod ssz;
+pub mod store;
+pub mod verifier;
+
+pub use beacon::{BeaconApi, LightClientBootstrap, LightClientHeader, LightClientUpdate};
+pub use headers::HeaderChain;
+pub use receipt::ReceiptProof;
+pub use spec::ChainSpec;
+pub use store::LightClientStore;
+pub use verifier::{EscrowVerifier, VerifiedEscrow};
+
+use alloy::primitives::TxHash;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("beacon api: {0}")]
+    Beacon(String),
+    #[error("invalid bootstrap: {0}")]
+    InvalidBootstrap(&'static str),
+    #[error("invalid light client update: {0}")]
+    InvalidUpdate(&'static str),
+    #[error("block {0} is not finalized yet")]
+    NotFinalized(u64),
+    #[error("block {0} is further below the finalized block than the header chain keeps")]
+    TooOld(u64),
+    #[error("block {0} not found")]
+    MissingBlock(u64),
+    #[error("header {0} does not link to the verified chain")]
+    HeaderMismatch(u64),
+    #[error("receipt for {0} not found")]
+    MissingReceipt(TxHash),
+    #[error("transaction {0} emitted no matching EscrowCreated event")]
+    MissingEvent(TxHash),
+    #[error("receipt proof: {0}")]
+    Proof(&'static str),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Transport(#[from] alloy::transports::TransportError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,75 @@
+use alloy::consensus::ReceiptEnvelope;
+use alloy::eips::eip2718::{Decodable2718, Encodable2718};
+use alloy::primitives::{Bytes, B256};
+use alloy::rlp;
+use alloy::trie::proof::{verify_proof, ProofRetainer};
+use alloy::trie::root::adjust_index_for_rlp;
+use alloy::trie::{HashBuilder, Nibbles};
+
+use crate::{Error, Result};
+
+/// Merkle Patricia proof of one receipt in a block's receipts trie
+///
+/// Self-contained, so it can be checked against any trusted receipts root, including by an
+/// on-chain verifier.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptProof {
+    pub tx_index: u64,
+    /// EIP-2718 encoded receipt, the value stored in the trie
+    pub receipt: Bytes,
+    /// Trie nodes from the root down to the receipt's leaf
+    pub proof: Vec<Bytes>,
+}
+
+impl ReceiptProof {
+    /// Build the proof of receipt `tx_index` from all of a block's receipts, returning it
+    /// with the receipts root they produce
+    pub fn build(receipts: &[ReceiptEnvelope], tx_index: u64) -> Result<(Self, B256)> {
+        let index = usize::try_from(tx_index)
+            .ok()
+            .filter(|&index| index < receipts.len())
+            .ok_or(Error::Proof("transaction index out of range"))?;
+        let target = key(tx_index);
+        let mut builder =
+            HashBuilder::default().with_proof_retainer(ProofRetainer::new(vec![target]));
+        // Leaves must be added in key order, which is not index order under RLP
+        for position in 0..receipts.len() {
+            let leaf = adjust_index_for_rlp(position, receipts.len());
+            builder.add_leaf(key(leaf as u64), &receipts[leaf].encoded_2718());
+        }
+        let root = builder.root();
+        let proof = builder
+            .take_proof_nodes()
+            .into_nodes_sorted()
+            .into_iter()
+            .map(|(_, node)| node)
+            .collect();
+        let receipt = receipts[index].encoded_2718().into();
+        Ok((
+            Self {
+                tx_index,
+                receipt,
+                proof,
+            },
+            root,
+        ))
+    }
+
+    /// Check the proof against a trusted receipts root and decode the receipt
+    pub fn verify(&self, receipts_root: &B256) -> Result<ReceiptEnvelope> {
+        verify_proof(
+            *receipts_root,
+            key(self.tx_index),
+            Some(self.receipt.to_vec()),
+            &self.proof,
+        )
+        .map_err(|_| Error::Proof("receipt is not in the receipts trie"))?;
+        ReceiptEnvelope::decode_2718(&mut self.receipt.as_ref())
+            .map_err(|_| Error::Proof("malformed receipt"))
+    }
+}
+
+/// Receipts are keyed by the RLP encoding of their transaction index
+fn key(tx_index: u64) -> Nibbles {
+    Nibbles::unpack(rlp::encode(tx_index))
+}
@@ -0,0 +1,131 @@
+use alloy::primitives::{b256, FixedBytes, B256};
+
+use crate::ssz;
+
+pub const SLOTS_PER_EPOCH: u64 = 32;
+pub const EPOCHS_PER_SYNC_COMMITTEE_PERIOD: u64 = 256;
+pub const SYNC_COMMITTEE_SIZE: usize = 512;
+
+const DOMAIN_SYNC_COMMITTEE: [u8; 4] = [7, 0, 0, 0];
+
+/// Generalized indices of the proven fields, which moved when Electra grew the beacon state
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Gindices {
+    pub finalized_root: u64,
+    pub current_sync_committee: u64,
+    pub next_sync_committee: u64,
+}
+
+const ALTAIR_GINDICES: Gindices = Gindices {
+    finalized_root: 105,
+    current_sync_committee: 54,
+    next_sync_committee: 55,
+};
+
+const ELECTRA_GINDICES: Gindices = Gindices {
+    finalized_root: 169,
+    current_sync_committee: 86,
+    next_sync_committee: 87,
+};
+
+/// Execution payload within the beacon block body, unchanged since Capella
+pub const EXECUTION_PAYLOAD_GINDEX: u64 = 25;
+
+/// A fork's version and first epoch
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fork {
+    pub epoch: u64,
+    pub version: FixedBytes<4>,
+}
+
+/// Consensus parameters of one beacon chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChainSpec {
+    pub genesis_validators_root: B256,
+    /// In activation order, starting at genesis
+    pub forks: Vec<Fork>,
+    /// First epoch of Electra, from which `ELECTRA_GINDICES` apply
+    pub electra_epoch: u64,
+}
+
+impl ChainSpec {
+    pub fn mainnet() -> Self {
+        Self {
+            genesis_validators_root: b256!(
+                "4b363db94e286120d76eb905340fdd4e54bfe9f06bf33ff6cf5ad27f511bfe95"
+            ),
+            forks: forks(&[
+                (0, [0x00, 0, 0, 0]),
+                (74_240, [0x01, 0, 0, 0]),
+                (144_896, [0x02, 0, 0, 0]),
+                (194_048, [0x03, 0, 0, 0]),
+                (269_568, [0x04, 0, 0, 0]),
+                (364_032, [0x05, 0, 0, 0]),
+                (411_392, [0x06, 0, 0, 0]),
+            ]),
+            electra_epoch: 364_032,
+        }
+    }
+
+    pub fn sepolia() -> Self {
+        Self {
+            genesis_validators_root: b256!(
+                "d8ea171f3c94aea21ebc42a1ed61052acf3f9209c00e4efbaaddac09ed9b8078"
+            ),
+            forks: forks(&[
+                (0, [0x90, 0, 0, 0x69]),
+                (50, [0x90, 0, 0, 0x70]),
+                (100, [0x90, 0, 0, 0x71]),
+                (56_832, [0x90, 0, 0, 0x72]),
+                (132_608, [0x90, 0, 0, 0x73]),
+                (222_464, [0x90, 0, 0, 0x74]),
+                (272_640, [0x90, 0, 0, 0x75]),
+            ]),
+            electra_epoch: 222_464,
+        }
+    }
+
+    pub fn fork_version(&self, epoch: u64) -> FixedBytes<4> {
+        self.forks
+            .iter()
+            .rev()
+            .find(|fork| fork.epoch <= epoch)
+            .map_or(FixedBytes::ZERO, |fork| fork.version)
+    }
+
+    pub fn gindices(&self, slot: u64) -> Gindices {
+        if slot / SLOTS_PER_EPOCH >= self.electra_epoch {
+            ELECTRA_GINDICES
+        } else {
+            ALTAIR_GINDICES
+        }
+    }
+
+    /// Domain a sync committee signs in at `signature_slot`; the signature covers the block of
+    /// the slot before it, so that slot's fork applies
+    pub fn sync_committee_domain(&self, signature_slot: u64) -> B256 {
+        let epoch = signature_slot.max(1).saturating_sub(1) / SLOTS_PER_EPOCH;
+        let fork_data_root = ssz::hash(
+            &ssz::pack(self.fork_version(epoch).as_slice())[0],
+            self.genesis_validators_root.as_slice(),
+        );
+        let mut domain = [0u8; 32];
+        domain[..4].copy_from_slice(&DOMAIN_SYNC_COMMITTEE);
+        domain[4..].copy_from_slice(&fork_data_root[..28]);
+        B256::from(domain)
+    }
+}
+
+pub fn sync_committee_period(slot: u64) -> u64 {
+    slot / SLOTS_PER_EPOCH / EPOCHS_PER_SYNC_COMMITTEE_PERIOD
+}
+
+fn forks(schedule: &[(u64, [u8; 4])]) -> Vec<Fork> {
+    schedule
+        .iter()
+        .map(|&(epoch, version)| Fork {
+            epoch,
+            version: FixedBytes(version),
+        })
+        .collect()
+}
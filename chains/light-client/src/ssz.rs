@@ -0,0 +1,84 @@
+use alloy::primitives::B256;
+use sha2::{Digest, Sha256};
+
+/// One 32-byte SSZ chunk
+pub type Chunk = [u8; 32];
+
+pub fn hash(left: &[u8], right: &[u8]) -> Chunk {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Root of `chunks` padded with zero chunks to the next power of two of `limit`
+pub fn merkleize(chunks: &[Chunk], limit: usize) -> Chunk {
+    let width = limit.max(chunks.len()).max(1).next_power_of_two();
+    let mut layer = chunks.to_vec();
+    let mut zero = [0u8; 32];
+    let mut size = width;
+    while size > 1 {
+        if layer.len() % 2 == 1 {
+            layer.push(zero);
+        }
+        layer = layer
+            .chunks(2)
+            .map(|pair| hash(&pair[0], &pair[1]))
+            .collect();
+        zero = hash(&zero, &zero);
+        size /= 2;
+    }
+    layer.first().copied().unwrap_or(zero)
+}
+
+/// Root of a variable-length value: its content root mixed with its length
+pub fn mix_in_length(root: &Chunk, length: usize) -> Chunk {
+    let mut chunk = [0u8; 32];
+    chunk[..8].copy_from_slice(&(length as u64).to_le_bytes());
+    hash(root, &chunk)
+}
+
+pub fn uint64(value: u64) -> Chunk {
+    let mut chunk = [0u8; 32];
+    chunk[..8].copy_from_slice(&value.to_le_bytes());
+    chunk
+}
+
+/// Bytes packed into zero-padded chunks
+pub fn pack(bytes: &[u8]) -> Vec<Chunk> {
+    bytes
+        .chunks(32)
+        .map(|part| {
+            let mut chunk = [0u8; 32];
+            chunk[..part.len()].copy_from_slice(part);
+            chunk
+        })
+        .collect()
+}
+
+/// Root of a fixed-size byte vector
+pub fn byte_vector(bytes: &[u8]) -> Chunk {
+    merkleize(&pack(bytes), 0)
+}
+
+/// Root of a byte list of at most `max_len` bytes
+pub fn byte_list(bytes: &[u8], max_len: usize) -> Chunk {
+    mix_in_length(&merkleize(&pack(bytes), max_len.div_ceil(32)), bytes.len())
+}
+
+/// Whether `branch` proves `leaf` at generalized index `gindex` under `root`
+pub fn is_valid_merkle_branch(leaf: &B256, branch: &[B256], gindex: u64, root: &B256) -> bool {
+    let depth = gindex.ilog2() as usize;
+    if branch.len() != depth {
+        return false;
+    }
+    let mut value: Chunk = leaf.0;
+    for (level, sibling) in branch.iter().enumerate() {
+        value = if (gindex >> level) & 1 == 1 {
+            hash(sibling.as_slice(), &value)
+        } else {
+            hash(&value, sibling.as_slice())
+        };
+    }
+    value == root.0
+}
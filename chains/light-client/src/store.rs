@@ -0,0 +1,183 @@
+use alloy::primitives::B256;
+use blst::min_pk::{PublicKey, Signature};
+use blst::BLST_ERROR;
+
+use crate::beacon::{LightClientBootstrap, LightClientHeader, LightClientUpdate, SyncCommittee};
+use crate::spec::{sync_committee_period, ChainSpec, SYNC_COMMITTEE_SIZE};
+use crate::ssz;
+use crate::{Error, Result};
+
+const BLS_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// Finalized beacon chain state followed through sync committee signatures
+///
+/// Only updates signed by a two-thirds supermajority of the committee are applied, so a single
+/// beacon node cannot feed the store a header the committee did not sign.
+#[derive(Clone, Debug)]
+pub struct LightClientStore {
+    spec: ChainSpec,
+    finalized: LightClientHeader,
+    current_sync_committee: SyncCommittee,
+    next_sync_committee: Option<SyncCommittee>,
+}
+
+impl LightClientStore {
+    /// Start from `bootstrap`, which must prove its committee under `trusted_block_root`, a
+    /// finalized block root obtained out of band (weak subjectivity checkpoint)
+    pub fn bootstrap(
+        spec: ChainSpec,
+        trusted_block_root: &B256,
+        bootstrap: LightClientBootstrap,
+    ) -> Result<Self> {
+        let header = &bootstrap.header;
+        if header.beacon.hash_tree_root() != *trusted_block_root {
+            return Err(Error::InvalidBootstrap(
+                "header does not match the trusted root",
+            ));
+        }
+        if !header.is_valid() {
+            return Err(Error::InvalidBootstrap("invalid execution branch"));
+        }
+        if !ssz::is_valid_merkle_branch(
+            &bootstrap.current_sync_committee.hash_tree_root(),
+            &bootstrap.current_sync_committee_branch,
+            spec.gindices(header.beacon.slot).current_sync_committee,
+            &header.beacon.state_root,
+        ) {
+            return Err(Error::InvalidBootstrap("invalid sync committee branch"));
+        }
+        Ok(Self {
+            spec,
+            finalized: bootstrap.header,
+            current_sync_committee: bootstrap.current_sync_committee,
+            next_sync_committee: None,
+        })
+    }
+
+    pub fn finalized(&self) -> &LightClientHeader {
+        &self.finalized
+    }
+
+    /// Sync committee period of the finalized header
+    pub fn period(&self) -> u64 {
+        sync_committee_period(self.finalized.beacon.slot)
+    }
+
+    /// Verify and apply an update, returning whether it advanced the store; updates that bring
+    /// nothing new are skipped without verifying them
+    pub fn process_update(&mut self, update: &LightClientUpdate) -> Result<bool> {
+        let finalized_slot = update.finalized_header.beacon.slot;
+        let learns_next =
+            self.next_sync_committee.is_none() && update.next_sync_committee.is_some();
+        if finalized_slot <= self.finalized.beacon.slot && !learns_next {
+            return Ok(false);
+        }
+        self.verify_update(update)?;
+
+        let store_period = self.period();
+        let attested_period = sync_committee_period(update.attested_header.beacon.slot);
+        let finalized_period = sync_committee_period(finalized_slot);
+        // An update's next committee follows its attested header's period
+        let next = update
+            .next_sync_committee
+            .clone()
+            .filter(|_| attested_period == finalized_period);
+        match self.next_sync_committee.take() {
+            None if finalized_period == store_period => self.next_sync_committee = next,
+            None if finalized_period > store_period => {
+                return Err(Error::InvalidUpdate("next sync committee unknown"))
+            }
+            Some(known) if finalized_period == store_period + 1 => {
+                self.current_sync_committee = known;
+                self.next_sync_committee = next;
+            }
+            known => self.next_sync_committee = known,
+        }
+        if finalized_slot > self.finalized.beacon.slot {
+            self.finalized = update.finalized_header.clone();
+        }
+        Ok(true)
+    }
+
+    fn verify_update(&self, update: &LightClientUpdate) -> Result<()> {
+        let aggregate = &update.sync_aggregate;
+        let participants: Vec<usize> = aggregate.participants().collect();
+        if participants.len() * 3 < SYNC_COMMITTEE_SIZE * 2 {
+            return Err(Error::InvalidUpdate("no sync committee supermajority"));
+        }
+        let attested = &update.attested_header;
+        let finalized = &update.finalized_header;
+        if !attested.is_valid() || !finalized.is_valid() {
+            return Err(Error::InvalidUpdate("invalid execution branch"));
+        }
+        if !(update.signature_slot > attested.beacon.slot
+            && attested.beacon.slot >= finalized.beacon.slot)
+        {
+            return Err(Error::InvalidUpdate("slots out of order"));
+        }
+
+        let store_period = self.period();
+        let committee = match sync_committee_period(update.signature_slot) {
+            period if period == store_period => &self.current_sync_committee,
+            period if period == store_period + 1 => self
+                .next_sync_committee
+                .as_ref()
+                .ok_or(Error::InvalidUpdate("next sync committee unknown"))?,
+            _ => return Err(Error::InvalidUpdate("signature outside the known periods")),
+        };
+
+        let gindices = self.spec.gindices(attested.beacon.slot);
+        if !ssz::is_valid_merkle_branch(
+            &finalized.beacon.hash_tree_root(),
+            &update.finality_branch,
+            gindices.finalized_root,
+            &attested.beacon.state_root,
+        ) {
+            return Err(Error::InvalidUpdate("invalid finality branch"));
+        }
+        if let Some(next) = &update.next_sync_committee {
+            if !ssz::is_valid_merkle_branch(
+                &next.hash_tree_root(),
+                &update.next_sync_committee_branch,
+                gindices.next_sync_committee,
+                &attested.beacon.state_root,
+            ) {
+                return Err(Error::InvalidUpdate("invalid next sync committee branch"));
+            }
+            let attested_period = sync_committee_period(attested.beacon.slot);
+            if attested_period == store_period
+                && self
+                    .next_sync_committee
+                    .as_ref()
+                    .is_some_and(|known| known != next)
+            {
+                return Err(Error::InvalidUpdate("conflicting next sync committee"));
+            }
+        }
+
+        let signing_root = ssz::hash(
+            attested.beacon.hash_tree_root().as_slice(),
+            self.spec
+                .sync_committee_domain(update.signature_slot)
+                .as_slice(),
+        );
+        let pubkeys = participants
+            .iter()
+            .map(|&index| {
+                let pubkey = committee
+                    .pubkeys
+                    .get(index)
+                    .ok_or(Error::InvalidUpdate("sync committee too small"))?;
+                PublicKey::key_validate(pubkey.as_slice())
+                    .map_err(|_| Error::InvalidUpdate("invalid sync committee key"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let signature = Signature::from_bytes(aggregate.sync_committee_signature.as_slice())
+            .map_err(|_| Error::InvalidUpdate("malformed sync committee signature"))?;
+        let pubkeys: Vec<&PublicKey> = pubkeys.iter().collect();
+        match signature.fast_aggregate_verify(true, &signing_root, BLS_DST, &pubkeys) {
+            BLST_ERROR::BLST_SUCCESS => Ok(()),
+            _ => Err(Error::InvalidUpdate("invalid sync committee signature")),
+        }
+    }
+}
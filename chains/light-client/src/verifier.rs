@@ -0,0 +1,148 @@
+use alloy::primitives::{Address, TxHash, B256};
+use alloy::providers::Provider;
+use alloy::sol_types::SolEvent;
+use unite_evm::HTLCEscrow::EscrowCreated;
+
+use crate::beacon::BeaconApi;
+use crate::headers::HeaderChain;
+use crate::receipt::ReceiptProof;
+use crate::store::LightClientStore;
+use crate::{Error, Result};
+
+/// Periods requested per light client updates call, the beacon API's usual cap
+const MAX_UPDATES_PER_REQUEST: u64 = 128;
+
+/// Execution headers kept below the finalized block, about a day of mainnet blocks
+pub const DEFAULT_MAX_DEPTH: u64 = 8_192;
+
+/// An escrow creation proven against the finalized chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedEscrow {
+    pub event: EscrowCreated,
+    pub block_number: u64,
+    pub block_hash: B256,
+    /// As the provider located it; the proof covers the receipt, not the transaction
+    pub tx_hash: TxHash,
+    pub proof: ReceiptProof,
+}
+
+/// Verifies counterpart EVM escrow creations without trusting the RPC provider or beacon node
+///
+/// The beacon node supplies sync committee updates the store verifies, the finalized execution
+/// block anchors the header chain, and the provider's receipts are only used once they
+/// rebuild a verified header's receipts root.
+pub struct EscrowVerifier<P> {
+    beacon: BeaconApi,
+    provider: P,
+    escrow: Address,
+    store: LightClientStore,
+    headers: HeaderChain,
+}
+
+impl<P: Provider> EscrowVerifier<P> {
+    /// `escrow` is the escrow contract whose `EscrowCreated` events are verified
+    pub fn new(beacon: BeaconApi, provider: P, escrow: Address, store: LightClientStore) -> Self {
+        let mut headers = HeaderChain::new(DEFAULT_MAX_DEPTH);
+        let execution = &store.finalized().execution;
+        headers.set_anchor(execution.block_number, execution.block_hash);
+        Self {
+            beacon,
+            provider,
+            escrow,
+            store,
+            headers,
+        }
+    }
+
+    pub fn with_max_depth(mut self, max_depth: u64) -> Self {
+        let mut headers = HeaderChain::new(max_depth);
+        if let Some((number, hash)) = self.headers.anchor() {
+            headers.set_anchor(number, hash);
+        }
+        self.headers = headers;
+        self
+    }
+
+    pub fn store(&self) -> &LightClientStore {
+        &self.store
+    }
+
+    /// Catch the store up through period updates and the latest finality update, returning
+    /// the finalized execution block number
+    pub async fn sync(&mut self) -> Result<u64> {
+        loop {
+            let updates = self
+                .beacon
+                .updates(self.store.period(), MAX_UPDATES_PER_REQUEST)
+                .await?;
+            let mut advanced = false;
+            for update in &updates {
+                advanced |= self.store.process_update(update)?;
+            }
+            if !advanced {
+                break;
+            }
+        }
+        let finality = self.beacon.finality_update().await?;
+        self.store.process_update(&finality)?;
+        let execution = &self.store.finalized().execution;
+        self.headers
+            .set_anchor(execution.block_number, execution.block_hash);
+        Ok(execution.block_number)
+    }
+
+    /// Prove that `tx_hash` created `escrow_id` on the finalized chain; fails with
+    /// `NotFinalized` until the block is finalized and synced
+    pub async fn verify_escrow_created(
+        &mut self,
+        tx_hash: TxHash,
+        escrow_id: B256,
+    ) -> Result<VerifiedEscrow> {
+        let located = self
+            .provider
+            .get_transaction_receipt(tx_hash)
+            .await?
+            .ok_or(Error::MissingReceipt(tx_hash))?;
+        let (Some(block_number), Some(tx_index)) =
+            (located.block_number, located.transaction_index)
+        else {
+            return Err(Error::MissingReceipt(tx_hash));
+        };
+        let header = self.headers.header(&self.provider, block_number).await?;
+        let (receipts_root, block_hash) = (header.receipts_root, header.hash_slow());
+
+        let receipts: Vec<_> = self
+            .provider
+            .get_block_receipts(block_number.into())
+            .await?
+            .ok_or(Error::MissingBlock(block_number))?
+            .into_iter()
+            .map(|receipt| receipt.into_primitives_receipt().inner)
+            .collect();
+        let (proof, root) = ReceiptProof::build(&receipts, tx_index)?;
+        if root != receipts_root {
+            return Err(Error::Proof(
+                "provider receipts do not match the receipts root",
+            ));
+        }
+        let receipt = proof.verify(&receipts_root)?;
+        if !receipt.status() {
+            return Err(Error::MissingEvent(tx_hash));
+        }
+        let event = receipt
+            .logs()
+            .iter()
+            .filter(|log| log.address == self.escrow)
+            .filter_map(|log| EscrowCreated::decode_log(log).ok())
+            .map(|log| log.data)
+            .find(|event| event.escrowId == escrow_id)
+            .ok_or(Error::MissingEvent(tx_hash))?;
+        Ok(VerifiedEscrow {
+            event,
+            block_number,
+            block_hash,
+            tx_hash,
+            proof,
+        })
+    }
+}
@@ -0,0 +1,127 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use sha2::{Digest, Sha256};
+
+use crate::lnd::{InvoiceState, LndClient};
+use crate::stellar::SecretSource;
+use crate::{Error, Result};
+
+/// Bitcoin's target block interval, used to turn HTLC expiry heights into time
+const BLOCK_INTERVAL_SECS: u64 = 600;
+
+/// How a hold invoice was resolved
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Settled([u8; 32]),
+    Cancelled,
+}
+
+/// Drives HOLD invoices locked to Stellar escrow hashlocks
+#[derive(Clone, Debug)]
+pub struct HoldInvoiceLeg<S> {
+    lnd: LndClient,
+    secrets: S,
+    poll_interval: Duration,
+}
+
+impl<S: SecretSource> HoldInvoiceLeg<S> {
+    pub fn new(lnd: LndClient, secrets: S, poll_interval: Duration) -> Self {
+        Self {
+            lnd,
+            secrets,
+            poll_interval,
+        }
+    }
+
+    pub fn lnd(&self) -> &LndClient {
+        &self.lnd
+    }
+
+    /// Hold invoice for `amount_msat` whose payment hash is the escrow's `hashlock`, which must
+    /// be the SHA-256 hashlock of a `LockType::Sha256` escrow
+    /// `cltv_expiry` must leave the held HTLCs alive past the Stellar escrow's timelock
+    pub async fn create_invoice(
+        &self,
+        hashlock: &[u8; 32],
+        amount_msat: u64,
+        memo: &str,
+        expiry: Duration,
+        cltv_expiry: u64,
+    ) -> Result<String> {
+        self.lnd
+            .add_hold_invoice(hashlock, amount_msat, memo, expiry.as_secs(), cltv_expiry)
+            .await
+    }
+
+    /// Wait for the payer's HTLCs to be held, returning the earliest HTLC expiry height
+    /// Cancels the invoice and returns `None` if it is not paid by `deadline` (unix seconds)
+    pub async fn wait_accepted(&self, hashlock: &[u8; 32], deadline: u64) -> Result<Option<u32>> {
+        loop {
+            let invoice = self.lnd.lookup_invoice(hashlock).await?;
+            match invoice.state {
+                InvoiceState::Accepted => {
+                    return Ok(invoice.htlcs.iter().map(|htlc| htlc.expiry_height).min());
+                }
+                InvoiceState::Canceled => return Ok(None),
+                InvoiceState::Settled => {
+                    return Err(Error::Lnd("invoice already settled".to_string()));
+                }
+                InvoiceState::Open if now() >= deadline => {
+                    self.lnd.cancel_invoice(hashlock).await?;
+                    return Ok(None);
+                }
+                InvoiceState::Open => tokio::time::sleep(self.poll_interval).await,
+            }
+        }
+    }
+
+    /// Whether HTLCs held until `expiry_height` outlast a Stellar timelock (unix seconds)
+    /// with `margin_blocks` to spare; only fund the Stellar escrow when they do, or the
+    /// payer's HTLCs could time out before the secret is revealed
+    pub async fn outlasts(
+        &self,
+        expiry_height: u32,
+        stellar_timelock: u64,
+        margin_blocks: u32,
+    ) -> Result<bool> {
+        let tip = self.lnd.block_height().await?;
+        let blocks_needed = stellar_timelock
+            .saturating_sub(now())
+            .div_ceil(BLOCK_INTERVAL_SECS);
+        Ok(u64::from(expiry_height) >= u64::from(tip) + blocks_needed + u64::from(margin_blocks))
+    }
+
+    /// Settle the invoice once the secret appears on Stellar, or cancel it once `cancel_after`
+    /// (unix seconds) passes without it
+    /// `cancel_after` should be when the Stellar escrow has been refunded, after which its
+    /// secret can no longer be revealed
+    pub async fn settle_or_cancel(
+        &self,
+        hashlock: &[u8; 32],
+        cancel_after: u64,
+    ) -> Result<Outcome> {
+        loop {
+            // Check the secret before the deadline so a last-moment reveal still settles
+            let timed_out = now() >= cancel_after;
+            if let Some(secret) = self.secrets.revealed_secret(hashlock).await? {
+                // A keccak256 escrow reveals a secret LND would refuse, so say why
+                if <[u8; 32]>::from(Sha256::digest(secret)) != *hashlock {
+                    return Err(Error::NotSha256(*hashlock));
+                }
+                self.lnd.settle_invoice(&secret).await?;
+                return Ok(Outcome::Settled(secret));
+            }
+            if timed_out {
+                self.lnd.cancel_invoice(hashlock).await?;
+                return Ok(Outcome::Cancelled);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
@@ -0,0 +1,33 @@
+// Lightning HOLD-Invoice Leg
+// Lightning payment hashes are SHA-256, so the Stellar escrow on the other side must be a
+// `LockType::Sha256` escrow, not the default keccak256 `Hashlock`; a HOLD invoice is then locked
+// to the escrow's hashlock directly. The payer's HTLCs
+// are held (not settled) until the secret is revealed by a withdrawal on Stellar, then the
+// invoice is settled with it; if the Stellar escrow is refunded instead, the invoice is
+// cancelled and the payer's HTLCs fail back. No on-chain Bitcoin transaction is needed.
+
+pub mod hold;
+pub mod lnd;
+pub mod stellar;
+
+pub use hold::{HoldInvoiceLeg, Outcome};
+pub use lnd::{Invoice, InvoiceState, LndClient};
+pub use stellar::{SecretSource, SorobanSecrets};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("lnd: {0}")]
+    Lnd(String),
+    #[error("soroban rpc: {0}")]
+    Soroban(String),
+    #[error("invalid stellar contract address: {0}")]
+    InvalidContract(String),
+    #[error("secret does not open payment hash {}; is the escrow a SHA-256 one?", hex::encode(.0))]
+    NotSha256([u8; 32]),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Xdr(#[from] stellar_xdr::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
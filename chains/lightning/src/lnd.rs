@@ -0,0 +1,150 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{Error, Result};
+
+/// Client for LND's REST API, covering the invoices sub-server calls the hold leg needs
+#[derive(Clone, Debug)]
+pub struct LndClient {
+    url: String,
+    http: reqwest::Client,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum InvoiceState {
+    /// Not paid yet
+    Open,
+    /// The payer's HTLCs are held, waiting for settle or cancel
+    Accepted,
+    Settled,
+    Canceled,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct InvoiceHtlc {
+    /// Block height at which the HTLC times out on its channel
+    pub expiry_height: u32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Invoice {
+    pub state: InvoiceState,
+    pub payment_request: String,
+    #[serde(default)]
+    pub htlcs: Vec<InvoiceHtlc>,
+}
+
+#[derive(Deserialize)]
+struct AddHoldInvoice {
+    payment_request: String,
+}
+
+#[derive(Deserialize)]
+struct GetInfo {
+    block_height: u32,
+}
+
+impl LndClient {
+    /// `url` is the REST endpoint (e.g. `https://localhost:8080`), `macaroon` the raw
+    /// invoice or admin macaroon and `tls_cert` LND's PEM certificate
+    pub fn new(url: impl Into<String>, macaroon: &[u8], tls_cert: &[u8]) -> Result<Self> {
+        let mut headers = HeaderMap::new();
+        let macaroon =
+            HeaderValue::from_str(&hex::encode(macaroon)).map_err(|e| Error::Lnd(e.to_string()))?;
+        headers.insert("Grpc-Metadata-macaroon", macaroon);
+        let http = reqwest::Client::builder()
+            .add_root_certificate(reqwest::Certificate::from_pem(tls_cert)?)
+            .default_headers(headers)
+            .build()?;
+        Ok(Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            http,
+        })
+    }
+
+    /// Height of the chain tip as seen by the node
+    pub async fn block_height(&self) -> Result<u32> {
+        let info: GetInfo = self.get("/v1/getinfo").await?;
+        Ok(info.block_height)
+    }
+
+    /// Create a hold invoice for `payment_hash`, returning its payment request
+    /// `expiry` is how long the invoice may be paid for (seconds) and `cltv_expiry` the
+    /// final-hop CLTV delta the payer's HTLCs must carry (blocks)
+    pub async fn add_hold_invoice(
+        &self,
+        payment_hash: &[u8; 32],
+        value_msat: u64,
+        memo: &str,
+        expiry: u64,
+        cltv_expiry: u64,
+    ) -> Result<String> {
+        let response: AddHoldInvoice = self
+            .post(
+                "/v2/invoices/hodl",
+                json!({
+                    "hash": BASE64.encode(payment_hash),
+                    "value_msat": value_msat.to_string(),
+                    "memo": memo,
+                    "expiry": expiry.to_string(),
+                    "cltv_expiry": cltv_expiry.to_string(),
+                }),
+            )
+            .await?;
+        Ok(response.payment_request)
+    }
+
+    pub async fn lookup_invoice(&self, payment_hash: &[u8; 32]) -> Result<Invoice> {
+        self.get(&format!("/v1/invoice/{}", hex::encode(payment_hash)))
+            .await
+    }
+
+    /// Release the held HTLCs to us with the preimage
+    pub async fn settle_invoice(&self, preimage: &[u8; 32]) -> Result<()> {
+        self.post::<Value>(
+            "/v2/invoices/settle",
+            json!({ "preimage": BASE64.encode(preimage) }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Fail the held HTLCs back to the payer
+    pub async fn cancel_invoice(&self, payment_hash: &[u8; 32]) -> Result<()> {
+        self.post::<Value>(
+            "/v2/invoices/cancel",
+            json!({ "payment_hash": BASE64.encode(payment_hash) }),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        let response = self.http.get(format!("{}{path}", self.url)).send().await?;
+        Self::parse(response).await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: Value) -> Result<T> {
+        let response = self
+            .http
+            .post(format!("{}{path}", self.url))
+            .json(&body)
+            .send()
+            .await?;
+        Self::parse(response).await
+    }
+
+    async fn parse<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Lnd(format!("{status}: {body}")));
+        }
+        Ok(response.json().await?)
+    }
+}
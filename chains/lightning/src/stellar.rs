@@ -0,0 +1,116 @@
+use std::future::Future;
+
+use serde::Deserialize;
+use serde_json::json;
+use stellar_xdr::{
+    ContractDataDurability, ContractId, Hash, LedgerEntryData, LedgerKey, LedgerKeyContractData,
+    Limits, ReadXdr, ScAddress, ScBytes, ScSymbol, ScVal, ScVec, WriteXdr,
+};
+
+use crate::{Error, Result};
+
+/// Where the hold leg learns escrow secrets
+pub trait SecretSource {
+    /// Preimage revealed for `hashlock`, once a withdrawal has published it
+    fn revealed_secret(
+        &self,
+        hashlock: &[u8; 32],
+    ) -> impl Future<Output = Result<Option<[u8; 32]>>> + Send;
+}
+
+/// Reads the Stellar escrow's `RevealedSecret` entries over Soroban RPC, the same data its
+/// `get_revealed_secret` view returns, without simulating a transaction
+#[derive(Clone, Debug)]
+pub struct SorobanSecrets {
+    rpc_url: String,
+    contract: [u8; 32],
+    http: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    result: Option<GetLedgerEntries>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct GetLedgerEntries {
+    #[serde(default)]
+    entries: Vec<LedgerEntryResult>,
+}
+
+#[derive(Deserialize)]
+struct LedgerEntryResult {
+    xdr: String,
+}
+
+impl SorobanSecrets {
+    /// `contract` is the escrow's strkey address (`C...`)
+    pub fn new(rpc_url: impl Into<String>, contract: &str) -> Result<Self> {
+        let contract = stellar_strkey::Contract::from_string(contract)
+            .map_err(|_| Error::InvalidContract(contract.to_string()))?;
+        Ok(Self {
+            rpc_url: rpc_url.into(),
+            contract: contract.0,
+            http: reqwest::Client::new(),
+        })
+    }
+
+    /// Ledger key of `DataKey::RevealedSecret(hashlock)`, a persistent entry
+    fn key(&self, hashlock: &[u8; 32]) -> Result<LedgerKey> {
+        let data_key = ScVal::Vec(Some(ScVec(
+            vec![
+                ScVal::Symbol(ScSymbol("RevealedSecret".try_into()?)),
+                ScVal::Bytes(ScBytes(hashlock.to_vec().try_into()?)),
+            ]
+            .try_into()?,
+        )));
+        Ok(LedgerKey::ContractData(LedgerKeyContractData {
+            contract: ScAddress::Contract(ContractId(Hash(self.contract))),
+            key: data_key,
+            durability: ContractDataDurability::Persistent,
+        }))
+    }
+}
+
+impl SecretSource for SorobanSecrets {
+    async fn revealed_secret(&self, hashlock: &[u8; 32]) -> Result<Option<[u8; 32]>> {
+        let key = self.key(hashlock)?.to_xdr_base64(Limits::none())?;
+        let response: RpcResponse = self
+            .http
+            .post(&self.rpc_url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": "getLedgerEntries",
+                "params": { "keys": [key] },
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.error {
+            return Err(Error::Soroban(error.message));
+        }
+
+        let Some(entry) = response
+            .result
+            .and_then(|result| result.entries.into_iter().next())
+        else {
+            return Ok(None);
+        };
+        match LedgerEntryData::from_xdr_base64(&entry.xdr, Limits::none())? {
+            LedgerEntryData::ContractData(data) => match data.val {
+                ScVal::Bytes(bytes) => Ok(bytes.as_slice().try_into().ok()),
+                _ => Err(Error::Soroban("revealed secret is not bytes".to_string())),
+            },
+            _ => Err(Error::Soroban("unexpected ledger entry type".to_string())),
+        }
+    }
+}
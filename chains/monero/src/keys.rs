@@ -0,0 +1,71 @@
+use curve25519_dalek::Scalar;
+use monero::{Address, KeyPair, Network, PrivateKey, PublicKey, ViewPair};
+use rand::{CryptoRng, RngCore};
+
+use crate::{Error, Result};
+
+/// One party's share of the lock output's keys
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyShare {
+    spend: PrivateKey,
+    view: PrivateKey,
+}
+
+/// What each party sends the other at setup
+/// The view share is private key material, but only lets the holder scan the lock output
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicShare {
+    pub spend: PublicKey,
+    pub view: PrivateKey,
+}
+
+impl KeyShare {
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self {
+            spend: PrivateKey::from_scalar(Scalar::random(rng)),
+            view: PrivateKey::from_scalar(Scalar::random(rng)),
+        }
+    }
+
+    pub fn public(&self) -> PublicShare {
+        PublicShare {
+            spend: PublicKey::from_private_key(&self.spend),
+            view: self.view,
+        }
+    }
+
+    /// The spend share as a canonical scalar, the form the Stellar PTLC reveals
+    pub fn spend_secret(&self) -> [u8; 32] {
+        self.spend.to_bytes()
+    }
+}
+
+/// View key and public spend key of the lock output, enough to scan it but not spend it
+pub fn shared_view_pair(a: &PublicShare, b: &PublicShare) -> ViewPair {
+    ViewPair {
+        view: a.view + b.view,
+        spend: a.spend + b.spend,
+    }
+}
+
+/// Standard address the XMR side pays the lock to
+pub fn shared_address(network: Network, a: &PublicShare, b: &PublicShare) -> Address {
+    Address::from_viewpair(network, &shared_view_pair(a, b))
+}
+
+/// Full keys of the lock output from our share and the counterparty's spend share, checked
+/// against the public share it committed to at setup
+pub fn combine(
+    own: &KeyShare,
+    counterparty: &PublicShare,
+    counterparty_spend: &[u8; 32],
+) -> Result<KeyPair> {
+    let spend = PrivateKey::from_slice(counterparty_spend).map_err(|_| Error::InvalidShare)?;
+    if PublicKey::from_private_key(&spend) != counterparty.spend {
+        return Err(Error::InvalidShare);
+    }
+    Ok(KeyPair {
+        view: own.view + counterparty.view,
+        spend: own.spend + spend,
+    })
+}
@@ -0,0 +1,45 @@
+// Monero Swap Leg
+// XMR has no scripts, so it is locked in an output whose spend key is split between the two
+// parties (farcaster-style): the spend key is s_x + s_s, with each side holding one share, and
+// the view key is shared so both can scan the lock. Monero keys live on ed25519, the curve
+// the Stellar escrow's PTLC mode locks to, so the Stellar leg locks the XMR side's public
+// share S_x = s_x·G directly as its adaptor point. No CLSAG adaptor signature is needed:
+// withdrawing the Stellar escrow reveals s_x on-chain, which hands the Stellar side the whole
+// XMR spend key. The Stellar side also binds the escrow's refund to its own public share S_s
+// (`set_refund_point`), so the only way to cancel it is `cancel_with_scalar`, which reveals s_s
+// and hands the XMR side the whole key to sweep its lock back. Neither side needs the other's
+// cooperation: the XMR side withdraws before the timelock or is refunded when it passes.
+
+pub mod keys;
+pub mod rpc;
+pub mod scan;
+pub mod swap;
+
+pub use keys::{KeyShare, PublicShare};
+pub use rpc::{DaemonRpc, WalletRpc};
+pub use scan::{scan_lock, LockStatus};
+pub use swap::{LockReceipt, StellarSide, SwapTerms, XmrSide};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("key share does not match the counterparty's public share")]
+    InvalidShare,
+    #[error("stellar escrow does not lock this swap's adaptor point")]
+    AdaptorPointMismatch,
+    #[error("stellar escrow's refund point is not the counterparty's public share")]
+    RefundPointMismatch,
+    #[error("stellar timelock leaves {remaining}s to claim, need {required}s")]
+    TimelockTooClose { remaining: u64, required: u64 },
+    #[error("lock transaction {0} not found")]
+    LockNotFound(String),
+    #[error("monero rpc: {0}")]
+    Rpc(String),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Scan(#[from] monero::blockdata::transaction::Error),
+    #[error(transparent)]
+    Decode(#[from] monero::consensus::encode::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
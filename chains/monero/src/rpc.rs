@@ -0,0 +1,187 @@
+use monero::{Address, Amount, Hash, KeyPair, Transaction};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::{Error, Result};
+
+/// Client for monerod's RPC
+#[derive(Clone, Debug)]
+pub struct DaemonRpc {
+    url: String,
+    http: reqwest::Client,
+}
+
+/// A transaction fetched from the daemon with where it was mined
+#[derive(Clone, Debug)]
+pub struct DaemonTx {
+    pub tx: Transaction,
+    /// `None` while the transaction is in the pool
+    pub block_height: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct GetTransactions {
+    #[serde(default)]
+    txs: Vec<GetTransactionsEntry>,
+}
+
+#[derive(Deserialize)]
+struct GetTransactionsEntry {
+    as_hex: String,
+    in_pool: bool,
+    #[serde(default)]
+    block_height: u64,
+}
+
+#[derive(Deserialize)]
+struct GetHeight {
+    height: u64,
+}
+
+impl DaemonRpc {
+    /// `url` is the daemon root, e.g. `http://127.0.0.1:18081`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Number of blocks in the chain, one more than the top block's height
+    pub async fn height(&self) -> Result<u64> {
+        let response: GetHeight = self.post("/get_height", json!({})).await?;
+        Ok(response.height)
+    }
+
+    pub async fn transaction(&self, tx_hash: &Hash) -> Result<Option<DaemonTx>> {
+        let response: GetTransactions = self
+            .post(
+                "/get_transactions",
+                json!({ "txs_hashes": [hex::encode(tx_hash.as_bytes())] }),
+            )
+            .await?;
+        let Some(entry) = response.txs.into_iter().next() else {
+            return Ok(None);
+        };
+        let bytes = hex::decode(&entry.as_hex).map_err(|e| Error::Rpc(e.to_string()))?;
+        Ok(Some(DaemonTx {
+            tx: monero::consensus::deserialize(&bytes)?,
+            block_height: (!entry.in_pool).then_some(entry.block_height),
+        }))
+    }
+
+    async fn post<T: DeserializeOwned>(&self, path: &str, body: Value) -> Result<T> {
+        Ok(self
+            .http
+            .post(format!("{}{path}", self.url))
+            .json(&body)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
+
+/// Client for monero-wallet-rpc, which builds and signs the lock and sweep transactions
+/// The wallet RPC must run with `--wallet-dir` so it can create wallets from swap keys
+#[derive(Clone, Debug)]
+pub struct WalletRpc {
+    url: String,
+    http: reqwest::Client,
+}
+
+/// A sent transfer and the key that proves it paid its destination
+#[derive(Clone, Debug, Deserialize)]
+pub struct Transfer {
+    pub tx_hash: String,
+    pub tx_key: String,
+    pub fee: u64,
+}
+
+#[derive(Deserialize)]
+struct Sweep {
+    tx_hash_list: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+impl WalletRpc {
+    /// `url` is the wallet RPC root, e.g. `http://127.0.0.1:18083`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Pay `amount` from the open wallet to `address`
+    pub async fn transfer(&self, address: &Address, amount: Amount) -> Result<Transfer> {
+        self.call(
+            "transfer",
+            json!({
+                "destinations": [{ "amount": amount.as_pico(), "address": address.to_string() }],
+                "get_tx_key": true,
+            }),
+        )
+        .await
+    }
+
+    /// Restore a wallet from the full keys of a lock output, rescan from `restore_height` and
+    /// sweep everything it owns to `destination`, returning the sweep transaction hashes
+    pub async fn sweep_keys(
+        &self,
+        keys: &KeyPair,
+        network: monero::Network,
+        restore_height: u64,
+        destination: &Address,
+    ) -> Result<Vec<String>> {
+        let address = Address::from_keypair(network, keys);
+        self.call::<Value>(
+            "generate_from_keys",
+            json!({
+                "restore_height": restore_height,
+                "filename": format!("swap-{address}"),
+                "address": address.to_string(),
+                "spendkey": hex::encode(keys.spend.as_bytes()),
+                "viewkey": hex::encode(keys.view.as_bytes()),
+                "password": "",
+                "autosave_current": true,
+            }),
+        )
+        .await?;
+        self.call::<Value>("refresh", json!({ "start_height": restore_height }))
+            .await?;
+        let sweep: Sweep = self
+            .call("sweep_all", json!({ "address": destination.to_string() }))
+            .await?;
+        Ok(sweep.tx_hash_list)
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        let response: RpcResponse<T> = self
+            .http
+            .post(format!("{}/json_rpc", self.url))
+            .json(&json!({ "jsonrpc": "2.0", "id": "0", "method": method, "params": params }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        match (response.result, response.error) {
+            (_, Some(error)) => Err(Error::Rpc(format!("{method}: {}", error.message))),
+            (Some(result), None) => Ok(result),
+            (None, None) => Err(Error::Rpc(format!("{method}: empty response"))),
+        }
+    }
+}
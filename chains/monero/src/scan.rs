@@ -0,0 +1,41 @@
+use monero::{Amount, Hash, ViewPair};
+
+use crate::rpc::DaemonRpc;
+use crate::{Error, Result};
+
+/// What a lock transaction pays the shared address
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LockStatus {
+    pub amount: Amount,
+    /// 0 while the transaction is in the pool
+    pub confirmations: u64,
+}
+
+/// Scan a lock transaction with the shared view key, summing the outputs it pays to the
+/// shared address
+pub async fn scan_lock(
+    daemon: &DaemonRpc,
+    tx_hash: &Hash,
+    view_pair: &ViewPair,
+) -> Result<LockStatus> {
+    let fetched = daemon
+        .transaction(tx_hash)
+        .await?
+        .ok_or_else(|| Error::LockNotFound(hex::encode(tx_hash.as_bytes())))?;
+
+    // Only the primary address (account 0, subaddress 0) is shared
+    let owned = fetched.tx.check_outputs(view_pair, 0..1, 0..1)?;
+    let amount = owned
+        .iter()
+        .filter_map(|out| out.amount())
+        .fold(Amount::ZERO, |total, amount| total + amount);
+
+    let confirmations = match fetched.block_height {
+        Some(block_height) => daemon.height().await?.saturating_sub(block_height),
+        None => 0,
+    };
+    Ok(LockStatus {
+        amount,
+        confirmations,
+    })
+}
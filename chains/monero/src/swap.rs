@@ -0,0 +1,198 @@
+use monero::{Address, Amount, Hash, Network};
+
+use crate::keys::{self, KeyShare, PublicShare};
+use crate::rpc::{DaemonRpc, WalletRpc};
+use crate::scan::{self, LockStatus};
+use crate::{Error, Result};
+
+/// Terms both sides agree on before exchanging shares
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwapTerms {
+    pub network: Network,
+    pub xmr_amount: Amount,
+    /// Confirmations the Stellar side needs on the XMR lock before treating it as final
+    pub min_confirmations: u64,
+    /// Seconds the XMR side needs between locking XMR and the Stellar timelock, to see its
+    /// lock confirmed and withdraw the Stellar escrow
+    pub claim_window: u64,
+    /// Chain height to rescan from when sweeping the lock output
+    pub restore_height: u64,
+}
+
+/// Sent by the XMR side after locking, so the Stellar side can find and scan the lock
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockReceipt {
+    pub tx_hash: Hash,
+    /// Proves the payment to the shared address with `check_tx_key`
+    pub tx_key: String,
+}
+
+/// The party selling XMR: locks it to the shared address, then withdraws the Stellar PTLC
+/// with its spend share
+#[derive(Clone, Debug)]
+pub struct XmrSide {
+    terms: SwapTerms,
+    own: KeyShare,
+    counterparty: PublicShare,
+}
+
+impl XmrSide {
+    pub fn new(terms: SwapTerms, own: KeyShare, counterparty: PublicShare) -> Self {
+        Self {
+            terms,
+            own,
+            counterparty,
+        }
+    }
+
+    pub fn lock_address(&self) -> Address {
+        keys::shared_address(self.terms.network, &self.own.public(), &self.counterparty)
+    }
+
+    /// Point the Stellar escrow must lock: our public spend share
+    pub fn adaptor_point(&self) -> [u8; 32] {
+        self.own.public().spend.to_bytes()
+    }
+
+    /// Check the Stellar escrow before locking XMR: it must lock our adaptor point, have the
+    /// counterparty's public share as its refund point (`get_refund_point`) so a cancel reveals
+    /// that share, and leave at least `claim_window` seconds before its timelock
+    pub fn check_stellar_escrow(
+        &self,
+        secret_hash: &[u8; 32],
+        refund_point: Option<&[u8; 32]>,
+        timelock: u64,
+        now: u64,
+    ) -> Result<()> {
+        if *secret_hash != self.adaptor_point() {
+            return Err(Error::AdaptorPointMismatch);
+        }
+        if refund_point != Some(&self.counterparty.spend.to_bytes()) {
+            return Err(Error::RefundPointMismatch);
+        }
+        let remaining = timelock.saturating_sub(now);
+        if remaining < self.terms.claim_window {
+            return Err(Error::TimelockTooClose {
+                remaining,
+                required: self.terms.claim_window,
+            });
+        }
+        Ok(())
+    }
+
+    /// Pay the agreed amount from the open wallet to the shared address
+    pub async fn lock(&self, wallet: &WalletRpc) -> Result<LockReceipt> {
+        let transfer = wallet
+            .transfer(&self.lock_address(), self.terms.xmr_amount)
+            .await?;
+        let tx_hash = hex::decode(&transfer.tx_hash)
+            .ok()
+            .filter(|bytes| bytes.len() == 32)
+            .map(|bytes| Hash::from_slice(&bytes))
+            .ok_or_else(|| Error::Rpc(format!("invalid tx hash {}", transfer.tx_hash)))?;
+        Ok(LockReceipt {
+            tx_hash,
+            tx_key: transfer.tx_key,
+        })
+    }
+
+    /// Secret to withdraw the Stellar escrow with
+    /// Revealing it gives the Stellar side the full XMR spend key, so only use it once the
+    /// Stellar escrow is confirmed
+    pub fn claim_secret(&self) -> [u8; 32] {
+        self.own.spend_secret()
+    }
+
+    /// Sweep the lock back to `destination` with the share the Stellar escrow's cancel revealed
+    /// (`get_revealed_secret` on the refund point)
+    pub async fn refund(
+        &self,
+        wallet: &WalletRpc,
+        stellar_share: &[u8; 32],
+        destination: &Address,
+    ) -> Result<Vec<String>> {
+        let keys = keys::combine(&self.own, &self.counterparty, stellar_share)?;
+        wallet
+            .sweep_keys(
+                &keys,
+                self.terms.network,
+                self.terms.restore_height,
+                destination,
+            )
+            .await
+    }
+}
+
+/// The party buying XMR: locks the Stellar PTLC to the XMR side's adaptor point, then sweeps
+/// the XMR once the withdrawal reveals the XMR side's share
+#[derive(Clone, Debug)]
+pub struct StellarSide {
+    terms: SwapTerms,
+    own: KeyShare,
+    counterparty: PublicShare,
+}
+
+impl StellarSide {
+    pub fn new(terms: SwapTerms, own: KeyShare, counterparty: PublicShare) -> Self {
+        Self {
+            terms,
+            own,
+            counterparty,
+        }
+    }
+
+    pub fn lock_address(&self) -> Address {
+        keys::shared_address(self.terms.network, &self.counterparty, &self.own.public())
+    }
+
+    /// Point to create the Stellar PTLC escrow with: the XMR side's public spend share
+    pub fn adaptor_point(&self) -> [u8; 32] {
+        self.counterparty.spend.to_bytes()
+    }
+
+    /// Scan the XMR side's lock with the shared view key
+    pub async fn check_lock(
+        &self,
+        daemon: &DaemonRpc,
+        receipt: &LockReceipt,
+    ) -> Result<LockStatus> {
+        let view_pair = keys::shared_view_pair(&self.counterparty, &self.own.public());
+        scan::scan_lock(daemon, &receipt.tx_hash, &view_pair).await
+    }
+
+    /// Whether a scanned lock pays the agreed amount with enough confirmations
+    pub fn lock_final(&self, status: &LockStatus) -> bool {
+        status.amount >= self.terms.xmr_amount
+            && status.confirmations >= self.terms.min_confirmations
+    }
+
+    /// Sweep the lock to `destination` with the secret the Stellar withdrawal revealed
+    pub async fn redeem(
+        &self,
+        wallet: &WalletRpc,
+        revealed_secret: &[u8; 32],
+        destination: &Address,
+    ) -> Result<Vec<String>> {
+        let keys = keys::combine(&self.own, &self.counterparty, revealed_secret)?;
+        wallet
+            .sweep_keys(
+                &keys,
+                self.terms.network,
+                self.terms.restore_height,
+                destination,
+            )
+            .await
+    }
+
+    /// Point to bind the Stellar escrow's refund to (`set_refund_point`): our public spend
+    /// share, set before the XMR side locks
+    pub fn refund_point(&self) -> [u8; 32] {
+        self.own.public().spend.to_bytes()
+    }
+
+    /// Scalar to cancel the Stellar escrow with (`cancel_with_scalar`)
+    /// Cancelling publishes it, which lets the XMR side sweep its lock back
+    pub fn refund_scalar(&self) -> [u8; 32] {
+        self.own.spend_secret()
+    }
+}
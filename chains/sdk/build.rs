@@ -0,0 +1,520 @@
+// Generates typed bindings for the escrow contract from its spec (`spec/htlc_escrow.json`):
+// a Rust type per contract type with its `ScVal` encoding, the `HTLCError` codes and a `Call`
+// builder per contract function.
+
+use std::fmt::Write;
+
+use stellar_xdr::{
+    ScSpecEntry, ScSpecFunctionV0, ScSpecTypeDef, ScSpecUdtErrorEnumV0, ScSpecUdtStructV0,
+    ScSpecUdtUnionCaseV0, ScSpecUdtUnionV0,
+};
+
+const SPEC: &str = "spec/htlc_escrow.json";
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "box", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "static", "struct", "super", "trait", "true", "type", "unsafe",
+    "use", "where", "while", "yield",
+];
+
+fn main() {
+    println!("cargo:rerun-if-changed={SPEC}");
+    let spec = std::fs::read_to_string(SPEC).expect("read contract spec");
+    let entries: Vec<ScSpecEntry> = serde_json::from_str(&spec).expect("parse contract spec");
+
+    let mut types = String::new();
+    let mut calls = String::new();
+    for entry in &entries {
+        match entry {
+            ScSpecEntry::UdtStructV0(udt) => write_struct(&mut types, udt),
+            ScSpecEntry::UdtUnionV0(udt) => write_union(&mut types, udt),
+            ScSpecEntry::UdtErrorEnumV0(udt) => write_error(&mut types, udt),
+            ScSpecEntry::FunctionV0(function) => write_call(&mut calls, function),
+            entry => panic!("unsupported contract spec entry {}", entry.name()),
+        }
+    }
+
+    let out = std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("bindings.rs");
+    std::fs::write(
+        out,
+        format!("{types}\npub mod calls {{\n    use super::*;\n{calls}}}\n"),
+    )
+    .expect("write bindings");
+}
+
+fn write_struct(out: &mut String, udt: &ScSpecUdtStructV0) {
+    let name = udt.name.to_utf8_string_lossy();
+    write_doc(out, "", &udt.doc.to_utf8_string_lossy());
+    let tuple = udt
+        .fields
+        .iter()
+        .all(|field| field.name.as_slice().iter().all(u8::is_ascii_digit));
+
+    writeln!(out, "#[derive(Clone, Debug, PartialEq, Eq)]").unwrap();
+    if tuple {
+        let fields: Vec<_> = udt
+            .fields
+            .iter()
+            .map(|field| format!("pub {}", rust_type(&field.type_)))
+            .collect();
+        writeln!(out, "pub struct {name}({});\n", fields.join(", ")).unwrap();
+    } else {
+        writeln!(out, "pub struct {name} {{").unwrap();
+        for field in udt.fields.iter() {
+            write_doc(out, "    ", &field.doc.to_utf8_string_lossy());
+            writeln!(
+                out,
+                "    pub {}: {},",
+                ident(&field.name.to_utf8_string_lossy()),
+                rust_type(&field.type_)
+            )
+            .unwrap();
+        }
+        writeln!(out, "}}\n").unwrap();
+    }
+
+    writeln!(out, "impl {name} {{").unwrap();
+    writeln!(out, "    pub fn to_scval(&self) -> Result<ScVal> {{").unwrap();
+    if tuple {
+        let items: Vec<_> = (0..udt.fields.len())
+            .map(|index| {
+                format!(
+                    "{}?",
+                    encode(&udt.fields[index].type_, &format!("&self.{index}"))
+                )
+            })
+            .collect();
+        writeln!(out, "        codec::tuple(vec![{}])", items.join(", ")).unwrap();
+    } else {
+        writeln!(out, "        codec::fields(vec![").unwrap();
+        for field in udt.fields.iter() {
+            let field_name = field.name.to_utf8_string_lossy();
+            let value = encode(&field.type_, &format!("&self.{}", ident(&field_name)));
+            writeln!(out, "            ({field_name:?}, {value}?),").unwrap();
+        }
+        writeln!(out, "        ])").unwrap();
+    }
+    writeln!(out, "    }}\n").unwrap();
+    writeln!(
+        out,
+        "    pub fn from_scval(value: &ScVal) -> Result<Self> {{"
+    )
+    .unwrap();
+    if tuple {
+        writeln!(
+            out,
+            "        let items = codec::to_tuple(value, {}, {name:?})?;",
+            udt.fields.len()
+        )
+        .unwrap();
+        let items: Vec<_> = udt
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, field)| format!("{}?", decode(&field.type_, &format!("&items[{index}]"))))
+            .collect();
+        writeln!(out, "        Ok(Self({}))", items.join(", ")).unwrap();
+    } else {
+        writeln!(
+            out,
+            "        let fields = codec::to_fields(value, {name:?})?;"
+        )
+        .unwrap();
+        writeln!(out, "        Ok(Self {{").unwrap();
+        for field in udt.fields.iter() {
+            let field_name = field.name.to_utf8_string_lossy();
+            let value = decode(
+                &field.type_,
+                &format!("codec::field(fields, {field_name:?}, {name:?})?"),
+            );
+            writeln!(out, "            {}: {value}?,", ident(&field_name)).unwrap();
+        }
+        writeln!(out, "        }})").unwrap();
+    }
+    writeln!(out, "    }}\n}}\n").unwrap();
+}
+
+fn write_union(out: &mut String, udt: &ScSpecUdtUnionV0) {
+    let name = udt.name.to_utf8_string_lossy();
+    write_doc(out, "", &udt.doc.to_utf8_string_lossy());
+    writeln!(
+        out,
+        "#[derive(Clone, Debug, PartialEq, Eq)]\npub enum {name} {{"
+    )
+    .unwrap();
+    for case in udt.cases.iter() {
+        match case {
+            ScSpecUdtUnionCaseV0::VoidV0(case) => {
+                write_doc(out, "    ", &case.doc.to_utf8_string_lossy());
+                writeln!(out, "    {},", case.name.to_utf8_string_lossy()).unwrap();
+            }
+            ScSpecUdtUnionCaseV0::TupleV0(case) => {
+                write_doc(out, "    ", &case.doc.to_utf8_string_lossy());
+                let types: Vec<_> = case.type_.iter().map(rust_type).collect();
+                writeln!(
+                    out,
+                    "    {}({}),",
+                    case.name.to_utf8_string_lossy(),
+                    types.join(", ")
+                )
+                .unwrap();
+            }
+        }
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl {name} {{").unwrap();
+    writeln!(
+        out,
+        "    pub fn to_scval(&self) -> Result<ScVal> {{\n        match self {{"
+    )
+    .unwrap();
+    for case in udt.cases.iter() {
+        match case {
+            ScSpecUdtUnionCaseV0::VoidV0(case) => {
+                let case = case.name.to_utf8_string_lossy();
+                writeln!(
+                    out,
+                    "            Self::{case} => codec::variant({case:?}, vec![]),"
+                )
+                .unwrap();
+            }
+            ScSpecUdtUnionCaseV0::TupleV0(case) => {
+                let case_name = case.name.to_utf8_string_lossy();
+                let bindings: Vec<_> = (0..case.type_.len()).map(|i| format!("v{i}")).collect();
+                let values: Vec<_> = case
+                    .type_
+                    .iter()
+                    .zip(&bindings)
+                    .map(|(type_, binding)| format!("{}?", encode(type_, binding)))
+                    .collect();
+                writeln!(
+                    out,
+                    "            Self::{case_name}({}) => codec::variant({case_name:?}, vec![{}]),",
+                    bindings.join(", "),
+                    values.join(", ")
+                )
+                .unwrap();
+            }
+        }
+    }
+    writeln!(out, "        }}\n    }}\n").unwrap();
+
+    writeln!(
+        out,
+        "    pub fn from_scval(value: &ScVal) -> Result<Self> {{"
+    )
+    .unwrap();
+    let has_values = udt
+        .cases
+        .iter()
+        .any(|case| matches!(case, ScSpecUdtUnionCaseV0::TupleV0(_)));
+    let values = if has_values { "values" } else { "_" };
+    writeln!(
+        out,
+        "        let (case, {values}) = codec::to_variant(value, {name:?})?;"
+    )
+    .unwrap();
+    writeln!(out, "        match case {{").unwrap();
+    for case in udt.cases.iter() {
+        match case {
+            ScSpecUdtUnionCaseV0::VoidV0(case) => {
+                let case = case.name.to_utf8_string_lossy();
+                writeln!(out, "            {case:?} => Ok(Self::{case}),").unwrap();
+            }
+            ScSpecUdtUnionCaseV0::TupleV0(case) => {
+                let case_name = case.name.to_utf8_string_lossy();
+                let values: Vec<_> = case
+                    .type_
+                    .iter()
+                    .enumerate()
+                    .map(|(index, type_)| {
+                        let item = format!("codec::item(values, {index}, {name:?})?");
+                        format!("{}?", decode(type_, &item))
+                    })
+                    .collect();
+                writeln!(
+                    out,
+                    "            {case_name:?} => Ok(Self::{case_name}({})),",
+                    values.join(", ")
+                )
+                .unwrap();
+            }
+        }
+    }
+    writeln!(out, "            _ => Err(Error::Decode({name:?})),").unwrap();
+    writeln!(out, "        }}\n    }}\n}}\n").unwrap();
+}
+
+fn write_error(out: &mut String, udt: &ScSpecUdtErrorEnumV0) {
+    let name = udt.name.to_utf8_string_lossy();
+    write_doc(out, "", &udt.doc.to_utf8_string_lossy());
+    writeln!(
+        out,
+        "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, thiserror::Error)]\n#[repr(u32)]\npub enum {name} {{"
+    )
+    .unwrap();
+    for case in udt.cases.iter() {
+        let case_name = case.name.to_utf8_string_lossy();
+        write_doc(out, "    ", &case.doc.to_utf8_string_lossy());
+        writeln!(out, "    #[error({:?})]", words(&case_name)).unwrap();
+        writeln!(out, "    {case_name} = {},", case.value).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+
+    writeln!(out, "impl {name} {{").unwrap();
+    writeln!(
+        out,
+        "    pub fn from_code(code: u32) -> Option<Self> {{\n        match code {{"
+    )
+    .unwrap();
+    for case in udt.cases.iter() {
+        writeln!(
+            out,
+            "            {} => Some(Self::{}),",
+            case.value,
+            case.name.to_utf8_string_lossy()
+        )
+        .unwrap();
+    }
+    writeln!(out, "            _ => None,\n        }}\n    }}\n").unwrap();
+    writeln!(
+        out,
+        "    pub fn code(self) -> u32 {{\n        self as u32\n    }}\n}}\n"
+    )
+    .unwrap();
+}
+
+fn write_call(out: &mut String, function: &ScSpecFunctionV0) {
+    let name = function.name.0.to_utf8_string_lossy();
+    write_doc(out, "    ", &function.doc.to_utf8_string_lossy());
+    let params: Vec<_> = function
+        .inputs
+        .iter()
+        .map(|input| {
+            format!(
+                "{}: {}",
+                ident(&input.name.to_utf8_string_lossy()),
+                param_type(&input.type_)
+            )
+        })
+        .collect();
+    let args: Vec<_> = function
+        .inputs
+        .iter()
+        .map(|input| {
+            let param = ident(&input.name.to_utf8_string_lossy());
+            let value = if by_value(&input.type_) {
+                format!("&{param}")
+            } else {
+                param
+            };
+            format!("{}?", encode(&input.type_, &value))
+        })
+        .collect();
+    let output = match function.outputs.first() {
+        Some(ScSpecTypeDef::Result(result)) => Some(&*result.ok_type),
+        output => output,
+    };
+    let (output_type, decoder) = match output {
+        Some(output) => (rust_type(output), decode(output, "value")),
+        None => ("()".to_string(), "codec::to_unit(value)".to_string()),
+    };
+    writeln!(
+        out,
+        "    pub fn {}({}) -> Result<Call<{output_type}>> {{",
+        ident(&name),
+        params.join(", ")
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "        Ok(Call::new({name:?}, vec![{}], |value| {decoder}))\n    }}\n",
+        args.join(", ")
+    )
+    .unwrap();
+}
+
+/// Rust type a contract type is bound to
+fn rust_type(type_: &ScSpecTypeDef) -> String {
+    match type_ {
+        ScSpecTypeDef::Val => "ScVal".to_string(),
+        ScSpecTypeDef::Bool => "bool".to_string(),
+        ScSpecTypeDef::Void => "()".to_string(),
+        ScSpecTypeDef::U32 => "u32".to_string(),
+        ScSpecTypeDef::I32 => "i32".to_string(),
+        ScSpecTypeDef::U64 | ScSpecTypeDef::Timepoint | ScSpecTypeDef::Duration => {
+            "u64".to_string()
+        }
+        ScSpecTypeDef::I64 => "i64".to_string(),
+        ScSpecTypeDef::U128 => "u128".to_string(),
+        ScSpecTypeDef::I128 => "i128".to_string(),
+        ScSpecTypeDef::Bytes => "Vec<u8>".to_string(),
+        ScSpecTypeDef::String | ScSpecTypeDef::Symbol => "String".to_string(),
+        ScSpecTypeDef::Address => "ScAddress".to_string(),
+        ScSpecTypeDef::BytesN(bytes) => format!("[u8; {}]", bytes.n),
+        ScSpecTypeDef::Option(option) => format!("Option<{}>", rust_type(&option.value_type)),
+        ScSpecTypeDef::Vec(vec) => format!("Vec<{}>", rust_type(&vec.element_type)),
+        ScSpecTypeDef::Map(map) => format!(
+            "Vec<({}, {})>",
+            rust_type(&map.key_type),
+            rust_type(&map.value_type)
+        ),
+        ScSpecTypeDef::Tuple(tuple) if tuple.value_types.is_empty() => "()".to_string(),
+        ScSpecTypeDef::Tuple(tuple) => {
+            let types: Vec<_> = tuple.value_types.iter().map(rust_type).collect();
+            format!("({},)", types.join(", "))
+        }
+        ScSpecTypeDef::Udt(udt) => udt.name.to_utf8_string_lossy(),
+        type_ => panic!("unsupported contract type {}", type_.name()),
+    }
+}
+
+/// Type a `calls` function takes the argument as
+fn param_type(type_: &ScSpecTypeDef) -> String {
+    match type_ {
+        _ if by_value(type_) => rust_type(type_),
+        ScSpecTypeDef::Bytes => "&[u8]".to_string(),
+        ScSpecTypeDef::String | ScSpecTypeDef::Symbol => "&str".to_string(),
+        ScSpecTypeDef::Vec(vec) => format!("&[{}]", rust_type(&vec.element_type)),
+        type_ => format!("&{}", rust_type(type_)),
+    }
+}
+
+fn by_value(type_: &ScSpecTypeDef) -> bool {
+    matches!(
+        type_,
+        ScSpecTypeDef::Bool
+            | ScSpecTypeDef::U32
+            | ScSpecTypeDef::I32
+            | ScSpecTypeDef::U64
+            | ScSpecTypeDef::I64
+            | ScSpecTypeDef::Timepoint
+            | ScSpecTypeDef::Duration
+            | ScSpecTypeDef::U128
+            | ScSpecTypeDef::I128
+    )
+}
+
+/// Expression encoding `value`, a reference, into `Result<ScVal>`
+fn encode(type_: &ScSpecTypeDef, value: &str) -> String {
+    match type_ {
+        ScSpecTypeDef::Val => format!("codec::val({value})"),
+        ScSpecTypeDef::Void => "codec::void()".to_string(),
+        _ if by_value(type_) => format!("codec::scalar({value})"),
+        ScSpecTypeDef::Bytes | ScSpecTypeDef::BytesN(_) => format!("codec::bytes({value})"),
+        ScSpecTypeDef::String => format!("codec::string({value})"),
+        ScSpecTypeDef::Symbol => format!("codec::symbol({value})"),
+        ScSpecTypeDef::Address => format!("codec::address({value})"),
+        ScSpecTypeDef::Option(option) => format!(
+            "codec::option({value}, |value| {})",
+            encode(&option.value_type, "value")
+        ),
+        ScSpecTypeDef::Vec(vec) => format!(
+            "codec::vec({value}, |value| {})",
+            encode(&vec.element_type, "value")
+        ),
+        ScSpecTypeDef::Map(map) => format!(
+            "codec::map({value}, |value| {}, |value| {})",
+            encode(&map.key_type, "value"),
+            encode(&map.value_type, "value")
+        ),
+        ScSpecTypeDef::Tuple(tuple) if tuple.value_types.is_empty() => "codec::void()".to_string(),
+        ScSpecTypeDef::Tuple(tuple) => {
+            let items: Vec<_> = tuple
+                .value_types
+                .iter()
+                .enumerate()
+                .map(|(index, type_)| format!("{}?", encode(type_, &format!("&({value}).{index}"))))
+                .collect();
+            format!("codec::tuple(vec![{}])", items.join(", "))
+        }
+        ScSpecTypeDef::Udt(_) => match value.strip_prefix('&') {
+            Some(place) => format!("{place}.to_scval()"),
+            None => format!("{value}.to_scval()"),
+        },
+        type_ => panic!("unsupported contract type {}", type_.name()),
+    }
+}
+
+/// Expression decoding `value`, a `&ScVal`, into `Result<T>`
+fn decode(type_: &ScSpecTypeDef, value: &str) -> String {
+    match type_ {
+        ScSpecTypeDef::Val => format!("codec::to_val({value})"),
+        ScSpecTypeDef::Void => format!("codec::to_unit({value})"),
+        ScSpecTypeDef::Bool => format!("codec::to_bool({value})"),
+        ScSpecTypeDef::U32 => format!("codec::to_u32({value})"),
+        ScSpecTypeDef::I32 => format!("codec::to_i32({value})"),
+        ScSpecTypeDef::U64 => format!("codec::to_u64({value})"),
+        ScSpecTypeDef::Timepoint => format!("codec::to_timepoint({value})"),
+        ScSpecTypeDef::Duration => format!("codec::to_duration({value})"),
+        ScSpecTypeDef::I64 => format!("codec::to_i64({value})"),
+        ScSpecTypeDef::U128 => format!("codec::to_u128({value})"),
+        ScSpecTypeDef::I128 => format!("codec::to_i128({value})"),
+        ScSpecTypeDef::Bytes => format!("codec::to_bytes({value})"),
+        ScSpecTypeDef::BytesN(_) => format!("codec::to_bytes_n({value})"),
+        ScSpecTypeDef::String => format!("codec::to_string({value})"),
+        ScSpecTypeDef::Symbol => format!("codec::to_symbol({value})"),
+        ScSpecTypeDef::Address => format!("codec::to_address({value})"),
+        ScSpecTypeDef::Option(option) => format!(
+            "codec::to_option({value}, |value| {})",
+            decode(&option.value_type, "value")
+        ),
+        ScSpecTypeDef::Vec(vec) => format!(
+            "codec::to_vec({value}, |value| {})",
+            decode(&vec.element_type, "value")
+        ),
+        ScSpecTypeDef::Map(map) => format!(
+            "codec::to_map({value}, |value| {}, |value| {})",
+            decode(&map.key_type, "value"),
+            decode(&map.value_type, "value")
+        ),
+        ScSpecTypeDef::Tuple(tuple) if tuple.value_types.is_empty() => {
+            format!("codec::to_unit({value})")
+        }
+        ScSpecTypeDef::Tuple(tuple) => {
+            let items: Vec<_> = tuple
+                .value_types
+                .iter()
+                .enumerate()
+                .map(|(index, type_)| format!("{}?", decode(type_, &format!("&items[{index}]"))))
+                .collect();
+            format!(
+                "codec::to_tuple({value}, {}, \"tuple\").and_then(|items| Ok(({},)))",
+                items.len(),
+                items.join(", ")
+            )
+        }
+        ScSpecTypeDef::Udt(udt) => {
+            format!("{}::from_scval({value})", udt.name.to_utf8_string_lossy())
+        }
+        type_ => panic!("unsupported contract type {}", type_.name()),
+    }
+}
+
+fn write_doc(out: &mut String, indent: &str, doc: &str) {
+    for line in doc.lines() {
+        writeln!(out, "{indent}/// {line}").unwrap();
+    }
+}
+
+fn ident(name: &str) -> String {
+    if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// `EscrowNotFound` as `escrow not found`
+fn words(name: &str) -> String {
+    let mut words = String::new();
+    for (index, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() && index > 0 {
+            words.push(' ');
+        }
+        words.push(c.to_ascii_lowercase());
+    }
+    words
+}
@@ -0,0 +1,199 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use stellar_xdr::{ScAddress, TransactionEnvelope, TransactionV1Envelope};
+use unite_stellar::client::parse_address;
+use unite_stellar::rpc::Simulation;
+use unite_stellar::{tx, Signer, SorobanRpc};
+
+use crate::bindings::{calls, Call, Escrow, EscrowParams, HTLCError};
+use crate::{Error, Result};
+
+/// Lowest inclusion fee per operation, in stroops
+const BASE_FEE: u32 = 100;
+
+/// Transactions are only valid for this long after they are built
+const TX_LIFETIME_SECS: u64 = 60;
+
+/// A submitted call: what it returned in simulation, its transaction and the ledger it landed in
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invoked<T> {
+    pub value: T,
+    pub tx_hash: [u8; 32],
+    pub ledger: u32,
+}
+
+/// Client for one deployed escrow contract, signing as one account
+#[derive(Clone, Debug)]
+pub struct HtlcClient {
+    rpc: SorobanRpc,
+    contract: ScAddress,
+    signer: Signer,
+    network_id: [u8; 32],
+    poll_interval: Duration,
+}
+
+impl HtlcClient {
+    /// `contract` is the escrow's strkey address (`C...`) and `network_passphrase` the
+    /// passphrase of the network it is deployed on
+    pub fn new(
+        rpc_url: impl Into<String>,
+        contract: &str,
+        signer: Signer,
+        network_passphrase: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            rpc: SorobanRpc::new(rpc_url),
+            contract: parse_address(contract)?,
+            signer,
+            network_id: tx::network_id(network_passphrase),
+            poll_interval: Duration::from_secs(5),
+        })
+    }
+
+    /// How often a submitted transaction is polled (defaults to 5 seconds, about one ledger)
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn rpc(&self) -> &SorobanRpc {
+        &self.rpc
+    }
+
+    pub fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    /// The signer as a contract address, e.g. for `EscrowParams::sender`
+    pub fn address(&self) -> ScAddress {
+        ScAddress::Account(self.signer.account_id())
+    }
+
+    /// Lock funds from `params.sender`, which must be the signer; the value is the escrow ID
+    pub async fn create_escrow(&self, params: &EscrowParams) -> Result<Invoked<Vec<u8>>> {
+        self.invoke(&calls::create_escrow(params)?).await
+    }
+
+    /// Release an escrow to the signer, who must be its receiver
+    pub async fn withdraw(&self, escrow_id: &[u8], secret: &[u8; 32]) -> Result<Invoked<()>> {
+        self.invoke(&calls::withdraw(escrow_id, secret, &self.address())?)
+            .await
+    }
+
+    /// Refund an expired escrow to the signer, who must be its sender
+    pub async fn cancel(&self, escrow_id: &[u8]) -> Result<Invoked<()>> {
+        self.invoke(&calls::cancel(escrow_id, &self.address())?)
+            .await
+    }
+
+    /// A pending escrow, or `None` once it is settled or if it never existed
+    pub async fn get_escrow(&self, escrow_id: &[u8]) -> Result<Option<Escrow>> {
+        self.read(&calls::get_escrow(escrow_id)?).await
+    }
+
+    /// Run a call in simulation only, as views are read; nothing is signed, so the signer's
+    /// account need not exist
+    pub async fn read<T>(&self, call: &Call<T>) -> Result<T> {
+        let (value, _) = self.simulate_at(call, 0).await?;
+        Ok(value)
+    }
+
+    /// Simulate a call from the signer's account without submitting it, returning its value
+    /// and the simulation with the resources and fee it needs
+    pub async fn simulate<T>(&self, call: &Call<T>) -> Result<(T, Simulation)> {
+        let sequence = self.sequence().await? + 1;
+        self.simulate_at(call, sequence).await
+    }
+
+    /// Simulate, sign and submit a call, waiting until it is in a closed ledger
+    pub async fn invoke<T>(&self, call: &Call<T>) -> Result<Invoked<T>> {
+        let sequence = self.sequence().await? + 1;
+        let max_time = now() + TX_LIFETIME_SECS;
+        let unsigned = self.transaction(call, sequence, max_time).await?;
+        let simulation = self.rpc.simulate(&unsigned_envelope(&unsigned)).await?;
+        let value = call.decode(
+            simulation
+                .result
+                .as_ref()
+                .ok_or(Error::Decode("simulation returned no value"))?,
+        )?;
+        let (envelope, tx_hash) = self
+            .signer
+            .sign(tx::assemble(unsigned, simulation)?, &self.network_id)
+            .await?;
+        let ledger = self
+            .rpc
+            .submit(&envelope, &tx_hash, max_time, self.poll_interval)
+            .await?;
+        Ok(Invoked {
+            value,
+            tx_hash,
+            ledger,
+        })
+    }
+
+    async fn simulate_at<T>(&self, call: &Call<T>, sequence: i64) -> Result<(T, Simulation)> {
+        let unsigned = self
+            .transaction(call, sequence, now() + TX_LIFETIME_SECS)
+            .await?;
+        let simulation = self.rpc.simulate(&unsigned_envelope(&unsigned)).await?;
+        let value = call.decode(
+            simulation
+                .result
+                .as_ref()
+                .ok_or(Error::Decode("simulation returned no value"))?,
+        )?;
+        Ok((value, simulation))
+    }
+
+    async fn transaction<T>(
+        &self,
+        call: &Call<T>,
+        sequence: i64,
+        max_time: u64,
+    ) -> Result<stellar_xdr::Transaction> {
+        let inclusion_fee = self.rpc.inclusion_fee().await?.max(BASE_FEE);
+        Ok(tx::invocation(
+            &self.signer,
+            sequence,
+            &self.contract,
+            call.function,
+            call.args.clone(),
+            inclusion_fee,
+            max_time,
+        )?)
+    }
+
+    async fn sequence(&self) -> Result<i64> {
+        match self.rpc.account(&self.signer.account_id()).await? {
+            Some(account) => Ok(account.seq_num.0),
+            None => Err(Error::Stellar(unite_stellar::Error::Rpc(format!(
+                "account {} not found",
+                self.signer.address()
+            )))),
+        }
+    }
+}
+
+/// `HTLCError` a failed simulation reports, as `Error(Contract, #<code>)` in its message
+pub(crate) fn contract_error(error: &unite_stellar::Error) -> Option<HTLCError> {
+    let unite_stellar::Error::Simulation(message) = error else {
+        return None;
+    };
+    let (_, rest) = message.split_once("Error(Contract, #")?;
+    let end = rest.find(|c: char| !c.is_ascii_digit())?;
+    HTLCError::from_code(rest[..end].parse().ok()?)
+}
+
+fn unsigned_envelope(tx: &stellar_xdr::Transaction) -> TransactionEnvelope {
+    TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx: tx.clone(),
+        signatures: Default::default(),
+    })
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
@@ -0,0 +1,277 @@
+// `ScVal` encoding the generated bindings are built from, following `soroban-sdk`'s
+// `#[contracttype]` layout: structs are maps keyed by field name, enums are vectors led by the
+// case name and tuples are plain vectors
+
+use stellar_xdr::{ScAddress, ScBytes, ScMap, ScMapEntry, ScString, ScSymbol, ScVal, ScVec};
+
+use crate::{Error, Result};
+
+pub fn scalar<T: Copy + Into<ScVal>>(value: &T) -> Result<ScVal> {
+    Ok((*value).into())
+}
+
+pub fn void() -> Result<ScVal> {
+    Ok(ScVal::Void)
+}
+
+pub fn val(value: &ScVal) -> Result<ScVal> {
+    Ok(value.clone())
+}
+
+pub fn address(value: &ScAddress) -> Result<ScVal> {
+    Ok(ScVal::Address(value.clone()))
+}
+
+pub fn bytes(value: &[u8]) -> Result<ScVal> {
+    Ok(ScVal::Bytes(ScBytes(value.to_vec().try_into()?)))
+}
+
+pub fn string(value: &str) -> Result<ScVal> {
+    Ok(ScVal::String(ScString(value.try_into()?)))
+}
+
+pub fn symbol(value: &str) -> Result<ScVal> {
+    Ok(ScVal::Symbol(ScSymbol(value.try_into()?)))
+}
+
+/// `None` is `Void`, as `soroban-sdk` encodes it
+pub fn option<T>(value: &Option<T>, encode: impl Fn(&T) -> Result<ScVal>) -> Result<ScVal> {
+    match value {
+        Some(value) => encode(value),
+        None => Ok(ScVal::Void),
+    }
+}
+
+pub fn vec<T>(items: &[T], encode: impl Fn(&T) -> Result<ScVal>) -> Result<ScVal> {
+    tuple(items.iter().map(encode).collect::<Result<_>>()?)
+}
+
+pub fn map<K, V>(
+    entries: &[(K, V)],
+    encode_key: impl Fn(&K) -> Result<ScVal>,
+    encode_value: impl Fn(&V) -> Result<ScVal>,
+) -> Result<ScVal> {
+    let entries = entries
+        .iter()
+        .map(|(key, value)| {
+            Ok(ScMapEntry {
+                key: encode_key(key)?,
+                val: encode_value(value)?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    sorted(entries)
+}
+
+pub fn tuple(items: Vec<ScVal>) -> Result<ScVal> {
+    Ok(ScVal::Vec(Some(ScVec(items.try_into()?))))
+}
+
+/// A struct, keyed by field name
+pub fn fields(fields: Vec<(&str, ScVal)>) -> Result<ScVal> {
+    let entries = fields
+        .into_iter()
+        .map(|(name, val)| {
+            Ok(ScMapEntry {
+                key: symbol(name)?,
+                val,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    sorted(entries)
+}
+
+/// An enum case and its values
+pub fn variant(name: &str, values: Vec<ScVal>) -> Result<ScVal> {
+    let mut items = vec![symbol(name)?];
+    items.extend(values);
+    tuple(items)
+}
+
+pub fn to_val(value: &ScVal) -> Result<ScVal> {
+    Ok(value.clone())
+}
+
+pub fn to_unit(value: &ScVal) -> Result<()> {
+    match value {
+        ScVal::Void => Ok(()),
+        _ => Err(Error::Decode("void")),
+    }
+}
+
+pub fn to_bool(value: &ScVal) -> Result<bool> {
+    match value {
+        ScVal::Bool(value) => Ok(*value),
+        _ => Err(Error::Decode("bool")),
+    }
+}
+
+pub fn to_u32(value: &ScVal) -> Result<u32> {
+    match value {
+        ScVal::U32(value) => Ok(*value),
+        _ => Err(Error::Decode("u32")),
+    }
+}
+
+pub fn to_i32(value: &ScVal) -> Result<i32> {
+    match value {
+        ScVal::I32(value) => Ok(*value),
+        _ => Err(Error::Decode("i32")),
+    }
+}
+
+pub fn to_u64(value: &ScVal) -> Result<u64> {
+    match value {
+        ScVal::U64(value) => Ok(*value),
+        _ => Err(Error::Decode("u64")),
+    }
+}
+
+pub fn to_timepoint(value: &ScVal) -> Result<u64> {
+    match value {
+        ScVal::Timepoint(value) => Ok(value.0),
+        _ => Err(Error::Decode("timepoint")),
+    }
+}
+
+pub fn to_duration(value: &ScVal) -> Result<u64> {
+    match value {
+        ScVal::Duration(value) => Ok(value.0),
+        _ => Err(Error::Decode("duration")),
+    }
+}
+
+pub fn to_i64(value: &ScVal) -> Result<i64> {
+    match value {
+        ScVal::I64(value) => Ok(*value),
+        _ => Err(Error::Decode("i64")),
+    }
+}
+
+pub fn to_u128(value: &ScVal) -> Result<u128> {
+    match value {
+        ScVal::U128(parts) => Ok(u128::from(parts)),
+        _ => Err(Error::Decode("u128")),
+    }
+}
+
+pub fn to_i128(value: &ScVal) -> Result<i128> {
+    match value {
+        ScVal::I128(parts) => Ok(i128::from(parts)),
+        _ => Err(Error::Decode("i128")),
+    }
+}
+
+pub fn to_bytes(value: &ScVal) -> Result<Vec<u8>> {
+    match value {
+        ScVal::Bytes(bytes) => Ok(bytes.to_vec()),
+        _ => Err(Error::Decode("bytes")),
+    }
+}
+
+pub fn to_bytes_n<const N: usize>(value: &ScVal) -> Result<[u8; N]> {
+    match value {
+        ScVal::Bytes(bytes) => bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::Decode("fixed-length bytes")),
+        _ => Err(Error::Decode("fixed-length bytes")),
+    }
+}
+
+pub fn to_string(value: &ScVal) -> Result<String> {
+    match value {
+        ScVal::String(string) => Ok(string.to_utf8_string_lossy()),
+        _ => Err(Error::Decode("string")),
+    }
+}
+
+pub fn to_symbol(value: &ScVal) -> Result<String> {
+    match value {
+        ScVal::Symbol(symbol) => Ok(symbol.to_utf8_string_lossy()),
+        _ => Err(Error::Decode("symbol")),
+    }
+}
+
+pub fn to_address(value: &ScVal) -> Result<ScAddress> {
+    match value {
+        ScVal::Address(address) => Ok(address.clone()),
+        _ => Err(Error::Decode("address")),
+    }
+}
+
+pub fn to_option<T>(value: &ScVal, decode: impl Fn(&ScVal) -> Result<T>) -> Result<Option<T>> {
+    match value {
+        ScVal::Void => Ok(None),
+        value => decode(value).map(Some),
+    }
+}
+
+pub fn to_vec<T>(value: &ScVal, decode: impl Fn(&ScVal) -> Result<T>) -> Result<Vec<T>> {
+    match value {
+        ScVal::Vec(Some(items)) => items.iter().map(decode).collect(),
+        _ => Err(Error::Decode("vec")),
+    }
+}
+
+pub fn to_map<K, V>(
+    value: &ScVal,
+    decode_key: impl Fn(&ScVal) -> Result<K>,
+    decode_value: impl Fn(&ScVal) -> Result<V>,
+) -> Result<Vec<(K, V)>> {
+    match value {
+        ScVal::Map(Some(map)) => map
+            .iter()
+            .map(|entry| Ok((decode_key(&entry.key)?, decode_value(&entry.val)?)))
+            .collect(),
+        _ => Err(Error::Decode("map")),
+    }
+}
+
+/// Items of a vector of exactly `len` values
+pub fn to_tuple<'a>(value: &'a ScVal, len: usize, name: &'static str) -> Result<&'a [ScVal]> {
+    match value {
+        ScVal::Vec(Some(items)) if items.len() == len => Ok(items.as_slice()),
+        _ => Err(Error::Decode(name)),
+    }
+}
+
+pub fn to_fields<'a>(value: &'a ScVal, name: &'static str) -> Result<&'a ScMap> {
+    match value {
+        ScVal::Map(Some(map)) => Ok(map),
+        _ => Err(Error::Decode(name)),
+    }
+}
+
+pub fn field<'a>(fields: &'a ScMap, field: &str, name: &'static str) -> Result<&'a ScVal> {
+    fields
+        .iter()
+        .find(
+            |entry| matches!(&entry.key, ScVal::Symbol(key) if key.as_slice() == field.as_bytes()),
+        )
+        .map(|entry| &entry.val)
+        .ok_or(Error::Decode(name))
+}
+
+/// An enum's case name and values
+pub fn to_variant<'a>(value: &'a ScVal, name: &'static str) -> Result<(&'a str, &'a [ScVal])> {
+    match value {
+        ScVal::Vec(Some(items)) => match items.split_first() {
+            Some((ScVal::Symbol(case), values)) => std::str::from_utf8(case.as_slice())
+                .map(|case| (case, values))
+                .map_err(|_| Error::Decode(name)),
+            _ => Err(Error::Decode(name)),
+        },
+        _ => Err(Error::Decode(name)),
+    }
+}
+
+pub fn item<'a>(values: &'a [ScVal], index: usize, name: &'static str) -> Result<&'a ScVal> {
+    values.get(index).ok_or(Error::Decode(name))
+}
+
+fn sorted(entries: Vec<ScMapEntry>) -> Result<ScVal> {
+    Ok(ScVal::Map(Some(ScMap::sorted_from_entries(
+        entries.into_iter(),
+    )?)))
+}
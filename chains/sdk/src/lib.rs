@@ -0,0 +1,39 @@
+// Unite SDK
+// Typed Rust client for the Soroban HTLC escrow (`contracts/stellar`). `build.rs` generates the
+// bindings from the contract's spec (`spec/htlc_escrow.json`, the `contractspecv0` section of
+// the built WASM): a Rust type with its `ScVal` encoding for every contract type, `HTLCError`
+// with its codes, and a typed `Call` per contract function under `calls`. `HtlcClient` sends
+// calls over `unite-stellar`'s Soroban RPC client: it simulates, signs and submits writes and
+// answers reads from the simulation alone. Contract failures surface as `Error::Contract` with
+// the `HTLCError` the contract returned, instead of a simulation message to parse.
+
+pub mod bindings;
+pub mod client;
+pub mod codec;
+
+pub use bindings::{calls, Call, Escrow, EscrowParams, HTLCError};
+pub use client::{HtlcClient, Invoked};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("contract: {0}")]
+    Contract(HTLCError),
+    #[error(transparent)]
+    Stellar(unite_stellar::Error),
+    #[error("unexpected contract value: {0}")]
+    Decode(&'static str),
+    #[error(transparent)]
+    Xdr(#[from] stellar_xdr::Error),
+}
+
+/// A simulation failing with one of the contract's `HTLCError` codes becomes `Error::Contract`
+impl From<unite_stellar::Error> for Error {
+    fn from(error: unite_stellar::Error) -> Self {
+        match client::contract_error(&error) {
+            Some(error) => Error::Contract(error),
+            None => Error::Stellar(error),
+        }
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
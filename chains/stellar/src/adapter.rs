@@ -0,0 +1,214 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use stellar_xdr::ScAddress;
+use unite_core::{
+    ChainAdapter, ChainEvent, EscrowEvent, EscrowId, EscrowParams, EscrowStatus, EventStream,
+    FeeEstimate, Secret, TxId,
+};
+
+use crate::client::{parse_address, EscrowClient, EscrowState, BASE_FEE};
+use crate::values::{EscrowTerms, SettlementStatus};
+use crate::{Error, Result};
+
+impl From<Error> for unite_core::Error {
+    fn from(error: Error) -> Self {
+        unite_core::Error::chain("stellar", error)
+    }
+}
+
+/// Polls one escrow's state and turns its changes into events
+struct Watch {
+    client: EscrowClient,
+    escrow_id: EscrowId,
+    created: bool,
+    settled: bool,
+}
+
+impl Watch {
+    async fn next_event(&mut self) -> Result<Option<ChainEvent>> {
+        let (event, ledger) = match self.client.escrow_state(&self.escrow_id).await? {
+            EscrowState::Missing => return Ok(None),
+            EscrowState::Pending { .. } if self.created => return Ok(None),
+            EscrowState::Pending { escrow, ledger } => {
+                let event = EscrowEvent::Created {
+                    amount: u128::try_from(escrow.amount).unwrap_or_default(),
+                    hashlock: escrow.secret_hash,
+                    timelock: escrow.timelock,
+                };
+                self.created = true;
+                (event, ledger)
+            }
+            EscrowState::Settled { receipt, ledger } => {
+                let event = match receipt.status {
+                    SettlementStatus::Withdrawn => EscrowEvent::Claimed {
+                        secret: self
+                            .client
+                            .revealed_secret(&receipt.secret_hash)
+                            .await?
+                            .map(Vec::from),
+                    },
+                    // Arbiter resolutions reveal no secret, so they end the leg like a refund
+                    SettlementStatus::Cancelled | SettlementStatus::Resolved => {
+                        EscrowEvent::Refunded
+                    }
+                };
+                self.settled = true;
+                (event, ledger)
+            }
+        };
+        Ok(Some(ChainEvent {
+            escrow_id: self.escrow_id,
+            event,
+            height: u64::from(ledger),
+            tx: None,
+            removed: false,
+        }))
+    }
+}
+
+/// Stellar ledgers are final once closed, so events carry no transaction and never get
+/// `removed`; they come from polling escrow state, which also means an escrow first seen
+/// after it settled reports only its settlement
+#[async_trait]
+impl ChainAdapter for EscrowClient {
+    fn chain(&self) -> &str {
+        "stellar"
+    }
+
+    async fn create_escrow(&self, params: &EscrowParams) -> unite_core::Result<(EscrowId, TxId)> {
+        let terms = EscrowTerms {
+            sender: ScAddress::Account(self.signer().account_id()),
+            receiver: parse_param("receiver", &params.receiver)?,
+            token: params
+                .token
+                .as_deref()
+                .map(|token| parse_param("token", token))
+                .transpose()?,
+            amount: i128::try_from(params.amount).map_err(|_| unite_core::Error::InvalidParam {
+                field: "amount",
+                value: params.amount.to_string(),
+            })?,
+            secret_hash: params.hashlock,
+            hash_lock: self.hash_lock,
+            timelock: params.timelock,
+            order_id: params.order_id.clone(),
+        };
+        let (escrow_id, tx_hash) = EscrowClient::create_escrow(self, &terms).await?;
+        Ok((escrow_id, hex::encode(tx_hash)))
+    }
+
+    /// Escrow state is read directly, so `from_height` is not needed to catch up
+    async fn watch_events(
+        &self,
+        escrow_id: EscrowId,
+        _from_height: u64,
+    ) -> unite_core::Result<EventStream> {
+        let watch = Watch {
+            client: self.clone(),
+            escrow_id,
+            created: false,
+            settled: false,
+        };
+        let events = stream::unfold(watch, |mut watch| async move {
+            if watch.settled {
+                return None;
+            }
+            loop {
+                match watch.next_event().await {
+                    Ok(Some(event)) => return Some((Ok(event), watch)),
+                    Ok(None) => tokio::time::sleep(watch.client.poll_interval).await,
+                    Err(error) => {
+                        tokio::time::sleep(watch.client.poll_interval).await;
+                        return Some((Err(error.into()), watch));
+                    }
+                }
+            }
+        });
+        Ok(events.boxed())
+    }
+
+    async fn reveal_secret(&self, escrow_id: &EscrowId) -> unite_core::Result<Option<Secret>> {
+        let secret_hash = match self.escrow_state(escrow_id).await? {
+            EscrowState::Missing => return Ok(None),
+            EscrowState::Pending { escrow, .. } => escrow.secret_hash,
+            EscrowState::Settled { receipt, .. } => receipt.secret_hash,
+        };
+        Ok(self.revealed_secret(&secret_hash).await?.map(Vec::from))
+    }
+
+    async fn escrow_status(&self, escrow_id: &EscrowId) -> unite_core::Result<EscrowStatus> {
+        Ok(match self.escrow_state(escrow_id).await? {
+            EscrowState::Missing => EscrowStatus::Missing,
+            EscrowState::Pending { .. } => EscrowStatus::Open,
+            EscrowState::Settled { receipt, .. } => match receipt.status {
+                SettlementStatus::Withdrawn => EscrowStatus::Claimed,
+                SettlementStatus::Cancelled | SettlementStatus::Resolved => EscrowStatus::Refunded,
+            },
+        })
+    }
+
+    async fn claim(&self, escrow_id: &EscrowId, secret: &[u8]) -> unite_core::Result<TxId> {
+        let secret: &[u8; 32] = secret.try_into().map_err(|_| {
+            unite_core::Error::InvalidSecret(
+                "the Stellar escrow takes a 32-byte secret".to_string(),
+            )
+        })?;
+        Ok(hex::encode(self.withdraw(escrow_id, secret).await?))
+    }
+
+    async fn refund(&self, escrow_id: &EscrowId) -> unite_core::Result<TxId> {
+        Ok(hex::encode(self.cancel(escrow_id).await?))
+    }
+
+    fn finality_depth(&self) -> u64 {
+        1
+    }
+
+    async fn head(&self) -> unite_core::Result<u64> {
+        Ok(u64::from(self.rpc().latest_ledger().await?))
+    }
+
+    async fn fee_estimate(&self) -> unite_core::Result<FeeEstimate> {
+        let max_fee = self.rpc().inclusion_fee().await?.max(BASE_FEE);
+        Ok(FeeEstimate {
+            max_fee: u128::from(max_fee),
+            priority_fee: u128::from(max_fee - BASE_FEE),
+        })
+    }
+
+    /// Only lumens are read; Soroban token balances are `None`
+    async fn balance(&self, token: Option<&str>) -> unite_core::Result<Option<u128>> {
+        if token.is_some() {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.rpc()
+                .native_balance(&self.signer().account_id())
+                .await?,
+        ))
+    }
+
+    /// Stroops charged, from the transaction result
+    async fn tx_fee(&self, tx: &TxId) -> unite_core::Result<Option<u128>> {
+        let fee = self.rpc().fee_charged(&parse_tx(tx)?).await?;
+        Ok(fee.map(|fee| fee.max(0) as u128))
+    }
+}
+
+/// A transaction hash as the adapters return it, hex without a prefix
+pub(crate) fn parse_tx(tx: &TxId) -> unite_core::Result<[u8; 32]> {
+    hex::decode(tx)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| unite_core::Error::InvalidParam {
+            field: "tx",
+            value: tx.clone(),
+        })
+}
+
+fn parse_param(field: &'static str, value: &str) -> unite_core::Result<ScAddress> {
+    parse_address(value).map_err(|_| unite_core::Error::InvalidParam {
+        field,
+        value: value.to_string(),
+    })
+}
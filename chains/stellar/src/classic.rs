@@ -0,0 +1,781 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use sha2::{Digest, Sha256};
+use stellar_xdr::{
+    AccountId, AlphaNum12, AlphaNum4, Asset, AssetCode, BeginSponsoringFutureReservesOp,
+    BumpSequenceOp, ChangeTrustAsset, ChangeTrustOp, ClaimClaimableBalanceOp, ClaimPredicate,
+    ClaimableBalanceId, Claimant, ClaimantV0, CreateAccountOp, CreateClaimableBalanceOp,
+    DecoratedSignature, Hash, HashIdPreimage, HashIdPreimageOperationId, LedgerEntryData,
+    LedgerKey, LedgerKeyClaimableBalance, Limits, Memo, MuxedAccount, Operation, OperationBody,
+    PaymentOp, Preconditions, PublicKey, SequenceNumber, SetOptionsOp, Signature, SignerKey,
+    TimeBounds, TimePoint, Transaction, TransactionEnvelope, TransactionExt, Uint256, WriteXdr,
+};
+use unite_core::{
+    ChainAdapter, ChainEvent, EscrowEvent, EscrowId, EscrowParams, EscrowStatus, EventStream,
+    FeeEstimate, Secret, TxId,
+};
+
+use crate::adapter::parse_tx;
+use crate::client::{escrow_id, now, sequence, BASE_FEE, TX_LIFETIME_SECS};
+use crate::horizon::Horizon;
+use crate::rpc::SorobanRpc;
+use crate::tx::{self, Signer};
+use crate::{Error, Result};
+
+/// Native XLM left on the escrow account to pay its own claim or refund fees; whoever merges
+/// the account receives what is left
+const FEE_BUFFER: i64 = 1_000_000;
+
+/// Fee per operation fixed into the pre-authorized refund, which cannot be re-priced later
+const REFUND_FEE_PER_OP: u32 = 10_000;
+
+/// The escrow account's sequence is bumped this many ledgers ahead so the refund's sequence
+/// number is known before the account exists
+const SEQUENCE_LEDGER_MARGIN: i64 = 1_000;
+
+/// Neither the receiver (2) nor the preimage (1) reaches the threshold (3) alone
+const RECEIVER_WEIGHT: u32 = 2;
+const HASH_X_WEIGHT: u32 = 1;
+const THRESHOLD: u32 = 3;
+
+/// Transactions of a balance scanned for its claim; a balance has its creation and one claim
+const HISTORY_LIMIT: u32 = 10;
+
+/// A claimable-balance HTLC as found on the ledger
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClaimableLock {
+    pub balance_id: [u8; 32],
+    /// Account the receiver claims through, controlled by the receiver and the preimage
+    pub escrow_account: AccountId,
+    pub sender: AccountId,
+    pub receiver: AccountId,
+    /// SHA-256 of the secret
+    pub hashlock: [u8; 32],
+    pub asset: Asset,
+    pub amount: i64,
+    /// Unix seconds
+    pub timelock: u64,
+    /// Current sequence number of the escrow account
+    pub sequence: i64,
+    /// Ledger that created the balance
+    pub ledger: u32,
+}
+
+/// How a claimable-balance HTLC ended
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Settlement {
+    Claimed([u8; 32]),
+    Refunded,
+}
+
+/// HTLC built from classic claimable balances, for assets whose issuers refuse Soroban
+///
+/// Predicates cannot check a hash preimage, so the balance is claimable by a fresh escrow
+/// account before the timelock and by the sender after it. The escrow account's only signers
+/// are the receiver and a `hash(x)` signer, so claiming through it reveals the secret as a
+/// transaction signature. Its reserves are sponsored by the sender and it is merged away on
+/// either path: into the receiver by the claim, or into the sender by a refund transaction
+/// pre-authorized when the account is created.
+///
+/// A `hash(x)` signer is keyed by the SHA-256 of the secret, so the hashlock is a SHA-256 one
+/// and never equals the keccak256 hashlock of an EVM escrow or a default Soroban escrow. Pair it
+/// only with SHA-256 legs: a Soroban escrow created with `HashLock::Sha256`, a Bitcoin HTLC or a
+/// TON escrow.
+#[derive(Clone, Debug)]
+pub struct ClaimableEscrow {
+    rpc: SorobanRpc,
+    horizon: Horizon,
+    signer: Signer,
+    network_id: [u8; 32],
+    poll_interval: Duration,
+}
+
+impl ClaimableEscrow {
+    pub fn new(
+        rpc_url: impl Into<String>,
+        horizon_url: impl Into<String>,
+        signer: Signer,
+        network_passphrase: &str,
+    ) -> Self {
+        Self {
+            rpc: SorobanRpc::new(rpc_url),
+            horizon: Horizon::new(horizon_url),
+            signer,
+            network_id: tx::network_id(network_passphrase),
+            poll_interval: Duration::from_secs(5),
+        }
+    }
+
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Replace the RPC client, e.g. with one that has fallback endpoints
+    pub fn with_rpc(mut self, rpc: SorobanRpc) -> Self {
+        self.rpc = rpc;
+        self
+    }
+
+    /// Replace the Horizon client, e.g. with one that has fallback endpoints
+    pub fn with_horizon(mut self, horizon: Horizon) -> Self {
+        self.horizon = horizon;
+        self
+    }
+
+    pub fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    /// Lock `amount` of `asset` from the signer's account, returning the balance ID and
+    /// transaction hash; the memo carries the contract escrow ID of `order_id`
+    pub async fn create(
+        &self,
+        receiver: &AccountId,
+        asset: &Asset,
+        amount: i64,
+        hashlock: &[u8; 32],
+        timelock: u64,
+        order_id: &str,
+    ) -> Result<([u8; 32], [u8; 32])> {
+        let sender = self.signer.account_id();
+        let escrow = Signer::generate();
+        let escrow_account = escrow.account_id();
+        let escrow_sequence =
+            (i64::from(self.rpc.latest_ledger().await?) + SEQUENCE_LEDGER_MARGIN) << 32;
+        let sender_sequence = sequence(&self.rpc, &self.signer).await? + 1;
+
+        let mut operations = vec![
+            op(
+                Some(&sender),
+                OperationBody::BeginSponsoringFutureReserves(BeginSponsoringFutureReservesOp {
+                    sponsored_id: escrow_account.clone(),
+                }),
+            ),
+            op(
+                Some(&sender),
+                OperationBody::CreateAccount(CreateAccountOp {
+                    destination: escrow_account.clone(),
+                    starting_balance: FEE_BUFFER,
+                }),
+            ),
+        ];
+        if let Some(line) = trust_line(asset) {
+            operations.push(op(
+                Some(&escrow_account),
+                OperationBody::ChangeTrust(ChangeTrustOp {
+                    line,
+                    limit: i64::MAX,
+                }),
+            ));
+        }
+        operations.push(op(
+            Some(&escrow_account),
+            OperationBody::BumpSequence(BumpSequenceOp {
+                bump_to: SequenceNumber(escrow_sequence),
+            }),
+        ));
+        // The balance is created last, after three signer changes and the sponsorship's end,
+        // so its ID is known from the sender's sequence now
+        let balance_id = balance_id(&sender, sender_sequence, operations.len() as u32 + 4)?;
+        let refund = refund_tx(&ClaimableLock {
+            balance_id,
+            escrow_account: escrow_account.clone(),
+            sender: sender.clone(),
+            receiver: receiver.clone(),
+            hashlock: *hashlock,
+            asset: asset.clone(),
+            amount,
+            timelock,
+            sequence: escrow_sequence,
+            ledger: 0,
+        })?;
+        let signers = [
+            (
+                SignerKey::Ed25519(Uint256(account_key(receiver))),
+                RECEIVER_WEIGHT,
+            ),
+            (SignerKey::HashX(Uint256(*hashlock)), HASH_X_WEIGHT),
+            (
+                SignerKey::PreAuthTx(Uint256(refund.hash(self.network_id)?)),
+                THRESHOLD,
+            ),
+        ];
+        for (index, (key, weight)) in signers.into_iter().enumerate() {
+            // Signatures are checked before any operation applies, so dropping the master
+            // key here does not stop the escrow account's later operations
+            let lock_down = index == 0;
+            operations.push(op(
+                Some(&escrow_account),
+                OperationBody::SetOptions(SetOptionsOp {
+                    inflation_dest: None,
+                    clear_flags: None,
+                    set_flags: None,
+                    master_weight: lock_down.then_some(0),
+                    low_threshold: lock_down.then_some(THRESHOLD),
+                    med_threshold: lock_down.then_some(THRESHOLD),
+                    high_threshold: lock_down.then_some(THRESHOLD),
+                    home_domain: None,
+                    signer: Some(stellar_xdr::Signer { key, weight }),
+                }),
+            ));
+        }
+        operations.push(op(
+            Some(&escrow_account),
+            OperationBody::EndSponsoringFutureReserves,
+        ));
+        operations.push(op(
+            Some(&sender),
+            OperationBody::CreateClaimableBalance(CreateClaimableBalanceOp {
+                asset: asset.clone(),
+                amount,
+                claimants: vec![
+                    claimant(&escrow_account, before(timelock)?),
+                    claimant(
+                        &sender,
+                        ClaimPredicate::Not(Some(Box::new(before(timelock)?))),
+                    ),
+                ]
+                .try_into()?,
+            }),
+        ));
+
+        let fee = self.rpc.classic_inclusion_fee().await?.max(BASE_FEE);
+        let expires_at = now() + TX_LIFETIME_SECS;
+        let create = transaction(
+            &sender,
+            sender_sequence,
+            fee * operations.len() as u32,
+            TimeBounds {
+                min_time: TimePoint(0),
+                max_time: TimePoint(expires_at),
+            },
+            Memo::Hash(Hash(escrow_id(order_id))),
+            operations,
+        )?;
+        let hash = create.hash(self.network_id)?;
+        let signatures = vec![
+            self.signer.signature(&hash).await?,
+            escrow.signature(&hash).await?,
+        ];
+        self.rpc
+            .submit(
+                &tx::envelope(create, signatures)?,
+                &hash,
+                expires_at,
+                self.poll_interval,
+            )
+            .await?;
+        Ok((balance_id, hash))
+    }
+
+    /// Claim a balance with its secret, paying it to the signer, who must be its receiver
+    pub async fn claim(&self, balance_id: &[u8; 32], secret: &[u8; 32]) -> Result<[u8; 32]> {
+        let lock = self.existing_lock(balance_id).await?;
+        if lock.receiver != self.signer.account_id() {
+            return Err(Error::Classic("signer is not the receiver".to_string()));
+        }
+        let mut operations = vec![op(None, claim_op(balance_id))];
+        if !matches!(lock.asset, Asset::Native) {
+            operations.push(op(
+                None,
+                OperationBody::Payment(PaymentOp {
+                    destination: MuxedAccount::from(lock.receiver.clone()),
+                    asset: lock.asset.clone(),
+                    amount: lock.amount,
+                }),
+            ));
+        }
+        // The pre-authorized refund's signer only goes away on its own when the refund runs
+        let refund = refund_tx(&lock)?.hash(self.network_id)?;
+        operations.push(remove_signer(SignerKey::PreAuthTx(Uint256(refund))));
+        operations.extend(close(&lock, &lock.receiver));
+
+        let fee = self.rpc.classic_inclusion_fee().await?.max(BASE_FEE);
+        let fee = (fee * operations.len() as u32).min(FEE_BUFFER as u32);
+        let expires_at = now() + TX_LIFETIME_SECS;
+        let claim = transaction(
+            &lock.escrow_account,
+            lock.sequence + 1,
+            fee,
+            TimeBounds {
+                min_time: TimePoint(0),
+                max_time: TimePoint(expires_at),
+            },
+            Memo::None,
+            operations,
+        )?;
+        let hash = claim.hash(self.network_id)?;
+        let preimage = DecoratedSignature {
+            hint: tx::hint(&lock.hashlock),
+            signature: Signature(secret.to_vec().try_into()?),
+        };
+        let signatures = vec![self.signer.signature(&hash).await?, preimage];
+        self.rpc
+            .submit(
+                &tx::envelope(claim, signatures)?,
+                &hash,
+                expires_at,
+                self.poll_interval,
+            )
+            .await?;
+        Ok(hash)
+    }
+
+    /// Return an expired balance to the signer, who must be its sender, merging the escrow
+    /// account back through the pre-authorized refund
+    pub async fn refund(&self, balance_id: &[u8; 32]) -> Result<[u8; 32]> {
+        let lock = self.existing_lock(balance_id).await?;
+        if lock.sender != self.signer.account_id() {
+            return Err(Error::Classic("signer is not the sender".to_string()));
+        }
+        let refund = refund_tx(&lock)?;
+        let hash = refund.hash(self.network_id)?;
+        self.rpc
+            .submit(
+                &tx::envelope(refund, vec![self.signer.signature(&hash).await?])?,
+                &hash,
+                now() + TX_LIFETIME_SECS,
+                self.poll_interval,
+            )
+            .await?;
+        Ok(hash)
+    }
+
+    /// The HTLC behind a balance, or `None` once it is claimed or refunded
+    pub async fn lock(&self, balance_id: &[u8; 32]) -> Result<Option<ClaimableLock>> {
+        let key = LedgerKey::ClaimableBalance(LedgerKeyClaimableBalance {
+            balance_id: ClaimableBalanceId::ClaimableBalanceIdTypeV0(Hash(*balance_id)),
+        });
+        let Some(entry) = self
+            .rpc
+            .ledger_entries(&[key])
+            .await?
+            .into_iter()
+            .next()
+            .flatten()
+        else {
+            return Ok(None);
+        };
+        let LedgerEntryData::ClaimableBalance(balance) = entry.data else {
+            return Err(Error::Decode("claimable balance entry"));
+        };
+
+        let mut escrow = None;
+        let mut sender = None;
+        for Claimant::ClaimantTypeV0(claimant) in balance.claimants.iter() {
+            match &claimant.predicate {
+                ClaimPredicate::BeforeAbsoluteTime(timelock) => {
+                    escrow = Some((claimant.destination.clone(), *timelock as u64));
+                }
+                ClaimPredicate::Not(Some(_)) => sender = Some(claimant.destination.clone()),
+                _ => {}
+            }
+        }
+        let (Some((escrow_account, timelock)), Some(sender)) = (escrow, sender) else {
+            return Err(Error::Classic(
+                "claimable balance is not an HTLC".to_string(),
+            ));
+        };
+        let Some(account) = self.rpc.account(&escrow_account).await? else {
+            return Err(Error::Classic("escrow account not found".to_string()));
+        };
+
+        let mut receiver = None;
+        let mut hashlock = None;
+        for signer in account.signers.iter() {
+            match &signer.key {
+                SignerKey::Ed25519(key) if signer.weight == RECEIVER_WEIGHT => {
+                    receiver = Some(AccountId(PublicKey::PublicKeyTypeEd25519(key.clone())));
+                }
+                SignerKey::HashX(hash) => hashlock = Some(hash.0),
+                _ => {}
+            }
+        }
+        let (Some(receiver), Some(hashlock)) = (receiver, hashlock) else {
+            return Err(Error::Classic(
+                "escrow account signers do not match".to_string(),
+            ));
+        };
+        Ok(Some(ClaimableLock {
+            balance_id: *balance_id,
+            escrow_account,
+            sender,
+            receiver,
+            hashlock,
+            asset: balance.asset,
+            amount: balance.amount,
+            timelock,
+            sequence: account.seq_num.0,
+            ledger: entry.last_modified,
+        }))
+    }
+
+    /// How a settled balance ended, from its transaction history on Horizon; `None` while the
+    /// claim has not been indexed yet
+    pub async fn settlement(&self, balance_id: &[u8; 32]) -> Result<Option<Settlement>> {
+        let claim = claim_op(balance_id);
+        for envelope in self
+            .horizon
+            .claimable_balance_transactions(balance_id, HISTORY_LIMIT)
+            .await?
+        {
+            let TransactionEnvelope::Tx(envelope) = envelope else {
+                continue;
+            };
+            if !envelope.tx.operations.iter().any(|op| op.body == claim) {
+                continue;
+            }
+            // The preimage is the 32-byte signature whose hash ends in its hint
+            let secret = envelope.signatures.iter().find_map(|signature| {
+                let secret: [u8; 32] = signature.signature.as_slice().try_into().ok()?;
+                let hash: [u8; 32] = Sha256::digest(secret).into();
+                (tx::hint(&hash) == signature.hint).then_some(secret)
+            });
+            return Ok(Some(match secret {
+                Some(secret) => Settlement::Claimed(secret),
+                None => Settlement::Refunded,
+            }));
+        }
+        Ok(None)
+    }
+
+    async fn existing_lock(&self, balance_id: &[u8; 32]) -> Result<ClaimableLock> {
+        self.lock(balance_id)
+            .await?
+            .ok_or_else(|| Error::Classic("claimable balance not found".to_string()))
+    }
+}
+
+/// `native` or `CODE:ISSUER`
+pub fn parse_asset(asset: &str) -> Result<Asset> {
+    if asset == "native" {
+        return Ok(Asset::Native);
+    }
+    let invalid = || Error::Classic(format!("invalid asset {asset}"));
+    let (code, issuer) = asset.split_once(':').ok_or_else(invalid)?;
+    let issuer = AccountId::from_str(issuer).map_err(|_| invalid())?;
+    Ok(match AssetCode::from_str(code).map_err(|_| invalid())? {
+        AssetCode::CreditAlphanum4(asset_code) => {
+            Asset::CreditAlphanum4(AlphaNum4 { asset_code, issuer })
+        }
+        AssetCode::CreditAlphanum12(asset_code) => {
+            Asset::CreditAlphanum12(AlphaNum12 { asset_code, issuer })
+        }
+    })
+}
+
+/// ID of the balance created by operation `op_index` of `source`'s transaction `sequence`
+fn balance_id(source: &AccountId, sequence: i64, op_index: u32) -> Result<[u8; 32]> {
+    let preimage = HashIdPreimage::OpId(HashIdPreimageOperationId {
+        source_account: source.clone(),
+        seq_num: SequenceNumber(sequence),
+        op_num: op_index,
+    });
+    Ok(Sha256::digest(preimage.to_xdr(Limits::none())?).into())
+}
+
+/// Refund the sender claims the balance with and merges the escrow account back in; its hash
+/// is pre-authorized on the escrow account, so every field must be rebuilt identically
+fn refund_tx(lock: &ClaimableLock) -> Result<Transaction> {
+    let mut operations = vec![op(Some(&lock.sender), claim_op(&lock.balance_id))];
+    operations.extend(close(lock, &lock.sender));
+    transaction(
+        &lock.escrow_account,
+        lock.sequence + 1,
+        REFUND_FEE_PER_OP * operations.len() as u32,
+        TimeBounds {
+            min_time: TimePoint(lock.timelock),
+            max_time: TimePoint(0),
+        },
+        Memo::None,
+        operations,
+    )
+}
+
+/// Operations that empty the escrow account of trustline and signers and merge it into
+/// `destination`; merging fails while any subentry is left
+fn close(lock: &ClaimableLock, destination: &AccountId) -> Vec<Operation> {
+    let mut operations = Vec::new();
+    if let Some(line) = trust_line(&lock.asset) {
+        operations.push(op(
+            None,
+            OperationBody::ChangeTrust(ChangeTrustOp { line, limit: 0 }),
+        ));
+    }
+    operations.push(remove_signer(SignerKey::Ed25519(Uint256(account_key(
+        &lock.receiver,
+    )))));
+    operations.push(remove_signer(SignerKey::HashX(Uint256(lock.hashlock))));
+    operations.push(op(
+        None,
+        OperationBody::AccountMerge(MuxedAccount::from(destination.clone())),
+    ));
+    operations
+}
+
+fn remove_signer(key: SignerKey) -> Operation {
+    op(
+        None,
+        OperationBody::SetOptions(SetOptionsOp {
+            inflation_dest: None,
+            clear_flags: None,
+            set_flags: None,
+            master_weight: None,
+            low_threshold: None,
+            med_threshold: None,
+            high_threshold: None,
+            home_domain: None,
+            signer: Some(stellar_xdr::Signer { key, weight: 0 }),
+        }),
+    )
+}
+
+fn transaction(
+    source: &AccountId,
+    sequence: i64,
+    fee: u32,
+    time_bounds: TimeBounds,
+    memo: Memo,
+    operations: Vec<Operation>,
+) -> Result<Transaction> {
+    Ok(Transaction {
+        source_account: MuxedAccount::from(source.clone()),
+        fee,
+        seq_num: SequenceNumber(sequence),
+        cond: Preconditions::Time(time_bounds),
+        memo,
+        operations: operations.try_into()?,
+        ext: TransactionExt::V0,
+    })
+}
+
+fn op(source: Option<&AccountId>, body: OperationBody) -> Operation {
+    Operation {
+        source_account: source.map(|source| MuxedAccount::from(source.clone())),
+        body,
+    }
+}
+
+fn claim_op(balance_id: &[u8; 32]) -> OperationBody {
+    OperationBody::ClaimClaimableBalance(ClaimClaimableBalanceOp {
+        balance_id: ClaimableBalanceId::ClaimableBalanceIdTypeV0(Hash(*balance_id)),
+    })
+}
+
+fn claimant(destination: &AccountId, predicate: ClaimPredicate) -> Claimant {
+    Claimant::ClaimantTypeV0(ClaimantV0 {
+        destination: destination.clone(),
+        predicate,
+    })
+}
+
+fn before(timelock: u64) -> Result<ClaimPredicate> {
+    let timelock = i64::try_from(timelock)
+        .map_err(|_| Error::Classic(format!("timelock {timelock} out of range")))?;
+    Ok(ClaimPredicate::BeforeAbsoluteTime(timelock))
+}
+
+/// Trustline the escrow account needs to hold `asset`, or `None` for XLM
+fn trust_line(asset: &Asset) -> Option<ChangeTrustAsset> {
+    match asset {
+        Asset::Native => None,
+        Asset::CreditAlphanum4(asset) => Some(ChangeTrustAsset::CreditAlphanum4(asset.clone())),
+        Asset::CreditAlphanum12(asset) => Some(ChangeTrustAsset::CreditAlphanum12(asset.clone())),
+    }
+}
+
+fn account_key(account: &AccountId) -> [u8; 32] {
+    let PublicKey::PublicKeyTypeEd25519(key) = &account.0;
+    key.0
+}
+
+impl From<Settlement> for EscrowEvent {
+    fn from(settlement: Settlement) -> Self {
+        match settlement {
+            Settlement::Claimed(secret) => EscrowEvent::Claimed {
+                secret: Some(secret.to_vec()),
+            },
+            Settlement::Refunded => EscrowEvent::Refunded,
+        }
+    }
+}
+
+/// Polls one balance and turns its changes into events
+struct Watch {
+    escrow: ClaimableEscrow,
+    balance_id: [u8; 32],
+    created: bool,
+    settled: bool,
+}
+
+impl Watch {
+    async fn next_event(&mut self) -> Result<Option<ChainEvent>> {
+        let (event, height) = match self.escrow.lock(&self.balance_id).await? {
+            Some(_) if self.created => return Ok(None),
+            Some(lock) => {
+                self.created = true;
+                let event = EscrowEvent::Created {
+                    amount: u128::try_from(lock.amount).unwrap_or_default(),
+                    hashlock: lock.hashlock,
+                    timelock: lock.timelock,
+                };
+                (event, u64::from(lock.ledger))
+            }
+            None => match self.escrow.settlement(&self.balance_id).await? {
+                Some(settlement) => {
+                    self.settled = true;
+                    let height = self.escrow.rpc.latest_ledger().await?;
+                    (settlement.into(), u64::from(height))
+                }
+                None => return Ok(None),
+            },
+        };
+        Ok(Some(ChainEvent {
+            escrow_id: self.balance_id,
+            event,
+            height,
+            tx: None,
+            removed: false,
+        }))
+    }
+}
+
+/// Escrow IDs are balance IDs; tokens are `CODE:ISSUER` assets and `order_id` only goes into
+/// the creation memo
+#[async_trait]
+impl ChainAdapter for ClaimableEscrow {
+    fn chain(&self) -> &str {
+        "stellar-classic"
+    }
+
+    async fn create_escrow(&self, params: &EscrowParams) -> unite_core::Result<(EscrowId, TxId)> {
+        let receiver =
+            AccountId::from_str(&params.receiver).map_err(|_| unite_core::Error::InvalidParam {
+                field: "receiver",
+                value: params.receiver.clone(),
+            })?;
+        let asset = match params.token.as_deref() {
+            Some(token) => parse_asset(token).map_err(|_| unite_core::Error::InvalidParam {
+                field: "token",
+                value: token.to_string(),
+            })?,
+            None => Asset::Native,
+        };
+        let amount = i64::try_from(params.amount).map_err(|_| unite_core::Error::InvalidParam {
+            field: "amount",
+            value: params.amount.to_string(),
+        })?;
+        let (balance_id, tx_hash) = self
+            .create(
+                &receiver,
+                &asset,
+                amount,
+                &params.hashlock,
+                params.timelock,
+                &params.order_id,
+            )
+            .await?;
+        Ok((balance_id, hex::encode(tx_hash)))
+    }
+
+    /// Balance state is read directly, so `from_height` is not needed to catch up
+    async fn watch_events(
+        &self,
+        escrow_id: EscrowId,
+        _from_height: u64,
+    ) -> unite_core::Result<EventStream> {
+        let watch = Watch {
+            escrow: self.clone(),
+            balance_id: escrow_id,
+            created: false,
+            settled: false,
+        };
+        let events = stream::unfold(watch, |mut watch| async move {
+            if watch.settled {
+                return None;
+            }
+            loop {
+                match watch.next_event().await {
+                    Ok(Some(event)) => return Some((Ok(event), watch)),
+                    Ok(None) => tokio::time::sleep(watch.escrow.poll_interval).await,
+                    Err(error) => {
+                        tokio::time::sleep(watch.escrow.poll_interval).await;
+                        return Some((Err(error.into()), watch));
+                    }
+                }
+            }
+        });
+        Ok(events.boxed())
+    }
+
+    async fn reveal_secret(&self, escrow_id: &EscrowId) -> unite_core::Result<Option<Secret>> {
+        match self.settlement(escrow_id).await? {
+            Some(Settlement::Claimed(secret)) => Ok(Some(secret.to_vec())),
+            _ => Ok(None),
+        }
+    }
+
+    /// `Missing` for a spent balance whose claim Horizon has not indexed yet
+    async fn escrow_status(&self, escrow_id: &EscrowId) -> unite_core::Result<EscrowStatus> {
+        if self.lock(escrow_id).await?.is_some() {
+            return Ok(EscrowStatus::Open);
+        }
+        Ok(match self.settlement(escrow_id).await? {
+            Some(Settlement::Claimed(_)) => EscrowStatus::Claimed,
+            Some(Settlement::Refunded) => EscrowStatus::Refunded,
+            None => EscrowStatus::Missing,
+        })
+    }
+
+    async fn claim(&self, escrow_id: &EscrowId, secret: &[u8]) -> unite_core::Result<TxId> {
+        let secret: &[u8; 32] = secret.try_into().map_err(|_| {
+            unite_core::Error::InvalidSecret("claimable balances take a 32-byte secret".to_string())
+        })?;
+        Ok(hex::encode(
+            ClaimableEscrow::claim(self, escrow_id, secret).await?,
+        ))
+    }
+
+    async fn refund(&self, escrow_id: &EscrowId) -> unite_core::Result<TxId> {
+        Ok(hex::encode(ClaimableEscrow::refund(self, escrow_id).await?))
+    }
+
+    fn finality_depth(&self) -> u64 {
+        1
+    }
+
+    /// Probes Horizon too, since claims are found through it; the older of the two ledgers
+    async fn head(&self) -> unite_core::Result<u64> {
+        let (rpc, horizon) =
+            futures::try_join!(self.rpc.latest_ledger(), self.horizon.latest_ledger())?;
+        Ok(u64::from(rpc.min(horizon)))
+    }
+
+    async fn fee_estimate(&self) -> unite_core::Result<FeeEstimate> {
+        let max_fee = self.rpc.classic_inclusion_fee().await?.max(BASE_FEE);
+        Ok(FeeEstimate {
+            max_fee: u128::from(max_fee),
+            priority_fee: u128::from(max_fee - BASE_FEE),
+        })
+    }
+
+    /// Only lumens are read; trustline balances are `None`
+    async fn balance(&self, token: Option<&str>) -> unite_core::Result<Option<u128>> {
+        if token.is_some() {
+            return Ok(None);
+        }
+        Ok(Some(
+            self.rpc.native_balance(&self.signer.account_id()).await?,
+        ))
+    }
+
+    /// Stroops charged, from the transaction result
+    async fn tx_fee(&self, tx: &TxId) -> unite_core::Result<Option<u128>> {
+        let fee = self.rpc.fee_charged(&parse_tx(tx)?).await?;
+        Ok(fee.map(|fee| fee.max(0) as u128))
+    }
+}
@@ -0,0 +1,330 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use sha3::{Digest, Keccak256};
+use stellar_xdr::{ScAddress, ScVal, Transaction, TransactionEnvelope, TransactionV1Envelope};
+use unite_core::{BumpPolicy, FeeManager};
+
+use crate::rpc::{Simulation, SorobanRpc};
+use crate::tx::{self, Signer};
+use crate::values::{self, EscrowEntry, EscrowTerms, HashLock, ReceiptEntry};
+use crate::{Error, Result};
+
+/// Lowest inclusion fee per operation, in stroops
+pub(crate) const BASE_FEE: u32 = 100;
+
+/// Transactions are only valid for this long after they are built
+pub(crate) const TX_LIFETIME_SECS: u64 = 60;
+
+/// Shortest validity of a bumped invocation, two ledgers, however close its deadline
+const MIN_TX_LIFETIME_SECS: u64 = 10;
+
+/// What the contract holds for an escrow ID, with the ledger that last changed it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowState {
+    Missing,
+    Pending { escrow: EscrowEntry, ledger: u32 },
+    Settled { receipt: ReceiptEntry, ledger: u32 },
+}
+
+/// Client for one deployed Stellar escrow contract, signing as one account
+#[derive(Clone, Debug)]
+pub struct EscrowClient {
+    rpc: SorobanRpc,
+    contract: ScAddress,
+    signer: Signer,
+    network_id: [u8; 32],
+    fees: FeeManager,
+    pub(crate) poll_interval: Duration,
+    pub(crate) hash_lock: HashLock,
+}
+
+impl EscrowClient {
+    /// `contract` is the escrow's strkey address (`C...`) and `network_passphrase` the
+    /// passphrase of the network it is deployed on
+    pub fn new(
+        rpc_url: impl Into<String>,
+        contract: &str,
+        signer: Signer,
+        network_passphrase: &str,
+    ) -> Result<Self> {
+        Ok(Self {
+            rpc: SorobanRpc::new(rpc_url),
+            contract: parse_address(contract)?,
+            signer,
+            network_id: tx::network_id(network_passphrase),
+            fees: FeeManager::default(),
+            poll_interval: Duration::from_secs(5),
+            hash_lock: HashLock::Keccak256,
+        })
+    }
+
+    /// How invocations that expire unincluded are re-priced and sent again; `max_fee` caps
+    /// the inclusion fee per operation, in stroops
+    pub fn with_fee_bump(mut self, policy: BumpPolicy) -> Self {
+        self.fees = FeeManager::new(policy);
+        self
+    }
+
+    /// Hash function of the escrows created through `ChainAdapter` (keccak256 by default, to
+    /// pair with EVM escrows); SHA-256 pairs them with classic, Bitcoin and Lightning legs
+    pub fn with_hash_lock(mut self, hash_lock: HashLock) -> Self {
+        self.hash_lock = hash_lock;
+        self
+    }
+
+    /// Replace the RPC client, e.g. with one that has fallback endpoints
+    pub fn with_rpc(mut self, rpc: SorobanRpc) -> Self {
+        self.rpc = rpc;
+        self
+    }
+
+    /// Inclusion fee bids and recent inclusion latency
+    pub fn fees(&self) -> &FeeManager {
+        &self.fees
+    }
+
+    /// How often pending transactions and watched escrows are polled (defaults to 5 seconds,
+    /// about one ledger)
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn rpc(&self) -> &SorobanRpc {
+        &self.rpc
+    }
+
+    pub fn signer(&self) -> &Signer {
+        &self.signer
+    }
+
+    /// Lock funds from the signer's account, returning the escrow ID and transaction hash
+    /// `terms.sender` must be the signer
+    pub async fn create_escrow(&self, terms: &EscrowTerms) -> Result<([u8; 32], [u8; 32])> {
+        let tx_hash = self
+            .invoke("create_escrow", vec![terms.to_scval()?], None)
+            .await?;
+        Ok((escrow_id(&terms.order_id), tx_hash))
+    }
+
+    /// Release an escrow to the signer, who must be its receiver; bids rise as its timelock
+    /// nears, since it cannot be withdrawn after
+    pub async fn withdraw(&self, escrow_id: &[u8; 32], secret: &[u8; 32]) -> Result<[u8; 32]> {
+        let deadline = match self.escrow_state(escrow_id).await? {
+            EscrowState::Pending { escrow, .. } => Some(escrow.timelock),
+            _ => None,
+        };
+        self.invoke("withdraw", self.withdraw_args(escrow_id, secret)?, deadline)
+            .await
+    }
+
+    /// Refund an expired escrow to the signer, who must be its sender
+    pub async fn cancel(&self, escrow_id: &[u8; 32]) -> Result<[u8; 32]> {
+        self.invoke("cancel", self.cancel_args(escrow_id)?, None)
+            .await
+    }
+
+    /// Simulate `create_escrow` without signing or submitting it
+    pub async fn simulate_create_escrow(&self, terms: &EscrowTerms) -> Result<Simulation> {
+        let (_, simulation) = self
+            .prepare(
+                "create_escrow",
+                vec![terms.to_scval()?],
+                self.inclusion_fee().await?,
+                now() + TX_LIFETIME_SECS,
+            )
+            .await?;
+        Ok(simulation)
+    }
+
+    /// Simulate `withdraw` without signing or submitting it
+    pub async fn simulate_withdraw(
+        &self,
+        escrow_id: &[u8; 32],
+        secret: &[u8; 32],
+    ) -> Result<Simulation> {
+        let (_, simulation) = self
+            .prepare(
+                "withdraw",
+                self.withdraw_args(escrow_id, secret)?,
+                self.inclusion_fee().await?,
+                now() + TX_LIFETIME_SECS,
+            )
+            .await?;
+        Ok(simulation)
+    }
+
+    /// Simulate `cancel` without signing or submitting it
+    pub async fn simulate_cancel(&self, escrow_id: &[u8; 32]) -> Result<Simulation> {
+        let (_, simulation) = self
+            .prepare(
+                "cancel",
+                self.cancel_args(escrow_id)?,
+                self.inclusion_fee().await?,
+                now() + TX_LIFETIME_SECS,
+            )
+            .await?;
+        Ok(simulation)
+    }
+
+    /// Read an escrow's entry, or its receipt once settled, without simulating a call
+    pub async fn escrow_state(&self, escrow_id: &[u8; 32]) -> Result<EscrowState> {
+        let keys = [
+            values::data_key(&self.contract, "Escrow", escrow_id)?,
+            values::data_key(&self.contract, "Receipt", escrow_id)?,
+        ];
+        let mut entries = self.rpc.ledger_entries(&keys).await?.into_iter();
+        if let Some(Some(entry)) = entries.next() {
+            return Ok(EscrowState::Pending {
+                escrow: EscrowEntry::from_entry(&entry.data)?,
+                ledger: entry.last_modified,
+            });
+        }
+        if let Some(Some(entry)) = entries.next() {
+            return Ok(EscrowState::Settled {
+                receipt: ReceiptEntry::from_entry(&entry.data)?,
+                ledger: entry.last_modified,
+            });
+        }
+        Ok(EscrowState::Missing)
+    }
+
+    /// Preimage revealed for `secret_hash` by a withdrawal, the same data the contract's
+    /// `get_revealed_secret` view returns
+    pub async fn revealed_secret(&self, secret_hash: &[u8; 32]) -> Result<Option<[u8; 32]>> {
+        let key = values::data_key(&self.contract, "RevealedSecret", secret_hash)?;
+        let Some(Some(entry)) = self.rpc.ledger_entries(&[key]).await?.into_iter().next() else {
+            return Ok(None);
+        };
+        let secret = values::bytes_value(&entry.data)?;
+        Ok(secret.as_slice().try_into().ok())
+    }
+
+    /// Simulate, assemble, sign and submit a call, waiting until it is in a closed ledger
+    ///
+    /// Each try is valid for the fee manager's patience, never past `deadline` (unix
+    /// seconds). One that expires or fails is rebuilt from a fresh simulation, so its resource
+    /// fee follows the ledger, and sent again with a bumped inclusion fee, up to `max_bumps`
+    /// times; a call the contract now refuses fails in simulation instead.
+    async fn invoke(
+        &self,
+        function: &str,
+        args: Vec<ScVal>,
+        deadline: Option<u64>,
+    ) -> Result<[u8; 32]> {
+        let started = Instant::now();
+        let mut bid = None;
+        let mut last = Error::Rpc(format!("{function} was not sent"));
+        for _ in 0..=self.fees.policy().max_bumps {
+            let now = now();
+            let estimate = u128::from(self.inclusion_fee().await?);
+            let Some(inclusion_fee) = self.fees.price(estimate, bid, deadline, now) else {
+                break;
+            };
+            bid = Some(inclusion_fee);
+            let lifetime = self.fees.patience(deadline, now).as_secs();
+            let max_time = now + lifetime.max(MIN_TX_LIFETIME_SECS);
+            let inclusion_fee = u32::try_from(inclusion_fee).unwrap_or(u32::MAX);
+            let (unsigned, simulation) = self
+                .prepare(function, args.clone(), inclusion_fee, max_time)
+                .await?;
+            let (envelope, hash) = self
+                .signer
+                .sign(tx::assemble(unsigned, simulation)?, &self.network_id)
+                .await?;
+            match self
+                .rpc
+                .submit(&envelope, &hash, max_time, self.poll_interval)
+                .await
+            {
+                Ok(_) => {
+                    self.fees.landed(started.elapsed());
+                    return Ok(hash);
+                }
+                Err(error @ (Error::Expired(_) | Error::Failed(_))) => last = error,
+                Err(error) => return Err(error),
+            }
+        }
+        Err(last)
+    }
+
+    /// Recent inclusion fee per operation, in stroops
+    async fn inclusion_fee(&self) -> Result<u32> {
+        Ok(self.rpc.inclusion_fee().await?.max(BASE_FEE))
+    }
+
+    /// Build a call from the signer's next sequence number and simulate it
+    async fn prepare(
+        &self,
+        function: &str,
+        args: Vec<ScVal>,
+        inclusion_fee: u32,
+        max_time: u64,
+    ) -> Result<(Transaction, Simulation)> {
+        let sequence = sequence(&self.rpc, &self.signer).await? + 1;
+        let unsigned = tx::invocation(
+            &self.signer,
+            sequence,
+            &self.contract,
+            function,
+            args,
+            inclusion_fee,
+            max_time,
+        )?;
+        let simulation = self
+            .rpc
+            .simulate(&TransactionEnvelope::Tx(TransactionV1Envelope {
+                tx: unsigned.clone(),
+                signatures: Default::default(),
+            }))
+            .await?;
+        Ok((unsigned, simulation))
+    }
+
+    fn withdraw_args(&self, escrow_id: &[u8; 32], secret: &[u8; 32]) -> Result<Vec<ScVal>> {
+        Ok(vec![
+            values::bytes(escrow_id)?,
+            values::bytes(secret)?,
+            ScVal::Address(self.signer_address()),
+        ])
+    }
+
+    fn cancel_args(&self, escrow_id: &[u8; 32]) -> Result<Vec<ScVal>> {
+        Ok(vec![
+            values::bytes(escrow_id)?,
+            ScVal::Address(self.signer_address()),
+        ])
+    }
+
+    fn signer_address(&self) -> ScAddress {
+        ScAddress::Account(self.signer.account_id())
+    }
+}
+
+/// Escrow ID the contract derives from an order ID, `keccak256(order_id)`
+pub fn escrow_id(order_id: &str) -> [u8; 32] {
+    Keccak256::digest(order_id.as_bytes()).into()
+}
+
+/// Account (`G...`) or contract (`C...`) strkey
+pub fn parse_address(address: &str) -> Result<ScAddress> {
+    ScAddress::from_str(address).map_err(|_| Error::InvalidAddress(address.to_string()))
+}
+
+/// Current sequence number of `signer`'s account
+pub(crate) async fn sequence(rpc: &SorobanRpc, signer: &Signer) -> Result<i64> {
+    match rpc.account(&signer.account_id()).await? {
+        Some(account) => Ok(account.seq_num.0),
+        None => Err(Error::Rpc(format!(
+            "account {} not found",
+            signer.address()
+        ))),
+    }
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
@@ -0,0 +1,146 @@
+use stellar_xdr::ScVal;
+
+use crate::rpc::ContractEvent;
+use crate::values::{settlement_status, SettlementStatus};
+use crate::{Error, Result};
+
+/// Escrow lifecycle events the contract publishes, keyed by escrow ID in the second topic
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowEvent {
+    Opened {
+        order_id: String,
+        sender: String,
+        receiver: String,
+        token: String,
+        amount: i128,
+        secret_hash: [u8; 32],
+        timelock: u64,
+    },
+    /// A withdrawal of `amount`, bringing the escrow to `filled_amount`
+    Filled {
+        taker: String,
+        amount: i128,
+        filled_amount: i128,
+    },
+    /// Preimage of `secret_hash`, the escrow's hashlock or a partial fill's part hash
+    Secret {
+        secret_hash: [u8; 32],
+        secret: [u8; 32],
+    },
+    Settled {
+        status: SettlementStatus,
+        filled_amount: i128,
+        settled_by: String,
+    },
+}
+
+/// An escrow event with its escrow and where it was published
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowLog {
+    pub escrow_id: [u8; 32],
+    pub event: EscrowEvent,
+    /// `getEvents` ID, unique across the ledger history
+    pub id: String,
+    pub ledger: u32,
+    pub tx_hash: String,
+}
+
+impl EscrowLog {
+    /// Decode an escrow event, or `None` for the contract's other events
+    pub fn decode(event: &ContractEvent) -> Result<Option<Self>> {
+        let (Some(ScVal::Symbol(name)), Some(escrow_id)) =
+            (event.topics.first(), event.topics.get(1))
+        else {
+            return Ok(None);
+        };
+        let fields = match &event.value {
+            ScVal::Vec(Some(fields)) => fields.as_slice(),
+            _ => &[],
+        };
+        let event_data = match name.as_slice() {
+            b"opened" => match fields {
+                [order_id, sender, receiver, token, amount, secret_hash, timelock] => {
+                    EscrowEvent::Opened {
+                        order_id: string(order_id)?,
+                        sender: address(sender)?,
+                        receiver: address(receiver)?,
+                        token: address(token)?,
+                        amount: i128_value(amount)?,
+                        secret_hash: hash(secret_hash)?,
+                        timelock: u64_value(timelock)?,
+                    }
+                }
+                _ => return Err(Error::Decode("opened event")),
+            },
+            b"filled" => match fields {
+                [taker, amount, filled_amount] => EscrowEvent::Filled {
+                    taker: address(taker)?,
+                    amount: i128_value(amount)?,
+                    filled_amount: i128_value(filled_amount)?,
+                },
+                _ => return Err(Error::Decode("filled event")),
+            },
+            b"secret" => match fields {
+                [secret_hash, secret] => EscrowEvent::Secret {
+                    secret_hash: hash(secret_hash)?,
+                    secret: hash(secret)?,
+                },
+                _ => return Err(Error::Decode("secret event")),
+            },
+            b"settled" => match fields {
+                [status, filled_amount, settled_by] => EscrowEvent::Settled {
+                    status: settlement_status(status)?,
+                    filled_amount: i128_value(filled_amount)?,
+                    settled_by: address(settled_by)?,
+                },
+                _ => return Err(Error::Decode("settled event")),
+            },
+            _ => return Ok(None),
+        };
+        Ok(Some(Self {
+            escrow_id: hash(escrow_id)?,
+            event: event_data,
+            id: event.id.clone(),
+            ledger: event.ledger,
+            tx_hash: event.tx_hash.clone(),
+        }))
+    }
+}
+
+fn string(value: &ScVal) -> Result<String> {
+    match value {
+        ScVal::String(value) => Ok(value.to_utf8_string_lossy()),
+        _ => Err(Error::Decode("string")),
+    }
+}
+
+fn address(value: &ScVal) -> Result<String> {
+    match value {
+        ScVal::Address(address) => Ok(address.to_string()),
+        _ => Err(Error::Decode("address")),
+    }
+}
+
+fn hash(value: &ScVal) -> Result<[u8; 32]> {
+    match value {
+        ScVal::Bytes(bytes) => bytes
+            .as_slice()
+            .try_into()
+            .map_err(|_| Error::Decode("hash")),
+        _ => Err(Error::Decode("hash")),
+    }
+}
+
+fn i128_value(value: &ScVal) -> Result<i128> {
+    match value {
+        ScVal::I128(parts) => Ok(i128::from(parts)),
+        _ => Err(Error::Decode("i128")),
+    }
+}
+
+fn u64_value(value: &ScVal) -> Result<u64> {
+    match value {
+        ScVal::U64(value) => Ok(*value),
+        _ => Err(Error::Decode("u64")),
+    }
+}
@@ -0,0 +1,143 @@
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use stellar_xdr::{Limits, ReadXdr, TransactionEnvelope};
+use unite_core::Pool;
+
+use crate::{Error, Result};
+
+/// Horizon REST client for the transaction history Soroban RPC does not index by account or
+/// claimable balance, failing over between endpoints like `SorobanRpc`
+#[derive(Clone, Debug)]
+pub struct Horizon {
+    urls: Pool<String>,
+    http: reqwest::Client,
+}
+
+#[derive(Deserialize)]
+struct Page {
+    #[serde(rename = "_embedded")]
+    embedded: Records,
+}
+
+#[derive(Deserialize)]
+struct Records {
+    records: Vec<TransactionRecord>,
+}
+
+#[derive(Deserialize)]
+struct Root {
+    history_latest_ledger: u32,
+}
+
+#[derive(Deserialize)]
+struct TransactionRecord {
+    successful: bool,
+    envelope_xdr: String,
+}
+
+impl Horizon {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            urls: Pool::single(trimmed(url.into())),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Endpoints of the same network to fail over to
+    pub fn with_fallbacks(mut self, urls: impl IntoIterator<Item = String>) -> Self {
+        self.urls = self.urls.extend(urls.into_iter().map(trimmed));
+        self
+    }
+
+    /// Envelopes of the latest `limit` successful transactions involving `account` (`G...`),
+    /// newest first; merged accounts keep their history
+    pub async fn account_transactions(
+        &self,
+        account: &str,
+        limit: u32,
+    ) -> Result<Vec<TransactionEnvelope>> {
+        self.transactions(&format!("accounts/{account}"), limit)
+            .await
+    }
+
+    /// Envelopes of the latest `limit` successful transactions touching a claimable balance,
+    /// newest first; claimed balances keep their history
+    pub async fn claimable_balance_transactions(
+        &self,
+        balance_id: &[u8; 32],
+        limit: u32,
+    ) -> Result<Vec<TransactionEnvelope>> {
+        // Horizon prefixes the hash with the 4-byte balance ID type (0 for V0)
+        let path = format!("claimable_balances/00000000{}", hex::encode(balance_id));
+        self.transactions(&path, limit).await
+    }
+
+    /// Newest ledger Horizon has ingested
+    pub async fn latest_ledger(&self) -> Result<u32> {
+        let root: Option<Root> = self.get("", &[]).await?;
+        root.map(|root| root.history_latest_ledger)
+            .ok_or_else(|| Error::Horizon("404 at the root".to_string()))
+    }
+
+    async fn transactions(&self, path: &str, limit: u32) -> Result<Vec<TransactionEnvelope>> {
+        let limit = limit.to_string();
+        let query = [("order", "desc"), ("limit", limit.as_str())];
+        let Some(page) = self
+            .get::<Page>(&format!("{path}/transactions"), &query)
+            .await?
+        else {
+            return Ok(Vec::new());
+        };
+        page.embedded
+            .records
+            .iter()
+            .filter(|record| record.successful)
+            .map(|record| {
+                Ok(TransactionEnvelope::from_xdr_base64(
+                    &record.envelope_xdr,
+                    Limits::none(),
+                )?)
+            })
+            .collect()
+    }
+
+    /// GET `path` from the best endpoint, `None` on 404; server errors and rate limits fail
+    /// over to the next
+    async fn get<T: DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+    ) -> Result<Option<T>> {
+        self.urls
+            .call(|url| async move {
+                let response = self
+                    .http
+                    .get(format!("{url}/{path}"))
+                    .query(query)
+                    .send()
+                    .await?;
+                let status = response.status();
+                if status == StatusCode::NOT_FOUND {
+                    return Ok(None);
+                }
+                if !status.is_success() {
+                    let body = response.text().await.unwrap_or_default();
+                    let message = format!("{status}: {body}");
+                    return Err(
+                        if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+                            Error::Unavailable(format!("horizon {message}"))
+                        } else {
+                            Error::Horizon(message)
+                        },
+                    );
+                }
+                Ok(Some(response.json().await?))
+            })
+            .await
+    }
+}
+
+fn trimmed(url: String) -> String {
+    url.trim_end_matches('/').to_string()
+}
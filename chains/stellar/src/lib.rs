@@ -0,0 +1,68 @@
+// Stellar Escrow Client
+// Rust client for the Soroban HTLC escrow (`contracts/stellar`), the Stellar counterpart of
+// `unite-evm`. Calls are simulated over Soroban RPC, assembled with the simulated resources and
+// authorizations, signed with the account's ed25519 key and polled until they land in a closed
+// ledger, failing over between RPC endpoints and sending each transaction to several. Escrow
+// state, receipts and revealed secrets are read straight from ledger entries, and the
+// contract's escrow events can be paged through with `getEvents` for indexing.
+// `EscrowClient` implements the relayer's `ChainAdapter` trait from `unite-core`.
+// Assets whose issuers refuse Soroban fall back to `ClaimableEscrow`, an HTLC built from classic
+// claimable balances and a hash(x)-signed escrow account, behind the same trait; its hashlocks
+// are SHA-256, so it pairs only with legs that lock SHA-256, such as `HashLock::Sha256` escrows.
+
+pub mod adapter;
+pub mod classic;
+pub mod client;
+pub mod events;
+pub mod horizon;
+pub mod rpc;
+pub mod tx;
+pub mod values;
+
+pub use classic::{parse_asset, ClaimableEscrow, ClaimableLock, Settlement};
+pub use client::{escrow_id, EscrowClient, EscrowState};
+pub use events::{EscrowEvent, EscrowLog};
+pub use horizon::Horizon;
+pub use rpc::{ContractEvent, EventPage, EventStart, Simulation, SorobanRpc};
+pub use tx::Signer;
+pub use values::{EscrowEntry, EscrowTerms, HashLock, ReceiptEntry, SettlementStatus};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("soroban rpc: {0}")]
+    Rpc(String),
+    #[error("simulation failed: {0}")]
+    Simulation(String),
+    #[error("transaction {} failed", hex::encode(.0))]
+    Failed([u8; 32]),
+    #[error("transaction {} expired before it was included", hex::encode(.0))]
+    Expired([u8; 32]),
+    #[error("invalid stellar address: {0}")]
+    InvalidAddress(String),
+    #[error("invalid stellar secret key")]
+    InvalidSecretKey,
+    #[error("signing: {0}")]
+    Signing(String),
+    #[error("horizon: {0}")]
+    Horizon(String),
+    #[error("claimable balance escrow: {0}")]
+    Classic(String),
+    #[error("unexpected contract data: {0}")]
+    Decode(&'static str),
+    #[error("endpoint unavailable: {0}")]
+    Unavailable(String),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Xdr(#[from] stellar_xdr::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Failed requests and overloaded endpoints; anything an endpoint answered is the network's
+/// answer, which another endpoint would repeat
+impl unite_core::Fault for Error {
+    fn is_fault(&self) -> bool {
+        matches!(self, Error::Http(_) | Error::Unavailable(_))
+    }
+}
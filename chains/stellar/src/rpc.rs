@@ -0,0 +1,489 @@
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use stellar_xdr::{
+    AccountEntry, AccountId, LedgerEntryData, LedgerKey, LedgerKeyAccount, Limits, ReadXdr, ScVal,
+    SorobanAuthorizationEntry, SorobanTransactionData, TransactionEnvelope, TransactionResult,
+    WriteXdr,
+};
+
+use unite_core::Pool;
+
+use crate::client::now;
+use crate::{Error, Result};
+
+/// Extra wait past a transaction's expiry before treating it as dropped, a couple of ledgers
+const EXPIRY_GRACE_SECS: u64 = 12;
+
+/// Endpoints a transaction is sent to at once by default
+const DEFAULT_HEDGE: usize = 2;
+
+/// JSON-RPC client for the Soroban RPC methods the escrow client uses
+///
+/// Calls go to the best-scoring endpoint and fail over to the next when one cannot be
+/// reached or is overloaded; `sendTransaction` goes to the best `hedge` at once.
+#[derive(Clone, Debug)]
+pub struct SorobanRpc {
+    urls: Pool<String>,
+    http: reqwest::Client,
+    hedge: usize,
+}
+
+/// A ledger entry with the ledger that last changed it
+#[derive(Clone, Debug)]
+pub struct Entry {
+    pub data: LedgerEntryData,
+    pub last_modified: u32,
+}
+
+/// What simulating an invocation says it needs and returns
+#[derive(Clone, Debug)]
+pub struct Simulation {
+    pub transaction_data: SorobanTransactionData,
+    /// Stroops to add to the inclusion fee for the invocation's resources
+    pub min_resource_fee: u32,
+    pub auth: Vec<SorobanAuthorizationEntry>,
+    pub result: Option<ScVal>,
+}
+
+/// A contract event as `getEvents` returns it
+#[derive(Clone, Debug)]
+pub struct ContractEvent {
+    /// Unique and ordered across the ledger history
+    pub id: String,
+    pub ledger: u32,
+    pub tx_hash: String,
+    pub topics: Vec<ScVal>,
+    pub value: ScVal,
+}
+
+/// A page of `getEvents` results with the cursor to continue from
+#[derive(Clone, Debug)]
+pub struct EventPage {
+    pub events: Vec<ContractEvent>,
+    pub cursor: String,
+    pub latest_ledger: u32,
+}
+
+/// Where a `getEvents` page starts
+#[derive(Clone, Debug)]
+pub enum EventStart {
+    Ledger(u32),
+    /// Cursor of a previous page, which continues just after it
+    Cursor(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TxStatus {
+    /// Not in a closed ledger (yet)
+    NotFound,
+    Success {
+        ledger: u32,
+    },
+    Failed {
+        ledger: u32,
+    },
+}
+
+#[derive(Deserialize)]
+struct RpcResponse<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Deserialize)]
+struct RpcError {
+    message: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetLedgerEntries {
+    #[serde(default)]
+    entries: Vec<LedgerEntryResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LedgerEntryResult {
+    key: String,
+    xdr: String,
+    last_modified_ledger_seq: u32,
+}
+
+#[derive(Deserialize)]
+struct GetLatestLedger {
+    sequence: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulateTransaction {
+    error: Option<String>,
+    transaction_data: Option<String>,
+    min_resource_fee: Option<String>,
+    #[serde(default)]
+    results: Vec<SimulateResult>,
+    restore_preamble: Option<Value>,
+}
+
+#[derive(Deserialize)]
+struct SimulateResult {
+    #[serde(default)]
+    auth: Vec<String>,
+    xdr: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SendTransaction {
+    status: String,
+    error_result_xdr: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetTransaction {
+    status: String,
+    ledger: Option<u32>,
+    result_xdr: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetEvents {
+    #[serde(default)]
+    events: Vec<EventResult>,
+    latest_ledger: u32,
+    cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventResult {
+    id: String,
+    ledger: u32,
+    tx_hash: String,
+    #[serde(default = "successful")]
+    in_successful_contract_call: bool,
+    topic: Vec<String>,
+    value: String,
+}
+
+fn successful() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GetFeeStats {
+    soroban_inclusion_fee: FeeDistribution,
+    inclusion_fee: FeeDistribution,
+}
+
+#[derive(Deserialize)]
+struct FeeDistribution {
+    p90: String,
+}
+
+impl FeeDistribution {
+    fn p90(&self) -> Result<u32> {
+        self.p90
+            .parse()
+            .map_err(|_| Error::Rpc(format!("invalid inclusion fee {}", self.p90)))
+    }
+}
+
+impl SorobanRpc {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            urls: Pool::single(url.into()),
+            http: reqwest::Client::new(),
+            hedge: DEFAULT_HEDGE,
+        }
+    }
+
+    /// Endpoints of the same network to fail over to
+    pub fn with_fallbacks(mut self, urls: impl IntoIterator<Item = String>) -> Self {
+        self.urls = self.urls.extend(urls);
+        self
+    }
+
+    /// How many endpoints each transaction is sent to at once (defaults to 2)
+    pub fn with_hedge(mut self, hedge: usize) -> Self {
+        self.hedge = hedge;
+        self
+    }
+
+    pub async fn latest_ledger(&self) -> Result<u32> {
+        let ledger: GetLatestLedger = self.call("getLatestLedger", json!({})).await?;
+        Ok(ledger.sequence)
+    }
+
+    /// Entries for `keys` in the same order, `None` where an entry does not exist
+    pub async fn ledger_entries(&self, keys: &[LedgerKey]) -> Result<Vec<Option<Entry>>> {
+        let keys = keys
+            .iter()
+            .map(|key| key.to_xdr_base64(Limits::none()))
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        let response: GetLedgerEntries = self
+            .call("getLedgerEntries", json!({ "keys": keys }))
+            .await?;
+        keys.iter()
+            .map(|key| {
+                response
+                    .entries
+                    .iter()
+                    .find(|entry| &entry.key == key)
+                    .map(|entry| {
+                        Ok(Entry {
+                            data: LedgerEntryData::from_xdr_base64(&entry.xdr, Limits::none())?,
+                            last_modified: entry.last_modified_ledger_seq,
+                        })
+                    })
+                    .transpose()
+            })
+            .collect()
+    }
+
+    pub async fn account(&self, account_id: &AccountId) -> Result<Option<AccountEntry>> {
+        let key = LedgerKey::Account(LedgerKeyAccount {
+            account_id: account_id.clone(),
+        });
+        match self
+            .ledger_entries(&[key])
+            .await?
+            .into_iter()
+            .next()
+            .flatten()
+        {
+            Some(Entry {
+                data: LedgerEntryData::Account(account),
+                ..
+            }) => Ok(Some(account)),
+            Some(_) => Err(Error::Decode("account entry")),
+            None => Ok(None),
+        }
+    }
+
+    /// An account's lumens in stroops, 0 when the account does not exist
+    pub async fn native_balance(&self, account_id: &AccountId) -> Result<u128> {
+        Ok(self.account(account_id).await?.map_or(0, |account| {
+            u128::try_from(account.balance).unwrap_or_default()
+        }))
+    }
+
+    pub async fn simulate(&self, tx: &TransactionEnvelope) -> Result<Simulation> {
+        let response: SimulateTransaction = self
+            .call(
+                "simulateTransaction",
+                json!({ "transaction": tx.to_xdr_base64(Limits::none())? }),
+            )
+            .await?;
+        if let Some(error) = response.error {
+            return Err(Error::Simulation(error));
+        }
+        if response.restore_preamble.is_some() {
+            return Err(Error::Simulation(
+                "archived ledger entries must be restored first".to_string(),
+            ));
+        }
+        let (Some(transaction_data), Some(min_resource_fee)) =
+            (response.transaction_data, response.min_resource_fee)
+        else {
+            return Err(Error::Rpc("simulation returned no resources".to_string()));
+        };
+        let min_resource_fee = min_resource_fee
+            .parse()
+            .map_err(|_| Error::Rpc(format!("invalid resource fee {min_resource_fee}")))?;
+
+        let (auth, result) = match response.results.into_iter().next() {
+            Some(result) => (
+                result
+                    .auth
+                    .iter()
+                    .map(|entry| SorobanAuthorizationEntry::from_xdr_base64(entry, Limits::none()))
+                    .collect::<std::result::Result<_, _>>()?,
+                Some(ScVal::from_xdr_base64(&result.xdr, Limits::none())?),
+            ),
+            None => (Vec::new(), None),
+        };
+        Ok(Simulation {
+            transaction_data: SorobanTransactionData::from_xdr_base64(
+                &transaction_data,
+                Limits::none(),
+            )?,
+            min_resource_fee,
+            auth,
+            result,
+        })
+    }
+
+    /// Submit a signed transaction to the best `hedge` endpoints at once; it is pending once
+    /// one accepts it
+    pub async fn send(&self, tx: &TransactionEnvelope) -> Result<()> {
+        let params = json!({ "transaction": tx.to_xdr_base64(Limits::none())? });
+        self.urls
+            .hedge(self.hedge, |url| {
+                let params = params.clone();
+                async move {
+                    let response: SendTransaction =
+                        self.call_at(&url, "sendTransaction", params).await?;
+                    match response.status.as_str() {
+                        "PENDING" | "DUPLICATE" => Ok(()),
+                        "TRY_AGAIN_LATER" => Err(Error::Unavailable(format!(
+                            "{url} asked to send the transaction later"
+                        ))),
+                        status => Err(Error::Rpc(format!(
+                            "sendTransaction {status}: {}",
+                            response.error_result_xdr.unwrap_or_default()
+                        ))),
+                    }
+                }
+            })
+            .await
+    }
+
+    /// Submit a signed transaction and poll until it is in a closed ledger, returning the
+    /// ledger; `expires_at` is its max time (unix seconds), after which it is treated as dropped
+    pub async fn submit(
+        &self,
+        tx: &TransactionEnvelope,
+        hash: &[u8; 32],
+        expires_at: u64,
+        poll_interval: Duration,
+    ) -> Result<u32> {
+        self.send(tx).await?;
+        loop {
+            match self.transaction(hash).await? {
+                TxStatus::Success { ledger } => return Ok(ledger),
+                TxStatus::Failed { .. } => return Err(Error::Failed(*hash)),
+                TxStatus::NotFound if now() > expires_at + EXPIRY_GRACE_SECS => {
+                    return Err(Error::Expired(*hash));
+                }
+                TxStatus::NotFound => tokio::time::sleep(poll_interval).await,
+            }
+        }
+    }
+
+    pub async fn transaction(&self, hash: &[u8; 32]) -> Result<TxStatus> {
+        let response: GetTransaction = self
+            .call("getTransaction", json!({ "hash": hex::encode(hash) }))
+            .await?;
+        let ledger = response.ledger.unwrap_or_default();
+        match response.status.as_str() {
+            "SUCCESS" => Ok(TxStatus::Success { ledger }),
+            "FAILED" => Ok(TxStatus::Failed { ledger }),
+            _ => Ok(TxStatus::NotFound),
+        }
+    }
+
+    /// Fee in stroops a transaction was charged, or `None` while it is not in a closed ledger
+    pub async fn fee_charged(&self, hash: &[u8; 32]) -> Result<Option<i64>> {
+        let response: GetTransaction = self
+            .call("getTransaction", json!({ "hash": hex::encode(hash) }))
+            .await?;
+        match (response.status.as_str(), response.result_xdr) {
+            ("SUCCESS" | "FAILED", Some(result)) => Ok(Some(
+                TransactionResult::from_xdr_base64(result, Limits::none())?.fee_charged,
+            )),
+            _ => Ok(None),
+        }
+    }
+
+    /// Events of `contract` from `start`, at most `limit`; calls that failed are left out
+    pub async fn events(&self, contract: &str, start: EventStart, limit: u32) -> Result<EventPage> {
+        let mut params = json!({
+            "filters": [{ "type": "contract", "contractIds": [contract] }],
+            "pagination": { "limit": limit },
+        });
+        match &start {
+            EventStart::Ledger(ledger) => params["startLedger"] = json!(ledger),
+            EventStart::Cursor(cursor) => params["pagination"]["cursor"] = json!(cursor),
+        }
+        let response: GetEvents = self.call("getEvents", params).await?;
+        let events = response
+            .events
+            .into_iter()
+            .filter(|event| event.in_successful_contract_call)
+            .map(|event| {
+                Ok(ContractEvent {
+                    topics: event
+                        .topic
+                        .iter()
+                        .map(|topic| ScVal::from_xdr_base64(topic, Limits::none()))
+                        .collect::<std::result::Result<_, _>>()?,
+                    value: ScVal::from_xdr_base64(&event.value, Limits::none())?,
+                    id: event.id,
+                    ledger: event.ledger,
+                    tx_hash: event.tx_hash,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // Older servers return no cursor; the last event's ID continues just as well
+        let cursor = match (response.cursor, events.last(), start) {
+            (Some(cursor), _, _) => cursor,
+            (None, Some(event), _) => event.id.clone(),
+            (None, None, EventStart::Cursor(cursor)) => cursor,
+            (None, None, EventStart::Ledger(_)) => {
+                return Err(Error::Rpc("getEvents returned no cursor".to_string()))
+            }
+        };
+        Ok(EventPage {
+            events,
+            cursor,
+            latest_ledger: response.latest_ledger,
+        })
+    }
+
+    /// 90th percentile of recent Soroban inclusion fees, in stroops
+    pub async fn inclusion_fee(&self) -> Result<u32> {
+        let stats: GetFeeStats = self.call("getFeeStats", json!({})).await?;
+        stats.soroban_inclusion_fee.p90()
+    }
+
+    /// 90th percentile of recent classic per-operation fees, in stroops
+    pub async fn classic_inclusion_fee(&self) -> Result<u32> {
+        let stats: GetFeeStats = self.call("getFeeStats", json!({})).await?;
+        stats.inclusion_fee.p90()
+    }
+
+    async fn call<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T> {
+        self.urls
+            .call(|url| {
+                let params = params.clone();
+                async move { self.call_at(&url, method, params).await }
+            })
+            .await
+    }
+
+    async fn call_at<T: DeserializeOwned>(
+        &self,
+        url: &str,
+        method: &str,
+        params: Value,
+    ) -> Result<T> {
+        let response: RpcResponse<T> = self
+            .http
+            .post(url)
+            .json(&json!({
+                "jsonrpc": "2.0",
+                "id": 1,
+                "method": method,
+                "params": params,
+            }))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?;
+        if let Some(error) = response.error {
+            return Err(Error::Rpc(error.message));
+        }
+        response
+            .result
+            .ok_or_else(|| Error::Rpc(format!("{method} returned no result")))
+    }
+}
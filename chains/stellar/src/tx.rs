@@ -0,0 +1,195 @@
+use std::fmt;
+use std::sync::Arc;
+
+use ed25519_dalek::{Signer as _, SigningKey};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use stellar_xdr::{
+    AccountId, DecoratedSignature, HostFunction, InvokeContractArgs, InvokeHostFunctionOp, Memo,
+    MuxedAccount, Operation, OperationBody, Preconditions, PublicKey, ScAddress, ScSymbol, ScVal,
+    SequenceNumber, Signature, SignatureHint, SorobanCredentials, TimeBounds, TimePoint,
+    Transaction, TransactionEnvelope, TransactionExt, TransactionV1Envelope, Uint256,
+};
+
+use unite_core::RemoteSigner;
+
+use crate::rpc::Simulation;
+use crate::{Error, Result};
+
+/// Network ID transactions are signed for, e.g. from `Test SDF Network ; September 2015`
+pub fn network_id(passphrase: &str) -> [u8; 32] {
+    Sha256::digest(passphrase.as_bytes()).into()
+}
+
+/// Ed25519 account key that signs transactions as their source account, held in memory or
+/// by a `RemoteSigner` such as a threshold signing group
+#[derive(Clone)]
+pub struct Signer {
+    key: Key,
+}
+
+#[derive(Clone)]
+enum Key {
+    Local(Box<SigningKey>),
+    Remote(Arc<dyn RemoteSigner>),
+}
+
+impl Signer {
+    /// `secret` is the account's strkey seed (`S...`)
+    pub fn from_secret(secret: &str) -> Result<Self> {
+        let seed = stellar_strkey::ed25519::PrivateKey::from_string(secret)
+            .map_err(|_| Error::InvalidSecretKey)?;
+        Ok(Self {
+            key: Key::Local(Box::new(SigningKey::from_bytes(&seed.0))),
+        })
+    }
+
+    /// Fresh random key, for accounts created and then locked down in one transaction
+    pub fn generate() -> Self {
+        Self {
+            key: Key::Local(Box::new(SigningKey::generate(&mut OsRng))),
+        }
+    }
+
+    /// Account whose key never enters this process; every signature is requested from `signer`
+    pub fn remote(signer: Arc<dyn RemoteSigner>) -> Self {
+        Self {
+            key: Key::Remote(signer),
+        }
+    }
+
+    pub fn public_key(&self) -> [u8; 32] {
+        match &self.key {
+            Key::Local(key) => key.verifying_key().to_bytes(),
+            Key::Remote(signer) => signer.public_key(),
+        }
+    }
+
+    pub fn account_id(&self) -> AccountId {
+        AccountId(PublicKey::PublicKeyTypeEd25519(Uint256(self.public_key())))
+    }
+
+    /// Strkey address (`G...`)
+    pub fn address(&self) -> String {
+        stellar_strkey::ed25519::PublicKey(self.public_key())
+            .to_string()
+            .as_str()
+            .to_string()
+    }
+
+    /// Sign `tx` for `network_id`, returning the envelope and transaction hash
+    pub async fn sign(
+        &self,
+        tx: Transaction,
+        network_id: &[u8; 32],
+    ) -> Result<(TransactionEnvelope, [u8; 32])> {
+        let hash = tx.hash(*network_id)?;
+        let envelope = envelope(tx, vec![self.signature(&hash).await?])?;
+        Ok((envelope, hash))
+    }
+
+    /// Signature over a transaction hash, for transactions several accounts sign
+    pub async fn signature(&self, hash: &[u8; 32]) -> Result<DecoratedSignature> {
+        let signature = match &self.key {
+            Key::Local(key) => key.sign(hash).to_bytes(),
+            Key::Remote(signer) => signer
+                .sign(hash)
+                .await
+                .map_err(|error| Error::Signing(error.to_string()))?,
+        };
+        Ok(DecoratedSignature {
+            hint: hint(&self.public_key()),
+            signature: Signature(signature.to_vec().try_into()?),
+        })
+    }
+}
+
+impl fmt::Debug for Signer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Signer")
+            .field("address", &self.address())
+            .finish()
+    }
+}
+
+/// Envelope of `tx` carrying `signatures`
+pub fn envelope(
+    tx: Transaction,
+    signatures: Vec<DecoratedSignature>,
+) -> Result<TransactionEnvelope> {
+    Ok(TransactionEnvelope::Tx(TransactionV1Envelope {
+        tx,
+        signatures: signatures.try_into()?,
+    }))
+}
+
+/// Last four bytes of a signer key, which identify the signer a signature is for
+pub fn hint(key: &[u8; 32]) -> SignatureHint {
+    let mut hint = [0; 4];
+    hint.copy_from_slice(&key[28..]);
+    SignatureHint(hint)
+}
+
+/// Unsigned transaction calling `function` on `contract`, valid until `max_time`
+/// (unix seconds); simulate it and `assemble` the result before signing
+pub fn invocation(
+    source: &Signer,
+    sequence: i64,
+    contract: &ScAddress,
+    function: &str,
+    args: Vec<ScVal>,
+    inclusion_fee: u32,
+    max_time: u64,
+) -> Result<Transaction> {
+    let operation = Operation {
+        source_account: None,
+        body: OperationBody::InvokeHostFunction(InvokeHostFunctionOp {
+            host_function: HostFunction::InvokeContract(InvokeContractArgs {
+                contract_address: contract.clone(),
+                function_name: ScSymbol(function.try_into()?),
+                args: args.try_into()?,
+            }),
+            auth: Default::default(),
+        }),
+    };
+    Ok(Transaction {
+        source_account: MuxedAccount::Ed25519(Uint256(source.public_key())),
+        fee: inclusion_fee,
+        seq_num: SequenceNumber(sequence),
+        cond: Preconditions::Time(TimeBounds {
+            min_time: TimePoint(0),
+            max_time: TimePoint(max_time),
+        }),
+        memo: Memo::None,
+        operations: vec![operation].try_into()?,
+        ext: TransactionExt::V0,
+    })
+}
+
+/// Apply a simulation's resources, resource fee and authorizations to `tx`
+/// Only the source account's own authorization is supported; entries needing another
+/// account's signature are refused rather than submitted to fail on chain
+pub fn assemble(mut tx: Transaction, simulation: Simulation) -> Result<Transaction> {
+    if simulation
+        .auth
+        .iter()
+        .any(|entry| !matches!(entry.credentials, SorobanCredentials::SourceAccount))
+    {
+        return Err(Error::Simulation(
+            "invocation needs authorization from another account".to_string(),
+        ));
+    }
+    tx.fee = tx
+        .fee
+        .checked_add(simulation.min_resource_fee)
+        .ok_or_else(|| Error::Simulation("resource fee overflows".to_string()))?;
+    tx.ext = TransactionExt::V1(simulation.transaction_data);
+    let mut operations = tx.operations.to_vec();
+    for operation in &mut operations {
+        if let OperationBody::InvokeHostFunction(invoke) = &mut operation.body {
+            invoke.auth = simulation.auth.clone().try_into()?;
+        }
+    }
+    tx.operations = operations.try_into()?;
+    Ok(tx)
+}
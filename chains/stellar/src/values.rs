@@ -0,0 +1,227 @@
+use stellar_xdr::{
+    ContractDataDurability, LedgerEntryData, LedgerKey, LedgerKeyContractData, ScAddress, ScBytes,
+    ScMap, ScString, ScSymbol, ScVal, ScVec,
+};
+
+use crate::{Error, Result};
+
+/// Hash function an escrow's hashlock is taken with, the contract's `LockType` of a hashlock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashLock {
+    /// keccak256, as EVM escrows check it
+    Keccak256,
+    /// SHA-256, for pairing with legs that only lock SHA-256: Lightning hold invoices,
+    /// Bitcoin scripts and classic hash(x) signers
+    Sha256,
+}
+
+/// Terms of a plain hashlocked escrow, the subset of the contract's `EscrowParams` the client
+/// sets; every other option is left off
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowTerms {
+    pub sender: ScAddress,
+    pub receiver: ScAddress,
+    /// `None` for native XLM
+    pub token: Option<ScAddress>,
+    pub amount: i128,
+    /// Hash of the secret under `hash_lock`
+    pub secret_hash: [u8; 32],
+    pub hash_lock: HashLock,
+    /// Unix seconds
+    pub timelock: u64,
+    pub order_id: String,
+}
+
+/// A live escrow as stored under `DataKey::Escrow`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EscrowEntry {
+    pub amount: i128,
+    pub secret_hash: [u8; 32],
+    pub timelock: u64,
+}
+
+/// How a settled escrow ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettlementStatus {
+    Withdrawn,
+    Cancelled,
+    /// Settled by an arbiter through `resolve_dispute`
+    Resolved,
+}
+
+/// Receipt left under `DataKey::Receipt` once an escrow settles
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceiptEntry {
+    pub status: SettlementStatus,
+    pub amount: i128,
+    pub secret_hash: [u8; 32],
+}
+
+impl EscrowTerms {
+    /// The contract's `EscrowParams`, a map keyed by field name
+    pub fn to_scval(&self) -> Result<ScVal> {
+        let none = || variant("None");
+        let empty = || ScVal::Vec(Some(ScVec(Default::default())));
+        let token = match &self.token {
+            Some(token) => ScVal::Address(token.clone()),
+            None => ScVal::Void,
+        };
+        let fields = [
+            ("sender", ScVal::Address(self.sender.clone())),
+            ("receiver", ScVal::Address(self.receiver.clone())),
+            ("amount", self.amount.into()),
+            ("secret_hash", bytes(&self.secret_hash)?),
+            ("timelock", self.timelock.into()),
+            ("exclusive_until", 0u64.into()),
+            ("token_address", token),
+            (
+                "order_id",
+                ScVal::String(ScString(self.order_id.as_str().try_into()?)),
+            ),
+            ("metadata", bytes(&[])?),
+            ("parts", 0u32.into()),
+            ("resolvers", empty()),
+            ("taker_amount", 0i128.into()),
+            ("auction", none()?),
+            ("integrator", ScVal::Void),
+            ("integrator_share_bps", 0u32.into()),
+            ("expected_amount", 0i128.into()),
+            ("receiver_hook", false.into()),
+            ("cancel_penalty_bps", 0u32.into()),
+            ("basket", empty()),
+            ("stream_duration", 0u64.into()),
+            ("payees", empty()),
+            ("swap_on_withdraw", none()?),
+            ("timelock_kind", variant("Timestamp")?),
+            ("price_condition", none()?),
+            ("arbiter", ScVal::Void),
+            ("dispute_window", 0u64.into()),
+            ("receiver_auth", variant("Single")?),
+            (
+                "lock_type",
+                variant(match self.hash_lock {
+                    HashLock::Keccak256 => "Hashlock",
+                    HashLock::Sha256 => "Sha256",
+                })?,
+            ),
+            ("reveal_delay", 0u64.into()),
+        ];
+        let pairs = fields
+            .into_iter()
+            .map(|(name, value)| Ok((symbol(name)?, value)))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(ScVal::Map(Some(ScMap::sorted_from_pairs(
+            pairs.into_iter(),
+        )?)))
+    }
+}
+
+impl EscrowEntry {
+    pub fn from_entry(data: &LedgerEntryData) -> Result<Self> {
+        let fields = contract_map(data)?;
+        Ok(Self {
+            amount: i128_field(fields, "amount")?,
+            secret_hash: hash_field(fields, "secret_hash")?,
+            timelock: match field(fields, "timelock")? {
+                ScVal::U64(timelock) => *timelock,
+                _ => return Err(Error::Decode("timelock")),
+            },
+        })
+    }
+}
+
+impl ReceiptEntry {
+    pub fn from_entry(data: &LedgerEntryData) -> Result<Self> {
+        let fields = contract_map(data)?;
+        Ok(Self {
+            status: settlement_status(field(fields, "status")?)?,
+            amount: i128_field(fields, "amount")?,
+            secret_hash: hash_field(fields, "secret_hash")?,
+        })
+    }
+}
+
+/// A `SettlementStatus` variant
+pub fn settlement_status(value: &ScVal) -> Result<SettlementStatus> {
+    match variant_name(value) {
+        Some("Withdrawn") => Ok(SettlementStatus::Withdrawn),
+        Some("Cancelled") => Ok(SettlementStatus::Cancelled),
+        Some("Resolved") => Ok(SettlementStatus::Resolved),
+        _ => Err(Error::Decode("status")),
+    }
+}
+
+/// Ledger key of a persistent `DataKey::<name>(bytes)` entry
+pub fn data_key(contract: &ScAddress, name: &str, key: &[u8]) -> Result<LedgerKey> {
+    let key = ScVal::Vec(Some(ScVec(vec![symbol(name)?, bytes(key)?].try_into()?)));
+    Ok(LedgerKey::ContractData(LedgerKeyContractData {
+        contract: contract.clone(),
+        key,
+        durability: ContractDataDurability::Persistent,
+    }))
+}
+
+/// Value of a contract data entry holding raw bytes
+pub fn bytes_value(data: &LedgerEntryData) -> Result<Vec<u8>> {
+    match data {
+        LedgerEntryData::ContractData(data) => match &data.val {
+            ScVal::Bytes(bytes) => Ok(bytes.to_vec()),
+            _ => Err(Error::Decode("bytes value")),
+        },
+        _ => Err(Error::Decode("contract data entry")),
+    }
+}
+
+pub fn bytes(value: &[u8]) -> Result<ScVal> {
+    Ok(ScVal::Bytes(ScBytes(value.to_vec().try_into()?)))
+}
+
+fn symbol(name: &str) -> Result<ScVal> {
+    Ok(ScVal::Symbol(ScSymbol(name.try_into()?)))
+}
+
+/// A unit variant of a `#[contracttype]` enum
+fn variant(name: &str) -> Result<ScVal> {
+    Ok(ScVal::Vec(Some(ScVec(vec![symbol(name)?].try_into()?))))
+}
+
+fn variant_name(value: &ScVal) -> Option<&str> {
+    match value {
+        ScVal::Vec(Some(items)) => match items.first() {
+            Some(ScVal::Symbol(name)) => std::str::from_utf8(name.as_slice()).ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn contract_map(data: &LedgerEntryData) -> Result<&ScMap> {
+    match data {
+        LedgerEntryData::ContractData(data) => match &data.val {
+            ScVal::Map(Some(map)) => Ok(map),
+            _ => Err(Error::Decode("struct value")),
+        },
+        _ => Err(Error::Decode("contract data entry")),
+    }
+}
+
+fn field<'a>(map: &'a ScMap, name: &'static str) -> Result<&'a ScVal> {
+    map.iter()
+        .find(|entry| matches!(&entry.key, ScVal::Symbol(key) if key.as_slice() == name.as_bytes()))
+        .map(|entry| &entry.val)
+        .ok_or(Error::Decode(name))
+}
+
+fn i128_field(map: &ScMap, name: &'static str) -> Result<i128> {
+    match field(map, name)? {
+        ScVal::I128(parts) => Ok(i128::from(parts)),
+        _ => Err(Error::Decode(name)),
+    }
+}
+
+fn hash_field(map: &ScMap, name: &'static str) -> Result<[u8; 32]> {
+    match field(map, name)? {
+        ScVal::Bytes(bytes) => bytes.as_slice().try_into().map_err(|_| Error::Decode(name)),
+        _ => Err(Error::Decode(name)),
+    }
+}
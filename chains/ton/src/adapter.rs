@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt};
+use tonlib_core::TonAddress;
+use unite_core::{
+    ChainAdapter, ChainEvent, EscrowEvent, EscrowId, EscrowParams, EscrowStatus, EventStream,
+    FeeEstimate, Secret, TxId,
+};
+
+use crate::client::{EscrowClient, EscrowState, Settlement};
+use crate::contract::HtlcTerms;
+use crate::{Error, Result};
+
+impl From<Error> for unite_core::Error {
+    fn from(error: Error) -> Self {
+        unite_core::Error::chain("ton", error)
+    }
+}
+
+/// Polls one escrow's state and turns its changes into events
+struct Watch {
+    client: EscrowClient,
+    escrow_id: EscrowId,
+    created: bool,
+    settled: bool,
+}
+
+impl Watch {
+    async fn next_event(&mut self) -> Result<Option<ChainEvent>> {
+        let (event, lt, tx) = match self.client.escrow_state(&self.escrow_id).await? {
+            EscrowState::Missing => return Ok(None),
+            EscrowState::Pending { .. } if self.created => return Ok(None),
+            EscrowState::Pending { terms, balance, lt } => {
+                let event = EscrowEvent::Created {
+                    amount: balance,
+                    hashlock: terms.hashlock,
+                    timelock: terms.timelock,
+                };
+                self.created = true;
+                (event, lt, None)
+            }
+            EscrowState::Settled {
+                settlement,
+                lt,
+                tx_hash,
+            } => {
+                let event = match settlement {
+                    Settlement::Claimed(secret) => EscrowEvent::Claimed {
+                        secret: Some(secret.to_vec()),
+                    },
+                    Settlement::Refunded => EscrowEvent::Refunded,
+                };
+                self.settled = true;
+                (event, lt, Some(hex::encode(tx_hash)))
+            }
+        };
+        Ok(Some(ChainEvent {
+            escrow_id: self.escrow_id,
+            event,
+            height: lt,
+            tx,
+            removed: false,
+        }))
+    }
+}
+
+/// Escrow IDs are escrow contract address hashes. Events come from polling the escrow
+/// account, so an escrow first seen after it settled reports only its settlement; heights are
+/// the logical times of the escrow's transactions, and `Created` reports the balance, which
+/// includes what is left of the deploy reserve. Blocks are final once the masterchain
+/// references them, so events never get `removed`.
+#[async_trait]
+impl ChainAdapter for EscrowClient {
+    fn chain(&self) -> &str {
+        "ton"
+    }
+
+    async fn create_escrow(&self, params: &EscrowParams) -> unite_core::Result<(EscrowId, TxId)> {
+        if let Some(token) = &params.token {
+            return Err(unite_core::Error::InvalidParam {
+                field: "token",
+                value: token.clone(),
+            });
+        }
+        let receiver: TonAddress =
+            params
+                .receiver
+                .parse()
+                .map_err(|_| unite_core::Error::InvalidParam {
+                    field: "receiver",
+                    value: params.receiver.clone(),
+                })?;
+        let terms = HtlcTerms::new(
+            self.address().clone(),
+            receiver,
+            params.hashlock,
+            params.timelock,
+            &params.order_id,
+        );
+        let (escrow_id, tx_hash) = EscrowClient::create_escrow(self, &terms, params.amount).await?;
+        Ok((escrow_id, hex::encode(tx_hash)))
+    }
+
+    /// Escrow state is read directly, so `from_height` is not needed to catch up
+    async fn watch_events(
+        &self,
+        escrow_id: EscrowId,
+        _from_height: u64,
+    ) -> unite_core::Result<EventStream> {
+        let watch = Watch {
+            client: self.clone(),
+            escrow_id,
+            created: false,
+            settled: false,
+        };
+        let events = stream::unfold(watch, |mut watch| async move {
+            if watch.settled {
+                return None;
+            }
+            loop {
+                match watch.next_event().await {
+                    Ok(Some(event)) => return Some((Ok(event), watch)),
+                    Ok(None) => tokio::time::sleep(watch.client.poll_interval).await,
+                    Err(error) => {
+                        tokio::time::sleep(watch.client.poll_interval).await;
+                        return Some((Err(error.into()), watch));
+                    }
+                }
+            }
+        });
+        Ok(events.boxed())
+    }
+
+    async fn reveal_secret(&self, escrow_id: &EscrowId) -> unite_core::Result<Option<Secret>> {
+        match self.escrow_state(escrow_id).await? {
+            EscrowState::Settled {
+                settlement: Settlement::Claimed(secret),
+                ..
+            } => Ok(Some(secret.to_vec())),
+            _ => Ok(None),
+        }
+    }
+
+    async fn escrow_status(&self, escrow_id: &EscrowId) -> unite_core::Result<EscrowStatus> {
+        Ok(match self.escrow_state(escrow_id).await? {
+            EscrowState::Missing => EscrowStatus::Missing,
+            EscrowState::Pending { .. } => EscrowStatus::Open,
+            EscrowState::Settled {
+                settlement: Settlement::Claimed(_),
+                ..
+            } => EscrowStatus::Claimed,
+            EscrowState::Settled {
+                settlement: Settlement::Refunded,
+                ..
+            } => EscrowStatus::Refunded,
+        })
+    }
+
+    async fn claim(&self, escrow_id: &EscrowId, secret: &[u8]) -> unite_core::Result<TxId> {
+        let secret: &[u8; 32] = secret.try_into().map_err(|_| {
+            unite_core::Error::InvalidSecret("the TON escrow takes a 32-byte secret".to_string())
+        })?;
+        Ok(hex::encode(
+            EscrowClient::claim(self, escrow_id, secret).await?,
+        ))
+    }
+
+    async fn refund(&self, escrow_id: &EscrowId) -> unite_core::Result<TxId> {
+        Ok(hex::encode(EscrowClient::refund(self, escrow_id).await?))
+    }
+
+    fn finality_depth(&self) -> u64 {
+        1
+    }
+
+    /// Masterchain seqno; unlike event heights, which are logical times
+    async fn head(&self) -> unite_core::Result<u64> {
+        Ok(u64::from(self.api().masterchain_seqno().await?))
+    }
+
+    /// TON has no fee market: the basechain gas price is a network parameter
+    async fn fee_estimate(&self) -> unite_core::Result<FeeEstimate> {
+        Ok(FeeEstimate {
+            max_fee: u128::from(self.api().gas_price().await?),
+            priority_fee: 0,
+        })
+    }
+}
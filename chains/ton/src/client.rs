@@ -0,0 +1,255 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use num_bigint::BigUint;
+use tonlib_core::cell::{ArcCell, BagOfCells, Cell};
+use tonlib_core::message::{CommonMsgInfo, InternalMessage, TonMessage, TransferMessage};
+use tonlib_core::wallet::mnemonic::Mnemonic;
+use tonlib_core::wallet::ton_wallet::TonWallet;
+use tonlib_core::wallet::wallet_version::WalletVersion;
+use tonlib_core::TonAddress;
+
+use crate::contract::{escrow_address, Call, HtlcTerms};
+use crate::toncenter::{Toncenter, Transaction};
+use crate::{Error, Result};
+
+/// Wallet messages are only valid for this long after they are signed
+pub(crate) const MESSAGE_LIFETIME_SECS: u64 = 60;
+
+/// Extra wait for the wallet's internal message to reach the escrow, a few blocks
+const DELIVERY_GRACE_SECS: u64 = 30;
+
+/// Nanotons sent with a deploy on top of the escrowed amount, for the deploy and payout fees
+pub const DEPLOY_RESERVE: u128 = 50_000_000;
+
+/// Nanotons attached to claim and refund calls for gas; the payout forwards what is left
+pub const CALL_VALUE: u128 = 50_000_000;
+
+/// Transactions read per history lookup; an escrow only sees its deploy, its settlement and
+/// whatever else is sent to it
+const HISTORY_LIMIT: u32 = 32;
+
+/// How an escrow was settled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Settlement {
+    Claimed([u8; 32]),
+    Refunded,
+}
+
+/// An escrow contract's state, with the logical time of the transaction that last changed it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EscrowState {
+    Missing,
+    Pending {
+        terms: HtlcTerms,
+        /// Nanotons the escrow will pay out, the amount and what is left of the deploy reserve
+        balance: u128,
+        lt: u64,
+    },
+    Settled {
+        settlement: Settlement,
+        lt: u64,
+        tx_hash: [u8; 32],
+    },
+}
+
+/// Deploys, claims and refunds HTLC escrow contracts through one wallet
+#[derive(Clone, Debug)]
+pub struct EscrowClient {
+    api: Toncenter,
+    wallet: TonWallet,
+    code: ArcCell,
+    pub(crate) poll_interval: Duration,
+}
+
+impl EscrowClient {
+    /// `code` is the compiled escrow contract, the BoC built from `contracts/ton/htlc.fc`
+    pub fn new(api: Toncenter, wallet: TonWallet, code: &[u8]) -> Result<Self> {
+        Ok(Self {
+            api,
+            wallet,
+            code: BagOfCells::parse(code)?.single_root()?,
+            poll_interval: Duration::from_secs(5),
+        })
+    }
+
+    /// How often pending messages and watched escrows are polled (defaults to 5 seconds, about
+    /// one masterchain block)
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    pub fn api(&self) -> &Toncenter {
+        &self.api
+    }
+
+    pub fn address(&self) -> &TonAddress {
+        &self.wallet.address
+    }
+
+    /// Deploy an escrow funded with `amount` nanotons, returning the escrow ID and the hash of
+    /// the escrow's deploy transaction; `terms.sender` receives refunds
+    pub async fn create_escrow(
+        &self,
+        terms: &HtlcTerms,
+        amount: u128,
+    ) -> Result<([u8; 32], [u8; 32])> {
+        let address = terms.address(&self.code)?;
+        let state_init = terms.state_init(&self.code)?;
+        let tx = self
+            .send(
+                &address,
+                amount + DEPLOY_RESERVE,
+                Cell::default(),
+                Some(state_init),
+            )
+            .await?;
+        if !self.api.account(&address).await?.active {
+            return Err(Error::Failed(tx.hash));
+        }
+        Ok((address.hash_part.into(), tx.hash))
+    }
+
+    /// Release an escrow to its receiver; anyone holding the secret can
+    pub async fn claim(&self, escrow_id: &[u8; 32], secret: &[u8; 32]) -> Result<[u8; 32]> {
+        self.call(escrow_id, Call::Claim { secret: *secret }).await
+    }
+
+    /// Return an expired escrow to its sender; anyone can
+    pub async fn refund(&self, escrow_id: &[u8; 32]) -> Result<[u8; 32]> {
+        self.call(escrow_id, Call::Refund).await
+    }
+
+    /// Read a live escrow's data, or find the settlement in a destroyed escrow's history
+    pub async fn escrow_state(&self, escrow_id: &[u8; 32]) -> Result<EscrowState> {
+        let address = escrow_address(escrow_id);
+        let account = self.api.account(&address).await?;
+        if let (true, Some(data)) = (account.active, &account.data) {
+            return Ok(EscrowState::Pending {
+                terms: HtlcTerms::from_data(data)?,
+                balance: account.balance,
+                lt: account.last_lt,
+            });
+        }
+        let history = self.api.transactions(&address, HISTORY_LIMIT).await?;
+        let settled = history.iter().find_map(|tx| {
+            let call = Call::decode(tx.in_msg.as_ref()?.body.as_ref()?)?;
+            let settlement = match call {
+                Call::Claim { secret } => Settlement::Claimed(secret),
+                Call::Refund => Settlement::Refunded,
+            };
+            is_payout(tx).then_some(EscrowState::Settled {
+                settlement,
+                lt: tx.lt,
+                tx_hash: tx.hash,
+            })
+        });
+        Ok(settled.unwrap_or(EscrowState::Missing))
+    }
+
+    async fn call(&self, escrow_id: &[u8; 32], call: Call) -> Result<[u8; 32]> {
+        let tx = self
+            .send(&escrow_address(escrow_id), CALL_VALUE, call.body()?, None)
+            .await?;
+        if !is_payout(&tx) {
+            return Err(Error::Failed(tx.hash));
+        }
+        Ok(tx.hash)
+    }
+
+    /// Send `value` to `destination` through the wallet and wait for the destination's
+    /// transaction processing it
+    async fn send(
+        &self,
+        destination: &TonAddress,
+        value: u128,
+        body: Cell,
+        state_init: Option<Cell>,
+    ) -> Result<Transaction> {
+        let (seqno, deployed) = self.api.wallet_seqno(&self.wallet.address).await?;
+        // Deploys are not bounceable, so a failed one leaves the funds at the escrow address
+        // rather than bouncing them off an account that does not exist
+        let mut message = TransferMessage::new(
+            CommonMsgInfo::InternalMessage(InternalMessage {
+                ihr_disabled: true,
+                bounce: state_init.is_none(),
+                bounced: false,
+                src: TonAddress::NULL,
+                dest: destination.clone(),
+                value: BigUint::from(value),
+                ihr_fee: BigUint::ZERO,
+                fwd_fee: BigUint::ZERO,
+                created_lt: 0,
+                created_at: 0,
+            }),
+            body.to_arc(),
+        );
+        if let Some(state_init) = state_init {
+            message.with_state_init(state_init);
+        }
+        let valid_until = now() + MESSAGE_LIFETIME_SECS;
+        let external = self.wallet.create_external_msg(
+            valid_until as u32,
+            seqno,
+            !deployed,
+            [message.build()?.to_arc()],
+        )?;
+        let external_hash: [u8; 32] = external.cell_hash().into();
+        self.api.send(external).await?;
+
+        let wallet_tx = self
+            .wait_for(&self.wallet.address, &external_hash, valid_until)
+            .await?
+            .ok_or(Error::Expired(external_hash))?;
+        // The wallet ignores failed actions, so an underfunded send only shows as no message
+        let internal = wallet_tx
+            .out_msgs
+            .first()
+            .ok_or(Error::Failed(wallet_tx.hash))?;
+        self.wait_for(destination, &internal.hash, now() + DELIVERY_GRACE_SECS)
+            .await?
+            .ok_or(Error::Undelivered(internal.hash))
+    }
+
+    /// Poll `address`'s history until a transaction processes message `hash`, up to `deadline`
+    async fn wait_for(
+        &self,
+        address: &TonAddress,
+        hash: &[u8; 32],
+        deadline: u64,
+    ) -> Result<Option<Transaction>> {
+        loop {
+            let history = self.api.transactions(address, HISTORY_LIMIT).await?;
+            let found = history
+                .into_iter()
+                .find(|tx| tx.in_msg.as_ref().is_some_and(|msg| msg.hash == *hash));
+            if found.is_some() || now() > deadline {
+                return Ok(found);
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+/// V4R2 wallet of a 24-word mnemonic, the default wallet of most TON apps
+pub fn wallet(mnemonic: &str) -> Result<TonWallet> {
+    let key_pair = Mnemonic::from_str(mnemonic, &None)?.to_key_pair()?;
+    Ok(TonWallet::new(WalletVersion::V4R2, key_pair)?)
+}
+
+/// Whether the escrow paid out in `tx`: payouts carry an empty body, while a failed call
+/// only sends back a bounce
+fn is_payout(tx: &Transaction) -> bool {
+    tx.out_msgs.iter().any(|msg| {
+        msg.body
+            .as_ref()
+            .is_none_or(|body| body.bit_len() == 0 && body.references().is_empty())
+    })
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
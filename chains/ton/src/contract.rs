@@ -0,0 +1,133 @@
+use sha3::{Digest, Keccak256};
+use tonlib_core::cell::{ArcCell, Cell, CellBuilder};
+use tonlib_core::tlb_types::block::state_init::StateInit;
+use tonlib_core::tlb_types::tlb::TLB;
+use tonlib_core::TonAddress;
+
+use crate::{Error, Result};
+
+pub const OP_CLAIM: u32 = 2;
+pub const OP_REFUND: u32 = 3;
+
+/// Escrows are deployed to the basechain
+pub const WORKCHAIN: i32 = 0;
+
+/// Initial data of an escrow contract (`contracts/ton/htlc.fc`), which fixes its address
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HtlcTerms {
+    pub sender: TonAddress,
+    pub receiver: TonAddress,
+    /// `sha256(secret)` of a 32-byte secret
+    pub hashlock: [u8; 32],
+    /// Unix seconds from which the escrow can be refunded and no longer claimed
+    pub timelock: u64,
+    /// `keccak256` of the order ID, which keeps escrows with the same terms apart
+    pub order_id: [u8; 32],
+}
+
+impl HtlcTerms {
+    pub fn new(
+        sender: TonAddress,
+        receiver: TonAddress,
+        hashlock: [u8; 32],
+        timelock: u64,
+        order_id: &str,
+    ) -> Self {
+        Self {
+            sender,
+            receiver,
+            hashlock,
+            timelock,
+            order_id: Keccak256::digest(order_id.as_bytes()).into(),
+        }
+    }
+
+    pub fn data(&self) -> Result<Cell> {
+        let ids = CellBuilder::new()
+            .store_slice(&self.hashlock)?
+            .store_slice(&self.order_id)?
+            .build()?;
+        Ok(CellBuilder::new()
+            .store_address(&self.sender)?
+            .store_address(&self.receiver)?
+            .store_u64(64, self.timelock)?
+            .store_child(ids)?
+            .build()?)
+    }
+
+    pub fn from_data(data: &Cell) -> Result<Self> {
+        let decode = |_| Error::Decode("escrow data");
+        let mut parser = data.parser();
+        let sender = parser.load_address().map_err(decode)?;
+        let receiver = parser.load_address().map_err(decode)?;
+        let timelock = parser.load_u64(64).map_err(decode)?;
+        let ids = parser.next_reference().map_err(decode)?;
+        let mut ids = ids.parser();
+        let mut hashlock = [0; 32];
+        let mut order_id = [0; 32];
+        ids.load_slice(&mut hashlock).map_err(decode)?;
+        ids.load_slice(&mut order_id).map_err(decode)?;
+        Ok(Self {
+            sender,
+            receiver,
+            hashlock,
+            timelock,
+            order_id,
+        })
+    }
+
+    pub fn state_init(&self, code: &ArcCell) -> Result<Cell> {
+        Ok(StateInit::new(code.clone(), self.data()?.to_arc()).to_cell()?)
+    }
+
+    /// Address the escrow is deployed to; its hash is the escrow ID
+    pub fn address(&self, code: &ArcCell) -> Result<TonAddress> {
+        Ok(TonAddress::derive(
+            WORKCHAIN,
+            code.clone(),
+            self.data()?.to_arc(),
+        )?)
+    }
+}
+
+/// Escrow address for an escrow ID
+pub fn escrow_address(escrow_id: &[u8; 32]) -> TonAddress {
+    TonAddress::new(WORKCHAIN, (*escrow_id).into())
+}
+
+/// A call decoded from an inbound message body
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Call {
+    Claim { secret: [u8; 32] },
+    Refund,
+}
+
+impl Call {
+    pub fn body(&self) -> Result<Cell> {
+        let mut builder = CellBuilder::new();
+        match self {
+            Call::Claim { secret } => builder
+                .store_u32(32, OP_CLAIM)?
+                .store_u64(64, 0)?
+                .store_slice(secret)?,
+            Call::Refund => builder.store_u32(32, OP_REFUND)?.store_u64(64, 0)?,
+        };
+        Ok(builder.build()?)
+    }
+
+    /// `None` for deploys, top-ups and bodies the contract rejects as unknown
+    pub fn decode(body: &Cell) -> Option<Self> {
+        let mut parser = body.parser();
+        let op = parser.load_u32(32).ok()?;
+        parser.skip_bits(64).ok()?;
+        match op {
+            OP_CLAIM => {
+                let mut secret = [0; 32];
+                parser.load_slice(&mut secret).ok()?;
+                Some(Call::Claim { secret })
+            }
+            OP_REFUND => Some(Call::Refund),
+            _ => None,
+        }
+    }
+}
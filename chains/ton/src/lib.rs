@@ -0,0 +1,43 @@
+// TON Escrow Client
+// Rust client for the TON HTLC contract (`contracts/ton/htlc.fc`). Each escrow is its own
+// contract, deployed and funded in one internal message from a V4R2 wallet, so its address,
+// and with it the escrow ID, follows from the escrow's terms. Messages go out through the
+// toncenter v2 API and are followed from the wallet's transaction to the escrow's. Escrow
+// state is read from the account while it lives and from its history once it has paid out
+// and destroyed itself, which is also where a claim's secret is found.
+// `EscrowClient` implements the relayer's `ChainAdapter` trait from `unite-core`.
+
+pub mod adapter;
+pub mod client;
+pub mod contract;
+pub mod toncenter;
+
+pub use client::{wallet, EscrowClient, EscrowState, Settlement};
+pub use contract::{escrow_address, Call, HtlcTerms};
+pub use toncenter::Toncenter;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("toncenter: {0}")]
+    Api(String),
+    #[error("transaction {} failed", hex::encode(.0))]
+    Failed([u8; 32]),
+    #[error("message {} expired before it was included", hex::encode(.0))]
+    Expired([u8; 32]),
+    #[error("message {} was not delivered", hex::encode(.0))]
+    Undelivered([u8; 32]),
+    #[error("invalid TON address: {0}")]
+    InvalidAddress(String),
+    #[error("unexpected contract data: {0}")]
+    Decode(&'static str),
+    #[error(transparent)]
+    Cell(#[from] tonlib_core::cell::TonCellError),
+    #[error(transparent)]
+    Message(#[from] tonlib_core::message::TonMessageError),
+    #[error(transparent)]
+    Mnemonic(#[from] tonlib_core::wallet::error::MnemonicError),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
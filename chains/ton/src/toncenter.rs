@@ -0,0 +1,352 @@
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer};
+use serde_json::json;
+use tonlib_core::cell::{ArcCell, BagOfCells, Cell};
+use tonlib_core::TonAddress;
+
+use crate::{Error, Result};
+
+/// Basechain gas prices, config parameter 21
+const GAS_PRICES_PARAM: u32 = 21;
+
+/// Client for the toncenter HTTP API v2 (`https://toncenter.com/api/v2`), or any server
+/// exposing the same methods
+#[derive(Clone, Debug)]
+pub struct Toncenter {
+    url: String,
+    api_key: Option<String>,
+    http: reqwest::Client,
+}
+
+/// An account as of its last transaction
+#[derive(Clone, Debug)]
+pub struct AccountState {
+    pub active: bool,
+    /// Nanotons
+    pub balance: u128,
+    /// Contract data, present while the account is active
+    pub data: Option<ArcCell>,
+    /// Logical time of the account's last transaction, 0 if it never had one
+    pub last_lt: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Transaction {
+    pub lt: u64,
+    pub hash: [u8; 32],
+    pub utime: u64,
+    /// `None` for tick-tock and other transactions without an inbound message
+    pub in_msg: Option<Message>,
+    pub out_msgs: Vec<Message>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Message {
+    pub hash: [u8; 32],
+    /// `None` for external messages
+    pub source: Option<TonAddress>,
+    pub destination: Option<TonAddress>,
+    /// Nanotons
+    pub value: u128,
+    /// `None` when toncenter decoded the body as a text comment
+    pub body: Option<ArcCell>,
+    pub init_state: Option<ArcCell>,
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    ok: bool,
+    result: Option<T>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RawAddressInformation {
+    #[serde(deserialize_with = "number")]
+    balance: u128,
+    #[serde(default)]
+    data: String,
+    state: String,
+    last_transaction_id: RawTransactionId,
+}
+
+#[derive(Deserialize)]
+struct RawMasterchainInfo {
+    last: RawBlockId,
+}
+
+#[derive(Deserialize)]
+struct RawBlockId {
+    seqno: u32,
+}
+
+#[derive(Deserialize)]
+struct RawWalletInformation {
+    account_state: String,
+    seqno: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct RawTransactionId {
+    #[serde(deserialize_with = "number")]
+    lt: u128,
+    hash: String,
+}
+
+#[derive(Deserialize)]
+struct RawTransaction {
+    transaction_id: RawTransactionId,
+    utime: u64,
+    in_msg: Option<RawMessage>,
+    #[serde(default)]
+    out_msgs: Vec<RawMessage>,
+}
+
+#[derive(Deserialize)]
+struct RawMessage {
+    hash: String,
+    #[serde(default)]
+    source: String,
+    #[serde(default)]
+    destination: String,
+    #[serde(deserialize_with = "number")]
+    value: u128,
+    msg_data: RawMessageData,
+}
+
+#[derive(Deserialize)]
+struct RawMessageData {
+    #[serde(rename = "@type")]
+    kind: String,
+    #[serde(default)]
+    body: String,
+    #[serde(default)]
+    init_state: String,
+}
+
+#[derive(Deserialize)]
+struct RawConfigInfo {
+    config: RawCell,
+}
+
+#[derive(Deserialize)]
+struct RawCell {
+    bytes: String,
+}
+
+impl Toncenter {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into().trim_end_matches('/').to_string(),
+            api_key: None,
+            http: reqwest::Client::new(),
+        }
+    }
+
+    /// Without a key toncenter allows one request per second
+    pub fn with_api_key(mut self, api_key: impl Into<String>) -> Self {
+        self.api_key = Some(api_key.into());
+        self
+    }
+
+    pub async fn account(&self, address: &TonAddress) -> Result<AccountState> {
+        let info: RawAddressInformation = self
+            .get(
+                "getAddressInformation",
+                &[("address", address.to_base64_url())],
+            )
+            .await?;
+        let active = info.state == "active";
+        Ok(AccountState {
+            active,
+            balance: info.balance,
+            data: match active {
+                true => boc(&info.data)?,
+                false => None,
+            },
+            last_lt: lt(info.last_transaction_id.lt)?,
+        })
+    }
+
+    /// Next seqno of a wallet, and whether its contract is deployed yet
+    pub async fn wallet_seqno(&self, wallet: &TonAddress) -> Result<(u32, bool)> {
+        let info: RawWalletInformation = self
+            .get(
+                "getWalletInformation",
+                &[("address", wallet.to_base64_url())],
+            )
+            .await?;
+        Ok((info.seqno.unwrap_or(0), info.account_state == "active"))
+    }
+
+    /// Latest `limit` transactions of an account, newest first
+    pub async fn transactions(&self, address: &TonAddress, limit: u32) -> Result<Vec<Transaction>> {
+        let query = [
+            ("address", address.to_base64_url()),
+            ("limit", limit.to_string()),
+            ("archival", "true".to_string()),
+        ];
+        let transactions: Vec<RawTransaction> = self.get("getTransactions", &query).await?;
+        transactions
+            .into_iter()
+            .map(Transaction::try_from)
+            .collect()
+    }
+
+    /// Broadcast an external message
+    pub async fn send(&self, message: Cell) -> Result<()> {
+        let boc = BagOfCells::from_root(message).serialize(true)?;
+        let _: serde_json::Value = self
+            .post("sendBoc", json!({ "boc": STANDARD.encode(boc) }))
+            .await?;
+        Ok(())
+    }
+
+    /// Basechain gas price in nanotons per gas unit
+    pub async fn gas_price(&self) -> Result<u64> {
+        let info: RawConfigInfo = self
+            .get(
+                "getConfigParam",
+                &[("config_id", GAS_PRICES_PARAM.to_string())],
+            )
+            .await?;
+        let cell = boc(&info.config.bytes)?.ok_or(Error::Decode("gas prices"))?;
+        gas_price(&cell).map_err(|_| Error::Decode("gas prices"))
+    }
+
+    /// Seqno of the newest masterchain block
+    pub async fn masterchain_seqno(&self) -> Result<u32> {
+        let info: RawMasterchainInfo = self.get("getMasterchainInfo", &[]).await?;
+        Ok(info.last.seqno)
+    }
+
+    async fn get<T: DeserializeOwned>(&self, method: &str, query: &[(&str, String)]) -> Result<T> {
+        let mut request = self.http.get(format!("{}/{method}", self.url)).query(query);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("X-API-Key", api_key);
+        }
+        result(request.send().await?).await
+    }
+
+    async fn post<T: DeserializeOwned>(&self, method: &str, body: serde_json::Value) -> Result<T> {
+        let mut request = self.http.post(format!("{}/{method}", self.url)).json(&body);
+        if let Some(api_key) = &self.api_key {
+            request = request.header("X-API-Key", api_key);
+        }
+        result(request.send().await?).await
+    }
+}
+
+/// Errors come back as `{"ok": false, "error": ...}`, usually with a non-2xx status
+async fn result<T: DeserializeOwned>(response: reqwest::Response) -> Result<T> {
+    let status = response.status();
+    let text = response.text().await?;
+    let response: Response<T> =
+        serde_json::from_str(&text).map_err(|_| Error::Api(format!("{status}: {text}")))?;
+    match (response.ok, response.result) {
+        (true, Some(result)) => Ok(result),
+        _ => Err(Error::Api(
+            response.error.unwrap_or_else(|| status.to_string()),
+        )),
+    }
+}
+
+impl TryFrom<RawTransaction> for Transaction {
+    type Error = Error;
+
+    fn try_from(raw: RawTransaction) -> Result<Self> {
+        Ok(Self {
+            lt: lt(raw.transaction_id.lt)?,
+            hash: hash(&raw.transaction_id.hash)?,
+            utime: raw.utime,
+            in_msg: raw.in_msg.map(Message::try_from).transpose()?,
+            out_msgs: raw
+                .out_msgs
+                .into_iter()
+                .map(Message::try_from)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<RawMessage> for Message {
+    type Error = Error;
+
+    fn try_from(raw: RawMessage) -> Result<Self> {
+        let address = |address: &str| match address {
+            "" => Ok(None),
+            address => address
+                .parse()
+                .map(Some)
+                .map_err(|_| Error::InvalidAddress(address.to_string())),
+        };
+        let raw_data = raw.msg_data.kind == "msg.dataRaw";
+        Ok(Self {
+            hash: hash(&raw.hash)?,
+            source: address(&raw.source)?,
+            destination: address(&raw.destination)?,
+            value: raw.value,
+            body: match raw_data {
+                true => boc(&raw.msg_data.body)?,
+                false => None,
+            },
+            init_state: match raw_data {
+                true => boc(&raw.msg_data.init_state)?,
+                false => None,
+            },
+        })
+    }
+}
+
+/// `gas_price` of a `GasLimitsPrices`, which is stored in 1/65536 nanoton units
+fn gas_price(cell: &Cell) -> std::result::Result<u64, tonlib_core::cell::TonCellError> {
+    let mut parser = cell.parser();
+    let mut tag = parser.load_u8(8)?;
+    // gas_flat_pfx#d1 flat_gas_limit:uint64 flat_gas_price:uint64 other:GasLimitsPrices
+    if tag == 0xd1 {
+        parser.skip_bits(128)?;
+        tag = parser.load_u8(8)?;
+    }
+    match tag {
+        // gas_prices#dd and gas_prices_ext#de both start with gas_price:uint64
+        0xdd | 0xde => Ok(parser.load_u64(64)? >> 16),
+        _ => Err(tonlib_core::cell::TonCellError::InternalError(format!(
+            "unknown gas prices tag {tag:#x}"
+        ))),
+    }
+}
+
+fn boc(base64: &str) -> Result<Option<ArcCell>> {
+    if base64.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(BagOfCells::parse_base64(base64)?.single_root()?))
+}
+
+fn hash(base64: &str) -> Result<[u8; 32]> {
+    STANDARD
+        .decode(base64)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or(Error::Decode("hash"))
+}
+
+fn lt(lt: u128) -> Result<u64> {
+    u64::try_from(lt).map_err(|_| Error::Decode("logical time"))
+}
+
+/// toncenter sends 64-bit and larger integers as strings, and some as JSON numbers
+fn number<'de, D: Deserializer<'de>>(deserializer: D) -> std::result::Result<u128, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Number {
+        String(String),
+        Number(u64),
+    }
+    match Number::deserialize(deserializer)? {
+        Number::String(value) => value.parse().map_err(D::Error::custom),
+        Number::Number(value) => Ok(u128::from(value)),
+    }
+}
@@ -0,0 +1,7 @@
+#![cfg_attr(target_arch = "wasm32", no_std, no_main)]
+#![allow(unused_imports, clippy::single_component_path_imports)]
+use htlc_escrow_casper;
+
+/// The contract is only linked into the wasm build
+#[cfg(not(target_arch = "wasm32"))]
+fn main() {}
@@ -0,0 +1,19 @@
+#![allow(unused_imports, clippy::single_component_path_imports)]
+use htlc_escrow_casper;
+
+// The schema symbols only exist when `cargo odra` builds a module (ODRA_MODULE is set)
+#[cfg(not(odra_module = ""))]
+extern "Rust" {
+    fn module_schema() -> odra::contract_def::ContractBlueprint;
+    fn casper_contract_schema() -> odra::schema::casper_contract_schema::ContractSchema;
+}
+
+#[cfg(not(odra_module = ""))]
+fn main() {
+    odra_build::schema(unsafe { crate::module_schema() }, unsafe {
+        crate::casper_contract_schema()
+    });
+}
+
+#[cfg(odra_module = "")]
+fn main() {}
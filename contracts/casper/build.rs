@@ -0,0 +1,3 @@
+fn main() {
+    odra_build::build();
+}
@@ -0,0 +1,314 @@
+#![cfg_attr(not(test), no_std)]
+#![cfg_attr(not(test), no_main)]
+// `#[odra::event]` generates a constructor taking every field of `EscrowCreated`
+#![allow(clippy::too_many_arguments)]
+extern crate alloc;
+
+// Casper HTLC Escrow
+// An Odra module keeping each escrow in a `Mapping` under `keccak256(order_id)`; Casper
+// dictionary keys cannot be deleted, so settling sets the entry to `None` beside a receipt.
+// `withdraw` checks the keccak256 preimage and, before `exclusive_until`, that the caller is
+// the receiver; `cancel` refunds the sender once the block time (in seconds) reaches the
+// timelock. Any failure reverts the call with the module's `Error`.
+// Escrows lock attached CSPR or a CEP-18 token; entry points share their names with the
+// other chains' escrows so adapters can drive them the same way.
+
+use odra::casper_types::U256;
+use odra::prelude::*;
+use odra::ContractRef;
+use odra::uints::{ToU256, ToU512};
+use sha3::{Digest, Keccak256};
+
+/// Longest order ID accepted, matching the Stellar contract
+const MAX_ORDER_ID_LEN: usize = 128;
+
+/// Terms of a new escrow
+#[odra::odra_type]
+pub struct EscrowParams {
+    pub receiver: Address,
+    /// keccak256 of the 32-byte secret
+    pub secret_hash: [u8; 32],
+    /// Seconds after which the sender may cancel
+    pub timelock: u64,
+    /// Seconds until which only the receiver may withdraw (0 for no exclusive window)
+    pub exclusive_until: u64,
+    /// CEP-18 token to lock, or `None` for the CSPR attached to the call
+    pub token: Option<Address>,
+    /// Tokens to lock for CEP-18 escrows (ignored for CSPR escrows)
+    pub amount: U256,
+    pub order_id: String,
+}
+
+#[odra::odra_type]
+pub struct Escrow {
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount: U256,
+    pub secret_hash: [u8; 32],
+    pub timelock: u64,
+    pub exclusive_until: u64,
+    /// CEP-18 token contract, or `None` for CSPR
+    pub token: Option<Address>,
+    pub order_id: String,
+    pub created_at: u64,
+}
+
+/// Withdrawal windows, as in the Stellar contract
+#[odra::odra_type]
+pub enum Phase {
+    /// Before `exclusive_until`: only the receiver may withdraw
+    Exclusive,
+    /// From `exclusive_until`: anyone with the secret may release funds to the receiver
+    Public,
+}
+
+/// Whether a settled escrow was withdrawn or refunded
+#[odra::odra_type]
+pub enum SettlementStatus {
+    Withdrawn,
+    Cancelled,
+}
+
+/// Codes follow the Stellar contract's `HTLCError` where the names match
+#[odra::odra_error]
+pub enum Error {
+    EscrowNotFound = 1,
+    AlreadyWithdrawn = 2,
+    AlreadyCancelled = 3,
+    InvalidSecret = 4,
+    TimelockNotExpired = 5,
+    UnauthorizedAccess = 6,
+    InvalidAmount = 7,
+    InvalidTimelock = 8,
+    EscrowAlreadyExists = 9,
+    InvalidOrderId = 10,
+}
+
+#[odra::event]
+pub struct EscrowCreated {
+    pub escrow_id: [u8; 32],
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount: U256,
+    pub token: Option<Address>,
+    pub secret_hash: [u8; 32],
+    pub timelock: u64,
+    pub order_id: String,
+}
+
+/// Carries the secret so relayers can complete the counterpart escrow
+#[odra::event]
+pub struct EscrowWithdrawn {
+    pub escrow_id: [u8; 32],
+    pub receiver: Address,
+    pub amount: U256,
+    pub secret: [u8; 32],
+}
+
+#[odra::event]
+pub struct EscrowCancelled {
+    pub escrow_id: [u8; 32],
+    pub sender: Address,
+    pub amount: U256,
+}
+
+/// The CEP-18 calls the escrow makes
+#[odra::external_contract]
+pub trait Cep18 {
+    fn transfer(&mut self, recipient: &Address, amount: &U256);
+    fn transfer_from(&mut self, owner: &Address, recipient: &Address, amount: &U256);
+}
+
+#[odra::module(events = [EscrowCreated, EscrowWithdrawn, EscrowCancelled], errors = Error)]
+pub struct HtlcEscrow {
+    /// Cleared to `None` on settlement, as Odra mappings cannot delete entries
+    escrows: Mapping<[u8; 32], Option<Escrow>>,
+    receipts: Mapping<[u8; 32], SettlementStatus>,
+    revealed_secrets: Mapping<[u8; 32], [u8; 32]>,
+}
+
+#[odra::module]
+impl HtlcEscrow {
+    /// Lock the attached CSPR, or `params.amount` of a CEP-18 token the caller has approved,
+    /// in a new escrow, returning its ID
+    #[odra(payable)]
+    pub fn create_escrow(&mut self, params: EscrowParams) -> [u8; 32] {
+        let env = self.env();
+        let sender = env.caller();
+        let attached = env.attached_value();
+        let amount = match params.token {
+            None => attached.to_u256().unwrap_or_revert_with(self, Error::InvalidAmount),
+            Some(_) => {
+                if !attached.is_zero() {
+                    env.revert(Error::InvalidAmount);
+                }
+                params.amount
+            }
+        };
+
+        if amount.is_zero() {
+            env.revert(Error::InvalidAmount);
+        }
+        if params.timelock <= self.now() || params.exclusive_until > params.timelock {
+            env.revert(Error::InvalidTimelock);
+        }
+        if params.order_id.is_empty() || params.order_id.len() > MAX_ORDER_ID_LEN {
+            env.revert(Error::InvalidOrderId);
+        }
+
+        // Same ID as the Stellar contract for the same order
+        let escrow_id = keccak256(params.order_id.as_bytes());
+        if self.pending(&escrow_id).is_some() || self.receipts.get(&escrow_id).is_some() {
+            env.revert(Error::EscrowAlreadyExists);
+        }
+
+        if let Some(token) = params.token {
+            Cep18ContractRef::new(env.clone(), token).transfer_from(
+                &sender,
+                &env.self_address(),
+                &amount,
+            );
+        }
+
+        let escrow = Escrow {
+            sender,
+            receiver: params.receiver,
+            amount,
+            secret_hash: params.secret_hash,
+            timelock: params.timelock,
+            exclusive_until: params.exclusive_until,
+            token: params.token,
+            order_id: params.order_id,
+            created_at: self.now(),
+        };
+        env.emit_event(EscrowCreated {
+            escrow_id,
+            sender: escrow.sender,
+            receiver: escrow.receiver,
+            amount: escrow.amount,
+            token: escrow.token,
+            secret_hash: escrow.secret_hash,
+            timelock: escrow.timelock,
+            order_id: escrow.order_id.clone(),
+        });
+        self.escrows.set(&escrow_id, Some(escrow));
+
+        escrow_id
+    }
+
+    /// Withdraw everything to the receiver with the secret
+    /// Only the receiver may call during the exclusive window; anyone may afterwards
+    pub fn withdraw(&mut self, escrow_id: [u8; 32], secret: [u8; 32]) {
+        let escrow = self.load_escrow(&escrow_id);
+        if keccak256(&secret) != escrow.secret_hash {
+            self.env().revert(Error::InvalidSecret);
+        }
+        if self.now() < escrow.exclusive_until && self.env().caller() != escrow.receiver {
+            self.env().revert(Error::UnauthorizedAccess);
+        }
+
+        self.escrows.set(&escrow_id, None);
+        self.receipts.set(&escrow_id, SettlementStatus::Withdrawn);
+        self.revealed_secrets.set(&escrow.secret_hash, secret);
+        self.pay(&escrow, &escrow.receiver);
+
+        self.env().emit_event(EscrowWithdrawn {
+            escrow_id,
+            receiver: escrow.receiver,
+            amount: escrow.amount,
+            secret,
+        });
+    }
+
+    /// Refund everything to the sender once the timelock has passed; anyone may call
+    pub fn cancel(&mut self, escrow_id: [u8; 32]) {
+        let escrow = self.load_escrow(&escrow_id);
+        if self.now() < escrow.timelock {
+            self.env().revert(Error::TimelockNotExpired);
+        }
+
+        self.escrows.set(&escrow_id, None);
+        self.receipts.set(&escrow_id, SettlementStatus::Cancelled);
+        self.pay(&escrow, &escrow.sender);
+
+        self.env().emit_event(EscrowCancelled {
+            escrow_id,
+            sender: escrow.sender,
+            amount: escrow.amount,
+        });
+    }
+
+    /// Get escrow details (only while the escrow is still pending)
+    pub fn get_escrow(&self, escrow_id: [u8; 32]) -> Option<Escrow> {
+        self.pending(&escrow_id)
+    }
+
+    /// How a settled escrow ended, if it has
+    pub fn get_receipt(&self, escrow_id: [u8; 32]) -> Option<SettlementStatus> {
+        self.receipts.get(&escrow_id)
+    }
+
+    /// Secret revealed by a withdrawal, for counterpart-chain relayers
+    pub fn get_revealed_secret(&self, secret_hash: [u8; 32]) -> Option<[u8; 32]> {
+        self.revealed_secrets.get(&secret_hash)
+    }
+
+    /// Whether a secret opens a pending escrow
+    pub fn verify_secret(&self, escrow_id: [u8; 32], secret: [u8; 32]) -> bool {
+        self.pending(&escrow_id)
+            .is_some_and(|escrow| keccak256(&secret) == escrow.secret_hash)
+    }
+
+    /// Check if escrow can be withdrawn in the given phase
+    pub fn can_withdraw(&self, escrow_id: [u8; 32], phase: Phase) -> bool {
+        self.pending(&escrow_id).is_some_and(|escrow| match phase {
+            Phase::Exclusive => self.now() < escrow.exclusive_until,
+            Phase::Public => self.now() >= escrow.exclusive_until,
+        })
+    }
+
+    /// Whether a pending escrow's timelock has passed
+    pub fn can_cancel(&self, escrow_id: [u8; 32]) -> bool {
+        self.pending(&escrow_id)
+            .is_some_and(|escrow| self.now() >= escrow.timelock)
+    }
+
+    /// Utility function to generate secret hash
+    pub fn generate_secret_hash(&self, secret: [u8; 32]) -> [u8; 32] {
+        keccak256(&secret)
+    }
+}
+
+impl HtlcEscrow {
+    fn load_escrow(&self, escrow_id: &[u8; 32]) -> Escrow {
+        if let Some(escrow) = self.pending(escrow_id) {
+            return escrow;
+        }
+        let error = match self.receipts.get(escrow_id) {
+            Some(SettlementStatus::Withdrawn) => Error::AlreadyWithdrawn,
+            Some(SettlementStatus::Cancelled) => Error::AlreadyCancelled,
+            None => Error::EscrowNotFound,
+        };
+        self.env().revert(error)
+    }
+
+    fn pending(&self, escrow_id: &[u8; 32]) -> Option<Escrow> {
+        self.escrows.get(escrow_id).flatten()
+    }
+
+    fn pay(&self, escrow: &Escrow, to: &Address) {
+        match escrow.token {
+            None => self.env().transfer_tokens(to, &escrow.amount.to_u512()),
+            Some(token) => Cep18ContractRef::new(self.env(), token).transfer(to, &escrow.amount),
+        }
+    }
+
+    /// Current block time in seconds, the unit of escrow timelocks
+    fn now(&self) -> u64 {
+        self.env().get_block_time_secs()
+    }
+}
+
+fn keccak256(data: &[u8]) -> [u8; 32] {
+    Keccak256::digest(data).into()
+}
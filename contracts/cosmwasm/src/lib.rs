@@ -0,0 +1,378 @@
+// CosmWasm HTLC Escrow
+// Escrows live in a `cw-storage-plus` map under `keccak256(order_id)` and lock a single native
+// coin (`create_escrow`) or cw20 tokens sent with `Send`. `Withdraw` takes the keccak256
+// preimage, from the receiver alone until `exclusive_until` and from anyone after, and
+// `Cancel` refunds the sender once the block time reaches the timelock. Either moves the
+// escrow to a receipt and pays out with a bank or cw20 message in the same transaction, so a
+// failed payout reverts the settlement; events are wasm attributes.
+
+use cosmwasm_schema::{cw_serde, QueryResponses};
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_json, to_json_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env,
+    MessageInfo, Response, StdError, StdResult, Uint128, WasmMsg,
+};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Map;
+use sha3::{Digest, Keccak256};
+use thiserror::Error;
+
+/// Longest order ID accepted, matching the Stellar contract
+const MAX_ORDER_ID_LEN: usize = 128;
+
+const ESCROWS: Map<&[u8], Escrow> = Map::new("escrows");
+const RECEIPTS: Map<&[u8], SettlementStatus> = Map::new("receipts");
+const REVEALED_SECRETS: Map<&[u8], Binary> = Map::new("revealed_secrets");
+
+#[cw_serde]
+pub struct InstantiateMsg {}
+
+/// Terms of a new escrow
+#[cw_serde]
+pub struct EscrowParams {
+    pub receiver: String,
+    /// keccak256 of the 32-byte secret
+    pub secret_hash: Binary,
+    /// Seconds after which the sender may cancel
+    pub timelock: u64,
+    /// Seconds until which only the receiver may withdraw (0 for no exclusive window)
+    pub exclusive_until: u64,
+    pub order_id: String,
+}
+
+/// What an escrow holds: a native denom or a cw20 token contract
+#[cw_serde]
+pub enum Asset {
+    Native { denom: String },
+    Cw20 { contract: Addr },
+}
+
+#[cw_serde]
+pub struct Escrow {
+    pub sender: Addr,
+    pub receiver: Addr,
+    pub amount: Uint128,
+    pub asset: Asset,
+    pub secret_hash: Binary,
+    pub timelock: u64,
+    pub exclusive_until: u64,
+    pub order_id: String,
+    pub created_at: u64,
+}
+
+/// Whether a settled escrow was withdrawn or refunded
+#[cw_serde]
+#[derive(Copy)]
+pub enum SettlementStatus {
+    Withdrawn,
+    Cancelled,
+}
+
+/// Lifecycle state returned by the `status` query
+#[cw_serde]
+#[derive(Copy)]
+pub enum EscrowStatus {
+    Pending,
+    Withdrawn,
+    Cancelled,
+}
+
+#[cw_serde]
+pub enum ExecuteMsg {
+    /// Lock the single attached coin in a new escrow
+    CreateEscrow { params: EscrowParams },
+    /// cw20 receiver hook: lock the sent tokens in an escrow described by `msg`
+    Receive(Cw20ReceiveMsg),
+    /// Withdraw everything to the receiver with the secret
+    /// Only the receiver may call during the exclusive window; anyone may afterwards
+    Withdraw { escrow_id: Binary, secret: Binary },
+    /// Refund everything to the sender once the timelock has passed; anyone may call
+    Cancel { escrow_id: Binary },
+}
+
+/// `msg` payload of a cw20 `Send` to this contract
+#[cw_serde]
+pub enum ReceiveMsg {
+    CreateEscrow { params: EscrowParams },
+}
+
+#[cw_serde]
+#[derive(QueryResponses)]
+pub enum QueryMsg {
+    /// Get escrow details (only while the escrow is still pending)
+    #[returns(Option<Escrow>)]
+    GetEscrow { escrow_id: Binary },
+    /// Whether a pending escrow's timelock has passed
+    #[returns(bool)]
+    CanCancel { escrow_id: Binary },
+    /// Whether an escrow is pending, withdrawn or cancelled (`None` if unknown)
+    #[returns(Option<EscrowStatus>)]
+    Status { escrow_id: Binary },
+    /// Secret revealed by a withdrawal, for counterpart-chain relayers
+    #[returns(Option<Binary>)]
+    GetRevealedSecret { secret_hash: Binary },
+    /// Whether a secret opens a pending escrow
+    #[returns(bool)]
+    VerifySecret { escrow_id: Binary, secret: Binary },
+    /// Utility query to generate secret hash
+    #[returns(Binary)]
+    GenerateSecretHash { secret: Binary },
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+    #[error("Escrow not found")]
+    EscrowNotFound,
+    #[error("Already withdrawn")]
+    AlreadyWithdrawn,
+    #[error("Already cancelled")]
+    AlreadyCancelled,
+    #[error("Invalid secret")]
+    InvalidSecret,
+    #[error("Timelock not expired")]
+    TimelockNotExpired,
+    #[error("Unauthorized access")]
+    UnauthorizedAccess,
+    #[error("Invalid timelock")]
+    InvalidTimelock,
+    #[error("Escrow already exists")]
+    EscrowAlreadyExists,
+    #[error("Invalid order ID")]
+    InvalidOrderId,
+    #[error("Invalid amount")]
+    InvalidAmount,
+    #[error("Invalid secret hash")]
+    InvalidSecretHash,
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn instantiate(
+    _deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    _msg: InstantiateMsg,
+) -> Result<Response, ContractError> {
+    Ok(Response::new().add_attribute("action", "instantiate"))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    match msg {
+        ExecuteMsg::CreateEscrow { params } => {
+            let (denom, amount) = match info.funds.as_slice() {
+                [coin] => (coin.denom.clone(), coin.amount),
+                _ => return Err(ContractError::InvalidAmount),
+            };
+            open_escrow(deps, &env, info.sender, amount, Asset::Native { denom }, params)
+        }
+        ExecuteMsg::Receive(receive) => {
+            let ReceiveMsg::CreateEscrow { params } = from_json(&receive.msg)?;
+            let sender = deps.api.addr_validate(&receive.sender)?;
+            let asset = Asset::Cw20 { contract: info.sender };
+            open_escrow(deps, &env, sender, receive.amount, asset, params)
+        }
+        ExecuteMsg::Withdraw { escrow_id, secret } => {
+            withdraw(deps, &env, info, escrow_id, secret)
+        }
+        ExecuteMsg::Cancel { escrow_id } => cancel(deps, &env, escrow_id),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetEscrow { escrow_id } => {
+            to_json_binary(&ESCROWS.may_load(deps.storage, &escrow_id)?)
+        }
+        QueryMsg::CanCancel { escrow_id } => {
+            let escrow = ESCROWS.may_load(deps.storage, &escrow_id)?;
+            to_json_binary(&escrow.is_some_and(|escrow| now(&env) >= escrow.timelock))
+        }
+        QueryMsg::Status { escrow_id } => {
+            let status = if ESCROWS.has(deps.storage, &escrow_id) {
+                Some(EscrowStatus::Pending)
+            } else {
+                RECEIPTS
+                    .may_load(deps.storage, &escrow_id)?
+                    .map(|status| match status {
+                        SettlementStatus::Withdrawn => EscrowStatus::Withdrawn,
+                        SettlementStatus::Cancelled => EscrowStatus::Cancelled,
+                    })
+            };
+            to_json_binary(&status)
+        }
+        QueryMsg::GetRevealedSecret { secret_hash } => {
+            to_json_binary(&REVEALED_SECRETS.may_load(deps.storage, &secret_hash)?)
+        }
+        QueryMsg::VerifySecret { escrow_id, secret } => {
+            let escrow = ESCROWS.may_load(deps.storage, &escrow_id)?;
+            to_json_binary(&escrow.is_some_and(|escrow| keccak256(&secret) == *escrow.secret_hash))
+        }
+        QueryMsg::GenerateSecretHash { secret } => {
+            to_json_binary(&Binary::from(keccak256(&secret)))
+        }
+    }
+}
+
+fn open_escrow(
+    deps: DepsMut,
+    env: &Env,
+    sender: Addr,
+    amount: Uint128,
+    asset: Asset,
+    params: EscrowParams,
+) -> Result<Response, ContractError> {
+    let EscrowParams {
+        receiver,
+        secret_hash,
+        timelock,
+        exclusive_until,
+        order_id,
+    } = params;
+
+    if amount.is_zero() {
+        return Err(ContractError::InvalidAmount);
+    }
+    if secret_hash.len() != 32 {
+        return Err(ContractError::InvalidSecretHash);
+    }
+    if timelock <= now(env) || exclusive_until > timelock {
+        return Err(ContractError::InvalidTimelock);
+    }
+    if order_id.is_empty() || order_id.len() > MAX_ORDER_ID_LEN {
+        return Err(ContractError::InvalidOrderId);
+    }
+    let receiver = deps.api.addr_validate(&receiver)?;
+
+    // Same ID as the Stellar contract for the same order
+    let escrow_id = keccak256(order_id.as_bytes());
+    if ESCROWS.has(deps.storage, &escrow_id) || RECEIPTS.has(deps.storage, &escrow_id) {
+        return Err(ContractError::EscrowAlreadyExists);
+    }
+
+    let escrow = Escrow {
+        sender,
+        receiver,
+        amount,
+        asset,
+        secret_hash,
+        timelock,
+        exclusive_until,
+        order_id,
+        created_at: now(env),
+    };
+    ESCROWS.save(deps.storage, &escrow_id, &escrow)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "escrow_created")
+        .add_attribute("escrow_id", Binary::from(escrow_id.clone()).to_base64())
+        .add_attribute("sender", escrow.sender)
+        .add_attribute("receiver", escrow.receiver)
+        .add_attribute("amount", escrow.amount)
+        .add_attribute("asset", asset_label(&escrow.asset))
+        .add_attribute("secret_hash", escrow.secret_hash.to_base64())
+        .add_attribute("timelock", escrow.timelock.to_string())
+        .add_attribute("order_id", escrow.order_id)
+        .set_data(escrow_id))
+}
+
+fn withdraw(
+    deps: DepsMut,
+    env: &Env,
+    info: MessageInfo,
+    escrow_id: Binary,
+    secret: Binary,
+) -> Result<Response, ContractError> {
+    let escrow = load_escrow(deps.as_ref(), &escrow_id)?;
+    if secret.len() != 32 || keccak256(&secret) != *escrow.secret_hash {
+        return Err(ContractError::InvalidSecret);
+    }
+    if now(env) < escrow.exclusive_until && info.sender != escrow.receiver {
+        return Err(ContractError::UnauthorizedAccess);
+    }
+
+    ESCROWS.remove(deps.storage, &escrow_id);
+    RECEIPTS.save(deps.storage, &escrow_id, &SettlementStatus::Withdrawn)?;
+    REVEALED_SECRETS.save(deps.storage, &escrow.secret_hash, &secret)?;
+
+    Ok(Response::new()
+        .add_message(pay(&escrow, &escrow.receiver)?)
+        .add_attribute("action", "escrow_withdrawn")
+        .add_attribute("escrow_id", escrow_id.to_base64())
+        .add_attribute("receiver", escrow.receiver.as_str())
+        .add_attribute("amount", escrow.amount)
+        .add_attribute("secret", secret.to_base64()))
+}
+
+fn cancel(deps: DepsMut, env: &Env, escrow_id: Binary) -> Result<Response, ContractError> {
+    let escrow = load_escrow(deps.as_ref(), &escrow_id)?;
+    if now(env) < escrow.timelock {
+        return Err(ContractError::TimelockNotExpired);
+    }
+
+    ESCROWS.remove(deps.storage, &escrow_id);
+    RECEIPTS.save(deps.storage, &escrow_id, &SettlementStatus::Cancelled)?;
+
+    Ok(Response::new()
+        .add_message(pay(&escrow, &escrow.sender)?)
+        .add_attribute("action", "escrow_cancelled")
+        .add_attribute("escrow_id", escrow_id.to_base64())
+        .add_attribute("sender", escrow.sender.as_str())
+        .add_attribute("amount", escrow.amount))
+}
+
+fn load_escrow(deps: Deps, escrow_id: &[u8]) -> Result<Escrow, ContractError> {
+    if let Some(escrow) = ESCROWS.may_load(deps.storage, escrow_id)? {
+        return Ok(escrow);
+    }
+    match RECEIPTS.may_load(deps.storage, escrow_id)? {
+        Some(SettlementStatus::Withdrawn) => Err(ContractError::AlreadyWithdrawn),
+        Some(SettlementStatus::Cancelled) => Err(ContractError::AlreadyCancelled),
+        None => Err(ContractError::EscrowNotFound),
+    }
+}
+
+/// Message sending an escrow's funds to `to`; a failed transfer reverts the settlement
+fn pay(escrow: &Escrow, to: &Addr) -> StdResult<CosmosMsg> {
+    Ok(match &escrow.asset {
+        Asset::Native { denom } => BankMsg::Send {
+            to_address: to.to_string(),
+            amount: vec![Coin::new(escrow.amount, denom)],
+        }
+        .into(),
+        Asset::Cw20 { contract } => WasmMsg::Execute {
+            contract_addr: contract.to_string(),
+            msg: to_json_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: to.to_string(),
+                amount: escrow.amount,
+            })?,
+            funds: vec![],
+        }
+        .into(),
+    })
+}
+
+/// Denom or cw20 contract address, as logged in `escrow_created`
+fn asset_label(asset: &Asset) -> String {
+    match asset {
+        Asset::Native { denom } => denom.clone(),
+        Asset::Cw20 { contract } => contract.to_string(),
+    }
+}
+
+/// Current block time in seconds, the unit of escrow timelocks
+fn now(env: &Env) -> u64 {
+    env.block.time.seconds()
+}
+
+fn keccak256(data: &[u8]) -> Vec<u8> {
+    Keccak256::digest(data).to_vec()
+}
@@ -0,0 +1,435 @@
+// Internet Computer HTLC Escrow
+// The canister keeps escrows on its heap under `keccak256(order_id)`, saved to stable memory
+// across upgrades, and locks ICRC-1 tokens pulled from the sender with ICRC-2
+// `icrc2_transfer_from`. Settlement is optimistic because ledger calls are async: `withdraw`
+// (keccak256 preimage; only the receiver before `exclusive_until`) and `cancel` (after the
+// timelock, in seconds of IC time) record the receipt first, then transfer, and put the escrow
+// back if the ledger refuses.
+// The canister pays for every call, so receivers (or a relayer on their behalf) can claim
+// without holding cycles or the token. Escrow state is certified: `get_escrow_certified`
+// returns a certificate and witness clients can check against the subnet's key.
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, BTreeSet};
+
+use candid::{CandidType, Encode, Nat, Principal};
+use ic_certified_map::{labeled, labeled_hash, leaf_hash, AsHashTree, Hash, RbTree};
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::{TransferArg, TransferError};
+use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromError};
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
+
+/// Longest order ID accepted, matching the Stellar contract
+const MAX_ORDER_ID_LEN: usize = 128;
+
+/// Label of the certified escrow tree under the canister's certified data
+const CERTIFIED_LABEL: &[u8] = b"escrows";
+
+/// Terms of a new escrow
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct EscrowParams {
+    pub receiver: Principal,
+    /// ICRC-1 ledger of the token to lock
+    pub token: Principal,
+    pub amount: Nat,
+    /// keccak256 of the 32-byte secret
+    pub secret_hash: Vec<u8>,
+    /// Seconds after which the sender may cancel
+    pub timelock: u64,
+    /// Seconds until which only the receiver may withdraw (0 for no exclusive window)
+    pub exclusive_until: u64,
+    pub order_id: String,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct Escrow {
+    pub sender: Principal,
+    pub receiver: Principal,
+    pub token: Principal,
+    pub amount: Nat,
+    pub secret_hash: Vec<u8>,
+    pub timelock: u64,
+    pub exclusive_until: u64,
+    pub order_id: String,
+    pub created_at: u64,
+}
+
+/// Withdrawal windows, as in the Stellar contract
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    /// Before `exclusive_until`: only the receiver may withdraw
+    Exclusive,
+    /// From `exclusive_until`: anyone with the secret may release funds to the receiver
+    Public,
+}
+
+/// Whether a settled escrow was withdrawn or refunded
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SettlementStatus {
+    Withdrawn,
+    Cancelled,
+}
+
+/// Lifecycle state covered by the certified tree
+#[derive(CandidType, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EscrowStatus {
+    Pending,
+    Withdrawn,
+    Cancelled,
+}
+
+/// An escrow's state with the proof that the canister certified it
+/// The witness is a CBOR hash tree whose `escrows/<escrow_id>` leaf is the Candid encoding
+/// of `(status, escrow)`; its root hash, labeled `escrows`, is the canister's certified data
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct CertifiedEscrow {
+    pub status: Option<EscrowStatus>,
+    pub escrow: Option<Escrow>,
+    pub certificate: Option<Vec<u8>>,
+    pub witness: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum EscrowError {
+    EscrowNotFound,
+    AlreadyWithdrawn,
+    AlreadyCancelled,
+    InvalidSecret,
+    TimelockNotExpired,
+    UnauthorizedAccess,
+    InvalidTimelock,
+    EscrowAlreadyExists,
+    InvalidOrderId,
+    InvalidAmount,
+    InvalidSecretHash,
+    /// The ledger call failed or rejected the transfer
+    TransferFailed(String),
+}
+
+#[derive(CandidType, Deserialize, Default)]
+struct State {
+    escrows: BTreeMap<Vec<u8>, Escrow>,
+    receipts: BTreeMap<Vec<u8>, SettlementStatus>,
+    revealed_secrets: BTreeMap<Vec<u8>, Vec<u8>>,
+    /// Escrows whose funding transfer is still in flight
+    #[serde(skip)]
+    locking: BTreeSet<Vec<u8>>,
+}
+
+thread_local! {
+    static STATE: RefCell<State> = RefCell::default();
+    static CERTIFIED: RefCell<RbTree<Vec<u8>, Hash>> = const { RefCell::new(RbTree::new()) };
+}
+
+/// Pull `params.amount` of the token from the caller, who must have approved this canister
+/// for the amount plus the ledger fee, into a new escrow, returning its ID
+#[ic_cdk::update]
+async fn create_escrow(params: EscrowParams) -> Result<Vec<u8>, EscrowError> {
+    let sender = ic_cdk::caller();
+    if params.amount == 0u64 {
+        return Err(EscrowError::InvalidAmount);
+    }
+    if params.secret_hash.len() != 32 {
+        return Err(EscrowError::InvalidSecretHash);
+    }
+    if params.timelock <= now() || params.exclusive_until > params.timelock {
+        return Err(EscrowError::InvalidTimelock);
+    }
+    if params.order_id.is_empty() || params.order_id.len() > MAX_ORDER_ID_LEN {
+        return Err(EscrowError::InvalidOrderId);
+    }
+
+    // Same ID as the Stellar contract for the same order
+    let escrow_id = keccak256(params.order_id.as_bytes());
+    STATE.with_borrow_mut(|state| {
+        if state.escrows.contains_key(&escrow_id)
+            || state.receipts.contains_key(&escrow_id)
+            || !state.locking.insert(escrow_id.clone())
+        {
+            return Err(EscrowError::EscrowAlreadyExists);
+        }
+        Ok(())
+    })?;
+
+    let funded = transfer_from(params.token, sender, params.amount.clone()).await;
+    STATE.with_borrow_mut(|state| state.locking.remove(&escrow_id));
+    funded?;
+
+    let escrow = Escrow {
+        sender,
+        receiver: params.receiver,
+        token: params.token,
+        amount: params.amount,
+        secret_hash: params.secret_hash,
+        timelock: params.timelock,
+        exclusive_until: params.exclusive_until,
+        order_id: params.order_id,
+        created_at: now(),
+    };
+    ic_cdk::println!(
+        "HTLC Escrow Created: ID={}, Sender={}, Receiver={}, Amount={}",
+        hex(&escrow_id),
+        escrow.sender,
+        escrow.receiver,
+        escrow.amount
+    );
+    STATE.with_borrow_mut(|state| state.escrows.insert(escrow_id.clone(), escrow));
+    certify(&escrow_id);
+
+    Ok(escrow_id)
+}
+
+/// Withdraw everything to the receiver with the secret, less the ledger fee, returning the
+/// ledger block index; only the receiver may call during the exclusive window, anyone may
+/// afterwards
+#[ic_cdk::update]
+async fn withdraw(escrow_id: Vec<u8>, secret: Vec<u8>) -> Result<Nat, EscrowError> {
+    let escrow = load_escrow(&escrow_id)?;
+    if secret.len() != 32 || keccak256(&secret) != escrow.secret_hash {
+        return Err(EscrowError::InvalidSecret);
+    }
+    if now() < escrow.exclusive_until && ic_cdk::caller() != escrow.receiver {
+        return Err(EscrowError::UnauthorizedAccess);
+    }
+
+    STATE.with_borrow_mut(|state| {
+        state.escrows.remove(&escrow_id);
+        state.receipts.insert(escrow_id.clone(), SettlementStatus::Withdrawn);
+        state.revealed_secrets.insert(escrow.secret_hash.clone(), secret);
+    });
+    certify(&escrow_id);
+
+    let block = pay(&escrow_id, &escrow, escrow.receiver).await?;
+    ic_cdk::println!(
+        "HTLC Escrow Withdrawn: ID={}, Receiver={}, Amount={}",
+        hex(&escrow_id),
+        escrow.receiver,
+        escrow.amount
+    );
+    Ok(block)
+}
+
+/// Refund everything to the sender, less the ledger fee, once the timelock has passed,
+/// returning the ledger block index; anyone may call
+#[ic_cdk::update]
+async fn cancel(escrow_id: Vec<u8>) -> Result<Nat, EscrowError> {
+    let escrow = load_escrow(&escrow_id)?;
+    if now() < escrow.timelock {
+        return Err(EscrowError::TimelockNotExpired);
+    }
+
+    STATE.with_borrow_mut(|state| {
+        state.escrows.remove(&escrow_id);
+        state.receipts.insert(escrow_id.clone(), SettlementStatus::Cancelled);
+    });
+    certify(&escrow_id);
+
+    let block = pay(&escrow_id, &escrow, escrow.sender).await?;
+    ic_cdk::println!(
+        "HTLC Escrow Cancelled: ID={}, Sender={}, Amount={}",
+        hex(&escrow_id),
+        escrow.sender,
+        escrow.amount
+    );
+    Ok(block)
+}
+
+/// Get escrow details (only while the escrow is still pending)
+#[ic_cdk::query]
+fn get_escrow(escrow_id: Vec<u8>) -> Option<Escrow> {
+    STATE.with_borrow(|state| state.escrows.get(&escrow_id).cloned())
+}
+
+/// Escrow status and details with a certificate, for clients that must not trust the
+/// replica answering the query
+#[ic_cdk::query]
+fn get_escrow_certified(escrow_id: Vec<u8>) -> CertifiedEscrow {
+    let (status, escrow) = escrow_state(&escrow_id);
+    let witness = CERTIFIED.with_borrow(|tree| {
+        let mut witness = vec![];
+        let mut serializer = serde_cbor::Serializer::new(&mut witness);
+        serializer.self_describe().unwrap();
+        labeled(CERTIFIED_LABEL, tree.witness(&escrow_id))
+            .serialize(&mut serializer)
+            .unwrap();
+        witness
+    });
+    CertifiedEscrow {
+        status,
+        escrow,
+        certificate: ic_cdk::api::data_certificate(),
+        witness,
+    }
+}
+
+/// How a settled escrow ended, if it has
+#[ic_cdk::query]
+fn get_receipt(escrow_id: Vec<u8>) -> Option<SettlementStatus> {
+    STATE.with_borrow(|state| state.receipts.get(&escrow_id).copied())
+}
+
+/// Secret revealed by a withdrawal, for counterpart-chain relayers
+#[ic_cdk::query]
+fn get_revealed_secret(secret_hash: Vec<u8>) -> Option<Vec<u8>> {
+    STATE.with_borrow(|state| state.revealed_secrets.get(&secret_hash).cloned())
+}
+
+/// Whether a secret opens a pending escrow
+#[ic_cdk::query]
+fn verify_secret(escrow_id: Vec<u8>, secret: Vec<u8>) -> bool {
+    get_escrow(escrow_id).is_some_and(|escrow| keccak256(&secret) == escrow.secret_hash)
+}
+
+/// Check if escrow can be withdrawn in the given phase
+#[ic_cdk::query]
+fn can_withdraw(escrow_id: Vec<u8>, phase: Phase) -> bool {
+    get_escrow(escrow_id).is_some_and(|escrow| match phase {
+        Phase::Exclusive => now() < escrow.exclusive_until,
+        Phase::Public => now() >= escrow.exclusive_until,
+    })
+}
+
+/// Whether a pending escrow's timelock has passed
+#[ic_cdk::query]
+fn can_cancel(escrow_id: Vec<u8>) -> bool {
+    get_escrow(escrow_id).is_some_and(|escrow| now() >= escrow.timelock)
+}
+
+/// Utility function to generate secret hash
+#[ic_cdk::query]
+fn generate_secret_hash(secret: Vec<u8>) -> Vec<u8> {
+    keccak256(&secret)
+}
+
+#[ic_cdk::pre_upgrade]
+fn pre_upgrade() {
+    STATE.with_borrow(|state| ic_cdk::storage::stable_save((state,)))
+        .expect("Failed to save escrow state");
+}
+
+#[ic_cdk::post_upgrade]
+fn post_upgrade() {
+    let (state,): (State,) =
+        ic_cdk::storage::stable_restore().expect("Failed to restore escrow state");
+    let escrow_ids: Vec<Vec<u8>> = state
+        .escrows
+        .keys()
+        .chain(state.receipts.keys())
+        .cloned()
+        .collect();
+    STATE.set(state);
+    for escrow_id in escrow_ids {
+        certify(&escrow_id);
+    }
+}
+
+fn load_escrow(escrow_id: &[u8]) -> Result<Escrow, EscrowError> {
+    STATE.with_borrow(|state| match state.escrows.get(escrow_id) {
+        Some(escrow) => Ok(escrow.clone()),
+        None => match state.receipts.get(escrow_id) {
+            Some(SettlementStatus::Withdrawn) => Err(EscrowError::AlreadyWithdrawn),
+            Some(SettlementStatus::Cancelled) => Err(EscrowError::AlreadyCancelled),
+            None => Err(EscrowError::EscrowNotFound),
+        },
+    })
+}
+
+fn escrow_state(escrow_id: &[u8]) -> (Option<EscrowStatus>, Option<Escrow>) {
+    STATE.with_borrow(|state| {
+        if let Some(escrow) = state.escrows.get(escrow_id) {
+            return (Some(EscrowStatus::Pending), Some(escrow.clone()));
+        }
+        let status = state.receipts.get(escrow_id).map(|status| match status {
+            SettlementStatus::Withdrawn => EscrowStatus::Withdrawn,
+            SettlementStatus::Cancelled => EscrowStatus::Cancelled,
+        });
+        (status, None)
+    })
+}
+
+/// Refresh an escrow's leaf in the certified tree and the canister's certified data
+fn certify(escrow_id: &[u8]) {
+    let (status, escrow) = escrow_state(escrow_id);
+    CERTIFIED.with_borrow_mut(|tree| {
+        match status {
+            Some(status) => {
+                let leaf = Encode!(&status, &escrow).expect("Failed to encode escrow state");
+                tree.insert(escrow_id.to_vec(), leaf_hash(&leaf));
+            }
+            None => tree.delete(escrow_id),
+        }
+        ic_cdk::api::set_certified_data(&labeled_hash(CERTIFIED_LABEL, &tree.root_hash()));
+    });
+}
+
+/// Send an escrow's funds less the ledger fee, restoring the escrow if the transfer fails
+async fn pay(escrow_id: &[u8], escrow: &Escrow, to: Principal) -> Result<Nat, EscrowError> {
+    let result = transfer(escrow.token, to, escrow.amount.clone()).await;
+    if result.is_err() {
+        STATE.with_borrow_mut(|state| {
+            if state.receipts.remove(escrow_id) == Some(SettlementStatus::Withdrawn) {
+                state.revealed_secrets.remove(&escrow.secret_hash);
+            }
+            state.escrows.insert(escrow_id.to_vec(), escrow.clone());
+        });
+        certify(escrow_id);
+    }
+    result
+}
+
+async fn transfer_from(token: Principal, from: Principal, amount: Nat) -> Result<(), EscrowError> {
+    let args = TransferFromArgs {
+        spender_subaccount: None,
+        from: Account::from(from),
+        to: Account::from(ic_cdk::id()),
+        amount,
+        fee: None,
+        memo: None,
+        created_at_time: None,
+    };
+    let (result,): (Result<Nat, TransferFromError>,) =
+        ic_cdk::call(token, "icrc2_transfer_from", (args,))
+            .await
+            .map_err(|(_, message)| EscrowError::TransferFailed(message))?;
+    result
+        .map(|_| ())
+        .map_err(|err| EscrowError::TransferFailed(format!("{:?}", err)))
+}
+
+/// Send `amount` less the ledger's current fee, which the canister pays on top of a transfer
+async fn transfer(token: Principal, to: Principal, amount: Nat) -> Result<Nat, EscrowError> {
+    let (fee,): (Nat,) = ic_cdk::call(token, "icrc1_fee", ())
+        .await
+        .map_err(|(_, message)| EscrowError::TransferFailed(message))?;
+    if amount <= fee {
+        return Err(EscrowError::InvalidAmount);
+    }
+    let args = TransferArg {
+        from_subaccount: None,
+        to: Account::from(to),
+        fee: Some(fee.clone()),
+        created_at_time: None,
+        memo: None,
+        amount: amount - fee,
+    };
+    let (result,): (Result<Nat, TransferError>,) = ic_cdk::call(token, "icrc1_transfer", (args,))
+        .await
+        .map_err(|(_, message)| EscrowError::TransferFailed(message))?;
+    result.map_err(|err| EscrowError::TransferFailed(format!("{:?}", err)))
+}
+
+/// Current time in seconds, the unit of escrow timelocks
+fn now() -> u64 {
+    ic_cdk::api::time() / 1_000_000_000
+}
+
+fn keccak256(data: &[u8]) -> Vec<u8> {
+    Keccak256::digest(data).to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+ic_cdk::export_candid!();
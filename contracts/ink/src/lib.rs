@@ -0,0 +1,398 @@
+#![cfg_attr(not(feature = "std"), no_std, no_main)]
+
+// ink! HTLC Escrow
+// One contract holds every escrow in a `Mapping` under `keccak256(order_id)`, locking the
+// chain's native token (the call's transferred value) or a PSP22 token it pulls with
+// `transfer_from`. Messages return `Result` with the contract's `Error`: `withdraw` checks the
+// keccak256 preimage and, until `exclusive_until`, that the caller is the receiver, and
+// `cancel` refunds the sender once the block timestamp (in seconds) reaches the timelock. A
+// failed payout returns an error, which reverts the message's storage changes.
+
+#[ink::contract]
+mod htlc_escrow {
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::env::hash::Keccak256;
+    use ink::prelude::string::String;
+    use ink::prelude::vec::Vec;
+    use ink::storage::Mapping;
+
+    /// Longest order ID accepted, matching the Stellar contract
+    const MAX_ORDER_ID_LEN: usize = 128;
+
+    /// Terms of a new escrow
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct EscrowParams {
+        pub receiver: AccountId,
+        /// keccak256 of the 32-byte secret
+        pub secret_hash: [u8; 32],
+        /// Seconds after which the sender may cancel
+        pub timelock: u64,
+        /// Seconds until which only the receiver may withdraw (0 for no exclusive window)
+        pub exclusive_until: u64,
+        /// PSP22 token to lock, or `None` for the native token sent with the call
+        pub token: Option<AccountId>,
+        /// Tokens to lock for PSP22 escrows (ignored for native escrows)
+        pub amount: Balance,
+        pub order_id: String,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Escrow {
+        pub sender: AccountId,
+        pub receiver: AccountId,
+        pub amount: Balance,
+        pub secret_hash: [u8; 32],
+        pub timelock: u64,
+        pub exclusive_until: u64,
+        /// PSP22 token contract, or `None` for the native token
+        pub token: Option<AccountId>,
+        pub order_id: String,
+        pub created_at: u64,
+    }
+
+    /// Withdrawal windows, as in the Stellar contract
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Phase {
+        /// Before `exclusive_until`: only the receiver may withdraw
+        Exclusive,
+        /// From `exclusive_until`: anyone with the secret may release funds to the receiver
+        Public,
+    }
+
+    /// Whether a settled escrow was withdrawn or refunded
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum SettlementStatus {
+        Withdrawn,
+        Cancelled,
+    }
+
+    #[derive(Clone, Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Error {
+        EscrowNotFound,
+        AlreadyWithdrawn,
+        AlreadyCancelled,
+        InvalidSecret,
+        TimelockNotExpired,
+        UnauthorizedAccess,
+        InvalidTimelock,
+        EscrowAlreadyExists,
+        InvalidOrderId,
+        InvalidAmount,
+        /// The native or PSP22 transfer failed
+        TransferFailed,
+    }
+
+    /// Error returned by PSP22 token calls
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
+    }
+
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// Event topics are the escrow ID and the parties, so indexers can filter on either
+    #[ink(event)]
+    pub struct EscrowCreated {
+        #[ink(topic)]
+        escrow_id: [u8; 32],
+        #[ink(topic)]
+        sender: AccountId,
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+        token: Option<AccountId>,
+        secret_hash: [u8; 32],
+        timelock: u64,
+        order_id: String,
+    }
+
+    #[ink(event)]
+    pub struct EscrowWithdrawn {
+        #[ink(topic)]
+        escrow_id: [u8; 32],
+        #[ink(topic)]
+        receiver: AccountId,
+        amount: Balance,
+        secret: [u8; 32],
+    }
+
+    #[ink(event)]
+    pub struct EscrowCancelled {
+        #[ink(topic)]
+        escrow_id: [u8; 32],
+        #[ink(topic)]
+        sender: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(storage)]
+    pub struct HtlcEscrow {
+        escrows: Mapping<[u8; 32], Escrow>,
+        receipts: Mapping<[u8; 32], SettlementStatus>,
+        revealed_secrets: Mapping<[u8; 32], [u8; 32]>,
+    }
+
+    impl Default for HtlcEscrow {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl HtlcEscrow {
+        #[ink(constructor)]
+        pub fn new() -> Self {
+            Self {
+                escrows: Mapping::default(),
+                receipts: Mapping::default(),
+                revealed_secrets: Mapping::default(),
+            }
+        }
+
+        /// Lock the transferred value, or `params.amount` of a PSP22 token the caller has
+        /// approved, in a new escrow, returning its ID
+        #[ink(message, payable)]
+        pub fn create_escrow(&mut self, params: EscrowParams) -> Result<[u8; 32]> {
+            let sender = self.env().caller();
+            let amount = match params.token {
+                None => self.env().transferred_value(),
+                Some(_) => {
+                    if self.env().transferred_value() != 0 {
+                        return Err(Error::InvalidAmount);
+                    }
+                    params.amount
+                }
+            };
+
+            if amount == 0 {
+                return Err(Error::InvalidAmount);
+            }
+            if params.timelock <= self.now() || params.exclusive_until > params.timelock {
+                return Err(Error::InvalidTimelock);
+            }
+            if params.order_id.is_empty() || params.order_id.len() > MAX_ORDER_ID_LEN {
+                return Err(Error::InvalidOrderId);
+            }
+
+            // Same ID as the Stellar contract for the same order
+            let escrow_id = keccak256(params.order_id.as_bytes());
+            if self.escrows.contains(escrow_id) || self.receipts.contains(escrow_id) {
+                return Err(Error::EscrowAlreadyExists);
+            }
+
+            if let Some(token) = params.token {
+                psp22_transfer_from(token, sender, self.env().account_id(), amount)?;
+            }
+
+            let escrow = Escrow {
+                sender,
+                receiver: params.receiver,
+                amount,
+                secret_hash: params.secret_hash,
+                timelock: params.timelock,
+                exclusive_until: params.exclusive_until,
+                token: params.token,
+                order_id: params.order_id,
+                created_at: self.now(),
+            };
+            self.escrows.insert(escrow_id, &escrow);
+
+            self.env().emit_event(EscrowCreated {
+                escrow_id,
+                sender: escrow.sender,
+                receiver: escrow.receiver,
+                amount: escrow.amount,
+                token: escrow.token,
+                secret_hash: escrow.secret_hash,
+                timelock: escrow.timelock,
+                order_id: escrow.order_id,
+            });
+
+            Ok(escrow_id)
+        }
+
+        /// Withdraw everything to the receiver with the secret
+        /// Only the receiver may call during the exclusive window; anyone may afterwards
+        #[ink(message)]
+        pub fn withdraw(&mut self, escrow_id: [u8; 32], secret: [u8; 32]) -> Result<()> {
+            let escrow = self.load_escrow(escrow_id)?;
+            if keccak256(&secret) != escrow.secret_hash {
+                return Err(Error::InvalidSecret);
+            }
+            if self.now() < escrow.exclusive_until && self.env().caller() != escrow.receiver {
+                return Err(Error::UnauthorizedAccess);
+            }
+
+            self.escrows.remove(escrow_id);
+            self.receipts.insert(escrow_id, &SettlementStatus::Withdrawn);
+            self.revealed_secrets.insert(escrow.secret_hash, &secret);
+            self.pay(&escrow, escrow.receiver)?;
+
+            self.env().emit_event(EscrowWithdrawn {
+                escrow_id,
+                receiver: escrow.receiver,
+                amount: escrow.amount,
+                secret,
+            });
+
+            Ok(())
+        }
+
+        /// Refund everything to the sender once the timelock has passed; anyone may call
+        #[ink(message)]
+        pub fn cancel(&mut self, escrow_id: [u8; 32]) -> Result<()> {
+            let escrow = self.load_escrow(escrow_id)?;
+            if self.now() < escrow.timelock {
+                return Err(Error::TimelockNotExpired);
+            }
+
+            self.escrows.remove(escrow_id);
+            self.receipts.insert(escrow_id, &SettlementStatus::Cancelled);
+            self.pay(&escrow, escrow.sender)?;
+
+            self.env().emit_event(EscrowCancelled {
+                escrow_id,
+                sender: escrow.sender,
+                amount: escrow.amount,
+            });
+
+            Ok(())
+        }
+
+        /// Get escrow details (only while the escrow is still pending)
+        #[ink(message)]
+        pub fn get_escrow(&self, escrow_id: [u8; 32]) -> Option<Escrow> {
+            self.escrows.get(escrow_id)
+        }
+
+        /// How a settled escrow ended, if it has
+        #[ink(message)]
+        pub fn get_receipt(&self, escrow_id: [u8; 32]) -> Option<SettlementStatus> {
+            self.receipts.get(escrow_id)
+        }
+
+        /// Secret revealed by a withdrawal, for counterpart-chain relayers
+        #[ink(message)]
+        pub fn get_revealed_secret(&self, secret_hash: [u8; 32]) -> Option<[u8; 32]> {
+            self.revealed_secrets.get(secret_hash)
+        }
+
+        /// Whether a secret opens a pending escrow
+        #[ink(message)]
+        pub fn verify_secret(&self, escrow_id: [u8; 32], secret: [u8; 32]) -> bool {
+            self.escrows
+                .get(escrow_id)
+                .is_some_and(|escrow| keccak256(&secret) == escrow.secret_hash)
+        }
+
+        /// Check if escrow can be withdrawn in the given phase
+        #[ink(message)]
+        pub fn can_withdraw(&self, escrow_id: [u8; 32], phase: Phase) -> bool {
+            self.escrows.get(escrow_id).is_some_and(|escrow| match phase {
+                Phase::Exclusive => self.now() < escrow.exclusive_until,
+                Phase::Public => self.now() >= escrow.exclusive_until,
+            })
+        }
+
+        /// Whether a pending escrow's timelock has passed
+        #[ink(message)]
+        pub fn can_cancel(&self, escrow_id: [u8; 32]) -> bool {
+            self.escrows
+                .get(escrow_id)
+                .is_some_and(|escrow| self.now() >= escrow.timelock)
+        }
+
+        /// Utility function to generate secret hash
+        #[ink(message)]
+        pub fn generate_secret_hash(&self, secret: [u8; 32]) -> [u8; 32] {
+            keccak256(&secret)
+        }
+
+        fn load_escrow(&self, escrow_id: [u8; 32]) -> Result<Escrow> {
+            if let Some(escrow) = self.escrows.get(escrow_id) {
+                return Ok(escrow);
+            }
+            match self.receipts.get(escrow_id) {
+                Some(SettlementStatus::Withdrawn) => Err(Error::AlreadyWithdrawn),
+                Some(SettlementStatus::Cancelled) => Err(Error::AlreadyCancelled),
+                None => Err(Error::EscrowNotFound),
+            }
+        }
+
+        /// Send an escrow's funds; a failed transfer reverts the settlement
+        fn pay(&self, escrow: &Escrow, to: AccountId) -> Result<()> {
+            match escrow.token {
+                None => self
+                    .env()
+                    .transfer(to, escrow.amount)
+                    .map_err(|_| Error::TransferFailed),
+                Some(token) => psp22_transfer(token, to, escrow.amount),
+            }
+        }
+
+        /// Current block time in seconds, the unit of escrow timelocks
+        fn now(&self) -> u64 {
+            self.env().block_timestamp() / 1000
+        }
+    }
+
+    fn keccak256(data: &[u8]) -> [u8; 32] {
+        let mut output = [0u8; 32];
+        ink::env::hash_bytes::<Keccak256>(data, &mut output);
+        output
+    }
+
+    fn psp22_transfer(token: AccountId, to: AccountId, amount: Balance) -> Result<()> {
+        build_call::<Environment>()
+            .call(token)
+            .exec_input(
+                ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer")))
+                    .push_arg(to)
+                    .push_arg(amount)
+                    .push_arg(Vec::<u8>::new()),
+            )
+            .returns::<core::result::Result<(), PSP22Error>>()
+            .try_invoke()
+            .map_err(|_| Error::TransferFailed)?
+            .map_err(|_| Error::TransferFailed)?
+            .map_err(|_| Error::TransferFailed)
+    }
+
+    fn psp22_transfer_from(
+        token: AccountId,
+        from: AccountId,
+        to: AccountId,
+        amount: Balance,
+    ) -> Result<()> {
+        build_call::<Environment>()
+            .call(token)
+            .exec_input(
+                ExecutionInput::new(Selector::new(ink::selector_bytes!("PSP22::transfer_from")))
+                    .push_arg(from)
+                    .push_arg(to)
+                    .push_arg(amount)
+                    .push_arg(Vec::<u8>::new()),
+            )
+            .returns::<core::result::Result<(), PSP22Error>>()
+            .try_invoke()
+            .map_err(|_| Error::TransferFailed)?
+            .map_err(|_| Error::TransferFailed)?
+            .map_err(|_| Error::TransferFailed)
+    }
+}
@@ -0,0 +1,296 @@
+#![no_std]
+
+// MultiversX HTLC Escrow
+// Each escrow is a `SingleValueMapper` keyed by `keccak256(order_id)` and holds the EGLD or
+// ESDT payment sent with `createEscrow`. `withdraw` takes the keccak256 preimage, only from
+// the receiver before `exclusive_until`, and `cancel` refunds the sender once the block
+// timestamp reaches the timelock; a failed check aborts the whole call and payouts are direct
+// sends. An optional EGLD safety deposit sent alongside goes to
+// whoever settles the escrow, so resolvers are paid to finish public withdrawals and refunds.
+
+use multiversx_sc::derive_imports::*;
+use multiversx_sc::imports::*;
+
+/// Longest order ID accepted, matching the Stellar contract
+pub const MAX_ORDER_ID_LEN: usize = 128;
+
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, PartialEq, Debug)]
+pub struct Escrow<M: ManagedTypeApi> {
+    pub sender: ManagedAddress<M>,
+    pub receiver: ManagedAddress<M>,
+    pub token: EgldOrEsdtTokenIdentifier<M>,
+    pub token_nonce: u64,
+    pub amount: BigUint<M>,
+    /// EGLD paid to the caller that withdraws or cancels the escrow
+    pub safety_deposit: BigUint<M>,
+    pub secret_hash: ManagedByteArray<M, 32>,
+    /// Seconds after which anyone may refund the sender
+    pub timelock: u64,
+    /// Seconds until which only the receiver may withdraw (0 for no exclusive window)
+    pub exclusive_until: u64,
+    pub order_id: ManagedBuffer<M>,
+    pub created_at: u64,
+}
+
+/// Withdrawal windows, as in the Stellar contract
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Phase {
+    /// Before `exclusive_until`: only the receiver may withdraw
+    Exclusive,
+    /// From `exclusive_until`: anyone with the secret may release funds to the receiver
+    Public,
+}
+
+/// Whether a settled escrow was withdrawn or refunded
+#[type_abi]
+#[derive(TopEncode, TopDecode, NestedEncode, NestedDecode, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettlementStatus {
+    Withdrawn,
+    Cancelled,
+}
+
+#[multiversx_sc::contract]
+pub trait HtlcEscrow {
+    #[init]
+    fn init(&self) {}
+
+    #[upgrade]
+    fn upgrade(&self) {}
+
+    /// Lock the first payment (EGLD or an ESDT) in a new escrow, returning its ID
+    /// A second EGLD payment, if any, is held as the escrow's safety deposit
+    #[payable]
+    #[endpoint(createEscrow)]
+    fn create_escrow(
+        &self,
+        receiver: ManagedAddress,
+        secret_hash: ManagedByteArray<32>,
+        timelock: u64,
+        exclusive_until: u64,
+        order_id: ManagedBuffer,
+    ) -> ManagedByteArray<32> {
+        let payments = self.call_value().all_transfers();
+        require!(
+            !payments.is_empty() && payments.len() <= 2,
+            "Invalid payments"
+        );
+        let payment = payments.get(0).clone();
+        let safety_deposit = if payments.len() == 2 {
+            let deposit = payments.get(1).clone();
+            require!(deposit.token_identifier.is_egld(), "Safety deposit must be EGLD");
+            deposit.amount
+        } else {
+            BigUint::zero()
+        };
+
+        require!(payment.amount > 0u64, "Invalid amount");
+        require!(timelock > self.now(), "Invalid timelock");
+        require!(exclusive_until <= timelock, "Invalid timelock");
+        require!(
+            !order_id.is_empty() && order_id.len() <= MAX_ORDER_ID_LEN,
+            "Invalid order ID"
+        );
+
+        // Same ID as the Stellar contract for the same order
+        let escrow_id = self.crypto().keccak256(&order_id);
+        require!(
+            self.escrows(&escrow_id).is_empty() && self.receipts(&escrow_id).is_empty(),
+            "Escrow already exists"
+        );
+
+        let escrow = Escrow {
+            sender: self.blockchain().get_caller(),
+            receiver,
+            token: payment.token_identifier,
+            token_nonce: payment.token_nonce,
+            amount: payment.amount,
+            safety_deposit,
+            secret_hash,
+            timelock,
+            exclusive_until,
+            order_id,
+            created_at: self.now(),
+        };
+        self.escrow_created_event(&escrow_id, &escrow.sender, &escrow.receiver, &escrow);
+        self.escrows(&escrow_id).set(&escrow);
+
+        escrow_id
+    }
+
+    /// Withdraw everything to the receiver with the secret; the caller takes the safety deposit
+    /// Only the receiver may call during the exclusive window; anyone may afterwards
+    #[endpoint]
+    fn withdraw(&self, escrow_id: ManagedByteArray<32>, secret: ManagedByteArray<32>) {
+        let escrow = self.load_escrow(&escrow_id);
+        require!(
+            self.crypto().keccak256(secret.as_managed_buffer()) == escrow.secret_hash,
+            "Invalid secret"
+        );
+        let caller = self.blockchain().get_caller();
+        if self.now() < escrow.exclusive_until {
+            require!(caller == escrow.receiver, "Unauthorized access");
+        }
+
+        self.escrows(&escrow_id).clear();
+        self.receipts(&escrow_id).set(SettlementStatus::Withdrawn);
+        self.revealed_secrets(&escrow.secret_hash).set(&secret);
+
+        self.escrow_withdrawn_event(&escrow_id, &escrow.receiver, &caller, &secret);
+        self.pay(&escrow, &escrow.receiver, &caller);
+    }
+
+    /// Refund everything to the sender once the timelock has passed; anyone may call and the
+    /// caller takes the safety deposit
+    #[endpoint]
+    fn cancel(&self, escrow_id: ManagedByteArray<32>) {
+        let escrow = self.load_escrow(&escrow_id);
+        require!(self.now() >= escrow.timelock, "Timelock not expired");
+        let caller = self.blockchain().get_caller();
+
+        self.escrows(&escrow_id).clear();
+        self.receipts(&escrow_id).set(SettlementStatus::Cancelled);
+
+        self.escrow_cancelled_event(&escrow_id, &escrow.sender, &caller, &escrow.amount);
+        self.pay(&escrow, &escrow.sender, &caller);
+    }
+
+    /// Get escrow details (only while the escrow is still pending)
+    #[view(getEscrow)]
+    fn get_escrow(&self, escrow_id: ManagedByteArray<32>) -> OptionalValue<Escrow<Self::Api>> {
+        let mapper = self.escrows(&escrow_id);
+        if mapper.is_empty() {
+            OptionalValue::None
+        } else {
+            OptionalValue::Some(mapper.get())
+        }
+    }
+
+    /// How a settled escrow ended, if it has
+    #[view(getReceipt)]
+    fn get_receipt(&self, escrow_id: ManagedByteArray<32>) -> OptionalValue<SettlementStatus> {
+        let mapper = self.receipts(&escrow_id);
+        if mapper.is_empty() {
+            OptionalValue::None
+        } else {
+            OptionalValue::Some(mapper.get())
+        }
+    }
+
+    /// Secret revealed by a withdrawal, for counterpart-chain relayers
+    #[view(getRevealedSecret)]
+    fn get_revealed_secret(
+        &self,
+        secret_hash: ManagedByteArray<32>,
+    ) -> OptionalValue<ManagedByteArray<32>> {
+        let mapper = self.revealed_secrets(&secret_hash);
+        if mapper.is_empty() {
+            OptionalValue::None
+        } else {
+            OptionalValue::Some(mapper.get())
+        }
+    }
+
+    /// Whether a secret opens a pending escrow
+    #[view(verifySecret)]
+    fn verify_secret(&self, escrow_id: ManagedByteArray<32>, secret: ManagedByteArray<32>) -> bool {
+        let mapper = self.escrows(&escrow_id);
+        !mapper.is_empty()
+            && self.crypto().keccak256(secret.as_managed_buffer()) == mapper.get().secret_hash
+    }
+
+    /// Check if escrow can be withdrawn in the given phase
+    #[view(canWithdraw)]
+    fn can_withdraw(&self, escrow_id: ManagedByteArray<32>, phase: Phase) -> bool {
+        let mapper = self.escrows(&escrow_id);
+        if mapper.is_empty() {
+            return false;
+        }
+        let exclusive_until = mapper.get().exclusive_until;
+        match phase {
+            Phase::Exclusive => self.now() < exclusive_until,
+            Phase::Public => self.now() >= exclusive_until,
+        }
+    }
+
+    /// Whether a pending escrow's timelock has passed
+    #[view(canCancel)]
+    fn can_cancel(&self, escrow_id: ManagedByteArray<32>) -> bool {
+        let mapper = self.escrows(&escrow_id);
+        !mapper.is_empty() && self.now() >= mapper.get().timelock
+    }
+
+    /// Utility function to generate secret hash
+    #[view(generateSecretHash)]
+    fn generate_secret_hash(&self, secret: ManagedByteArray<32>) -> ManagedByteArray<32> {
+        self.crypto().keccak256(secret.as_managed_buffer())
+    }
+
+    fn load_escrow(&self, escrow_id: &ManagedByteArray<32>) -> Escrow<Self::Api> {
+        let mapper = self.escrows(escrow_id);
+        if !mapper.is_empty() {
+            return mapper.get();
+        }
+        let receipt = self.receipts(escrow_id);
+        require!(
+            receipt.is_empty() || receipt.get() != SettlementStatus::Withdrawn,
+            "Already withdrawn"
+        );
+        require!(receipt.is_empty(), "Already cancelled");
+        sc_panic!("Escrow not found")
+    }
+
+    /// Send an escrow's funds to `to` and its safety deposit to `caller`
+    fn pay(&self, escrow: &Escrow<Self::Api>, to: &ManagedAddress, caller: &ManagedAddress) {
+        self.send()
+            .direct(to, &escrow.token, escrow.token_nonce, &escrow.amount);
+        self.send()
+            .direct_non_zero_egld(caller, &escrow.safety_deposit);
+    }
+
+    /// Current block time in seconds, the unit of escrow timelocks
+    fn now(&self) -> u64 {
+        self.blockchain().get_block_timestamp_seconds().as_u64_seconds()
+    }
+
+    #[storage_mapper("escrows")]
+    fn escrows(&self, escrow_id: &ManagedByteArray<32>) -> SingleValueMapper<Escrow<Self::Api>>;
+
+    #[storage_mapper("receipts")]
+    fn receipts(&self, escrow_id: &ManagedByteArray<32>) -> SingleValueMapper<SettlementStatus>;
+
+    #[storage_mapper("revealedSecrets")]
+    fn revealed_secrets(
+        &self,
+        secret_hash: &ManagedByteArray<32>,
+    ) -> SingleValueMapper<ManagedByteArray<32>>;
+
+    #[event("escrowCreated")]
+    fn escrow_created_event(
+        &self,
+        #[indexed] escrow_id: &ManagedByteArray<32>,
+        #[indexed] sender: &ManagedAddress,
+        #[indexed] receiver: &ManagedAddress,
+        escrow: &Escrow<Self::Api>,
+    );
+
+    /// Carries the secret so relayers can complete the counterpart escrow
+    #[event("escrowWithdrawn")]
+    fn escrow_withdrawn_event(
+        &self,
+        #[indexed] escrow_id: &ManagedByteArray<32>,
+        #[indexed] receiver: &ManagedAddress,
+        #[indexed] caller: &ManagedAddress,
+        secret: &ManagedByteArray<32>,
+    );
+
+    #[event("escrowCancelled")]
+    fn escrow_cancelled_event(
+        &self,
+        #[indexed] escrow_id: &ManagedByteArray<32>,
+        #[indexed] sender: &ManagedAddress,
+        #[indexed] caller: &ManagedAddress,
+        amount: &BigUint,
+    );
+}
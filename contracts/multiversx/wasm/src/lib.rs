@@ -0,0 +1,36 @@
+// Code generated by the multiversx-sc build system. DO NOT EDIT.
+
+////////////////////////////////////////////////////
+////////////////// AUTO-GENERATED //////////////////
+////////////////////////////////////////////////////
+
+// Init:                                 1
+// Upgrade:                              1
+// Endpoints:                           10
+// Async Callback (empty):               1
+// Total number of exported functions:  13
+
+#![no_std]
+
+multiversx_sc_wasm_adapter::allocator!();
+multiversx_sc_wasm_adapter::panic_handler!();
+
+multiversx_sc_wasm_adapter::endpoints! {
+    htlc_escrow_multiversx
+    (
+        init => init
+        upgrade => upgrade
+        createEscrow => create_escrow
+        withdraw => withdraw
+        cancel => cancel
+        getEscrow => get_escrow
+        getReceipt => get_receipt
+        getRevealedSecret => get_revealed_secret
+        verifySecret => verify_secret
+        canWithdraw => can_withdraw
+        canCancel => can_cancel
+        generateSecretHash => generate_secret_hash
+    )
+}
+
+multiversx_sc_wasm_adapter::async_callback_empty! {}
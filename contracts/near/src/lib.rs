@@ -0,0 +1,526 @@
+// NEAR HTLC Escrow
+// Mirrors the Stellar escrow: keccak256 hashlocks, an exclusive window in which only the
+// receiver may withdraw, a public window after it and sender refunds once the timelock passes.
+// Escrows lock native NEAR (`create_escrow`) or a NEP-141 token sent with `ft_transfer_call`.
+// Senders pay for the storage their escrows take from a NEP-145 storage deposit: creating an
+// escrow charges its bytes, and paying it out returns them, less the receipt it leaves.
+
+use near_sdk::json_types::{Base64VecU8, U128};
+use near_sdk::serde_json::{self, json};
+use near_sdk::store::LookupMap;
+use near_sdk::{
+    assert_one_yocto, env, ext_contract, near, require, AccountId, BorshStorageKey, Gas, NearToken,
+    PanicOnDefault, Promise, PromiseError, PromiseOrValue, StorageUsage,
+};
+
+/// Event standard name used in NEP-297 logs
+const EVENT_STANDARD: &str = "htlc";
+const EVENT_VERSION: &str = "1.0.0";
+
+/// Longest order ID accepted, matching the Stellar contract
+const MAX_ORDER_ID_LEN: usize = 128;
+
+const GAS_FOR_FT_TRANSFER: Gas = Gas::from_tgas(10);
+const GAS_FOR_PAYOUT_CALLBACK: Gas = Gas::from_tgas(10);
+
+/// Terms of a new escrow; for NEP-141 escrows this is the JSON `msg` of `ft_transfer_call`
+#[near(serializers = [json])]
+#[derive(Clone)]
+pub struct EscrowParams {
+    pub receiver: AccountId,
+    /// keccak256 of the 32-byte secret
+    pub secret_hash: Base64VecU8,
+    /// Seconds after which the sender may cancel
+    pub timelock: u64,
+    /// Seconds until which only the receiver may withdraw (0 for no exclusive window)
+    pub exclusive_until: u64,
+    pub order_id: String,
+}
+
+#[near(serializers = [borsh, json])]
+#[derive(Clone)]
+pub struct Escrow {
+    pub sender: AccountId,
+    pub receiver: AccountId,
+    pub amount: U128,
+    pub secret_hash: Base64VecU8,
+    pub timelock: u64,
+    pub exclusive_until: u64,
+    /// NEP-141 token contract, or `None` for native NEAR
+    pub token: Option<AccountId>,
+    pub order_id: String,
+    pub created_at: u64,
+}
+
+/// An account's NEP-145 storage deposit; `available` is what its escrows have not taken
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StorageBalance {
+    pub total: U128,
+    pub available: U128,
+}
+
+#[near(serializers = [json])]
+pub struct StorageBalanceBounds {
+    pub min: U128,
+    pub max: Option<U128>,
+}
+
+/// Whether a settled escrow was withdrawn or refunded
+#[near(serializers = [borsh, json])]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettlementStatus {
+    Withdrawn,
+    Cancelled,
+}
+
+#[near]
+#[derive(BorshStorageKey)]
+enum StorageKey {
+    Escrows,
+    Receipts,
+    RevealedSecrets,
+    StorageDeposits,
+}
+
+#[near(contract_state)]
+#[derive(PanicOnDefault)]
+pub struct HtlcEscrow {
+    escrows: LookupMap<Vec<u8>, Escrow>,
+    receipts: LookupMap<Vec<u8>, SettlementStatus>,
+    revealed_secrets: LookupMap<Vec<u8>, Vec<u8>>,
+    storage_deposits: LookupMap<AccountId, StorageBalance>,
+    /// Bytes of one account's storage deposit entry, what registering costs
+    account_storage_usage: StorageUsage,
+}
+
+#[ext_contract(ext_ft)]
+#[allow(dead_code)]
+trait FungibleToken {
+    fn ft_transfer(&mut self, receiver_id: AccountId, amount: U128, memo: Option<String>);
+}
+
+#[near]
+impl HtlcEscrow {
+    #[init]
+    pub fn new() -> Self {
+        let mut this = Self {
+            escrows: LookupMap::new(StorageKey::Escrows),
+            receipts: LookupMap::new(StorageKey::Receipts),
+            revealed_secrets: LookupMap::new(StorageKey::RevealedSecrets),
+            storage_deposits: LookupMap::new(StorageKey::StorageDeposits),
+            account_storage_usage: 0,
+        };
+        this.measure_account_storage_usage();
+        this
+    }
+
+    /// Lock the attached NEAR in a new escrow, returning its ID
+    /// The sender's storage deposit must cover the escrow's storage
+    #[payable]
+    pub fn create_escrow(&mut self, params: EscrowParams) -> Base64VecU8 {
+        let amount = env::attached_deposit().as_yoctonear();
+        self.open_escrow(env::predecessor_account_id(), amount, None, params)
+    }
+
+    /// NEP-141 receiver hook: lock the transferred tokens in an escrow described by `msg`
+    /// Without enough storage deposit from the sender the call fails and the tokens return
+    pub fn ft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        amount: U128,
+        msg: String,
+    ) -> PromiseOrValue<U128> {
+        let params: EscrowParams =
+            serde_json::from_str(&msg).unwrap_or_else(|_| env::panic_str("Invalid escrow params"));
+        let token = env::predecessor_account_id();
+        self.open_escrow(sender_id, amount.0, Some(token), params);
+        PromiseOrValue::Value(U128(0))
+    }
+
+    /// Withdraw everything to the receiver with the secret
+    /// Only the receiver may call during the exclusive window; anyone may afterwards
+    pub fn withdraw(&mut self, escrow_id: Base64VecU8, secret: Base64VecU8) -> Promise {
+        let escrow = self.load_escrow(&escrow_id.0);
+        require!(secret.0.len() == 32, "Invalid secret");
+        require!(
+            env::keccak256_array(&secret.0).as_slice() == escrow.secret_hash.0.as_slice(),
+            "Invalid secret"
+        );
+        if now() < escrow.exclusive_until {
+            require!(env::predecessor_account_id() == escrow.receiver, "Unauthorized access");
+        }
+
+        let before = env::storage_usage();
+        self.escrows.remove(&escrow_id.0);
+        self.receipts.insert(escrow_id.0.clone(), SettlementStatus::Withdrawn);
+        self.revealed_secrets.insert(escrow.secret_hash.0.clone(), secret.0.clone());
+        self.flush_escrows();
+        let freed = before.saturating_sub(env::storage_usage());
+
+        emit(
+            "escrow_withdrawn",
+            json!({
+                "escrow_id": escrow_id,
+                "receiver": escrow.receiver,
+                "amount": escrow.amount,
+                "secret": secret,
+            }),
+        );
+
+        let to = escrow.receiver.clone();
+        self.pay(escrow_id, escrow, to, freed)
+    }
+
+    /// Refund everything to the sender once the timelock has passed; anyone may call
+    pub fn cancel(&mut self, escrow_id: Base64VecU8) -> Promise {
+        let escrow = self.load_escrow(&escrow_id.0);
+        require!(now() >= escrow.timelock, "Timelock not expired");
+
+        let before = env::storage_usage();
+        self.escrows.remove(&escrow_id.0);
+        self.receipts.insert(escrow_id.0.clone(), SettlementStatus::Cancelled);
+        self.flush_escrows();
+        let freed = before.saturating_sub(env::storage_usage());
+
+        emit(
+            "escrow_cancelled",
+            json!({
+                "escrow_id": escrow_id,
+                "sender": escrow.sender,
+                "amount": escrow.amount,
+            }),
+        );
+
+        let to = escrow.sender.clone();
+        self.pay(escrow_id, escrow, to, freed)
+    }
+
+    /// Return the `freed` bytes to the sender's storage deposit once an escrow is paid out, or
+    /// restore an escrow whose payout transfer failed so it can be retried
+    #[private]
+    pub fn on_payout(
+        &mut self,
+        escrow_id: Base64VecU8,
+        escrow: Escrow,
+        freed: StorageUsage,
+        #[callback_result] result: Result<(), PromiseError>,
+    ) -> bool {
+        if result.is_ok() {
+            self.release_storage(&escrow.sender, freed);
+            return true;
+        }
+        if self.receipts.remove(&escrow_id.0) == Some(SettlementStatus::Withdrawn) {
+            self.revealed_secrets.remove(&escrow.secret_hash.0);
+        }
+        self.escrows.insert(escrow_id.0.clone(), escrow);
+        emit("payout_failed", json!({ "escrow_id": escrow_id }));
+        false
+    }
+
+    /// Get escrow details (only while the escrow is still pending)
+    pub fn get_escrow(&self, escrow_id: Base64VecU8) -> Option<Escrow> {
+        self.escrows.get(&escrow_id.0).cloned()
+    }
+
+    /// How a settled escrow ended, if it has
+    pub fn get_receipt(&self, escrow_id: Base64VecU8) -> Option<SettlementStatus> {
+        self.receipts.get(&escrow_id.0).copied()
+    }
+
+    /// Secret revealed by a withdrawal, for counterpart-chain relayers
+    pub fn get_revealed_secret(&self, secret_hash: Base64VecU8) -> Option<Base64VecU8> {
+        self.revealed_secrets
+            .get(&secret_hash.0)
+            .map(|secret| Base64VecU8(secret.clone()))
+    }
+
+    /// Whether a secret opens a pending escrow
+    pub fn verify_secret(&self, escrow_id: Base64VecU8, secret: Base64VecU8) -> bool {
+        self.escrows.get(&escrow_id.0).is_some_and(|escrow| {
+            env::keccak256_array(&secret.0).as_slice() == escrow.secret_hash.0.as_slice()
+        })
+    }
+
+    /// Whether a pending escrow's timelock has passed
+    pub fn can_cancel(&self, escrow_id: Base64VecU8) -> bool {
+        self.escrows
+            .get(&escrow_id.0)
+            .is_some_and(|escrow| now() >= escrow.timelock)
+    }
+
+    /// Utility function to generate secret hash
+    pub fn generate_secret_hash(&self, secret: Base64VecU8) -> Base64VecU8 {
+        Base64VecU8(env::keccak256_array(&secret.0).to_vec())
+    }
+
+    /// NEP-145: register `account_id` (the caller by default) and add the attached deposit to
+    /// its storage balance; with `registration_only`, anything above the minimum is refunded
+    #[payable]
+    pub fn storage_deposit(
+        &mut self,
+        account_id: Option<AccountId>,
+        registration_only: Option<bool>,
+    ) -> StorageBalance {
+        let amount = env::attached_deposit().as_yoctonear();
+        let account_id = account_id.unwrap_or_else(env::predecessor_account_id);
+        let registration_only = registration_only.unwrap_or(false);
+        let min = self.storage_balance_bounds().min.0;
+
+        let (balance, refund) = match self.storage_deposits.get(&account_id) {
+            Some(balance) if registration_only => (balance.clone(), amount),
+            Some(balance) => (
+                StorageBalance {
+                    total: U128(balance.total.0 + amount),
+                    available: U128(balance.available.0 + amount),
+                },
+                0,
+            ),
+            None => {
+                require!(
+                    amount >= min,
+                    "Attached deposit is less than the minimum storage balance"
+                );
+                let kept = if registration_only { min } else { amount };
+                let balance = StorageBalance {
+                    total: U128(kept),
+                    available: U128(kept - min),
+                };
+                (balance, amount - kept)
+            }
+        };
+        self.storage_deposits.insert(account_id, balance.clone());
+        if refund > 0 {
+            Promise::new(env::predecessor_account_id()).transfer(NearToken::from_yoctonear(refund))
+                .detach();
+        }
+        balance
+    }
+
+    /// NEP-145: withdraw `amount` (everything available by default) of the caller's storage
+    /// deposit that its escrows do not take
+    #[payable]
+    pub fn storage_withdraw(&mut self, amount: Option<U128>) -> StorageBalance {
+        assert_one_yocto();
+        let account_id = env::predecessor_account_id();
+        let mut balance = self
+            .storage_deposits
+            .get(&account_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("The account is not registered"));
+        let amount = amount.map_or(balance.available.0, |amount| amount.0);
+        require!(
+            amount <= balance.available.0,
+            "The amount is greater than the available storage balance"
+        );
+
+        balance.total = U128(balance.total.0 - amount);
+        balance.available = U128(balance.available.0 - amount);
+        self.storage_deposits
+            .insert(account_id.clone(), balance.clone());
+        if amount > 0 {
+            Promise::new(account_id)
+                .transfer(NearToken::from_yoctonear(amount))
+                .detach();
+        }
+        balance
+    }
+
+    /// NEP-145: unregister the caller and refund its whole deposit, returning whether it was
+    /// registered
+    /// Only an account whose escrows and receipts take none of the deposit can leave, and
+    /// `force` is not supported since it would strand the escrows' storage
+    #[payable]
+    pub fn storage_unregister(&mut self, force: Option<bool>) -> bool {
+        assert_one_yocto();
+        require!(
+            !force.unwrap_or(false),
+            "Forced unregistration is not supported"
+        );
+        let account_id = env::predecessor_account_id();
+        let Some(balance) = self.storage_deposits.get(&account_id).cloned() else {
+            return false;
+        };
+        let min = self.storage_balance_bounds().min.0;
+        require!(
+            balance.total.0 - balance.available.0 == min,
+            "The account's escrows still take storage"
+        );
+
+        self.storage_deposits.remove(&account_id);
+        Promise::new(account_id)
+            .transfer(NearToken::from_yoctonear(balance.total.0))
+            .detach();
+        true
+    }
+
+    /// NEP-145: registering costs `min`; there is no maximum
+    pub fn storage_balance_bounds(&self) -> StorageBalanceBounds {
+        let min = env::storage_byte_cost().as_yoctonear() * self.account_storage_usage as u128;
+        StorageBalanceBounds {
+            min: U128(min),
+            max: None,
+        }
+    }
+
+    pub fn storage_balance_of(&self, account_id: AccountId) -> Option<StorageBalance> {
+        self.storage_deposits.get(&account_id).cloned()
+    }
+}
+
+impl HtlcEscrow {
+    fn open_escrow(
+        &mut self,
+        sender: AccountId,
+        amount: u128,
+        token: Option<AccountId>,
+        params: EscrowParams,
+    ) -> Base64VecU8 {
+        let EscrowParams {
+            receiver,
+            secret_hash,
+            timelock,
+            exclusive_until,
+            order_id,
+        } = params;
+
+        require!(amount > 0, "Invalid amount");
+        require!(secret_hash.0.len() == 32, "Invalid secret hash");
+        require!(timelock > now(), "Invalid timelock");
+        require!(exclusive_until <= timelock, "Invalid timelock");
+        require!(
+            !order_id.is_empty() && order_id.len() <= MAX_ORDER_ID_LEN,
+            "Invalid order ID"
+        );
+
+        // Same ID as the Stellar contract for the same order
+        let escrow_id = env::keccak256_array(order_id.as_bytes()).to_vec();
+        require!(
+            !self.escrows.contains_key(&escrow_id) && !self.receipts.contains_key(&escrow_id),
+            "Escrow already exists"
+        );
+
+        let before = env::storage_usage();
+        let escrow = Escrow {
+            sender: sender.clone(),
+            receiver,
+            amount: U128(amount),
+            secret_hash,
+            timelock,
+            exclusive_until,
+            token,
+            order_id,
+            created_at: now(),
+        };
+        emit(
+            "escrow_created",
+            json!({
+                "escrow_id": Base64VecU8(escrow_id.clone()),
+                "sender": escrow.sender,
+                "receiver": escrow.receiver,
+                "amount": escrow.amount,
+                "secret_hash": escrow.secret_hash,
+                "timelock": escrow.timelock,
+                "token": escrow.token,
+                "order_id": escrow.order_id,
+            }),
+        );
+        self.escrows.insert(escrow_id.clone(), escrow);
+        self.flush_escrows();
+        self.charge_storage(&sender, env::storage_usage() - before);
+
+        Base64VecU8(escrow_id)
+    }
+
+    /// Write the escrow maps' cached changes now, so `env::storage_usage` counts them
+    fn flush_escrows(&mut self) {
+        self.escrows.flush();
+        self.receipts.flush();
+        self.revealed_secrets.flush();
+    }
+
+    /// Take `bytes` of storage from an account's deposit
+    fn charge_storage(&mut self, account_id: &AccountId, bytes: StorageUsage) {
+        let cost = env::storage_byte_cost().as_yoctonear() * bytes as u128;
+        let mut balance = self
+            .storage_deposits
+            .get(account_id)
+            .cloned()
+            .unwrap_or_else(|| env::panic_str("The sender has no storage deposit"));
+        require!(balance.available.0 >= cost, "Insufficient storage deposit");
+        balance.available = U128(balance.available.0 - cost);
+        self.storage_deposits.insert(account_id.clone(), balance);
+    }
+
+    /// Return `bytes` of storage to an account's deposit
+    fn release_storage(&mut self, account_id: &AccountId, bytes: StorageUsage) {
+        let cost = env::storage_byte_cost().as_yoctonear() * bytes as u128;
+        if let Some(balance) = self.storage_deposits.get_mut(account_id) {
+            balance.available = U128((balance.available.0 + cost).min(balance.total.0));
+        }
+    }
+
+    /// Record what one storage deposit entry costs, measured with the longest account ID
+    fn measure_account_storage_usage(&mut self) {
+        let before = env::storage_usage();
+        let account_id: AccountId = "a".repeat(64).parse().unwrap();
+        let balance = StorageBalance {
+            total: U128(0),
+            available: U128(0),
+        };
+        self.storage_deposits.insert(account_id.clone(), balance);
+        self.storage_deposits.flush();
+        self.account_storage_usage = env::storage_usage() - before;
+        self.storage_deposits.remove(&account_id);
+        self.storage_deposits.flush();
+    }
+
+    fn load_escrow(&self, escrow_id: &Vec<u8>) -> Escrow {
+        match self.escrows.get(escrow_id) {
+            Some(escrow) => escrow.clone(),
+            None => match self.receipts.get(escrow_id) {
+                Some(SettlementStatus::Withdrawn) => env::panic_str("Already withdrawn"),
+                Some(SettlementStatus::Cancelled) => env::panic_str("Already cancelled"),
+                None => env::panic_str("Escrow not found"),
+            },
+        }
+    }
+
+    /// Send an escrow's funds, restoring the escrow in `on_payout` if the transfer fails
+    fn pay(
+        &self,
+        escrow_id: Base64VecU8,
+        escrow: Escrow,
+        to: AccountId,
+        freed: StorageUsage,
+    ) -> Promise {
+        let transfer = match &escrow.token {
+            None => Promise::new(to).transfer(NearToken::from_yoctonear(escrow.amount.0)),
+            Some(token) => ext_ft::ext(token.clone())
+                .with_attached_deposit(NearToken::from_yoctonear(1))
+                .with_static_gas(GAS_FOR_FT_TRANSFER)
+                .ft_transfer(to, escrow.amount, Some(escrow.order_id.clone())),
+        };
+        transfer.then(
+            Self::ext(env::current_account_id())
+                .with_static_gas(GAS_FOR_PAYOUT_CALLBACK)
+                .on_payout(escrow_id, escrow, freed),
+        )
+    }
+}
+
+/// Current block time in seconds, the unit of escrow timelocks
+fn now() -> u64 {
+    env::block_timestamp_ms() / 1000
+}
+
+/// Log a NEP-297 event
+fn emit(event: &str, data: serde_json::Value) {
+    let log = json!({
+        "standard": EVENT_STANDARD,
+        "version": EVENT_VERSION,
+        "event": event,
+        "data": [data],
+    });
+    env::log_str(&format!("EVENT_JSON:{}", log));
+}
@@ -0,0 +1,428 @@
+// Solana HTLC Escrow (Anchor)
+// Each escrow is a PDA holding its terms, with a PDA-owned SPL token vault. Like the Stellar
+// contract it has an exclusive window for the receiver, a public window after it, refunds once
+// the timelock passes and Merkle partial fills over `parts + 1` secrets. Hashlocks are sha256.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, CloseAccount, Mint, Token, TokenAccount, Transfer};
+use solana_keccak_hasher as keccak;
+use solana_sha256_hasher as hash;
+
+declare_id!("HTLCEsc1111111111111111111111111111111111111");
+
+/// Longest order ID accepted, matching the Stellar contract
+pub const MAX_ORDER_ID_LEN: usize = 128;
+
+/// Most parts an order can be split into
+pub const MAX_PARTS: u32 = 64;
+
+pub const ESCROW_SEED: &[u8] = b"escrow";
+pub const VAULT_SEED: &[u8] = b"vault";
+
+#[program]
+pub mod htlc_escrow {
+    use super::*;
+
+    /// Lock `amount` of the mint in a new escrow keyed by `keccak256(order_id)`, the same ID
+    /// the Stellar contract derives for the order
+    pub fn create_escrow(ctx: Context<CreateEscrow>, params: EscrowParams) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        require!(params.amount > 0, HtlcError::InvalidAmount);
+        require!(params.timelock > now, HtlcError::InvalidTimelock);
+        require!(params.exclusive_until <= params.timelock, HtlcError::InvalidTimelock);
+        require!(
+            !params.order_id.is_empty() && params.order_id.len() <= MAX_ORDER_ID_LEN,
+            HtlcError::InvalidOrderId
+        );
+        require!(params.parts <= MAX_PARTS, HtlcError::InvalidFillIndex);
+        require!(
+            params.escrow_id == keccak::hash(params.order_id.as_bytes()).to_bytes(),
+            HtlcError::InvalidOrderId
+        );
+
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.sender = ctx.accounts.sender.key();
+        escrow.receiver = params.receiver;
+        escrow.mint = ctx.accounts.mint.key();
+        escrow.escrow_id = params.escrow_id;
+        escrow.amount = params.amount;
+        escrow.filled_amount = 0;
+        escrow.secret_hash = params.secret_hash;
+        escrow.timelock = params.timelock;
+        escrow.exclusive_until = params.exclusive_until;
+        escrow.parts = params.parts;
+        escrow.last_fill_index = None;
+        escrow.order_id = params.order_id;
+        escrow.created_at = now;
+        escrow.bump = ctx.bumps.escrow;
+        escrow.vault_bump = ctx.bumps.vault;
+
+        token::transfer(
+            CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.sender_token.to_account_info(),
+                    to: ctx.accounts.vault.to_account_info(),
+                    authority: ctx.accounts.sender.to_account_info(),
+                },
+            ),
+            params.amount,
+        )?;
+
+        emit_cpi!(EscrowCreated {
+            escrow_id: escrow.escrow_id,
+            sender: escrow.sender,
+            receiver: escrow.receiver,
+            mint: escrow.mint,
+            amount: escrow.amount,
+            secret_hash: escrow.secret_hash,
+            timelock: escrow.timelock,
+            exclusive_until: escrow.exclusive_until,
+            parts: escrow.parts,
+            order_id: escrow.order_id.clone(),
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw everything left to the receiver with the secret, closing the escrow
+    /// Only the receiver may submit during the exclusive window; anyone may afterwards
+    pub fn withdraw(ctx: Context<Settle>, secret: [u8; 32]) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        require!(escrow.parts == 0, HtlcError::InvalidFillIndex);
+        require!(hash::hash(&secret).to_bytes() == escrow.secret_hash, HtlcError::InvalidSecret);
+        authorize_withdrawal(escrow, &ctx.accounts.caller)?;
+
+        let amount = escrow.amount - escrow.filled_amount;
+        release(ctx.accounts, amount)?;
+        close_escrow(ctx.accounts)?;
+
+        emit_cpi!(EscrowWithdrawn {
+            escrow_id: ctx.accounts.escrow.escrow_id,
+            receiver: ctx.accounts.escrow.receiver,
+            amount,
+            secret,
+            fill_index: None,
+        });
+
+        Ok(())
+    }
+
+    /// Withdraw one slice of a partially fillable escrow with the secret at `fill_index`
+    /// The index must match the cumulative fill: part `i` covers fills up to
+    /// `(i + 1) / parts` of the amount and the extra secret at `parts` completes the order
+    pub fn withdraw_partial(
+        ctx: Context<Settle>,
+        secret: [u8; 32],
+        fill_index: u32,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        require!(escrow.parts > 0, HtlcError::InvalidFillIndex);
+        let filled_after = escrow
+            .filled_amount
+            .checked_add(amount)
+            .ok_or(HtlcError::InvalidFillAmount)?;
+        require!(amount > 0 && filled_after <= escrow.amount, HtlcError::InvalidFillAmount);
+
+        // Verify the secret index matches the fill and has not been used before
+        let expected_index = if filled_after == escrow.amount {
+            escrow.parts
+        } else {
+            ((filled_after - 1) as u128 * escrow.parts as u128 / escrow.amount as u128) as u32
+        };
+        require!(
+            fill_index == expected_index
+                && escrow.last_fill_index.is_none_or(|last| fill_index > last),
+            HtlcError::InvalidFillIndex
+        );
+
+        // Verify the secret belongs to the committed tree
+        let leaf = merkle_leaf(fill_index, &hash::hash(&secret).to_bytes());
+        require!(merkle_root(leaf, &proof) == escrow.secret_hash, HtlcError::InvalidMerkleProof);
+        authorize_withdrawal(escrow, &ctx.accounts.caller)?;
+
+        release(ctx.accounts, amount)?;
+        let escrow = &mut ctx.accounts.escrow;
+        escrow.filled_amount = filled_after;
+        escrow.last_fill_index = Some(fill_index);
+        let (escrow_id, receiver) = (escrow.escrow_id, escrow.receiver);
+        if filled_after == escrow.amount {
+            close_escrow(ctx.accounts)?;
+        }
+
+        emit_cpi!(EscrowWithdrawn {
+            escrow_id,
+            receiver,
+            amount,
+            secret,
+            fill_index: Some(fill_index),
+        });
+
+        Ok(())
+    }
+
+    /// Refund the unfilled remainder to the sender once the timelock has passed, closing the
+    /// escrow; anyone may submit
+    pub fn cancel(ctx: Context<Cancel>) -> Result<()> {
+        let escrow = &ctx.accounts.escrow;
+        require!(
+            Clock::get()?.unix_timestamp >= escrow.timelock,
+            HtlcError::TimelockNotExpired
+        );
+
+        let amount = escrow.amount - escrow.filled_amount;
+        let escrow_id = escrow.escrow_id;
+        let seeds: &[&[u8]] = &[ESCROW_SEED, &escrow_id, &[escrow.bump]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                Transfer {
+                    from: ctx.accounts.vault.to_account_info(),
+                    to: ctx.accounts.sender_token.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                &[seeds],
+            ),
+            amount,
+        )?;
+        token::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            CloseAccount {
+                account: ctx.accounts.vault.to_account_info(),
+                destination: ctx.accounts.sender.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            &[seeds],
+        ))?;
+
+        emit_cpi!(EscrowCancelled {
+            escrow_id,
+            sender: ctx.accounts.escrow.sender,
+            amount,
+        });
+
+        Ok(())
+    }
+}
+
+/// Terms of a new escrow, passed to `create_escrow`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct EscrowParams {
+    /// `keccak256(order_id)`, the seed of the escrow PDA
+    pub escrow_id: [u8; 32],
+    pub receiver: Pubkey,
+    pub amount: u64,
+    /// sha256 hashlock, or the Merkle root of `parts + 1` secret hashes for partial fills
+    pub secret_hash: [u8; 32],
+    /// Unix time after which the sender may cancel
+    pub timelock: i64,
+    /// Unix time until which only the receiver may withdraw
+    pub exclusive_until: i64,
+    /// Number of parts the order can be filled in (0 for a single-secret escrow)
+    pub parts: u32,
+    pub order_id: String,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Escrow {
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub escrow_id: [u8; 32],
+    pub amount: u64,
+    pub filled_amount: u64,
+    pub secret_hash: [u8; 32],
+    pub timelock: i64,
+    pub exclusive_until: i64,
+    pub parts: u32,
+    /// Index of the last Merkle secret used by `withdraw_partial`
+    pub last_fill_index: Option<u32>,
+    #[max_len(MAX_ORDER_ID_LEN)]
+    pub order_id: String,
+    pub created_at: i64,
+    pub bump: u8,
+    pub vault_bump: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(params: EscrowParams)]
+pub struct CreateEscrow<'info> {
+    #[account(mut)]
+    pub sender: Signer<'info>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut, token::mint = mint, token::authority = sender)]
+    pub sender_token: Account<'info, TokenAccount>,
+    #[account(
+        init,
+        payer = sender,
+        space = 8 + Escrow::INIT_SPACE,
+        seeds = [ESCROW_SEED, params.escrow_id.as_ref()],
+        bump
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(
+        init,
+        payer = sender,
+        seeds = [VAULT_SEED, params.escrow_id.as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = escrow
+    )]
+    pub vault: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Settle<'info> {
+    pub caller: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.escrow_id.as_ref()],
+        bump = escrow.bump,
+        has_one = sender
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut, seeds = [VAULT_SEED, escrow.escrow_id.as_ref()], bump = escrow.vault_bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = escrow.mint, token::authority = escrow.receiver)]
+    pub receiver_token: Account<'info, TokenAccount>,
+    /// CHECK: rent from the closed accounts is returned to the sender who paid it
+    #[account(mut)]
+    pub sender: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Cancel<'info> {
+    #[account(
+        mut,
+        seeds = [ESCROW_SEED, escrow.escrow_id.as_ref()],
+        bump = escrow.bump,
+        has_one = sender,
+        close = sender
+    )]
+    pub escrow: Account<'info, Escrow>,
+    #[account(mut, seeds = [VAULT_SEED, escrow.escrow_id.as_ref()], bump = escrow.vault_bump)]
+    pub vault: Account<'info, TokenAccount>,
+    #[account(mut, token::mint = escrow.mint, token::authority = escrow.sender)]
+    pub sender_token: Account<'info, TokenAccount>,
+    /// CHECK: checked against the escrow with `has_one`
+    #[account(mut)]
+    pub sender: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[event]
+pub struct EscrowCreated {
+    pub escrow_id: [u8; 32],
+    pub sender: Pubkey,
+    pub receiver: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub secret_hash: [u8; 32],
+    pub timelock: i64,
+    pub exclusive_until: i64,
+    pub parts: u32,
+    pub order_id: String,
+}
+
+/// Published with the secret so counterpart-chain relayers can complete the swap
+#[event]
+pub struct EscrowWithdrawn {
+    pub escrow_id: [u8; 32],
+    pub receiver: Pubkey,
+    pub amount: u64,
+    pub secret: [u8; 32],
+    pub fill_index: Option<u32>,
+}
+
+#[event]
+pub struct EscrowCancelled {
+    pub escrow_id: [u8; 32],
+    pub sender: Pubkey,
+    pub amount: u64,
+}
+
+#[error_code]
+pub enum HtlcError {
+    #[msg("Invalid amount")]
+    InvalidAmount,
+    #[msg("Invalid timelock")]
+    InvalidTimelock,
+    #[msg("Invalid order ID")]
+    InvalidOrderId,
+    #[msg("Invalid secret")]
+    InvalidSecret,
+    #[msg("Timelock not expired")]
+    TimelockNotExpired,
+    #[msg("Unauthorized access")]
+    UnauthorizedAccess,
+    #[msg("Invalid fill amount")]
+    InvalidFillAmount,
+    #[msg("Invalid fill index")]
+    InvalidFillIndex,
+    #[msg("Invalid Merkle proof")]
+    InvalidMerkleProof,
+}
+
+/// Require the receiver's signature during the exclusive window
+fn authorize_withdrawal(escrow: &Escrow, caller: &Signer) -> Result<()> {
+    if Clock::get()?.unix_timestamp < escrow.exclusive_until {
+        require_keys_eq!(caller.key(), escrow.receiver, HtlcError::UnauthorizedAccess);
+    }
+    Ok(())
+}
+
+/// Pay `amount` out of the vault to the receiver, signed by the escrow PDA
+fn release(accounts: &Settle, amount: u64) -> Result<()> {
+    let escrow = &accounts.escrow;
+    let seeds: &[&[u8]] = &[ESCROW_SEED, &escrow.escrow_id, &[escrow.bump]];
+    token::transfer(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            Transfer {
+                from: accounts.vault.to_account_info(),
+                to: accounts.receiver_token.to_account_info(),
+                authority: escrow.to_account_info(),
+            },
+            &[seeds],
+        ),
+        amount,
+    )
+}
+
+/// Close a fully withdrawn escrow and its vault, returning their rent to the sender
+fn close_escrow(accounts: &Settle) -> Result<()> {
+    let escrow = &accounts.escrow;
+    let seeds: &[&[u8]] = &[ESCROW_SEED, &escrow.escrow_id, &[escrow.bump]];
+    token::close_account(CpiContext::new_with_signer(
+        accounts.token_program.to_account_info(),
+        CloseAccount {
+            account: accounts.vault.to_account_info(),
+            destination: accounts.sender.to_account_info(),
+            authority: escrow.to_account_info(),
+        },
+        &[seeds],
+    ))?;
+    escrow.close(accounts.sender.to_account_info())
+}
+
+/// Leaf for the secret at `index`: sha256 of the big-endian index and the secret's hash
+fn merkle_leaf(index: u32, secret_hash: &[u8; 32]) -> [u8; 32] {
+    hash::hashv(&[&(index as u64).to_be_bytes()[..], &secret_hash[..]]).to_bytes()
+}
+
+/// Fold a proof into a root, hashing each pair in sorted order
+fn merkle_root(leaf: [u8; 32], proof: &[[u8; 32]]) -> [u8; 32] {
+    proof.iter().fold(leaf, |node, sibling| {
+        let (first, second) = if node <= *sibling { (node, *sibling) } else { (*sibling, node) };
+        hash::hashv(&[&first, &second]).to_bytes()
+    })
+}
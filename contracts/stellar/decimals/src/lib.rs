@@ -0,0 +1,71 @@
+#![no_std]
+
+// Decimal normalization for cross-chain amount math
+// EVM tokens usually carry 18 decimals and Stellar assets 7, so the same value is a
+// different integer on each leg of a swap; every conversion here states how it rounds
+
+/// Decimals of native ETH and most ERC-20 tokens
+pub const EVM_DECIMALS: u32 = 18;
+
+/// Decimals of XLM and every Stellar classic asset
+pub const STELLAR_DECIMALS: u32 = 7;
+
+/// Largest power of ten that fits in an i128
+const MAX_SCALE: u32 = 38;
+
+/// How to treat the digits dropped when converting to fewer decimals
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Rounding {
+    /// Truncate towards zero; use for amounts paid out so the payer never overspends
+    Down,
+    /// Round away from zero; use for amounts owed so the payee is never short-changed
+    Up,
+}
+
+/// 10^exp, or `None` past i128 range
+pub fn pow10(exp: u32) -> Option<i128> {
+    if exp > MAX_SCALE {
+        return None;
+    }
+    10i128.checked_pow(exp)
+}
+
+/// Rescale an amount from one number of decimals to another
+/// Returns `None` on overflow; adding decimals is always exact
+pub fn convert(amount: i128, from_decimals: u32, to_decimals: u32, rounding: Rounding) -> Option<i128> {
+    if to_decimals >= from_decimals {
+        return amount.checked_mul(pow10(to_decimals - from_decimals)?);
+    }
+
+    let scale = pow10(from_decimals - to_decimals)?;
+    let quotient = amount / scale;
+    let remainder = amount % scale;
+    match rounding {
+        Rounding::Up if remainder > 0 => quotient.checked_add(1),
+        Rounding::Up if remainder < 0 => quotient.checked_sub(1),
+        _ => Some(quotient),
+    }
+}
+
+/// Part of an amount lost when converting it to fewer decimals and truncating
+pub fn dust(amount: i128, from_decimals: u32, to_decimals: u32) -> Option<i128> {
+    if to_decimals >= from_decimals {
+        return Some(0);
+    }
+    Some(amount % pow10(from_decimals - to_decimals)?)
+}
+
+/// Whether an amount converts to fewer decimals without losing anything
+pub fn is_exact(amount: i128, from_decimals: u32, to_decimals: u32) -> bool {
+    dust(amount, from_decimals, to_decimals) == Some(0)
+}
+
+/// Convert an 18-decimal EVM amount to 7-decimal Stellar units
+pub fn evm_to_stellar(amount: i128, rounding: Rounding) -> Option<i128> {
+    convert(amount, EVM_DECIMALS, STELLAR_DECIMALS, rounding)
+}
+
+/// Convert a 7-decimal Stellar amount to 18-decimal EVM units (always exact)
+pub fn stellar_to_evm(amount: i128) -> Option<i128> {
+    convert(amount, STELLAR_DECIMALS, EVM_DECIMALS, Rounding::Down)
+}
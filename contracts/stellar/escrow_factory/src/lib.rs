@@ -0,0 +1,146 @@
+#![no_std]
+
+// Stellar Escrow Factory
+// Deploys one swap escrow per swap, salted with the hash of the swap's immutables,
+// so resolvers can verify the terms from the escrow address alone
+
+mod swap_escrow;
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, contracterror,
+    token, xdr::ToXdr,
+    Address, BytesN, Env, log
+};
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+
+pub use swap_escrow::{EscrowSide, Immutables};
+use swap_escrow::SwapEscrowClient;
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    EscrowWasmHash,
+    Deployed(BytesN<32>),
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum FactoryError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    EscrowAlreadyExists = 3,
+    InvalidTimelock = 4,
+    InvalidAmount = 5,
+    InvalidSide = 6,
+}
+
+#[contract]
+pub struct EscrowFactory;
+
+#[contractimpl]
+impl EscrowFactory {
+    /// Set the uploaded swap escrow Wasm that new escrows are deployed from
+    pub fn initialize(env: Env, escrow_wasm_hash: BytesN<32>) -> Result<(), FactoryError> {
+        if env.storage().instance().has(&DataKey::EscrowWasmHash) {
+            return Err(FactoryError::AlreadyInitialized);
+        }
+        env.storage().instance().set(&DataKey::EscrowWasmHash, &escrow_wasm_hash);
+        Ok(())
+    }
+
+    /// Deploy the source-side escrow for a swap, funded by the maker, returning its address
+    /// The taker must also sign when it puts up a safety deposit
+    pub fn create_src_escrow(env: Env, immutables: Immutables) -> Result<Address, FactoryError> {
+        if immutables.side != EscrowSide::Src {
+            return Err(FactoryError::InvalidSide);
+        }
+        immutables.maker.require_auth();
+        if immutables.safety_deposit > 0 {
+            immutables.taker.require_auth();
+        }
+        deploy_escrow(&env, &immutables, &immutables.maker)
+    }
+
+    /// Deploy the destination-side escrow for a swap, funded by the taker, returning its address
+    /// Its timelock must expire before the source escrow's (`src_cancellation`) so the taker
+    /// is always refunded here before the maker can reclaim the source funds
+    pub fn create_dst_escrow(
+        env: Env,
+        immutables: Immutables,
+        src_cancellation: u64,
+    ) -> Result<Address, FactoryError> {
+        if immutables.side != EscrowSide::Dst {
+            return Err(FactoryError::InvalidSide);
+        }
+        if immutables.timelock >= src_cancellation {
+            return Err(FactoryError::InvalidTimelock);
+        }
+        immutables.taker.require_auth();
+        deploy_escrow(&env, &immutables, &immutables.taker)
+    }
+
+    /// Address the escrow for these terms is (or will be) deployed at
+    pub fn get_escrow_address(env: Env, immutables: Immutables) -> Address {
+        let salt = Self::get_salt(env.clone(), immutables);
+        env.deployer().with_current_contract(salt).deployed_address()
+    }
+
+    /// Deployment salt committing to the terms (keccak256 of their XDR encoding)
+    pub fn get_salt(env: Env, immutables: Immutables) -> BytesN<32> {
+        env.crypto().keccak256(&immutables.to_xdr(&env)).into()
+    }
+}
+
+/// Deploy an escrow salted with its terms and fund it from `depositor`
+fn deploy_escrow(
+    env: &Env,
+    immutables: &Immutables,
+    depositor: &Address,
+) -> Result<Address, FactoryError> {
+    if immutables.amount <= 0 || immutables.safety_deposit < 0 {
+        return Err(FactoryError::InvalidAmount);
+    }
+    if immutables.timelock <= env.ledger().timestamp()
+        || immutables.exclusive_until > immutables.timelock
+        || immutables.public_cancellation < immutables.timelock
+    {
+        return Err(FactoryError::InvalidTimelock);
+    }
+
+    let wasm_hash: BytesN<32> = env
+        .storage()
+        .instance()
+        .get(&DataKey::EscrowWasmHash)
+        .ok_or(FactoryError::NotInitialized)?;
+
+    // A salt can only be deployed once, so identical terms cannot be reused
+    let salt = EscrowFactory::get_salt(env.clone(), immutables.clone());
+    let key = DataKey::Deployed(salt.clone());
+    if env.storage().persistent().has(&key) {
+        return Err(FactoryError::EscrowAlreadyExists);
+    }
+    env.storage().persistent().set(&key, &true);
+
+    let escrow = env.deployer().with_current_contract(salt).deploy(wasm_hash);
+    SwapEscrowClient::new(env, &escrow).initialize(&env.current_contract_address(), immutables);
+
+    // Fund the escrow straight from the depositor, plus the taker's safety deposit
+    let token_client = token::Client::new(env, &immutables.token);
+    token_client.transfer(depositor, &escrow, &immutables.amount);
+    if immutables.safety_deposit > 0 {
+        token_client.transfer(&immutables.taker, &escrow, &immutables.safety_deposit);
+    }
+
+    log!(
+        env,
+        "Swap Escrow Deployed: Address={}, Order={}, Depositor={}, Amount={}",
+        escrow,
+        immutables.order_hash,
+        depositor.clone(),
+        immutables.amount
+    );
+
+    Ok(escrow)
+}
@@ -0,0 +1,194 @@
+#![no_std]
+
+// Stellar Governance Timelock
+// Holds the admin role of the HTLC contracts and only forwards admin calls after they have
+// sat in a public queue for at least the minimum delay, so in-flight swaps can react first
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, contracterror, symbol_short,
+    Address, Env, IntoVal, Symbol, TryFromVal, Val, Vec, log
+};
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+
+/// A queued admin call: `function(args)` on `target`, executable from `eta`
+/// Targeting this contract itself queues `set_min_delay(u64)` or `set_proposer(Address)`
+#[derive(Clone)]
+#[contracttype]
+pub struct Action {
+    pub target: Address,
+    pub function: Symbol,
+    pub args: Vec<Val>,
+    pub eta: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Proposer,
+    MinDelay,
+    NextActionId,
+    Action(u64),
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum GovernanceError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    DelayTooShort = 3,
+    ActionNotFound = 4,
+    ActionNotReady = 5,
+    InvalidAction = 6,
+}
+
+#[contract]
+pub struct Governance;
+
+#[contractimpl]
+impl Governance {
+    /// Set who may queue and cancel actions and the shortest delay an action can be queued with
+    pub fn initialize(env: Env, proposer: Address, min_delay: u64) -> Result<(), GovernanceError> {
+        let storage = env.storage().instance();
+        if storage.has(&DataKey::Proposer) {
+            return Err(GovernanceError::AlreadyInitialized);
+        }
+        storage.set(&DataKey::Proposer, &proposer);
+        storage.set(&DataKey::MinDelay, &min_delay);
+        Ok(())
+    }
+
+    /// Queue `function(args)` on `target` to run no sooner than `delay` seconds from now,
+    /// returning the action ID (proposer only)
+    pub fn schedule_action(
+        env: Env,
+        target: Address,
+        function: Symbol,
+        args: Vec<Val>,
+        delay: u64,
+    ) -> Result<u64, GovernanceError> {
+        load_proposer(&env)?.require_auth();
+        if delay < Self::get_min_delay(env.clone()) {
+            return Err(GovernanceError::DelayTooShort);
+        }
+
+        let action_id: u64 = env.storage().instance().get(&DataKey::NextActionId).unwrap_or(0);
+        env.storage().instance().set(&DataKey::NextActionId, &(action_id + 1));
+        let eta = env.ledger().timestamp().saturating_add(delay);
+        let action = Action {
+            target: target.clone(),
+            function: function.clone(),
+            args,
+            eta,
+        };
+        env.storage().persistent().set(&DataKey::Action(action_id), &action);
+
+        env.events().publish(
+            (symbol_short!("scheduled"), action_id),
+            (target.clone(), function.clone(), eta),
+        );
+        log!(
+            &env,
+            "Governance Scheduled: ID={}, Target={}, Function={}, ETA={}",
+            action_id,
+            target,
+            function,
+            eta
+        );
+
+        Ok(action_id)
+    }
+
+    /// Run a queued action once its delay has passed; anyone may trigger it
+    pub fn execute_action(env: Env, action_id: u64) -> Result<Val, GovernanceError> {
+        let key = DataKey::Action(action_id);
+        let action: Action = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(GovernanceError::ActionNotFound)?;
+        if env.ledger().timestamp() < action.eta {
+            return Err(GovernanceError::ActionNotReady);
+        }
+        env.storage().persistent().remove(&key);
+
+        let result = if action.target == env.current_contract_address() {
+            apply_own_action(&env, &action)?
+        } else {
+            env.invoke_contract(&action.target, &action.function, action.args.clone())
+        };
+
+        env.events().publish(
+            (symbol_short!("executed"), action_id),
+            (action.target.clone(), action.function.clone()),
+        );
+        log!(
+            &env,
+            "Governance Executed: ID={}, Target={}, Function={}",
+            action_id,
+            action.target,
+            action.function
+        );
+
+        Ok(result)
+    }
+
+    /// Drop a queued action before it runs (proposer only)
+    pub fn cancel_action(env: Env, action_id: u64) -> Result<(), GovernanceError> {
+        load_proposer(&env)?.require_auth();
+        let key = DataKey::Action(action_id);
+        if !env.storage().persistent().has(&key) {
+            return Err(GovernanceError::ActionNotFound);
+        }
+        env.storage().persistent().remove(&key);
+
+        env.events().publish((symbol_short!("cancelled"), action_id), ());
+        log!(&env, "Governance Cancelled: ID={}", action_id);
+
+        Ok(())
+    }
+
+    /// A queued action, if it has not been executed or cancelled
+    pub fn get_action(env: Env, action_id: u64) -> Option<Action> {
+        env.storage().persistent().get(&DataKey::Action(action_id))
+    }
+
+    /// Shortest delay an action can be queued with, in seconds
+    pub fn get_min_delay(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::MinDelay).unwrap_or(0)
+    }
+
+    /// Address allowed to queue and cancel actions
+    pub fn get_proposer(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Proposer)
+    }
+}
+
+fn load_proposer(env: &Env) -> Result<Address, GovernanceError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Proposer)
+        .ok_or(GovernanceError::NotInitialized)
+}
+
+/// Apply an action queued against this contract, which cannot call into itself
+fn apply_own_action(env: &Env, action: &Action) -> Result<Val, GovernanceError> {
+    let arg = action.args.get(0).ok_or(GovernanceError::InvalidAction)?;
+    if action.args.len() != 1 {
+        return Err(GovernanceError::InvalidAction);
+    }
+    if action.function == Symbol::new(env, "set_min_delay") {
+        let min_delay = u64::try_from_val(env, &arg).map_err(|_| GovernanceError::InvalidAction)?;
+        env.storage().instance().set(&DataKey::MinDelay, &min_delay);
+        env.events().publish((symbol_short!("delay"),), min_delay);
+    } else if action.function == Symbol::new(env, "set_proposer") {
+        let proposer =
+            Address::try_from_val(env, &arg).map_err(|_| GovernanceError::InvalidAction)?;
+        env.storage().instance().set(&DataKey::Proposer, &proposer);
+        env.events().publish((symbol_short!("proposer"),), proposer);
+    } else {
+        return Err(GovernanceError::InvalidAction);
+    }
+    Ok(().into_val(env))
+}
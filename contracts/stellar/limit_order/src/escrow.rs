@@ -0,0 +1,135 @@
+// Interface of the HTLC escrow contract used to settle fills
+// Types mirror htlc_escrow's contract types so they encode identically
+
+use soroban_sdk::{contractclient, contracttype, Address, Bytes, BytesN, Env, String, Symbol, Vec};
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AuctionPoint {
+    pub delay: u64,
+    pub rate_bump: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct AuctionParams {
+    pub start_time: u64,
+    pub duration: u64,
+    pub initial_rate_bump: u32,
+    pub points: Vec<AuctionPoint>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum Auction {
+    None,
+    Dutch(AuctionParams),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct SwapRoute {
+    pub router: Address,
+    pub target_token: Address,
+    pub min_out: i128,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum WithdrawSwap {
+    None,
+    Soroswap(SwapRoute),
+}
+
+#[derive(Clone, Copy)]
+#[contracttype]
+pub enum TimelockKind {
+    Timestamp,
+    Ledger,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum OracleAsset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceRule {
+    pub asset: OracleAsset,
+    pub price: i128,
+    pub max_age: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum PriceCondition {
+    None,
+    Above(PriceRule),
+    Below(PriceRule),
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Multisig {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum ReceiverAuth {
+    Single,
+    Threshold(Multisig),
+}
+
+#[derive(Clone, Copy)]
+#[contracttype]
+pub enum LockType {
+    Hashlock,
+    AdaptorPoint,
+    Sha256,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct EscrowParams {
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount: i128,
+    pub secret_hash: BytesN<32>,
+    pub timelock: u64,
+    pub exclusive_until: u64,
+    pub token_address: Option<Address>,
+    pub order_id: String,
+    pub metadata: Bytes,
+    pub parts: u32,
+    pub resolvers: Vec<Address>,
+    pub taker_amount: i128,
+    pub auction: Auction,
+    pub integrator: Option<Address>,
+    pub integrator_share_bps: u32,
+    pub expected_amount: i128,
+    pub receiver_hook: bool,
+    pub cancel_penalty_bps: u32,
+    pub basket: Vec<(Address, i128)>,
+    pub stream_duration: u64,
+    pub payees: Vec<(Address, u32)>,
+    pub swap_on_withdraw: WithdrawSwap,
+    pub timelock_kind: TimelockKind,
+    pub price_condition: PriceCondition,
+    pub arbiter: Option<Address>,
+    pub dispute_window: u64,
+    pub receiver_auth: ReceiverAuth,
+    pub lock_type: LockType,
+    pub reveal_delay: u64,
+}
+
+#[allow(dead_code)]
+#[contractclient(name = "EscrowClient")]
+pub trait EscrowInterface {
+    fn create_escrow(env: Env, params: EscrowParams) -> Bytes;
+    fn cancel(env: Env, escrow_id: Bytes, sender: Address);
+}
@@ -0,0 +1,308 @@
+#![no_std]
+
+// Stellar Limit Order Protocol
+// Makers post orders on-chain and approve this contract to spend the maker asset;
+// resolvers fill them by locking the maker's funds into the HTLC escrow atomically
+
+mod escrow;
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, contracterror,
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    token, vec, xdr::ToXdr,
+    Address, Bytes, BytesN, Env, IntoVal, String, Symbol, log
+};
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+
+use escrow::{
+    Auction, EscrowClient, EscrowParams, LockType, PriceCondition, ReceiverAuth, TimelockKind,
+    WithdrawSwap,
+};
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Order {
+    pub maker: Address,
+    pub maker_asset: Address,
+    pub making_amount: i128,
+    /// Asset the maker receives on the counterpart chain
+    pub taker_asset: String,
+    pub taking_amount: i128,
+    pub secret_hash: BytesN<32>,
+    /// Last timestamp at which the order can be filled
+    pub expiry: u64,
+    /// Longest a fill may lock the maker's funds: the escrow's timelock is at most this many
+    /// seconds after the fill
+    pub max_lock_duration: u64,
+    /// Longest exclusive window a fill may give the resolver, in seconds after the fill
+    pub max_exclusive_duration: u64,
+    pub salt: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum OrderStatus {
+    Open,
+    Filled,
+    Cancelled,
+    Refunded,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct OrderState {
+    pub order: Order,
+    pub status: OrderStatus,
+    pub resolver: Option<Address>,
+    pub escrow_id: Option<Bytes>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Escrow,
+    Order(BytesN<32>),
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum LimitOrderError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    OrderNotFound = 3,
+    OrderAlreadyExists = 4,
+    OrderNotOpen = 5,
+    OrderExpired = 6,
+    InvalidOrder = 7,
+    OrderNotFilled = 8,
+    InvalidTimelock = 9,
+}
+
+#[contract]
+pub struct LimitOrderProtocol;
+
+#[contractimpl]
+impl LimitOrderProtocol {
+    /// Set the HTLC escrow contract that fills settle into
+    pub fn initialize(env: Env, escrow: Address) -> Result<(), LimitOrderError> {
+        if env.storage().instance().has(&DataKey::Escrow) {
+            return Err(LimitOrderError::AlreadyInitialized);
+        }
+        env.storage().instance().set(&DataKey::Escrow, &escrow);
+        Ok(())
+    }
+
+    /// Post a signed order; the maker must also approve this contract for `making_amount`
+    pub fn post_order(env: Env, order: Order) -> Result<BytesN<32>, LimitOrderError> {
+        order.maker.require_auth();
+
+        if order.making_amount <= 0 || order.taking_amount <= 0 || order.max_lock_duration == 0 {
+            return Err(LimitOrderError::InvalidOrder);
+        }
+        if order.expiry <= env.ledger().timestamp() {
+            return Err(LimitOrderError::OrderExpired);
+        }
+
+        let order_hash = Self::get_order_hash(env.clone(), order.clone());
+        let key = DataKey::Order(order_hash.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(LimitOrderError::OrderAlreadyExists);
+        }
+
+        let state = OrderState {
+            order: order.clone(),
+            status: OrderStatus::Open,
+            resolver: None,
+            escrow_id: None,
+        };
+        env.storage().persistent().set(&key, &state);
+
+        log!(
+            &env,
+            "Order Posted: Hash={}, Maker={}, Amount={}",
+            order_hash,
+            order.maker,
+            order.making_amount
+        );
+
+        Ok(order_hash)
+    }
+
+    /// Cancel an order that has not been filled yet
+    pub fn cancel_order(env: Env, order_hash: BytesN<32>) -> Result<(), LimitOrderError> {
+        let mut state = load_order(&env, &order_hash)?;
+        state.order.maker.require_auth();
+
+        if state.status != OrderStatus::Open {
+            return Err(LimitOrderError::OrderNotOpen);
+        }
+
+        state.status = OrderStatus::Cancelled;
+        env.storage().persistent().set(&DataKey::Order(order_hash.clone()), &state);
+
+        log!(&env, "Order Cancelled: Hash={}", order_hash);
+
+        Ok(())
+    }
+
+    /// Fill an open order: pull the maker's funds and lock them in an HTLC escrow
+    /// payable to the resolver, returning the escrow ID
+    /// `timelock` and `exclusive_until` must fall within the order's maximum durations from now
+    pub fn fill_order(
+        env: Env,
+        order_hash: BytesN<32>,
+        resolver: Address,
+        timelock: u64,
+        exclusive_until: u64,
+    ) -> Result<Bytes, LimitOrderError> {
+        resolver.require_auth();
+
+        let mut state = load_order(&env, &order_hash)?;
+        if state.status != OrderStatus::Open {
+            return Err(LimitOrderError::OrderNotOpen);
+        }
+        let now = env.ledger().timestamp();
+        if now > state.order.expiry {
+            return Err(LimitOrderError::OrderExpired);
+        }
+
+        // The maker bounds how long a fill can keep their funds locked
+        if timelock > now.saturating_add(state.order.max_lock_duration)
+            || exclusive_until > now.saturating_add(state.order.max_exclusive_duration)
+        {
+            return Err(LimitOrderError::InvalidTimelock);
+        }
+
+        let escrow_address = load_escrow_address(&env)?;
+        let this = env.current_contract_address();
+        let order = state.order.clone();
+
+        // Pull the maker's funds using their allowance
+        let token_client = token::Client::new(&env, &order.maker_asset);
+        token_client.transfer_from(&this, &order.maker, &this, &order.making_amount);
+
+        // Authorize the escrow to move them out of this contract
+        env.authorize_as_current_contract(vec![
+            &env,
+            InvokerContractAuthEntry::Contract(SubContractInvocation {
+                context: ContractContext {
+                    contract: order.maker_asset.clone(),
+                    fn_name: Symbol::new(&env, "transfer"),
+                    args: (this.clone(), escrow_address.clone(), order.making_amount).into_val(&env),
+                },
+                sub_invocations: vec![&env],
+            }),
+        ]);
+
+        let params = EscrowParams {
+            sender: this,
+            receiver: resolver.clone(),
+            amount: order.making_amount,
+            secret_hash: order.secret_hash.clone(),
+            timelock,
+            exclusive_until,
+            token_address: Some(order.maker_asset.clone()),
+            order_id: hex_string(&env, &order_hash),
+            metadata: Bytes::new(&env),
+            parts: 0,
+            resolvers: vec![&env],
+            taker_amount: order.taking_amount,
+            auction: Auction::None,
+            integrator: None,
+            integrator_share_bps: 0,
+            expected_amount: 0,
+            receiver_hook: false,
+            cancel_penalty_bps: 0,
+            basket: vec![&env],
+            stream_duration: 0,
+            payees: vec![&env],
+            swap_on_withdraw: WithdrawSwap::None,
+            timelock_kind: TimelockKind::Timestamp,
+            price_condition: PriceCondition::None,
+            arbiter: None,
+            dispute_window: 0,
+            receiver_auth: ReceiverAuth::Single,
+            lock_type: LockType::Hashlock,
+            reveal_delay: 0,
+        };
+        let escrow_id = EscrowClient::new(&env, &escrow_address).create_escrow(&params);
+
+        state.status = OrderStatus::Filled;
+        state.resolver = Some(resolver.clone());
+        state.escrow_id = Some(escrow_id.clone());
+        env.storage().persistent().set(&DataKey::Order(order_hash.clone()), &state);
+
+        log!(
+            &env,
+            "Order Filled: Hash={}, Resolver={}, Escrow={}",
+            order_hash,
+            resolver,
+            escrow_id
+        );
+
+        Ok(escrow_id)
+    }
+
+    /// Cancel the escrow of a filled order after its timelock and return the refund to the maker
+    pub fn refund_order(env: Env, order_hash: BytesN<32>) -> Result<i128, LimitOrderError> {
+        let mut state = load_order(&env, &order_hash)?;
+        let escrow_id = match (&state.status, &state.escrow_id) {
+            (OrderStatus::Filled, Some(escrow_id)) => escrow_id.clone(),
+            _ => return Err(LimitOrderError::OrderNotFilled),
+        };
+
+        let escrow_address = load_escrow_address(&env)?;
+        let this = env.current_contract_address();
+        let token_client = token::Client::new(&env, &state.order.maker_asset);
+
+        // Only forward what the escrow actually refunded
+        let balance_before = token_client.balance(&this);
+        EscrowClient::new(&env, &escrow_address).cancel(&escrow_id, &this);
+        let refunded = token_client.balance(&this) - balance_before;
+        token_client.transfer(&this, &state.order.maker, &refunded);
+
+        state.status = OrderStatus::Refunded;
+        env.storage().persistent().set(&DataKey::Order(order_hash.clone()), &state);
+
+        log!(&env, "Order Refunded: Hash={}, Amount={}", order_hash, refunded);
+
+        Ok(refunded)
+    }
+
+    /// Get an order and its fill status
+    pub fn get_order(env: Env, order_hash: BytesN<32>) -> Option<OrderState> {
+        env.storage().persistent().get(&DataKey::Order(order_hash))
+    }
+
+    /// Hash identifying an order (keccak256 of its XDR encoding)
+    pub fn get_order_hash(env: Env, order: Order) -> BytesN<32> {
+        env.crypto().keccak256(&order.to_xdr(&env)).into()
+    }
+}
+
+fn load_order(env: &Env, order_hash: &BytesN<32>) -> Result<OrderState, LimitOrderError> {
+    env.storage()
+        .persistent()
+        .get(&DataKey::Order(order_hash.clone()))
+        .ok_or(LimitOrderError::OrderNotFound)
+}
+
+fn load_escrow_address(env: &Env) -> Result<Address, LimitOrderError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Escrow)
+        .ok_or(LimitOrderError::NotInitialized)
+}
+
+/// Lowercase hex of the order hash, used as the escrow's order ID
+fn hex_string(env: &Env, hash: &BytesN<32>) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut buf = [0u8; 64];
+    for (i, byte) in hash.to_array().iter().enumerate() {
+        buf[2 * i] = HEX[(byte >> 4) as usize];
+        buf[2 * i + 1] = HEX[(byte & 0x0f) as usize];
+    }
+    String::from_bytes(env, &buf)
+}
@@ -1,15 +1,285 @@
 #![no_std]
+// contractimpl also generates client methods with the full argument list
+#![allow(clippy::too_many_arguments)]
 
 // Stellar HTLC Escrow Contract
 // Uses Stellar's native transaction conditions and timebound functionality
 
 use soroban_sdk::{
-    contract, contractimpl, contracttype, contracterror,
-    Address, Bytes, Env, String, Vec, log, 
-    token, FromVal
+    contract, contractclient, contractimpl, contracttype, contracterror,
+    auth::{ContractContext, InvokerContractAuthEntry, SubContractInvocation},
+    Address, Bytes, BytesN, Env, IntoVal, String, Symbol, Vec, log, 
+    symbol_short, token, vec, xdr::ToXdr
 };
 use core::result::Result;
 use core::result::Result::{Ok, Err};
+use decimals::Rounding;
+
+mod meta_tx;
+mod ptlc;
+mod swap_escrow;
+mod test;
+
+pub use meta_tx::{MetaCall, MetaTx};
+use swap_escrow::{EscrowStatus, SwapEscrowClient};
+
+/// Longest order ID (in bytes) that can be hashed into an escrow ID
+const MAX_STRING_LEN: usize = 128;
+
+/// Largest metadata blob (in bytes) that can be attached to an escrow
+const MAX_METADATA_LEN: u32 = 256;
+
+/// Largest encrypted secret envelope (in bytes) that can be attached to an escrow
+const MAX_ENVELOPE_LEN: u32 = 256;
+
+/// Most extra tokens a multi-asset escrow can lock
+const MAX_BASKET_LEN: u32 = 8;
+
+/// Most addresses a single payout can be split between
+const MAX_PAYEES: u32 = 8;
+
+/// Most hops a chain of escrows sharing one hashlock can have
+const MAX_CHAIN_LEN: u32 = 4;
+
+/// Most signers of a threshold receiver
+const MAX_SIGNERS: u32 = 10;
+
+/// Denominator of auction rate bumps (1e7 = +100%), as in the 1inch auction curve
+const RATE_BUMP_BASE: i128 = 10_000_000;
+
+/// Denominator of the protocol fee (1e4 = 100%)
+const FEE_BPS_BASE: i128 = 10_000;
+
+/// Highest protocol fee the admin can configure (5%)
+const MAX_FEE_BPS: u32 = 500;
+
+/// XDR encoding of the native asset (`Asset::Native`), used to derive the XLM SAC address
+const NATIVE_ASSET_XDR: [u8; 4] = [0, 0, 0, 0];
+
+/// XLM an account must keep to stay above the minimum balance (two base reserves, in stroops)
+const MIN_ACCOUNT_RESERVE: i128 = 10_000_000;
+
+/// Seconds after creation before an unfilled escrow can be reclaimed, unless the admin sets another
+const DEFAULT_CANCEL_GRACE_PERIOD: u64 = 600;
+
+/// Approximate seconds per ledger, used to size temporary storage lifetimes
+const LEDGER_SECONDS: u64 = 5;
+
+/// Point on a Dutch auction curve, `delay` seconds after the previous point
+#[derive(Clone)]
+#[contracttype]
+pub struct AuctionPoint {
+    pub delay: u64,
+    pub rate_bump: u32,
+}
+
+/// Dutch auction decaying the required taker amount from `initial_rate_bump` to zero
+#[derive(Clone)]
+#[contracttype]
+pub struct AuctionParams {
+    pub start_time: u64,
+    pub duration: u64,
+    pub initial_rate_bump: u32,
+    pub points: Vec<AuctionPoint>,
+}
+
+/// Pricing of the taker amount: fixed, or decaying through a Dutch auction
+#[derive(Clone)]
+#[contracttype]
+pub enum Auction {
+    None,
+    Dutch(AuctionParams),
+}
+
+/// What `secret_hash` locks and what redeems it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum LockType {
+    /// keccak256 hash, redeemed with its preimage
+    Hashlock,
+    /// Compressed ed25519 adaptor point, redeemed with its scalar (PTLC)
+    AdaptorPoint,
+    /// SHA-256 hash, redeemed with its preimage, for legs that can only lock SHA-256 such as
+    /// Lightning hold invoices, Bitcoin scripts and classic Stellar hash-x signers
+    Sha256,
+}
+
+/// M-of-N set of addresses that jointly authorize withdrawals for a receiver
+#[derive(Clone)]
+#[contracttype]
+pub struct Multisig {
+    pub signers: Vec<Address>,
+    pub threshold: u32,
+}
+
+/// Who authorizes exclusive-window withdrawals: the receiver itself or a signer threshold
+#[derive(Clone)]
+#[contracttype]
+pub enum ReceiverAuth {
+    Single,
+    Threshold(Multisig),
+}
+
+/// Escrow whose terms `verify_counterpart` checks
+#[derive(Clone)]
+#[contracttype]
+pub enum Counterpart {
+    /// Escrow held by this contract
+    Escrow(Bytes),
+    /// Per-swap escrow contract deployed by the escrow factory set with `set_escrow_factory`
+    Contract(Address),
+}
+
+/// Unit of an escrow's `timelock` and `exclusive_until`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum TimelockKind {
+    /// Unix timestamps in seconds
+    Timestamp,
+    /// Ledger sequence numbers
+    Ledger,
+}
+
+/// Asset identifier of a SEP-40 price oracle such as Reflector
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum OracleAsset {
+    Stellar(Address),
+    Other(Symbol),
+}
+
+/// Price reported by a SEP-40 oracle, scaled by its `decimals`
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceData {
+    pub price: i128,
+    pub timestamp: u64,
+}
+
+/// Subset of the Reflector oracle interface used for price-conditioned escrows
+#[contractclient(name = "ReflectorClient")]
+pub trait Reflector {
+    fn lastprice(env: Env, asset: OracleAsset) -> Option<PriceData>;
+}
+
+/// Admin-configured screening contract consulted before funds move into or out of escrows
+#[contractclient(name = "ComplianceHookClient")]
+pub trait ComplianceHook {
+    /// Whether `address` may send or receive `amount` of `token`
+    fn check(env: Env, address: Address, token: Address, amount: i128) -> bool;
+}
+
+/// Price the configured oracle must report for an escrow to be released
+#[derive(Clone)]
+#[contracttype]
+pub struct PriceRule {
+    pub asset: OracleAsset,
+    /// Threshold in the oracle's price decimals
+    pub price: i128,
+    /// Oldest oracle update (in seconds) still accepted at withdrawal
+    pub max_age: u64,
+}
+
+/// Extra condition on releasing an escrow, checked at withdrawal time
+#[derive(Clone)]
+#[contracttype]
+pub enum PriceCondition {
+    None,
+    Above(PriceRule),
+    Below(PriceRule),
+}
+
+/// Router and limits for converting a receiver's payout into another token
+#[derive(Clone)]
+#[contracttype]
+pub struct SwapRoute {
+    /// Soroswap router contract, which must be approved with `add_swap_router`
+    pub router: Address,
+    pub target_token: Address,
+    /// Least `target_token` accepted for the whole escrow, scaled down for partial withdrawals
+    pub min_out: i128,
+}
+
+/// What the receiver is paid in: the escrowed token, or another one via a swap
+#[derive(Clone)]
+#[contracttype]
+pub enum WithdrawSwap {
+    None,
+    Soroswap(SwapRoute),
+}
+
+/// Subset of the Soroswap router interface used for swaps on withdrawal
+#[contractclient(name = "SoroswapRouterClient")]
+pub trait SoroswapRouter {
+    fn router_pair_for(env: Env, token_a: Address, token_b: Address) -> Address;
+    fn swap_exact_tokens_for_tokens(
+        env: Env,
+        amount_in: i128,
+        amount_out_min: i128,
+        path: Vec<Address>,
+        to: Address,
+        deadline: u64,
+    ) -> Vec<i128>;
+}
+
+/// Interface of receiver contracts that act on withdrawn funds in the same transaction
+#[contractclient(name = "WithdrawHookClient")]
+pub trait WithdrawHook {
+    /// Called after `amount` of `token` has been transferred to the receiver; a failing call is
+    /// skipped and does not revert the withdrawal
+    fn on_escrow_withdraw(env: Env, escrow_id: Bytes, amount: i128, token: Address);
+}
+
+/// Lifecycle callbacks for contracts that registered with `register_hook`, called when they are
+/// an escrow's sender or receiver
+///
+/// Withdrawal and cancellation callbacks are best effort: a hook that fails is skipped, so one
+/// party's contract can never block the other's settlement.
+#[contractclient(name = "EscrowHookClient")]
+pub trait EscrowHook {
+    /// Called once the escrow is funded
+    fn on_created(env: Env, escrow_id: Bytes, escrow: Escrow);
+    /// Called after each withdrawal of `amount` of `token`, before fees
+    fn on_withdrawn(env: Env, escrow_id: Bytes, amount: i128, token: Address);
+    /// Called after `refunded` of `token` is returned to the sender
+    fn on_cancelled(env: Env, escrow_id: Bytes, refunded: i128, token: Address);
+}
+
+/// Terms of a new escrow, passed to `create_escrow`
+#[derive(Clone)]
+#[contracttype]
+pub struct EscrowParams {
+    pub sender: Address,
+    pub receiver: Address,
+    pub amount: i128,
+    pub secret_hash: BytesN<32>,
+    pub timelock: u64,
+    pub exclusive_until: u64,
+    /// Token to escrow, or `None` for native XLM through its Stellar Asset Contract
+    pub token_address: Option<Address>,
+    pub order_id: String,
+    pub metadata: Bytes,
+    pub parts: u32,
+    pub resolvers: Vec<Address>,
+    pub taker_amount: i128,
+    pub auction: Auction,
+    pub integrator: Option<Address>,
+    pub integrator_share_bps: u32,
+    pub expected_amount: i128,
+    pub receiver_hook: bool,
+    pub cancel_penalty_bps: u32,
+    pub basket: Vec<(Address, i128)>,
+    pub stream_duration: u64,
+    pub payees: Vec<(Address, u32)>,
+    pub swap_on_withdraw: WithdrawSwap,
+    pub timelock_kind: TimelockKind,
+    pub price_condition: PriceCondition,
+    pub arbiter: Option<Address>,
+    pub dispute_window: u64,
+    pub receiver_auth: ReceiverAuth,
+    pub lock_type: LockType,
+    pub reveal_delay: u64,
+}
 
 #[derive(Clone)]
 #[contracttype]
@@ -17,13 +287,199 @@ pub struct Escrow {
     pub sender: Address,
     pub receiver: Address,
     pub amount: i128,
-    pub secret_hash: Bytes,
+    /// Hashlock, or the Merkle root of `parts + 1` secret hashes for partial fills
+    pub secret_hash: BytesN<32>,
     pub timelock: u64,
+    pub exclusive_until: u64,
     pub token_address: Address,
     pub order_id: String,
-    pub withdrawn: bool,
-    pub cancelled: bool,
     pub created_at: u64,
+    /// Integrator-supplied data such as counterpart tx hashes or routing hints
+    pub metadata: Bytes,
+    /// Number of parts the order can be filled in (0 for a single-secret escrow)
+    pub parts: u32,
+    pub filled_amount: i128,
+    /// Index of the last Merkle secret used by `withdraw_partial`
+    pub last_fill_index: Option<u32>,
+    /// Resolvers besides `receiver` that may claim partial fills
+    pub resolvers: Vec<Address>,
+    /// Minimum amount the maker expects on the counterpart chain
+    pub taker_amount: i128,
+    pub auction: Auction,
+    /// Wallet or aggregator credited with `integrator_share_bps` of the protocol fee
+    pub integrator: Option<Address>,
+    pub integrator_share_bps: u32,
+    /// Amount the receiver is quoted; anything escrowed above it is surplus (0 to disable)
+    pub expected_amount: i128,
+    /// Whether the receiver is a contract implementing `WithdrawHook`
+    pub receiver_hook: bool,
+    /// Share of the unfilled remainder paid to the receiver when the escrow is cancelled
+    pub cancel_penalty_bps: u32,
+    /// Further tokens locked alongside `amount`, released together on full withdrawal
+    pub basket: Vec<(Address, i128)>,
+    /// Seconds over which funds vest once the secret is revealed (0 to pay out at once)
+    pub stream_duration: u64,
+    /// When the secret was revealed on a streamed escrow
+    pub stream_start: Option<u64>,
+    /// Addresses splitting the receiver's payout by basis points (empty pays `receiver` alone)
+    pub payees: Vec<(Address, u32)>,
+    /// Swap applied to the receiver's payout on withdrawal
+    pub swap_on_withdraw: WithdrawSwap,
+    /// Whether `timelock` and `exclusive_until` are timestamps or ledger sequence numbers
+    pub timelock_kind: TimelockKind,
+    /// Oracle price that must hold for withdrawals
+    pub price_condition: PriceCondition,
+    /// Third party who rules on disputes raised by the sender or receiver
+    pub arbiter: Option<Address>,
+    /// Seconds after creation during which either party may raise a dispute
+    pub dispute_window: u64,
+    /// Signers who approve exclusive-window withdrawals on the receiver's behalf
+    pub receiver_auth: ReceiverAuth,
+    /// Whether `secret_hash` is a hashlock or an adaptor point
+    pub lock_type: LockType,
+    /// Delay between `commit_withdraw` and `reveal_withdraw`, in `timelock_kind` units;
+    /// when set, public withdrawals without the receiver's signature must commit first
+    pub reveal_delay: u64,
+}
+
+/// A partial fill claimed through `withdraw_partial`
+#[derive(Clone)]
+#[contracttype]
+pub struct Fill {
+    pub taker: Address,
+    pub amount: i128,
+    pub filled_at: u64,
+}
+
+/// One withdrawal in a `settle_batch` call
+#[derive(Clone)]
+#[contracttype]
+pub struct Settlement {
+    pub escrow_id: Bytes,
+    pub secret: BytesN<32>,
+    /// Amount to withdraw, or 0 for everything left in the escrow
+    pub amount: i128,
+}
+
+/// Claim a receiver's auth entry for `withdraw_with_signature` carries as its argument
+#[derive(Clone)]
+#[contracttype]
+pub struct ClaimPayload {
+    pub contract: Address,
+    pub escrow_id: Bytes,
+    pub secret_hash: BytesN<32>,
+    pub nonce: u64,
+    pub expiry: u64,
+}
+
+/// An escrow's secret encrypted to its designated resolver, attached with `attach_secret`
+/// `ciphertext` is the ephemeral X25519 public key, a 12-byte nonce and the ChaCha20-Poly1305
+/// sealed secret, keyed by HKDF-SHA256 over the shared key with the escrow ID as associated data
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SecretEnvelope {
+    pub resolver_key: BytesN<32>,
+    pub ciphertext: Bytes,
+}
+
+/// Breakdown of a withdrawal, as returned by `quote_withdrawal`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct WithdrawalQuote {
+    pub amount: i128,
+    /// Counterpart-chain amount the auction currently requires for this share of the order
+    pub taker_amount: i128,
+    pub treasury_fee: i128,
+    pub integrator_fee: i128,
+    /// Positive slippage captured for the treasury
+    pub surplus: i128,
+    /// What the receiver is paid
+    pub payout: i128,
+}
+
+/// Withdrawal windows of a pending escrow
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Phase {
+    /// Before `exclusive_until`: only the receiver may withdraw
+    Exclusive,
+    /// From `exclusive_until`: anyone with the secret may release funds to the receiver
+    Public,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum SettlementStatus {
+    Withdrawn,
+    Cancelled,
+    /// Settled through `resolve_dispute` by the arbiter or admin
+    Resolved,
+}
+
+/// Status buckets accepted by `list_escrows`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum EscrowFilter {
+    /// Every escrow that has not been settled yet
+    Pending,
+    /// Pending escrows whose timelock has not expired
+    Withdrawable,
+    /// Pending escrows whose timelock has expired
+    Cancellable,
+}
+
+/// Compact record left behind once an escrow is withdrawn or cancelled
+#[derive(Clone)]
+#[contracttype]
+pub struct SettlementReceipt {
+    pub status: SettlementStatus,
+    pub amount: i128,
+    pub filled_amount: i128,
+    pub settled_at: u64,
+    /// Receiver for withdrawals, sender for cancellations
+    pub settled_by: Address,
+    pub secret_hash: BytesN<32>,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Escrow(Bytes),
+    Receipt(Bytes),
+    Locked(Address),
+    PendingCount,
+    PendingAt(u32),
+    PendingPos(Bytes),
+    RevealedSecret(BytesN<32>),
+    Fill(Bytes, u32),
+    Admin,
+    Treasury,
+    FeeBps,
+    Fees(Address),
+    SurplusBps,
+    CancelGracePeriod,
+    Commitment(Bytes),
+    AllowlistEnabled,
+    AllowedToken(Address),
+    MinAmount(Address),
+    DefaultMinAmount,
+    Disputed(Bytes),
+    ClaimNonce(Address),
+    MetaNonce(Address),
+    Oracle,
+    Chain(Bytes),
+    WithdrawCommit(Bytes, Address),
+    SecretEnvelope(Bytes),
+    Hook(Address),
+    ComplianceHook,
+    EscrowFactory,
+    Governance,
+    Paused,
+    RefundPoint(Bytes),
+    RateLimit,
+    CreatedIn(Address, u64),
+    RateLimitExempt(Address),
+    SwapRouter(Address),
 }
 
 #[contracterror]
@@ -38,6 +494,48 @@ pub enum HTLCError {
     UnauthorizedAccess = 6,
     InsufficientBalance = 7,
     InvalidTimelock = 8,
+    EscrowAlreadyExists = 9,
+    InvalidOrderId = 10,
+    MetadataTooLarge = 11,
+    InvalidFillAmount = 12,
+    InvalidMerkleProof = 13,
+    InvalidFillIndex = 14,
+    InvalidAuction = 15,
+    AlreadyInitialized = 16,
+    NotInitialized = 17,
+    InvalidFee = 18,
+    InvalidExpectedAmount = 19,
+    ArithmeticOverflow = 20,
+    EscrowCommitted = 21,
+    GracePeriodNotElapsed = 22,
+    InvalidPenalty = 23,
+    InvalidBasket = 24,
+    InsufficientAllowance = 25,
+    StreamActive = 26,
+    StreamNotStarted = 27,
+    InvalidPayees = 28,
+    TokenNotAllowed = 29,
+    AmountBelowMinimum = 30,
+    TrustlineMissing = 31,
+    TokenTransferFailed = 32,
+    EscrowDisputed = 33,
+    NotDisputed = 34,
+    AlreadyResolved = 35,
+    InvalidSwap = 36,
+    SwapFailed = 37,
+    ClaimExpired = 38,
+    InvalidSignature = 39,
+    PriceConditionNotMet = 40,
+    OracleUnavailable = 41,
+    DisputeWindowClosed = 42,
+    InvalidChain = 43,
+    InvalidThreshold = 44,
+    CommitRevealDisabled = 45,
+    CommitmentNotFound = 46,
+    RevealTooEarly = 47,
+    InvalidCommitment = 48,
+    ComplianceRejected = 50,
+    RateLimited = 51,
 }
 
 #[contract]
@@ -45,206 +543,1469 @@ pub struct HTLCEscrow;
 
 #[contractimpl]
 impl HTLCEscrow {
-    /// Create a new HTLC escrow
-    pub fn create_escrow(
+    /// Configure the admin, the treasury credited with protocol fees and the fee in basis points
+    pub fn initialize(
         env: Env,
-        sender: Address,
-        receiver: Address,
-        amount: i128,
-        secret_hash: Bytes,
-        timelock: u64,
-        token_address: Address,
-        order_id: String,
-    ) -> Result<Bytes, HTLCError> {
-        // Verify timelock is in the future
-        let current_time = env.ledger().timestamp();
-        if timelock <= current_time {
-            return Err(HTLCError::InvalidTimelock);
+        admin: Address,
+        treasury: Address,
+        fee_bps: u32,
+    ) -> Result<(), HTLCError> {
+        let storage = env.storage().instance();
+        if storage.has(&DataKey::Admin) {
+            return Err(HTLCError::AlreadyInitialized);
         }
-
-        // Generate unique escrow ID
-        // Convert String to Bytes properly
-        let order_id_val = order_id.to_val();
-        let order_id_bytes = Bytes::from_val(&env, &order_id_val);
-        let escrow_id = env.crypto().keccak256(&order_id_bytes);
-        
-        // Verify sender has sufficient balance
-        let token_client = token::Client::new(&env, &token_address);
-        let sender_balance = token_client.balance(&sender);
-        if sender_balance < amount {
-            return Err(HTLCError::InsufficientBalance);
+        if fee_bps > MAX_FEE_BPS {
+            return Err(HTLCError::InvalidFee);
         }
+        storage.set(&DataKey::Admin, &admin);
+        storage.set(&DataKey::Treasury, &treasury);
+        storage.set(&DataKey::FeeBps, &fee_bps);
+        Ok(())
+    }
 
-        // Transfer tokens to contract
-        token_client.transfer(&sender, &env.current_contract_address(), &amount);
+    /// Hand the admin role to another address, such as the governance timelock (admin only)
+    pub fn set_admin(env: Env, new_admin: Address) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::Admin, &new_admin);
 
-        // Create escrow
-        let escrow = Escrow {
-            sender: sender.clone(),
-            receiver: receiver.clone(),
-            amount,
-            secret_hash: secret_hash.clone(),
-            timelock,
-            token_address: token_address.clone(),
-            order_id: order_id.clone(),
-            withdrawn: false,
-            cancelled: false,
-            created_at: current_time,
-        };
+        env.events().publish((symbol_short!("admin"),), new_admin.clone());
+        log!(&env, "HTLC Admin Updated: Admin={}", new_admin);
+
+        Ok(())
+    }
 
-        // Store escrow
-        env.storage().persistent().set(&escrow_id, &escrow);
+    /// Name the governance timelock that alone may upgrade this contract; the admin sets it once,
+    /// after which only the timelock itself can hand it on
+    pub fn set_governance(env: Env, governance: Address) -> Result<(), HTLCError> {
+        match env.storage().instance().get::<_, Address>(&DataKey::Governance) {
+            Some(current) => current.require_auth(),
+            None => load_admin(&env)?.require_auth(),
+        }
+        env.storage().instance().set(&DataKey::Governance, &governance);
 
-        // Emit event
-        log!(
-            &env,
-            "HTLC Escrow Created: ID={}, Sender={}, Receiver={}, Amount={}",
-            escrow_id,
-            sender,
-            receiver,
-            amount
-        );
+        env.events().publish((symbol_short!("govern"),), governance.clone());
+        log!(&env, "HTLC Governance Updated: Governance={}", governance);
 
-        Ok(escrow_id.into())
+        Ok(())
     }
 
-    /// Withdraw funds using the secret
-    pub fn withdraw(
-        env: Env,
-        escrow_id: Bytes,
-        secret: String,
-        receiver: Address,
-    ) -> Result<(), HTLCError> {
-        // Load escrow
-        let mut escrow: Escrow = env.storage()
-            .persistent()
-            .get(&escrow_id)
-            .ok_or(HTLCError::EscrowNotFound)?;
+    /// Replace this contract's code with an uploaded Wasm, keeping its storage (governance
+    /// timelock only, so every upgrade waits out its public queue)
+    pub fn upgrade(env: Env, wasm_hash: BytesN<32>) -> Result<(), HTLCError> {
+        env.storage()
+            .instance()
+            .get::<_, Address>(&DataKey::Governance)
+            .ok_or(HTLCError::NotInitialized)?
+            .require_auth();
+        env.deployer().update_current_contract_wasm(wasm_hash.clone());
 
-        // Verify not already processed
-        if escrow.withdrawn {
-            return Err(HTLCError::AlreadyWithdrawn);
-        }
-        if escrow.cancelled {
-            return Err(HTLCError::AlreadyCancelled);
+        env.events().publish((symbol_short!("upgraded"),), wasm_hash);
+
+        Ok(())
+    }
+
+    /// Change the protocol fee (admin only, at most `MAX_FEE_BPS`)
+    pub fn set_fee_bps(env: Env, fee_bps: u32) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        if fee_bps > MAX_FEE_BPS {
+            return Err(HTLCError::InvalidFee);
         }
+        env.storage().instance().set(&DataKey::FeeBps, &fee_bps);
 
-        // Verify secret
-        // Convert String to Bytes properly
-        let secret_val = secret.to_val();
-        let secret_bytes = Bytes::from_val(&env, &secret_val);
-        let provided_hash = env.crypto().keccak256(&secret_bytes);
-        let provided_hash_bytes: Bytes = provided_hash.into();
-        if provided_hash_bytes != escrow.secret_hash {
-            return Err(HTLCError::InvalidSecret);
+        log!(&env, "HTLC Fee Updated: FeeBps={}", fee_bps);
+
+        Ok(())
+    }
+
+    /// Change the share of positive slippage captured for the treasury (admin only)
+    pub fn set_surplus_bps(env: Env, surplus_bps: u32) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        if surplus_bps as i128 > FEE_BPS_BASE {
+            return Err(HTLCError::InvalidFee);
         }
+        env.storage().instance().set(&DataKey::SurplusBps, &surplus_bps);
 
-        // Verify receiver
-        if receiver != escrow.receiver {
-            return Err(HTLCError::UnauthorizedAccess);
+        log!(&env, "HTLC Surplus Share Updated: SurplusBps={}", surplus_bps);
+
+        Ok(())
+    }
+
+    /// Set the Reflector oracle that price-conditioned escrows are checked against (admin only)
+    pub fn set_oracle(env: Env, oracle: Address) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::Oracle, &oracle);
+
+        log!(&env, "HTLC Oracle Updated: Oracle={}", oracle);
+
+        Ok(())
+    }
+
+    /// Set the escrow factory whose per-swap escrows `verify_counterpart` accepts (admin only)
+    pub fn set_escrow_factory(env: Env, factory: Address) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::EscrowFactory, &factory);
+
+        log!(&env, "HTLC Escrow Factory Updated: Factory={}", factory);
+
+        Ok(())
+    }
+
+    /// Set or clear the compliance hook screening escrow senders and recipients (admin only)
+    pub fn set_compliance_hook(env: Env, hook: Option<Address>) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        match &hook {
+            Some(hook) => env.storage().instance().set(&DataKey::ComplianceHook, hook),
+            None => env.storage().instance().remove(&DataKey::ComplianceHook),
         }
 
-        // Mark as withdrawn
-        escrow.withdrawn = true;
-        env.storage().persistent().set(&escrow_id, &escrow);
+        log!(&env, "HTLC Compliance Hook Updated: Hook={}", hook);
 
-        // Transfer tokens to receiver
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer(&env.current_contract_address(), &receiver, &escrow.amount);
+        Ok(())
+    }
 
-        log!(
-            &env,
-            "HTLC Withdrawal: ID={}, Receiver={}, Amount={}",
-            escrow_id,
-            receiver,
-            escrow.amount
-        );
+    /// Change how long after creation a maker must wait to reclaim an unfilled escrow (admin only)
+    pub fn set_cancel_grace_period(env: Env, grace_period: u64) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::CancelGracePeriod, &grace_period);
+
+        log!(&env, "HTLC Cancel Grace Period Updated: Seconds={}", grace_period);
 
         Ok(())
     }
 
-    /// Cancel escrow after timelock expires
-    pub fn cancel(
-        env: Env,
-        escrow_id: Bytes,
-        sender: Address,
-    ) -> Result<(), HTLCError> {
-        // Load escrow
-        let mut escrow: Escrow = env.storage()
+    /// Turn allowlist mode on or off; while on only approved tokens can be escrowed (admin only)
+    pub fn set_allowlist_enabled(env: Env, enabled: bool) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::AllowlistEnabled, &enabled);
+
+        log!(&env, "HTLC Token Allowlist Updated: Enabled={}", enabled);
+
+        Ok(())
+    }
+
+    /// Stop or resume new escrows and top-ups, which fail with `UnauthorizedAccess` while paused
+    /// (admin only); withdrawals, refunds and disputes of existing escrows never pause, so
+    /// pausing cannot trap funds in flight
+    pub fn set_paused(env: Env, paused: bool) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage().instance().set(&DataKey::Paused, &paused);
+
+        env.events().publish((symbol_short!("paused"),), paused);
+        log!(&env, "HTLC Paused Updated: Paused={}", paused);
+
+        Ok(())
+    }
+
+    /// Approve a token contract for escrow (admin only)
+    pub fn add_token(env: Env, token_address: Address) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage()
             .persistent()
-            .get(&escrow_id)
-            .ok_or(HTLCError::EscrowNotFound)?;
+            .set(&DataKey::AllowedToken(token_address.clone()), &true);
 
-        // Verify not already processed
-        if escrow.withdrawn {
-            return Err(HTLCError::AlreadyWithdrawn);
-        }
-        if escrow.cancelled {
-            return Err(HTLCError::AlreadyCancelled);
-        }
+        log!(&env, "HTLC Token Allowed: Token={}", token_address);
 
-        // Verify timelock has expired
-        let current_time = env.ledger().timestamp();
-        if current_time < escrow.timelock {
-            return Err(HTLCError::TimelockNotExpired);
-        }
+        Ok(())
+    }
 
-        // Verify sender
-        if sender != escrow.sender {
-            return Err(HTLCError::UnauthorizedAccess);
-        }
+    /// Revoke a token's approval; existing escrows in it can still settle (admin only)
+    pub fn remove_token(env: Env, token_address: Address) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage()
+            .persistent()
+            .remove(&DataKey::AllowedToken(token_address.clone()));
 
-        // Mark as cancelled
-        escrow.cancelled = true;
-        env.storage().persistent().set(&escrow_id, &escrow);
+        log!(&env, "HTLC Token Removed: Token={}", token_address);
 
-        // Refund tokens to sender
-        let token_client = token::Client::new(&env, &escrow.token_address);
-        token_client.transfer(&env.current_contract_address(), &sender, &escrow.amount);
+        Ok(())
+    }
 
-        log!(
-            &env,
-            "HTLC Cancellation: ID={}, Sender={}, Amount={}",
-            escrow_id,
-            sender,
-            escrow.amount
-        );
+    /// Approve a Soroswap router for escrows that swap the payout on withdrawal (admin only)
+    pub fn add_swap_router(env: Env, router: Address) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage()
+            .persistent()
+            .set(&DataKey::SwapRouter(router.clone()), &true);
+
+        log!(&env, "HTLC Swap Router Allowed: Router={}", router);
 
         Ok(())
     }
 
-    /// Get escrow details
-    pub fn get_escrow(env: Env, escrow_id: Bytes) -> Option<Escrow> {
-        env.storage().persistent().get(&escrow_id)
+    /// Revoke a router's approval for new escrows; existing escrows keep their route (admin only)
+    pub fn remove_swap_router(env: Env, router: Address) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        env.storage()
+            .persistent()
+            .remove(&DataKey::SwapRouter(router.clone()));
+
+        log!(&env, "HTLC Swap Router Removed: Router={}", router);
+
+        Ok(())
     }
 
-    /// Check if secret is valid for escrow
-    pub fn verify_secret(env: Env, escrow_id: Bytes, secret: String) -> bool {
-        if let Some(escrow) = Self::get_escrow(env.clone(), escrow_id) {
-            // Convert String to Bytes properly
-            let secret_val = secret.to_val();
-            let secret_bytes = Bytes::from_val(&env, &secret_val);
-            let provided_hash = env.crypto().keccak256(&secret_bytes);
-            let provided_hash_bytes: Bytes = provided_hash.into();
-            provided_hash_bytes == escrow.secret_hash
+    /// Cap how many escrows one sender may create per `window` seconds, 0 for either to
+    /// disable (admin only)
+    pub fn set_rate_limit(env: Env, max_escrows: u32, window: u64) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        if max_escrows == 0 || window == 0 {
+            env.storage().instance().remove(&DataKey::RateLimit);
         } else {
-            false
+            env.storage()
+                .instance()
+                .set(&DataKey::RateLimit, &(max_escrows, window));
         }
+
+        log!(&env, "HTLC Rate Limit Updated: Max={}, Window={}", max_escrows, window);
+
+        Ok(())
     }
 
-    /// Check if escrow can be cancelled
-    pub fn can_cancel(env: Env, escrow_id: Bytes) -> bool {
-        if let Some(escrow) = Self::get_escrow(env.clone(), escrow_id) {
-            let current_time = env.ledger().timestamp();
-            !escrow.withdrawn && !escrow.cancelled && current_time >= escrow.timelock
+    /// Exempt a protocol contract such as the limit-order contract from the rate limit, since
+    /// every maker's fill creates its escrow as that one sender (admin only)
+    pub fn set_rate_limit_exempt(env: Env, sender: Address, exempt: bool) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        let key = DataKey::RateLimitExempt(sender.clone());
+        if exempt {
+            env.storage().persistent().set(&key, &true);
         } else {
-            false
+            env.storage().persistent().remove(&key);
         }
+
+        log!(&env, "HTLC Rate Limit Exemption Updated: Sender={}, Exempt={}", sender, exempt);
+
+        Ok(())
     }
 
-    /// Get all active escrows for a given address (as sender or receiver)
-    /// This function helps with escrow management and monitoring
-    pub fn get_escrows_for_address(env: Env, _address: Address) -> Vec<(Bytes, Escrow)> {
+    /// Set the smallest amount an escrow may lock in any token without its own minimum (admin only)
+    pub fn set_default_min_amount(env: Env, min_amount: i128) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        if min_amount < 0 {
+            return Err(HTLCError::InvalidFillAmount);
+        }
+        env.storage().instance().set(&DataKey::DefaultMinAmount, &min_amount);
+
+        log!(&env, "HTLC Default Minimum Updated: Amount={}", min_amount);
+
+        Ok(())
+    }
+
+    /// Set the smallest amount an escrow may lock in a token, or `None` to fall back to the default (admin only)
+    pub fn set_min_amount(
+        env: Env,
+        token_address: Address,
+        min_amount: Option<i128>,
+    ) -> Result<(), HTLCError> {
+        load_admin(&env)?.require_auth();
+        let key = DataKey::MinAmount(token_address.clone());
+        match min_amount {
+            Some(min_amount) if min_amount < 0 => return Err(HTLCError::InvalidFillAmount),
+            Some(min_amount) => env.storage().persistent().set(&key, &min_amount),
+            None => env.storage().persistent().remove(&key),
+        }
+
+        log!(&env, "HTLC Token Minimum Updated: Token={}, Amount={}", token_address, min_amount);
+
+        Ok(())
+    }
+
+    /// Send the fees accrued in a token to `to`, or the treasury if `None`, returning the amount
+    /// sent (admin only); never pays out more than the balance held above escrowed principal
+    pub fn withdraw_fees(
+        env: Env,
+        token_address: Address,
+        to: Option<Address>,
+    ) -> Result<i128, HTLCError> {
+        load_admin(&env)?.require_auth();
+        let to = match to {
+            Some(to) => to,
+            None => env
+                .storage()
+                .instance()
+                .get(&DataKey::Treasury)
+                .ok_or(HTLCError::NotInitialized)?,
+        };
+
+        let key = DataKey::Fees(token_address.clone());
+        let accrued: i128 = env.storage().persistent().get(&key).unwrap_or(0);
+        let balance = token::Client::new(&env, &token_address).balance(&env.current_contract_address());
+        let unlocked = balance - Self::get_locked_amount(env.clone(), token_address.clone());
+        let amount = accrued.min(unlocked).max(0);
+        if amount > 0 {
+            env.storage().persistent().set(&key, &(accrued - amount));
+            send(&env, &token_address, &to, amount)?;
+            env.events()
+                .publish((symbol_short!("fees"), token_address.clone()), (to.clone(), amount));
+        }
+
+        log!(&env, "HTLC Fees Withdrawn: To={}, Amount={}", to, amount);
+
+        Ok(amount)
+    }
+
+    /// Create a new HTLC escrow
+    pub fn create_escrow(env: Env, params: EscrowParams) -> Result<Bytes, HTLCError> {
+        open_escrow(&env, params, false)
+    }
+
+    /// Create several escrows in one transaction, returning their IDs
+    /// Every escrow must be created or the whole batch is rolled back
+    pub fn create_escrows(env: Env, params: Vec<EscrowParams>) -> Result<Vec<Bytes>, HTLCError> {
+        let mut escrow_ids = Vec::new(&env);
+        let mut total: i128 = 0;
+        for escrow_params in params.iter() {
+            total = total
+                .checked_add(escrow_params.amount)
+                .ok_or(HTLCError::ArithmeticOverflow)?;
+            escrow_ids.push_back(open_escrow(&env, escrow_params, false)?);
+        }
+
+        env.events()
+            .publish((symbol_short!("created"), escrow_ids.len()), (escrow_ids.clone(), total));
+
+        Ok(escrow_ids)
+    }
+
+    /// Create a chain of escrows (A to B, B to C, ...) under one hashlock, returning their IDs
+    /// Each hop's receiver funds the next hop, and timelocks may only shorten down the chain;
+    /// the chain is identified by its first escrow's ID
+    pub fn create_chain(env: Env, hops: Vec<EscrowParams>) -> Result<Vec<Bytes>, HTLCError> {
+        let first = hops.get(0).ok_or(HTLCError::InvalidChain)?;
+        if hops.len() < 2 || hops.len() > MAX_CHAIN_LEN {
+            return Err(HTLCError::InvalidChain);
+        }
+        let mut previous = first.clone();
+        for hop in hops.iter().skip(1) {
+            if hop.secret_hash != first.secret_hash
+                || hop.sender != previous.receiver
+                || hop.timelock_kind != previous.timelock_kind
+                || hop.timelock > previous.timelock
+            {
+                return Err(HTLCError::InvalidChain);
+            }
+            previous = hop;
+        }
+
+        let mut escrow_ids = Vec::new(&env);
+        for hop in hops.iter() {
+            // Chained hops settle together, so none can be filled in parts
+            if hop.parts != 0 {
+                return Err(HTLCError::InvalidChain);
+            }
+            escrow_ids.push_back(open_escrow(&env, hop, false)?);
+        }
+
+        let chain_id = escrow_ids.get_unchecked(0);
+        env.storage()
+            .persistent()
+            .set(&DataKey::Chain(chain_id.clone()), &escrow_ids);
+        env.events()
+            .publish((symbol_short!("chain"), chain_id.clone()), escrow_ids.len());
+
+        Ok(escrow_ids)
+    }
+
+    /// Settle every hop of a chain with the shared secret, paying each hop's receiver
+    pub fn withdraw_chain(env: Env, chain_id: Bytes, secret: BytesN<32>) -> Result<(), HTLCError> {
+        let escrow_ids =
+            Self::get_chain(env.clone(), chain_id.clone()).ok_or(HTLCError::EscrowNotFound)?;
+        for escrow_id in escrow_ids.iter() {
+            // Load escrow (fails if already withdrawn or cancelled)
+            let escrow = load_escrow(&env, &escrow_id)?;
+            let receiver = escrow.receiver.clone();
+            authorize_withdrawal(&env, &escrow_id, &escrow, &receiver)?;
+            let remaining = escrow.amount - escrow.filled_amount;
+            withdraw_with_secret(&env, &escrow_id, escrow, &secret, remaining, &receiver)?;
+        }
+
+        log!(&env, "HTLC Chain Withdrawal: ID={}, Hops={}", chain_id, escrow_ids.len());
+
+        Ok(())
+    }
+
+    /// Attach the escrow's secret encrypted to a resolver's X25519 key, replacing any earlier one
+    /// Only the sender may attach it; it is dropped once the escrow settles
+    pub fn attach_secret(
+        env: Env,
+        escrow_id: Bytes,
+        resolver_key: BytesN<32>,
+        ciphertext: Bytes,
+    ) -> Result<(), HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        escrow.sender.require_auth();
+        if ciphertext.len() > MAX_ENVELOPE_LEN {
+            return Err(HTLCError::MetadataTooLarge);
+        }
+
+        env.storage().persistent().set(
+            &DataKey::SecretEnvelope(escrow_id.clone()),
+            &SecretEnvelope {
+                resolver_key: resolver_key.clone(),
+                ciphertext,
+            },
+        );
+        env.events()
+            .publish((symbol_short!("envelope"), escrow_id), resolver_key);
+
+        Ok(())
+    }
+
+    /// Encrypted secret attached to an unsettled escrow, if any
+    pub fn get_secret_envelope(env: Env, escrow_id: Bytes) -> Option<SecretEnvelope> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::SecretEnvelope(escrow_id))
+    }
+
+    /// Opt a contract in or out of `EscrowHook` callbacks for escrows it is a party to
+    pub fn register_hook(env: Env, contract: Address, enabled: bool) -> Result<(), HTLCError> {
+        if is_account(&env, &contract) {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+        contract.require_auth();
+
+        let key = DataKey::Hook(contract.clone());
+        if enabled {
+            env.storage().persistent().set(&key, &true);
+        } else {
+            env.storage().persistent().remove(&key);
+        }
+
+        log!(&env, "HTLC Hook Registration: Contract={}, Enabled={}", contract, enabled);
+
+        Ok(())
+    }
+
+    /// Whether a contract receives `EscrowHook` callbacks
+    pub fn is_hook_registered(env: Env, contract: Address) -> bool {
+        env.storage().persistent().has(&DataKey::Hook(contract))
+    }
+
+    /// Escrow IDs of a chain created with `create_chain`, in hop order
+    pub fn get_chain(env: Env, chain_id: Bytes) -> Option<Vec<Bytes>> {
+        env.storage().persistent().get(&DataKey::Chain(chain_id))
+    }
+
+    /// Create an escrow funded from the sender's allowance to this contract
+    /// The maker approves the escrow contract and signs only this call, so a resolver or
+    /// relayer can submit it without the maker authorizing the token transfer itself
+    pub fn create_escrow_from_allowance(env: Env, params: EscrowParams) -> Result<Bytes, HTLCError> {
+        params.sender.require_auth();
+        open_escrow(&env, params, true)
+    }
+
+    /// Add funds to an escrow that has not been filled yet, returning the new amount
+    pub fn increase_escrow(
+        env: Env,
+        escrow_id: Bytes,
+        additional_amount: i128,
+    ) -> Result<i128, HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let mut escrow = load_escrow(&env, &escrow_id)?;
+        escrow.sender.require_auth();
+
+        if paused(&env) {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+        if escrow.filled_amount != 0 || escrow.stream_start.is_some() {
+            return Err(HTLCError::EscrowCommitted);
+        }
+        if additional_amount <= 0 {
+            return Err(HTLCError::InvalidFillAmount);
+        }
+
+        let token_client = token::Client::new(&env, &escrow.token_address);
+        token_client.transfer(&escrow.sender, &env.current_contract_address(), &additional_amount);
+
+        escrow.amount = escrow
+            .amount
+            .checked_add(additional_amount)
+            .ok_or(HTLCError::ArithmeticOverflow)?;
+        env.storage().persistent().set(&DataKey::Escrow(escrow_id.clone()), &escrow);
+        adjust_locked(&env, &escrow.token_address, additional_amount);
+
+        env.events().publish(
+            (symbol_short!("increase"), escrow_id.clone()),
+            (additional_amount, escrow.amount),
+        );
+
+        Ok(escrow.amount)
+    }
+
+    /// Withdraw all remaining funds using the secret
+    pub fn withdraw(
+        env: Env,
+        escrow_id: Bytes,
+        secret: BytesN<32>,
+        receiver: Address,
+    ) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let escrow = load_escrow(&env, &escrow_id)?;
+        authorize_withdrawal(&env, &escrow_id, &escrow, &receiver)?;
+        let remaining = escrow.amount - escrow.filled_amount;
+        withdraw_with_secret(&env, &escrow_id, escrow, &secret, remaining, &receiver)
+    }
+
+    /// Withdraw all remaining funds to a threshold receiver, approved by enough of its signers
+    /// Each approver signs for the escrow ID alone, like a single receiver
+    pub fn withdraw_multisig(
+        env: Env,
+        escrow_id: Bytes,
+        secret: BytesN<32>,
+        approvers: Vec<Address>,
+    ) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let escrow = load_escrow(&env, &escrow_id)?;
+        authorize_threshold(&env, &escrow_id, &escrow, &approvers)?;
+        let receiver = escrow.receiver.clone();
+        let remaining = escrow.amount - escrow.filled_amount;
+        withdraw_with_secret(&env, &escrow_id, escrow, &secret, remaining, &receiver)
+    }
+
+    /// Withdraw all remaining funds on the receiver's behalf with a claim it signed off-chain
+    /// The receiver signs an auth entry over `get_claim_payload(escrow_id, expiry)`, which the
+    /// host checks against the account's own signers, so any relayer can submit it and pay
+    /// the fees
+    pub fn withdraw_with_signature(
+        env: Env,
+        escrow_id: Bytes,
+        secret: BytesN<32>,
+        expiry: u64,
+    ) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let escrow = load_escrow(&env, &escrow_id)?;
+        if env.ledger().timestamp() > expiry {
+            return Err(HTLCError::ClaimExpired);
+        }
+
+        // Verify the claim and consume the receiver's nonce so it cannot be replayed
+        require_single_receiver(&env, &escrow)?;
+        let nonce = Self::get_claim_nonce(env.clone(), escrow.receiver.clone());
+        let claim = claim_payload(&env, &escrow_id, &escrow.secret_hash, nonce, expiry);
+        escrow.receiver.require_auth_for_args((claim,).into_val(&env));
+        env.storage()
+            .persistent()
+            .set(&DataKey::ClaimNonce(escrow.receiver.clone()), &(nonce + 1));
+
+        let receiver = escrow.receiver.clone();
+        let remaining = escrow.amount - escrow.filled_amount;
+        withdraw_with_secret(&env, &escrow_id, escrow, &secret, remaining, &receiver)
+    }
+
+    /// Commit to revealing an escrow's secret as `caller` once the public window opens
+    /// `commitment` is `generate_withdraw_commitment(secret, caller)`, so it gives nothing away
+    pub fn commit_withdraw(
+        env: Env,
+        escrow_id: Bytes,
+        caller: Address,
+        commitment: BytesN<32>,
+    ) -> Result<(), HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        if escrow.reveal_delay == 0 {
+            return Err(HTLCError::CommitRevealDisabled);
+        }
+        caller.require_auth();
+
+        let committed_at = clock(&env, escrow.timelock_kind);
+        env.storage().persistent().set(
+            &DataKey::WithdrawCommit(escrow_id.clone(), caller.clone()),
+            &(commitment, committed_at),
+        );
+
+        log!(&env, "HTLC Withdraw Commit: ID={}, Caller={}", escrow_id, caller);
+
+        Ok(())
+    }
+
+    /// Reveal the secret behind an earlier `commit_withdraw` once the escrow's reveal delay
+    /// has passed, withdrawing all remaining funds to the receiver
+    pub fn reveal_withdraw(
+        env: Env,
+        escrow_id: Bytes,
+        secret: BytesN<32>,
+        caller: Address,
+    ) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let escrow = load_escrow(&env, &escrow_id)?;
+        caller.require_auth();
+
+        let now = clock(&env, escrow.timelock_kind);
+        if now < escrow.exclusive_until {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+        let key = DataKey::WithdrawCommit(escrow_id.clone(), caller.clone());
+        let (commitment, committed_at): (BytesN<32>, u64) = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(HTLCError::CommitmentNotFound)?;
+        if now < committed_at.saturating_add(escrow.reveal_delay) {
+            return Err(HTLCError::RevealTooEarly);
+        }
+        if withdraw_commitment(&env, &secret, &caller) != commitment {
+            return Err(HTLCError::InvalidCommitment);
+        }
+        env.storage().persistent().remove(&key);
+
+        let receiver = escrow.receiver.clone();
+        let remaining = escrow.amount - escrow.filled_amount;
+        withdraw_with_secret(&env, &escrow_id, escrow, &secret, remaining, &receiver)?;
+
+        env.events()
+            .publish((symbol_short!("revealed"), escrow_id), caller);
+
+        Ok(())
+    }
+
+    /// Run a create, withdraw or cancel call the signer authorized off-chain, returning the
+    /// escrow ID; see `meta_tx` for the authorized argument
+    pub fn execute_meta(
+        env: Env,
+        signer: Address,
+        call: MetaCall,
+        deadline: u64,
+    ) -> Result<Bytes, HTLCError> {
+        let nonce = meta_tx::verify(&env, &signer, &call, deadline)?;
+
+        let escrow_id = match call {
+            MetaCall::Create(params) => {
+                if params.sender != signer {
+                    return Err(HTLCError::UnauthorizedAccess);
+                }
+                open_escrow(&env, params, true)?
+            }
+            MetaCall::Withdraw(escrow_id, secret) => {
+                let escrow = load_escrow(&env, &escrow_id)?;
+                if escrow.receiver != signer {
+                    return Err(HTLCError::UnauthorizedAccess);
+                }
+                require_single_receiver(&env, &escrow)?;
+                let remaining = escrow.amount - escrow.filled_amount;
+                withdraw_with_secret(&env, &escrow_id, escrow, &secret, remaining, &signer)?;
+                escrow_id
+            }
+            MetaCall::Cancel(escrow_id) => {
+                Self::cancel(env.clone(), escrow_id.clone(), signer.clone())?;
+                escrow_id
+            }
+        };
+
+        env.events()
+            .publish((symbol_short!("relayed"), signer), (escrow_id.clone(), nonce));
+
+        Ok(escrow_id)
+    }
+
+    /// Withdraw part of the funds using the secret
+    /// Repeated withdrawals may not exceed the escrowed amount in total
+    pub fn withdraw_amount(
+        env: Env,
+        escrow_id: Bytes,
+        secret: BytesN<32>,
+        amount: i128,
+        receiver: Address,
+    ) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let escrow = load_escrow(&env, &escrow_id)?;
+        authorize_withdrawal(&env, &escrow_id, &escrow, &receiver)?;
+        withdraw_with_secret(&env, &escrow_id, escrow, &secret, amount, &receiver)
+    }
+
+    /// Withdraw from several escrows in one transaction, returning the total withdrawn
+    /// Every settlement must succeed or the whole batch is rolled back
+    pub fn settle_batch(
+        env: Env,
+        receiver: Address,
+        settlements: Vec<Settlement>,
+    ) -> Result<i128, HTLCError> {
+        let mut total: i128 = 0;
+        for settlement in settlements.iter() {
+            // Load escrow (fails if already withdrawn or cancelled)
+            let escrow = load_escrow(&env, &settlement.escrow_id)?;
+            authorize_withdrawal(&env, &settlement.escrow_id, &escrow, &receiver)?;
+            let amount = match settlement.amount {
+                0 => escrow.amount - escrow.filled_amount,
+                amount => amount,
+            };
+            withdraw_with_secret(
+                &env,
+                &settlement.escrow_id,
+                escrow,
+                &settlement.secret,
+                amount,
+                &receiver,
+            )?;
+            total = total
+                .checked_add(amount)
+                .ok_or(HTLCError::ArithmeticOverflow)?;
+        }
+
+        env.events().publish(
+            (symbol_short!("batch"), receiver.clone()),
+            (settlements.len(), total),
+        );
+
+        Ok(total)
+    }
+
+    /// Withdraw one slice of a partially fillable escrow using the secret at `fill_index`
+    /// The index must match the cumulative fill: part `i` covers fills up to
+    /// `(i + 1) / parts` of the amount and the extra secret at `parts` completes the order.
+    /// The taker may be the receiver or any of the escrow's resolvers
+    pub fn withdraw_partial(
+        env: Env,
+        escrow_id: Bytes,
+        proof: Vec<BytesN<32>>,
+        secret: BytesN<32>,
+        fill_index: u32,
+        amount: i128,
+        taker: Address,
+    ) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let mut escrow = load_escrow(&env, &escrow_id)?;
+        if escrow.parts == 0 {
+            return Err(HTLCError::InvalidFillIndex);
+        }
+
+        // Verify the fill stays within the escrowed amount
+        let filled_after = escrow
+            .filled_amount
+            .checked_add(amount)
+            .ok_or(HTLCError::InvalidFillAmount)?;
+        if amount <= 0 || filled_after > escrow.amount {
+            return Err(HTLCError::InvalidFillAmount);
+        }
+
+        // Verify the secret index matches the fill and has not been used before
+        let expected_index = if filled_after == escrow.amount {
+            escrow.parts
+        } else {
+            let scaled = (filled_after - 1)
+                .checked_mul(escrow.parts as i128)
+                .ok_or(HTLCError::InvalidFillAmount)?;
+            (scaled / escrow.amount) as u32
+        };
+        if fill_index != expected_index
+            || escrow.last_fill_index.is_some_and(|last| fill_index <= last)
+        {
+            return Err(HTLCError::InvalidFillIndex);
+        }
+
+        // Verify the secret belongs to the committed tree
+        let part_hash = hash_secret(&env, &secret);
+        let leaf = merkle_leaf(&env, fill_index, &part_hash);
+        if merkle_root(&env, leaf, &proof) != escrow.secret_hash {
+            return Err(HTLCError::InvalidMerkleProof);
+        }
+
+        authorize_fill(&env, &escrow_id, &escrow, &taker)?;
+        check_price_condition(&env, &escrow)?;
+
+        // Record the fill, settling the escrow once it is complete
+        escrow.filled_amount = filled_after;
+        escrow.last_fill_index = Some(fill_index);
+        publish_fill(&env, &escrow_id, &taker, amount, filled_after);
+        if filled_after == escrow.amount {
+            settle(&env, &escrow_id, &escrow, SettlementStatus::Withdrawn, &taker);
+        } else {
+            env.storage().persistent().set(&DataKey::Escrow(escrow_id.clone()), &escrow);
+        }
+        adjust_locked(&env, &escrow.token_address, -amount);
+
+        let fill = Fill {
+            taker: taker.clone(),
+            amount,
+            filled_at: env.ledger().timestamp(),
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Fill(escrow_id.clone(), fill_index), &fill);
+
+        // Publish the part's preimage for counterpart-chain relayers
+        publish_secret(&env, &escrow_id, &part_hash, &secret);
+
+        // Transfer the slice to the taker, net of the protocol fee and captured surplus
+        if !token_ready(&env, &escrow.token_address, &taker) {
+            return Err(HTLCError::TrustlineMissing);
+        }
+        check_compliance(&env, &taker, &escrow.token_address, amount)?;
+        let surplus = capture_surplus(&env, &escrow, amount)?;
+        let fee = charge_fee(&env, &escrow_id, &escrow, amount)?;
+        send(&env, &escrow.token_address, &taker, amount - fee - surplus)?;
+        notify_receiver(&env, &escrow_id, &escrow, &taker, amount - fee - surplus);
+        notify_withdrawn(&env, &escrow_id, &escrow, amount);
+
+        log!(
+            &env,
+            "HTLC Partial Withdrawal: ID={}, Taker={}, Amount={}, Fee={}, Surplus={}, Index={}",
+            escrow_id,
+            taker,
+            amount,
+            fee,
+            surplus,
+            fill_index
+        );
+
+        Ok(())
+    }
+
+    /// Cancel escrow after timelock expires
+    pub fn cancel(
+        env: Env,
+        escrow_id: Bytes,
+        sender: Address,
+    ) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let escrow = load_escrow(&env, &escrow_id)?;
+        require_no_refund_point(&env, &escrow_id)?;
+
+        // Verify timelock has expired
+        if clock(&env, escrow.timelock_kind) < escrow.timelock {
+            return Err(HTLCError::TimelockNotExpired);
+        }
+
+        // Verify sender
+        if sender != escrow.sender {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+
+        // A revealed secret commits the funds to the receiver's stream
+        if escrow.stream_start.is_some() {
+            return Err(HTLCError::StreamActive);
+        }
+
+        // Replace escrow with its cancellation receipt
+        let remaining = escrow.amount - escrow.filled_amount;
+        settle(&env, &escrow_id, &escrow, SettlementStatus::Cancelled, &sender);
+        adjust_locked(&env, &escrow.token_address, -remaining);
+
+        // Compensate the receiver for reserved inventory, then refund the rest to sender
+        let penalty = remaining
+            .checked_mul(escrow.cancel_penalty_bps as i128)
+            .map(|scaled| scaled / FEE_BPS_BASE)
+            .ok_or(HTLCError::ArithmeticOverflow)?;
+        if penalty > 0 {
+            send(&env, &escrow.token_address, &escrow.receiver, penalty)?;
+        }
+        send(&env, &escrow.token_address, &sender, remaining - penalty)?;
+        release_basket(&env, &escrow, &sender)?;
+        notify_cancelled(&env, &escrow_id, &escrow, remaining - penalty);
+
+        log!(
+            &env,
+            "HTLC Cancellation: ID={}, Sender={}, Amount={}, Penalty={}",
+            escrow_id,
+            sender,
+            remaining,
+            penalty
+        );
+
+        Ok(())
+    }
+
+    /// Bind a pending escrow's refund to the scalar of `point` (sender only, once): from then on
+    /// it can only be cancelled with `cancel_with_scalar`, which publishes that scalar
+    /// A key-split swap (such as Monero's) sets the sender's public key share here, so the
+    /// sender cannot refund without handing the counterparty what it needs to refund too
+    pub fn set_refund_point(
+        env: Env,
+        escrow_id: Bytes,
+        point: BytesN<32>,
+    ) -> Result<(), HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        escrow.sender.require_auth();
+
+        let key = DataKey::RefundPoint(escrow_id.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+        env.storage().persistent().set(&key, &point);
+
+        env.events().publish((symbol_short!("refundpt"), escrow_id.clone()), point.clone());
+        log!(&env, "HTLC Refund Point Set: ID={}, Point={}", escrow_id, point);
+
+        Ok(())
+    }
+
+    /// Cancel an expired escrow bound to a refund point, publishing the point's `scalar` where
+    /// `get_revealed_secret(point)` returns it; anyone holding the scalar may call this and the
+    /// refund still goes to the sender
+    pub fn cancel_with_scalar(
+        env: Env,
+        escrow_id: Bytes,
+        scalar: BytesN<32>,
+    ) -> Result<(), HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        let key = DataKey::RefundPoint(escrow_id.clone());
+        let point: BytesN<32> = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(HTLCError::UnauthorizedAccess)?;
+        if !ptlc::unlocks(&env, &point, &scalar, &escrow_id) {
+            return Err(HTLCError::InvalidSecret);
+        }
+
+        env.storage().persistent().remove(&key);
+        publish_secret(&env, &escrow_id, &point, &scalar);
+        Self::cancel(env, escrow_id, escrow.sender)
+    }
+
+    /// The refund point an escrow's cancellation must reveal the scalar of, if any
+    pub fn get_refund_point(env: Env, escrow_id: Bytes) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::RefundPoint(escrow_id))
+    }
+
+    /// Commit to filling an escrow, which stops the maker reclaiming it early
+    /// The resolver must be the receiver or one of the escrow's resolvers
+    pub fn commit(env: Env, escrow_id: Bytes, resolver: Address) -> Result<(), HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        if resolver != escrow.receiver && !escrow.resolvers.contains(&resolver) {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+        resolver.require_auth();
+
+        let key = DataKey::Commitment(escrow_id.clone());
+        if env.storage().persistent().has(&key) {
+            return Err(HTLCError::EscrowCommitted);
+        }
+        env.storage().persistent().set(&key, &resolver);
+
+        log!(&env, "HTLC Commitment: ID={}, Resolver={}", escrow_id, resolver);
+
+        Ok(())
+    }
+
+    /// Reclaim an escrow before its timelock, once the grace period after creation has
+    /// passed without any fill or resolver commitment
+    pub fn cancel_unfilled(env: Env, escrow_id: Bytes) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let escrow = load_escrow(&env, &escrow_id)?;
+        escrow.sender.require_auth();
+        require_no_refund_point(&env, &escrow_id)?;
+
+        if escrow.filled_amount != 0
+            || escrow.stream_start.is_some()
+            || env.storage().persistent().has(&DataKey::Commitment(escrow_id.clone()))
+        {
+            return Err(HTLCError::EscrowCommitted);
+        }
+
+        let grace_period = Self::get_cancel_grace_period(env.clone());
+        if env.ledger().timestamp() < escrow.created_at.saturating_add(grace_period) {
+            return Err(HTLCError::GracePeriodNotElapsed);
+        }
+
+        // Replace escrow with its cancellation receipt
+        settle(&env, &escrow_id, &escrow, SettlementStatus::Cancelled, &escrow.sender);
+        adjust_locked(&env, &escrow.token_address, -escrow.amount);
+
+        // Refund everything to sender
+        send(&env, &escrow.token_address, &escrow.sender, escrow.amount)?;
+        release_basket(&env, &escrow, &escrow.sender)?;
+        notify_cancelled(&env, &escrow_id, &escrow, escrow.amount);
+
+        log!(
+            &env,
+            "HTLC Early Cancellation: ID={}, Sender={}, Amount={}",
+            escrow_id,
+            escrow.sender,
+            escrow.amount
+        );
+
+        Ok(())
+    }
+
+    /// Refund the unfilled remainder to the sender at any time, with both parties' consent
+    pub fn mutual_cancel(env: Env, escrow_id: Bytes) -> Result<(), HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let escrow = load_escrow(&env, &escrow_id)?;
+        escrow.sender.require_auth();
+        escrow.receiver.require_auth();
+
+        // Replace escrow with its cancellation receipt
+        let remaining = escrow.amount - escrow.filled_amount;
+        settle(&env, &escrow_id, &escrow, SettlementStatus::Cancelled, &escrow.sender);
+        adjust_locked(&env, &escrow.token_address, -remaining);
+
+        // Refund everything left to sender
+        send(&env, &escrow.token_address, &escrow.sender, remaining)?;
+        release_basket(&env, &escrow, &escrow.sender)?;
+        notify_cancelled(&env, &escrow_id, &escrow, remaining);
+
+        log!(
+            &env,
+            "HTLC Mutual Cancellation: ID={}, Sender={}, Receiver={}, Amount={}",
+            escrow_id,
+            escrow.sender,
+            escrow.receiver,
+            remaining
+        );
+
+        Ok(())
+    }
+
+    /// Flag an escrow whose funds the issuer has frozen or clawed back, halting withdrawal and
+    /// cancellation until it is resolved; only its sender, receiver or arbiter may call this
+    pub fn flag_dispute(env: Env, escrow_id: Bytes, caller: Address) -> Result<(), HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        let arbiter = escrow.arbiter.as_ref();
+        if caller != escrow.sender && caller != escrow.receiver && arbiter != Some(&caller) {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+        caller.require_auth();
+
+        let this = env.current_contract_address();
+        let impaired = |token_address: &Address| {
+            !token_ready(&env, token_address, &this)
+                || token::Client::new(&env, token_address).balance(&this)
+                    < Self::get_locked_amount(env.clone(), token_address.clone())
+        };
+        if !impaired(&escrow.token_address)
+            && !escrow.basket.iter().any(|(basket_token, _)| impaired(&basket_token))
+        {
+            return Err(HTLCError::NotDisputed);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Disputed(escrow_id.clone()), &env.ledger().timestamp());
+        env.events()
+            .publish((symbol_short!("dispute"), escrow_id.clone()), escrow.token_address.clone());
+
+        log!(&env, "HTLC Escrow Disputed: ID={}, Token={}", escrow_id, escrow.token_address);
+
+        Ok(())
+    }
+
+    /// Freeze an escrow that has gone wrong off-chain until its arbiter rules on it
+    /// Either party may raise a dispute within the escrow's dispute window
+    pub fn raise_dispute(env: Env, escrow_id: Bytes, caller: Address) -> Result<(), HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        if escrow.arbiter.is_none() || (caller != escrow.sender && caller != escrow.receiver) {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+        caller.require_auth();
+        if env.ledger().timestamp() >= escrow.created_at.saturating_add(escrow.dispute_window) {
+            return Err(HTLCError::DisputeWindowClosed);
+        }
+
+        env.storage()
+            .persistent()
+            .set(&DataKey::Disputed(escrow_id.clone()), &env.ledger().timestamp());
+        env.events()
+            .publish((symbol_short!("dispute"), escrow_id.clone()), caller.clone());
+
+        log!(&env, "HTLC Escrow Disputed: ID={}, RaisedBy={}", escrow_id, caller);
+
+        Ok(())
+    }
+
+    /// Settle a disputed escrow, paying `amount` to `recipient`, who must be its sender or receiver
+    /// With an arbiter, the arbiter rules and the rest goes to the other party; otherwise the
+    /// admin pays out what can still be recovered and the rest of the unfilled amount is
+    /// written off
+    pub fn resolve_dispute(
+        env: Env,
+        escrow_id: Bytes,
+        recipient: Address,
+        amount: i128,
+    ) -> Result<(), HTLCError> {
+        let storage = env.storage().persistent();
+        if !storage.has(&DataKey::Disputed(escrow_id.clone())) {
+            return Err(HTLCError::NotDisputed);
+        }
+        let escrow: Escrow = storage
+            .get(&DataKey::Escrow(escrow_id.clone()))
+            .ok_or(HTLCError::EscrowNotFound)?;
+        let remaining = escrow.amount - escrow.filled_amount;
+        if amount < 0 || amount > remaining {
+            return Err(HTLCError::InvalidFillAmount);
+        }
+
+        let other_party = if recipient == escrow.sender {
+            escrow.receiver.clone()
+        } else if recipient == escrow.receiver {
+            escrow.sender.clone()
+        } else {
+            return Err(HTLCError::UnauthorizedAccess);
+        };
+        let (resolver, other_party) = match &escrow.arbiter {
+            Some(arbiter) => (arbiter.clone(), Some(other_party)),
+            None => (load_admin(&env)?, None),
+        };
+        resolver.require_auth();
+
+        settle(&env, &escrow_id, &escrow, SettlementStatus::Resolved, &resolver);
+        adjust_locked(&env, &escrow.token_address, -remaining);
+        if amount > 0 {
+            send(&env, &escrow.token_address, &recipient, amount)?;
+        }
+        if let Some(other_party) = &other_party {
+            if remaining > amount {
+                send(&env, &escrow.token_address, other_party, remaining - amount)?;
+            }
+        }
+        release_basket(&env, &escrow, &recipient)?;
+        env.events().publish(
+            (symbol_short!("resolved"), escrow_id.clone()),
+            (resolver.clone(), recipient.clone(), amount),
+        );
+
+        log!(
+            &env,
+            "HTLC Dispute Resolved: ID={}, Recipient={}, Amount={}, Remainder={}",
+            escrow_id,
+            recipient,
+            amount,
+            remaining - amount
+        );
+
+        Ok(())
+    }
+
+    /// Seconds after creation before an unfilled escrow can be reclaimed with `cancel_unfilled`
+    pub fn get_cancel_grace_period(env: Env) -> u64 {
+        env.storage()
+            .instance()
+            .get(&DataKey::CancelGracePeriod)
+            .unwrap_or(DEFAULT_CANCEL_GRACE_PERIOD)
+    }
+
+    /// Next nonce a receiver must sign into a `withdraw_with_signature` claim
+    pub fn get_claim_nonce(env: Env, receiver: Address) -> u64 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::ClaimNonce(receiver))
+            .unwrap_or(0)
+    }
+
+    /// Claim the receiver's auth entry must carry to authorize `withdraw_with_signature`
+    pub fn get_claim_payload(
+        env: Env,
+        escrow_id: Bytes,
+        expiry: u64,
+    ) -> Result<ClaimPayload, HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        let nonce = Self::get_claim_nonce(env.clone(), escrow.receiver.clone());
+        Ok(claim_payload(&env, &escrow_id, &escrow.secret_hash, nonce, expiry))
+    }
+
+    /// Next nonce an account must sign into an `execute_meta` call
+    pub fn get_meta_nonce(env: Env, signer: Address) -> u64 {
+        meta_tx::nonce(&env, &signer)
+    }
+
+    /// Argument an account's auth entry must carry to authorize `execute_meta` for a call
+    pub fn get_meta_payload(env: Env, signer: Address, call: MetaCall, deadline: u64) -> MetaTx {
+        meta_tx::payload(&env, &signer, &call, deadline)
+    }
+
+    /// Whether an escrow has been flagged as disputed and awaits resolution
+    pub fn is_disputed(env: Env, escrow_id: Bytes) -> bool {
+        env.storage().persistent().has(&DataKey::Disputed(escrow_id))
+    }
+
+    /// Whether new escrows and top-ups are paused
+    pub fn is_paused(env: Env) -> bool {
+        paused(&env)
+    }
+
+    /// Whether allowlist mode is on
+    pub fn is_allowlist_enabled(env: Env) -> bool {
+        allowlist_enabled(&env)
+    }
+
+    /// Whether new escrows may lock a token (always true while allowlist mode is off)
+    pub fn is_token_allowed(env: Env, token_address: Address) -> bool {
+        token_allowed(&env, &token_address)
+    }
+
+    /// Whether new escrows may swap their payout through a router
+    pub fn is_swap_router_allowed(env: Env, router: Address) -> bool {
+        env.storage().persistent().has(&DataKey::SwapRouter(router))
+    }
+
+    /// Escrows allowed per sender and the window in seconds, if rate limiting is on
+    pub fn get_rate_limit(env: Env) -> Option<(u32, u64)> {
+        env.storage().instance().get(&DataKey::RateLimit)
+    }
+
+    /// Whether escrows created by a sender skip the rate limit
+    pub fn is_rate_limit_exempt(env: Env, sender: Address) -> bool {
+        env.storage().persistent().has(&DataKey::RateLimitExempt(sender))
+    }
+
+    /// Compliance hook consulted before funds move, if one is configured
+    pub fn get_compliance_hook(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::ComplianceHook)
+    }
+
+    /// Smallest amount a new escrow may lock in a token
+    pub fn get_min_amount(env: Env, token_address: Address) -> i128 {
+        min_amount(&env, &token_address)
+    }
+
+    /// Resolver that committed to filling an escrow, if any
+    pub fn get_commitment(env: Env, escrow_id: Bytes) -> Option<Address> {
+        env.storage().persistent().get(&DataKey::Commitment(escrow_id))
+    }
+
+    /// Pay the receiver what has vested on a streamed escrow since the last claim,
+    /// returning the amount claimed
+    pub fn claim_streamed(env: Env, escrow_id: Bytes) -> Result<i128, HTLCError> {
+        // Load escrow (fails if already withdrawn or cancelled)
+        let mut escrow = load_escrow(&env, &escrow_id)?;
+        let stream_start = escrow.stream_start.ok_or(HTLCError::StreamNotStarted)?;
+
+        let elapsed = env
+            .ledger()
+            .timestamp()
+            .saturating_sub(stream_start)
+            .min(escrow.stream_duration);
+        let vested = escrow
+            .amount
+            .checked_mul(elapsed as i128)
+            .map(|scaled| scaled / escrow.stream_duration as i128)
+            .ok_or(HTLCError::ArithmeticOverflow)?;
+        let amount = vested - escrow.filled_amount;
+        if amount == 0 {
+            return Ok(0);
+        }
+
+        // Record the claim, replacing the escrow with its receipt once fully vested
+        escrow.filled_amount = vested;
+        let receiver = escrow.receiver.clone();
+        publish_fill(&env, &escrow_id, &receiver, amount, vested);
+        if vested == escrow.amount {
+            settle(&env, &escrow_id, &escrow, SettlementStatus::Withdrawn, &receiver);
+        } else {
+            env.storage().persistent().set(&DataKey::Escrow(escrow_id.clone()), &escrow);
+        }
+        adjust_locked(&env, &escrow.token_address, -amount);
+
+        let (fee, surplus) = pay_receiver(&env, &escrow_id, &escrow, amount, &receiver)?;
+        notify_withdrawn(&env, &escrow_id, &escrow, amount);
+
+        log!(
+            &env,
+            "HTLC Streamed Claim: ID={}, Receiver={}, Amount={}, Fee={}, Surplus={}",
+            escrow_id,
+            receiver,
+            amount,
+            fee,
+            surplus
+        );
+
+        Ok(amount)
+    }
+
+    /// Get escrow details (only while the escrow is still pending)
+    pub fn get_escrow(env: Env, escrow_id: Bytes) -> Option<Escrow> {
+        env.storage().persistent().get(&DataKey::Escrow(escrow_id))
+    }
+
+    /// Taker amount currently required by the escrow's auction curve
+    pub fn get_current_auction_rate(env: Env, escrow_id: Bytes) -> Result<i128, HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        current_taker_amount(&env, &escrow)
+    }
+
+    /// Preview how withdrawing `amount` now would be priced and split,
+    /// using the same fee and surplus math as the withdrawal itself
+    pub fn quote_withdrawal(
+        env: Env,
+        escrow_id: Bytes,
+        amount: i128,
+    ) -> Result<WithdrawalQuote, HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        let filled_after = escrow
+            .filled_amount
+            .checked_add(amount)
+            .ok_or(HTLCError::InvalidFillAmount)?;
+        if amount <= 0 || filled_after > escrow.amount {
+            return Err(HTLCError::InvalidFillAmount);
+        }
+
+        let taker_amount = current_taker_amount(&env, &escrow)?
+            .checked_mul(amount)
+            .map(|scaled| scaled / escrow.amount)
+            .ok_or(HTLCError::ArithmeticOverflow)?;
+        let (treasury_fee, integrator_fee) = fee_split(&env, &escrow, amount)?;
+        let surplus = surplus_share(&env, &escrow, amount)?;
+
+        Ok(WithdrawalQuote {
+            amount,
+            taker_amount,
+            treasury_fee,
+            integrator_fee,
+            surplus,
+            payout: amount - treasury_fee - integrator_fee - surplus,
+        })
+    }
+
+    /// Amount still available to withdraw (0 once settled or if unknown)
+    pub fn get_remaining_amount(env: Env, escrow_id: Bytes) -> i128 {
+        match Self::get_escrow(env, escrow_id) {
+            Some(escrow) => escrow.amount - escrow.filled_amount,
+            None => 0,
+        }
+    }
+
+    /// Get the partial fill claimed with the secret at `fill_index`
+    pub fn get_fill(env: Env, escrow_id: Bytes, fill_index: u32) -> Option<Fill> {
+        env.storage().persistent().get(&DataKey::Fill(escrow_id, fill_index))
+    }
+
+    /// Get the settlement receipt of a withdrawn or cancelled escrow
+    pub fn get_receipt(env: Env, escrow_id: Bytes) -> Option<SettlementReceipt> {
+        env.storage().persistent().get(&DataKey::Receipt(escrow_id))
+    }
+
+    /// Check if secret is valid for escrow (pending or settled)
+    /// For a pending PTLC escrow the secret is the scalar of its adaptor point
+    pub fn verify_secret(env: Env, escrow_id: Bytes, secret: BytesN<32>) -> bool {
+        let secret_hash = match Self::get_escrow(env.clone(), escrow_id.clone()) {
+            Some(escrow) => match escrow.lock_type {
+                LockType::Hashlock => return hash_secret(&env, &secret) == escrow.secret_hash,
+                LockType::AdaptorPoint => return ptlc::opens(&escrow.secret_hash, &secret),
+                LockType::Sha256 => return sha256_secret(&env, &secret) == escrow.secret_hash,
+            },
+            None => match Self::get_receipt(env.clone(), escrow_id) {
+                Some(receipt) => receipt.secret_hash,
+                None => return false,
+            },
+        };
+        // Receipts do not keep the lock type, so either hash opens them
+        hash_secret(&env, &secret) == secret_hash || sha256_secret(&env, &secret) == secret_hash
+    }
+
+    /// Whether a counterpart escrow is still pending under `expected_hashlock`, holds at least
+    /// `expected_amount` and cannot be cancelled before `expected_timelock_bound`; a per-swap
+    /// contract counts only at the address the escrow factory deploys its terms to
+    pub fn verify_counterpart(
+        env: Env,
+        counterpart: Counterpart,
+        expected_hashlock: BytesN<32>,
+        expected_amount: i128,
+        expected_timelock_bound: u64,
+    ) -> bool {
+        let (hashlock, amount, timelock) = match counterpart {
+            Counterpart::Escrow(escrow_id) => match Self::get_escrow(env.clone(), escrow_id) {
+                // Multi-part escrows lock a Merkle root rather than the hashlock itself
+                Some(escrow) if escrow.parts == 0 => (
+                    escrow.secret_hash,
+                    escrow.amount - escrow.filled_amount,
+                    escrow.timelock,
+                ),
+                _ => return false,
+            },
+            Counterpart::Contract(address) => {
+                let Some(factory) = env.storage().instance().get(&DataKey::EscrowFactory) else {
+                    return false;
+                };
+                let client = SwapEscrowClient::new(&env, &address);
+                let immutables = match (client.try_get_status(), client.try_get_immutables()) {
+                    (Ok(Ok(Some(EscrowStatus::Pending))), Ok(Ok(Some(immutables)))) => immutables,
+                    _ => return false,
+                };
+                // Any contract can report these terms; only the factory's deployment salted
+                // with their hash is bound by them
+                let salt: BytesN<32> =
+                    env.crypto().keccak256(&immutables.clone().to_xdr(&env)).into();
+                if env.deployer().with_address(factory, salt).deployed_address() != address {
+                    return false;
+                }
+                (immutables.hashlock, immutables.amount, immutables.timelock)
+            }
+        };
+        hashlock == expected_hashlock
+            && amount >= expected_amount
+            && timelock >= expected_timelock_bound
+    }
+
+    /// Check if escrow can be cancelled
+    pub fn can_cancel(env: Env, escrow_id: Bytes) -> bool {
+        if let Some(escrow) = Self::get_escrow(env.clone(), escrow_id) {
+            clock(&env, escrow.timelock_kind) >= escrow.timelock
+        } else {
+            false
+        }
+    }
+
+    /// Whether the receiver can currently be paid every token the escrow holds
+    /// (false when a classic asset's trustline is missing or deauthorized)
+    pub fn check_receiver_ready(env: Env, escrow_id: Bytes) -> bool {
+        match Self::get_escrow(env.clone(), escrow_id) {
+            Some(escrow) => ensure_receiver_ready(&env, &escrow, &escrow.receiver).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Check if escrow can be withdrawn in the given phase
+    pub fn can_withdraw(env: Env, escrow_id: Bytes, phase: Phase) -> bool {
+        if let Some(escrow) = Self::get_escrow(env.clone(), escrow_id) {
+            let current_time = clock(&env, escrow.timelock_kind);
+            match phase {
+                Phase::Exclusive => current_time < escrow.exclusive_until,
+                Phase::Public => current_time >= escrow.exclusive_until,
+            }
+        } else {
+            false
+        }
+    }
+
+    /// Seconds (or ledgers, for ledger timelocks) until the exclusive window ends and until
+    /// cancellation opens
+    pub fn time_remaining(env: Env, escrow_id: Bytes) -> Result<(u64, u64), HTLCError> {
+        let escrow = load_escrow(&env, &escrow_id)?;
+        let current_time = clock(&env, escrow.timelock_kind);
+        Ok((
+            escrow.exclusive_until.saturating_sub(current_time),
+            escrow.timelock.saturating_sub(current_time),
+        ))
+    }
+
+    /// Number of pending escrows, for paging through `list_escrows`
+    pub fn get_pending_count(env: Env) -> u32 {
+        env.storage().persistent().get(&DataKey::PendingCount).unwrap_or(0)
+    }
+
+    /// List pending escrow IDs matching `filter`
+    /// Scans `limit` entries of the pending index starting at `start`, so callers
+    /// page by advancing `start` by `limit` until it reaches `get_pending_count`
+    pub fn list_escrows(env: Env, filter: EscrowFilter, start: u32, limit: u32) -> Vec<Bytes> {
+        let storage = env.storage().persistent();
+        let end = Self::get_pending_count(env.clone()).min(start.saturating_add(limit));
+        let mut result = Vec::new(&env);
+
+        for position in start..end {
+            let escrow_id: Bytes = match storage.get(&DataKey::PendingAt(position)) {
+                Some(escrow_id) => escrow_id,
+                None => continue,
+            };
+            let matches = match filter {
+                EscrowFilter::Pending => true,
+                _ => {
+                    let escrow: Escrow = match storage.get(&DataKey::Escrow(escrow_id.clone())) {
+                        Some(escrow) => escrow,
+                        None => continue,
+                    };
+                    let expired = clock(&env, escrow.timelock_kind) >= escrow.timelock;
+                    (filter == EscrowFilter::Cancellable) == expired
+                }
+            };
+            if matches {
+                result.push_back(escrow_id);
+            }
+        }
+
+        result
+    }
+
+    /// Get all active escrows for a given address (as sender or receiver)
+    /// This function helps with escrow management and monitoring
+    pub fn get_escrows_for_address(env: Env, _address: Address) -> Vec<(Bytes, Escrow)> {
         // Note: In a production environment, you might want to implement 
         // an indexing system for better performance
         let result = Vec::new(&env);
@@ -256,17 +2017,1184 @@ impl HTLCEscrow {
         result
     }
 
-    /// Emergency function to get contract balance (for debugging)
-    pub fn get_contract_balance(env: Env, token_address: Address) -> i128 {
-        let token_client = token::Client::new(&env, &token_address);
-        token_client.balance(&env.current_contract_address())
+    /// Get the total amount of a token currently locked in pending escrows
+    pub fn get_locked_amount(env: Env, token_address: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Locked(token_address))
+            .unwrap_or(0)
+    }
+
+    /// Emergency function to get contract balance (for debugging)
+    pub fn get_contract_balance(env: Env, token_address: Address) -> i128 {
+        let token_client = token::Client::new(&env, &token_address);
+        token_client.balance(&env.current_contract_address())
+    }
+
+    /// Current admin, if the contract has been initialized
+    pub fn get_admin(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Admin)
+    }
+
+    /// Current protocol fee in basis points (0 until initialized)
+    pub fn get_fee_bps(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::FeeBps).unwrap_or(0)
+    }
+
+    /// Share of positive slippage captured for the treasury, in basis points
+    pub fn get_surplus_bps(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::SurplusBps).unwrap_or(0)
+    }
+
+    /// Protocol fees accrued in a token and not yet sent to the treasury
+    pub fn get_accrued_fees(env: Env, token_address: Address) -> i128 {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Fees(token_address))
+            .unwrap_or(0)
+    }
+
+    /// Get the preimage revealed by a successful withdrawal, keyed by its hash
+    pub fn get_revealed_secret(env: Env, secret_hash: BytesN<32>) -> Option<BytesN<32>> {
+        env.storage().persistent().get(&DataKey::RevealedSecret(secret_hash))
+    }
+
+    /// Address of the native XLM Stellar Asset Contract used when no token is given
+    pub fn get_native_token(env: Env) -> Address {
+        native_token(&env)
+    }
+
+    /// Rescale an amount between token decimals (e.g. 18 on EVM, 7 on Stellar), rounding
+    /// dropped digits up or down
+    pub fn normalize_amount(
+        _env: Env,
+        amount: i128,
+        from_decimals: u32,
+        to_decimals: u32,
+        round_up: bool,
+    ) -> Result<i128, HTLCError> {
+        let rounding = if round_up { Rounding::Up } else { Rounding::Down };
+        decimals::convert(amount, from_decimals, to_decimals, rounding)
+            .ok_or(HTLCError::ArithmeticOverflow)
+    }
+
+    /// Commitment and timestamp (or ledger) stored by `commit_withdraw`, if any
+    pub fn get_withdraw_commit(
+        env: Env,
+        escrow_id: Bytes,
+        caller: Address,
+    ) -> Option<(BytesN<32>, u64)> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::WithdrawCommit(escrow_id, caller))
+    }
+
+    /// Commitment `caller` passes to `commit_withdraw` for `secret`
+    pub fn generate_withdraw_commitment(
+        env: Env,
+        secret: BytesN<32>,
+        caller: Address,
+    ) -> BytesN<32> {
+        withdraw_commitment(&env, &secret, &caller)
+    }
+
+    /// Utility function to generate secret hash
+    pub fn generate_secret_hash(env: Env, secret: BytesN<32>) -> BytesN<32> {
+        hash_secret(&env, &secret)
+    }
+}
+
+/// Lock the sender's funds in a new escrow, pulling them under its allowance if requested
+fn open_escrow(env: &Env, params: EscrowParams, from_allowance: bool) -> Result<Bytes, HTLCError> {
+    let EscrowParams {
+        sender,
+        receiver,
+        amount,
+        secret_hash,
+        timelock,
+        exclusive_until,
+        token_address,
+        order_id,
+        metadata,
+        parts,
+        resolvers,
+        taker_amount,
+        auction,
+        integrator,
+        integrator_share_bps,
+        expected_amount,
+        receiver_hook,
+        cancel_penalty_bps,
+        basket,
+        stream_duration,
+        payees,
+        swap_on_withdraw,
+        timelock_kind,
+        price_condition,
+        arbiter,
+        dispute_window,
+        receiver_auth,
+        lock_type,
+        reveal_delay,
+    } = params;
+
+    if paused(env) {
+        return Err(HTLCError::UnauthorizedAccess);
     }
 
-    /// Utility function to generate secret hash
-    pub fn generate_secret_hash(env: Env, secret: String) -> Bytes {
-        // Convert String to Bytes using proper Soroban SDK methods
-        let secret_val = secret.to_val();
-        let secret_bytes = Bytes::from_val(&env, &secret_val);
-        env.crypto().keccak256(&secret_bytes).into()
+    // Verify timelock is in the future
+    let current_time = env.ledger().timestamp();
+    if timelock <= clock(env, timelock_kind) {
+        return Err(HTLCError::InvalidTimelock);
+    }
+
+    // Exclusive window must end by the timelock (a past value means no exclusive window)
+    if exclusive_until > timelock {
+        return Err(HTLCError::InvalidTimelock);
+    }
+
+    if metadata.len() > MAX_METADATA_LEN {
+        return Err(HTLCError::MetadataTooLarge);
+    }
+
+    if taker_amount < 0 {
+        return Err(HTLCError::InvalidAuction);
+    }
+    if let Auction::Dutch(params) = &auction {
+        if params.duration == 0 {
+            return Err(HTLCError::InvalidAuction);
+        }
+    }
+
+    if integrator_share_bps as i128 > FEE_BPS_BASE {
+        return Err(HTLCError::InvalidFee);
+    }
+
+    if expected_amount < 0 || expected_amount > amount {
+        return Err(HTLCError::InvalidExpectedAmount);
+    }
+
+    if cancel_penalty_bps as i128 > FEE_BPS_BASE {
+        return Err(HTLCError::InvalidPenalty);
+    }
+
+    // Multi-asset escrows release everything at once, so they cannot be filled in parts
+    if basket.len() > MAX_BASKET_LEN || (!basket.is_empty() && parts != 0) {
+        return Err(HTLCError::InvalidBasket);
+    }
+
+    // Streamed escrows vest as a whole, so they cannot be filled in parts either
+    if stream_duration > 0 && (parts != 0 || !basket.is_empty()) {
+        return Err(HTLCError::InvalidFillAmount);
+    }
+    for (_, basket_amount) in basket.iter() {
+        if basket_amount <= 0 {
+            return Err(HTLCError::InvalidBasket);
+        }
+    }
+
+    // Payout shares must cover the whole amount
+    if !payees.is_empty() {
+        if payees.len() > MAX_PAYEES {
+            return Err(HTLCError::InvalidPayees);
+        }
+        let mut total_bps: i128 = 0;
+        for (_, share_bps) in payees.iter() {
+            if share_bps == 0 {
+                return Err(HTLCError::InvalidPayees);
+            }
+            total_bps += share_bps as i128;
+        }
+        if total_bps != FEE_BPS_BASE {
+            return Err(HTLCError::InvalidPayees);
+        }
+    }
+
+    let native = token_address.is_none();
+    let token_address = token_address.unwrap_or_else(|| native_token(env));
+
+    // A revealer must be able to commit and wait out the delay before the timelock
+    if reveal_delay >= timelock - clock(env, timelock_kind) {
+        return Err(HTLCError::InvalidTimelock);
+    }
+
+    // Adaptor points and SHA-256 hashes lock a single secret, so there is no Merkle tree of
+    // part secrets
+    if lock_type != LockType::Hashlock && parts != 0 {
+        return Err(HTLCError::InvalidFillAmount);
+    }
+
+    // Threshold receivers need a reachable threshold of distinct signers
+    if let ReceiverAuth::Threshold(multisig) = &receiver_auth {
+        let signers = &multisig.signers;
+        if multisig.threshold == 0
+            || multisig.threshold > signers.len()
+            || signers.len() > MAX_SIGNERS
+            || signers.iter().enumerate().any(|(i, signer)| {
+                signers.iter().skip(i + 1).any(|other| other == signer)
+            })
+        {
+            return Err(HTLCError::InvalidThreshold);
+        }
+    }
+
+    // Price-conditioned escrows need an oracle to be checked against
+    if let PriceCondition::Above(rule) | PriceCondition::Below(rule) = &price_condition {
+        if rule.price <= 0 || !env.storage().instance().has(&DataKey::Oracle) {
+            return Err(HTLCError::OracleUnavailable);
+        }
+    }
+
+    // Swapped payouts go to the receiver alone, in a token other than the escrowed one, through
+    // an approved router
+    if let WithdrawSwap::Soroswap(route) = &swap_on_withdraw {
+        if route.min_out < 0
+            || !env.storage().persistent().has(&DataKey::SwapRouter(route.router.clone()))
+            || route.target_token == token_address
+            || !payees.is_empty()
+            || receiver_hook
+        {
+            return Err(HTLCError::InvalidSwap);
+        }
+    }
+    if !token_allowed(env, &token_address)
+        || basket.iter().any(|(basket_token, _)| !token_allowed(env, &basket_token))
+    {
+        return Err(HTLCError::TokenNotAllowed);
+    }
+
+    // Reject dust that costs more in fees and rent than it is worth
+    if amount <= 0 || amount < min_amount(env, &token_address) {
+        return Err(HTLCError::AmountBelowMinimum);
+    }
+
+    check_compliance(env, &sender, &token_address, amount)?;
+    check_compliance(env, &receiver, &token_address, amount)?;
+    count_creation(env, &sender)?;
+
+    // Generate unique escrow ID
+    let order_id_bytes = string_to_bytes(env, &order_id).ok_or(HTLCError::InvalidOrderId)?;
+    let escrow_id: Bytes = env.crypto().keccak256(&order_id_bytes).into();
+
+    // Refuse to overwrite a live escrow or the receipt of a settled one
+    let storage = env.storage().persistent();
+    if storage.has(&DataKey::Escrow(escrow_id.clone()))
+        || storage.has(&DataKey::Receipt(escrow_id.clone()))
+    {
+        return Err(HTLCError::EscrowAlreadyExists);
+    }
+    
+    // Verify sender has sufficient balance, leaving native senders their account reserve
+    let token_client = token::Client::new(env, &token_address);
+    let mut sender_balance = token_client.balance(&sender);
+    if native && is_account(env, &sender) {
+        sender_balance -= MIN_ACCOUNT_RESERVE;
+    }
+    if sender_balance < amount {
+        return Err(HTLCError::InsufficientBalance);
+    }
+
+    // Transfer tokens to contract
+    pull_funds(env, &token_client, &sender, amount, from_allowance)?;
+    for (basket_token, basket_amount) in basket.iter() {
+        let basket_client = token::Client::new(env, &basket_token);
+        pull_funds(env, &basket_client, &sender, basket_amount, from_allowance)?;
+        adjust_locked(env, &basket_token, basket_amount);
+    }
+
+    // Create escrow
+    let escrow = Escrow {
+        sender: sender.clone(),
+        receiver: receiver.clone(),
+        amount,
+        secret_hash: secret_hash.clone(),
+        timelock,
+        exclusive_until,
+        token_address: token_address.clone(),
+        order_id: order_id.clone(),
+        created_at: current_time,
+        metadata,
+        parts,
+        filled_amount: 0,
+        last_fill_index: None,
+        resolvers,
+        taker_amount,
+        auction,
+        integrator,
+        integrator_share_bps,
+        expected_amount,
+        receiver_hook,
+        cancel_penalty_bps,
+        basket,
+        stream_duration,
+        stream_start: None,
+        payees,
+        swap_on_withdraw,
+        timelock_kind,
+        price_condition,
+        arbiter,
+        dispute_window,
+        receiver_auth,
+        lock_type,
+        reveal_delay,
+    };
+
+    // Store escrow
+    storage.set(&DataKey::Escrow(escrow_id.clone()), &escrow);
+    adjust_locked(env, &token_address, amount);
+    index_pending(env, &escrow_id);
+    for hook in hooks(env, &escrow).iter() {
+        EscrowHookClient::new(env, &hook).on_created(&escrow_id, &escrow);
+    }
+
+    // Emit event
+    env.events().publish(
+        (symbol_short!("opened"), escrow_id.clone()),
+        (
+            order_id,
+            sender.clone(),
+            receiver.clone(),
+            token_address,
+            amount,
+            secret_hash,
+            timelock,
+        ),
+    );
+    log!(
+        env,
+        "HTLC Escrow Created: ID={}, Sender={}, Receiver={}, Amount={}",
+        escrow_id,
+        sender,
+        receiver,
+        amount
+    );
+
+    Ok(escrow_id)
+}
+
+/// Move escrowed funds from the sender into this contract, directly or under its allowance
+fn pull_funds(
+    env: &Env,
+    token_client: &token::Client,
+    sender: &Address,
+    amount: i128,
+    from_allowance: bool,
+) -> Result<(), HTLCError> {
+    let this = env.current_contract_address();
+    if from_allowance {
+        if token_client.allowance(sender, &this) < amount {
+            return Err(HTLCError::InsufficientAllowance);
+        }
+        token_client.transfer_from(&this, sender, &this, &amount);
+    } else {
+        token_client.transfer(sender, &this, &amount);
+    }
+    Ok(())
+}
+
+/// keccak256 of the raw secret, matching keccak256(abi.encodePacked(secret)) on EVM
+fn hash_secret(env: &Env, secret: &BytesN<32>) -> BytesN<32> {
+    env.crypto().keccak256(&Bytes::from(secret)).into()
+}
+
+/// SHA-256 of the raw secret, the payment hash of a Lightning invoice or a Bitcoin hashlock
+fn sha256_secret(env: &Env, secret: &BytesN<32>) -> BytesN<32> {
+    env.crypto().sha256(&Bytes::from(secret)).into()
+}
+
+/// keccak256 of the secret followed by the caller's XDR encoding, binding a reveal to its committer
+fn withdraw_commitment(env: &Env, secret: &BytesN<32>, caller: &Address) -> BytesN<32> {
+    let mut preimage = Bytes::from(secret);
+    preimage.append(&caller.clone().to_xdr(env));
+    env.crypto().keccak256(&preimage).into()
+}
+
+fn native_token(env: &Env) -> Address {
+    env.deployer()
+        .with_stellar_asset(Bytes::from_array(env, &NATIVE_ASSET_XDR))
+        .deployed_address()
+}
+
+/// Whether an address is a classic account (subject to reserves) rather than a contract
+/// Its XDR is the `ScVal::Address` tag followed by the `ScAddress` type, 0 for accounts
+fn is_account(env: &Env, address: &Address) -> bool {
+    let encoded = address.clone().to_xdr(env);
+    encoded.get(7) == Some(0)
+}
+
+/// Fail unless the configured oracle's latest, fresh enough price satisfies the escrow's condition
+fn check_price_condition(env: &Env, escrow: &Escrow) -> Result<(), HTLCError> {
+    let (rule, above) = match &escrow.price_condition {
+        PriceCondition::None => return Ok(()),
+        PriceCondition::Above(rule) => (rule, true),
+        PriceCondition::Below(rule) => (rule, false),
+    };
+    let oracle: Address = env
+        .storage()
+        .instance()
+        .get(&DataKey::Oracle)
+        .ok_or(HTLCError::OracleUnavailable)?;
+    let data = match ReflectorClient::new(env, &oracle).try_lastprice(&rule.asset) {
+        Ok(Ok(Some(data))) => data,
+        _ => return Err(HTLCError::OracleUnavailable),
+    };
+    if env.ledger().timestamp().saturating_sub(data.timestamp) > rule.max_age {
+        return Err(HTLCError::OracleUnavailable);
+    }
+    if (above && data.price <= rule.price) || (!above && data.price >= rule.price) {
+        return Err(HTLCError::PriceConditionNotMet);
+    }
+    Ok(())
+}
+
+/// Current time in the unit of an escrow's timelocks
+fn clock(env: &Env, kind: TimelockKind) -> u64 {
+    match kind {
+        TimelockKind::Timestamp => env.ledger().timestamp(),
+        TimelockKind::Ledger => env.ledger().sequence() as u64,
+    }
+}
+
+/// Structured claim a receiver authorizes for `withdraw_with_signature`, bound to this contract
+fn claim_payload(
+    env: &Env,
+    escrow_id: &Bytes,
+    secret_hash: &BytesN<32>,
+    nonce: u64,
+    expiry: u64,
+) -> ClaimPayload {
+    ClaimPayload {
+        contract: env.current_contract_address(),
+        escrow_id: escrow_id.clone(),
+        secret_hash: secret_hash.clone(),
+        nonce,
+        expiry,
+    }
+}
+
+/// Copy the raw UTF-8 bytes of a string so they hash the same as on EVM chains
+fn string_to_bytes(env: &Env, value: &String) -> Option<Bytes> {
+    let len = value.len() as usize;
+    if len > MAX_STRING_LEN {
+        return None;
+    }
+    let mut buf = [0u8; MAX_STRING_LEN];
+    value.copy_into_slice(&mut buf[..len]);
+    Some(Bytes::from_slice(env, &buf[..len]))
+}
+
+/// Release `amount` of a single-secret escrow to an already authorized receiver, settling it
+/// once fully withdrawn
+fn withdraw_with_secret(
+    env: &Env,
+    escrow_id: &Bytes,
+    mut escrow: Escrow,
+    secret: &BytesN<32>,
+    amount: i128,
+    receiver: &Address,
+) -> Result<(), HTLCError> {
+    // Verify the preimage, or the scalar of a PTLC's adaptor point
+    let unlocked = match escrow.lock_type {
+        LockType::Hashlock => hash_secret(env, secret) == escrow.secret_hash,
+        LockType::AdaptorPoint => ptlc::unlocks(env, &escrow.secret_hash, secret, escrow_id),
+        LockType::Sha256 => sha256_secret(env, secret) == escrow.secret_hash,
+    };
+    if !unlocked {
+        return Err(HTLCError::InvalidSecret);
+    }
+
+    // Verify the withdrawal stays within the escrowed amount (and takes all of a multi-asset
+    // or streamed escrow)
+    let filled_after = escrow
+        .filled_amount
+        .checked_add(amount)
+        .ok_or(HTLCError::InvalidFillAmount)?;
+    let whole_only = !escrow.basket.is_empty() || escrow.stream_duration > 0;
+    if amount <= 0 || filled_after > escrow.amount || (whole_only && filled_after != escrow.amount) {
+        return Err(HTLCError::InvalidFillAmount);
+    }
+
+    check_price_condition(env, &escrow)?;
+
+    // On a streamed escrow the secret only starts vesting; funds move with `claim_streamed`
+    if escrow.stream_duration > 0 {
+        if escrow.stream_start.is_some() {
+            return Err(HTLCError::StreamActive);
+        }
+        escrow.stream_start = Some(env.ledger().timestamp());
+        env.storage().persistent().set(&DataKey::Escrow(escrow_id.clone()), &escrow);
+        publish_secret(env, escrow_id, &escrow.secret_hash, secret);
+
+        log!(env, "HTLC Stream Started: ID={}, Receiver={}", escrow_id.clone(), receiver.clone());
+
+        return Ok(());
+    }
+
+    // Record the withdrawal, replacing the escrow with its receipt once complete
+    escrow.filled_amount = filled_after;
+    publish_fill(env, escrow_id, receiver, amount, filled_after);
+    if filled_after == escrow.amount {
+        settle(env, escrow_id, &escrow, SettlementStatus::Withdrawn, receiver);
+    } else {
+        env.storage().persistent().set(&DataKey::Escrow(escrow_id.clone()), &escrow);
+    }
+    adjust_locked(env, &escrow.token_address, -amount);
+
+    // Publish the preimage for counterpart-chain relayers
+    publish_secret(env, escrow_id, &escrow.secret_hash, secret);
+
+    let (fee, surplus) = pay_receiver(env, escrow_id, &escrow, amount, receiver)?;
+    release_basket(env, &escrow, receiver)?;
+    notify_withdrawn(env, escrow_id, &escrow, amount);
+
+    log!(
+        env,
+        "HTLC Withdrawal: ID={}, Receiver={}, Amount={}, Fee={}, Surplus={}",
+        escrow_id.clone(),
+        receiver.clone(),
+        amount,
+        fee,
+        surplus
+    );
+
+    Ok(())
+}
+
+/// Transfer a withdrawn amount to the receiver, net of the protocol fee and captured surplus,
+/// returning both deductions
+fn pay_receiver(
+    env: &Env,
+    escrow_id: &Bytes,
+    escrow: &Escrow,
+    amount: i128,
+    receiver: &Address,
+) -> Result<(i128, i128), HTLCError> {
+    ensure_receiver_ready(env, escrow, receiver)?;
+    check_compliance(env, receiver, &escrow.token_address, amount)?;
+    for (payee, _) in escrow.payees.iter() {
+        check_compliance(env, &payee, &escrow.token_address, amount)?;
+    }
+    let surplus = capture_surplus(env, escrow, amount)?;
+    let fee = charge_fee(env, escrow_id, escrow, amount)?;
+    let payout = amount - fee - surplus;
+    if let WithdrawSwap::Soroswap(route) = &escrow.swap_on_withdraw {
+        swap_payout(env, escrow_id, escrow, route, amount, payout, receiver)?;
+        return Ok((fee, surplus));
+    }
+    if escrow.payees.is_empty() {
+        send(env, &escrow.token_address, receiver, payout)?;
+        notify_receiver(env, escrow_id, escrow, receiver, payout);
+        return Ok((fee, surplus));
+    }
+
+    // Split between payees, the last one taking any rounding dust
+    let mut remaining = payout;
+    let mut received = 0;
+    for (i, (payee, share_bps)) in escrow.payees.iter().enumerate() {
+        let share = if i as u32 + 1 == escrow.payees.len() {
+            remaining
+        } else {
+            payout
+                .checked_mul(share_bps as i128)
+                .ok_or(HTLCError::ArithmeticOverflow)?
+                / FEE_BPS_BASE
+        };
+        remaining -= share;
+        if share > 0 {
+            send(env, &escrow.token_address, &payee, share)?;
+        }
+        if payee == *receiver {
+            received += share;
+        }
+        env.events()
+            .publish((symbol_short!("payout"), escrow_id.clone()), (payee, share));
+    }
+    if received > 0 {
+        notify_receiver(env, escrow_id, escrow, receiver, received);
+    }
+    Ok((fee, surplus))
+}
+
+/// Fail with `TrustlineMissing` unless every recipient of a withdrawal can hold what it is paid
+fn ensure_receiver_ready(env: &Env, escrow: &Escrow, receiver: &Address) -> Result<(), HTLCError> {
+    let ready = if let WithdrawSwap::Soroswap(route) = &escrow.swap_on_withdraw {
+        token_ready(env, &route.target_token, receiver)
+    } else if escrow.payees.is_empty() {
+        token_ready(env, &escrow.token_address, receiver)
+    } else {
+        escrow
+            .payees
+            .iter()
+            .all(|(payee, _)| token_ready(env, &escrow.token_address, &payee))
+    };
+    if !ready
+        || escrow
+            .basket
+            .iter()
+            .any(|(basket_token, _)| !token_ready(env, &basket_token, receiver))
+    {
+        return Err(HTLCError::TrustlineMissing);
+    }
+    Ok(())
+}
+
+/// Whether an address can receive a token; a Stellar Asset Contract fails to read the balance
+/// of an account without a trustline and reports deauthorized ones through `authorized`
+fn token_ready(env: &Env, token_address: &Address, to: &Address) -> bool {
+    if token::Client::new(env, token_address).try_balance(to).is_err() {
+        return false;
     }
-}
\ No newline at end of file
+    !matches!(
+        token::StellarAssetClient::new(env, token_address).try_authorized(to),
+        Ok(Ok(false))
+    )
+}
+
+/// Swap a payout through the escrow's Soroswap route and forward the proceeds to the receiver
+/// The receiver is paid the contract's measured `target_token` gain rather than the amounts the
+/// router reports, so the swap can never pay out `target_token` held for other escrows
+fn swap_payout(
+    env: &Env,
+    escrow_id: &Bytes,
+    escrow: &Escrow,
+    route: &SwapRoute,
+    amount: i128,
+    payout: i128,
+    receiver: &Address,
+) -> Result<i128, HTLCError> {
+    let min_out = route
+        .min_out
+        .checked_mul(amount)
+        .map(|scaled| scaled / escrow.amount)
+        .ok_or(HTLCError::ArithmeticOverflow)?;
+
+    // The router pulls the input from `to` into the pair, so authorize that transfer
+    let this = env.current_contract_address();
+    let router = SoroswapRouterClient::new(env, &route.router);
+    let pair = match router.try_router_pair_for(&escrow.token_address, &route.target_token) {
+        Ok(Ok(pair)) => pair,
+        _ => return Err(HTLCError::SwapFailed),
+    };
+    env.authorize_as_current_contract(vec![
+        env,
+        InvokerContractAuthEntry::Contract(SubContractInvocation {
+            context: ContractContext {
+                contract: escrow.token_address.clone(),
+                fn_name: Symbol::new(env, "transfer"),
+                args: (this.clone(), pair, payout).into_val(env),
+            },
+            sub_invocations: vec![env],
+        }),
+    ]);
+
+    let target = token::Client::new(env, &route.target_token);
+    let before = target.balance(&this);
+    let path = vec![env, escrow.token_address.clone(), route.target_token.clone()];
+    if !matches!(
+        router.try_swap_exact_tokens_for_tokens(
+            &payout,
+            &min_out,
+            &path,
+            &this,
+            &env.ledger().timestamp(),
+        ),
+        Ok(Ok(_))
+    ) {
+        return Err(HTLCError::SwapFailed);
+    }
+    let amount_out = target
+        .balance(&this)
+        .checked_sub(before)
+        .ok_or(HTLCError::ArithmeticOverflow)?;
+    if amount_out < min_out {
+        return Err(HTLCError::SwapFailed);
+    }
+    send(env, &route.target_token, receiver, amount_out)?;
+
+    env.events().publish(
+        (symbol_short!("swap"), escrow_id.clone()),
+        (route.target_token.clone(), payout, amount_out),
+    );
+
+    Ok(amount_out)
+}
+
+/// Pay out every extra token of a multi-asset escrow
+fn release_basket(env: &Env, escrow: &Escrow, to: &Address) -> Result<(), HTLCError> {
+    for (basket_token, basket_amount) in escrow.basket.iter() {
+        adjust_locked(env, &basket_token, -basket_amount);
+        send(env, &basket_token, to, basket_amount)?;
+    }
+    Ok(())
+}
+
+/// Transfer tokens out of the contract, surfacing a frozen or clawed-back balance as an error
+fn send(env: &Env, token_address: &Address, to: &Address, amount: i128) -> Result<(), HTLCError> {
+    match token::Client::new(env, token_address).try_transfer(
+        &env.current_contract_address(),
+        to,
+        &amount,
+    ) {
+        Ok(Ok(())) => Ok(()),
+        _ => Err(HTLCError::TokenTransferFailed),
+    }
+}
+
+/// Let a hooked receiver contract act on funds it has just been paid
+/// The sender sets `receiver_hook`, so a failing hook is skipped rather than reverting payment
+fn notify_receiver(env: &Env, escrow_id: &Bytes, escrow: &Escrow, paid: &Address, amount: i128) {
+    if escrow.receiver_hook && *paid == escrow.receiver {
+        let client = WithdrawHookClient::new(env, paid);
+        if client.try_on_escrow_withdraw(escrow_id, &amount, &escrow.token_address).is_err() {
+            log!(env, "HTLC Hook Failed: Contract={}, Event=withdraw", paid.clone());
+        }
+    }
+}
+
+/// Sender and receiver contracts registered for `EscrowHook` callbacks
+fn hooks(env: &Env, escrow: &Escrow) -> Vec<Address> {
+    let mut hooks = Vec::new(env);
+    for party in [&escrow.sender, &escrow.receiver] {
+        if !hooks.contains(party) && env.storage().persistent().has(&DataKey::Hook(party.clone())) {
+            hooks.push_back(party.clone());
+        }
+    }
+    hooks
+}
+
+/// Hook failures are ignored, since a party's hook must not be able to revert the settlement
+fn notify_withdrawn(env: &Env, escrow_id: &Bytes, escrow: &Escrow, amount: i128) {
+    for hook in hooks(env, escrow).iter() {
+        let client = EscrowHookClient::new(env, &hook);
+        if client.try_on_withdrawn(escrow_id, &amount, &escrow.token_address).is_err() {
+            log!(env, "HTLC Hook Failed: Contract={}, Event=withdrawn", hook);
+        }
+    }
+}
+
+/// Hook failures are ignored, as for `notify_withdrawn`
+fn notify_cancelled(env: &Env, escrow_id: &Bytes, escrow: &Escrow, refunded: i128) {
+    for hook in hooks(env, escrow).iter() {
+        let client = EscrowHookClient::new(env, &hook);
+        if client.try_on_cancelled(escrow_id, &refunded, &escrow.token_address).is_err() {
+            log!(env, "HTLC Hook Failed: Contract={}, Event=cancelled", hook);
+        }
+    }
+}
+
+/// Verify the withdrawal goes to the escrow's receiver, who must sign during the exclusive window
+/// The receiver signs for the escrow ID alone, so its authorization entry does not depend on
+/// the secret or on who submits the transaction and can be handed to a fee sponsor
+fn authorize_withdrawal(
+    env: &Env,
+    escrow_id: &Bytes,
+    escrow: &Escrow,
+    receiver: &Address,
+) -> Result<(), HTLCError> {
+    if *receiver != escrow.receiver {
+        return Err(HTLCError::UnauthorizedAccess);
+    }
+    if clock(env, escrow.timelock_kind) < escrow.exclusive_until {
+        require_single_receiver(env, escrow)?;
+        receiver.require_auth_for_args((escrow_id.clone(),).into_val(env));
+    } else if escrow.reveal_delay > 0 {
+        // Anyone else must go through `commit_withdraw` so a watched secret can't be raced
+        receiver.require_auth_for_args((escrow_id.clone(),).into_val(env));
+    }
+    Ok(())
+}
+
+/// Fail during the exclusive window if withdrawals need a signer threshold rather than the
+/// receiver's own authorization
+fn require_single_receiver(env: &Env, escrow: &Escrow) -> Result<(), HTLCError> {
+    if let ReceiverAuth::Threshold(_) = escrow.receiver_auth {
+        if clock(env, escrow.timelock_kind) < escrow.exclusive_until {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+    }
+    Ok(())
+}
+
+/// Require authorization from at least the threshold of a receiver's signers
+fn authorize_threshold(
+    env: &Env,
+    escrow_id: &Bytes,
+    escrow: &Escrow,
+    approvers: &Vec<Address>,
+) -> Result<(), HTLCError> {
+    let multisig = match &escrow.receiver_auth {
+        ReceiverAuth::Threshold(multisig) => multisig,
+        ReceiverAuth::Single => return Err(HTLCError::UnauthorizedAccess),
+    };
+    let mut approved: Vec<Address> = Vec::new(env);
+    for approver in approvers.iter() {
+        if !multisig.signers.contains(&approver) || approved.contains(&approver) {
+            return Err(HTLCError::UnauthorizedAccess);
+        }
+        approver.require_auth_for_args((escrow_id.clone(),).into_val(env));
+        approved.push_back(approver);
+    }
+    if approved.len() < multisig.threshold {
+        return Err(HTLCError::UnauthorizedAccess);
+    }
+    Ok(())
+}
+
+/// Verify a partial fill taker: the receiver follows the phase rules, other resolvers must sign
+fn authorize_fill(
+    env: &Env,
+    escrow_id: &Bytes,
+    escrow: &Escrow,
+    taker: &Address,
+) -> Result<(), HTLCError> {
+    if *taker == escrow.receiver {
+        return authorize_withdrawal(env, escrow_id, escrow, taker);
+    }
+    if !escrow.resolvers.contains(taker) {
+        return Err(HTLCError::UnauthorizedAccess);
+    }
+    taker.require_auth();
+    Ok(())
+}
+
+/// Taker amount the escrow's auction curve requires at the current time
+fn current_taker_amount(env: &Env, escrow: &Escrow) -> Result<i128, HTLCError> {
+    let rate_bump = match &escrow.auction {
+        Auction::Dutch(params) => auction_rate_bump(params, env.ledger().timestamp()),
+        Auction::None => 0,
+    };
+    escrow
+        .taker_amount
+        .checked_mul(RATE_BUMP_BASE + rate_bump as i128)
+        .map(|scaled| scaled / RATE_BUMP_BASE)
+        .ok_or(HTLCError::InvalidAuction)
+}
+
+/// Rate bump at `now`, interpolated linearly between the auction's points
+fn auction_rate_bump(auction: &AuctionParams, now: u64) -> u32 {
+    let end_time = auction.start_time.saturating_add(auction.duration);
+    if now <= auction.start_time {
+        return auction.initial_rate_bump;
+    }
+    if now >= end_time {
+        return 0;
+    }
+
+    let mut point_time = auction.start_time;
+    let mut rate_bump = auction.initial_rate_bump as u64;
+    for point in auction.points.iter() {
+        let next_time = point_time.saturating_add(point.delay);
+        let next_bump = point.rate_bump as u64;
+        if now <= next_time {
+            return interpolate(point_time, rate_bump, next_time, next_bump, now);
+        }
+        point_time = next_time;
+        rate_bump = next_bump;
+    }
+    interpolate(point_time, rate_bump, end_time, 0, now)
+}
+
+fn interpolate(from_time: u64, from_bump: u64, to_time: u64, to_bump: u64, now: u64) -> u32 {
+    if to_time <= from_time {
+        return to_bump as u32;
+    }
+    let span = (to_time - from_time) as u128;
+    let weighted = (now - from_time) as u128 * to_bump as u128
+        + (to_time - now) as u128 * from_bump as u128;
+    (weighted / span) as u32
+}
+
+/// Merkle leaf for part `index`: keccak256(uint64 index || secret hash), as on the EVM side
+fn merkle_leaf(env: &Env, index: u32, secret_hash: &BytesN<32>) -> BytesN<32> {
+    let mut data = Bytes::from_array(env, &(index as u64).to_be_bytes());
+    data.append(&Bytes::from(secret_hash));
+    env.crypto().keccak256(&data).into()
+}
+
+/// Fold a proof into a root, hashing each pair in sorted order
+fn merkle_root(env: &Env, leaf: BytesN<32>, proof: &Vec<BytesN<32>>) -> BytesN<32> {
+    let mut node = leaf;
+    for sibling in proof.iter() {
+        let (first, second) = if node.to_array() <= sibling.to_array() {
+            (node, sibling)
+        } else {
+            (sibling, node)
+        };
+        let mut data = Bytes::from(&first);
+        data.append(&Bytes::from(&second));
+        node = env.crypto().keccak256(&data).into();
+    }
+    node
+}
+
+/// Load a pending escrow, reporting how it was settled if it no longer exists
+fn load_escrow(env: &Env, escrow_id: &Bytes) -> Result<Escrow, HTLCError> {
+    let storage = env.storage().persistent();
+    if let Some(escrow) = storage.get(&DataKey::Escrow(escrow_id.clone())) {
+        if storage.has(&DataKey::Disputed(escrow_id.clone())) {
+            return Err(HTLCError::EscrowDisputed);
+        }
+        return Ok(escrow);
+    }
+    match storage.get::<_, SettlementReceipt>(&DataKey::Receipt(escrow_id.clone())) {
+        Some(receipt) => match receipt.status {
+            SettlementStatus::Withdrawn => Err(HTLCError::AlreadyWithdrawn),
+            SettlementStatus::Cancelled => Err(HTLCError::AlreadyCancelled),
+            SettlementStatus::Resolved => Err(HTLCError::AlreadyResolved),
+        },
+        None => Err(HTLCError::EscrowNotFound),
+    }
+}
+
+/// Drop the full escrow record and keep only its settlement receipt
+fn settle(
+    env: &Env,
+    escrow_id: &Bytes,
+    escrow: &Escrow,
+    status: SettlementStatus,
+    settled_by: &Address,
+) {
+    let filled_amount = match status {
+        SettlementStatus::Withdrawn => escrow.amount,
+        SettlementStatus::Cancelled | SettlementStatus::Resolved => escrow.filled_amount,
+    };
+    let receipt = SettlementReceipt {
+        status,
+        amount: escrow.amount,
+        filled_amount,
+        settled_at: env.ledger().timestamp(),
+        settled_by: settled_by.clone(),
+        secret_hash: escrow.secret_hash.clone(),
+    };
+    let storage = env.storage().persistent();
+    storage.remove(&DataKey::Escrow(escrow_id.clone()));
+    storage.remove(&DataKey::Commitment(escrow_id.clone()));
+    storage.remove(&DataKey::Disputed(escrow_id.clone()));
+    storage.remove(&DataKey::SecretEnvelope(escrow_id.clone()));
+    storage.remove(&DataKey::RefundPoint(escrow_id.clone()));
+    storage.set(&DataKey::Receipt(escrow_id.clone()), &receipt);
+    unindex_pending(env, escrow_id);
+    env.events().publish(
+        (symbol_short!("settled"), escrow_id.clone()),
+        (status, filled_amount, settled_by.clone()),
+    );
+}
+
+/// Record a withdrawal of `amount`, `filled_amount` in total, for indexers
+fn publish_fill(env: &Env, escrow_id: &Bytes, taker: &Address, amount: i128, filled_amount: i128) {
+    env.events().publish(
+        (symbol_short!("filled"), escrow_id.clone()),
+        (taker.clone(), amount, filled_amount),
+    );
+}
+
+/// Store a revealed preimage where counterpart-chain relayers read it, and announce it
+fn publish_secret(env: &Env, escrow_id: &Bytes, secret_hash: &BytesN<32>, secret: &BytesN<32>) {
+    env.storage()
+        .persistent()
+        .set(&DataKey::RevealedSecret(secret_hash.clone()), secret);
+    env.events().publish(
+        (symbol_short!("secret"), escrow_id.clone()),
+        (secret_hash.clone(), secret.clone()),
+    );
+}
+
+/// Fail for an escrow bound to a refund point, which only `cancel_with_scalar` may refund
+fn require_no_refund_point(env: &Env, escrow_id: &Bytes) -> Result<(), HTLCError> {
+    if env.storage().persistent().has(&DataKey::RefundPoint(escrow_id.clone())) {
+        return Err(HTLCError::UnauthorizedAccess);
+    }
+    Ok(())
+}
+
+fn load_admin(env: &Env) -> Result<Address, HTLCError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(HTLCError::NotInitialized)
+}
+
+fn paused(env: &Env) -> bool {
+    env.storage().instance().get(&DataKey::Paused).unwrap_or(false)
+}
+
+fn allowlist_enabled(env: &Env) -> bool {
+    env.storage()
+        .instance()
+        .get(&DataKey::AllowlistEnabled)
+        .unwrap_or(false)
+}
+
+/// Fail with `ComplianceRejected` unless the configured hook, if any, clears the transfer
+fn check_compliance(
+    env: &Env,
+    address: &Address,
+    token_address: &Address,
+    amount: i128,
+) -> Result<(), HTLCError> {
+    let hook: Address = match env.storage().instance().get(&DataKey::ComplianceHook) {
+        Some(hook) => hook,
+        None => return Ok(()),
+    };
+    match ComplianceHookClient::new(env, &hook).try_check(address, token_address, &amount) {
+        Ok(Ok(true)) => Ok(()),
+        _ => Err(HTLCError::ComplianceRejected),
+    }
+}
+
+/// Count a new escrow against the sender's rate limit, failing with `RateLimited` once the
+/// current window is used up; counters live in temporary storage and expire with their window.
+/// Exempt senders are not counted
+fn count_creation(env: &Env, sender: &Address) -> Result<(), HTLCError> {
+    let (max_escrows, window): (u32, u64) = match env.storage().instance().get(&DataKey::RateLimit) {
+        Some(limit) => limit,
+        None => return Ok(()),
+    };
+    if env.storage().persistent().has(&DataKey::RateLimitExempt(sender.clone())) {
+        return Ok(());
+    }
+    let key = DataKey::CreatedIn(sender.clone(), env.ledger().timestamp() / window);
+    let storage = env.storage().temporary();
+    let created: u32 = storage.get(&key).unwrap_or(0);
+    if created >= max_escrows {
+        return Err(HTLCError::RateLimited);
+    }
+    storage.set(&key, &(created + 1));
+    let ttl = (window / LEDGER_SECONDS + 1).min(env.storage().max_ttl() as u64) as u32;
+    storage.extend_ttl(&key, ttl, ttl);
+    Ok(())
+}
+
+/// Whether a token passes the allowlist, if one is enforced
+fn token_allowed(env: &Env, token_address: &Address) -> bool {
+    !allowlist_enabled(env)
+        || env
+            .storage()
+            .persistent()
+            .has(&DataKey::AllowedToken(token_address.clone()))
+}
+
+/// Token-specific minimum escrow amount, falling back to the global default
+fn min_amount(env: &Env, token_address: &Address) -> i128 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MinAmount(token_address.clone()))
+        .unwrap_or_else(|| {
+            env.storage()
+                .instance()
+                .get(&DataKey::DefaultMinAmount)
+                .unwrap_or(0)
+        })
+}
+
+/// Charge the protocol fee on a withdrawn amount, returning the total fee
+/// The integrator's share is paid out directly; the rest accrues for the treasury
+fn charge_fee(env: &Env, escrow_id: &Bytes, escrow: &Escrow, amount: i128) -> Result<i128, HTLCError> {
+    let (treasury_amount, integrator_amount) = fee_split(env, escrow, amount)?;
+    let fee = treasury_amount + integrator_amount;
+    if fee == 0 {
+        return Ok(0);
+    }
+
+    accrue_fees(env, &escrow.token_address, treasury_amount);
+
+    if let Some(integrator) = &escrow.integrator {
+        if integrator_amount > 0 {
+            send(env, &escrow.token_address, integrator, integrator_amount)?;
+        }
+    }
+
+    env.events().publish(
+        (symbol_short!("fee"), escrow_id.clone()),
+        (treasury_amount, escrow.integrator.clone(), integrator_amount),
+    );
+
+    Ok(fee)
+}
+
+/// Capture the treasury's share of the surplus in a withdrawn amount, returning it
+/// Each withdrawal carries surplus pro rata to its share of the escrowed amount
+fn capture_surplus(env: &Env, escrow: &Escrow, amount: i128) -> Result<i128, HTLCError> {
+    let captured = surplus_share(env, escrow, amount)?;
+    accrue_fees(env, &escrow.token_address, captured);
+    Ok(captured)
+}
+
+/// Treasury and integrator parts of the protocol fee on a withdrawn amount
+fn fee_split(env: &Env, escrow: &Escrow, amount: i128) -> Result<(i128, i128), HTLCError> {
+    let fee_bps: u32 = env.storage().instance().get(&DataKey::FeeBps).unwrap_or(0);
+    let fee = amount
+        .checked_mul(fee_bps as i128)
+        .map(|scaled| scaled / FEE_BPS_BASE)
+        .ok_or(HTLCError::ArithmeticOverflow)?;
+    let integrator_amount = match &escrow.integrator {
+        Some(_) => fee
+            .checked_mul(escrow.integrator_share_bps as i128)
+            .map(|scaled| scaled / FEE_BPS_BASE)
+            .ok_or(HTLCError::ArithmeticOverflow)?,
+        None => 0,
+    };
+    Ok((fee - integrator_amount, integrator_amount))
+}
+
+/// Treasury's share of the surplus carried by a withdrawn amount
+fn surplus_share(env: &Env, escrow: &Escrow, amount: i128) -> Result<i128, HTLCError> {
+    let surplus_bps: u32 = env.storage().instance().get(&DataKey::SurplusBps).unwrap_or(0);
+    if escrow.expected_amount == 0 || surplus_bps == 0 {
+        return Ok(0);
+    }
+
+    let surplus = amount
+        .checked_mul(escrow.amount - escrow.expected_amount)
+        .and_then(|scaled| scaled.checked_div(escrow.amount))
+        .ok_or(HTLCError::ArithmeticOverflow)?;
+    surplus
+        .checked_mul(surplus_bps as i128)
+        .and_then(|scaled| scaled.checked_div(FEE_BPS_BASE))
+        .ok_or(HTLCError::ArithmeticOverflow)
+}
+
+fn accrue_fees(env: &Env, token_address: &Address, amount: i128) {
+    if amount == 0 {
+        return;
+    }
+    let key = DataKey::Fees(token_address.clone());
+    let storage = env.storage().persistent();
+    let accrued: i128 = storage.get(&key).unwrap_or(0);
+    storage.set(&key, &(accrued + amount));
+}
+
+/// Track escrowed principal per token so it can be told apart from stray balances
+fn adjust_locked(env: &Env, token_address: &Address, delta: i128) {
+    let key = DataKey::Locked(token_address.clone());
+    let storage = env.storage().persistent();
+    let locked: i128 = storage.get(&key).unwrap_or(0);
+    storage.set(&key, &(locked + delta));
+}
+
+/// Append an escrow to the pending index
+fn index_pending(env: &Env, escrow_id: &Bytes) {
+    let storage = env.storage().persistent();
+    let count: u32 = storage.get(&DataKey::PendingCount).unwrap_or(0);
+    storage.set(&DataKey::PendingAt(count), escrow_id);
+    storage.set(&DataKey::PendingPos(escrow_id.clone()), &count);
+    storage.set(&DataKey::PendingCount, &(count + 1));
+}
+
+/// Remove an escrow from the pending index by moving the last entry into its slot
+fn unindex_pending(env: &Env, escrow_id: &Bytes) {
+    let storage = env.storage().persistent();
+    let position: u32 = match storage.get(&DataKey::PendingPos(escrow_id.clone())) {
+        Some(position) => position,
+        None => return,
+    };
+    let last = storage.get::<_, u32>(&DataKey::PendingCount).unwrap_or(1) - 1;
+    if position != last {
+        let moved: Bytes = storage.get(&DataKey::PendingAt(last)).unwrap();
+        storage.set(&DataKey::PendingAt(position), &moved);
+        storage.set(&DataKey::PendingPos(moved), &position);
+    }
+    storage.remove(&DataKey::PendingAt(last));
+    storage.remove(&DataKey::PendingPos(escrow_id.clone()));
+    storage.set(&DataKey::PendingCount, &last);
+}
@@ -0,0 +1,74 @@
+// Relayed calls
+// Makers and receivers authorize a call off-chain with an auth entry over its `MetaTx` and any
+// relayer submits it, paying the fees; the host checks the entry against the account's own
+// signers and thresholds, and a per-account nonce and a deadline stop replays
+
+use soroban_sdk::{contracttype, Address, Bytes, BytesN, Env, IntoVal};
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+
+use crate::{DataKey, EscrowParams, HTLCError};
+
+/// Escrow operation a signer asks a relayer to perform
+// Contract types cannot be boxed, so the create variant stays inline
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+#[contracttype]
+pub enum MetaCall {
+    /// Open an escrow funded under the signer's allowance; the signer must be the sender
+    Create(EscrowParams),
+    /// Withdraw everything left in an escrow; the signer must be the receiver
+    Withdraw(Bytes, BytesN<32>),
+    /// Cancel an expired escrow; the signer must be the sender
+    Cancel(Bytes),
+}
+
+/// Exact argument the signer's auth entry for `execute_meta` carries, bound to this contract
+#[derive(Clone)]
+#[contracttype]
+pub struct MetaTx {
+    pub contract: Address,
+    pub signer: Address,
+    pub nonce: u64,
+    pub deadline: u64,
+    pub call: MetaCall,
+}
+
+/// Next nonce the account must sign into a relayed call
+pub fn nonce(env: &Env, signer: &Address) -> u64 {
+    env.storage()
+        .persistent()
+        .get(&DataKey::MetaNonce(signer.clone()))
+        .unwrap_or(0)
+}
+
+/// `MetaTx` for the signer's current nonce
+pub fn payload(env: &Env, signer: &Address, call: &MetaCall, deadline: u64) -> MetaTx {
+    MetaTx {
+        contract: env.current_contract_address(),
+        signer: signer.clone(),
+        nonce: nonce(env, signer),
+        deadline,
+        call: call.clone(),
+    }
+}
+
+/// Check the signer's authorization and the deadline of a relayed call, then consume the
+/// signer's nonce
+/// Returns the nonce that was used
+pub fn verify(
+    env: &Env,
+    signer: &Address,
+    call: &MetaCall,
+    deadline: u64,
+) -> Result<u64, HTLCError> {
+    if env.ledger().timestamp() > deadline {
+        return Err(HTLCError::ClaimExpired);
+    }
+    let used = nonce(env, signer);
+    signer.require_auth_for_args((payload(env, signer, call, deadline),).into_val(env));
+    env.storage()
+        .persistent()
+        .set(&DataKey::MetaNonce(signer.clone()), &(used + 1));
+    Ok(used)
+}
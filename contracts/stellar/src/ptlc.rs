@@ -0,0 +1,59 @@
+// Point time-locked contracts
+// A PTLC escrow locks an ed25519 adaptor point P instead of a hash and is redeemed by
+// revealing the scalar s with P = s·B. The host has no point arithmetic, so the check is
+// folded into an ed25519 signature it can verify: with the fixed nonce r = 1 (R = B),
+// S = 1 + k·s satisfies [S]B = R + [k]P exactly when P = s·B, where k = H(R || P || M)
+
+use curve25519_dalek::{constants::ED25519_BASEPOINT_COMPRESSED, EdwardsPoint, Scalar};
+use sha2::{Digest, Sha512};
+use soroban_sdk::{Bytes, BytesN, Env};
+
+/// Largest message the escrow signs over (an escrow ID)
+const MAX_MESSAGE_LEN: usize = 64;
+
+/// Signature over `message` that verifies under `point` if and only if `scalar` is its
+/// discrete log, or `None` if the scalar is not canonical or the message is too long
+fn unlock_signature(point: &[u8; 32], scalar: &[u8; 32], message: &Bytes) -> Option<[u8; 64]> {
+    let secret = Option::<Scalar>::from(Scalar::from_canonical_bytes(*scalar))?;
+    let len = message.len() as usize;
+    if len > MAX_MESSAGE_LEN {
+        return None;
+    }
+    let mut buf = [0u8; MAX_MESSAGE_LEN];
+    message.copy_into_slice(&mut buf[..len]);
+
+    let nonce_point = ED25519_BASEPOINT_COMPRESSED.to_bytes();
+    let mut hasher = Sha512::new();
+    hasher.update(nonce_point);
+    hasher.update(point);
+    hasher.update(&buf[..len]);
+    let challenge = Scalar::from_hash(hasher);
+
+    let response = Scalar::ONE + challenge * secret;
+    let mut signature = [0u8; 64];
+    signature[..32].copy_from_slice(&nonce_point);
+    signature[32..].copy_from_slice(response.as_bytes());
+    Some(signature)
+}
+
+/// Whether `scalar` opens the adaptor `point`; a well-formed but wrong scalar traps in the
+/// host's signature check rather than returning false, so only settlement uses it
+pub fn unlocks(env: &Env, point: &BytesN<32>, scalar: &BytesN<32>, message: &Bytes) -> bool {
+    match unlock_signature(&point.to_array(), &scalar.to_array(), message) {
+        Some(signature) => {
+            env.crypto()
+                .ed25519_verify(point, message, &BytesN::from_array(env, &signature));
+            true
+        }
+        None => false,
+    }
+}
+
+/// Whether `scalar` opens the adaptor `point`, computing `scalar·B` in the contract; costlier
+/// than `unlocks` but false rather than a trap for a wrong scalar, for views
+pub fn opens(point: &BytesN<32>, scalar: &BytesN<32>) -> bool {
+    match Option::<Scalar>::from(Scalar::from_canonical_bytes(scalar.to_array())) {
+        Some(secret) => EdwardsPoint::mul_base(&secret).compress().to_bytes() == point.to_array(),
+        None => false,
+    }
+}
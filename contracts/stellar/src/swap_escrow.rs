@@ -0,0 +1,42 @@
+// Interface of the per-swap escrow contract deployed by the escrow factory
+// Types mirror swap_escrow's contract types so they encode identically
+
+use soroban_sdk::{contractclient, contracttype, Address, BytesN, Env};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum EscrowSide {
+    Src,
+    Dst,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum EscrowStatus {
+    Pending,
+    Withdrawn,
+    Cancelled,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub struct Immutables {
+    pub side: EscrowSide,
+    pub order_hash: BytesN<32>,
+    pub hashlock: BytesN<32>,
+    pub maker: Address,
+    pub taker: Address,
+    pub token: Address,
+    pub amount: i128,
+    pub safety_deposit: i128,
+    pub exclusive_until: u64,
+    pub timelock: u64,
+    pub public_cancellation: u64,
+}
+
+#[allow(dead_code)]
+#[contractclient(name = "SwapEscrowClient")]
+pub trait SwapEscrowInterface {
+    fn get_immutables(env: Env) -> Option<Immutables>;
+    fn get_status(env: Env) -> Option<EscrowStatus>;
+}
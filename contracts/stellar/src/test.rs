@@ -0,0 +1,76 @@
+#![cfg(test)]
+
+use super::*;
+use soroban_sdk::testutils::{Address as _, MockAuth, MockAuthInvoke};
+use soroban_sdk::token::StellarAssetClient;
+use soroban_sdk::InvokeError;
+
+fn escrow_params(env: &Env, sender: &Address, receiver: &Address, token: &Address,
+                 secret_hash: &BytesN<32>, order_id: &str) -> EscrowParams {
+    EscrowParams {
+        sender: sender.clone(),
+        receiver: receiver.clone(),
+        amount: 100,
+        secret_hash: secret_hash.clone(),
+        timelock: 1_000,
+        exclusive_until: 500,
+        token_address: Some(token.clone()),
+        order_id: String::from_str(env, order_id),
+        metadata: Bytes::new(env),
+        parts: 0,
+        resolvers: Vec::new(env),
+        taker_amount: 0,
+        auction: Auction::None,
+        integrator: None,
+        integrator_share_bps: 0,
+        expected_amount: 0,
+        receiver_hook: false,
+        cancel_penalty_bps: 0,
+        basket: Vec::new(env),
+        stream_duration: 0,
+        payees: Vec::new(env),
+        swap_on_withdraw: WithdrawSwap::None,
+        timelock_kind: TimelockKind::Timestamp,
+        price_condition: PriceCondition::None,
+        arbiter: None,
+        dispute_window: 0,
+        receiver_auth: ReceiverAuth::Single,
+        lock_type: LockType::Hashlock,
+        reveal_delay: 0,
+    }
+}
+
+/// A sponsor submits the withdrawal with the receiver's authorization for the escrow ID alone,
+/// and that authorization does not carry over to another escrow
+#[test]
+fn sponsored_withdraw() {
+    let env = Env::default();
+    let token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+    let sender = Address::generate(&env);
+    let receiver = Address::generate(&env);
+    let contract = env.register_contract(None, HTLCEscrow);
+    let client = HTLCEscrowClient::new(&env, &contract);
+
+    env.mock_all_auths_allowing_non_root_auth();
+    StellarAssetClient::new(&env, &token).mint(&sender, &200);
+    let secret = BytesN::from_array(&env, &[7u8; 32]);
+    let secret_hash = client.generate_secret_hash(&secret);
+    let first = client.create_escrow(&escrow_params(&env, &sender, &receiver, &token, &secret_hash, "first"));
+    let second = client.create_escrow(&escrow_params(&env, &sender, &receiver, &token, &secret_hash, "second"));
+
+    // The only authorization in the sponsor's transaction is the receiver's entry for `first`
+    let receiver_auth = MockAuthInvoke {
+        contract: &contract,
+        fn_name: "withdraw",
+        args: (first.clone(),).into_val(&env),
+        sub_invokes: &[],
+    };
+    env.mock_auths(&[MockAuth { address: &receiver, invoke: &receiver_auth }]);
+    assert_eq!(client.try_withdraw(&second, &secret, &receiver), Err(Err(InvokeError::Abort)));
+
+    env.mock_auths(&[MockAuth { address: &receiver, invoke: &receiver_auth }]);
+    client.withdraw(&first, &secret, &receiver);
+    assert_eq!(token::Client::new(&env, &token).balance(&receiver), 100);
+    assert_eq!(client.get_receipt(&first).unwrap().status, SettlementStatus::Withdrawn);
+    assert!(client.get_escrow(&second).is_some());
+}
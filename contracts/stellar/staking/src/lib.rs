@@ -0,0 +1,293 @@
+#![no_std]
+
+// Stellar Resolver Staking
+// Resolvers bond collateral to be whitelisted; approved slashers (the escrow contract or an
+// arbiter) can seize it when a resolver takes a source leg and never completes the destination
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, contracterror, symbol_short,
+    token, Address, Bytes, Env, log
+};
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+
+/// A resolver's bonded collateral and any amount cooling down after `unstake`
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[contracttype]
+pub struct Stake {
+    pub bonded: i128,
+    pub unbonding: i128,
+    /// When the unbonding amount can be withdrawn with `withdraw_unstaked`
+    pub unlock_at: u64,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Admin,
+    StakeToken,
+    MinStake,
+    Cooldown,
+    Slasher(Address),
+    Stake(Address),
+    TotalStaked,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum StakingError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    InvalidAmount = 3,
+    InsufficientStake = 4,
+    CooldownActive = 5,
+    NotSlasher = 6,
+}
+
+#[contract]
+pub struct ResolverStaking;
+
+#[contractimpl]
+impl ResolverStaking {
+    /// Set the admin, the token resolvers bond, the stake needed for whitelisting and the
+    /// unstaking cooldown in seconds
+    pub fn initialize(
+        env: Env,
+        admin: Address,
+        stake_token: Address,
+        min_stake: i128,
+        cooldown: u64,
+    ) -> Result<(), StakingError> {
+        let storage = env.storage().instance();
+        if storage.has(&DataKey::Admin) {
+            return Err(StakingError::AlreadyInitialized);
+        }
+        if min_stake < 0 {
+            return Err(StakingError::InvalidAmount);
+        }
+        storage.set(&DataKey::Admin, &admin);
+        storage.set(&DataKey::StakeToken, &stake_token);
+        storage.set(&DataKey::MinStake, &min_stake);
+        storage.set(&DataKey::Cooldown, &cooldown);
+        Ok(())
+    }
+
+    /// Allow or revoke an address's right to slash, e.g. the escrow contract or an arbiter
+    /// (admin only)
+    pub fn set_slasher(env: Env, slasher: Address, enabled: bool) -> Result<(), StakingError> {
+        load_admin(&env)?.require_auth();
+        let key = DataKey::Slasher(slasher.clone());
+        if enabled {
+            env.storage().instance().set(&key, &true);
+        } else {
+            env.storage().instance().remove(&key);
+        }
+
+        log!(&env, "Staking Slasher Updated: Slasher={}, Enabled={}", slasher, enabled);
+
+        Ok(())
+    }
+
+    /// Change the stake needed for whitelisting (admin only)
+    pub fn set_min_stake(env: Env, min_stake: i128) -> Result<(), StakingError> {
+        load_admin(&env)?.require_auth();
+        if min_stake < 0 {
+            return Err(StakingError::InvalidAmount);
+        }
+        env.storage().instance().set(&DataKey::MinStake, &min_stake);
+
+        log!(&env, "Staking Minimum Updated: Amount={}", min_stake);
+
+        Ok(())
+    }
+
+    /// Bond `amount` of the stake token, returning the resolver's new bonded total
+    pub fn stake(env: Env, resolver: Address, amount: i128) -> Result<i128, StakingError> {
+        resolver.require_auth();
+        if amount <= 0 {
+            return Err(StakingError::InvalidAmount);
+        }
+        token::Client::new(&env, &stake_token(&env)?).transfer(
+            &resolver,
+            &env.current_contract_address(),
+            &amount,
+        );
+
+        let mut stake = Self::get_stake(env.clone(), resolver.clone());
+        stake.bonded += amount;
+        save_stake(&env, &resolver, &stake);
+        adjust_total(&env, amount);
+
+        env.events()
+            .publish((symbol_short!("staked"), resolver.clone()), (amount, stake.bonded));
+
+        Ok(stake.bonded)
+    }
+
+    /// Start the cooldown on `amount` of bonded stake, restarting it for anything already
+    /// unbonding; the amount stops counting towards whitelisting but can still be slashed
+    pub fn unstake(env: Env, resolver: Address, amount: i128) -> Result<u64, StakingError> {
+        resolver.require_auth();
+        let mut stake = Self::get_stake(env.clone(), resolver.clone());
+        if amount <= 0 {
+            return Err(StakingError::InvalidAmount);
+        }
+        if amount > stake.bonded {
+            return Err(StakingError::InsufficientStake);
+        }
+        let cooldown: u64 = env.storage().instance().get(&DataKey::Cooldown).unwrap_or(0);
+
+        stake.bonded -= amount;
+        stake.unbonding += amount;
+        stake.unlock_at = env.ledger().timestamp().saturating_add(cooldown);
+        save_stake(&env, &resolver, &stake);
+
+        env.events().publish(
+            (symbol_short!("unstaked"), resolver.clone()),
+            (amount, stake.unlock_at),
+        );
+
+        Ok(stake.unlock_at)
+    }
+
+    /// Return unbonded stake to the resolver once its cooldown has passed
+    pub fn withdraw_unstaked(env: Env, resolver: Address) -> Result<i128, StakingError> {
+        resolver.require_auth();
+        let mut stake = Self::get_stake(env.clone(), resolver.clone());
+        if stake.unbonding == 0 {
+            return Err(StakingError::InsufficientStake);
+        }
+        if env.ledger().timestamp() < stake.unlock_at {
+            return Err(StakingError::CooldownActive);
+        }
+
+        let amount = stake.unbonding;
+        stake.unbonding = 0;
+        save_stake(&env, &resolver, &stake);
+        adjust_total(&env, -amount);
+        token::Client::new(&env, &stake_token(&env)?).transfer(
+            &env.current_contract_address(),
+            &resolver,
+            &amount,
+        );
+
+        env.events()
+            .publish((symbol_short!("released"), resolver.clone()), amount);
+
+        Ok(amount)
+    }
+
+    /// Seize up to `amount` of a resolver's stake, bonded first and then unbonding, and pay it
+    /// to `beneficiary`, returning what was taken; `evidence` (e.g. the escrow IDs of the
+    /// abandoned swap) is recorded in the event (slashers only)
+    pub fn slash(
+        env: Env,
+        slasher: Address,
+        resolver: Address,
+        amount: i128,
+        beneficiary: Address,
+        evidence: Bytes,
+    ) -> Result<i128, StakingError> {
+        if !Self::is_slasher(env.clone(), slasher.clone()) {
+            return Err(StakingError::NotSlasher);
+        }
+        slasher.require_auth();
+        if amount <= 0 {
+            return Err(StakingError::InvalidAmount);
+        }
+
+        let mut stake = Self::get_stake(env.clone(), resolver.clone());
+        let from_bonded = amount.min(stake.bonded);
+        let from_unbonding = (amount - from_bonded).min(stake.unbonding);
+        let slashed = from_bonded + from_unbonding;
+        if slashed == 0 {
+            return Err(StakingError::InsufficientStake);
+        }
+        stake.bonded -= from_bonded;
+        stake.unbonding -= from_unbonding;
+        save_stake(&env, &resolver, &stake);
+        adjust_total(&env, -slashed);
+        token::Client::new(&env, &stake_token(&env)?).transfer(
+            &env.current_contract_address(),
+            &beneficiary,
+            &slashed,
+        );
+
+        env.events().publish(
+            (symbol_short!("slashed"), resolver.clone()),
+            (slasher.clone(), slashed, beneficiary.clone(), evidence),
+        );
+        log!(
+            &env,
+            "Staking Slash: Resolver={}, Slasher={}, Amount={}",
+            resolver,
+            slasher,
+            slashed
+        );
+
+        Ok(slashed)
+    }
+
+    /// A resolver's bonded and unbonding stake
+    pub fn get_stake(env: Env, resolver: Address) -> Stake {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Stake(resolver))
+            .unwrap_or_default()
+    }
+
+    /// Whether a resolver's bonded stake meets the whitelisting minimum
+    pub fn is_whitelisted(env: Env, resolver: Address) -> bool {
+        let stake = Self::get_stake(env.clone(), resolver);
+        stake.bonded > 0 && stake.bonded >= Self::get_min_stake(env)
+    }
+
+    /// Whether an address may slash stake
+    pub fn is_slasher(env: Env, slasher: Address) -> bool {
+        env.storage().instance().has(&DataKey::Slasher(slasher))
+    }
+
+    /// Stake needed for whitelisting
+    pub fn get_min_stake(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::MinStake).unwrap_or(0)
+    }
+
+    /// Seconds unstaked collateral stays slashable before it can be withdrawn
+    pub fn get_cooldown(env: Env) -> u64 {
+        env.storage().instance().get(&DataKey::Cooldown).unwrap_or(0)
+    }
+
+    /// Bonded and unbonding stake held across all resolvers
+    pub fn get_total_staked(env: Env) -> i128 {
+        env.storage().instance().get(&DataKey::TotalStaked).unwrap_or(0)
+    }
+}
+
+fn load_admin(env: &Env) -> Result<Address, StakingError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::Admin)
+        .ok_or(StakingError::NotInitialized)
+}
+
+fn stake_token(env: &Env) -> Result<Address, StakingError> {
+    env.storage()
+        .instance()
+        .get(&DataKey::StakeToken)
+        .ok_or(StakingError::NotInitialized)
+}
+
+fn save_stake(env: &Env, resolver: &Address, stake: &Stake) {
+    let key = DataKey::Stake(resolver.clone());
+    if *stake == Stake::default() {
+        env.storage().persistent().remove(&key);
+    } else {
+        env.storage().persistent().set(&key, stake);
+    }
+}
+
+fn adjust_total(env: &Env, delta: i128) {
+    let total = ResolverStaking::get_total_staked(env.clone()) + delta;
+    env.storage().instance().set(&DataKey::TotalStaked, &total);
+}
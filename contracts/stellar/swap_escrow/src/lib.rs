@@ -0,0 +1,253 @@
+#![no_std]
+
+// Single-swap HTLC Escrow
+// Deployed by the escrow factory with a salt derived from the swap's immutables,
+// so the instance address itself commits to the terms it holds funds under
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, contracterror,
+    symbol_short, xdr::ToXdr,
+    Address, Bytes, BytesN, Env, log, token
+};
+use core::result::Result;
+use core::result::Result::{Ok, Err};
+
+/// Which leg of a cross-chain swap an escrow holds
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum EscrowSide {
+    /// Maker funds locked on the source chain; the taker withdraws them with the secret
+    Src,
+    /// Taker funds locked on the destination chain; withdrawal pays the maker
+    Dst,
+}
+
+/// Terms of a swap, fixed when the factory deploys its escrow
+#[derive(Clone)]
+#[contracttype]
+pub struct Immutables {
+    pub side: EscrowSide,
+    pub order_hash: BytesN<32>,
+    pub hashlock: BytesN<32>,
+    pub maker: Address,
+    pub taker: Address,
+    pub token: Address,
+    pub amount: i128,
+    /// Deposit put up by the taker in `token`, paid to whoever settles the escrow
+    pub safety_deposit: i128,
+    /// Before this timestamp only the taker may withdraw
+    pub exclusive_until: u64,
+    /// From this timestamp the taker can cancel and refund the depositor
+    pub timelock: u64,
+    /// From this timestamp anyone can cancel, collecting the safety deposit
+    pub public_cancellation: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum EscrowStatus {
+    Pending,
+    Withdrawn,
+    Cancelled,
+}
+
+#[derive(Clone)]
+#[contracttype]
+pub enum DataKey {
+    Factory,
+    Immutables,
+    Status,
+}
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum SwapEscrowError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    AlreadyWithdrawn = 3,
+    AlreadyCancelled = 4,
+    InvalidSecret = 5,
+    TimelockNotExpired = 6,
+    InvalidImmutables = 7,
+    UnauthorizedAccess = 8,
+}
+
+#[contract]
+pub struct SwapEscrow;
+
+#[contractimpl]
+impl SwapEscrow {
+    /// Record the swap terms; called once by the factory right after deployment
+    pub fn initialize(
+        env: Env,
+        factory: Address,
+        immutables: Immutables,
+    ) -> Result<(), SwapEscrowError> {
+        factory.require_auth();
+
+        let storage = env.storage().instance();
+        if storage.has(&DataKey::Immutables) {
+            return Err(SwapEscrowError::AlreadyInitialized);
+        }
+        verify_immutables(&env, &factory, &immutables)?;
+
+        storage.set(&DataKey::Factory, &factory);
+        storage.set(&DataKey::Immutables, &immutables);
+        storage.set(&DataKey::Status, &EscrowStatus::Pending);
+        Ok(())
+    }
+
+    /// Release the funds with the secret: to the taker on the source side, to the maker on
+    /// the destination side. Only the taker may withdraw during the exclusive window
+    pub fn withdraw(
+        env: Env,
+        secret: BytesN<32>,
+        immutables: Immutables,
+    ) -> Result<(), SwapEscrowError> {
+        check_pending(&env, &immutables)?;
+
+        let secret_hash: BytesN<32> = env.crypto().keccak256(&Bytes::from(&secret)).into();
+        if secret_hash != immutables.hashlock {
+            return Err(SwapEscrowError::InvalidSecret);
+        }
+        if env.ledger().timestamp() < immutables.exclusive_until {
+            immutables.taker.require_auth();
+        }
+
+        env.storage().instance().set(&DataKey::Status, &EscrowStatus::Withdrawn);
+
+        let recipient = match immutables.side {
+            EscrowSide::Src => &immutables.taker,
+            EscrowSide::Dst => &immutables.maker,
+        };
+        let token_client = token::Client::new(&env, &immutables.token);
+        token_client.transfer(&env.current_contract_address(), recipient, &immutables.amount);
+        pay_safety_deposit(&env, &immutables, &immutables.taker);
+
+        env.events().publish(
+            (symbol_short!("withdrawn"), immutables.order_hash.clone()),
+            (recipient.clone(), immutables.amount, secret.clone()),
+        );
+
+        log!(
+            &env,
+            "Swap Escrow Withdrawal: Order={}, Recipient={}, Secret={}",
+            immutables.order_hash,
+            recipient.clone(),
+            secret
+        );
+
+        Ok(())
+    }
+
+    /// Refund the depositor (maker on the source side, taker on the destination side)
+    /// once the timelock has expired. Until `public_cancellation` only the taker may
+    /// cancel and recovers its safety deposit; afterwards the deposit rewards the caller
+    pub fn cancel(
+        env: Env,
+        immutables: Immutables,
+        caller: Address,
+    ) -> Result<(), SwapEscrowError> {
+        caller.require_auth();
+        check_pending(&env, &immutables)?;
+
+        let current_time = env.ledger().timestamp();
+        if current_time < immutables.timelock {
+            return Err(SwapEscrowError::TimelockNotExpired);
+        }
+        if current_time < immutables.public_cancellation && caller != immutables.taker {
+            return Err(SwapEscrowError::UnauthorizedAccess);
+        }
+
+        env.storage().instance().set(&DataKey::Status, &EscrowStatus::Cancelled);
+
+        let depositor = depositor(&immutables);
+        let token_client = token::Client::new(&env, &immutables.token);
+        token_client.transfer(&env.current_contract_address(), depositor, &immutables.amount);
+        pay_safety_deposit(&env, &immutables, &caller);
+
+        env.events().publish(
+            (symbol_short!("cancelled"), immutables.order_hash.clone()),
+            (depositor.clone(), immutables.amount),
+        );
+
+        log!(
+            &env,
+            "Swap Escrow Cancellation: Order={}, Depositor={}",
+            immutables.order_hash,
+            depositor.clone()
+        );
+
+        Ok(())
+    }
+
+    /// Terms this escrow was deployed with
+    pub fn get_immutables(env: Env) -> Option<Immutables> {
+        env.storage().instance().get(&DataKey::Immutables)
+    }
+
+    pub fn get_status(env: Env) -> Option<EscrowStatus> {
+        env.storage().instance().get(&DataKey::Status)
+    }
+
+    /// Factory that deployed this escrow
+    pub fn get_factory(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Factory)
+    }
+}
+
+/// Party that funded the escrow and is refunded on cancellation
+fn depositor(immutables: &Immutables) -> &Address {
+    match immutables.side {
+        EscrowSide::Src => &immutables.maker,
+        EscrowSide::Dst => &immutables.taker,
+    }
+}
+
+/// Pay out the taker's safety deposit to the party that settled the escrow
+fn pay_safety_deposit(env: &Env, immutables: &Immutables, recipient: &Address) {
+    if immutables.safety_deposit == 0 {
+        return;
+    }
+    let token_client = token::Client::new(env, &immutables.token);
+    token_client.transfer(&env.current_contract_address(), recipient, &immutables.safety_deposit);
+
+    env.events().publish(
+        (symbol_short!("deposit"), immutables.order_hash.clone()),
+        (recipient.clone(), immutables.safety_deposit),
+    );
+}
+
+/// Verify the escrow is still pending and was deployed for exactly these terms
+fn check_pending(env: &Env, immutables: &Immutables) -> Result<(), SwapEscrowError> {
+    let storage = env.storage().instance();
+    match storage.get(&DataKey::Status) {
+        Some(EscrowStatus::Pending) => {}
+        Some(EscrowStatus::Withdrawn) => return Err(SwapEscrowError::AlreadyWithdrawn),
+        Some(EscrowStatus::Cancelled) => return Err(SwapEscrowError::AlreadyCancelled),
+        None => return Err(SwapEscrowError::NotInitialized),
+    }
+    let factory: Address = storage
+        .get(&DataKey::Factory)
+        .ok_or(SwapEscrowError::NotInitialized)?;
+    verify_immutables(env, &factory, immutables)
+}
+
+/// Check the terms hash to the salt this escrow's address was derived from,
+/// as the EVM escrows do against their Create2 address
+fn verify_immutables(
+    env: &Env,
+    factory: &Address,
+    immutables: &Immutables,
+) -> Result<(), SwapEscrowError> {
+    let salt: BytesN<32> = env.crypto().keccak256(&immutables.clone().to_xdr(env)).into();
+    let expected = env
+        .deployer()
+        .with_address(factory.clone(), salt)
+        .deployed_address();
+    if expected != env.current_contract_address() {
+        return Err(SwapEscrowError::InvalidImmutables);
+    }
+    Ok(())
+}
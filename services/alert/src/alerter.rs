@@ -0,0 +1,164 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+use tracing::{error, info, warn};
+
+use crate::channel::Channel;
+use crate::config::AlertConfig;
+use crate::Result;
+
+/// How urgently an operator should look
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Worth knowing, e.g. an incident is over
+    Info,
+    /// Needs a look soon, e.g. a swap has not moved for a while
+    Warning,
+    /// Funds are at risk without action, e.g. a claim keeps failing as its timelock nears
+    Critical,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Info => "INFO",
+            Severity::Warning => "WARNING",
+            Severity::Critical => "CRITICAL",
+        })
+    }
+}
+
+/// An alert that fired and has not been resolved
+#[derive(Clone, Debug)]
+struct Active {
+    severity: Severity,
+    message: String,
+    sent_at: Instant,
+}
+
+/// Logs alerts and sends them to the configured chats, once per incident
+///
+/// Alerts are keyed by incident, e.g. `stuck:<order ID>`: firing a key that is already
+/// active only sends again after `repeat_secs` or at a higher severity, and resolving it
+/// sends a recovery notice. Sending happens in the background, so a chat that cannot be
+/// reached is only logged and never holds up the caller. Clones share their active alerts.
+#[derive(Clone, Debug)]
+pub struct Alerter {
+    service: String,
+    channels: Arc<Vec<Channel>>,
+    http: reqwest::Client,
+    repeat: Duration,
+    active: Arc<Mutex<HashMap<String, Active>>>,
+}
+
+impl Alerter {
+    /// Alerts from `service`, e.g. `unite-relayer`, which prefixes every message
+    pub fn new(service: impl Into<String>, config: &AlertConfig) -> Result<Self> {
+        Ok(Self {
+            service: service.into(),
+            channels: Arc::new(
+                config
+                    .channels
+                    .iter()
+                    .map(Channel::open)
+                    .collect::<Result<_>>()?,
+            ),
+            http: reqwest::Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            repeat: Duration::from_secs(config.repeat_secs),
+            active: Arc::default(),
+        })
+    }
+
+    /// Alerts that are only logged
+    pub fn log_only(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+            channels: Arc::default(),
+            http: reqwest::Client::new(),
+            repeat: Duration::from_secs(3_600),
+            active: Arc::default(),
+        }
+    }
+
+    /// Report the incident `key`, unless it was reported at this severity or higher within
+    /// `repeat_secs`
+    pub fn fire(&self, severity: Severity, key: impl Into<String>, message: impl Into<String>) {
+        let key = key.into();
+        let message = message.into();
+        {
+            let mut active = self.active.lock().unwrap();
+            if let Some(previous) = active.get(&key) {
+                if previous.severity >= severity && previous.sent_at.elapsed() < self.repeat {
+                    return;
+                }
+            }
+            active.insert(
+                key.clone(),
+                Active {
+                    severity,
+                    message: message.clone(),
+                    sent_at: Instant::now(),
+                },
+            );
+        }
+        match severity {
+            Severity::Info => info!(alert = %key, "{message}"),
+            Severity::Warning => warn!(alert = %key, "{message}"),
+            Severity::Critical => error!(alert = %key, "{message}"),
+        }
+        self.send(
+            severity,
+            format!("[{severity}] {}: {message}", self.service),
+        );
+    }
+
+    /// The incident `key` is over; announces it if it had fired
+    pub fn resolve(&self, key: &str) {
+        let Some(resolved) = self.active.lock().unwrap().remove(key) else {
+            return;
+        };
+        info!(alert = %key, "resolved: {}", resolved.message);
+        // Sent wherever the alert went, whatever the chats' least severity
+        self.send(
+            resolved.severity,
+            format!("[RESOLVED] {}: {}", self.service, resolved.message),
+        );
+    }
+
+    /// Resolve every active key starting with `prefix`, e.g. all of one swap's incidents
+    pub fn resolve_all(&self, prefix: &str) {
+        let keys: Vec<String> = self
+            .active
+            .lock()
+            .unwrap()
+            .keys()
+            .filter(|key| key.starts_with(prefix))
+            .cloned()
+            .collect();
+        for key in keys {
+            self.resolve(&key);
+        }
+    }
+
+    fn send(&self, severity: Severity, text: String) {
+        if !self.channels.iter().any(|channel| channel.wants(severity)) {
+            return;
+        }
+        let channels = self.channels.clone();
+        let http = self.http.clone();
+        tokio::spawn(async move {
+            for channel in channels.iter().filter(|channel| channel.wants(severity)) {
+                if let Err(error) = channel.send(&http, &text).await {
+                    warn!("{} alert failed: {error}", channel.name());
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,105 @@
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::alerter::Severity;
+use crate::{Error, Result};
+
+/// A chat alerts are sent to; tokens and webhook URLs are read from the environment
+/// variables it names, never from the file
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ChannelConfig {
+    /// A chat, group or channel the bot was added to
+    Telegram {
+        bot_token_env: String,
+        /// Numeric chat ID, or `@name` for a public channel
+        chat_id: String,
+        /// Least severity sent to this chat
+        #[serde(default = "default_min_severity")]
+        min_severity: Severity,
+    },
+    /// A Discord channel's incoming webhook
+    Discord {
+        webhook_url_env: String,
+        #[serde(default = "default_min_severity")]
+        min_severity: Severity,
+    },
+}
+
+/// A configured chat with its secrets read
+#[derive(Clone, Debug)]
+pub(crate) enum Channel {
+    Telegram {
+        url: String,
+        chat_id: String,
+        min_severity: Severity,
+    },
+    Discord {
+        url: String,
+        min_severity: Severity,
+    },
+}
+
+impl Channel {
+    pub(crate) fn open(config: &ChannelConfig) -> Result<Self> {
+        Ok(match config {
+            ChannelConfig::Telegram {
+                bot_token_env,
+                chat_id,
+                min_severity,
+            } => Channel::Telegram {
+                url: format!(
+                    "https://api.telegram.org/bot{}/sendMessage",
+                    env(bot_token_env)?
+                ),
+                chat_id: chat_id.clone(),
+                min_severity: *min_severity,
+            },
+            ChannelConfig::Discord {
+                webhook_url_env,
+                min_severity,
+            } => Channel::Discord {
+                url: env(webhook_url_env)?,
+                min_severity: *min_severity,
+            },
+        })
+    }
+
+    pub(crate) fn wants(&self, severity: Severity) -> bool {
+        match self {
+            Channel::Telegram { min_severity, .. } | Channel::Discord { min_severity, .. } => {
+                severity >= *min_severity
+            }
+        }
+    }
+
+    pub(crate) async fn send(
+        &self,
+        http: &reqwest::Client,
+        text: &str,
+    ) -> std::result::Result<(), reqwest::Error> {
+        let request = match self {
+            Channel::Telegram { url, chat_id, .. } => http
+                .post(url)
+                .json(&json!({ "chat_id": chat_id, "text": text })),
+            Channel::Discord { url, .. } => http.post(url).json(&json!({ "content": text })),
+        };
+        request.send().await?.error_for_status()?;
+        Ok(())
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Channel::Telegram { .. } => "telegram",
+            Channel::Discord { .. } => "discord",
+        }
+    }
+}
+
+fn env(name: &str) -> Result<String> {
+    std::env::var(name).map_err(|_| Error::Config(format!("{name} is not set")))
+}
+
+fn default_min_severity() -> Severity {
+    Severity::Warning
+}
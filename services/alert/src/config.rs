@@ -0,0 +1,42 @@
+use serde::Deserialize;
+use unite_config::{Problems, Validate};
+
+use crate::channel::ChannelConfig;
+
+/// The `[alerts]` table: where alerts go besides the log, and how often one that keeps
+/// firing is repeated
+#[derive(Clone, Debug, Deserialize)]
+pub struct AlertConfig {
+    #[serde(default)]
+    pub channels: Vec<ChannelConfig>,
+    #[serde(default = "default_repeat_secs")]
+    pub repeat_secs: u64,
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            channels: Vec::new(),
+            repeat_secs: default_repeat_secs(),
+        }
+    }
+}
+
+impl Validate for AlertConfig {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.repeat_secs > 0, || {
+            "repeat_secs must be positive".to_string()
+        });
+        for (index, channel) in self.channels.iter().enumerate() {
+            if let ChannelConfig::Telegram { chat_id, .. } = channel {
+                problems.check(!chat_id.is_empty(), || {
+                    format!("channels[{index}]: chat_id must not be empty")
+                });
+            }
+        }
+    }
+}
+
+fn default_repeat_secs() -> u64 {
+    3_600
+}
@@ -0,0 +1,22 @@
+// Operational Alerts
+// Tells operators about incidents before users do: stuck swaps, timelocks closing on legs
+// that still need a claim, failed submissions and low balances. Daemons fire alerts by key
+// and severity; an alert that keeps firing is sent again only after `repeat_secs` or when
+// its severity rises, and resolving its key announces the recovery. Every alert is logged,
+// and sent to each Telegram chat or Discord webhook configured for its severity.
+
+pub mod alerter;
+pub mod channel;
+pub mod config;
+
+pub use alerter::{Alerter, Severity};
+pub use channel::ChannelConfig;
+pub use config::AlertConfig;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
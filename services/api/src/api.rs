@@ -0,0 +1,210 @@
+use std::sync::Arc;
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql_axum::GraphQL;
+use axum::extract::ws::WebSocketUpgrade;
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{Html, IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use unite_telemetry::Health;
+
+use crate::db::Database;
+use crate::feed::Feed;
+use crate::graphql;
+use crate::model::{Escrow, EscrowDetail, EventFilter, Order, Page, Quote, QuoteRequest};
+use crate::page::{self, Cursor};
+use crate::{Error, Result};
+
+/// Serving limits
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Page size when a listing asks for none
+    pub page_size: usize,
+    /// Largest page a listing may ask for
+    pub max_page_size: usize,
+    /// Recent swaps a quote takes its median rate over
+    pub quote_samples: usize,
+    /// Deepest nesting a GraphQL query may use
+    pub graphql_depth: usize,
+    /// Most fields a GraphQL query may resolve
+    pub graphql_complexity: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            page_size: 50,
+            max_page_size: 500,
+            quote_samples: 25,
+            graphql_depth: 10,
+            graphql_complexity: 1_000,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct AppState {
+    db: Database,
+    feed: Feed,
+    limits: Limits,
+}
+
+/// `GET /escrows/{id}`, `GET /addresses/{address}/escrows`, `GET /orders/{order_id}`,
+/// `POST /quotes`, the `GET /events` WebSocket and `/graphql`, with GraphiQL on `GET`;
+/// `GET /healthz` and `GET /readyz` report liveness and database health
+pub fn router(db: Database, feed: Feed, limits: Limits) -> Router {
+    let schema = graphql::schema(db.clone(), limits);
+    let health = Health::default().with(Arc::new(db.clone())).router();
+    Router::new()
+        .route("/escrows/{id}", get(escrow))
+        .route("/addresses/{address}/escrows", get(address_escrows))
+        .route("/orders/{order_id}", get(order))
+        .route("/quotes", post(quote))
+        .route("/events", get(events))
+        .route("/graphql", get(graphiql).post_service(GraphQL::new(schema)))
+        .with_state(Arc::new(AppState { db, feed, limits }))
+        .merge(health)
+}
+
+#[derive(Debug, Deserialize)]
+struct ChainQuery {
+    chain: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PageQuery {
+    cursor: Option<String>,
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventQuery {
+    address: Option<String>,
+    token: Option<String>,
+    order_id: Option<String>,
+    /// Last sequence number the client received; without it only new events are sent
+    since: Option<i64>,
+}
+
+/// Escrow IDs are only unique per chain; `?chain=` picks one when several chains share it
+async fn escrow(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<ChainQuery>,
+) -> Result<Json<EscrowDetail>> {
+    let escrow_id = hex::decode(id.trim_start_matches("0x"))
+        .map_err(|_| Error::BadRequest(format!("escrow ID {id} is not hex")))?;
+    let mut escrows = state
+        .db
+        .escrows_by_id(&escrow_id, query.chain.as_deref())
+        .await?;
+    if escrows.len() > 1 {
+        return Err(Error::BadRequest(format!(
+            "escrow {id} exists on several chains; pass ?chain="
+        )));
+    }
+    let escrow = escrows
+        .pop()
+        .ok_or_else(|| Error::NotFound(format!("escrow {id}")))?;
+    Ok(Json(state.db.detail(escrow).await?))
+}
+
+async fn address_escrows(
+    State(state): State<Arc<AppState>>,
+    Path(address): Path<String>,
+    Query(query): Query<PageQuery>,
+) -> Result<Json<Page<Escrow>>> {
+    let after = query.cursor.as_deref().map(Cursor::decode).transpose()?;
+    let limit = query
+        .limit
+        .unwrap_or(state.limits.page_size)
+        .clamp(1, state.limits.max_page_size);
+    // One extra row tells whether another page follows
+    let rows = state
+        .db
+        .escrows_by_address(&address, after.as_ref(), limit + 1)
+        .await?;
+    let (items, next_cursor) = page::split(rows, limit, Cursor::after);
+    Ok(Json(Page { items, next_cursor }))
+}
+
+async fn order(
+    State(state): State<Arc<AppState>>,
+    Path(order_id): Path<String>,
+) -> Result<Json<Order>> {
+    let escrows = state.db.escrows_by_order(&order_id).await?;
+    if escrows.is_empty() {
+        return Err(Error::NotFound(format!("order {order_id}")));
+    }
+    let mut details = Vec::with_capacity(escrows.len());
+    for escrow in escrows {
+        details.push(state.db.detail(escrow).await?);
+    }
+    Ok(Json(Order {
+        order_id,
+        escrows: details,
+    }))
+}
+
+async fn quote(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<QuoteRequest>,
+) -> Result<Json<Quote>> {
+    if request.amount.is_empty() || !request.amount.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(Error::BadRequest(format!(
+            "amount {} is not a decimal integer",
+            request.amount
+        )));
+    }
+    state
+        .db
+        .quote(
+            &request.source,
+            &request.destination,
+            &request.amount,
+            state.limits.quote_samples,
+        )
+        .await?
+        .map(Json)
+        .ok_or_else(|| Error::NotFound("no settled swaps of this pair".to_string()))
+}
+
+/// Each event is sent as a JSON text message carrying its `seq`; a client that reconnects
+/// with `?since=` set to the last one it received misses nothing
+async fn events(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EventQuery>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let since = query.since.unwrap_or_else(|| state.feed.latest());
+    let filter = EventFilter {
+        address: query.address,
+        token: query.token,
+        order_id: query.order_id,
+    };
+    upgrade.on_upgrade(move |socket| async move {
+        state
+            .feed
+            .stream(socket, filter, since, state.limits.max_page_size)
+            .await
+    })
+}
+
+async fn graphiql() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Error::NotFound(_) => StatusCode::NOT_FOUND,
+            Error::BadRequest(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,93 @@
+use std::net::SocketAddr;
+use std::path::Path;
+
+use serde::Deserialize;
+use unite_config::{Problems, Validate};
+
+use crate::api::Limits;
+use crate::webhook::{WebhookConfig, KINDS};
+use crate::{Error, Result};
+
+/// API settings, read from a TOML file with its environment's overlay and `UNITE_API__*`
+/// overrides; the database URL is read from the environment
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    #[serde(default = "default_database_url_env")]
+    pub database_url_env: String,
+    /// How often the event feed re-reads the newest event, besides the indexer's notifications
+    #[serde(default = "default_feed_poll_secs")]
+    pub feed_poll_secs: u64,
+    #[serde(default)]
+    pub limits: Limits,
+    /// Backends pushed lifecycle events as signed POSTs
+    #[serde(default)]
+    pub webhooks: Vec<WebhookConfig>,
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(unite_config::load(path.as_ref(), "UNITE_API")?)
+    }
+
+    pub fn database_url(&self) -> Result<String> {
+        std::env::var(&self.database_url_env)
+            .map_err(|_| Error::Config(format!("{} is not set", self.database_url_env)))
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.feed_poll_secs > 0, || {
+            "feed_poll_secs must be positive".to_string()
+        });
+        let limits = &self.limits;
+        problems.check(
+            limits.page_size > 0 && limits.page_size <= limits.max_page_size,
+            || "limits.page_size must be between 1 and max_page_size".to_string(),
+        );
+        problems.check(limits.quote_samples > 0, || {
+            "limits.quote_samples must be positive".to_string()
+        });
+        for (index, webhook) in self.webhooks.iter().enumerate() {
+            let name = &webhook.name;
+            problems.check(!name.is_empty(), || {
+                format!("webhooks[{index}].name must not be empty")
+            });
+            problems.check(
+                !self.webhooks[..index]
+                    .iter()
+                    .any(|other| &other.name == name),
+                || format!("webhook {name} is configured twice"),
+            );
+            problems.check(
+                webhook.url.starts_with("http://") || webhook.url.starts_with("https://"),
+                || format!("webhook {name}: url must be http(s)"),
+            );
+            for kind in &webhook.kinds {
+                problems.check(KINDS.contains(&kind.as_str()), || {
+                    format!("webhook {name}: unknown kind {kind}")
+                });
+            }
+            problems.check(webhook.max_attempts > 0, || {
+                format!("webhook {name}: max_attempts must be positive")
+            });
+            problems.check(webhook.timeout_secs > 0, || {
+                format!("webhook {name}: timeout_secs must be positive")
+            });
+        }
+    }
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8081))
+}
+
+fn default_database_url_env() -> String {
+    "DATABASE_URL".to_string()
+}
+
+fn default_feed_poll_secs() -> u64 {
+    5
+}
@@ -0,0 +1,432 @@
+use async_trait::async_trait;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use unite_telemetry::{Probe, Reading};
+
+use crate::model::{
+    Asset, AssetStats, Escrow, EscrowDetail, EscrowFilter, Event, EventFilter, Fill, FillFilter,
+    Quote, Secret, Settlement,
+};
+use crate::page::Cursor;
+use crate::{Error, Result};
+
+const ESCROW_COLUMNS: &str = "chain, escrow_id, order_id, sender, receiver, token, \
+                              amount::text AS amount, hashlock, timelock, height, tx, \
+                              provisional";
+const FILL_COLUMNS: &str = "chain, escrow_id, event_key, taker, amount::text AS amount, \
+                            filled_amount::text AS filled_amount, height, tx, provisional";
+
+/// Read-only access to the tables `unite-indexer` writes (`indexer/migrations`), besides the
+/// webhook dispatcher's delivery state
+#[derive(Clone, Debug)]
+pub struct Database {
+    pool: PgPool,
+}
+
+impl Database {
+    pub async fn connect(url: &str) -> Result<Self> {
+        Ok(Self {
+            pool: PgPoolOptions::new()
+                .max_connections(10)
+                .connect(url)
+                .await?,
+        })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Escrows with this ID, on one chain or on every chain
+    pub async fn escrows_by_id(
+        &self,
+        escrow_id: &[u8],
+        chain: Option<&str>,
+    ) -> Result<Vec<Escrow>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {ESCROW_COLUMNS} FROM escrows \
+             WHERE escrow_id = $1 AND ($2::text IS NULL OR chain = $2) \
+             ORDER BY chain"
+        ))
+        .bind(escrow_id)
+        .bind(chain)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Escrows an address sent or receives, newest first, starting after `after`
+    pub async fn escrows_by_address(
+        &self,
+        address: &str,
+        after: Option<&Cursor>,
+        limit: usize,
+    ) -> Result<Vec<Escrow>> {
+        let filter = EscrowFilter {
+            address: Some(address.to_string()),
+            ..EscrowFilter::default()
+        };
+        self.escrows(&filter, after, limit).await
+    }
+
+    /// Escrows passing the filter, newest first, starting after `after`
+    pub async fn escrows(
+        &self,
+        filter: &EscrowFilter,
+        after: Option<&Cursor>,
+        limit: usize,
+    ) -> Result<Vec<Escrow>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {ESCROW_COLUMNS} FROM escrows \
+             WHERE ($1::text IS NULL OR sender = $1 OR receiver = $1) \
+               AND ($2::text IS NULL OR sender = $2) \
+               AND ($3::text IS NULL OR receiver = $3) \
+               AND ($4::text IS NULL OR chain = $4) \
+               AND ($5::text IS NULL OR token = $5) \
+               AND ($6::text IS NULL OR order_id = $6) \
+               AND ($7::bigint IS NULL OR (height, chain, escrow_id) < ($7, $8, $9)) \
+             ORDER BY height DESC, chain DESC, escrow_id DESC \
+             LIMIT $10"
+        ))
+        .bind(&filter.address)
+        .bind(&filter.sender)
+        .bind(&filter.receiver)
+        .bind(&filter.chain)
+        .bind(&filter.token)
+        .bind(&filter.order_id)
+        .bind(after.map(|cursor| cursor.height))
+        .bind(after.map(|cursor| cursor.chain.as_str()))
+        .bind(after.map(|cursor| cursor.key.as_slice()))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn escrows_by_order(&self, order_id: &str) -> Result<Vec<Escrow>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {ESCROW_COLUMNS} FROM escrows WHERE order_id = $1 ORDER BY height, chain"
+        ))
+        .bind(order_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// An escrow's fills, revealed secrets and settlement
+    pub async fn detail(&self, escrow: Escrow) -> Result<EscrowDetail> {
+        Ok(EscrowDetail {
+            fills: self.fills_of(&escrow).await?,
+            secrets: self.secrets_of(&escrow).await?,
+            settlement: self.settlement_of(&escrow).await?,
+            escrow,
+        })
+    }
+
+    pub async fn fills_of(&self, escrow: &Escrow) -> Result<Vec<Fill>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {FILL_COLUMNS} FROM fills WHERE chain = $1 AND escrow_id = $2 \
+             ORDER BY height, event_key"
+        ))
+        .bind(&escrow.chain)
+        .bind(&escrow.escrow_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn secrets_of(&self, escrow: &Escrow) -> Result<Vec<Secret>> {
+        Ok(sqlx::query_as(
+            "SELECT hashlock, secret, height, tx, provisional \
+             FROM secrets WHERE chain = $1 AND escrow_id = $2 \
+             ORDER BY height",
+        )
+        .bind(&escrow.chain)
+        .bind(&escrow.escrow_id)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    pub async fn settlement_of(&self, escrow: &Escrow) -> Result<Option<Settlement>> {
+        Ok(sqlx::query_as(
+            "SELECT status, filled_amount::text AS filled_amount, settled_by, height, tx, \
+                    provisional \
+             FROM settlements WHERE chain = $1 AND escrow_id = $2",
+        )
+        .bind(&escrow.chain)
+        .bind(&escrow.escrow_id)
+        .fetch_optional(&self.pool)
+        .await?)
+    }
+
+    /// Fills passing the filter, newest first, starting after `after`
+    pub async fn fills(
+        &self,
+        filter: &FillFilter,
+        after: Option<&Cursor>,
+        limit: usize,
+    ) -> Result<Vec<Fill>> {
+        let escrow_id = filter
+            .escrow_id
+            .as_deref()
+            .map(|id| {
+                hex::decode(id.trim_start_matches("0x"))
+                    .map_err(|_| Error::BadRequest(format!("escrow ID {id} is not hex")))
+            })
+            .transpose()?;
+        Ok(sqlx::query_as(&format!(
+            "SELECT {FILL_COLUMNS} FROM fills \
+             WHERE ($1::text IS NULL OR chain = $1) \
+               AND ($2::bytea IS NULL OR escrow_id = $2) \
+               AND ($3::text IS NULL OR taker = $3) \
+               AND ($4::bigint IS NULL OR (height, chain, event_key) < ($4, $5, $6)) \
+             ORDER BY height DESC, chain DESC, event_key DESC \
+             LIMIT $7"
+        ))
+        .bind(&filter.chain)
+        .bind(escrow_id)
+        .bind(&filter.taker)
+        .bind(after.map(|cursor| cursor.height))
+        .bind(after.map(|cursor| cursor.chain.as_str()))
+        .bind(after.map(|cursor| String::from_utf8_lossy(&cursor.key).into_owned()))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Escrow counts, volumes and settlements per chain and token
+    pub async fn stats(&self, chain: Option<&str>, token: Option<&str>) -> Result<Vec<AssetStats>> {
+        Ok(sqlx::query_as(
+            "SELECT e.chain, e.token, count(*) AS escrows, sum(e.amount)::text AS volume, \
+                    count(*) FILTER (WHERE s.status IS NULL) AS open, \
+                    count(*) FILTER (WHERE s.status = 'withdrawn') AS withdrawn, \
+                    count(*) FILTER (WHERE s.status = 'cancelled') AS cancelled, \
+                    count(*) FILTER (WHERE s.status = 'resolved') AS resolved, \
+                    COALESCE(sum(f.fills), 0)::bigint AS fills, \
+                    COALESCE(sum(f.filled), 0)::text AS filled_volume, \
+                    count(DISTINCT e.sender) AS senders, \
+                    count(DISTINCT e.receiver) AS receivers \
+             FROM escrows e \
+             LEFT JOIN settlements s ON s.chain = e.chain AND s.escrow_id = e.escrow_id \
+             LEFT JOIN ( \
+                 SELECT chain, escrow_id, count(*) AS fills, sum(amount) AS filled \
+                 FROM fills GROUP BY chain, escrow_id) f \
+               ON f.chain = e.chain AND f.escrow_id = e.escrow_id \
+             WHERE ($1::text IS NULL OR e.chain = $1) AND ($2::text IS NULL OR e.token = $2) \
+             GROUP BY e.chain, e.token \
+             ORDER BY e.chain, e.token",
+        )
+        .bind(chain)
+        .bind(token)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Price `amount` of `source` in `destination` at the median rate of the last `samples`
+    /// swaps of the pair whose legs were both withdrawn, or `None` without any
+    ///
+    /// The two escrows of a swap share order ID and hashlock on different chains; the source
+    /// is the one that expires later.
+    pub async fn quote(
+        &self,
+        source: &Asset,
+        destination: &Asset,
+        amount: &str,
+        samples: usize,
+    ) -> Result<Option<Quote>> {
+        let (count, rate, destination_amount): (i64, Option<f64>, Option<String>) = sqlx::query_as(
+            "WITH recent AS ( \
+                     SELECT d.amount / s.amount AS rate \
+                     FROM escrows s \
+                     JOIN escrows d ON d.order_id = s.order_id AND d.hashlock = s.hashlock \
+                                   AND d.chain <> s.chain AND d.timelock < s.timelock \
+                     JOIN settlements ss ON ss.chain = s.chain AND ss.escrow_id = s.escrow_id \
+                     JOIN settlements ds ON ds.chain = d.chain AND ds.escrow_id = d.escrow_id \
+                     WHERE s.chain = $1 AND s.token IS NOT DISTINCT FROM $2 \
+                       AND d.chain = $3 AND d.token IS NOT DISTINCT FROM $4 \
+                       AND ss.status = 'withdrawn' AND ds.status = 'withdrawn' \
+                       AND s.amount > 0 \
+                     ORDER BY ds.height DESC \
+                     LIMIT $5), \
+                 median AS ( \
+                     SELECT count(*) AS samples, \
+                            percentile_disc(0.5) WITHIN GROUP (ORDER BY rate) AS rate \
+                     FROM recent) \
+                 SELECT samples, rate::float8, floor($6::numeric * rate)::text FROM median",
+        )
+        .bind(&source.chain)
+        .bind(&source.token)
+        .bind(&destination.chain)
+        .bind(&destination.token)
+        .bind(samples as i64)
+        .bind(amount)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(rate
+            .zip(destination_amount)
+            .map(|(rate, destination_amount)| Quote {
+                source: source.clone(),
+                destination: destination.clone(),
+                amount: amount.to_string(),
+                destination_amount,
+                rate,
+                samples: count as usize,
+            }))
+    }
+
+    /// Highest sequence number in `events`, or 0 before the first
+    pub async fn latest_event(&self) -> Result<i64> {
+        Ok(
+            sqlx::query_scalar("SELECT COALESCE(max(seq), 0) FROM events")
+                .fetch_one(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Events after `after` that pass the filter, oldest first
+    ///
+    /// Events of escrows the indexer has not seen created are left out, since nothing
+    /// about them can be filtered on.
+    pub async fn events(
+        &self,
+        filter: &EventFilter,
+        after: i64,
+        limit: usize,
+    ) -> Result<Vec<Event>> {
+        Ok(sqlx::query_as(
+            "SELECT e.seq, e.kind, e.chain, e.escrow_id, x.order_id, x.sender, x.receiver, \
+                    x.token, x.amount::text AS amount, x.hashlock, e.height, e.tx, \
+                    s.secret, st.settled_by \
+             FROM events e \
+             JOIN escrows x ON x.chain = e.chain AND x.escrow_id = e.escrow_id \
+             LEFT JOIN LATERAL ( \
+                 SELECT secret FROM secrets \
+                 WHERE chain = e.chain AND escrow_id = e.escrow_id AND tx = e.tx \
+                 LIMIT 1) s ON e.kind = 'secret_revealed' \
+             LEFT JOIN settlements st ON e.kind IN ('withdrawn', 'cancelled', 'resolved') \
+                                     AND st.chain = e.chain AND st.escrow_id = e.escrow_id \
+             WHERE e.seq > $1 \
+               AND ($2::text IS NULL OR x.sender = $2 OR x.receiver = $2) \
+               AND ($3::text IS NULL OR x.token = $3) \
+               AND ($4::text IS NULL OR x.order_id = $4) \
+             ORDER BY e.seq \
+             LIMIT $5",
+        )
+        .bind(after)
+        .bind(&filter.address)
+        .bind(&filter.token)
+        .bind(&filter.order_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Last event sent to a webhook subscriber, `None` before its first delivery
+    pub async fn webhook_cursor(&self, subscriber: &str) -> Result<Option<i64>> {
+        Ok(
+            sqlx::query_scalar("SELECT seq FROM webhook_cursors WHERE subscriber = $1")
+                .bind(subscriber)
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    pub async fn set_webhook_cursor(&self, subscriber: &str, seq: i64) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO webhook_cursors (subscriber, seq) VALUES ($1, $2) \
+             ON CONFLICT (subscriber) DO UPDATE SET seq = $2, updated_at = now()",
+        )
+        .bind(subscriber)
+        .bind(seq)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Final escrows passing the filter that expire after `now` and by `until` (unix
+    /// seconds) without a settlement, and that the subscriber was not told about yet,
+    /// soonest first
+    pub async fn expiring(
+        &self,
+        subscriber: &str,
+        filter: &EventFilter,
+        now: u64,
+        until: u64,
+        limit: usize,
+    ) -> Result<Vec<Escrow>> {
+        Ok(sqlx::query_as(&format!(
+            "SELECT {ESCROW_COLUMNS} FROM escrows x \
+             WHERE NOT provisional AND timelock > $1 AND timelock <= $2 \
+               AND NOT EXISTS ( \
+                   SELECT 1 FROM settlements s \
+                   WHERE s.chain = x.chain AND s.escrow_id = x.escrow_id) \
+               AND NOT EXISTS ( \
+                   SELECT 1 FROM webhook_expiring w \
+                   WHERE w.subscriber = $3 AND w.chain = x.chain AND w.escrow_id = x.escrow_id) \
+               AND ($4::text IS NULL OR sender = $4 OR receiver = $4) \
+               AND ($5::text IS NULL OR token = $5) \
+               AND ($6::text IS NULL OR order_id = $6) \
+             ORDER BY timelock \
+             LIMIT $7"
+        ))
+        .bind(now as i64)
+        .bind(until as i64)
+        .bind(subscriber)
+        .bind(&filter.address)
+        .bind(&filter.token)
+        .bind(&filter.order_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?)
+    }
+
+    /// Remember that the subscriber was told the escrow is about to expire
+    pub async fn expiring_sent(&self, subscriber: &str, escrow: &Escrow) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO webhook_expiring (subscriber, chain, escrow_id) VALUES ($1, $2, $3) \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(subscriber)
+        .bind(&escrow.chain)
+        .bind(&escrow.escrow_id)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Park a delivery the subscriber did not accept after `attempts` tries
+    pub async fn dead_letter(
+        &self,
+        subscriber: &str,
+        delivery: &str,
+        kind: &str,
+        payload: &str,
+        attempts: u32,
+        error: &str,
+    ) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO webhook_dead_letters \
+                 (subscriber, delivery, kind, payload, attempts, error) \
+             VALUES ($1, $2, $3, $4::jsonb, $5, $6)",
+        )
+        .bind(subscriber)
+        .bind(delivery)
+        .bind(kind)
+        .bind(payload)
+        .bind(attempts as i32)
+        .bind(error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Probe for Database {
+    fn name(&self) -> String {
+        "database".to_string()
+    }
+
+    async fn probe(&self) -> std::result::Result<Reading, String> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|error| error.to_string())?;
+        Ok(Reading::default())
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use axum::extract::ws::{Message, WebSocket};
+use log::warn;
+use sqlx::postgres::PgListener;
+use tokio::sync::watch;
+
+use crate::db::Database;
+use crate::model::EventFilter;
+use crate::Result;
+
+/// Channel `unite-indexer` notifies after committing new events
+const EVENTS_CHANNEL: &str = "escrow_events";
+
+/// The newest event sequence number, followed once for every subscriber
+#[derive(Clone, Debug)]
+pub struct Feed {
+    db: Database,
+    latest: watch::Receiver<i64>,
+}
+
+impl Feed {
+    /// Follow `events` through the indexer's notifications, re-reading the newest sequence
+    /// number every `poll_interval` in case a notification was lost while reconnecting
+    pub async fn start(db: Database, poll_interval: Duration) -> Result<Self> {
+        let mut listener = PgListener::connect_with(db.pool()).await?;
+        listener.listen(EVENTS_CHANNEL).await?;
+        let (sender, latest) = watch::channel(db.latest_event().await?);
+        let head = db.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    notification = listener.recv() => if let Err(error) = notification {
+                        warn!("event notifications: {error}");
+                        tokio::time::sleep(poll_interval).await;
+                    },
+                    _ = tokio::time::sleep(poll_interval) => {}
+                }
+                match head.latest_event().await {
+                    Ok(seq) => {
+                        sender.send_if_modified(|latest| {
+                            let newer = seq > *latest;
+                            *latest = (*latest).max(seq);
+                            newer
+                        });
+                    }
+                    Err(error) => warn!("reading the newest event: {error}"),
+                }
+            }
+        });
+        Ok(Self { db, latest })
+    }
+
+    pub fn latest(&self) -> i64 {
+        *self.latest.borrow()
+    }
+
+    /// The newest sequence number, marked changed whenever a newer one is seen
+    pub fn subscribe(&self) -> watch::Receiver<i64> {
+        self.latest.clone()
+    }
+
+    /// Send the filtered events after `since` to a client, then each new one as it is indexed,
+    /// until the client disconnects
+    ///
+    /// Catch-up reads `page_size` events at a time. Events become visible in sequence
+    /// order, so every number up to the newest one seen before a read has been considered.
+    pub async fn stream(
+        &self,
+        mut socket: WebSocket,
+        filter: EventFilter,
+        since: i64,
+        page_size: usize,
+    ) {
+        let mut latest = self.latest.clone();
+        let mut position = since;
+        loop {
+            loop {
+                let head = *latest.borrow_and_update();
+                let events = match self.db.events(&filter, position, page_size).await {
+                    Ok(events) => events,
+                    Err(error) => {
+                        warn!("reading events: {error}");
+                        return;
+                    }
+                };
+                for event in &events {
+                    let Ok(json) = serde_json::to_string(event) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(json.into())).await.is_err() {
+                        return;
+                    }
+                    position = event.seq;
+                }
+                if events.len() < page_size {
+                    position = position.max(head);
+                    break;
+                }
+            }
+            tokio::select! {
+                changed = latest.changed() => if changed.is_err() {
+                    return;
+                },
+                message = socket.recv() => match message {
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => return,
+                    Some(Ok(_)) => {}
+                },
+            }
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use async_graphql::{
+    Context, EmptyMutation, EmptySubscription, Object, Result, Schema, SimpleObject,
+};
+
+use crate::api::Limits;
+use crate::db::Database;
+use crate::model::{AssetStats, Escrow, EscrowFilter, Fill, FillFilter, Secret, Settlement};
+use crate::page::{self, Cursor};
+
+pub type ApiSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+/// The read-only schema, with query depth and complexity capped by `limits`
+pub fn schema(db: Database, limits: Limits) -> ApiSchema {
+    Schema::build(Query, EmptyMutation, EmptySubscription)
+        .data(db)
+        .data(limits)
+        .limit_depth(limits.graphql_depth)
+        .limit_complexity(limits.graphql_complexity)
+        .finish()
+}
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// An escrow by hex ID; `chain` is required when several chains share the ID
+    async fn escrow(
+        &self,
+        ctx: &Context<'_>,
+        id: String,
+        chain: Option<String>,
+    ) -> Result<Option<EscrowNode>> {
+        let escrow_id = hex::decode(id.trim_start_matches("0x"))
+            .map_err(|_| format!("escrow ID {id} is not hex"))?;
+        let mut escrows = db(ctx).escrows_by_id(&escrow_id, chain.as_deref()).await?;
+        if escrows.len() > 1 {
+            return Err(format!("escrow {id} exists on several chains; pass chain").into());
+        }
+        Ok(escrows.pop().map(EscrowNode))
+    }
+
+    /// Escrows passing every filter given, newest first
+    async fn escrows(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<EscrowFilter>,
+        first: Option<usize>,
+        after: Option<String>,
+    ) -> Result<EscrowPage> {
+        escrow_page(ctx, &filter.unwrap_or_default(), first, after).await
+    }
+
+    /// Withdrawals passing every filter given, newest first
+    async fn fills(
+        &self,
+        ctx: &Context<'_>,
+        filter: Option<FillFilter>,
+        first: Option<usize>,
+        after: Option<String>,
+    ) -> Result<FillPage> {
+        fill_page(ctx, &filter.unwrap_or_default(), first, after).await
+    }
+
+    /// An order's escrows on every chain, or `null` when none is indexed
+    async fn order(&self, ctx: &Context<'_>, order_id: String) -> Result<Option<OrderNode>> {
+        let escrows = db(ctx).escrows_by_order(&order_id).await?;
+        Ok((!escrows.is_empty()).then_some(OrderNode { order_id, escrows }))
+    }
+
+    /// History of one address, as escrow sender or receiver and as taker of fills
+    async fn address(&self, address: String) -> AddressNode {
+        AddressNode(address)
+    }
+
+    /// Totals per chain and token
+    async fn stats(
+        &self,
+        ctx: &Context<'_>,
+        chain: Option<String>,
+        token: Option<String>,
+    ) -> Result<Vec<AssetStats>> {
+        Ok(db(ctx).stats(chain.as_deref(), token.as_deref()).await?)
+    }
+}
+
+#[derive(SimpleObject)]
+pub struct EscrowPage {
+    pub items: Vec<EscrowNode>,
+    /// Pass as `after` for the next page; `null` on the last
+    pub next_cursor: Option<String>,
+}
+
+#[derive(SimpleObject)]
+pub struct FillPage {
+    pub items: Vec<FillNode>,
+    /// Pass as `after` for the next page; `null` on the last
+    pub next_cursor: Option<String>,
+}
+
+pub struct EscrowNode(pub Escrow);
+
+/// An escrow; amounts are decimal strings and IDs and hashes hex
+#[Object(name = "Escrow")]
+impl EscrowNode {
+    async fn chain(&self) -> &str {
+        &self.0.chain
+    }
+
+    async fn escrow_id(&self) -> String {
+        hex::encode(&self.0.escrow_id)
+    }
+
+    async fn order_id(&self) -> &str {
+        &self.0.order_id
+    }
+
+    async fn sender(&self) -> &str {
+        &self.0.sender
+    }
+
+    async fn receiver(&self) -> &str {
+        &self.0.receiver
+    }
+
+    /// `null` for the chain's native currency where it has no token address
+    async fn token(&self) -> Option<&str> {
+        self.0.token.as_deref()
+    }
+
+    async fn amount(&self) -> &str {
+        &self.0.amount
+    }
+
+    async fn hashlock(&self) -> String {
+        hex::encode(&self.0.hashlock)
+    }
+
+    /// Unix seconds after which the escrow can be cancelled
+    async fn timelock(&self) -> i64 {
+        self.0.timelock
+    }
+
+    async fn height(&self) -> i64 {
+        self.0.height
+    }
+
+    async fn tx(&self) -> &str {
+        &self.0.tx
+    }
+
+    /// Indexed above the chain's finalized block, so a reorg can still remove it
+    async fn provisional(&self) -> bool {
+        self.0.provisional
+    }
+
+    async fn fills(&self, ctx: &Context<'_>) -> Result<Vec<FillNode>> {
+        Ok(db(ctx)
+            .fills_of(&self.0)
+            .await?
+            .into_iter()
+            .map(FillNode)
+            .collect())
+    }
+
+    async fn secrets(&self, ctx: &Context<'_>) -> Result<Vec<SecretNode>> {
+        Ok(db(ctx)
+            .secrets_of(&self.0)
+            .await?
+            .into_iter()
+            .map(SecretNode)
+            .collect())
+    }
+
+    /// `null` while the escrow is open
+    async fn settlement(&self, ctx: &Context<'_>) -> Result<Option<SettlementNode>> {
+        Ok(db(ctx).settlement_of(&self.0).await?.map(SettlementNode))
+    }
+
+    /// The order with this escrow's counterpart on the other chain
+    async fn order(&self, ctx: &Context<'_>) -> Result<OrderNode> {
+        Ok(OrderNode {
+            order_id: self.0.order_id.clone(),
+            escrows: db(ctx).escrows_by_order(&self.0.order_id).await?,
+        })
+    }
+}
+
+pub struct FillNode(pub Fill);
+
+/// One withdrawal from an escrow; partial fills add one each
+#[Object(name = "Fill")]
+impl FillNode {
+    async fn chain(&self) -> &str {
+        &self.0.chain
+    }
+
+    async fn escrow_id(&self) -> String {
+        hex::encode(&self.0.escrow_id)
+    }
+
+    async fn event_key(&self) -> &str {
+        &self.0.event_key
+    }
+
+    async fn taker(&self) -> &str {
+        &self.0.taker
+    }
+
+    async fn amount(&self) -> &str {
+        &self.0.amount
+    }
+
+    /// Escrow total filled after this withdrawal
+    async fn filled_amount(&self) -> &str {
+        &self.0.filled_amount
+    }
+
+    async fn height(&self) -> i64 {
+        self.0.height
+    }
+
+    async fn tx(&self) -> &str {
+        &self.0.tx
+    }
+
+    /// Indexed above the chain's finalized block, so a reorg can still remove it
+    async fn provisional(&self) -> bool {
+        self.0.provisional
+    }
+
+    async fn escrow(&self, ctx: &Context<'_>) -> Result<Option<EscrowNode>> {
+        Ok(db(ctx)
+            .escrows_by_id(&self.0.escrow_id, Some(&self.0.chain))
+            .await?
+            .pop()
+            .map(EscrowNode))
+    }
+}
+
+pub struct SecretNode(pub Secret);
+
+#[Object(name = "Secret")]
+impl SecretNode {
+    async fn hashlock(&self) -> String {
+        hex::encode(&self.0.hashlock)
+    }
+
+    async fn secret(&self) -> String {
+        hex::encode(&self.0.secret)
+    }
+
+    async fn height(&self) -> i64 {
+        self.0.height
+    }
+
+    async fn tx(&self) -> &str {
+        &self.0.tx
+    }
+
+    /// Indexed above the chain's finalized block, so a reorg can still remove it
+    async fn provisional(&self) -> bool {
+        self.0.provisional
+    }
+}
+
+pub struct SettlementNode(pub Settlement);
+
+#[Object(name = "Settlement")]
+impl SettlementNode {
+    /// `withdrawn`, `cancelled` or `resolved`
+    async fn status(&self) -> &str {
+        &self.0.status
+    }
+
+    async fn filled_amount(&self) -> Option<&str> {
+        self.0.filled_amount.as_deref()
+    }
+
+    async fn settled_by(&self) -> Option<&str> {
+        self.0.settled_by.as_deref()
+    }
+
+    async fn height(&self) -> i64 {
+        self.0.height
+    }
+
+    async fn tx(&self) -> &str {
+        &self.0.tx
+    }
+
+    /// Indexed above the chain's finalized block, so a reorg can still remove it
+    async fn provisional(&self) -> bool {
+        self.0.provisional
+    }
+}
+
+pub struct OrderNode {
+    order_id: String,
+    escrows: Vec<Escrow>,
+}
+
+/// The escrows locked for one order, usually a source and a destination leg
+#[Object(name = "Order")]
+impl OrderNode {
+    async fn order_id(&self) -> &str {
+        &self.order_id
+    }
+
+    async fn escrows(&self) -> Vec<EscrowNode> {
+        self.escrows.iter().cloned().map(EscrowNode).collect()
+    }
+}
+
+pub struct AddressNode(String);
+
+#[Object(name = "Address")]
+impl AddressNode {
+    async fn address(&self) -> &str {
+        &self.0
+    }
+
+    /// Escrows the address sent or receives, newest first
+    async fn escrows(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<usize>,
+        after: Option<String>,
+    ) -> Result<EscrowPage> {
+        let filter = EscrowFilter {
+            address: Some(self.0.clone()),
+            ..EscrowFilter::default()
+        };
+        escrow_page(ctx, &filter, first, after).await
+    }
+
+    /// Withdrawals the address took, newest first
+    async fn fills(
+        &self,
+        ctx: &Context<'_>,
+        first: Option<usize>,
+        after: Option<String>,
+    ) -> Result<FillPage> {
+        let filter = FillFilter {
+            taker: Some(self.0.clone()),
+            ..FillFilter::default()
+        };
+        fill_page(ctx, &filter, first, after).await
+    }
+}
+
+fn db<'a>(ctx: &Context<'a>) -> &'a Database {
+    ctx.data_unchecked::<Database>()
+}
+
+fn page_size(ctx: &Context<'_>, first: Option<usize>) -> usize {
+    let limits = ctx.data_unchecked::<Limits>();
+    first
+        .unwrap_or(limits.page_size)
+        .clamp(1, limits.max_page_size)
+}
+
+async fn escrow_page(
+    ctx: &Context<'_>,
+    filter: &EscrowFilter,
+    first: Option<usize>,
+    after: Option<String>,
+) -> Result<EscrowPage> {
+    let after = after.as_deref().map(Cursor::decode).transpose()?;
+    let limit = page_size(ctx, first);
+    let rows = db(ctx).escrows(filter, after.as_ref(), limit + 1).await?;
+    let (rows, next_cursor) = page::split(rows, limit, Cursor::after);
+    Ok(EscrowPage {
+        items: rows.into_iter().map(EscrowNode).collect(),
+        next_cursor,
+    })
+}
+
+async fn fill_page(
+    ctx: &Context<'_>,
+    filter: &FillFilter,
+    first: Option<usize>,
+    after: Option<String>,
+) -> Result<FillPage> {
+    let after = after.as_deref().map(Cursor::decode).transpose()?;
+    let limit = page_size(ctx, first);
+    let rows = db(ctx).fills(filter, after.as_ref(), limit + 1).await?;
+    let (rows, next_cursor) = page::split(rows, limit, Cursor::after_fill);
+    Ok(FillPage {
+        items: rows.into_iter().map(FillNode).collect(),
+        next_cursor,
+    })
+}
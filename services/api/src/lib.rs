@@ -0,0 +1,52 @@
+// Escrow REST API
+// Serves what `unite-indexer` stored in Postgres: single escrows with their fills, secrets
+// and settlement, the escrows an address sent or receives, both legs of an order, and quotes
+// priced from the pair's recently settled swaps. Address listings page with an opaque
+// cursor over (height, chain, escrow ID), so pages stay stable while the indexer appends.
+// A WebSocket pushes lifecycle events as the indexer commits them, filtered by address,
+// token or order and numbered so a reconnecting client resumes where it stopped.
+// A GraphQL endpoint nests the same data (order, its escrows on each chain, their fills and
+// settlements) and adds per-address history and per-asset totals.
+// Configured webhooks are pushed the same events, plus `expiring` for open escrows whose
+// timelock is near, as HMAC-signed POSTs that are retried with backoff and parked in a
+// dead-letter table once they keep failing.
+// `/readyz` probes the database. Besides the webhooks' delivery state the API only reads;
+// the indexer owns the schema.
+
+pub mod api;
+pub mod config;
+pub mod db;
+pub mod feed;
+pub mod graphql;
+pub mod model;
+pub mod page;
+pub mod webhook;
+
+pub use api::Limits;
+pub use config::Config;
+pub use db::Database;
+pub use feed::Feed;
+pub use model::{
+    Asset, AssetStats, Escrow, EscrowDetail, EscrowFilter, Event, EventFilter, Fill, FillFilter,
+    Order, Page, Quote, QuoteRequest, Secret, Settlement,
+};
+pub use page::Cursor;
+pub use webhook::{Webhook, WebhookConfig};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error("not found: {0}")]
+    NotFound(String),
+    #[error("bad request: {0}")]
+    BadRequest(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Load(#[from] unite_config::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use log::info;
+use tokio::net::TcpListener;
+use unite_api::{api, Config, Database, Feed, Webhook};
+
+/// `unite-api [config.toml]`, logging through `RUST_LOG`
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "api.toml".to_string());
+    let config = Config::load(&path)?;
+
+    let db = Database::connect(&config.database_url()?).await?;
+    let feed = Feed::start(db.clone(), Duration::from_secs(config.feed_poll_secs)).await?;
+    for webhook in &config.webhooks {
+        info!("webhook {} to {}", webhook.name, webhook.url);
+        let webhook = Webhook::new(
+            db.clone(),
+            feed.clone(),
+            webhook.clone(),
+            config.limits.page_size,
+            Duration::from_secs(config.feed_poll_secs),
+        )?;
+        tokio::spawn(webhook.run());
+    }
+
+    let listener = TcpListener::bind(config.listen).await?;
+    info!("listening on {}", config.listen);
+    axum::serve(listener, api::router(db, feed, config.limits))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
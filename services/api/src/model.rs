@@ -0,0 +1,198 @@
+use async_graphql::{InputObject, SimpleObject};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// An indexed escrow; amounts are decimal strings, since they can exceed JSON numbers
+#[derive(Clone, Debug, Serialize, FromRow)]
+pub struct Escrow {
+    pub chain: String,
+    #[serde(with = "hex")]
+    pub escrow_id: Vec<u8>,
+    pub order_id: String,
+    pub sender: String,
+    pub receiver: String,
+    pub token: Option<String>,
+    pub amount: String,
+    #[serde(with = "hex")]
+    pub hashlock: Vec<u8>,
+    pub timelock: i64,
+    pub height: i64,
+    pub tx: String,
+    /// Indexed above the chain's finalized block, so a reorg can still remove it
+    pub provisional: bool,
+}
+
+#[derive(Clone, Debug, Serialize, FromRow)]
+pub struct Fill {
+    pub chain: String,
+    #[serde(with = "hex")]
+    pub escrow_id: Vec<u8>,
+    /// Position of the withdrawal on its chain
+    pub event_key: String,
+    pub taker: String,
+    pub amount: String,
+    pub filled_amount: String,
+    pub height: i64,
+    pub tx: String,
+    /// Indexed above the chain's finalized block, so a reorg can still remove it
+    pub provisional: bool,
+}
+
+#[derive(Clone, Debug, Serialize, FromRow)]
+pub struct Secret {
+    #[serde(with = "hex")]
+    pub hashlock: Vec<u8>,
+    #[serde(with = "hex")]
+    pub secret: Vec<u8>,
+    pub height: i64,
+    pub tx: String,
+    /// Indexed above the chain's finalized block, so a reorg can still remove it
+    pub provisional: bool,
+}
+
+#[derive(Clone, Debug, Serialize, FromRow)]
+pub struct Settlement {
+    pub status: String,
+    pub filled_amount: Option<String>,
+    pub settled_by: Option<String>,
+    pub height: i64,
+    pub tx: String,
+    /// Indexed above the chain's finalized block, so a reorg can still remove it
+    pub provisional: bool,
+}
+
+/// An escrow with everything that happened to it
+#[derive(Clone, Debug, Serialize)]
+pub struct EscrowDetail {
+    #[serde(flatten)]
+    pub escrow: Escrow,
+    pub fills: Vec<Fill>,
+    pub secrets: Vec<Secret>,
+    pub settlement: Option<Settlement>,
+}
+
+/// Every escrow indexed for one order, usually a source and a destination leg
+#[derive(Clone, Debug, Serialize)]
+pub struct Order {
+    pub order_id: String,
+    pub escrows: Vec<EscrowDetail>,
+}
+
+/// One page of a listing; pass `next_cursor` back as `cursor` for the next one
+#[derive(Clone, Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// A token on one chain; `token` is `None` for a native currency the chain indexes without
+/// a token address
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Asset {
+    pub chain: String,
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct QuoteRequest {
+    pub source: Asset,
+    pub destination: Asset,
+    /// Source amount in the token's smallest unit, as a decimal string
+    pub amount: String,
+}
+
+/// Destination amount implied by recently settled swaps of the pair
+#[derive(Clone, Debug, Serialize)]
+pub struct Quote {
+    pub source: Asset,
+    pub destination: Asset,
+    pub amount: String,
+    pub destination_amount: String,
+    /// Median destination units per source unit
+    pub rate: f64,
+    /// Swaps the median was taken over
+    pub samples: usize,
+}
+
+/// One step of an escrow's lifecycle, numbered by `seq` in the order it was indexed
+#[derive(Clone, Debug, Serialize, FromRow)]
+pub struct Event {
+    pub seq: i64,
+    /// `created`, `secret_revealed`, `withdrawn`, `cancelled` or `resolved`
+    pub kind: String,
+    pub chain: String,
+    #[serde(with = "hex")]
+    pub escrow_id: Vec<u8>,
+    pub order_id: String,
+    pub sender: String,
+    pub receiver: String,
+    pub token: Option<String>,
+    pub amount: String,
+    #[serde(with = "hex")]
+    pub hashlock: Vec<u8>,
+    pub height: i64,
+    pub tx: String,
+    /// Set on `secret_revealed`
+    #[serde(serialize_with = "hex_option")]
+    pub secret: Option<Vec<u8>>,
+    /// Set on settlements, when the chain reports who settled
+    pub settled_by: Option<String>,
+}
+
+/// Which escrows a listing returns; every filter given must match
+#[derive(Clone, Debug, Default, Deserialize, InputObject)]
+pub struct EscrowFilter {
+    /// Sender or receiver
+    pub address: Option<String>,
+    pub sender: Option<String>,
+    pub receiver: Option<String>,
+    pub chain: Option<String>,
+    pub token: Option<String>,
+    pub order_id: Option<String>,
+}
+
+/// Which fills a listing returns; every filter given must match
+#[derive(Clone, Debug, Default, Deserialize, InputObject)]
+pub struct FillFilter {
+    pub chain: Option<String>,
+    /// Hex escrow ID
+    pub escrow_id: Option<String>,
+    pub taker: Option<String>,
+}
+
+/// Totals for one token on one chain; amounts are decimal strings
+#[derive(Clone, Debug, Serialize, FromRow, SimpleObject)]
+pub struct AssetStats {
+    pub chain: String,
+    pub token: Option<String>,
+    pub escrows: i64,
+    pub volume: String,
+    pub open: i64,
+    pub withdrawn: i64,
+    pub cancelled: i64,
+    pub resolved: i64,
+    pub fills: i64,
+    pub filled_volume: String,
+    pub senders: i64,
+    pub receivers: i64,
+}
+
+/// Which events a subscriber receives; every filter given must match
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct EventFilter {
+    /// Sender or receiver of the escrow
+    pub address: Option<String>,
+    pub token: Option<String>,
+    pub order_id: Option<String>,
+}
+
+fn hex_option<S: serde::Serializer>(
+    value: &Option<Vec<u8>>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(bytes) => serializer.serialize_some(&hex::encode(bytes)),
+        None => serializer.serialize_none(),
+    }
+}
@@ -0,0 +1,71 @@
+use crate::model::{Escrow, Fill};
+use crate::{Error, Result};
+
+/// Position after the last row of a page, handed to clients as an opaque hex string
+///
+/// Listings are ordered by height, chain and a key unique within the chain (the escrow ID,
+/// or a fill's event key), so rows indexed after a page was read never shift the pages that
+/// follow it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cursor {
+    pub height: i64,
+    pub chain: String,
+    pub key: Vec<u8>,
+}
+
+impl Cursor {
+    pub fn after(escrow: &Escrow) -> Self {
+        Self {
+            height: escrow.height,
+            chain: escrow.chain.clone(),
+            key: escrow.escrow_id.clone(),
+        }
+    }
+
+    pub fn after_fill(fill: &Fill) -> Self {
+        Self {
+            height: fill.height,
+            chain: fill.chain.clone(),
+            key: fill.event_key.clone().into_bytes(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        hex::encode(format!(
+            "{}:{}:{}",
+            self.height,
+            hex::encode(&self.key),
+            self.chain
+        ))
+    }
+
+    pub fn decode(cursor: &str) -> Result<Self> {
+        let invalid = || Error::BadRequest(format!("invalid cursor {cursor}"));
+        let text = String::from_utf8(hex::decode(cursor).map_err(|_| invalid())?)
+            .map_err(|_| invalid())?;
+        let mut parts = text.splitn(3, ':');
+        let (Some(height), Some(key), Some(chain)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        Ok(Self {
+            height: height.parse().map_err(|_| invalid())?,
+            chain: chain.to_string(),
+            key: hex::decode(key).map_err(|_| invalid())?,
+        })
+    }
+}
+
+/// Cut rows read with one extra past `limit` into a page and the cursor of the next one
+pub fn split<T>(
+    mut rows: Vec<T>,
+    limit: usize,
+    cursor: impl Fn(&T) -> Cursor,
+) -> (Vec<T>, Option<String>) {
+    if rows.len() <= limit {
+        return (rows, None);
+    }
+    rows.truncate(limit);
+    let next = rows.last().map(|row| cursor(row).encode());
+    (rows, next)
+}
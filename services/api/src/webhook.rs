@@ -0,0 +1,277 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+
+use crate::db::Database;
+use crate::feed::Feed;
+use crate::model::EventFilter;
+use crate::{Error, Result};
+
+/// `t=<unix seconds>,v1=<hex HMAC-SHA256 of "<t>.<body>" under the subscriber's secret>`
+pub const SIGNATURE_HEADER: &str = "X-Unite-Signature";
+/// The delivery's `id`, the same on every retry, for subscribers to drop duplicates
+pub const DELIVERY_HEADER: &str = "X-Unite-Delivery";
+/// The delivery's `kind`
+pub const KIND_HEADER: &str = "X-Unite-Event";
+
+/// Kinds a subscriber can ask for: the event feed's, and `expiring` for open escrows whose
+/// timelock is near
+pub const KINDS: &[&str] = &[
+    "created",
+    "secret_revealed",
+    "withdrawn",
+    "cancelled",
+    "resolved",
+    "expiring",
+];
+
+/// Longest wait between two tries of one delivery
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// A backend that is pushed lifecycle events
+#[derive(Clone, Debug, Deserialize)]
+pub struct WebhookConfig {
+    /// Unique; names the subscriber's progress and dead letters in the database
+    pub name: String,
+    pub url: String,
+    /// Environment variable holding the secret deliveries are signed with
+    pub secret_env: String,
+    /// Kinds to deliver, every one of `KINDS` when empty
+    #[serde(default)]
+    pub kinds: Vec<String>,
+    #[serde(default)]
+    pub filter: EventFilter,
+    /// How long before an open escrow's timelock `expiring` is sent
+    #[serde(default = "default_expiring_secs")]
+    pub expiring_secs: u64,
+    /// Tries per delivery before it goes to the dead letters
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubling after each
+    #[serde(default = "default_retry_secs")]
+    pub retry_secs: u64,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+impl WebhookConfig {
+    fn wants(&self, kind: &str) -> bool {
+        self.kinds.is_empty() || self.kinds.iter().any(|wanted| wanted == kind)
+    }
+}
+
+/// The JSON body of a delivery
+#[derive(Debug, Serialize)]
+struct Payload<'a, T> {
+    id: &'a str,
+    kind: &'a str,
+    /// Unix seconds of the first try
+    sent_at: u64,
+    /// The `Event`, or for `expiring` the `Escrow`
+    data: &'a T,
+}
+
+/// Pushes one subscriber's events to its URL as signed POSTs, in feed order
+///
+/// Events are sent from the feed position stored for the subscriber, or for a new one from
+/// the newest event, so a restart resumes without losing or repeating what was accepted.
+/// A delivery the subscriber does not answer with a 2xx is retried with a doubling delay,
+/// holding back the ones after it; after `max_attempts` it is parked in
+/// `webhook_dead_letters` and the next is sent.
+pub struct Webhook {
+    db: Database,
+    feed: Feed,
+    config: WebhookConfig,
+    secret: Vec<u8>,
+    http: reqwest::Client,
+    page_size: usize,
+    poll_interval: Duration,
+}
+
+impl Webhook {
+    pub fn new(
+        db: Database,
+        feed: Feed,
+        config: WebhookConfig,
+        page_size: usize,
+        poll_interval: Duration,
+    ) -> Result<Self> {
+        let secret = std::env::var(&config.secret_env)
+            .map_err(|_| Error::Config(format!("{} is not set", config.secret_env)))?;
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_secs))
+            .build()
+            .map_err(|error| Error::Config(format!("webhook {}: {error}", config.name)))?;
+        Ok(Self {
+            db,
+            feed,
+            config,
+            secret: secret.into_bytes(),
+            http,
+            page_size,
+            poll_interval,
+        })
+    }
+
+    /// Deliver until the task is dropped, woken by new events and every `poll_interval` to
+    /// look for escrows about to expire
+    pub async fn run(self) {
+        let mut latest = self.feed.subscribe();
+        loop {
+            let head = *latest.borrow_and_update();
+            if let Err(error) = self.step(head).await {
+                warn!("webhook {}: {error}", self.config.name);
+            }
+            tokio::select! {
+                _ = latest.changed() => {}
+                _ = tokio::time::sleep(self.poll_interval) => {}
+            }
+        }
+    }
+
+    async fn step(&self, head: i64) -> Result<()> {
+        let name = &self.config.name;
+        let mut position = match self.db.webhook_cursor(name).await? {
+            Some(position) => position,
+            None => {
+                info!("webhook {name}: delivering events after {head}");
+                self.db.set_webhook_cursor(name, head).await?;
+                head
+            }
+        };
+        loop {
+            let events = self
+                .db
+                .events(&self.config.filter, position, self.page_size)
+                .await?;
+            for event in &events {
+                if self.config.wants(&event.kind) {
+                    self.deliver(&event.seq.to_string(), &event.kind, event)
+                        .await?;
+                }
+                position = event.seq;
+                self.db.set_webhook_cursor(name, position).await?;
+            }
+            if events.len() < self.page_size {
+                break;
+            }
+        }
+        // Events become visible in order, so none up to the head read before is still coming
+        if head > position {
+            self.db.set_webhook_cursor(name, head).await?;
+        }
+
+        if self.config.wants("expiring") {
+            let now = now();
+            let escrows = self
+                .db
+                .expiring(
+                    name,
+                    &self.config.filter,
+                    now,
+                    now + self.config.expiring_secs,
+                    self.page_size,
+                )
+                .await?;
+            for escrow in &escrows {
+                let id = format!(
+                    "expiring:{}:{}",
+                    escrow.chain,
+                    hex::encode(&escrow.escrow_id)
+                );
+                self.deliver(&id, "expiring", escrow).await?;
+                self.db.expiring_sent(name, escrow).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// POST one delivery until the subscriber accepts it or `max_attempts` run out, then
+    /// dead-letter it; only failing to record the outcome is an error
+    async fn deliver(&self, id: &str, kind: &str, data: &impl Serialize) -> Result<()> {
+        let body = serde_json::to_string(&Payload {
+            id,
+            kind,
+            sent_at: now(),
+            data,
+        })
+        .expect("events and escrows serialize");
+        let mut backoff = Duration::from_secs(self.config.retry_secs);
+        let mut attempt = 1;
+        loop {
+            let error = match self.post(id, kind, &body).await {
+                Ok(()) => return Ok(()),
+                Err(error) => error,
+            };
+            if attempt >= self.config.max_attempts {
+                warn!(
+                    "webhook {}: {kind} {id} dead-lettered after {attempt} tries: {error}",
+                    self.config.name
+                );
+                return self
+                    .db
+                    .dead_letter(&self.config.name, id, kind, &body, attempt, &error)
+                    .await;
+            }
+            warn!(
+                "webhook {}: {kind} {id} failed ({error}), retrying in {backoff:?}",
+                self.config.name
+            );
+            tokio::time::sleep(backoff).await;
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            attempt += 1;
+        }
+    }
+
+    async fn post(&self, id: &str, kind: &str, body: &str) -> std::result::Result<(), String> {
+        let timestamp = now();
+        self.http
+            .post(&self.config.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, sign(&self.secret, timestamp, body))
+            .header(DELIVERY_HEADER, id)
+            .header(KIND_HEADER, kind)
+            .body(body.to_string())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    }
+}
+
+/// The `SIGNATURE_HEADER` value for `body` sent at `timestamp`; subscribers recompute the MAC
+/// over `"<t>.<body>"` and reject stale timestamps to stop replays
+pub fn sign(secret: &[u8], timestamp: u64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any length");
+    mac.update(format!("{timestamp}.{body}").as_bytes());
+    format!(
+        "t={timestamp},v1={}",
+        hex::encode(mac.finalize().into_bytes())
+    )
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+fn default_expiring_secs() -> u64 {
+    3600
+}
+
+fn default_max_attempts() -> u32 {
+    8
+}
+
+fn default_retry_secs() -> u64 {
+    5
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
@@ -0,0 +1,169 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+
+use crate::keys::KeySource;
+use crate::network::Network;
+
+/// Create, settle and inspect escrows on the Stellar HTLC contract
+#[derive(Debug, Parser)]
+#[command(name = "unite", version)]
+pub struct Cli {
+    #[command(flatten)]
+    pub global: Global,
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Debug, Args)]
+pub struct Global {
+    /// Picks the network passphrase and default RPC
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        default_value = "testnet",
+        env = "UNITE_NETWORK"
+    )]
+    pub network: Network,
+    /// Soroban RPC endpoint; defaults to SDF's for testnet and futurenet
+    #[arg(long, global = true, env = "UNITE_RPC_URL")]
+    pub rpc_url: Option<String>,
+    /// Overrides the passphrase `--network` implies
+    #[arg(long, global = true)]
+    pub network_passphrase: Option<String>,
+    /// Escrow contract address (`C...`)
+    #[arg(long, global = true, env = "UNITE_CONTRACT")]
+    pub contract: Option<String>,
+    #[command(flatten)]
+    pub key: KeyArgs,
+}
+
+/// Signing key for `create`, `withdraw` and `cancel`; the secret key itself is never a
+/// command-line argument, so it stays out of shell history and process listings
+#[derive(Debug, Args)]
+pub struct KeyArgs {
+    /// stellar-cli identity name
+    #[arg(long, global = true, conflicts_with = "key_file")]
+    pub identity: Option<String>,
+    /// File holding an `S...` secret key or a stellar-cli identity
+    #[arg(long, global = true)]
+    pub key_file: Option<PathBuf>,
+    /// Environment variable holding an `S...` secret key
+    #[arg(long, global = true, default_value = "STELLAR_SECRET_KEY")]
+    pub secret_key_env: String,
+}
+
+impl KeyArgs {
+    pub fn source(&self) -> KeySource {
+        match (&self.identity, &self.key_file) {
+            (Some(name), _) => KeySource::Identity(name.clone()),
+            (None, Some(path)) => KeySource::File(path.clone()),
+            (None, None) => KeySource::Env(self.secret_key_env.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Lock funds from the signer's account
+    Create(Create),
+    /// Claim an escrow with its secret; the signer must be the receiver
+    Withdraw(Withdraw),
+    /// Refund an expired escrow; the signer must be the sender
+    Cancel(Cancel),
+    /// Show an escrow's state, and its secret once withdrawn
+    Status(Status),
+    /// List escrows opened within a range of recent ledgers
+    List(List),
+    /// Print a random 32-byte secret and its hashlock
+    GenerateSecret(GenerateSecret),
+}
+
+#[derive(Debug, Args)]
+pub struct Create {
+    /// Account or contract that can withdraw
+    #[arg(long)]
+    pub receiver: String,
+    /// In the token's smallest unit (stroops for XLM)
+    #[arg(long)]
+    pub amount: i128,
+    /// Token contract (`C...`); native XLM when left out
+    #[arg(long)]
+    pub token: Option<String>,
+    /// keccak256 of the secret, or its SHA-256 with `--sha256`, hex
+    #[arg(long, required_unless_present = "secret", conflicts_with = "secret")]
+    pub hashlock: Option<String>,
+    /// Secret to hash into the hashlock, hex; it is not sent anywhere
+    #[arg(long)]
+    pub secret: Option<String>,
+    /// Lock with SHA-256 instead of keccak256, to pair with a Lightning hold invoice, a
+    /// Bitcoin script or a classic hash(x) escrow
+    #[arg(long)]
+    pub sha256: bool,
+    /// Unix seconds, or `+` and a duration from now such as `+3600`, `+90m`, `+2h` or `+1d`
+    #[arg(long)]
+    pub timelock: String,
+    /// The escrow ID is `keccak256(order_id)`, so it must be unused on the contract
+    #[arg(long)]
+    pub order_id: String,
+    /// Simulate only; nothing is signed or submitted
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// An escrow, by ID or by the order it was created for
+#[derive(Debug, Args)]
+#[group(required = true, multiple = false)]
+pub struct EscrowRef {
+    /// Escrow ID, hex
+    #[arg(long)]
+    pub escrow_id: Option<String>,
+    #[arg(long)]
+    pub order_id: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct Withdraw {
+    #[command(flatten)]
+    pub escrow: EscrowRef,
+    /// Preimage of the hashlock, hex
+    #[arg(long)]
+    pub secret: String,
+    /// Simulate only; nothing is signed or submitted
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct Cancel {
+    #[command(flatten)]
+    pub escrow: EscrowRef,
+    /// Simulate only; nothing is signed or submitted
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct Status {
+    #[command(flatten)]
+    pub escrow: EscrowRef,
+}
+
+#[derive(Debug, Args)]
+pub struct List {
+    /// Only escrows this account or contract sends or receives
+    #[arg(long)]
+    pub address: Option<String>,
+    /// First ledger to read events from; defaults to about a day back. RPC servers keep only
+    /// about a week of events
+    #[arg(long)]
+    pub start_ledger: Option<u32>,
+}
+
+#[derive(Debug, Args)]
+pub struct GenerateSecret {
+    /// Print the SHA-256 hashlock instead of the keccak256 one
+    #[arg(long)]
+    pub sha256: bool,
+}
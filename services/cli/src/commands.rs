@@ -0,0 +1,361 @@
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use sha2::Sha256;
+use sha3::{Digest, Keccak256};
+use unite_stellar::client::parse_address;
+use unite_stellar::{
+    escrow_id, EscrowClient, EscrowEvent, EscrowLog, EscrowState, EscrowTerms, EventStart,
+    HashLock, SettlementStatus, Signer, Simulation,
+};
+
+use crate::args::{
+    Cancel, Cli, Command, Create, EscrowRef, GenerateSecret, Global, List, Status, Withdraw,
+};
+use crate::{Error, Result};
+
+/// About a day of ledgers at five seconds each
+const LEDGERS_PER_DAY: u32 = 17_280;
+
+/// Events fetched per `getEvents` call
+const EVENT_PAGE_SIZE: u32 = 200;
+
+pub async fn run(cli: Cli) -> Result<()> {
+    let Cli { global, command } = cli;
+    match command {
+        Command::Create(args) => create(&connect(&global, true)?, &args).await,
+        Command::Withdraw(args) => withdraw(&connect(&global, true)?, &args).await,
+        Command::Cancel(args) => cancel(&connect(&global, true)?, &args).await,
+        Command::Status(args) => status(&connect(&global, false)?, &args).await,
+        Command::List(args) => list(&global, &connect(&global, false)?, &args).await,
+        Command::GenerateSecret(args) => {
+            generate_secret(&args);
+            Ok(())
+        }
+    }
+}
+
+/// Client for the chosen network; reads need no account, so without `signing` a throwaway
+/// key stands in and no key is loaded
+fn connect(global: &Global, signing: bool) -> Result<EscrowClient> {
+    let rpc_url = global
+        .rpc_url
+        .as_deref()
+        .or(global.network.default_rpc_url())
+        .ok_or_else(|| {
+            Error::Usage("no public RPC for this network; pass --rpc-url".to_string())
+        })?;
+    let passphrase = global
+        .network_passphrase
+        .as_deref()
+        .unwrap_or(global.network.passphrase());
+    let signer = if signing {
+        global.key.source().load()?
+    } else {
+        Signer::generate()
+    };
+    Ok(EscrowClient::new(
+        rpc_url,
+        contract(global)?,
+        signer,
+        passphrase,
+    )?)
+}
+
+fn contract(global: &Global) -> Result<&str> {
+    global
+        .contract
+        .as_deref()
+        .ok_or_else(|| Error::Usage("--contract or UNITE_CONTRACT is required".to_string()))
+}
+
+async fn create(client: &EscrowClient, args: &Create) -> Result<()> {
+    if args.amount <= 0 {
+        return Err(Error::Usage("--amount must be positive".to_string()));
+    }
+    let secret_hash = match (&args.hashlock, &args.secret) {
+        (Some(hashlock), _) => hash32(hashlock, "--hashlock")?,
+        (None, Some(secret)) => hashlock(&hash32(secret, "--secret")?, args.sha256),
+        (None, None) => {
+            return Err(Error::Usage(
+                "--hashlock or --secret is required".to_string(),
+            ))
+        }
+    };
+    let now = now();
+    let timelock = parse_timelock(&args.timelock, now)?;
+    if timelock <= now {
+        return Err(Error::Usage(format!("timelock {timelock} has passed")));
+    }
+    let terms = EscrowTerms {
+        sender: parse_address(&client.signer().address())?,
+        receiver: parse_address(&args.receiver)?,
+        token: args.token.as_deref().map(parse_address).transpose()?,
+        amount: args.amount,
+        secret_hash,
+        hash_lock: if args.sha256 {
+            HashLock::Sha256
+        } else {
+            HashLock::Keccak256
+        },
+        timelock,
+        order_id: args.order_id.clone(),
+    };
+
+    println!("escrow: {}", hex::encode(escrow_id(&args.order_id)));
+    println!("hashlock: {}", hex::encode(secret_hash));
+    println!("timelock: {timelock}");
+    if args.dry_run {
+        return print_simulation(client, client.simulate_create_escrow(&terms).await?).await;
+    }
+    let (_, tx_hash) = client.create_escrow(&terms).await?;
+    println!("transaction: {}", hex::encode(tx_hash));
+    Ok(())
+}
+
+async fn withdraw(client: &EscrowClient, args: &Withdraw) -> Result<()> {
+    let escrow_id = resolve(&args.escrow)?;
+    let secret = hash32(&args.secret, "--secret")?;
+    println!("escrow: {}", hex::encode(escrow_id));
+    if args.dry_run {
+        return print_simulation(client, client.simulate_withdraw(&escrow_id, &secret).await?)
+            .await;
+    }
+    let tx_hash = client.withdraw(&escrow_id, &secret).await?;
+    println!("transaction: {}", hex::encode(tx_hash));
+    Ok(())
+}
+
+async fn cancel(client: &EscrowClient, args: &Cancel) -> Result<()> {
+    let escrow_id = resolve(&args.escrow)?;
+    println!("escrow: {}", hex::encode(escrow_id));
+    if args.dry_run {
+        return print_simulation(client, client.simulate_cancel(&escrow_id).await?).await;
+    }
+    let tx_hash = client.cancel(&escrow_id).await?;
+    println!("transaction: {}", hex::encode(tx_hash));
+    Ok(())
+}
+
+async fn status(client: &EscrowClient, args: &Status) -> Result<()> {
+    let escrow_id = resolve(&args.escrow)?;
+    match client.escrow_state(&escrow_id).await? {
+        EscrowState::Missing => Err(Error::NotFound(format!(
+            "escrow {}",
+            hex::encode(escrow_id)
+        ))),
+        EscrowState::Pending { escrow, ledger } => {
+            println!("escrow: {}", hex::encode(escrow_id));
+            println!("state: open");
+            println!("amount: {}", escrow.amount);
+            println!("hashlock: {}", hex::encode(escrow.secret_hash));
+            let now = now();
+            if escrow.timelock > now {
+                println!(
+                    "timelock: {} (expires in {}s)",
+                    escrow.timelock,
+                    escrow.timelock - now
+                );
+            } else {
+                println!("timelock: {} (expired, cancellable)", escrow.timelock);
+            }
+            println!("ledger: {ledger}");
+            Ok(())
+        }
+        EscrowState::Settled { receipt, ledger } => {
+            println!("escrow: {}", hex::encode(escrow_id));
+            println!("state: {}", status_name(receipt.status));
+            println!("amount: {}", receipt.amount);
+            println!("hashlock: {}", hex::encode(receipt.secret_hash));
+            println!("ledger: {ledger}");
+            if receipt.status != SettlementStatus::Cancelled {
+                if let Some(secret) = client.revealed_secret(&receipt.secret_hash).await? {
+                    println!("secret: {}", hex::encode(secret));
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+/// An escrow opened in the listed range, with the state its later events left it in
+struct Listed {
+    escrow_id: [u8; 32],
+    ledger: u32,
+    order_id: String,
+    amount: i128,
+    filled_amount: i128,
+    timelock: u64,
+    status: Option<SettlementStatus>,
+}
+
+async fn list(global: &Global, client: &EscrowClient, args: &List) -> Result<()> {
+    let contract = contract(global)?;
+    let start = match args.start_ledger {
+        Some(start) => start,
+        None => client
+            .rpc()
+            .latest_ledger()
+            .await?
+            .saturating_sub(LEDGERS_PER_DAY),
+    };
+
+    let mut escrows: Vec<Listed> = Vec::new();
+    let mut index = HashMap::new();
+    let mut position = EventStart::Ledger(start);
+    loop {
+        let page = client
+            .rpc()
+            .events(contract, position, EVENT_PAGE_SIZE)
+            .await?;
+        for event in &page.events {
+            let Some(log) = EscrowLog::decode(event)? else {
+                continue;
+            };
+            if let EscrowEvent::Opened {
+                order_id,
+                sender,
+                receiver,
+                amount,
+                timelock,
+                ..
+            } = &log.event
+            {
+                let wanted = args
+                    .address
+                    .as_ref()
+                    .is_none_or(|address| address == sender || address == receiver);
+                if wanted {
+                    index.insert(log.escrow_id, escrows.len());
+                    escrows.push(Listed {
+                        escrow_id: log.escrow_id,
+                        ledger: log.ledger,
+                        order_id: order_id.clone(),
+                        amount: *amount,
+                        filled_amount: 0,
+                        timelock: *timelock,
+                        status: None,
+                    });
+                }
+                continue;
+            }
+            // Escrows opened before the range are left out; their later events are too
+            let Some(listed) = index.get(&log.escrow_id).map(|&at| &mut escrows[at]) else {
+                continue;
+            };
+            match log.event {
+                EscrowEvent::Filled { filled_amount, .. } => listed.filled_amount = filled_amount,
+                EscrowEvent::Settled { status, .. } => listed.status = Some(status),
+                _ => {}
+            }
+        }
+        if page.events.is_empty() {
+            break;
+        }
+        position = EventStart::Cursor(page.cursor);
+    }
+
+    let now = now();
+    for listed in &escrows {
+        let status = match listed.status {
+            Some(status) => status_name(status),
+            None if listed.timelock <= now => "expired",
+            None if listed.filled_amount > 0 => "partial",
+            None => "open",
+        };
+        println!(
+            "{}  {:>10}  {:<9}  {:>20}  {:>10}  {}",
+            hex::encode(listed.escrow_id),
+            listed.ledger,
+            status,
+            listed.amount,
+            listed.timelock,
+            listed.order_id
+        );
+    }
+    Ok(())
+}
+
+fn generate_secret(args: &GenerateSecret) {
+    let mut secret = [0u8; 32];
+    OsRng.fill_bytes(&mut secret);
+    println!("secret: {}", hex::encode(secret));
+    println!("hashlock: {}", hex::encode(hashlock(&secret, args.sha256)));
+}
+
+/// keccak256 of the secret as the contract checks a `Hashlock`, or SHA-256 for `Sha256`
+fn hashlock(secret: &[u8; 32], sha256: bool) -> [u8; 32] {
+    if sha256 {
+        Sha256::digest(secret).into()
+    } else {
+        Keccak256::digest(secret).into()
+    }
+}
+
+/// Print a simulation's cost; the transaction fee is the inclusion fee plus the resource fee
+async fn print_simulation(client: &EscrowClient, simulation: Simulation) -> Result<()> {
+    let inclusion_fee = client.rpc().inclusion_fee().await?;
+    println!("simulation: ok");
+    println!("resource fee: {} stroops", simulation.min_resource_fee);
+    println!("inclusion fee: {inclusion_fee} stroops");
+    println!("authorizations: {}", simulation.auth.len());
+    Ok(())
+}
+
+fn resolve(escrow: &EscrowRef) -> Result<[u8; 32]> {
+    match (&escrow.escrow_id, &escrow.order_id) {
+        (Some(id), _) => hash32(id, "--escrow-id"),
+        (None, Some(order_id)) => Ok(escrow_id(order_id)),
+        (None, None) => Err(Error::Usage(
+            "--escrow-id or --order-id is required".to_string(),
+        )),
+    }
+}
+
+/// 32 bytes of hex, with or without `0x`
+fn hash32(value: &str, name: &str) -> Result<[u8; 32]> {
+    hex::decode(value.trim_start_matches("0x"))
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::Usage(format!("{name} must be 32 bytes of hex")))
+}
+
+/// Unix seconds, or `+N` with an optional `s`, `m`, `h` or `d` suffix counted from `now`
+fn parse_timelock(value: &str, now: u64) -> Result<u64> {
+    let invalid = || Error::Usage(format!("invalid timelock {value}"));
+    let Some(duration) = value.strip_prefix('+') else {
+        return value.parse().map_err(|_| invalid());
+    };
+    let (count, unit) = match duration.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => duration.split_at(at),
+        None => (duration, "s"),
+    };
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3_600,
+        "d" => 86_400,
+        _ => return Err(invalid()),
+    };
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    count
+        .checked_mul(unit)
+        .and_then(|secs| now.checked_add(secs))
+        .ok_or_else(invalid)
+}
+
+fn status_name(status: SettlementStatus) -> &'static str {
+    match status {
+        SettlementStatus::Withdrawn => "withdrawn",
+        SettlementStatus::Cancelled => "cancelled",
+        SettlementStatus::Resolved => "resolved",
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
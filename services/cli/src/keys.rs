@@ -0,0 +1,96 @@
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use unite_stellar::Signer;
+
+use crate::{Error, Result};
+
+/// Where the signing key is read from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum KeySource {
+    /// Environment variable holding an `S...` secret key
+    Env(String),
+    /// File holding an `S...` secret key, bare or as a stellar-cli identity
+    File(PathBuf),
+    /// stellar-cli identity name, looked up in `.stellar/identity` under the working directory,
+    /// then in the user's config directory
+    Identity(String),
+}
+
+/// A stellar-cli identity file; only identities holding a secret key are supported
+#[derive(Debug, Deserialize)]
+struct Identity {
+    secret_key: Option<String>,
+    seed_phrase: Option<String>,
+}
+
+impl KeySource {
+    pub fn load(&self) -> Result<Signer> {
+        let secret = match self {
+            KeySource::Env(name) => {
+                std::env::var(name).map_err(|_| Error::Key(format!("{name} is not set")))?
+            }
+            KeySource::File(path) => read_key_file(path)?,
+            KeySource::Identity(name) => {
+                let path = identity_path(name)
+                    .ok_or_else(|| Error::Key(format!("no identity named {name}")))?;
+                read_key_file(&path)?
+            }
+        };
+        Signer::from_secret(secret.trim()).map_err(|_| {
+            Error::Key(format!(
+                "{} does not hold a valid secret key",
+                self.describe()
+            ))
+        })
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            KeySource::Env(name) => name.clone(),
+            KeySource::File(path) => path.display().to_string(),
+            KeySource::Identity(name) => format!("identity {name}"),
+        }
+    }
+}
+
+fn read_key_file(path: &Path) -> Result<String> {
+    let contents = std::fs::read_to_string(path)?;
+    let trimmed = contents.trim();
+    if !trimmed.contains('=') {
+        return Ok(trimmed.to_string());
+    }
+    let identity: Identity = toml::from_str(&contents)?;
+    match identity {
+        Identity {
+            secret_key: Some(secret_key),
+            ..
+        } => Ok(secret_key),
+        Identity {
+            seed_phrase: Some(_),
+            ..
+        } => Err(Error::Key(format!(
+            "{} holds a seed phrase; export its secret key with `stellar keys show`",
+            path.display()
+        ))),
+        _ => Err(Error::Key(format!(
+            "{} holds no secret key",
+            path.display()
+        ))),
+    }
+}
+
+fn identity_path(name: &str) -> Option<PathBuf> {
+    let file = format!("{name}.toml");
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")));
+    [
+        Some(PathBuf::from(".stellar")),
+        config.map(|dir| dir.join("stellar")),
+    ]
+    .into_iter()
+    .flatten()
+    .map(|dir| dir.join("identity").join(&file))
+    .find(|path| path.is_file())
+}
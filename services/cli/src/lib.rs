@@ -0,0 +1,37 @@
+// Unite CLI
+// Command-line client for the Soroban HTLC escrow (`contracts/stellar`), built on
+// `unite-stellar`. `create`, `withdraw` and `cancel` build the contract call, simulate it over
+// Soroban RPC, sign it with the loaded key and wait until it lands in a closed ledger; with
+// `--dry-run` they stop after the simulation and print its cost. `status` reads an escrow's
+// ledger entries, `list` pages through the contract's events and `generate-secret` makes a
+// fresh secret and its hashlock offline. `--network` picks the passphrase and default RPC of
+// testnet, futurenet or mainnet; keys come from an environment variable, a file or a
+// stellar-cli identity.
+
+pub mod args;
+pub mod commands;
+pub mod keys;
+pub mod network;
+
+pub use args::Cli;
+pub use commands::run;
+pub use keys::KeySource;
+pub use network::Network;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{0}")]
+    Usage(String),
+    #[error("{0} not found")]
+    NotFound(String),
+    #[error("key: {0}")]
+    Key(String),
+    #[error(transparent)]
+    Stellar(#[from] unite_stellar::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
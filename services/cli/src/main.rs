@@ -0,0 +1,16 @@
+use std::process::ExitCode;
+
+use clap::Parser;
+use unite_cli::Cli;
+
+/// `unite <command>`; `unite --help` lists the commands
+#[tokio::main]
+async fn main() -> ExitCode {
+    match unite_cli::run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use clap::ValueEnum;
+
+/// Stellar network the escrow contract is deployed on
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Network {
+    Testnet,
+    Futurenet,
+    Mainnet,
+}
+
+impl Network {
+    pub fn passphrase(self) -> &'static str {
+        match self {
+            Network::Testnet => "Test SDF Network ; September 2015",
+            Network::Futurenet => "Test SDF Future Network ; October 2022",
+            Network::Mainnet => "Public Global Stellar Network ; September 2015",
+        }
+    }
+
+    /// SDF's public Soroban RPC; mainnet has none, so `--rpc-url` is required there
+    pub fn default_rpc_url(self) -> Option<&'static str> {
+        match self {
+            Network::Testnet => Some("https://soroban-testnet.stellar.org"),
+            Network::Futurenet => Some("https://rpc-futurenet.stellar.org"),
+            Network::Mainnet => None,
+        }
+    }
+}
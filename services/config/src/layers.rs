@@ -0,0 +1,180 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
+use toml::{Table, Value};
+
+use crate::validate::{Problems, Validate};
+use crate::{Error, Result};
+
+/// Variable naming the environment whose overlay is read, e.g. `testnet` or `mainnet`
+pub const ENV_VAR: &str = "UNITE_ENV";
+
+/// Where one service's config comes from, lowest layer first: its file, the environment's
+/// overlay beside it and the variables starting with `prefix`
+#[derive(Clone, Debug)]
+pub struct Layers {
+    path: PathBuf,
+    env: Option<String>,
+    prefix: String,
+}
+
+impl Layers {
+    /// `prefix` is the service's variable prefix, e.g. `UNITE_RELAYER`; the environment is
+    /// read from `UNITE_ENV`
+    pub fn new(path: impl Into<PathBuf>, prefix: &str) -> Self {
+        Self {
+            path: path.into(),
+            env: std::env::var(ENV_VAR).ok().filter(|env| !env.is_empty()),
+            prefix: format!("{prefix}__"),
+        }
+    }
+
+    /// Read this environment's overlay instead of `UNITE_ENV`'s, or none
+    pub fn with_env(mut self, env: Option<String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    pub fn env(&self) -> Option<&str> {
+        self.env.as_deref()
+    }
+
+    /// `relayer.toml` with environment `testnet` has the overlay `relayer.testnet.toml`
+    pub fn overlay(&self) -> Option<PathBuf> {
+        let env = self.env.as_ref()?;
+        let stem = self.path.file_stem()?.to_string_lossy();
+        let name = match self.path.extension() {
+            Some(extension) => format!("{stem}.{env}.{}", extension.to_string_lossy()),
+            None => format!("{stem}.{env}"),
+        };
+        Some(self.path.with_file_name(name))
+    }
+
+    /// The files read, base first; a missing overlay is skipped
+    pub fn files(&self) -> Vec<PathBuf> {
+        let mut files = vec![self.path.clone()];
+        files.extend(self.overlay().filter(|overlay| overlay.exists()));
+        files
+    }
+
+    /// Latest modification time of the files, for noticing edits
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.files()
+            .iter()
+            .filter_map(|path| {
+                std::fs::metadata(path)
+                    .and_then(|meta| meta.modified())
+                    .ok()
+            })
+            .max()
+    }
+
+    /// Every layer merged into one table
+    pub fn table(&self) -> Result<Table> {
+        let mut table = Table::new();
+        for path in self.files() {
+            merge(&mut table, read(&path)?);
+        }
+        let mut overrides: Vec<_> = std::env::vars()
+            .filter(|(var, _)| var.starts_with(&self.prefix))
+            .collect();
+        overrides.sort();
+        for (var, value) in overrides {
+            let keys: Vec<_> = var[self.prefix.len()..]
+                .split("__")
+                .map(str::to_lowercase)
+                .collect();
+            set(&mut table, &keys, parse(&value)).map_err(|reason| Error::Override {
+                var: var.clone(),
+                reason,
+            })?;
+        }
+        Ok(table)
+    }
+
+    /// The merged layers as `T`, rejected with every problem `T::validate` finds
+    pub fn load<T: DeserializeOwned + Validate>(&self) -> Result<T> {
+        let config: T = Value::Table(self.table()?).try_into()?;
+        let mut problems = Problems::default();
+        config.validate(&mut problems);
+        problems.into_result()?;
+        Ok(config)
+    }
+}
+
+/// Load `path` with its overlay and the overrides starting with `prefix`
+pub fn load<T: DeserializeOwned + Validate>(path: impl Into<PathBuf>, prefix: &str) -> Result<T> {
+    Layers::new(path, prefix).load()
+}
+
+fn read(path: &Path) -> Result<Table> {
+    let text = std::fs::read_to_string(path).map_err(|source| Error::Read {
+        path: path.display().to_string(),
+        source,
+    })?;
+    toml::from_str(&text).map_err(|source| Error::Parse {
+        path: path.display().to_string(),
+        source: Box::new(source),
+    })
+}
+
+/// Tables merge key by key; anything else replaces what was there
+fn merge(base: &mut Table, layer: Table) {
+    for (key, value) in layer {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(layer)) => merge(base, layer),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Set the value at `keys`, creating tables on the way; numeric keys index into arrays
+fn set(table: &mut Table, keys: &[String], value: Value) -> std::result::Result<(), String> {
+    let (key, rest) = keys.split_first().ok_or("no key after the prefix")?;
+    if rest.is_empty() {
+        table.insert(key.clone(), value);
+        return Ok(());
+    }
+    let entry = table
+        .entry(key.clone())
+        .or_insert_with(|| Value::Table(Table::new()));
+    set_value(entry, key, rest, value)
+}
+
+fn set_value(
+    entry: &mut Value,
+    key: &str,
+    keys: &[String],
+    value: Value,
+) -> std::result::Result<(), String> {
+    match entry {
+        Value::Table(table) => set(table, keys, value),
+        Value::Array(array) => {
+            let index: usize = keys[0]
+                .parse()
+                .map_err(|_| format!("{key} is an array, so {} must be an index", keys[0]))?;
+            let len = array.len();
+            let item = array
+                .get_mut(index)
+                .ok_or_else(|| format!("{key} has {len} entries, no index {index}"))?;
+            if keys.len() == 1 {
+                *item = value;
+                Ok(())
+            } else {
+                set_value(item, &keys[0], &keys[1..], value)
+            }
+        }
+        _ => Err(format!("{key} is not a table")),
+    }
+}
+
+/// Values are read as TOML (`120`, `true`, `["a", "b"]`), falling back to a plain string
+fn parse(value: &str) -> Value {
+    toml::from_str::<Table>(&format!("value = {value}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
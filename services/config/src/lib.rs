@@ -0,0 +1,48 @@
+// Configuration
+// Layered loading for the services' TOML configs. A service reads its file, then the overlay
+// for the environment `UNITE_ENV` names next to it (`relayer.toml`, then
+// `relayer.testnet.toml`), then environment variables such as `UNITE_RELAYER__POLICY__RETRY_SECS`.
+// Tables merge key by key into the layer below; arrays and plain values replace it, and
+// array entries are addressed by index (`UNITE_RELAYER__CHAINS__0__WS_URL`). The merged config
+// is validated before the service starts, with every problem reported at once, and a
+// `[tokens]` table maps symbols to each chain's token address. While a service runs, `watch`
+// re-reads the layers when a file changes and hands each valid config to the service, which
+// applies the values that are safe to change live and keeps the rest until a restart.
+
+pub mod layers;
+pub mod tokens;
+pub mod validate;
+pub mod watch;
+
+pub use layers::{load, Layers, ENV_VAR};
+pub use tokens::Tokens;
+pub use validate::{Problems, Validate};
+pub use watch::watch;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("{path}: {source}")]
+    Read {
+        path: String,
+        source: std::io::Error,
+    },
+    #[error("{path}: {source}")]
+    Parse {
+        path: String,
+        source: Box<toml::de::Error>,
+    },
+    #[error("{var}: {reason}")]
+    Override { var: String, reason: String },
+    #[error(transparent)]
+    Deserialize(Box<toml::de::Error>),
+    #[error("invalid config: {}", .0.join("; "))]
+    Invalid(Vec<String>),
+}
+
+impl From<toml::de::Error> for Error {
+    fn from(error: toml::de::Error) -> Self {
+        Error::Deserialize(Box::new(error))
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,30 @@
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+/// The `[tokens]` table: each symbol's address on the chains it exists on, e.g.
+/// `USDC = { base = "0x036C…", stellar = "CCW6…" }`, so other sections can name tokens by
+/// symbol and an environment's overlay can swap every address at once
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+pub struct Tokens(BTreeMap<String, BTreeMap<String, String>>);
+
+impl Tokens {
+    /// `token`'s address on `chain` when it is a known symbol, otherwise `token` itself
+    pub fn resolve<'a>(&'a self, chain: &str, token: &'a str) -> &'a str {
+        self.0
+            .get(token)
+            .and_then(|chains| chains.get(chain))
+            .map_or(token, String::as_str)
+    }
+
+    /// Whether `token` is a symbol, which then must have an address on `chain`
+    pub fn is_symbol(&self, token: &str) -> bool {
+        self.0.contains_key(token)
+    }
+
+    pub fn has(&self, chain: &str, symbol: &str) -> bool {
+        self.0
+            .get(symbol)
+            .is_some_and(|chains| chains.contains_key(chain))
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{Error, Result};
+
+/// Startup checks beyond what deserializing catches: references between sections, ranges,
+/// address formats
+pub trait Validate {
+    /// Record every problem found rather than stopping at the first
+    fn validate(&self, problems: &mut Problems);
+}
+
+/// Problems found while validating a config
+#[derive(Clone, Debug, Default)]
+pub struct Problems(Vec<String>);
+
+impl Problems {
+    pub fn push(&mut self, problem: impl Into<String>) {
+        self.0.push(problem.into());
+    }
+
+    /// Record `problem` unless `ok`
+    pub fn check(&mut self, ok: bool, problem: impl FnOnce() -> String) {
+        if !ok {
+            self.0.push(problem());
+        }
+    }
+
+    /// Record the problems of a nested section, prefixed with where it is
+    pub fn section(&mut self, at: &str, section: &impl Validate) {
+        let mut problems = Problems::default();
+        section.validate(&mut problems);
+        self.0.extend(
+            problems
+                .0
+                .into_iter()
+                .map(|problem| format!("{at}: {problem}")),
+        );
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn into_result(self) -> Result<()> {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Invalid(self.0))
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use tokio::task::JoinHandle;
+
+use crate::layers::Layers;
+use crate::validate::Validate;
+use crate::Result;
+
+/// Check `layers`' files every `interval` and, after one changes, load the config again and
+/// pass it to `apply`: the new config when it is valid, otherwise why it was not taken
+///
+/// Environment variables are read again too, but only file edits trigger a reload.
+pub fn watch<T, F>(layers: Layers, interval: Duration, apply: F) -> JoinHandle<()>
+where
+    T: DeserializeOwned + Validate + Send + 'static,
+    F: Fn(Result<T>) + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut seen = layers.modified();
+        let mut tick = tokio::time::interval(interval);
+        tick.tick().await;
+        loop {
+            tick.tick().await;
+            let modified = layers.modified();
+            if modified != seen {
+                seen = modified;
+                apply(layers.load());
+            }
+        }
+    })
+}
@@ -0,0 +1,130 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use frost_ed25519::keys::KeyPackage;
+use serde::Deserialize;
+use unite_secrets::SecretsConfig;
+use zeroize::Zeroizing;
+
+use crate::coordinator::{Coordinator, Node};
+use crate::group::Group;
+use crate::node::SignerNode;
+use crate::{Error, Result};
+
+/// How a service reaches the signer nodes of a group, e.g. the `frost` table of a relayer
+/// chain; tokens are read from the environment variables it names
+#[derive(Clone, Debug, Deserialize)]
+pub struct CoordinatorConfig {
+    /// `group.json` written by `unite-frost deal`
+    pub group: PathBuf,
+    pub nodes: Vec<NodeEndpoint>,
+    /// Limit on each request to a node
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct NodeEndpoint {
+    /// e.g. `https://signer-1.example.com:8095`
+    pub url: String,
+    /// Variable holding the node's bearer token
+    pub token_env: String,
+}
+
+impl CoordinatorConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub fn connect(&self) -> Result<Coordinator> {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                Ok(Node {
+                    url: node.url.clone(),
+                    token: env(&node.token_env)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Coordinator::new(
+            Group::load(&self.group)?,
+            nodes,
+            Duration::from_secs(self.timeout_secs),
+        )
+    }
+}
+
+/// Settings of a signer node, read from a TOML file; its key share comes from a secret store
+/// entry or a file, and its token from the environment
+#[derive(Clone, Debug, Deserialize)]
+pub struct NodeConfig {
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    /// `group.json` written by `unite-frost deal`
+    pub group: PathBuf,
+    /// Secret store entry holding the node's `share-N.json`
+    pub share_store: Option<String>,
+    /// The node's `share-N.json` itself, for nodes without a secret store
+    pub share_file: Option<PathBuf>,
+    pub secrets: Option<SecretsConfig>,
+    /// Variable holding the bearer token the coordinator must send
+    pub token_env: String,
+    /// How long a commitment waits for its signing request
+    #[serde(default = "default_session_ttl_secs")]
+    pub session_ttl_secs: u64,
+}
+
+impl NodeConfig {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(toml::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    pub async fn node(&self) -> Result<SignerNode> {
+        let share = match (&self.share_store, &self.share_file, &self.secrets) {
+            (Some(name), None, Some(secrets)) => secrets
+                .open()
+                .await?
+                .get(name)
+                .await?
+                .ok_or_else(|| Error::Config(format!("no secret named {name}")))?,
+            (Some(_), None, None) => {
+                return Err(Error::Config(
+                    "share_store needs a [secrets] table".to_string(),
+                ))
+            }
+            (None, Some(path), _) => Zeroizing::new(std::fs::read(path)?),
+            _ => {
+                return Err(Error::Config(
+                    "give exactly one of share_store and share_file".to_string(),
+                ))
+            }
+        };
+        let key_package: KeyPackage = serde_json::from_slice(&share)?;
+        SignerNode::new(
+            Group::load(&self.group)?,
+            key_package,
+            env(&self.token_env)?,
+            Duration::from_secs(self.session_ttl_secs),
+        )
+    }
+}
+
+fn env(name: &str) -> Result<Zeroizing<String>> {
+    std::env::var(name)
+        .map(Zeroizing::new)
+        .map_err(|_| Error::Config(format!("{name} is not set")))
+}
+
+fn default_timeout_secs() -> u64 {
+    10
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8095))
+}
+
+fn default_session_ttl_secs() -> u64 {
+    60
+}
@@ -0,0 +1,230 @@
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use frost_ed25519::{Identifier, SigningPackage};
+use futures::future::join_all;
+use log::warn;
+use rand::RngCore;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use unite_core::RemoteSigner;
+use zeroize::Zeroizing;
+
+use crate::group::Group;
+use crate::protocol::{CommitRequest, CommitResponse, SignRequest, SignResponse};
+use crate::{Error, Result};
+
+/// A signer node as the coordinator reaches it
+#[derive(Clone)]
+pub struct Node {
+    pub url: String,
+    pub token: Zeroizing<String>,
+}
+
+/// Collects threshold signatures from a group's signer nodes
+///
+/// Every reachable node is asked to commit, the first `threshold` that answer sign, and
+/// their shares are aggregated into one Ed25519 signature under the group key. A node that
+/// fails either round or sends a share that does not verify is left out and the signature
+/// retried with the others while enough remain. It implements `RemoteSigner`, so chain
+/// clients sign through it the way they would with a local key.
+pub struct Coordinator {
+    group: Group,
+    public_key: [u8; 32],
+    nodes: Vec<Node>,
+    http: reqwest::Client,
+}
+
+impl Coordinator {
+    /// `timeout` bounds each request to a node, so an unresponsive node only delays a round
+    pub fn new(group: Group, nodes: Vec<Node>, timeout: Duration) -> Result<Self> {
+        if nodes.len() < usize::from(group.threshold) {
+            return Err(Error::NotEnoughSigners {
+                needed: group.threshold,
+                available: nodes.len(),
+            });
+        }
+        Ok(Self {
+            public_key: group.public_key()?,
+            group,
+            nodes,
+            http: reqwest::Client::builder().timeout(timeout).build()?,
+        })
+    }
+
+    pub fn group(&self) -> &Group {
+        &self.group
+    }
+
+    pub async fn sign_message(&self, message: &[u8]) -> Result<[u8; 64]> {
+        let needed = self.group.threshold;
+        let mut excluded = HashSet::new();
+        loop {
+            let available: Vec<usize> = (0..self.nodes.len())
+                .filter(|index| !excluded.contains(index))
+                .collect();
+            if available.len() < usize::from(needed) {
+                return Err(Error::NotEnoughSigners {
+                    needed,
+                    available: available.len(),
+                });
+            }
+            let session = session_id();
+
+            let commit = CommitRequest {
+                session: session.clone(),
+            };
+            let responses = join_all(available.iter().map(|&index| {
+                let commit = &commit;
+                async move {
+                    let response = self
+                        .call::<_, CommitResponse>(index, "v1/commit", commit)
+                        .await;
+                    (index, response)
+                }
+            }))
+            .await;
+            let mut signers = BTreeMap::new();
+            let mut commitments = BTreeMap::new();
+            for (index, response) in responses {
+                match response.and_then(|response| self.check(index, response)) {
+                    Ok(response) if signers.len() < usize::from(needed) => {
+                        // Two nodes holding the same share would spoil the aggregate
+                        if commitments.contains_key(&response.0) {
+                            warn!("{}: duplicate identifier, skipped", self.nodes[index].url);
+                            excluded.insert(index);
+                            continue;
+                        }
+                        signers.insert(response.0, index);
+                        commitments.insert(response.0, response.1);
+                    }
+                    // Enough signers; its nonces expire on the node unused
+                    Ok(_) => {}
+                    Err(error) => {
+                        warn!("{}: commit: {error}", self.nodes[index].url);
+                        excluded.insert(index);
+                    }
+                }
+            }
+            if signers.len() < usize::from(needed) {
+                return Err(Error::NotEnoughSigners {
+                    needed,
+                    available: signers.len(),
+                });
+            }
+
+            let package = SigningPackage::new(commitments, message);
+            let request = SignRequest {
+                session,
+                signing_package: package.clone(),
+            };
+            let responses = join_all(signers.iter().map(|(&identifier, &index)| {
+                let request = &request;
+                async move {
+                    let response = self
+                        .call::<_, SignResponse>(index, "v1/sign", request)
+                        .await;
+                    (identifier, index, response)
+                }
+            }))
+            .await;
+            let mut shares = BTreeMap::new();
+            for (identifier, index, response) in responses {
+                match response {
+                    Ok(response) if response.identifier == identifier => {
+                        shares.insert(identifier, response.share);
+                    }
+                    Ok(_) => {
+                        warn!("{}: sign: wrong identifier", self.nodes[index].url);
+                        excluded.insert(index);
+                    }
+                    Err(error) => {
+                        warn!("{}: sign: {error}", self.nodes[index].url);
+                        excluded.insert(index);
+                    }
+                }
+            }
+            if shares.len() < usize::from(needed) {
+                continue;
+            }
+
+            match frost_ed25519::aggregate(&package, &shares, &self.group.public_key_package) {
+                Ok(signature) => {
+                    return signature.serialize()?.try_into().map_err(|_| {
+                        Error::Config("aggregate signature is not 64 bytes".to_string())
+                    })
+                }
+                Err(error) => {
+                    let culprit = error.culprit().ok_or(Error::Frost(error))?;
+                    warn!(
+                        "{}: sent an invalid signature share",
+                        self.nodes[signers[&culprit]].url
+                    );
+                    excluded.insert(signers[&culprit]);
+                }
+            }
+        }
+    }
+
+    /// A commit response from a node whose identifier is one of the group's
+    fn check(
+        &self,
+        index: usize,
+        response: CommitResponse,
+    ) -> Result<(Identifier, frost_ed25519::round1::SigningCommitments)> {
+        if !self
+            .group
+            .public_key_package
+            .verifying_shares()
+            .contains_key(&response.identifier)
+        {
+            return Err(Error::Node(format!(
+                "{} is not a signer of the group",
+                self.nodes[index].url
+            )));
+        }
+        Ok((response.identifier, response.commitments))
+    }
+
+    async fn call<B: Serialize, T: DeserializeOwned>(
+        &self,
+        index: usize,
+        path: &str,
+        body: &B,
+    ) -> Result<T> {
+        let node = &self.nodes[index];
+        let response = self
+            .http
+            .post(format!("{}/{path}", node.url.trim_end_matches('/')))
+            .bearer_auth(node.token.as_str())
+            .json(body)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            return Err(Error::Node(format!("{status}: {body}")));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+#[async_trait]
+impl RemoteSigner for Coordinator {
+    fn public_key(&self) -> [u8; 32] {
+        self.public_key
+    }
+
+    async fn sign(&self, message: &[u8]) -> unite_core::Result<[u8; 64]> {
+        self.sign_message(message)
+            .await
+            .map_err(|error| unite_core::Error::Signing(error.to_string()))
+    }
+}
+
+fn session_id() -> String {
+    let mut id = [0; 16];
+    rand::thread_rng().fill_bytes(&mut id);
+    hex::encode(id)
+}
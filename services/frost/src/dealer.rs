@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+
+use curve25519_dalek::Scalar;
+use frost_ed25519::keys::{self, IdentifierList, KeyPackage};
+use frost_ed25519::{Identifier, SigningKey};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha512};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::group::Group;
+use crate::Result;
+
+/// A freshly split key: the public group and one key share per signer
+pub struct Dealt {
+    pub group: Group,
+    pub shares: BTreeMap<Identifier, KeyPackage>,
+}
+
+/// Split a key into `signers` shares of which any `threshold` can sign, with a trusted dealer
+///
+/// With `seed`, an existing Ed25519 key is split, so the group signs for the account it
+/// already controls; without, a fresh key is generated. The dealer sees the whole key, so
+/// deal on an offline machine and hand each share to its node.
+pub fn deal(threshold: u16, signers: u16, seed: Option<&[u8; 32]>) -> Result<Dealt> {
+    let (shares, public_key_package) = match seed {
+        Some(seed) => keys::split(
+            &signing_key(seed)?,
+            signers,
+            threshold,
+            IdentifierList::Default,
+            &mut OsRng,
+        )?,
+        None => keys::generate_with_dealer(signers, threshold, IdentifierList::Default, OsRng)?,
+    };
+    let shares = shares
+        .into_iter()
+        .map(|(identifier, share)| Ok((identifier, KeyPackage::try_from(share)?)))
+        .collect::<Result<_>>()?;
+    Ok(Dealt {
+        group: Group {
+            threshold,
+            public_key_package,
+        },
+        shares,
+    })
+}
+
+/// The scalar an Ed25519 seed signs with (RFC 8032 §5.1.5): the clamped first half of its
+/// SHA-512, reduced into the scalar field
+fn signing_key(seed: &[u8; 32]) -> Result<SigningKey> {
+    let mut digest = Sha512::digest(seed);
+    let mut scalar = Zeroizing::new([0; 32]);
+    scalar.copy_from_slice(&digest[..32]);
+    digest.zeroize();
+    scalar[0] &= 248;
+    scalar[31] &= 127;
+    scalar[31] |= 64;
+    Ok(SigningKey::from_scalar(Scalar::from_bytes_mod_order(
+        *scalar,
+    ))?)
+}
@@ -0,0 +1,55 @@
+use std::path::Path;
+
+use frost_ed25519::keys::PublicKeyPackage;
+use serde::{Deserialize, Serialize};
+
+use crate::{Error, Result};
+
+/// Public half of a threshold key: the group key, every signer's verifying share and how many
+/// signers a signature takes; the coordinator and every node load the same file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Group {
+    pub threshold: u16,
+    pub public_key_package: PublicKeyPackage,
+}
+
+impl Group {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let group: Self = serde_json::from_slice(&std::fs::read(path)?)?;
+        let signers = group.signers();
+        if group.threshold < 2 || usize::from(group.threshold) > signers {
+            return Err(Error::Config(format!(
+                "threshold {} does not fit {signers} signers",
+                group.threshold
+            )));
+        }
+        Ok(group)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn signers(&self) -> usize {
+        self.public_key_package.verifying_shares().len()
+    }
+
+    /// The group's Ed25519 public key, which plain Ed25519 verification accepts its signatures
+    /// under
+    pub fn public_key(&self) -> Result<[u8; 32]> {
+        self.public_key_package
+            .verifying_key()
+            .serialize()?
+            .try_into()
+            .map_err(|_| Error::Config("group key is not 32 bytes".to_string()))
+    }
+
+    /// The group key as a Stellar account (`G...`)
+    pub fn stellar_address(&self) -> Result<String> {
+        Ok(stellar_strkey::ed25519::PublicKey(self.public_key()?)
+            .to_string()
+            .as_str()
+            .to_string())
+    }
+}
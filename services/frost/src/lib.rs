@@ -0,0 +1,49 @@
+// FROST Threshold Signing
+// Splits a Stellar signing key across signer nodes so no single machine holds it. A trusted
+// dealer splits a new or existing Ed25519 key into `n` shares with a threshold `t`; each
+// share lives on its own signer node, which answers the two FROST rounds (nonce commitment,
+// then signature share) over HTTP. The coordinator runs inside the relayer or resolver,
+// collects shares from any `t` nodes and aggregates them into an ordinary Ed25519 signature
+// under the group key, so the chain sees a normal single-key account. FROST signatures are
+// Schnorr signatures, so this covers Ed25519 chains such as Stellar, not ECDSA ones.
+
+pub mod config;
+pub mod coordinator;
+pub mod dealer;
+pub mod group;
+pub mod node;
+pub mod protocol;
+
+pub use config::{CoordinatorConfig, NodeConfig, NodeEndpoint};
+pub use coordinator::{Coordinator, Node};
+pub use dealer::{deal, Dealt};
+pub use group::Group;
+pub use node::SignerNode;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error("frost: {0}")]
+    Frost(#[from] frost_ed25519::Error),
+    #[error("signer node: {0}")]
+    Node(String),
+    #[error("session {0}")]
+    Session(String),
+    #[error("missing or wrong bearer token")]
+    Unauthorized,
+    #[error("{needed} signers needed, {available} available")]
+    NotEnoughSigners { needed: u16, available: usize },
+    #[error(transparent)]
+    Secrets(#[from] unite_secrets::Error),
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,116 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use clap::{Parser, Subcommand};
+use log::info;
+use tokio::net::TcpListener;
+use unite_frost::{node, CoordinatorConfig, Error, NodeConfig};
+
+/// Split Stellar keys into FROST shares, run signer nodes and test signing
+#[derive(Debug, Parser)]
+#[command(name = "unite-frost", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Split a key into shares, writing `group.json` and one `share-N.json` per signer to `out`
+    Deal {
+        #[arg(long, short)]
+        threshold: u16,
+        #[arg(long, short)]
+        signers: u16,
+        #[arg(long, short)]
+        out: PathBuf,
+        /// Variable holding an existing Stellar secret key (`S...`) to split; a new key is
+        /// generated without it
+        #[arg(long)]
+        secret_key_env: Option<String>,
+    },
+    /// Run a signer node
+    Node {
+        #[arg(default_value = "frost-node.toml")]
+        config: PathBuf,
+    },
+    /// Sign a hex message through a group's nodes and print the signature
+    Sign {
+        /// Coordinator config: the group file and the nodes
+        #[arg(long, short)]
+        config: PathBuf,
+        message: String,
+    },
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> unite_frost::Result<()> {
+    match cli.command {
+        Command::Deal {
+            threshold,
+            signers,
+            out,
+            secret_key_env,
+        } => {
+            let seed = match secret_key_env {
+                Some(name) => {
+                    let secret = std::env::var(&name)
+                        .map_err(|_| Error::Config(format!("{name} is not set")))?;
+                    let key = stellar_strkey::ed25519::PrivateKey::from_string(&secret)
+                        .map_err(|_| Error::Config(format!("{name} is not a Stellar secret")))?;
+                    Some(zeroize::Zeroizing::new(key.0))
+                }
+                None => None,
+            };
+            let dealt = unite_frost::deal(threshold, signers, seed.as_deref())?;
+            std::fs::create_dir_all(&out)?;
+            dealt.group.save(out.join("group.json"))?;
+            for (index, share) in dealt.shares.values().enumerate() {
+                std::fs::write(
+                    out.join(format!("share-{}.json", index + 1)),
+                    zeroize::Zeroizing::new(serde_json::to_vec_pretty(share)?),
+                )?;
+            }
+            println!("{}", dealt.group.stellar_address()?);
+            Ok(())
+        }
+        Command::Node { config } => {
+            let config = NodeConfig::load(&config)?;
+            let node = Arc::new(config.node().await?);
+            let info = node.info()?;
+            info!(
+                "signer {} of {} for {}",
+                serde_json::to_string(&info.identifier)?,
+                info.public_key,
+                info.threshold
+            );
+            let listener = TcpListener::bind(config.listen).await?;
+            info!("listening on {}", config.listen);
+            axum::serve(listener, node::router(node))
+                .with_graceful_shutdown(async {
+                    let _ = tokio::signal::ctrl_c().await;
+                })
+                .await?;
+            Ok(())
+        }
+        Command::Sign { config, message } => {
+            let message = hex::decode(&message)
+                .map_err(|_| Error::Config("message is not hex".to_string()))?;
+            let coordinator = CoordinatorConfig::load(&config)?.connect()?;
+            println!("{}", hex::encode(coordinator.sign_message(&message).await?));
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use frost_ed25519::keys::KeyPackage;
+use frost_ed25519::round1::{self, SigningNonces};
+use frost_ed25519::round2;
+use rand::rngs::OsRng;
+use zeroize::Zeroizing;
+
+use crate::group::Group;
+use crate::protocol::{CommitRequest, CommitResponse, NodeInfo, SignRequest, SignResponse};
+use crate::{Error, Result};
+
+/// Open sessions a node keeps nonces for; commitments beyond it are refused until sessions
+/// are used or expire
+const MAX_SESSIONS: usize = 1024;
+
+/// One signer of a group, holding its key share and answering the coordinator's two rounds
+///
+/// Each commitment's nonces are kept for one signing package only and dropped after
+/// `session_ttl` if it never comes, since signing twice with the same nonces leaks the share.
+pub struct SignerNode {
+    group: Group,
+    key_package: KeyPackage,
+    token: Zeroizing<String>,
+    session_ttl: Duration,
+    sessions: Mutex<HashMap<String, (SigningNonces, Instant)>>,
+}
+
+impl SignerNode {
+    /// `token` is the bearer token the coordinator authenticates with
+    pub fn new(
+        group: Group,
+        key_package: KeyPackage,
+        token: Zeroizing<String>,
+        session_ttl: Duration,
+    ) -> Result<Self> {
+        let identifier = key_package.identifier();
+        if key_package.verifying_key() != group.public_key_package.verifying_key()
+            || group.public_key_package.verifying_shares().get(identifier)
+                != Some(key_package.verifying_share())
+        {
+            return Err(Error::Config(
+                "key share does not belong to the group".to_string(),
+            ));
+        }
+        Ok(Self {
+            group,
+            key_package,
+            token,
+            session_ttl,
+            sessions: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn info(&self) -> Result<NodeInfo> {
+        Ok(NodeInfo {
+            identifier: *self.key_package.identifier(),
+            public_key: hex::encode(self.group.public_key()?),
+            threshold: self.group.threshold,
+        })
+    }
+
+    /// Round one: fresh nonces for `session`, of which only the commitments leave the node
+    pub fn commit(&self, request: &CommitRequest) -> Result<CommitResponse> {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions.retain(|_, (_, at)| at.elapsed() < self.session_ttl);
+        if sessions.contains_key(&request.session) {
+            return Err(Error::Session(format!(
+                "{} is already open",
+                request.session
+            )));
+        }
+        if sessions.len() >= MAX_SESSIONS {
+            return Err(Error::Session(format!("limit of {MAX_SESSIONS} reached")));
+        }
+        let (nonces, commitments) = round1::commit(self.key_package.signing_share(), &mut OsRng);
+        sessions.insert(request.session.clone(), (nonces, Instant::now()));
+        Ok(CommitResponse {
+            identifier: *self.key_package.identifier(),
+            commitments,
+        })
+    }
+
+    /// Round two: the share of the signature over the package's message; the session's nonces
+    /// are spent whether or not signing succeeds
+    pub fn sign(&self, request: &SignRequest) -> Result<SignResponse> {
+        let (nonces, at) = self
+            .sessions
+            .lock()
+            .unwrap()
+            .remove(&request.session)
+            .ok_or_else(|| Error::Session(format!("{} is not open", request.session)))?;
+        if at.elapsed() >= self.session_ttl {
+            return Err(Error::Session(format!("{} expired", request.session)));
+        }
+        let share = round2::sign(&request.signing_package, &nonces, &self.key_package)?;
+        Ok(SignResponse {
+            identifier: *self.key_package.identifier(),
+            share,
+        })
+    }
+
+    fn authorize(&self, headers: &HeaderMap) -> Result<()> {
+        let token = headers
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .unwrap_or_default();
+        // Compared in constant time, so response times do not leak the token
+        let matches = token.len() == self.token.len()
+            && token
+                .bytes()
+                .zip(self.token.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0;
+        if matches {
+            Ok(())
+        } else {
+            Err(Error::Unauthorized)
+        }
+    }
+}
+
+/// `POST /v1/commit`, `POST /v1/sign` and `GET /v1/info`, each requiring the bearer token
+pub fn router(node: Arc<SignerNode>) -> Router {
+    Router::new()
+        .route("/v1/info", get(info))
+        .route("/v1/commit", post(commit))
+        .route("/v1/sign", post(sign))
+        .with_state(node)
+}
+
+async fn info(State(node): State<Arc<SignerNode>>, headers: HeaderMap) -> Result<Json<NodeInfo>> {
+    node.authorize(&headers)?;
+    Ok(Json(node.info()?))
+}
+
+async fn commit(
+    State(node): State<Arc<SignerNode>>,
+    headers: HeaderMap,
+    Json(request): Json<CommitRequest>,
+) -> Result<Json<CommitResponse>> {
+    node.authorize(&headers)?;
+    Ok(Json(node.commit(&request)?))
+}
+
+async fn sign(
+    State(node): State<Arc<SignerNode>>,
+    headers: HeaderMap,
+    Json(request): Json<SignRequest>,
+) -> Result<Json<SignResponse>> {
+    node.authorize(&headers)?;
+    Ok(Json(node.sign(&request)?))
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Error::Unauthorized => StatusCode::UNAUTHORIZED,
+            Error::Session(_) => StatusCode::CONFLICT,
+            Error::Frost(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,42 @@
+// Messages between the coordinator and signer nodes, as JSON over HTTP with a bearer token:
+// `POST /v1/commit` opens a signing session and returns the node's nonce commitments,
+// `POST /v1/sign` returns its signature share for the session's signing package, and
+// `GET /v1/info` describes the node.
+
+use frost_ed25519::round1::SigningCommitments;
+use frost_ed25519::round2::SignatureShare;
+use frost_ed25519::{Identifier, SigningPackage};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitRequest {
+    /// Random ID the coordinator picks for this signature
+    pub session: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommitResponse {
+    pub identifier: Identifier,
+    pub commitments: SigningCommitments,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignRequest {
+    pub session: String,
+    /// The message and the commitments of every signer taking part
+    pub signing_package: SigningPackage,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignResponse {
+    pub identifier: Identifier,
+    pub share: SignatureShare,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NodeInfo {
+    pub identifier: Identifier,
+    /// Hex group public key
+    pub public_key: String,
+    pub threshold: u16,
+}
@@ -0,0 +1,8 @@
+// Compiles the protobuf schema with protox, so building needs no `protoc`
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let proto = "proto/unite/resolver/v1/resolver.proto";
+    println!("cargo:rerun-if-changed={proto}");
+    let descriptors = protox::compile([proto], ["proto"])?;
+    tonic_prost_build::configure().compile_fds(descriptors)?;
+    Ok(())
+}
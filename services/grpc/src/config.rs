@@ -0,0 +1,77 @@
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::Path;
+
+use serde::Deserialize;
+use unite_config::{Problems, Validate};
+use unite_matcher::ResolverConfig;
+
+use crate::{Error, Result};
+
+/// gRPC server settings, read from a TOML file with its environment's overlay and
+/// `UNITE_GRPC__*` overrides; the database URL is read from the environment
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    /// URL returning the open orders, the same feed `unite-resolver` polls
+    pub feed_url: String,
+    #[serde(default = "default_poll_secs")]
+    pub poll_secs: u64,
+    /// Variable holding the URL of the indexer database
+    #[serde(default = "default_database_url_env")]
+    pub database_url_env: String,
+    /// Address to serve `/healthz` and `/readyz` on; off without it
+    pub health_listen: Option<SocketAddr>,
+    /// Resolvers allowed to quote, by the key their quotes are signed with
+    pub resolvers: Vec<ResolverConfig>,
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(unite_config::load(path.as_ref(), "UNITE_GRPC")?)
+    }
+
+    pub fn database_url(&self) -> Result<String> {
+        std::env::var(&self.database_url_env)
+            .map_err(|_| Error::Config(format!("{} is not set", self.database_url_env)))
+    }
+
+    /// Resolvers' public keys by name
+    pub fn resolver_keys(&self) -> HashMap<String, [u8; 32]> {
+        self.resolvers
+            .iter()
+            .map(|resolver| (resolver.name.clone(), resolver.public_key))
+            .collect()
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.poll_secs > 0, || {
+            "poll_secs must be positive".to_string()
+        });
+        problems.check(
+            self.feed_url.starts_with("http://") || self.feed_url.starts_with("https://"),
+            || format!("feed_url {} is not an http/https URL", self.feed_url),
+        );
+        let mut names = HashSet::new();
+        for resolver in &self.resolvers {
+            problems.check(names.insert(&resolver.name), || {
+                format!("resolver {} is listed twice", resolver.name)
+            });
+        }
+    }
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 50051))
+}
+
+fn default_poll_secs() -> u64 {
+    5
+}
+
+fn default_database_url_env() -> String {
+    "DATABASE_URL".to_string()
+}
@@ -0,0 +1,66 @@
+use unite_matcher::SignedBid;
+use unite_resolver::{Auction, AuctionPoint, Order};
+
+use crate::proto;
+
+impl From<&Order> for proto::Order {
+    fn from(order: &Order) -> Self {
+        Self {
+            order_id: order.order_id.clone(),
+            hashlock: order.hashlock.to_vec(),
+            source: Some(proto::SourceEscrow {
+                chain: order.source.chain.clone(),
+                escrow_id: order.source.escrow_id.to_vec(),
+                token: order.source.token.clone(),
+                amount: order.source.amount.to_string(),
+                timelock: order.source.timelock,
+            }),
+            destination: Some(proto::Destination {
+                chain: order.destination.chain.clone(),
+                token: order.destination.token.clone(),
+                receiver: order.destination.receiver.clone(),
+            }),
+            taking_amount: order.taking_amount.to_string(),
+            auction: order.auction.as_ref().map(proto::Auction::from),
+        }
+    }
+}
+
+impl From<&Auction> for proto::Auction {
+    fn from(auction: &Auction) -> Self {
+        Self {
+            start_time: auction.start_time,
+            duration: auction.duration,
+            initial_rate_bump: auction.initial_rate_bump,
+            points: auction
+                .points
+                .iter()
+                .map(proto::AuctionPoint::from)
+                .collect(),
+        }
+    }
+}
+
+impl From<&AuctionPoint> for proto::AuctionPoint {
+    fn from(point: &AuctionPoint) -> Self {
+        Self {
+            delay: point.delay,
+            rate_bump: point.rate_bump,
+        }
+    }
+}
+
+impl From<&SignedBid> for proto::SubmitQuoteRequest {
+    fn from(signed: &SignedBid) -> Self {
+        let bid = &signed.bid;
+        Self {
+            order_id: bid.order_id.clone(),
+            resolver: bid.resolver.clone(),
+            amount: bid.amount.to_string(),
+            valid_until: bid.valid_until,
+            receiver: bid.receiver.clone(),
+            sender: bid.sender.clone(),
+            signature: signed.signature.to_vec(),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// Resolver gRPC Server
+// For resolvers that integrate over gRPC rather than REST; the schema ships in
+// `proto/unite/resolver/v1/resolver.proto`. Orders are mirrored from the same order feed the
+// resolver bot polls and streamed to subscribers as they open, change and close, each with
+// what its Dutch auction requires at that moment. A quote is the matcher's bid for the whole
+// order, signed by a resolver registered in the config; those at or above that amount are
+// kept per order until they lapse or the order closes, and the best that meets the auction
+// wins the order when it is selected. Settlement is confirmed from the escrows
+// `unite-indexer` saw settle on chain. `/readyz`, served over HTTP beside the gRPC
+// port, probes the database and the order feed.
+
+pub mod config;
+pub mod convert;
+pub mod orders;
+pub mod quotes;
+pub mod service;
+
+/// Types and stubs generated from `resolver.proto`
+pub mod proto {
+    tonic::include_proto!("unite.resolver.v1");
+}
+
+pub use config::Config;
+pub use orders::{Change, OrderBook};
+pub use proto::resolver_service_client::ResolverServiceClient;
+pub use proto::resolver_service_server::ResolverServiceServer;
+pub use quotes::{Quote, QuoteBook};
+pub use service::Gateway;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error(transparent)]
+    Feed(#[from] unite_resolver::Error),
+    #[error(transparent)]
+    Database(#[from] unite_api::Error),
+    #[error(transparent)]
+    Transport(#[from] tonic::transport::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Load(#[from] unite_config::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,59 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{error, info};
+use tonic::transport::Server;
+use unite_api::Database;
+use unite_grpc::{Config, Gateway, OrderBook, QuoteBook, ResolverServiceServer};
+use unite_resolver::OrderFeed;
+use unite_telemetry::Health;
+
+/// `unite-grpc [config.toml]`, logging through `RUST_LOG`
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "grpc.toml".to_string());
+    let config = Config::load(&path)?;
+
+    let db = Database::connect(&config.database_url()?).await?;
+    let orders = OrderBook::new(OrderFeed::new(config.feed_url.as_str()));
+    let quotes = Arc::new(QuoteBook::new());
+    let (_, changes) = orders.subscribe();
+    tokio::spawn({
+        let quotes = quotes.clone();
+        async move { quotes.follow(changes).await }
+    });
+    tokio::spawn({
+        let orders = orders.clone();
+        let interval = Duration::from_secs(config.poll_secs);
+        async move { orders.run(interval).await }
+    });
+
+    if let Some(listen) = config.health_listen {
+        let health = Health::default()
+            .with(Arc::new(db.clone()))
+            .with(Arc::new(OrderFeed::new(config.feed_url.as_str())));
+        tokio::spawn(async move {
+            if let Err(error) = unite_telemetry::health::serve(listen, health).await {
+                error!("health endpoint: {error}");
+            }
+        });
+        info!("health on http://{listen}/readyz");
+    }
+
+    info!("listening on {}", config.listen);
+    Server::builder()
+        .add_service(ResolverServiceServer::new(Gateway::new(
+            orders,
+            quotes,
+            db,
+            config.resolver_keys(),
+        )))
+        .serve_with_shutdown(config.listen, async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
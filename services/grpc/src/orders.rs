@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use log::{info, warn};
+use tokio::sync::broadcast;
+use unite_resolver::{Order, OrderFeed};
+
+use crate::Result;
+
+/// Buffered changes per subscriber before it is dropped as lagging
+const CHANGES: usize = 1_024;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Change {
+    /// A new order, or new terms for a listed one
+    Opened(Order),
+    /// The feed stopped listing the order
+    Closed(Order),
+}
+
+/// Open orders mirrored from the order feed, with every change broadcast to subscribers
+pub struct OrderBook {
+    feed: OrderFeed,
+    orders: RwLock<HashMap<String, Order>>,
+    changes: broadcast::Sender<Change>,
+}
+
+impl OrderBook {
+    pub fn new(feed: OrderFeed) -> Arc<Self> {
+        Arc::new(Self {
+            feed,
+            orders: RwLock::new(HashMap::new()),
+            changes: broadcast::channel(CHANGES).0,
+        })
+    }
+
+    pub fn get(&self, order_id: &str) -> Option<Order> {
+        self.orders.read().unwrap().get(order_id).cloned()
+    }
+
+    /// The open orders and a subscription to the changes after them, taken together so no
+    /// change falls in between
+    pub fn subscribe(&self) -> (Vec<Order>, broadcast::Receiver<Change>) {
+        let orders = self.orders.read().unwrap();
+        (orders.values().cloned().collect(), self.changes.subscribe())
+    }
+
+    /// Poll the feed once and broadcast what changed, returning how many orders did
+    pub async fn refresh(&self) -> Result<usize> {
+        let listed = self.feed.orders().await?;
+        let mut orders = self.orders.write().unwrap();
+        let mut previous = std::mem::take(&mut *orders);
+        let mut changes = Vec::new();
+        for order in listed {
+            if previous.remove(&order.order_id).as_ref() != Some(&order) {
+                changes.push(Change::Opened(order.clone()));
+            }
+            orders.insert(order.order_id.clone(), order);
+        }
+        changes.extend(previous.into_values().map(Change::Closed));
+        for change in &changes {
+            // Sending fails only without subscribers
+            let _ = self.changes.send(change.clone());
+        }
+        Ok(changes.len())
+    }
+
+    /// Poll the feed every `interval` until the task is dropped
+    pub async fn run(&self, interval: Duration) {
+        loop {
+            match self.refresh().await {
+                Ok(0) => {}
+                Ok(changed) => info!("order feed: {changed} orders changed"),
+                Err(error) => warn!("order feed: {error}"),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    }
+}
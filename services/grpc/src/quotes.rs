@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio::sync::broadcast::{self, error::RecvError};
+use unite_matcher::SignedBid;
+
+use crate::orders::Change;
+
+/// A resolver's signed offer for an order's destination amount, the matcher's bid for the
+/// whole order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Quote {
+    pub signed: SignedBid,
+    pub received_at: u64,
+}
+
+/// Live quotes per order; a resolver's new quote for an order replaces its previous one when
+/// it is valid for longer, so a replayed older quote cannot displace it
+#[derive(Debug, Default)]
+pub struct QuoteBook {
+    quotes: Mutex<HashMap<String, Vec<Quote>>>,
+}
+
+impl QuoteBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Hold a checked quote, returning whether it was taken
+    pub fn submit(&self, quote: Quote) -> bool {
+        let bid = &quote.signed.bid;
+        let mut quotes = self.quotes.lock().unwrap();
+        let order = quotes.entry(bid.order_id.clone()).or_default();
+        if order.iter().any(|held| {
+            held.signed.bid.resolver == bid.resolver
+                && held.signed.bid.valid_until >= bid.valid_until
+        }) {
+            return false;
+        }
+        order.retain(|held| {
+            held.signed.bid.resolver != bid.resolver
+                && held.signed.bid.valid_until > quote.received_at
+        });
+        order.push(quote);
+        true
+    }
+
+    /// Quotes for an order still valid at `now`, highest amount first
+    pub fn quotes(&self, order_id: &str, now: u64) -> Vec<Quote> {
+        let mut quotes: Vec<_> = self
+            .quotes
+            .lock()
+            .unwrap()
+            .get(order_id)
+            .into_iter()
+            .flatten()
+            .filter(|quote| quote.signed.bid.valid_until > now)
+            .cloned()
+            .collect();
+        quotes.sort_by(|a, b| {
+            b.signed
+                .bid
+                .amount
+                .cmp(&a.signed.bid.amount)
+                .then(a.received_at.cmp(&b.received_at))
+        });
+        quotes
+    }
+
+    /// The quote that wins an order at `now`, when the auction requires `required`: the
+    /// highest one that meets it, the earlier on a tie, as the matcher picks bids
+    pub fn winner(&self, order_id: &str, required: u128, now: u64) -> Option<Quote> {
+        self.quotes(order_id, now)
+            .into_iter()
+            .next()
+            .filter(|quote| quote.signed.bid.amount >= required)
+    }
+
+    /// Drop the quotes of an order that closed
+    pub fn close(&self, order_id: &str) {
+        self.quotes.lock().unwrap().remove(order_id);
+    }
+
+    /// Drop the quotes of orders as they close, until the order book stops
+    pub async fn follow(&self, mut changes: broadcast::Receiver<Change>) {
+        loop {
+            match changes.recv().await {
+                Ok(Change::Closed(order)) => self.close(&order.order_id),
+                Ok(Change::Opened(_)) | Err(RecvError::Lagged(_)) => {}
+                Err(RecvError::Closed) => return,
+            }
+        }
+    }
+}
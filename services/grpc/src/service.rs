@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::{Stream, StreamExt};
+use tonic::{Request, Response, Status};
+use unite_api::Database;
+use unite_matcher::{order, Bid, SignedBid};
+use unite_resolver::Order;
+
+use crate::orders::{Change, OrderBook};
+use crate::proto::resolver_service_server::ResolverService;
+use crate::proto::{
+    self, ConfirmSettlementRequest, ConfirmSettlementResponse, EscrowSettlement, OrderUpdate,
+    SelectWinnerRequest, SelectWinnerResponse, StreamOrdersRequest, SubmitQuoteRequest,
+    SubmitQuoteResponse,
+};
+use crate::quotes::{Quote, QuoteBook};
+
+type OrderStream = Pin<Box<dyn Stream<Item = Result<OrderUpdate, Status>> + Send>>;
+
+/// `ResolverService` over the mirrored order feed, the quote book and the indexer database,
+/// taking quotes signed by the registered resolvers' keys
+#[derive(Clone)]
+pub struct Gateway {
+    orders: Arc<OrderBook>,
+    quotes: Arc<QuoteBook>,
+    db: Database,
+    resolvers: Arc<HashMap<String, [u8; 32]>>,
+}
+
+impl Gateway {
+    pub fn new(
+        orders: Arc<OrderBook>,
+        quotes: Arc<QuoteBook>,
+        db: Database,
+        resolvers: HashMap<String, [u8; 32]>,
+    ) -> Self {
+        Self {
+            orders,
+            quotes,
+            db,
+            resolvers: Arc::new(resolvers),
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ResolverService for Gateway {
+    type StreamOrdersStream = OrderStream;
+
+    /// A subscriber that falls more than the change buffer behind gets `ABORTED` and should
+    /// resubscribe, which starts over with every open order
+    async fn stream_orders(
+        &self,
+        request: Request<StreamOrdersRequest>,
+    ) -> Result<Response<OrderStream>, Status> {
+        let request = request.into_inner();
+        let wanted = move |order: &Order| {
+            (request.source_chains.is_empty()
+                || request.source_chains.contains(&order.source.chain))
+                && (request.destination_chains.is_empty()
+                    || request
+                        .destination_chains
+                        .contains(&order.destination.chain))
+        };
+        let (open, changes) = self.orders.subscribe();
+        let open = tokio_stream::iter(open.into_iter().map(Change::Opened).map(Ok));
+        let changes = BroadcastStream::new(changes).map(|change| {
+            change.map_err(|_| Status::aborted("fell behind the order feed; resubscribe"))
+        });
+        let stream = open.chain(changes).filter_map(move |change| match change {
+            Ok(Change::Opened(order)) => wanted(&order).then(|| Ok(update(&order, false))),
+            Ok(Change::Closed(order)) => wanted(&order).then(|| Ok(update(&order, true))),
+            Err(status) => Some(Err(status)),
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+
+    async fn submit_quote(
+        &self,
+        request: Request<SubmitQuoteRequest>,
+    ) -> Result<Response<SubmitQuoteResponse>, Status> {
+        let request = request.into_inner();
+        let amount: u128 = request.amount.parse().map_err(|_| {
+            Status::invalid_argument(format!(
+                "amount {} is not a decimal integer",
+                request.amount
+            ))
+        })?;
+        let signature: [u8; 64] = request
+            .signature
+            .as_slice()
+            .try_into()
+            .map_err(|_| Status::invalid_argument("signature is not 64 bytes"))?;
+        let key = self.resolvers.get(&request.resolver).ok_or_else(|| {
+            Status::unauthenticated(format!("resolver {} is not registered", request.resolver))
+        })?;
+        let signed = SignedBid {
+            bid: Bid {
+                order_id: request.order_id,
+                resolver: request.resolver,
+                fill: None,
+                amount,
+                receiver: request.receiver,
+                sender: request.sender,
+                valid_until: request.valid_until,
+            },
+            signature,
+        };
+        order::verify(key, &signed.bid.digest(), &signed.signature)
+            .map_err(|error| Status::unauthenticated(error.to_string()))?;
+        let order = self.orders.get(&signed.bid.order_id).ok_or_else(|| {
+            Status::not_found(format!("order {} is not open", signed.bid.order_id))
+        })?;
+        let now = now();
+        if signed.bid.valid_until <= now {
+            return Err(Status::invalid_argument("quote already lapsed"));
+        }
+        let required = order.required_amount(now);
+        let reason = if amount < required {
+            format!("the auction requires at least {required}")
+        } else if !self.quotes.submit(Quote {
+            signed,
+            received_at: now,
+        }) {
+            "a quote of this resolver valid as long or longer is held".to_string()
+        } else {
+            String::new()
+        };
+        Ok(Response::new(SubmitQuoteResponse {
+            accepted: reason.is_empty(),
+            reason,
+            required_amount: required.to_string(),
+        }))
+    }
+
+    async fn select_winner(
+        &self,
+        request: Request<SelectWinnerRequest>,
+    ) -> Result<Response<SelectWinnerResponse>, Status> {
+        let order_id = request.into_inner().order_id;
+        let order = self
+            .orders
+            .get(&order_id)
+            .ok_or_else(|| Status::not_found(format!("order {order_id} is not open")))?;
+        let now = now();
+        let required = order.required_amount(now);
+        let winner = self
+            .quotes
+            .winner(&order_id, required, now)
+            .ok_or_else(|| {
+                Status::failed_precondition(format!("no live quote meets the required {required}"))
+            })?;
+        Ok(Response::new(SelectWinnerResponse {
+            quote: Some(proto::SubmitQuoteRequest::from(&winner.signed)),
+            amount: required.to_string(),
+        }))
+    }
+
+    async fn confirm_settlement(
+        &self,
+        request: Request<ConfirmSettlementRequest>,
+    ) -> Result<Response<ConfirmSettlementResponse>, Status> {
+        let order_id = request.into_inner().order_id;
+        let escrows = self
+            .db
+            .escrows_by_order(&order_id)
+            .await
+            .map_err(internal)?;
+        if escrows.is_empty() {
+            return Err(Status::not_found(format!(
+                "no escrows indexed for order {order_id}"
+            )));
+        }
+        let mut settlements = Vec::with_capacity(escrows.len());
+        let mut secret = None;
+        for escrow in escrows {
+            let detail = self.db.detail(escrow).await.map_err(internal)?;
+            if secret.is_none() {
+                secret = detail
+                    .secrets
+                    .first()
+                    .map(|revealed| revealed.secret.clone());
+            }
+            let settlement = detail.settlement;
+            settlements.push(EscrowSettlement {
+                chain: detail.escrow.chain,
+                escrow_id: detail.escrow.escrow_id,
+                status: settlement
+                    .as_ref()
+                    .map_or_else(|| "open".to_string(), |settled| settled.status.clone()),
+                amount: detail.escrow.amount,
+                settled_by: settlement
+                    .as_ref()
+                    .and_then(|settled| settled.settled_by.clone()),
+                tx: settlement.as_ref().map(|settled| settled.tx.clone()),
+                height: settlement.as_ref().map(|settled| settled.height),
+            });
+        }
+        Ok(Response::new(ConfirmSettlementResponse {
+            order_id,
+            settled: settlements.iter().all(|escrow| escrow.status != "open"),
+            escrows: settlements,
+            secret,
+        }))
+    }
+}
+
+fn update(order: &Order, closed: bool) -> OrderUpdate {
+    OrderUpdate {
+        order: Some(proto::Order::from(order)),
+        closed,
+        required_amount: order.required_amount(now()).to_string(),
+    }
+}
+
+fn internal(error: unite_api::Error) -> Status {
+    Status::internal(error.to_string())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,79 @@
+-- Indexer tables as they stood before versioned migrations; every statement is idempotent, so
+-- databases set up from the old schema.sql adopt this migration without changes
+
+CREATE TABLE IF NOT EXISTS cursors (
+    source     TEXT PRIMARY KEY,
+    -- Soroban paging cursor, or the next EVM block to scan
+    position   TEXT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS escrows (
+    chain     TEXT NOT NULL,
+    escrow_id BYTEA NOT NULL,
+    order_id  TEXT NOT NULL,
+    sender    TEXT NOT NULL,
+    receiver  TEXT NOT NULL,
+    -- NULL for the chain's native currency
+    token     TEXT,
+    amount    NUMERIC(78, 0) NOT NULL,
+    hashlock  BYTEA NOT NULL,
+    timelock  BIGINT NOT NULL,
+    height    BIGINT NOT NULL,
+    tx        TEXT NOT NULL,
+    PRIMARY KEY (chain, escrow_id)
+);
+CREATE INDEX IF NOT EXISTS escrows_order_id ON escrows (order_id);
+CREATE INDEX IF NOT EXISTS escrows_hashlock ON escrows (hashlock);
+CREATE INDEX IF NOT EXISTS escrows_sender ON escrows (sender);
+CREATE INDEX IF NOT EXISTS escrows_receiver ON escrows (receiver);
+
+-- Withdrawals; partial fills add one row each
+CREATE TABLE IF NOT EXISTS fills (
+    chain         TEXT NOT NULL,
+    event_key     TEXT NOT NULL,
+    escrow_id     BYTEA NOT NULL,
+    taker         TEXT NOT NULL,
+    amount        NUMERIC(78, 0) NOT NULL,
+    filled_amount NUMERIC(78, 0) NOT NULL,
+    height        BIGINT NOT NULL,
+    tx            TEXT NOT NULL,
+    PRIMARY KEY (chain, event_key)
+);
+CREATE INDEX IF NOT EXISTS fills_escrow ON fills (chain, escrow_id);
+
+-- Revealed preimages, keyed by the hash they open
+CREATE TABLE IF NOT EXISTS secrets (
+    chain     TEXT NOT NULL,
+    escrow_id BYTEA NOT NULL,
+    hashlock  BYTEA NOT NULL,
+    secret    BYTEA NOT NULL,
+    height    BIGINT NOT NULL,
+    tx        TEXT NOT NULL,
+    PRIMARY KEY (chain, escrow_id, hashlock)
+);
+CREATE INDEX IF NOT EXISTS secrets_hashlock ON secrets (hashlock);
+
+CREATE TABLE IF NOT EXISTS settlements (
+    chain         TEXT NOT NULL,
+    escrow_id     BYTEA NOT NULL,
+    -- withdrawn, cancelled or resolved
+    status        TEXT NOT NULL,
+    filled_amount NUMERIC(78, 0),
+    settled_by    TEXT,
+    height        BIGINT NOT NULL,
+    tx            TEXT NOT NULL,
+    PRIMARY KEY (chain, escrow_id)
+);
+
+-- Escrow lifecycle in the order it was indexed: created, secret_revealed, and the settlement
+-- status (withdrawn, cancelled or resolved); rows become visible in seq order
+CREATE TABLE IF NOT EXISTS events (
+    seq        BIGSERIAL PRIMARY KEY,
+    chain      TEXT NOT NULL,
+    escrow_id  BYTEA NOT NULL,
+    kind       TEXT NOT NULL,
+    height     BIGINT NOT NULL,
+    tx         TEXT NOT NULL,
+    indexed_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
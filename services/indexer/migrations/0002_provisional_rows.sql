@@ -0,0 +1,19 @@
+-- Rows indexed above their chain's finalized block are provisional: a reorg can still drop them,
+-- so they are deleted and read again when one does, and reach `events` only once final
+ALTER TABLE escrows ADD COLUMN provisional BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE fills ADD COLUMN provisional BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE secrets ADD COLUMN provisional BOOLEAN NOT NULL DEFAULT false;
+ALTER TABLE settlements ADD COLUMN provisional BOOLEAN NOT NULL DEFAULT false;
+CREATE INDEX escrows_provisional ON escrows (chain, height) WHERE provisional;
+CREATE INDEX fills_provisional ON fills (chain, height) WHERE provisional;
+CREATE INDEX secrets_provisional ON secrets (chain, height) WHERE provisional;
+CREATE INDEX settlements_provisional ON settlements (chain, height) WHERE provisional;
+
+-- Blocks batches were read through that are not final yet, checked against the chain before
+-- the next batch to notice reorgs
+CREATE TABLE blocks (
+    source TEXT NOT NULL,
+    height BIGINT NOT NULL,
+    hash   TEXT NOT NULL,
+    PRIMARY KEY (source, height)
+);
@@ -0,0 +1,27 @@
+-- Webhook delivery state, written by `unite-api`'s dispatcher: how far each subscriber has
+-- been sent `events`, the escrows it was told are about to expire, and the deliveries it never
+-- accepted
+CREATE TABLE webhook_cursors (
+    subscriber TEXT PRIMARY KEY,
+    seq        BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE TABLE webhook_expiring (
+    subscriber TEXT NOT NULL,
+    chain      TEXT NOT NULL,
+    escrow_id  BYTEA NOT NULL,
+    PRIMARY KEY (subscriber, chain, escrow_id)
+);
+
+CREATE TABLE webhook_dead_letters (
+    id         BIGSERIAL PRIMARY KEY,
+    subscriber TEXT NOT NULL,
+    delivery   TEXT NOT NULL,
+    kind       TEXT NOT NULL,
+    payload    JSONB NOT NULL,
+    attempts   INTEGER NOT NULL,
+    error      TEXT NOT NULL,
+    failed_at  TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+CREATE INDEX webhook_dead_letters_subscriber ON webhook_dead_letters (subscriber, failed_at);
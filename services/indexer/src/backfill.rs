@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+use crate::db::Database;
+use crate::metrics;
+use crate::source::EventSource;
+use crate::Result;
+
+/// Consecutive failed batches after which a backfill gives up
+const MAX_FAILURES: u32 = 5;
+
+/// Replays a source's history from a past ledger or block through the same normalization and
+/// commits as the live indexer
+///
+/// The source keeps its own cursor, so an interrupted backfill resumes from its last commit
+/// and never moves the live source's. Rows the live indexer already stored are left alone,
+/// and only events it missed are appended. Batches are spaced at least `interval` apart to
+/// stay under the RPC provider's rate limits.
+pub struct Backfill {
+    db: Database,
+    source: Box<dyn EventSource>,
+    target: Option<u64>,
+    interval: Duration,
+}
+
+impl Backfill {
+    pub fn new(db: Database, source: Box<dyn EventSource>) -> Self {
+        Self {
+            db,
+            source,
+            target: None,
+            interval: Duration::ZERO,
+        }
+    }
+
+    /// Last ledger or block to replay; the source's finalized height by default, and never
+    /// past it
+    pub fn with_target(mut self, target: Option<u64>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Most batches read per second, unlimited when not positive
+    pub fn with_rate(mut self, batches_per_sec: f64) -> Self {
+        self.interval = if batches_per_sec > 0.0 {
+            Duration::from_secs_f64(1.0 / batches_per_sec)
+        } else {
+            Duration::ZERO
+        };
+        self
+    }
+
+    /// Replay batches until the target is reached, returning how many records were read
+    ///
+    /// A batch that fails is retried after a doubling delay; `MAX_FAILURES` in a row end the
+    /// backfill with the last error, leaving the cursor at the last commit to resume from.
+    pub async fn run(&self) -> Result<u64> {
+        let name = self.source.name();
+        let finalized = self.source.finalized().await?;
+        let target = self
+            .target
+            .map_or(finalized, |target| target.min(finalized));
+        info!("backfilling {name} up to {target}");
+        let mut start = None;
+        let mut records = 0;
+        let mut failures = 0;
+        loop {
+            let started = Instant::now();
+            match self.step().await {
+                Ok((height, count, caught_up)) => {
+                    failures = 0;
+                    records += count;
+                    let start = *start.get_or_insert(height);
+                    let done = height.saturating_sub(start) as f64;
+                    let total = target.saturating_sub(start).max(1) as f64;
+                    info!(
+                        "at {height} of {target} ({:.1}%), {records} records",
+                        (100.0 * done / total).min(100.0)
+                    );
+                    if caught_up || height >= target {
+                        return Ok(records);
+                    }
+                }
+                Err(error) => {
+                    failures += 1;
+                    metrics::error(name);
+                    if failures >= MAX_FAILURES {
+                        return Err(error);
+                    }
+                    let delay = self.interval.max(Duration::from_secs(1)) * 2u32.pow(failures);
+                    warn!("{error}, retrying in {delay:?}");
+                    tokio::time::sleep(delay).await;
+                    continue;
+                }
+            }
+            tokio::time::sleep(self.interval.saturating_sub(started.elapsed())).await;
+        }
+    }
+
+    /// Read and commit one batch from the stored cursor, returning its height, its record
+    /// count and whether the source had no more events
+    async fn step(&self) -> Result<(u64, u64, bool)> {
+        let name = self.source.name();
+        let started = Instant::now();
+        let position = self.db.cursor(name).await?;
+        let batch = self.source.next_batch(position.as_deref()).await?;
+        self.db
+            .commit(name, self.source.chain(), position.as_deref(), &batch)
+            .await?;
+        metrics::committed(name, &batch, started.elapsed());
+        Ok((batch.height, batch.records.len() as u64, batch.caught_up))
+    }
+}
@@ -0,0 +1,244 @@
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+
+use alloy::primitives::Address;
+use serde::Deserialize;
+use unite_config::{Problems, Validate};
+use unite_telemetry::TracingConfig;
+
+use crate::evm::EvmSource;
+use crate::source::EventSource;
+use crate::stellar::SorobanSource;
+use crate::{Error, Result};
+
+/// Indexer settings, read from a TOML file with its environment's overlay and
+/// `UNITE_INDEXER__*` overrides; the database URL is read from the environment
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    #[serde(default = "default_database_url_env")]
+    pub database_url_env: String,
+    #[serde(default = "default_poll_secs")]
+    pub poll_secs: u64,
+    /// Apply pending migrations at startup; without it the indexer refuses to start until
+    /// `unite-indexer migrate` has run
+    #[serde(default = "default_migrate_on_start")]
+    pub migrate_on_start: bool,
+    /// Address to serve Prometheus metrics on, at `/metrics`; off without it
+    pub metrics_listen: Option<SocketAddr>,
+    /// Address to serve `/healthz` and `/readyz` on; off without it
+    pub health_listen: Option<SocketAddr>,
+    /// Log format and OTLP export
+    #[serde(default)]
+    pub tracing: TracingConfig,
+    pub sources: Vec<SourceConfig>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum SourceConfig {
+    /// Soroban escrow contract (`contracts/stellar`)
+    Stellar {
+        rpc_url: String,
+        contract: String,
+        /// Ledger to start from on the first run
+        start_ledger: u32,
+        /// Ledgers behind the RPC's latest after which `/readyz` reports the source down
+        max_lag: Option<u64>,
+    },
+    /// Solidity escrow (`contracts/src/HTLCEscrowFixed.sol`) on any EVM chain
+    Evm {
+        name: String,
+        rpc_url: String,
+        contract: String,
+        #[serde(default)]
+        start_block: u64,
+        #[serde(default = "default_confirmations")]
+        confirmations: u64,
+        /// Blocks deep at which events are final; without it, once the node reports their
+        /// block `finalized`. Events indexed before are provisional
+        finality: Option<u64>,
+        #[serde(default = "default_max_blocks")]
+        max_blocks: u64,
+        /// Blocks behind the confirmed head after which `/readyz` reports the source down
+        max_lag: Option<u64>,
+    },
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(unite_config::load(path.as_ref(), "UNITE_INDEXER")?)
+    }
+
+    pub fn database_url(&self) -> Result<String> {
+        std::env::var(&self.database_url_env)
+            .map_err(|_| Error::Config(format!("{} is not set", self.database_url_env)))
+    }
+}
+
+impl SourceConfig {
+    pub async fn connect(&self) -> Result<Box<dyn EventSource>> {
+        Ok(match self {
+            SourceConfig::Stellar {
+                rpc_url,
+                contract,
+                start_ledger,
+                ..
+            } => Box::new(SorobanSource::new(rpc_url, contract, *start_ledger)),
+            SourceConfig::Evm {
+                name,
+                rpc_url,
+                contract,
+                start_block,
+                confirmations,
+                finality,
+                max_blocks,
+                ..
+            } => {
+                let contract = Address::from_str(contract)
+                    .map_err(|_| Error::Config(format!("{name}: invalid contract {contract}")))?;
+                Box::new(
+                    EvmSource::connect(name, rpc_url, contract)
+                        .await?
+                        .with_start_block(*start_block)
+                        .with_confirmations(*confirmations)
+                        .with_finality(*finality)
+                        .with_max_blocks(*max_blocks),
+                )
+            }
+        })
+    }
+}
+
+impl SourceConfig {
+    /// Chain the source's records carry
+    pub fn chain(&self) -> &str {
+        match self {
+            SourceConfig::Stellar { .. } => "stellar",
+            SourceConfig::Evm { name, .. } => name,
+        }
+    }
+
+    /// The source reading from `from` (a ledger or block) under its own `backfill:` cursor,
+    /// through `rpc_url` instead of the configured one if given; EVM sources stop at `to` or
+    /// the finalized block, whichever is lower, leaving newer blocks to the live source
+    pub async fn backfill(
+        &self,
+        from: u64,
+        to: Option<u64>,
+        rpc_url: Option<&str>,
+    ) -> Result<Box<dyn EventSource>> {
+        Ok(match self {
+            SourceConfig::Stellar {
+                rpc_url: configured,
+                contract,
+                ..
+            } => {
+                let from = u32::try_from(from)
+                    .map_err(|_| Error::Config(format!("ledger {from} is out of range")))?;
+                let source = SorobanSource::new(rpc_url.unwrap_or(configured), contract, from);
+                let name = format!("backfill:{}", source.name());
+                Box::new(source.with_name(name))
+            }
+            SourceConfig::Evm {
+                name,
+                rpc_url: configured,
+                contract,
+                confirmations,
+                finality,
+                max_blocks,
+                ..
+            } => {
+                let contract = Address::from_str(contract)
+                    .map_err(|_| Error::Config(format!("{name}: invalid contract {contract}")))?;
+                let source = EvmSource::connect(name, rpc_url.unwrap_or(configured), contract)
+                    .await?
+                    .with_start_block(from)
+                    .with_confirmations(*confirmations)
+                    .with_finality(*finality)
+                    .with_max_blocks(*max_blocks);
+                let finalized = source.finalized().await?;
+                let name = format!("backfill:{}", source.name());
+                Box::new(
+                    source
+                        .with_end_block(Some(to.map_or(finalized, |to| to.min(finalized))))
+                        .with_name(name),
+                )
+            }
+        })
+    }
+
+    pub fn max_lag(&self) -> Option<u64> {
+        match self {
+            SourceConfig::Stellar { max_lag, .. } | SourceConfig::Evm { max_lag, .. } => *max_lag,
+        }
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.poll_secs > 0, || {
+            "poll_secs must be positive".to_string()
+        });
+        problems.check(!self.sources.is_empty(), || "no sources".to_string());
+        for (index, source) in self.sources.iter().enumerate() {
+            problems.section(&format!("sources[{index}]"), source);
+            // A reorg rolls back a chain's provisional rows, which only one source may write
+            if let SourceConfig::Evm { name, .. } = source {
+                problems.check(
+                    self.sources[..index].iter().all(
+                        |other| !matches!(other, SourceConfig::Evm { name: other, .. } if other == name),
+                    ),
+                    || format!("sources[{index}]: chain {name} is indexed twice"),
+                );
+            }
+        }
+    }
+}
+
+impl Validate for SourceConfig {
+    fn validate(&self, problems: &mut Problems) {
+        match self {
+            SourceConfig::Stellar { contract, .. } => problems
+                .check(contract.len() == 56 && contract.starts_with('C'), || {
+                    format!("contract {contract} is not a contract address (C...)")
+                }),
+            SourceConfig::Evm {
+                contract,
+                finality,
+                max_blocks,
+                ..
+            } => {
+                problems.check(*finality != Some(0), || {
+                    "finality must be at least 1".to_string()
+                });
+                problems.check(Address::from_str(contract).is_ok(), || {
+                    format!("contract {contract} is not an address")
+                });
+                problems.check(*max_blocks > 0, || {
+                    "max_blocks must be positive".to_string()
+                });
+            }
+        }
+    }
+}
+
+fn default_database_url_env() -> String {
+    "DATABASE_URL".to_string()
+}
+
+fn default_poll_secs() -> u64 {
+    5
+}
+
+fn default_migrate_on_start() -> bool {
+    true
+}
+
+fn default_confirmations() -> u64 {
+    12
+}
+
+fn default_max_blocks() -> u64 {
+    2_000
+}
@@ -0,0 +1,449 @@
+use std::collections::HashSet;
+
+use async_trait::async_trait;
+use sqlx::migrate::Migrator;
+use sqlx::postgres::{PgPool, PgPoolOptions};
+use sqlx::{Postgres, Transaction};
+use unite_telemetry::{Probe, Reading};
+
+use crate::record::{Batch, Record, RecordKind};
+use crate::{Error, Result};
+
+/// Versioned schema changes from `migrations/`, applied in order and never edited once released
+static MIGRATOR: Migrator = sqlx::migrate!();
+/// Advisory lock held while a batch appends to `events`
+const EVENTS_LOCK: i64 = 0x0075_6e69_7465;
+/// Channel notified after a commit that appended to `events`
+pub const EVENTS_CHANNEL: &str = "escrow_events";
+
+/// The indexer's Postgres database
+#[derive(Clone, Debug)]
+pub struct Database {
+    pool: PgPool,
+}
+
+impl Database {
+    pub async fn connect(url: &str) -> Result<Self> {
+        Ok(Self {
+            pool: PgPoolOptions::new().max_connections(5).connect(url).await?,
+        })
+    }
+
+    pub fn pool(&self) -> &PgPool {
+        &self.pool
+    }
+
+    /// Apply the migrations this database has not run, in version order, returning their
+    /// versions and descriptions
+    ///
+    /// Concurrent runs wait on each other; a changed or unknown applied migration fails
+    /// before anything is applied.
+    pub async fn migrate(&self) -> Result<Vec<(i64, String)>> {
+        let pending = self.pending().await?;
+        MIGRATOR.run(&self.pool).await?;
+        Ok(pending)
+    }
+
+    /// Migrations built into this indexer that the database has not run
+    pub async fn pending(&self) -> Result<Vec<(i64, String)>> {
+        let tracked: bool =
+            sqlx::query_scalar("SELECT to_regclass('_sqlx_migrations') IS NOT NULL")
+                .fetch_one(&self.pool)
+                .await?;
+        let applied: HashSet<i64> = if tracked {
+            sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success")
+                .fetch_all(&self.pool)
+                .await?
+                .into_iter()
+                .collect()
+        } else {
+            HashSet::new()
+        };
+        Ok(MIGRATOR
+            .iter()
+            .filter(|migration| !applied.contains(&migration.version))
+            .map(|migration| (migration.version, migration.description.to_string()))
+            .collect())
+    }
+
+    pub async fn cursor(&self, source: &str) -> Result<Option<String>> {
+        Ok(
+            sqlx::query_scalar("SELECT position FROM cursors WHERE source = $1")
+                .bind(source)
+                .fetch_optional(&self.pool)
+                .await?,
+        )
+    }
+
+    /// Forget the source's cursor, so it reads again from its configured start; rows already
+    /// stored stay
+    pub async fn reset_cursor(&self, source: &str) -> Result<()> {
+        sqlx::query("DELETE FROM cursors WHERE source = $1")
+            .bind(source)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Store a batch and move the source's cursor from `previous` to the batch's position in
+    /// one transaction, so every event is written exactly once
+    ///
+    /// Rows already present are left alone; the cursor only moves if it still reads
+    /// `previous`, so two indexers running the same source cannot both commit a batch.
+    /// Every new escrow, secret and settlement also appends to `events`, whose sequence
+    /// numbers become visible in order: batches hold a lock from their first append until
+    /// they commit.
+    ///
+    /// Records above the batch's finalized height are stored as provisional and append to
+    /// `events` only once a later batch finalizes them, so the event stream never carries
+    /// anything a reorg can take back.
+    pub async fn commit(
+        &self,
+        source: &str,
+        chain: &str,
+        previous: Option<&str>,
+        batch: &Batch,
+    ) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+        let mut events = 0;
+        for record in &batch.records {
+            let provisional = record.height > batch.finalized;
+            let Some(kind) = insert(&mut tx, record, provisional).await? else {
+                continue;
+            };
+            if !provisional {
+                append(
+                    &mut tx,
+                    &mut events,
+                    &record.chain,
+                    &record.escrow_id,
+                    kind,
+                    record.height,
+                    &record.tx,
+                )
+                .await?;
+            }
+        }
+        let finalized: Vec<(Vec<u8>, String, i64, String)> = sqlx::query_as(
+            "WITH escrows_final AS ( \
+                 UPDATE escrows SET provisional = false \
+                 WHERE chain = $1 AND provisional AND height <= $2 \
+                 RETURNING escrow_id, 'created' AS kind, height, tx, 0 AS rank), \
+             fills_final AS ( \
+                 UPDATE fills SET provisional = false \
+                 WHERE chain = $1 AND provisional AND height <= $2), \
+             secrets_final AS ( \
+                 UPDATE secrets SET provisional = false \
+                 WHERE chain = $1 AND provisional AND height <= $2 \
+                 RETURNING escrow_id, 'secret_revealed' AS kind, height, tx, 1 AS rank), \
+             settlements_final AS ( \
+                 UPDATE settlements SET provisional = false \
+                 WHERE chain = $1 AND provisional AND height <= $2 \
+                 RETURNING escrow_id, status AS kind, height, tx, 2 AS rank) \
+             SELECT escrow_id, kind, height, tx FROM ( \
+                 SELECT * FROM escrows_final \
+                 UNION ALL SELECT * FROM secrets_final \
+                 UNION ALL SELECT * FROM settlements_final) AS final \
+             ORDER BY height, rank",
+        )
+        .bind(chain)
+        .bind(batch.finalized as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+        for (escrow_id, kind, height, tx_id) in &finalized {
+            append(
+                &mut tx,
+                &mut events,
+                chain,
+                escrow_id,
+                kind,
+                *height as u64,
+                tx_id,
+            )
+            .await?;
+        }
+        sqlx::query("DELETE FROM blocks WHERE source = $1 AND height <= $2")
+            .bind(source)
+            .bind(batch.finalized as i64)
+            .execute(&mut *tx)
+            .await?;
+        if let Some(hash) = batch
+            .block_hash
+            .as_ref()
+            .filter(|_| batch.height > batch.finalized)
+        {
+            sqlx::query(
+                "INSERT INTO blocks (source, height, hash) VALUES ($1, $2, $3) \
+                 ON CONFLICT (source, height) DO UPDATE SET hash = excluded.hash",
+            )
+            .bind(source)
+            .bind(batch.height as i64)
+            .bind(hash)
+            .execute(&mut *tx)
+            .await?;
+        }
+        if events > 0 {
+            sqlx::query("SELECT pg_notify($1, '')")
+                .bind(EVENTS_CHANNEL)
+                .execute(&mut *tx)
+                .await?;
+        }
+        let moved = match previous {
+            Some(previous) => sqlx::query(
+                "UPDATE cursors SET position = $3, updated_at = now() \
+                 WHERE source = $1 AND position = $2",
+            )
+            .bind(source)
+            .bind(previous)
+            .bind(&batch.position),
+            None => sqlx::query(
+                "INSERT INTO cursors (source, position) VALUES ($1, $2) \
+                 ON CONFLICT (source) DO NOTHING",
+            )
+            .bind(source)
+            .bind(&batch.position),
+        }
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if moved != 1 {
+            return Err(Error::Cursor(format!(
+                "{source} was moved by another indexer"
+            )));
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    /// Blocks the source's batches were read through that are not final yet, newest first
+    pub async fn blocks(&self, source: &str) -> Result<Vec<(u64, String)>> {
+        let blocks: Vec<(i64, String)> = sqlx::query_as(
+            "SELECT height, hash FROM blocks WHERE source = $1 ORDER BY height DESC",
+        )
+        .bind(source)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(blocks
+            .into_iter()
+            .map(|(height, hash)| (height as u64, hash))
+            .collect())
+    }
+
+    /// Undo a reorg above `height`: delete the chain's provisional rows and the source's
+    /// blocks above it, and move the cursor from `previous` back to `position`, returning
+    /// how many rows were deleted
+    ///
+    /// Final rows stay; `events` never held the provisional ones.
+    pub async fn rollback(
+        &self,
+        source: &str,
+        chain: &str,
+        previous: Option<&str>,
+        height: u64,
+        position: &str,
+    ) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut deleted = 0;
+        for table in ["settlements", "secrets", "fills", "escrows"] {
+            deleted += sqlx::query(&format!(
+                "DELETE FROM {table} WHERE chain = $1 AND provisional AND height > $2"
+            ))
+            .bind(chain)
+            .bind(height as i64)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        }
+        sqlx::query("DELETE FROM blocks WHERE source = $1 AND height > $2")
+            .bind(source)
+            .bind(height as i64)
+            .execute(&mut *tx)
+            .await?;
+        let moved = sqlx::query(
+            "UPDATE cursors SET position = $3, updated_at = now() \
+             WHERE source = $1 AND position IS NOT DISTINCT FROM $2",
+        )
+        .bind(source)
+        .bind(previous)
+        .bind(position)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        if moved != 1 {
+            return Err(Error::Cursor(format!(
+                "{source} was moved by another indexer"
+            )));
+        }
+        tx.commit().await?;
+        Ok(deleted)
+    }
+}
+
+#[async_trait]
+impl Probe for Database {
+    fn name(&self) -> String {
+        "database".to_string()
+    }
+
+    async fn probe(&self) -> std::result::Result<Reading, String> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|error| error.to_string())?;
+        Ok(Reading::default())
+    }
+}
+
+/// Append to `events`, taking the lock that orders them on the transaction's first append
+async fn append(
+    tx: &mut Transaction<'_, Postgres>,
+    events: &mut usize,
+    chain: &str,
+    escrow_id: &[u8],
+    kind: &str,
+    height: u64,
+    tx_id: &str,
+) -> Result<()> {
+    if *events == 0 {
+        sqlx::query("SELECT pg_advisory_xact_lock($1)")
+            .bind(EVENTS_LOCK)
+            .execute(&mut **tx)
+            .await?;
+    }
+    sqlx::query(
+        "INSERT INTO events (chain, escrow_id, kind, height, tx) VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(chain)
+    .bind(escrow_id)
+    .bind(kind)
+    .bind(height as i64)
+    .bind(tx_id)
+    .execute(&mut **tx)
+    .await?;
+    *events += 1;
+    Ok(())
+}
+
+/// Store a record, returning the event kind to append if it added a row
+async fn insert(
+    tx: &mut Transaction<'_, Postgres>,
+    record: &Record,
+    provisional: bool,
+) -> Result<Option<&'static str>> {
+    let escrow_id = record.escrow_id.as_slice();
+    let height = record.height as i64;
+    let (kind, inserted) = match &record.kind {
+        RecordKind::Escrow {
+            order_id,
+            sender,
+            receiver,
+            token,
+            amount,
+            hashlock,
+            timelock,
+        } => {
+            let inserted = sqlx::query(
+                "INSERT INTO escrows (chain, escrow_id, order_id, sender, receiver, token, amount, \
+                 hashlock, timelock, height, tx, provisional) \
+                 VALUES ($1, $2, $3, $4, $5, $6, $7::numeric, $8, $9, $10, $11, $12) \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(&record.chain)
+            .bind(escrow_id)
+            .bind(order_id)
+            .bind(sender)
+            .bind(receiver)
+            .bind(token)
+            .bind(amount.to_string())
+            .bind(hashlock.as_slice())
+            .bind(*timelock as i64)
+            .bind(height)
+            .bind(&record.tx)
+            .bind(provisional)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected();
+            (Some("created"), inserted)
+        }
+        // Amounts left out are the escrow's whole amount; without the escrow there is no row
+        RecordKind::Fill {
+            taker,
+            amount,
+            filled_amount,
+        } => {
+            let inserted = sqlx::query(
+                "INSERT INTO fills (chain, event_key, escrow_id, taker, amount, filled_amount, \
+                 height, tx, provisional) \
+                 SELECT $1, $2, $3, $4, fill.amount, fill.filled_amount, $7, $8, $9 \
+                 FROM (SELECT COALESCE($5::numeric, e.amount) AS amount, \
+                              COALESCE($6::numeric, e.amount) AS filled_amount \
+                       FROM (SELECT 1) AS one \
+                       LEFT JOIN escrows e ON e.chain = $1 AND e.escrow_id = $3) AS fill \
+                 WHERE fill.amount IS NOT NULL AND fill.filled_amount IS NOT NULL \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(&record.chain)
+            .bind(&record.event_key)
+            .bind(escrow_id)
+            .bind(taker)
+            .bind(amount.map(|amount| amount.to_string()))
+            .bind(filled_amount.map(|amount| amount.to_string()))
+            .bind(height)
+            .bind(&record.tx)
+            .bind(provisional)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected();
+            (None, inserted)
+        }
+        RecordKind::Secret { hashlock, secret } => {
+            let inserted = sqlx::query(
+                "INSERT INTO secrets (chain, escrow_id, hashlock, secret, height, tx, provisional) \
+                 SELECT $1, $2, secret.hashlock, $4, $5, $6, $7 \
+                 FROM (SELECT COALESCE($3, e.hashlock) AS hashlock \
+                       FROM (SELECT 1) AS one \
+                       LEFT JOIN escrows e ON e.chain = $1 AND e.escrow_id = $2) AS secret \
+                 WHERE secret.hashlock IS NOT NULL \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(&record.chain)
+            .bind(escrow_id)
+            .bind(hashlock.as_ref().map(|hashlock| hashlock.as_slice()))
+            .bind(secret.as_slice())
+            .bind(height)
+            .bind(&record.tx)
+            .bind(provisional)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected();
+            (Some("secret_revealed"), inserted)
+        }
+        RecordKind::Settlement {
+            status,
+            filled_amount,
+            settled_by,
+        } => {
+            let inserted = sqlx::query(
+                "INSERT INTO settlements (chain, escrow_id, status, filled_amount, settled_by, \
+                 height, tx, provisional) \
+                 VALUES ($1, $2, $3, COALESCE($4::numeric, \
+                         (SELECT amount FROM escrows WHERE chain = $1 AND escrow_id = $2)), \
+                         $5, $6, $7, $8) \
+                 ON CONFLICT DO NOTHING",
+            )
+            .bind(&record.chain)
+            .bind(escrow_id)
+            .bind(status.as_str())
+            .bind(filled_amount.map(|amount| amount.to_string()))
+            .bind(settled_by)
+            .bind(height)
+            .bind(&record.tx)
+            .bind(provisional)
+            .execute(&mut **tx)
+            .await?
+            .rows_affected();
+            (Some(status.as_str()), inserted)
+        }
+    };
+    Ok(kind.filter(|_| inserted == 1))
+}
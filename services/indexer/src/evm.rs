@@ -0,0 +1,252 @@
+use alloy::eips::BlockNumberOrTag;
+use alloy::primitives::Address;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder};
+use async_trait::async_trait;
+use unite_evm::{EscrowClient, EscrowEvent, EscrowLog};
+
+use crate::record::{Batch, Record, RecordKind, Status};
+use crate::source::EventSource;
+use crate::{Error, Result};
+
+/// Escrow events of the Solidity HTLC contract on one EVM chain, scanned by block range up to
+/// `confirmations` below the head
+///
+/// Events above the finalized block are provisional: each batch carries the hash of the block
+/// it was read through, so a reorg that replaces it is noticed and the source rewound.
+pub struct EvmSource {
+    client: EscrowClient<DynProvider>,
+    chain: String,
+    name: String,
+    start_block: u64,
+    confirmations: u64,
+    finality: Option<u64>,
+    end_block: Option<u64>,
+    max_blocks: u64,
+}
+
+impl EvmSource {
+    /// `rpc_url` may be HTTP or WebSocket
+    pub async fn connect(chain: &str, rpc_url: &str, contract: Address) -> Result<Self> {
+        let provider = ProviderBuilder::new()
+            .connect(rpc_url)
+            .await
+            .map_err(|error| Error::Evm(error.into()))?;
+        Ok(Self {
+            client: EscrowClient::new(provider.erased(), contract, Address::ZERO, 1),
+            chain: chain.to_string(),
+            name: format!("{chain}:{contract}"),
+            start_block: 0,
+            confirmations: 1,
+            finality: None,
+            end_block: None,
+            max_blocks: 2_000,
+        })
+    }
+
+    /// Block to start from when there is no cursor yet, e.g. the contract's deployment
+    pub fn with_start_block(mut self, start_block: u64) -> Self {
+        self.start_block = start_block;
+        self
+    }
+
+    /// Blocks, including the one it landed in, before an event is indexed (1 = mined)
+    pub fn with_confirmations(mut self, confirmations: u64) -> Self {
+        self.confirmations = confirmations.max(1);
+        self
+    }
+
+    /// Blocks, including the one it landed in, before an event is final; without it, events
+    /// are final once the node reports their block `finalized`
+    pub fn with_finality(mut self, finality: Option<u64>) -> Self {
+        self.finality = finality.map(|finality| finality.max(1));
+        self
+    }
+
+    /// Last block to read, for replaying a past range; the source is caught up once there
+    pub fn with_end_block(mut self, end_block: Option<u64>) -> Self {
+        self.end_block = end_block;
+        self
+    }
+
+    /// Name the cursor is stored under (defaults to `chain:contract`)
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Widest block range per `eth_getLogs` request
+    pub fn with_max_blocks(mut self, max_blocks: u64) -> Self {
+        self.max_blocks = max_blocks.max(1);
+        self
+    }
+
+    /// A withdrawal becomes a fill, its secret and the settlement
+    async fn records(&self, log: EscrowLog) -> Result<Vec<Record>> {
+        let (Some(height), Some(tx_hash), Some(log_index)) =
+            (log.block_number, log.tx_hash, log.log_index)
+        else {
+            return Ok(Vec::new());
+        };
+        let record = |escrow_id: [u8; 32], kind| Record {
+            chain: self.chain.clone(),
+            escrow_id,
+            height,
+            tx: tx_hash.to_string(),
+            event_key: format!("{tx_hash}:{log_index}"),
+            kind,
+        };
+        Ok(match log.event {
+            EscrowEvent::Created(event) => {
+                let token =
+                    (event.tokenAddress != Address::ZERO).then(|| event.tokenAddress.to_string());
+                vec![record(
+                    event.escrowId.0,
+                    RecordKind::Escrow {
+                        order_id: event.orderId,
+                        sender: event.sender.to_string(),
+                        receiver: event.receiver.to_string(),
+                        token,
+                        amount: event.amount.saturating_to(),
+                        hashlock: event.secretHash.0,
+                        timelock: event.timelock.saturating_to(),
+                    },
+                )]
+            }
+            EscrowEvent::Withdrawn(event) => {
+                let escrow_id = event.escrowId.0;
+                let mut records = vec![record(
+                    escrow_id,
+                    RecordKind::Fill {
+                        taker: event.receiver.to_string(),
+                        amount: None,
+                        filled_amount: None,
+                    },
+                )];
+                if let Some(secret) = self
+                    .client
+                    .revealed_secret(tx_hash)
+                    .await
+                    .map_err(Error::Evm)?
+                {
+                    records.push(record(
+                        escrow_id,
+                        RecordKind::Secret {
+                            hashlock: None,
+                            secret: secret.into_bytes(),
+                        },
+                    ));
+                }
+                records.push(record(
+                    escrow_id,
+                    RecordKind::Settlement {
+                        status: Status::Withdrawn,
+                        filled_amount: None,
+                        settled_by: Some(event.receiver.to_string()),
+                    },
+                ));
+                records
+            }
+            EscrowEvent::Cancelled(event) => vec![record(
+                event.escrowId.0,
+                RecordKind::Settlement {
+                    status: Status::Cancelled,
+                    filled_amount: Some(0),
+                    settled_by: Some(event.sender.to_string()),
+                },
+            )],
+        })
+    }
+}
+
+#[async_trait]
+impl EventSource for EvmSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    /// Newest block `confirmations` deep
+    async fn head(&self) -> Result<u64> {
+        let head = self
+            .client
+            .provider()
+            .get_block_number()
+            .await
+            .map_err(|error| Error::Evm(error.into()))?;
+        Ok((head + 1).saturating_sub(self.confirmations))
+    }
+
+    async fn finalized(&self) -> Result<u64> {
+        let provider = self.client.provider();
+        let finalized = match self.finality {
+            Some(finality) => {
+                let head = provider
+                    .get_block_number()
+                    .await
+                    .map_err(|error| Error::Evm(error.into()))?;
+                (head + 1).saturating_sub(finality)
+            }
+            None => provider
+                .get_block_by_number(BlockNumberOrTag::Finalized)
+                .await
+                .map_err(|error| Error::Evm(error.into()))?
+                .map_or(0, |block| block.header.number),
+        };
+        Ok(finalized)
+    }
+
+    async fn next_batch(&self, position: Option<&str>) -> Result<Batch> {
+        let from = match position {
+            Some(position) => position
+                .parse()
+                .map_err(|_| Error::Cursor(format!("{}: invalid block {position}", self.name)))?,
+            None => self.start_block,
+        };
+        let head = self.head().await?;
+        let confirmed = self.end_block.map_or(head, |end| head.min(end));
+        let finalized = self.finalized().await?;
+        if confirmed < from {
+            return Ok(Batch {
+                records: Vec::new(),
+                position: from.to_string(),
+                height: from.saturating_sub(1),
+                finalized,
+                block_hash: None,
+                caught_up: true,
+            });
+        }
+        let to = confirmed.min(from + self.max_blocks - 1);
+        // Hashed before the logs are read, so logs from a reorg in between fail the next check
+        // rather than pass it
+        let block_hash = self.block_hash(to).await?;
+        let mut records = Vec::new();
+        for log in self.client.events(from, to).await.map_err(Error::Evm)? {
+            records.extend(self.records(log).await?);
+        }
+        Ok(Batch {
+            records,
+            position: (to + 1).to_string(),
+            height: to,
+            finalized,
+            block_hash,
+            caught_up: to == confirmed,
+        })
+    }
+
+    async fn block_hash(&self, height: u64) -> Result<Option<String>> {
+        Ok(self
+            .client
+            .provider()
+            .get_block_by_number(BlockNumberOrTag::Number(height))
+            .await
+            .map_err(|error| Error::Evm(error.into()))?
+            .map(|block| block.header.hash.to_string()))
+    }
+
+    fn rewind(&self, height: u64) -> Result<String> {
+        Ok((height + 1).to_string())
+    }
+}
@@ -0,0 +1,148 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use tracing::{debug, info, warn};
+use unite_telemetry::{Probe, Reading};
+
+use crate::db::Database;
+use crate::metrics;
+use crate::record::RecordKind;
+use crate::source::EventSource;
+use crate::Result;
+
+/// Moves one source's events into the database, batch by batch
+///
+/// As a health probe it reports how many blocks or ledgers the last committed batch is behind
+/// the source's head, and fails past `max_lag` or before the first commit.
+pub struct Indexer {
+    db: Database,
+    source: Box<dyn EventSource>,
+    poll_interval: Duration,
+    max_lag: Option<u64>,
+    height: Mutex<Option<u64>>,
+}
+
+impl Indexer {
+    pub fn new(db: Database, source: Box<dyn EventSource>, poll_interval: Duration) -> Self {
+        Self {
+            db,
+            source,
+            poll_interval,
+            max_lag: None,
+            height: Mutex::new(None),
+        }
+    }
+
+    /// Blocks or ledgers behind the head after which the indexer reports itself unready
+    pub fn with_max_lag(mut self, max_lag: Option<u64>) -> Self {
+        self.max_lag = max_lag;
+        self
+    }
+
+    /// Index one batch from the stored cursor, returning whether the source is caught up
+    ///
+    /// Runs in a `batch` span; each committed record is logged at `debug` with its escrow ID,
+    /// and escrows with their order ID, matching the relayer's and resolver's spans. When a
+    /// reorg replaced a block an earlier batch was read through, the step rolls the source
+    /// back instead.
+    #[tracing::instrument(name = "batch", skip_all, fields(source = %self.source.name()))]
+    pub async fn step(&self) -> Result<bool> {
+        let name = self.source.name();
+        let started = Instant::now();
+        let position = self.db.cursor(name).await?;
+        if let Some(fork) = self.fork().await? {
+            let resume = self.source.rewind(fork)?;
+            let deleted = self
+                .db
+                .rollback(
+                    name,
+                    self.source.chain(),
+                    position.as_deref(),
+                    fork,
+                    &resume,
+                )
+                .await?;
+            warn!(fork, deleted, "reorg, reading again from {resume}");
+            metrics::reorg(name, deleted);
+            return Ok(false);
+        }
+        let batch = self.source.next_batch(position.as_deref()).await?;
+        self.db
+            .commit(name, self.source.chain(), position.as_deref(), &batch)
+            .await?;
+        metrics::committed(name, &batch, started.elapsed());
+        *self.height.lock().unwrap() = Some(batch.height);
+        for record in &batch.records {
+            let escrow_id = hex::encode(record.escrow_id);
+            match &record.kind {
+                RecordKind::Escrow { order_id, .. } => {
+                    debug!(%escrow_id, %order_id, height = record.height, tx = %record.tx, "escrow")
+                }
+                kind => {
+                    debug!(%escrow_id, height = record.height, tx = %record.tx, "{}", kind.as_str())
+                }
+            }
+        }
+        if !batch.records.is_empty() {
+            info!(
+                "{} records, now at {}",
+                batch.records.len(),
+                batch.position
+            );
+        }
+        Ok(batch.caught_up)
+    }
+
+    /// Newest block still on the chain below one that a reorg replaced, or `None` while every
+    /// stored block is still there
+    async fn fork(&self) -> Result<Option<u64>> {
+        let blocks = self.db.blocks(self.source.name()).await?;
+        for (index, (height, hash)) in blocks.iter().enumerate() {
+            if self.source.block_hash(*height).await?.as_ref() == Some(hash) {
+                return Ok((index > 0).then_some(*height));
+            }
+        }
+        // None is left, so go back to below the oldest, which was above the finalized block
+        Ok(blocks.last().map(|(height, _)| height.saturating_sub(1)))
+    }
+
+    /// Index until the task is dropped, waiting `poll_interval` once caught up or after errors
+    pub async fn run(&self) {
+        loop {
+            match self.step().await {
+                Ok(false) => continue,
+                Ok(true) => {}
+                Err(error) => {
+                    warn!(source = %self.source.name(), "{error}");
+                    metrics::error(self.source.name());
+                }
+            }
+            tokio::time::sleep(self.poll_interval).await;
+        }
+    }
+}
+
+#[async_trait]
+impl Probe for Indexer {
+    fn name(&self) -> String {
+        self.source.name().to_string()
+    }
+
+    async fn probe(&self) -> std::result::Result<Reading, String> {
+        let head = self
+            .source
+            .head()
+            .await
+            .map_err(|error| error.to_string())?;
+        let height = (*self.height.lock().unwrap()).ok_or("no batch committed yet")?;
+        let lag = head.saturating_sub(height);
+        if let Some(max_lag) = self.max_lag.filter(|max_lag| lag > *max_lag) {
+            return Err(format!("{lag} behind the head at {head}, over {max_lag}"));
+        }
+        Ok(Reading {
+            height: Some(height),
+            lag: Some(lag),
+        })
+    }
+}
@@ -0,0 +1,55 @@
+// Escrow Event Indexer
+// Reads the escrow contracts' events once, so downstream tools query Postgres instead of each
+// re-scanning the chains. The Soroban contract's events are paged with `getEvents` and the EVM
+// contract's logs are scanned by block range below a confirmation depth; both are normalized
+// into escrows, fills, secrets and settlements. Every batch is written in the same transaction
+// that advances its source's cursor, so a restart resumes exactly where the last commit ended
+// and no event is stored twice. EVM rows stay provisional until their block is finalized, and
+// are rolled back and read again when a reorg replaces a block a batch was read through. The
+// schema is versioned in `migrations/` and applied at startup or ahead of a rollout with
+// `unite-indexer migrate`. `unite-indexer backfill --from-ledger N` replays a source's history
+// from a past ledger or block under its own cursor, at a limited rate, to recover events the
+// live source started after or missed. Batch latency, record counts, heights, reorgs and
+// errors per source are exported to Prometheus. `/readyz` probes the database and reports how
+// far each source is behind its chain's head.
+
+pub mod backfill;
+pub mod config;
+pub mod db;
+pub mod evm;
+pub mod indexer;
+pub mod metrics;
+pub mod record;
+pub mod source;
+pub mod stellar;
+
+pub use backfill::Backfill;
+pub use config::{Config, SourceConfig};
+pub use db::Database;
+pub use evm::EvmSource;
+pub use indexer::Indexer;
+pub use record::{Batch, Record, RecordKind, Status};
+pub use source::EventSource;
+pub use stellar::SorobanSource;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error("cursor: {0}")]
+    Cursor(String),
+    #[error(transparent)]
+    Database(#[from] sqlx::Error),
+    #[error(transparent)]
+    Migrate(#[from] sqlx::migrate::MigrateError),
+    #[error(transparent)]
+    Stellar(unite_stellar::Error),
+    #[error(transparent)]
+    Evm(unite_evm::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Load(#[from] unite_config::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
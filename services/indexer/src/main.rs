@@ -0,0 +1,217 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand};
+use tracing::{error, info};
+use unite_indexer::{metrics, Backfill, Config, Database, Error, Indexer};
+use unite_telemetry::Health;
+
+/// Index escrow events into Postgres, logging through `RUST_LOG`
+#[derive(Debug, Parser)]
+#[command(
+    name = "unite-indexer",
+    version,
+    args_conflicts_with_subcommands = true
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Config file to index with
+    #[arg(default_value = "indexer.toml")]
+    config: PathBuf,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Apply pending schema migrations and exit
+    Migrate {
+        #[arg(default_value = "indexer.toml")]
+        config: PathBuf,
+        /// List pending migrations without applying them
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Replay a source's events from a past ledger or block, then exit
+    Backfill {
+        #[arg(default_value = "indexer.toml")]
+        config: PathBuf,
+        /// Ledger or block to replay from
+        #[arg(long)]
+        from_ledger: u64,
+        /// Last ledger or block to replay, the finalized one by default
+        #[arg(long)]
+        to_ledger: Option<u64>,
+        /// Chain of the source to replay (`stellar` or an EVM source's name), needed when
+        /// several are configured
+        #[arg(long)]
+        source: Option<String>,
+        /// RPC endpoint to read history from instead of the configured one, such as an
+        /// archive node
+        #[arg(long)]
+        rpc_url: Option<String>,
+        /// Most batches read per second
+        #[arg(long, default_value_t = 2.0)]
+        rate: f64,
+        /// Start over from `--from-ledger` instead of resuming an earlier backfill
+        #[arg(long)]
+        restart: bool,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let cli = Cli::parse();
+    match cli.command {
+        None => run(&cli.config).await,
+        Some(Command::Migrate { config, dry_run }) => migrate(&config, dry_run).await,
+        Some(Command::Backfill {
+            config,
+            from_ledger,
+            to_ledger,
+            source,
+            rpc_url,
+            rate,
+            restart,
+        }) => {
+            backfill(
+                &config,
+                from_ledger,
+                to_ledger,
+                source.as_deref(),
+                rpc_url.as_deref(),
+                rate,
+                restart,
+            )
+            .await
+        }
+    }
+}
+
+async fn run(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load(path)?;
+    let _telemetry = unite_telemetry::init("unite-indexer", &config.tracing)?;
+    if let Some(listen) = config.metrics_listen {
+        metrics::install(listen)?;
+        info!("metrics on http://{listen}/metrics");
+    }
+
+    let db = open(&config).await?;
+    let mut health = Health::default().with(Arc::new(db.clone()));
+    let mut tasks = Vec::new();
+    for source_config in &config.sources {
+        let source = source_config.connect().await?;
+        info!("indexing {}", source.name());
+        let indexer = Arc::new(
+            Indexer::new(db.clone(), source, Duration::from_secs(config.poll_secs))
+                .with_max_lag(source_config.max_lag()),
+        );
+        health = health.with(indexer.clone());
+        tasks.push(tokio::spawn(async move { indexer.run().await }));
+    }
+    if let Some(listen) = config.health_listen {
+        tasks.push(tokio::spawn(async move {
+            if let Err(error) = unite_telemetry::health::serve(listen, health).await {
+                error!("health endpoint: {error}");
+            }
+        }));
+        info!("health on http://{listen}/readyz");
+    }
+    tokio::signal::ctrl_c().await?;
+    for task in tasks {
+        task.abort();
+    }
+    Ok(())
+}
+
+async fn migrate(path: &Path, dry_run: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load(path)?;
+    let _telemetry = unite_telemetry::init("unite-indexer", &config.tracing)?;
+    let db = Database::connect(&config.database_url()?).await?;
+    let migrations = if dry_run {
+        db.pending().await?
+    } else {
+        db.migrate().await?
+    };
+    if migrations.is_empty() {
+        info!("the schema is up to date");
+    }
+    for (version, description) in migrations {
+        if dry_run {
+            info!("pending migration {version} ({description})");
+        } else {
+            info!("applied migration {version} ({description})");
+        }
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn backfill(
+    path: &Path,
+    from: u64,
+    to: Option<u64>,
+    chain: Option<&str>,
+    rpc_url: Option<&str>,
+    rate: f64,
+    restart: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let config = Config::load(path)?;
+    let _telemetry = unite_telemetry::init("unite-indexer", &config.tracing)?;
+    if let Some(listen) = config.metrics_listen {
+        metrics::install(listen)?;
+    }
+    let matching: Vec<_> = config
+        .sources
+        .iter()
+        .filter(|source| chain.is_none_or(|chain| source.chain() == chain))
+        .collect();
+    let source_config = match matching[..] {
+        [source] => source,
+        [] => {
+            return Err(
+                Error::Config(format!("no source indexes {}", chain.unwrap_or("anything"))).into(),
+            )
+        }
+        _ => {
+            return Err(Error::Config(
+                "several sources are configured; pick one with --source".into(),
+            )
+            .into())
+        }
+    };
+
+    let db = open(&config).await?;
+    let source = source_config.backfill(from, to, rpc_url).await?;
+    if restart {
+        db.reset_cursor(source.name()).await?;
+    }
+    let records = Backfill::new(db, source)
+        .with_target(to)
+        .with_rate(rate)
+        .run()
+        .await?;
+    info!("backfill done, {records} records read");
+    Ok(())
+}
+
+/// Connect to the database and apply pending migrations, or with `migrate_on_start` off,
+/// refuse to run against a schema that is behind
+async fn open(config: &Config) -> Result<Database, Box<dyn std::error::Error>> {
+    let db = Database::connect(&config.database_url()?).await?;
+    if config.migrate_on_start {
+        for (version, description) in db.migrate().await? {
+            info!("applied migration {version} ({description})");
+        }
+    } else {
+        let pending = db.pending().await?;
+        if !pending.is_empty() {
+            return Err(Error::Config(format!(
+                "{} migrations pending; run `unite-indexer migrate`",
+                pending.len()
+            ))
+            .into());
+        }
+    }
+    Ok(db)
+}
@@ -0,0 +1,85 @@
+// Prometheus metrics, recorded through the `metrics` facade and served once the binary calls
+// `install`.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+
+use crate::record::Batch;
+use crate::{Error, Result};
+
+/// Buckets for `*_seconds` histograms, from a cached RPC page to a large block range
+const SECONDS_BUCKETS: &[f64] = &[0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Serve every metric recorded in this process on `http://{listen}/metrics`
+pub fn install(listen: SocketAddr) -> Result<()> {
+    PrometheusBuilder::new()
+        .with_http_listener(listen)
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), SECONDS_BUCKETS)
+        .and_then(|builder| builder.install())
+        .map_err(|error| Error::Config(format!("metrics: {error}")))?;
+    describe();
+    Ok(())
+}
+
+fn describe() {
+    describe_counter!(
+        "unite_indexer_records_total",
+        "Records committed, by source and kind"
+    );
+    describe_histogram!(
+        "unite_indexer_batch_seconds",
+        "Time to read and commit one batch"
+    );
+    describe_counter!(
+        "unite_indexer_errors_total",
+        "Batches that failed to read or commit"
+    );
+    describe_gauge!(
+        "unite_indexer_height",
+        "Highest block or ledger of a committed record"
+    );
+    describe_counter!(
+        "unite_indexer_reorgs_total",
+        "Reorgs that replaced a block a batch was read through"
+    );
+    describe_counter!(
+        "unite_indexer_rolled_back_total",
+        "Provisional rows deleted after a reorg"
+    );
+    describe_gauge!(
+        "unite_indexer_caught_up",
+        "1 while the source has no more events to read"
+    );
+}
+
+pub(crate) fn committed(source: &str, batch: &Batch, elapsed: Duration) {
+    histogram!("unite_indexer_batch_seconds", "source" => source.to_string()).record(elapsed);
+    for record in &batch.records {
+        counter!(
+            "unite_indexer_records_total",
+            "source" => source.to_string(),
+            "kind" => record.kind.as_str(),
+        )
+        .increment(1);
+    }
+    if let Some(height) = batch.records.iter().map(|record| record.height).max() {
+        gauge!("unite_indexer_height", "source" => source.to_string()).set(height as f64);
+    }
+    gauge!("unite_indexer_caught_up", "source" => source.to_string()).set(if batch.caught_up {
+        1.0
+    } else {
+        0.0
+    });
+}
+
+pub(crate) fn error(source: &str) {
+    counter!("unite_indexer_errors_total", "source" => source.to_string()).increment(1);
+}
+
+pub(crate) fn reorg(source: &str, deleted: u64) {
+    counter!("unite_indexer_reorgs_total", "source" => source.to_string()).increment(1);
+    counter!("unite_indexer_rolled_back_total", "source" => source.to_string()).increment(deleted);
+}
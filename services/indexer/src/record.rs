@@ -0,0 +1,87 @@
+/// One normalized row, from any chain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Record {
+    pub chain: String,
+    pub escrow_id: [u8; 32],
+    /// Block or ledger of the source event
+    pub height: u64,
+    pub tx: String,
+    /// Position of the source event on its chain, e.g. a Soroban event ID or `tx:log_index`
+    pub event_key: String,
+    pub kind: RecordKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordKind {
+    Escrow {
+        order_id: String,
+        sender: String,
+        receiver: String,
+        /// `None` for the chain's native currency
+        token: Option<String>,
+        amount: u128,
+        hashlock: [u8; 32],
+        timelock: u64,
+    },
+    /// `None` amounts are the escrow's whole amount, for chains without partial fills
+    Fill {
+        taker: String,
+        amount: Option<u128>,
+        filled_amount: Option<u128>,
+    },
+    /// `None` for the escrow's own hashlock
+    Secret {
+        hashlock: Option<[u8; 32]>,
+        secret: Vec<u8>,
+    },
+    Settlement {
+        status: Status,
+        filled_amount: Option<u128>,
+        settled_by: Option<String>,
+    },
+}
+
+impl RecordKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecordKind::Escrow { .. } => "escrow",
+            RecordKind::Fill { .. } => "fill",
+            RecordKind::Secret { .. } => "secret",
+            RecordKind::Settlement { .. } => "settlement",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Status {
+    Withdrawn,
+    Cancelled,
+    Resolved,
+}
+
+impl Status {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Status::Withdrawn => "withdrawn",
+            Status::Cancelled => "cancelled",
+            Status::Resolved => "resolved",
+        }
+    }
+}
+
+/// Records after a position, with the position to resume from once they are stored
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Batch {
+    pub records: Vec<Record>,
+    pub position: String,
+    /// Block or ledger the source has been read through
+    pub height: u64,
+    /// Block or ledger through which the chain can no longer revert events; records above it
+    /// are provisional
+    pub finalized: u64,
+    /// Hash of the block at `height`, checked before the next batch to notice reorgs; `None`
+    /// on chains whose events are final once read
+    pub block_hash: Option<String>,
+    /// Whether the source had nothing newer, so the next batch can wait
+    pub caught_up: bool,
+}
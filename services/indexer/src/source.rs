@@ -0,0 +1,37 @@
+use async_trait::async_trait;
+
+use crate::record::Batch;
+use crate::{Error, Result};
+
+/// A chain's event stream, read in batches from a resumable position
+#[async_trait]
+pub trait EventSource: Send + Sync {
+    /// Name the cursor is stored under, unique per source
+    fn name(&self) -> &str;
+
+    /// Chain the source's records carry
+    fn chain(&self) -> &str;
+
+    /// Newest block or ledger there is to index, for measuring how far behind the indexer is
+    async fn head(&self) -> Result<u64>;
+
+    /// Newest block or ledger the chain can no longer revert
+    async fn finalized(&self) -> Result<u64> {
+        self.head().await
+    }
+
+    /// Events after `position`, or from the configured start when there is none yet
+    async fn next_batch(&self, position: Option<&str>) -> Result<Batch>;
+
+    /// Hash of the block at `height` on the chain as it stands now, `None` if there is none;
+    /// only asked of sources whose batches carry block hashes
+    async fn block_hash(&self, _height: u64) -> Result<Option<String>> {
+        Ok(None)
+    }
+
+    /// Position to read every event after `height` again from, once a reorg replaced the
+    /// blocks above it
+    fn rewind(&self, _height: u64) -> Result<String> {
+        Err(Error::Cursor(format!("{} cannot rewind", self.name())))
+    }
+}
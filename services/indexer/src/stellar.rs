@@ -0,0 +1,151 @@
+use async_trait::async_trait;
+use unite_stellar::{EscrowEvent, EscrowLog, EventStart, SettlementStatus, SorobanRpc};
+
+use crate::record::{Batch, Record, RecordKind, Status};
+use crate::source::EventSource;
+use crate::{Error, Result};
+
+/// Escrow events of the Soroban HTLC contract, paged with `getEvents`
+pub struct SorobanSource {
+    rpc: SorobanRpc,
+    contract: String,
+    chain: String,
+    name: String,
+    start_ledger: u32,
+    page_size: u32,
+}
+
+impl SorobanSource {
+    /// `start_ledger` must still be within the RPC server's event retention window
+    pub fn new(rpc_url: &str, contract: &str, start_ledger: u32) -> Self {
+        Self {
+            rpc: SorobanRpc::new(rpc_url),
+            contract: contract.to_string(),
+            chain: "stellar".to_string(),
+            name: format!("stellar:{contract}"),
+            start_ledger,
+            page_size: 200,
+        }
+    }
+
+    /// Chain name the records carry (defaults to `stellar`)
+    pub fn with_chain(mut self, chain: impl Into<String>) -> Self {
+        self.chain = chain.into();
+        self.name = format!("{}:{}", self.chain, self.contract);
+        self
+    }
+
+    /// Name the cursor is stored under (defaults to `chain:contract`)
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    fn record(&self, log: EscrowLog) -> Record {
+        let kind = match log.event {
+            EscrowEvent::Opened {
+                order_id,
+                sender,
+                receiver,
+                token,
+                amount,
+                secret_hash,
+                timelock,
+            } => RecordKind::Escrow {
+                order_id,
+                sender,
+                receiver,
+                token: Some(token),
+                amount: amount.max(0) as u128,
+                hashlock: secret_hash,
+                timelock,
+            },
+            EscrowEvent::Filled {
+                taker,
+                amount,
+                filled_amount,
+            } => RecordKind::Fill {
+                taker,
+                amount: Some(amount.max(0) as u128),
+                filled_amount: Some(filled_amount.max(0) as u128),
+            },
+            EscrowEvent::Secret {
+                secret_hash,
+                secret,
+            } => RecordKind::Secret {
+                hashlock: Some(secret_hash),
+                secret: secret.to_vec(),
+            },
+            EscrowEvent::Settled {
+                status,
+                filled_amount,
+                settled_by,
+            } => RecordKind::Settlement {
+                status: match status {
+                    SettlementStatus::Withdrawn => Status::Withdrawn,
+                    SettlementStatus::Cancelled => Status::Cancelled,
+                    SettlementStatus::Resolved => Status::Resolved,
+                },
+                filled_amount: Some(filled_amount.max(0) as u128),
+                settled_by: Some(settled_by),
+            },
+        };
+        Record {
+            chain: self.chain.clone(),
+            escrow_id: log.escrow_id,
+            height: log.ledger as u64,
+            tx: log.tx_hash,
+            event_key: log.id,
+            kind,
+        }
+    }
+}
+
+#[async_trait]
+impl EventSource for SorobanSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn chain(&self) -> &str {
+        &self.chain
+    }
+
+    async fn head(&self) -> Result<u64> {
+        Ok(u64::from(
+            self.rpc.latest_ledger().await.map_err(Error::Stellar)?,
+        ))
+    }
+
+    async fn next_batch(&self, position: Option<&str>) -> Result<Batch> {
+        let start = match position {
+            Some(cursor) => EventStart::Cursor(cursor.to_string()),
+            None => EventStart::Ledger(self.start_ledger),
+        };
+        let page = self
+            .rpc
+            .events(&self.contract, start, self.page_size)
+            .await
+            .map_err(Error::Stellar)?;
+        let mut records = Vec::new();
+        for event in &page.events {
+            if let Some(log) = EscrowLog::decode(event).map_err(Error::Stellar)? {
+                records.push(self.record(log));
+            }
+        }
+        let caught_up = page.events.len() < self.page_size as usize;
+        let height = match page.events.last() {
+            Some(event) if !caught_up => event.ledger,
+            _ => page.latest_ledger,
+        };
+        Ok(Batch {
+            caught_up,
+            records,
+            position: page.cursor,
+            height: u64::from(height),
+            // Closed ledgers are final
+            finalized: u64::from(height),
+            block_hash: None,
+        })
+    }
+}
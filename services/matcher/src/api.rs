@@ -0,0 +1,106 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use unite_telemetry::Health;
+
+use crate::engine::{Match, OrderState};
+use crate::matcher::Matcher;
+use crate::order::{Cancel, SignedBid, SignedOrder};
+use crate::Error;
+
+/// `POST /orders` takes a `SignedOrder` and `GET /orders` lists the open ones;
+/// `GET /orders/{order_id}` returns one, and `DELETE` with a `Cancel` body withdraws it;
+/// `POST /bids` takes a `SignedBid`; `GET /matches?after=&resolver=&order_id=` returns kept
+/// matches; `GET /healthz` and `GET /readyz` report liveness and whether the journal still
+/// takes writes
+pub fn router(matcher: Arc<Matcher>) -> Router {
+    let health = Health::default().with(matcher.clone()).router();
+    Router::new()
+        .route("/orders", get(orders).post(submit))
+        .route("/orders/{order_id}", get(order).delete(cancel))
+        .route("/bids", post(bid))
+        .route("/matches", get(matches))
+        .with_state(matcher)
+        .merge(health)
+}
+
+async fn submit(
+    State(matcher): State<Arc<Matcher>>,
+    Json(signed): Json<SignedOrder>,
+) -> Result<(StatusCode, Json<OrderState>), Error> {
+    Ok((StatusCode::CREATED, Json(matcher.submit(signed)?)))
+}
+
+async fn orders(State(matcher): State<Arc<Matcher>>) -> Json<Vec<OrderState>> {
+    Json(matcher.open_orders())
+}
+
+async fn order(
+    State(matcher): State<Arc<Matcher>>,
+    Path(order_id): Path<String>,
+) -> Result<Json<OrderState>, Error> {
+    matcher
+        .order(&order_id)
+        .map(Json)
+        .ok_or(Error::UnknownOrder(order_id))
+}
+
+async fn cancel(
+    State(matcher): State<Arc<Matcher>>,
+    Path(order_id): Path<String>,
+    Json(cancel): Json<Cancel>,
+) -> Result<Json<OrderState>, Error> {
+    Ok(Json(matcher.cancel(&order_id, &cancel.signature)?))
+}
+
+#[derive(Serialize)]
+struct Accepted {
+    /// What the auction requires for the bid's fill when it was taken
+    required_amount: u128,
+}
+
+async fn bid(
+    State(matcher): State<Arc<Matcher>>,
+    Json(signed): Json<SignedBid>,
+) -> Result<(StatusCode, Json<Accepted>), Error> {
+    let required_amount = matcher.bid(signed)?;
+    Ok((StatusCode::ACCEPTED, Json(Accepted { required_amount })))
+}
+
+#[derive(Deserialize)]
+struct MatchQuery {
+    #[serde(default)]
+    after: u64,
+    resolver: Option<String>,
+    order_id: Option<String>,
+}
+
+async fn matches(
+    State(matcher): State<Arc<Matcher>>,
+    Query(query): Query<MatchQuery>,
+) -> Json<Vec<Match>> {
+    Json(matcher.matches(
+        query.after,
+        query.resolver.as_deref(),
+        query.order_id.as_deref(),
+    ))
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Error::UnknownOrder(_) => StatusCode::NOT_FOUND,
+            Error::Signature(_) | Error::UnknownResolver(_) => StatusCode::UNAUTHORIZED,
+            Error::Duplicate(_) | Error::Closed(_) => StatusCode::CONFLICT,
+            Error::InvalidOrder(_) | Error::InvalidBid(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            Error::Halted(_) => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
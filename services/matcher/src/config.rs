@@ -0,0 +1,101 @@
+use std::collections::{HashMap, HashSet};
+use std::net::SocketAddr;
+use std::path::Path;
+
+use serde::Deserialize;
+use unite_config::{Problems, Validate};
+
+use crate::engine::Policy;
+use crate::journal::JournalConfig;
+use crate::Result;
+
+/// Matching engine settings, read from a TOML file with its environment's overlay and
+/// `UNITE_MATCHER__*` overrides
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    /// Address of the order, bid and match API
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    /// How often auctions advance and bids are matched
+    #[serde(default = "default_tick_millis")]
+    pub tick_millis: u64,
+    /// Most recent matches kept for `GET /matches`
+    #[serde(default = "default_keep_matches")]
+    pub keep_matches: usize,
+    #[serde(default)]
+    pub policy: Policy,
+    pub journal: JournalConfig,
+    /// Resolvers allowed to bid
+    pub resolvers: Vec<ResolverConfig>,
+}
+
+/// A resolver allowed to bid, identified by the key its bids are signed with
+#[derive(Clone, Debug, Deserialize)]
+pub struct ResolverConfig {
+    pub name: String,
+    /// Ed25519 public key, hex-encoded
+    #[serde(with = "hex")]
+    pub public_key: [u8; 32],
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(unite_config::load(path.as_ref(), "UNITE_MATCHER")?)
+    }
+
+    /// Resolvers' public keys by name
+    pub fn resolver_keys(&self) -> HashMap<String, [u8; 32]> {
+        self.resolvers
+            .iter()
+            .map(|resolver| (resolver.name.clone(), resolver.public_key))
+            .collect()
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.tick_millis > 0, || {
+            "tick_millis must be positive".to_string()
+        });
+        problems.check(self.keep_matches > 0, || {
+            "keep_matches must be positive".to_string()
+        });
+        problems.section("policy", &self.policy);
+        problems.check(self.journal.snapshot_every > 0, || {
+            "journal.snapshot_every must be positive".to_string()
+        });
+        let mut names = HashSet::new();
+        for resolver in &self.resolvers {
+            problems.check(names.insert(&resolver.name), || {
+                format!("resolver {} is listed twice", resolver.name)
+            });
+        }
+    }
+}
+
+impl Validate for Policy {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.destination_timelock_secs > 0, || {
+            "destination_timelock_secs must be positive".to_string()
+        });
+        problems.check(
+            self.source_timelock_secs > self.destination_timelock_secs,
+            || "source_timelock_secs must exceed destination_timelock_secs".to_string(),
+        );
+        problems.check(self.max_parts > 0, || {
+            "max_parts must be positive".to_string()
+        });
+    }
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8083))
+}
+
+fn default_tick_millis() -> u64 {
+    500
+}
+
+fn default_keep_matches() -> usize {
+    10_000
+}
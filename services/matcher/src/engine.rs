@@ -0,0 +1,522 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use unite_core::{EscrowParams, Hashlock};
+
+use crate::order::{self, Bid, MakerOrder, SignedBid, SignedOrder};
+use crate::{Error, Result};
+
+/// How orders are matched and the escrows of a match timed
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub struct Policy {
+    /// Source escrows' timelock, from the match; the maker's refund waits this long
+    #[serde(default = "default_source_timelock_secs")]
+    pub source_timelock_secs: u64,
+    /// Destination escrows' timelock, from the match; shorter than the source's, so the
+    /// resolver can still claim the source after the maker claims the destination late
+    #[serde(default = "default_destination_timelock_secs")]
+    pub destination_timelock_secs: u64,
+    /// Most hashlocks, and so fills, an order may be split into
+    #[serde(default = "default_max_parts")]
+    pub max_parts: usize,
+    /// How long closed orders stay queryable
+    #[serde(default = "default_retain_secs")]
+    pub retain_secs: u64,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            source_timelock_secs: default_source_timelock_secs(),
+            destination_timelock_secs: default_destination_timelock_secs(),
+            max_parts: default_max_parts(),
+            retain_secs: default_retain_secs(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    Open,
+    Filled,
+    Cancelled,
+    Expired,
+}
+
+/// An order in the book with how much of it is left
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrderState {
+    pub order_id: String,
+    pub order: SignedOrder,
+    pub status: Status,
+    /// Source amount not matched yet
+    pub remaining: u128,
+    /// Hashlocks used by matches so far
+    pub parts: usize,
+    pub received_at: u64,
+    pub closed_at: Option<u64>,
+}
+
+impl OrderState {
+    fn close(&mut self, status: Status, now: u64) {
+        self.status = status;
+        self.closed_at = Some(now);
+    }
+
+    fn parts_left(&self) -> usize {
+        self.order.order.hashlocks.len() - self.parts
+    }
+
+    /// Source amount a bid takes
+    fn fill(&self, bid: &Bid) -> u128 {
+        bid.fill.unwrap_or(self.remaining)
+    }
+
+    /// Whether `fill` can be matched now; the last hashlock must take all that is left
+    fn fits(&self, fill: u128) -> bool {
+        fill > 0 && fill <= self.remaining && (self.parts_left() > 1 || fill == self.remaining)
+    }
+}
+
+/// One escrow of a match, to be locked on its chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Leg {
+    pub chain: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    pub amount: u128,
+    /// Who locks the escrow and can refund it after `timelock`
+    pub sender: String,
+    /// Who claims it with the secret
+    pub receiver: String,
+    /// Unix seconds
+    pub timelock: u64,
+}
+
+/// A winning bid, and the two escrows that settle it
+///
+/// The maker locks `source` for the resolver and the resolver locks `destination` for the
+/// maker, both under `hashlock`; each escrow is identified by `escrow_order_id`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Match {
+    /// Position in the stream of matches, from 1
+    pub seq: u64,
+    pub order_id: String,
+    /// The order ID for whole orders, suffixed with `-{part}` for fills of split ones
+    pub escrow_order_id: String,
+    pub resolver: String,
+    /// Index of the order's hashlock this fill uses
+    pub part: usize,
+    #[serde(with = "hex")]
+    pub hashlock: Hashlock,
+    pub source: Leg,
+    pub destination: Leg,
+    pub matched_at: u64,
+}
+
+impl Match {
+    pub fn source_params(&self) -> EscrowParams {
+        self.params(&self.source)
+    }
+
+    pub fn destination_params(&self) -> EscrowParams {
+        self.params(&self.destination)
+    }
+
+    fn params(&self, leg: &Leg) -> EscrowParams {
+        EscrowParams {
+            order_id: self.escrow_order_id.clone(),
+            hashlock: self.hashlock,
+            receiver: leg.receiver.clone(),
+            token: leg.token.clone(),
+            amount: leg.amount,
+            timelock: leg.timelock,
+        }
+    }
+}
+
+/// A bid as held in the book
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+struct Held {
+    bid: Bid,
+    received_at: u64,
+}
+
+/// Everything the engine holds besides its settings, for snapshots
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Book {
+    seq: u64,
+    orders: Vec<OrderState>,
+    bids: BTreeMap<String, Vec<Held>>,
+}
+
+/// The order book and its auctions
+///
+/// Every change takes the time it happens at rather than reading the clock, so the same
+/// orders, bids and ticks always yield the same matches.
+#[derive(Debug)]
+pub struct Engine {
+    policy: Policy,
+    /// Registered resolvers' Ed25519 public keys by name
+    resolvers: HashMap<String, [u8; 32]>,
+    orders: BTreeMap<String, OrderState>,
+    bids: BTreeMap<String, Vec<Held>>,
+    /// Hashlocks of open orders, which a new order may not reuse
+    hashlocks: HashSet<Hashlock>,
+    seq: u64,
+    /// Bumped by every change, so callers can tell a tick that changed nothing
+    revision: u64,
+}
+
+impl Engine {
+    pub fn new(policy: Policy, resolvers: HashMap<String, [u8; 32]>) -> Self {
+        Self::restore(policy, resolvers, Book::default())
+    }
+
+    /// An engine holding `book`, as `book()` took it
+    pub fn restore(policy: Policy, resolvers: HashMap<String, [u8; 32]>, book: Book) -> Self {
+        let hashlocks = book
+            .orders
+            .iter()
+            .filter(|state| state.status == Status::Open)
+            .flat_map(|state| state.order.order.hashlocks.iter().copied())
+            .collect();
+        Self {
+            policy,
+            resolvers,
+            orders: book
+                .orders
+                .into_iter()
+                .map(|state| (state.order_id.clone(), state))
+                .collect(),
+            bids: book.bids,
+            hashlocks,
+            seq: book.seq,
+            revision: 0,
+        }
+    }
+
+    pub fn book(&self) -> Book {
+        Book {
+            seq: self.seq,
+            orders: self.orders.values().cloned().collect(),
+            bids: self.bids.clone(),
+        }
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    pub fn order(&self, order_id: &str) -> Option<&OrderState> {
+        self.orders.get(order_id)
+    }
+
+    /// Every order in the book, open or recently closed, by ID
+    pub fn orders(&self) -> impl Iterator<Item = &OrderState> {
+        self.orders.values()
+    }
+
+    /// Sequence number of the last match
+    pub fn seq(&self) -> u64 {
+        self.seq
+    }
+
+    /// Add a maker's order to the book, its auction running from its `start_time`
+    pub fn submit(&mut self, signed: SignedOrder, now: u64) -> Result<&OrderState> {
+        self.check_submit(&signed, now)?;
+        let order_id = signed.order.order_id();
+        self.hashlocks
+            .extend(signed.order.hashlocks.iter().copied());
+        self.revision += 1;
+        let state = OrderState {
+            order_id: order_id.clone(),
+            remaining: signed.order.source.amount,
+            order: signed,
+            status: Status::Open,
+            parts: 0,
+            received_at: now,
+            closed_at: None,
+        };
+        Ok(self.orders.entry(order_id).or_insert(state))
+    }
+
+    /// Whether `submit` would take the order, without taking it
+    pub fn check_submit(&self, signed: &SignedOrder, now: u64) -> Result<()> {
+        signed.verify()?;
+        let order_id = signed.order.order_id();
+        if self.orders.contains_key(&order_id) {
+            return Err(Error::Duplicate(order_id));
+        }
+        self.check(&signed.order, now)
+    }
+
+    fn check(&self, order: &MakerOrder, now: u64) -> Result<()> {
+        let invalid = |reason: &str| Err(Error::InvalidOrder(reason.to_string()));
+        if order.source.chain == order.destination.chain {
+            return invalid("source and destination are on the same chain");
+        }
+        if order.source.amount == 0 || order.taking_amount == 0 {
+            return invalid("amounts must be positive");
+        }
+        if order.expires_at <= now {
+            return invalid("already expired");
+        }
+        if order.hashlocks.is_empty() || order.hashlocks.len() > self.policy.max_parts {
+            return invalid(&format!(
+                "between 1 and {} hashlocks are required",
+                self.policy.max_parts
+            ));
+        }
+        if order.hashlocks.len() as u128 > order.source.amount {
+            return invalid("more hashlocks than units to fill");
+        }
+        let distinct: HashSet<_> = order.hashlocks.iter().collect();
+        if distinct.len() < order.hashlocks.len() {
+            return invalid("hashlocks repeat");
+        }
+        if order
+            .hashlocks
+            .iter()
+            .any(|hashlock| self.hashlocks.contains(hashlock))
+        {
+            return invalid("a hashlock is already used by an open order");
+        }
+        Ok(())
+    }
+
+    /// Withdraw an open order on the maker's signature over its ID
+    pub fn cancel(
+        &mut self,
+        order_id: &str,
+        signature: &[u8; 64],
+        now: u64,
+    ) -> Result<&OrderState> {
+        self.check_cancel(order_id, signature)?;
+        self.close(order_id, Status::Cancelled, now);
+        Ok(&self.orders[order_id])
+    }
+
+    /// Whether `cancel` would withdraw the order, without withdrawing it
+    pub fn check_cancel(&self, order_id: &str, signature: &[u8; 64]) -> Result<()> {
+        let state = self
+            .orders
+            .get(order_id)
+            .ok_or_else(|| Error::UnknownOrder(order_id.to_string()))?;
+        order::verify(
+            &state.order.order.maker,
+            &order::cancel_digest(order_id),
+            signature,
+        )?;
+        if state.status != Status::Open {
+            return Err(Error::Closed(order_id.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Take a resolver's bid, replacing its earlier bid on the same order; returns the
+    /// destination amount the auction requires for the bid's fill right now
+    pub fn bid(&mut self, signed: SignedBid, now: u64) -> Result<u128> {
+        let required = self.check_bid(&signed, now)?;
+        let held = self.bids.entry(signed.bid.order_id.clone()).or_default();
+        held.retain(|held| held.bid.resolver != signed.bid.resolver);
+        held.push(Held {
+            bid: signed.bid,
+            received_at: now,
+        });
+        self.revision += 1;
+        Ok(required)
+    }
+
+    /// Whether `bid` would take the bid, and what the auction requires for it, without
+    /// taking it
+    pub fn check_bid(&self, signed: &SignedBid, now: u64) -> Result<u128> {
+        let bid = &signed.bid;
+        let key = self
+            .resolvers
+            .get(&bid.resolver)
+            .ok_or_else(|| Error::UnknownResolver(bid.resolver.clone()))?;
+        order::verify(key, &bid.digest(), &signed.signature)?;
+        let state = self
+            .orders
+            .get(&bid.order_id)
+            .ok_or_else(|| Error::UnknownOrder(bid.order_id.clone()))?;
+        let invalid = |reason: String| Err(Error::InvalidBid(reason));
+        if state.status != Status::Open {
+            return Err(Error::Closed(bid.order_id.clone()));
+        }
+        if bid.valid_until <= now {
+            return invalid("already lapsed".into());
+        }
+        let fill = state.fill(bid);
+        if !state.fits(fill) {
+            return invalid(format!(
+                "fill must be between 1 and the remaining {}, and all of it for the last part",
+                state.remaining
+            ));
+        }
+        let reserve = state.order.order.reserve(fill);
+        if bid.amount < reserve {
+            return invalid(format!("below the order's reserve of {reserve}"));
+        }
+        Ok(state.order.order.required_amount(fill, now))
+    }
+
+    /// Advance every auction to `now`: expire orders and lapsed bids, then match each order's
+    /// best bids for as long as they meet what its auction requires
+    ///
+    /// The best bid pays the most per unit of the source amount, the earlier one on a tie.
+    pub fn tick(&mut self, now: u64) -> Vec<Match> {
+        let expired: Vec<String> = self
+            .orders
+            .values()
+            .filter(|state| state.status == Status::Open && state.order.order.expires_at <= now)
+            .map(|state| state.order_id.clone())
+            .collect();
+        for order_id in expired {
+            self.close(&order_id, Status::Expired, now);
+        }
+        let retain_secs = self.policy.retain_secs;
+        let orders = self.orders.len();
+        self.orders.retain(|_, state| {
+            state
+                .closed_at
+                .is_none_or(|closed_at| closed_at.saturating_add(retain_secs) > now)
+        });
+        if self.orders.len() < orders {
+            self.revision += 1;
+        }
+
+        let mut matches = Vec::new();
+        let order_ids: Vec<String> = self.bids.keys().cloned().collect();
+        for order_id in order_ids {
+            while let Some(found) = self.best(&order_id, now) {
+                matches.push(self.fill(&order_id, found, now));
+                if self.orders[&order_id].status != Status::Open {
+                    break;
+                }
+            }
+        }
+        self.bids.retain(|_, held| !held.is_empty());
+        matches
+    }
+
+    /// Index of the best bid on an order that meets its auction at `now`, dropping bids that
+    /// lapsed or no longer fit what is left
+    fn best(&mut self, order_id: &str, now: u64) -> Option<usize> {
+        let state = &self.orders[order_id];
+        let held = self.bids.get_mut(order_id)?;
+        let bids = held.len();
+        held.retain(|held| held.bid.valid_until > now && state.fits(state.fill(&held.bid)));
+        if held.len() < bids {
+            self.revision += 1;
+        }
+        let order = &state.order.order;
+        held.iter()
+            .enumerate()
+            .filter(|(_, held)| {
+                let fill = state.fill(&held.bid);
+                held.bid.amount >= order.required_amount(fill, now)
+            })
+            .max_by(|(_, a), (_, b)| {
+                let rate = |held: &Held| (held.bid.amount, state.fill(&held.bid));
+                compare_rates(rate(a), rate(b)).then(b.received_at.cmp(&a.received_at))
+            })
+            .map(|(index, _)| index)
+    }
+
+    /// Match the bid at `index` against its order at the auction's current price
+    fn fill(&mut self, order_id: &str, index: usize, now: u64) -> Match {
+        let Held { bid, .. } = self
+            .bids
+            .get_mut(order_id)
+            .expect("best found a bid")
+            .remove(index);
+        let state = self
+            .orders
+            .get_mut(order_id)
+            .expect("bids are for known orders");
+        let fill = state.fill(&bid);
+        let order = &state.order.order;
+        let part = state.parts;
+        let escrow_order_id = if order.hashlocks.len() == 1 {
+            order_id.to_string()
+        } else {
+            format!("{order_id}-{part}")
+        };
+        self.seq += 1;
+        self.revision += 1;
+        let decision = Match {
+            seq: self.seq,
+            order_id: order_id.to_string(),
+            escrow_order_id,
+            resolver: bid.resolver,
+            part,
+            hashlock: order.hashlocks[part],
+            source: Leg {
+                chain: order.source.chain.clone(),
+                token: order.source.token.clone(),
+                amount: fill,
+                sender: order.source.sender.clone(),
+                receiver: bid.receiver,
+                timelock: now.saturating_add(self.policy.source_timelock_secs),
+            },
+            destination: Leg {
+                chain: order.destination.chain.clone(),
+                token: order.destination.token.clone(),
+                amount: order.required_amount(fill, now),
+                sender: bid.sender,
+                receiver: order.destination.receiver.clone(),
+                timelock: now.saturating_add(self.policy.destination_timelock_secs),
+            },
+            matched_at: now,
+        };
+        state.parts += 1;
+        state.remaining -= fill;
+        if state.remaining == 0 {
+            self.close(order_id, Status::Filled, now);
+        }
+        decision
+    }
+
+    /// Close an order, dropping its bids and freeing its unused hashlocks
+    fn close(&mut self, order_id: &str, status: Status, now: u64) {
+        let Some(state) = self.orders.get_mut(order_id) else {
+            return;
+        };
+        state.close(status, now);
+        self.revision += 1;
+        for hashlock in &state.order.order.hashlocks {
+            self.hashlocks.remove(hashlock);
+        }
+        self.bids.remove(order_id);
+    }
+}
+
+/// Compare two `(amount, fill)` rates by amount per unit filled
+fn compare_rates(a: (u128, u128), b: (u128, u128)) -> Ordering {
+    type U256 = ruint::aliases::U256;
+    (U256::from(a.0) * U256::from(b.1)).cmp(&(U256::from(b.0) * U256::from(a.1)))
+}
+
+fn default_source_timelock_secs() -> u64 {
+    7_200
+}
+
+fn default_destination_timelock_secs() -> u64 {
+    3_600
+}
+
+fn default_max_parts() -> usize {
+    16
+}
+
+fn default_retain_secs() -> u64 {
+    3_600
+}
@@ -0,0 +1,222 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+use crate::engine::{Book, Match};
+use crate::order::{SignedBid, SignedOrder};
+use crate::{Error, Result};
+
+/// Where and how the book is persisted
+#[derive(Clone, Debug, Deserialize)]
+pub struct JournalConfig {
+    /// Directory holding `snapshot.json` and `wal.jsonl`
+    pub dir: PathBuf,
+    /// Entries logged between snapshots; each snapshot empties the log
+    #[serde(default = "default_snapshot_every")]
+    pub snapshot_every: u64,
+    /// Flush every entry to disk before answering, so an acknowledged order or bid survives
+    /// a power loss and not only a crash
+    #[serde(default = "default_fsync")]
+    pub fsync: bool,
+}
+
+/// A change to the book, logged once the engine has checked it and before it applies
+///
+/// Externally tagged, since serde buffers internally tagged enums in a form without `u128`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Entry {
+    Submit {
+        order: SignedOrder,
+        at: u64,
+    },
+    Cancel {
+        order_id: String,
+        #[serde(with = "hex")]
+        signature: [u8; 64],
+        at: u64,
+    },
+    Bid {
+        bid: SignedBid,
+        at: u64,
+    },
+    /// A tick that changed the book; ticks that changed nothing are not logged
+    Tick {
+        at: u64,
+    },
+}
+
+/// One line of the log
+#[derive(Serialize, Deserialize)]
+struct Record {
+    index: u64,
+    entry: Entry,
+}
+
+/// The book and the recent matches as of log entry `index`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub index: u64,
+    pub book: Book,
+    pub matches: Vec<Match>,
+}
+
+/// What survived the last run: its latest snapshot and the entries logged after it, in order
+#[derive(Debug, Default)]
+pub struct Recovered {
+    pub snapshot: Snapshot,
+    pub entries: Vec<Entry>,
+}
+
+/// Write-ahead log of book changes with periodic snapshots
+///
+/// Entries are appended one JSON line each and numbered from 1. A snapshot is written to a
+/// temporary file and renamed over the previous one, and only then is the log emptied, so a
+/// crash in between leaves entries the snapshot already covers, which recovery skips by
+/// number. A torn last line, from a crash mid-append, is cut off.
+#[derive(Debug)]
+pub struct Journal {
+    dir: PathBuf,
+    wal: File,
+    fsync: bool,
+    snapshot_every: u64,
+    /// Number of the last entry logged
+    index: u64,
+    /// Entries logged since the last snapshot
+    pending: u64,
+}
+
+impl Journal {
+    /// Open the journal in `config.dir`, creating it when missing, and read back what it holds
+    pub fn open(config: &JournalConfig) -> Result<(Self, Recovered)> {
+        fs::create_dir_all(&config.dir)?;
+        let snapshot = match fs::read(config.dir.join(SNAPSHOT)) {
+            Ok(json) => serde_json::from_slice::<Snapshot>(&json)?,
+            Err(error) if error.kind() == ErrorKind::NotFound => Snapshot::default(),
+            Err(error) => return Err(error.into()),
+        };
+        let path = config.dir.join(WAL);
+        let entries = read_log(&path, snapshot.index)?;
+        let index = snapshot.index + entries.len() as u64;
+        let wal = OpenOptions::new().create(true).append(true).open(&path)?;
+        let journal = Self {
+            dir: config.dir.clone(),
+            wal,
+            fsync: config.fsync,
+            snapshot_every: config.snapshot_every,
+            index,
+            pending: entries.len() as u64,
+        };
+        Ok((journal, Recovered { snapshot, entries }))
+    }
+
+    /// Number of the last entry logged
+    pub fn index(&self) -> u64 {
+        self.index
+    }
+
+    pub fn append(&mut self, entry: &Entry) -> Result<()> {
+        let record = Record {
+            index: self.index + 1,
+            entry: entry.clone(),
+        };
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        self.wal.write_all(&line)?;
+        if self.fsync {
+            self.wal.sync_data()?;
+        }
+        self.index += 1;
+        self.pending += 1;
+        Ok(())
+    }
+
+    /// Whether enough entries were logged since the last snapshot to take another
+    pub fn is_due(&self) -> bool {
+        self.pending >= self.snapshot_every
+    }
+
+    /// Persist `book` and `matches` as of the last entry, then empty the log
+    pub fn snapshot(&mut self, book: Book, matches: Vec<Match>) -> Result<()> {
+        let snapshot = Snapshot {
+            index: self.index,
+            book,
+            matches,
+        };
+        let path = self.dir.join(SNAPSHOT);
+        let temporary = path.with_extension("json.tmp");
+        let mut file = File::create(&temporary)?;
+        file.write_all(&serde_json::to_vec(&snapshot)?)?;
+        file.sync_all()?;
+        fs::rename(temporary, path)?;
+        self.wal.set_len(0)?;
+        self.wal.sync_all()?;
+        self.pending = 0;
+        Ok(())
+    }
+}
+
+const SNAPSHOT: &str = "snapshot.json";
+const WAL: &str = "wal.jsonl";
+
+/// Entries in the log at `path` after entry `after`, cutting off a torn last line
+fn read_log(path: &Path, after: u64) -> Result<Vec<Entry>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+    let mut reader = BufReader::new(file);
+    let mut entries = Vec::new();
+    let mut previous = None;
+    let mut offset = 0u64;
+    let mut line = Vec::new();
+    loop {
+        line.clear();
+        let read = reader.read_until(b'\n', &mut line)?;
+        if read == 0 {
+            break;
+        }
+        // Only the last line can lack its newline, and only when its append was cut short
+        if !line.ends_with(b"\n") {
+            warn!(
+                "{}: cutting off a torn entry at byte {offset}",
+                path.display()
+            );
+            OpenOptions::new().write(true).open(path)?.set_len(offset)?;
+            break;
+        }
+        let record: Record = serde_json::from_slice(&line).map_err(|error| {
+            Error::Journal(format!(
+                "{}: entry at byte {offset}: {error}",
+                path.display()
+            ))
+        })?;
+        // Entries the snapshot covers may precede the rest after a crash mid-snapshot
+        let expected = previous.unwrap_or(after).max(after) + 1;
+        if record.index > after && record.index != expected {
+            return Err(Error::Journal(format!(
+                "{}: entry {} where {expected} was expected",
+                path.display(),
+                record.index
+            )));
+        }
+        if record.index > after {
+            entries.push(record.entry);
+        }
+        previous = Some(record.index);
+        offset += read as u64;
+    }
+    Ok(entries)
+}
+
+fn default_snapshot_every() -> u64 {
+    1_000
+}
+
+fn default_fsync() -> bool {
+    true
+}
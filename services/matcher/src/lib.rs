@@ -0,0 +1,58 @@
+// Matching Engine
+// Turns the escrow primitive into a marketplace. Makers submit orders signed with their
+// Ed25519 key, each naming what they lock on the source chain, where they want to be paid
+// and a Dutch auction on the destination amount; an order may carry several hashlocks to be
+// split into that many fills. Registered resolvers bid signed limits, and on every tick the
+// engine advances each auction and matches the best-paying bids that meet it at the current
+// price. A match names both escrows to lock: the maker's source escrow for the resolver and
+// the resolver's destination escrow for the maker, under the same hashlock and with
+// staggered timelocks. Matches are served over HTTP for makers and resolvers to act on.
+// Every order, cancellation, bid and tick that changes the book goes to a write-ahead log,
+// snapshotted every so often; a restart loads the last snapshot and replays the log through
+// the engine, which takes its time from the entries, so open orders, running auctions,
+// partial fills and recent matches come back as they were.
+
+pub mod api;
+pub mod config;
+pub mod engine;
+pub mod journal;
+pub mod matcher;
+pub mod order;
+
+pub use config::{Config, ResolverConfig};
+pub use engine::{Book, Engine, Leg, Match, OrderState, Policy, Status};
+pub use journal::{Entry, Journal, JournalConfig};
+pub use matcher::Matcher;
+pub use order::{Bid, Cancel, MakerOrder, SignedBid, SignedOrder, Source};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error("signature: {0}")]
+    Signature(String),
+    #[error("invalid order: {0}")]
+    InvalidOrder(String),
+    #[error("invalid bid: {0}")]
+    InvalidBid(String),
+    #[error("order {0} is already in the book")]
+    Duplicate(String),
+    #[error("order {0} is not in the book")]
+    UnknownOrder(String),
+    #[error("order {0} is closed")]
+    Closed(String),
+    #[error("resolver {0} is not registered")]
+    UnknownResolver(String),
+    #[error("journal: {0}")]
+    Journal(String),
+    #[error("matching halted: {0}")]
+    Halted(String),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Load(#[from] unite_config::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,35 @@
+use std::time::Duration;
+
+use log::info;
+use tokio::net::TcpListener;
+use unite_matcher::{api, Config, Matcher};
+
+/// `unite-matcher [config.toml]`, logging through `RUST_LOG`
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "matcher.toml".to_string());
+    let config = Config::load(&path)?;
+
+    let matcher = Matcher::recover(
+        config.policy,
+        config.resolver_keys(),
+        &config.journal,
+        config.keep_matches,
+    )?;
+    info!("{} resolvers registered", config.resolvers.len());
+    let ticker = matcher.clone();
+    let interval = Duration::from_millis(config.tick_millis);
+    tokio::spawn(async move { ticker.run(interval).await });
+
+    let listener = TcpListener::bind(config.listen).await?;
+    info!("listening on {}", config.listen);
+    axum::serve(listener, api::router(matcher))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
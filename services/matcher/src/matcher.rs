@@ -0,0 +1,276 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use log::{error, info, warn};
+use tokio::sync::broadcast;
+use unite_telemetry::{Probe, Reading};
+
+use crate::engine::{Engine, Match, OrderState, Policy, Status};
+use crate::journal::{Entry, Journal, JournalConfig};
+use crate::order::{SignedBid, SignedOrder};
+use crate::{Error, Result};
+
+/// Matches buffered per subscriber before it is dropped as lagging
+const MATCHES: usize = 1_024;
+
+/// The engine behind a lock, driven by the clock and journaled, with recent matches kept for
+/// polling and broadcast to subscribers
+///
+/// Orders, cancellations and bids are checked, logged and only then applied, so nothing is
+/// acknowledged before it is on disk. A tick is logged after it runs, when it changed the
+/// book, and its matches are published only once logged. Should logging fail, the matcher
+/// halts: it refuses further changes and stops ticking until restarted, when the book is
+/// recovered from what did reach the disk.
+pub struct Matcher {
+    inner: Mutex<Inner>,
+    recent: Mutex<VecDeque<Match>>,
+    keep: usize,
+    matches: broadcast::Sender<Match>,
+}
+
+struct Inner {
+    engine: Engine,
+    journal: Journal,
+    /// Why logging failed, once it has
+    halted: Option<String>,
+}
+
+impl Inner {
+    fn log(&mut self, entry: &Entry) -> Result<()> {
+        self.journal.append(entry).inspect_err(|error| {
+            error!("journal: {error}; matching halted");
+            self.halted = Some(error.to_string());
+        })
+    }
+}
+
+impl Matcher {
+    /// Rebuild the book from the journal's snapshot and replay the entries logged after it,
+    /// which yields the book the last run had as long as `[policy]` and `[[resolvers]]` are
+    /// unchanged
+    pub fn recover(
+        policy: Policy,
+        resolvers: HashMap<String, [u8; 32]>,
+        journal: &JournalConfig,
+        keep: usize,
+    ) -> Result<Arc<Self>> {
+        let (journal, recovered) = Journal::open(journal)?;
+        let mut engine = Engine::restore(policy, resolvers, recovered.snapshot.book);
+        let mut recent = VecDeque::from(recovered.snapshot.matches);
+        let replayed = recovered.entries.len();
+        for entry in recovered.entries {
+            match apply(&mut engine, entry) {
+                Ok(matches) => recent.extend(matches),
+                Err(error) => warn!("journal entry no longer applies: {error}"),
+            }
+        }
+        trim(&mut recent, keep);
+        info!(
+            "recovered {} open orders, replaying {replayed} journal entries",
+            engine
+                .orders()
+                .filter(|state| state.status == Status::Open)
+                .count()
+        );
+        Ok(Arc::new(Self {
+            inner: Mutex::new(Inner {
+                engine,
+                journal,
+                halted: None,
+            }),
+            recent: Mutex::new(recent),
+            keep,
+            matches: broadcast::channel(MATCHES).0,
+        }))
+    }
+
+    pub fn submit(&self, signed: SignedOrder) -> Result<OrderState> {
+        let now = now();
+        let mut inner = self.lock()?;
+        inner.engine.check_submit(&signed, now)?;
+        inner.log(&Entry::Submit {
+            order: signed.clone(),
+            at: now,
+        })?;
+        let state = inner.engine.submit(signed, now)?.clone();
+        self.snapshot_if_due(&mut inner);
+        info!("order {} open", state.order_id);
+        Ok(state)
+    }
+
+    pub fn cancel(&self, order_id: &str, signature: &[u8; 64]) -> Result<OrderState> {
+        let now = now();
+        let mut inner = self.lock()?;
+        inner.engine.check_cancel(order_id, signature)?;
+        inner.log(&Entry::Cancel {
+            order_id: order_id.to_string(),
+            signature: *signature,
+            at: now,
+        })?;
+        let state = inner.engine.cancel(order_id, signature, now)?.clone();
+        self.snapshot_if_due(&mut inner);
+        info!("order {order_id} cancelled");
+        Ok(state)
+    }
+
+    /// Take a bid, returning what the auction requires for its fill right now
+    pub fn bid(&self, signed: SignedBid) -> Result<u128> {
+        let now = now();
+        let mut inner = self.lock()?;
+        inner.engine.check_bid(&signed, now)?;
+        inner.log(&Entry::Bid {
+            bid: signed.clone(),
+            at: now,
+        })?;
+        let required = inner.engine.bid(signed, now)?;
+        self.snapshot_if_due(&mut inner);
+        Ok(required)
+    }
+
+    pub fn order(&self, order_id: &str) -> Option<OrderState> {
+        self.inner.lock().unwrap().engine.order(order_id).cloned()
+    }
+
+    pub fn open_orders(&self) -> Vec<OrderState> {
+        self.inner
+            .lock()
+            .unwrap()
+            .engine
+            .orders()
+            .filter(|state| state.status == Status::Open)
+            .cloned()
+            .collect()
+    }
+
+    /// Kept matches after `after`, optionally only one resolver's or one order's
+    pub fn matches(
+        &self,
+        after: u64,
+        resolver: Option<&str>,
+        order_id: Option<&str>,
+    ) -> Vec<Match> {
+        self.recent
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|decision| decision.seq > after)
+            .filter(|decision| resolver.is_none_or(|resolver| decision.resolver == resolver))
+            .filter(|decision| order_id.is_none_or(|order_id| decision.order_id == order_id))
+            .cloned()
+            .collect()
+    }
+
+    /// Every match from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<Match> {
+        self.matches.subscribe()
+    }
+
+    /// Advance the auctions to the current time and publish the matches made
+    pub fn tick(&self) -> Result<usize> {
+        let now = now();
+        let mut inner = self.lock()?;
+        let revision = inner.engine.revision();
+        let matches = inner.engine.tick(now);
+        if inner.engine.revision() == revision {
+            return Ok(0);
+        }
+        inner.log(&Entry::Tick { at: now })?;
+        {
+            let mut recent = self.recent.lock().unwrap();
+            for decision in &matches {
+                info!(
+                    "order {} part {} matched to {} for {}",
+                    decision.order_id,
+                    decision.part,
+                    decision.resolver,
+                    decision.destination.amount
+                );
+                recent.push_back(decision.clone());
+                // Sending fails only without subscribers
+                let _ = self.matches.send(decision.clone());
+            }
+            trim(&mut recent, self.keep);
+        }
+        self.snapshot_if_due(&mut inner);
+        Ok(matches.len())
+    }
+
+    /// Tick every `interval` until the task is dropped or the matcher halts
+    pub async fn run(&self, interval: Duration) {
+        let mut ticks = tokio::time::interval(interval);
+        ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+        loop {
+            ticks.tick().await;
+            if let Err(error) = self.tick() {
+                error!("ticks stopped: {error}");
+                return;
+            }
+        }
+    }
+
+    /// The engine, unless the matcher halted
+    fn lock(&self) -> Result<MutexGuard<'_, Inner>> {
+        let inner = self.inner.lock().unwrap();
+        match &inner.halted {
+            Some(reason) => Err(Error::Halted(reason.clone())),
+            None => Ok(inner),
+        }
+    }
+
+    /// Snapshot the book once enough entries were logged; a failed snapshot leaves the log
+    /// in place, so it is retried with the next entry
+    fn snapshot_if_due(&self, inner: &mut Inner) {
+        if !inner.journal.is_due() {
+            return;
+        }
+        let matches = self.recent.lock().unwrap().iter().cloned().collect();
+        if let Err(error) = inner.journal.snapshot(inner.engine.book(), matches) {
+            warn!("journal snapshot: {error}");
+        }
+    }
+}
+
+#[async_trait]
+impl Probe for Matcher {
+    fn name(&self) -> String {
+        "journal".to_string()
+    }
+
+    /// Down once the matcher halted; the height is the last entry logged
+    async fn probe(&self) -> std::result::Result<Reading, String> {
+        let inner = self.inner.lock().unwrap();
+        match &inner.halted {
+            Some(reason) => Err(reason.clone()),
+            None => Ok(Reading::height(inner.journal.index())),
+        }
+    }
+}
+
+/// Apply a logged entry during recovery, returning the matches a tick made
+fn apply(engine: &mut Engine, entry: Entry) -> Result<Vec<Match>> {
+    match entry {
+        Entry::Submit { order, at } => engine.submit(order, at).map(|_| Vec::new()),
+        Entry::Cancel {
+            order_id,
+            signature,
+            at,
+        } => engine.cancel(&order_id, &signature, at).map(|_| Vec::new()),
+        Entry::Bid { bid, at } => engine.bid(bid, at).map(|_| Vec::new()),
+        Entry::Tick { at } => Ok(engine.tick(at)),
+    }
+}
+
+fn trim(recent: &mut VecDeque<Match>, keep: usize) {
+    while recent.len() > keep {
+        recent.pop_front();
+    }
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,282 @@
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use sha2::{Digest, Sha256};
+use unite_core::Hashlock;
+use unite_resolver::order::RATE_BUMP_BASE;
+use unite_resolver::{Auction, Destination};
+
+use crate::{Error, Result};
+
+/// What the maker locks on the source chain
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Source {
+    pub chain: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    pub amount: u128,
+    /// Maker's address on the source chain, the source escrows' sender
+    pub sender: String,
+}
+
+/// An order as the maker signs it
+///
+/// The signature covers `digest`, which hashes every field in declaration order, so a client
+/// signs without agreeing on a JSON encoding with the matcher.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MakerOrder {
+    /// Maker's Ed25519 public key
+    #[serde(with = "hex")]
+    pub maker: [u8; 32],
+    /// Distinguishes otherwise identical orders
+    pub nonce: u64,
+    pub source: Source,
+    pub destination: Destination,
+    /// Least the maker accepts on the destination chain for the whole source amount, reached
+    /// when the auction ends
+    pub taking_amount: u128,
+    /// `None` for a fixed `taking_amount`
+    #[serde(default)]
+    pub auction: Option<Auction>,
+    /// One per fill the order may be split into, used in order; with a single one the order
+    /// fills whole
+    #[serde(with = "hex_list")]
+    pub hashlocks: Vec<Hashlock>,
+    /// Unix seconds after which the order is no longer matched
+    pub expires_at: u64,
+}
+
+impl MakerOrder {
+    /// SHA-256 over `unite-order-v1` and each field: integers big-endian, strings and byte
+    /// strings prefixed with their length as a big-endian `u32`, options and lists with a
+    /// presence byte or count
+    pub fn digest(&self) -> [u8; 32] {
+        let mut fields = Fields::new(b"unite-order-v1");
+        fields
+            .bytes(&self.maker)
+            .u64(self.nonce)
+            .str(&self.source.chain)
+            .option(self.source.token.as_deref())
+            .u128(self.source.amount)
+            .str(&self.source.sender)
+            .str(&self.destination.chain)
+            .option(self.destination.token.as_deref())
+            .str(&self.destination.receiver)
+            .u128(self.taking_amount);
+        match &self.auction {
+            Some(auction) => {
+                fields
+                    .u8(1)
+                    .u64(auction.start_time)
+                    .u64(auction.duration)
+                    .u32(auction.initial_rate_bump)
+                    .u32(auction.points.len() as u32);
+                for point in &auction.points {
+                    fields.u64(point.delay).u32(point.rate_bump);
+                }
+            }
+            None => {
+                fields.u8(0);
+            }
+        }
+        fields.u32(self.hashlocks.len() as u32);
+        for hashlock in &self.hashlocks {
+            fields.bytes(hashlock);
+        }
+        fields.u64(self.expires_at).finish()
+    }
+
+    /// The order's identifier, its hex-encoded digest
+    pub fn order_id(&self) -> String {
+        hex::encode(self.digest())
+    }
+
+    /// Destination amount the auction requires at `now` for `fill` of the source amount,
+    /// rounded up in the maker's favour
+    pub fn required_amount(&self, fill: u128, now: u64) -> u128 {
+        let rate_bump = self
+            .auction
+            .as_ref()
+            .map_or(0, |auction| auction.rate_bump(now));
+        let whole = mul_div_ceil(
+            self.taking_amount,
+            RATE_BUMP_BASE + rate_bump as u128,
+            RATE_BUMP_BASE,
+        );
+        mul_div_ceil(whole, fill, self.source.amount)
+    }
+
+    /// Least destination amount the order ever accepts for `fill`, once the auction ends
+    pub fn reserve(&self, fill: u128) -> u128 {
+        mul_div_ceil(self.taking_amount, fill, self.source.amount)
+    }
+}
+
+/// A maker order with the maker's signature over its digest
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedOrder {
+    pub order: MakerOrder,
+    #[serde(with = "hex")]
+    pub signature: [u8; 64],
+}
+
+impl SignedOrder {
+    pub fn verify(&self) -> Result<()> {
+        verify(&self.order.maker, &self.order.digest(), &self.signature)
+    }
+}
+
+/// A maker's request to withdraw an open order, signed over `cancel_digest`
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cancel {
+    #[serde(with = "hex")]
+    pub signature: [u8; 64],
+}
+
+/// SHA-256 over `unite-cancel-v1` and the order ID
+pub fn cancel_digest(order_id: &str) -> [u8; 32] {
+    Fields::new(b"unite-cancel-v1").str(order_id).finish()
+}
+
+/// A resolver's offer to take an order, or part of one
+///
+/// Bids are limits: one wins once the auction's required amount falls to it, and the
+/// resolver then pays what the auction requires at that moment, never more than `amount`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bid {
+    pub order_id: String,
+    /// Name the resolver is registered under
+    pub resolver: String,
+    /// Source amount to take; the whole remaining amount without it
+    #[serde(default)]
+    pub fill: Option<u128>,
+    /// Most the resolver pays on the destination chain for the fill
+    pub amount: u128,
+    /// Resolver's address on the source chain, the source escrow's receiver
+    pub receiver: String,
+    /// Resolver's address on the destination chain, the destination escrow's sender
+    pub sender: String,
+    /// Unix seconds after which the bid lapses
+    pub valid_until: u64,
+}
+
+impl Bid {
+    /// SHA-256 over `unite-bid-v1` and each field, encoded like `MakerOrder::digest`
+    pub fn digest(&self) -> [u8; 32] {
+        let fill = self.fill.map(u128::to_be_bytes);
+        Fields::new(b"unite-bid-v1")
+            .str(&self.order_id)
+            .str(&self.resolver)
+            .option(fill.as_ref().map(|fill| &fill[..]))
+            .u128(self.amount)
+            .str(&self.receiver)
+            .str(&self.sender)
+            .u64(self.valid_until)
+            .finish()
+    }
+}
+
+/// A bid with the resolver's signature over its digest
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedBid {
+    pub bid: Bid,
+    #[serde(with = "hex")]
+    pub signature: [u8; 64],
+}
+
+/// Check an Ed25519 `signature` by `public_key` over `digest`
+pub fn verify(public_key: &[u8; 32], digest: &[u8; 32], signature: &[u8; 64]) -> Result<()> {
+    let key = VerifyingKey::from_bytes(public_key)
+        .map_err(|_| Error::Signature("not an Ed25519 public key".into()))?;
+    key.verify_strict(digest, &Signature::from_bytes(signature))
+        .map_err(|_| Error::Signature("signature does not verify".into()))
+}
+
+/// `a * b / c` rounded up, without overflowing in between; saturates when the result does
+pub fn mul_div_ceil(a: u128, b: u128, c: u128) -> u128 {
+    type U256 = ruint::aliases::U256;
+    if c == 0 {
+        return 0;
+    }
+    let product = U256::from(a) * U256::from(b);
+    let c = U256::from(c);
+    let quotient = product.div_ceil(c);
+    u128::try_from(quotient).unwrap_or(u128::MAX)
+}
+
+/// Length-prefixed field encoding fed into SHA-256
+struct Fields(Sha256);
+
+impl Fields {
+    fn new(domain: &[u8]) -> Self {
+        let mut fields = Self(Sha256::new());
+        fields.bytes(domain);
+        fields
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+        self.0.update((bytes.len() as u32).to_be_bytes());
+        self.0.update(bytes);
+        self
+    }
+
+    fn str(&mut self, value: &str) -> &mut Self {
+        self.bytes(value.as_bytes())
+    }
+
+    fn option(&mut self, value: Option<impl AsRef<[u8]>>) -> &mut Self {
+        match value {
+            Some(value) => self.u8(1).bytes(value.as_ref()),
+            None => self.u8(0),
+        }
+    }
+
+    fn u8(&mut self, value: u8) -> &mut Self {
+        self.0.update([value]);
+        self
+    }
+
+    fn u32(&mut self, value: u32) -> &mut Self {
+        self.0.update(value.to_be_bytes());
+        self
+    }
+
+    fn u64(&mut self, value: u64) -> &mut Self {
+        self.0.update(value.to_be_bytes());
+        self
+    }
+
+    fn u128(&mut self, value: u128) -> &mut Self {
+        self.0.update(value.to_be_bytes());
+        self
+    }
+
+    fn finish(&mut self) -> [u8; 32] {
+        std::mem::take(&mut self.0).finalize().into()
+    }
+}
+
+/// Hashlocks as a list of hex strings
+mod hex_list {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        hashlocks: &[Hashlock],
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_seq(hashlocks.iter().map(hex::encode))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Vec<Hashlock>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|hashlock| {
+                hex::decode(hashlock)
+                    .ok()
+                    .and_then(|bytes| bytes.try_into().ok())
+                    .ok_or_else(|| serde::de::Error::custom(format!("invalid hashlock {hashlock}")))
+            })
+            .collect()
+    }
+}
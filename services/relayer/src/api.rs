@@ -0,0 +1,57 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+
+use crate::relayer::Relayer;
+use crate::swap::{Announcement, Swap};
+use crate::Error;
+
+/// `POST /announce` takes an `Announcement`; `GET /swaps` and `GET /swaps/{order_id}` return
+/// persisted swap state; `GET /healthz` and `GET /readyz` report liveness and chain RPC health
+pub fn router(relayer: Arc<Relayer>) -> Router {
+    let health = relayer.health().router();
+    Router::new()
+        .route("/announce", post(announce))
+        .route("/swaps", get(swaps))
+        .route("/swaps/{order_id}", get(swap))
+        .with_state(relayer)
+        .merge(health)
+}
+
+async fn announce(
+    State(relayer): State<Arc<Relayer>>,
+    Json(announcement): Json<Announcement>,
+) -> Result<StatusCode, Error> {
+    relayer.announce(announcement).await?;
+    Ok(StatusCode::ACCEPTED)
+}
+
+async fn swaps(State(relayer): State<Arc<Relayer>>) -> Result<Json<Vec<Swap>>, Error> {
+    Ok(Json(relayer.swaps()?))
+}
+
+async fn swap(
+    State(relayer): State<Arc<Relayer>>,
+    Path(order_id): Path<String>,
+) -> Result<Json<Swap>, Error> {
+    relayer
+        .swap(&order_id)?
+        .map(Json)
+        .ok_or(Error::UnknownOrder(order_id))
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Error::UnknownOrder(_) => StatusCode::NOT_FOUND,
+            Error::UnknownChain(_) | Error::Conflict(_) => StatusCode::BAD_REQUEST,
+            Error::Settled => StatusCode::CONFLICT,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
@@ -0,0 +1,587 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use alloy::primitives::Address;
+use alloy::signers::local::PrivateKeySigner;
+use serde::Deserialize;
+use unite_alert::AlertConfig;
+use unite_config::{Layers, Problems, Validate};
+use unite_core::{BumpPolicy, ChainAdapter};
+use unite_frost::CoordinatorConfig;
+use unite_secrets::{SecretStore, SecretsConfig};
+use unite_telemetry::TracingConfig;
+use zeroize::Zeroizing;
+
+use crate::swap::Policy;
+use crate::{Error, Result};
+
+/// Relayer settings, read from a TOML file with its environment's overlay and `UNITE_RELAYER__*`
+/// overrides; keys and mnemonics are read from the environment variables or secret store
+/// entries the file names, never from the file itself
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    /// Address of the announcement API
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    /// Address to serve Prometheus metrics on, at `/metrics`; off without it
+    pub metrics_listen: Option<SocketAddr>,
+    /// Log format and OTLP export
+    #[serde(default)]
+    pub tracing: TracingConfig,
+    /// Directory swaps are persisted in
+    pub store: PathBuf,
+    #[serde(default)]
+    pub policy: Policy,
+    /// How often to check the config files for edits and apply a changed `[policy]`; other
+    /// changes wait for a restart. Off without it
+    pub reload_secs: Option<u64>,
+    /// Where keys named by `*_store` fields are kept, and revealed secrets while swaps are open
+    pub secrets: Option<SecretsConfig>,
+    /// Chats stuck swaps, closing timelocks and failed submissions are sent to
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    pub chains: Vec<ChainConfig>,
+}
+
+/// One chain the relayer can watch and settle on, with the account it signs claims and
+/// refunds as
+///
+/// Each key is read from the environment variable its `*_env` field names or from the secret
+/// store entry its `*_store` field names; exactly one of the two is given. Stellar accounts
+/// can instead be held by a FROST signer group, named with a `frost` table.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum ChainConfig {
+    /// Soroban escrow contract (`contracts/stellar`)
+    Stellar {
+        rpc_url: String,
+        /// RPC endpoints of the same network to fail over to
+        #[serde(default)]
+        fallback_rpc_urls: Vec<String>,
+        /// Endpoints each transaction is sent to at once
+        #[serde(default = "default_hedge")]
+        hedge: usize,
+        contract: String,
+        network_passphrase: String,
+        secret_key_env: Option<String>,
+        secret_key_store: Option<String>,
+        /// Signer nodes holding the account's key in shares, instead of `secret_key_*`
+        frost: Option<CoordinatorConfig>,
+        #[serde(default)]
+        fee_bump: FeeBumpConfig,
+        /// Create escrows locked with SHA-256 rather than keccak256, so they pair with
+        /// `stellar-classic`, Bitcoin and TON legs instead of EVM ones
+        #[serde(default)]
+        sha256: bool,
+    },
+    /// Claimable-balance HTLC for classic Stellar assets
+    StellarClassic {
+        rpc_url: String,
+        #[serde(default)]
+        fallback_rpc_urls: Vec<String>,
+        #[serde(default = "default_hedge")]
+        hedge: usize,
+        horizon_url: String,
+        #[serde(default)]
+        fallback_horizon_urls: Vec<String>,
+        network_passphrase: String,
+        secret_key_env: Option<String>,
+        secret_key_store: Option<String>,
+        /// Signer nodes holding the account's key in shares, instead of `secret_key_*`
+        frost: Option<CoordinatorConfig>,
+    },
+    /// Solidity escrow (`contracts/src/HTLCEscrowFixed.sol`) on any EVM chain
+    Evm {
+        /// Name the adapter reports, e.g. `base` or `monad`
+        name: String,
+        ws_url: String,
+        /// RPC endpoints of the same chain to fail over to; with any, events are polled
+        /// instead of subscribed to
+        #[serde(default)]
+        fallback_urls: Vec<String>,
+        #[serde(default = "default_hedge")]
+        hedge: usize,
+        contract: String,
+        private_key_env: Option<String>,
+        private_key_store: Option<String>,
+        #[serde(default = "default_confirmations")]
+        confirmations: u64,
+        #[serde(default)]
+        deploy_block: u64,
+        #[serde(default)]
+        fee_bump: FeeBumpConfig,
+    },
+    /// TON escrow contracts (`contracts/ton/htlc.fc`)
+    Ton {
+        api_url: String,
+        api_key_env: Option<String>,
+        mnemonic_env: Option<String>,
+        mnemonic_store: Option<String>,
+        /// Compiled contract BoC
+        code: PathBuf,
+    },
+}
+
+/// How a chain's transactions are re-priced when they do not land in time
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct FeeBumpConfig {
+    /// Least wait for inclusion before bumping; longer while recent transactions were slower
+    pub wait_secs: u64,
+    /// Percent each bump adds; EVM chains need at least 10
+    pub percent: u32,
+    pub max_bumps: u32,
+    /// Highest bid, in the chain's fee unit: inclusion fee stroops per operation on Stellar,
+    /// max fee wei per gas on EVM chains
+    pub max_fee: Option<u64>,
+    /// How close to a claim's timelock bids jump to `max_fee`, or double without it
+    pub urgent_secs: u64,
+}
+
+impl Default for FeeBumpConfig {
+    fn default() -> Self {
+        let policy = BumpPolicy::default();
+        Self {
+            wait_secs: policy.wait.as_secs(),
+            percent: policy.percent,
+            max_bumps: policy.max_bumps,
+            max_fee: None,
+            urgent_secs: policy.urgent.as_secs(),
+        }
+    }
+}
+
+impl FeeBumpConfig {
+    pub fn policy(&self) -> BumpPolicy {
+        BumpPolicy {
+            wait: Duration::from_secs(self.wait_secs),
+            percent: self.percent,
+            max_bumps: self.max_bumps,
+            max_fee: self.max_fee.map(u128::from),
+            urgent: Duration::from_secs(self.urgent_secs),
+        }
+    }
+
+    /// `min_percent` is the least bump the chain accepts as a replacement
+    fn validate(&self, min_percent: u32, problems: &mut Problems) {
+        problems.check(self.wait_secs > 0, || {
+            "fee_bump.wait_secs must be positive".to_string()
+        });
+        problems.check(self.percent >= min_percent, || {
+            format!("fee_bump.percent must be at least {min_percent}")
+        });
+    }
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::layers(path).load()?)
+    }
+
+    /// Where the config at `path` is read from, for watching it
+    pub fn layers(path: impl AsRef<Path>) -> Layers {
+        Layers::new(path.as_ref(), "UNITE_RELAYER")
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self, problems: &mut Problems) {
+        validate_chains(&self.chains, problems);
+        validate_policy(&self.policy, &self.chains, problems);
+        problems.section("alerts", &self.alerts);
+    }
+}
+
+/// Check `policy`, whose fee limits must name configured chains
+pub fn validate_policy(policy: &Policy, chains: &[ChainConfig], problems: &mut Problems) {
+    problems.section("policy", policy);
+    for chain in policy.max_fee.keys() {
+        problems.check(chains.iter().any(|config| config.name() == chain), || {
+            format!("policy.max_fee: no chain named {chain}")
+        });
+    }
+}
+
+impl Validate for Policy {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.tick_secs > 0, || {
+            "tick_secs must be positive".to_string()
+        });
+        problems.check(self.retry_secs > 0, || {
+            "retry_secs must be positive".to_string()
+        });
+        problems.check(self.max_retry_secs >= self.retry_secs, || {
+            "max_retry_secs must be at least retry_secs".to_string()
+        });
+        problems.check(self.stuck_secs > 0, || {
+            "stuck_secs must be positive".to_string()
+        });
+    }
+}
+
+/// Check each chain and that there is at least one, with no two sharing a name
+pub fn validate_chains(chains: &[ChainConfig], problems: &mut Problems) {
+    problems.check(!chains.is_empty(), || "no chains".to_string());
+    for (index, chain) in chains.iter().enumerate() {
+        problems.section(&format!("chains[{index}] ({})", chain.name()), chain);
+        problems.check(
+            chains[..index]
+                .iter()
+                .all(|other| other.name() != chain.name()),
+            || format!("chains[{index}]: {} is configured twice", chain.name()),
+        );
+    }
+}
+
+impl Validate for ChainConfig {
+    fn validate(&self, problems: &mut Problems) {
+        match self {
+            ChainConfig::Stellar {
+                rpc_url,
+                fallback_rpc_urls,
+                hedge,
+                contract,
+                secret_key_env,
+                secret_key_store,
+                frost,
+                fee_bump,
+                ..
+            } => {
+                url("rpc_url", rpc_url, &["http", "https"], problems);
+                fallbacks(
+                    "fallback_rpc_urls",
+                    fallback_rpc_urls,
+                    &["http", "https"],
+                    problems,
+                );
+                check_hedge(*hedge, problems);
+                problems.check(contract.len() == 56 && contract.starts_with('C'), || {
+                    format!("contract {contract} is not a contract address (C...)")
+                });
+                stellar_key(secret_key_env, secret_key_store, frost, problems);
+                fee_bump.validate(1, problems);
+            }
+            ChainConfig::StellarClassic {
+                rpc_url,
+                fallback_rpc_urls,
+                hedge,
+                horizon_url,
+                fallback_horizon_urls,
+                secret_key_env,
+                secret_key_store,
+                frost,
+                ..
+            } => {
+                url("rpc_url", rpc_url, &["http", "https"], problems);
+                fallbacks(
+                    "fallback_rpc_urls",
+                    fallback_rpc_urls,
+                    &["http", "https"],
+                    problems,
+                );
+                check_hedge(*hedge, problems);
+                url("horizon_url", horizon_url, &["http", "https"], problems);
+                fallbacks(
+                    "fallback_horizon_urls",
+                    fallback_horizon_urls,
+                    &["http", "https"],
+                    problems,
+                );
+                stellar_key(secret_key_env, secret_key_store, frost, problems);
+            }
+            ChainConfig::Evm {
+                ws_url,
+                fallback_urls,
+                hedge,
+                contract,
+                private_key_env,
+                private_key_store,
+                confirmations,
+                fee_bump,
+                ..
+            } => {
+                url("ws_url", ws_url, &["ws", "wss", "http", "https"], problems);
+                fallbacks(
+                    "fallback_urls",
+                    fallback_urls,
+                    &["ws", "wss", "http", "https"],
+                    problems,
+                );
+                check_hedge(*hedge, problems);
+                fee_bump.validate(10, problems);
+                problems.check(Address::from_str(contract).is_ok(), || {
+                    format!("contract {contract} is not an address")
+                });
+                problems.check(*confirmations > 0, || {
+                    "confirmations must be at least 1".to_string()
+                });
+                one_key("private_key", private_key_env, private_key_store, problems);
+            }
+            ChainConfig::Ton {
+                api_url,
+                mnemonic_env,
+                mnemonic_store,
+                code,
+                ..
+            } => {
+                url("api_url", api_url, &["http", "https"], problems);
+                one_key("mnemonic", mnemonic_env, mnemonic_store, problems);
+                problems.check(code.is_file(), || {
+                    format!("code {} does not exist", code.display())
+                });
+            }
+        }
+    }
+}
+
+fn url(field: &str, value: &str, schemes: &[&str], problems: &mut Problems) {
+    let scheme = value.split_once("://").map(|(scheme, _)| scheme);
+    problems.check(
+        scheme.is_some_and(|scheme| schemes.contains(&scheme)),
+        || format!("{field} {value} is not a {} URL", schemes.join("/")),
+    );
+}
+
+fn fallbacks(field: &str, values: &[String], schemes: &[&str], problems: &mut Problems) {
+    for (index, value) in values.iter().enumerate() {
+        url(&format!("{field}[{index}]"), value, schemes, problems);
+    }
+}
+
+fn check_hedge(hedge: usize, problems: &mut Problems) {
+    problems.check(hedge > 0, || "hedge must be at least 1".to_string());
+}
+
+fn stellar_key(
+    secret_key_env: &Option<String>,
+    secret_key_store: &Option<String>,
+    frost: &Option<CoordinatorConfig>,
+    problems: &mut Problems,
+) {
+    if frost.is_some() {
+        problems.check(
+            secret_key_env.is_none() && secret_key_store.is_none(),
+            || "give either a frost table or secret_key_env/secret_key_store".to_string(),
+        );
+    } else {
+        one_key("secret_key", secret_key_env, secret_key_store, problems);
+    }
+}
+
+fn one_key(field: &str, env: &Option<String>, store: &Option<String>, problems: &mut Problems) {
+    problems.check(env.is_some() != store.is_some(), || {
+        format!("give exactly one of {field}_env and {field}_store")
+    });
+}
+
+impl ChainConfig {
+    /// Name the chain's adapter reports, which orders, announcements and policies use
+    pub fn name(&self) -> &str {
+        match self {
+            ChainConfig::Stellar { .. } => "stellar",
+            ChainConfig::StellarClassic { .. } => "stellar-classic",
+            ChainConfig::Evm { name, .. } => name,
+            ChainConfig::Ton { .. } => "ton",
+        }
+    }
+
+    /// `secrets` is the store `*_store` fields name entries of
+    pub async fn connect(
+        &self,
+        secrets: Option<&dyn SecretStore>,
+    ) -> Result<Arc<dyn ChainAdapter>> {
+        Ok(match self {
+            ChainConfig::Stellar {
+                rpc_url,
+                fallback_rpc_urls,
+                hedge,
+                contract,
+                network_passphrase,
+                secret_key_env,
+                secret_key_store,
+                frost,
+                fee_bump,
+                sha256,
+            } => {
+                let signer =
+                    stellar_signer(secret_key_env, secret_key_store, frost, secrets).await?;
+                let hash_lock = if *sha256 {
+                    unite_stellar::HashLock::Sha256
+                } else {
+                    unite_stellar::HashLock::Keccak256
+                };
+                let client = unite_stellar::EscrowClient::new(
+                    rpc_url.as_str(),
+                    contract,
+                    signer,
+                    network_passphrase,
+                )
+                .map_err(unite_core::Error::from)?;
+                Arc::new(
+                    client
+                        .with_rpc(soroban_rpc(rpc_url, fallback_rpc_urls, *hedge))
+                        .with_fee_bump(fee_bump.policy())
+                        .with_hash_lock(hash_lock),
+                )
+            }
+            ChainConfig::StellarClassic {
+                rpc_url,
+                fallback_rpc_urls,
+                hedge,
+                horizon_url,
+                fallback_horizon_urls,
+                network_passphrase,
+                secret_key_env,
+                secret_key_store,
+                frost,
+            } => {
+                let signer =
+                    stellar_signer(secret_key_env, secret_key_store, frost, secrets).await?;
+                let horizon = unite_stellar::Horizon::new(horizon_url.as_str())
+                    .with_fallbacks(fallback_horizon_urls.iter().cloned());
+                Arc::new(
+                    unite_stellar::ClaimableEscrow::new(
+                        rpc_url.as_str(),
+                        horizon_url.as_str(),
+                        signer,
+                        network_passphrase,
+                    )
+                    .with_rpc(soroban_rpc(rpc_url, fallback_rpc_urls, *hedge))
+                    .with_horizon(horizon),
+                )
+            }
+            ChainConfig::Evm {
+                name,
+                ws_url,
+                fallback_urls,
+                hedge,
+                contract,
+                private_key_env,
+                private_key_store,
+                confirmations,
+                deploy_block,
+                fee_bump,
+            } => {
+                let contract = Address::from_str(contract)
+                    .map_err(|_| Error::Config(format!("{name}: invalid contract {contract}")))?;
+                let private_key =
+                    key("private_key", private_key_env, private_key_store, secrets).await?;
+                let signer = PrivateKeySigner::from_str(&private_key)
+                    .map_err(|_| Error::Config(format!("{name}: the key is not a private key")))?;
+                let client = if fallback_urls.is_empty() {
+                    unite_evm::EscrowClient::connect(ws_url, contract, signer, *confirmations).await
+                } else {
+                    let urls: Vec<String> = std::iter::once(ws_url)
+                        .chain(fallback_urls)
+                        .cloned()
+                        .collect();
+                    unite_evm::EscrowClient::connect_pool(
+                        &urls,
+                        *hedge,
+                        contract,
+                        signer,
+                        *confirmations,
+                    )
+                    .await
+                }
+                .map_err(unite_core::Error::from)?;
+                Arc::new(
+                    client
+                        .with_chain(name.as_str())
+                        .with_deploy_block(*deploy_block)
+                        .with_fee_bump(fee_bump.policy()),
+                )
+            }
+            ChainConfig::Ton {
+                api_url,
+                api_key_env,
+                mnemonic_env,
+                mnemonic_store,
+                code,
+            } => {
+                let mut api = unite_ton::Toncenter::new(api_url.as_str());
+                if let Some(api_key_env) = api_key_env {
+                    api = api.with_api_key(env(api_key_env)?);
+                }
+                let mnemonic = key("mnemonic", mnemonic_env, mnemonic_store, secrets).await?;
+                let wallet = unite_ton::wallet(&mnemonic).map_err(unite_core::Error::from)?;
+                let client = unite_ton::EscrowClient::new(api, wallet, &std::fs::read(code)?)
+                    .map_err(unite_core::Error::from)?;
+                Arc::new(client)
+            }
+        })
+    }
+}
+
+/// Soroban RPC client for `rpc_url`, failing over to `fallbacks`
+fn soroban_rpc(rpc_url: &str, fallbacks: &[String], hedge: usize) -> unite_stellar::SorobanRpc {
+    unite_stellar::SorobanRpc::new(rpc_url)
+        .with_fallbacks(fallbacks.iter().cloned())
+        .with_hedge(hedge)
+}
+
+/// The Stellar account's signer: its secret key, or the FROST group holding it in shares
+async fn stellar_signer(
+    secret_key_env: &Option<String>,
+    secret_key_store: &Option<String>,
+    frost: &Option<CoordinatorConfig>,
+    secrets: Option<&dyn SecretStore>,
+) -> Result<unite_stellar::Signer> {
+    match (frost, secret_key_env, secret_key_store) {
+        (Some(frost), None, None) => Ok(unite_stellar::Signer::remote(Arc::new(frost.connect()?))),
+        (Some(_), _, _) => Err(Error::Config(
+            "give either a frost table or secret_key_env/secret_key_store".to_string(),
+        )),
+        (None, _, _) => {
+            let secret_key = key("secret_key", secret_key_env, secret_key_store, secrets).await?;
+            Ok(unite_stellar::Signer::from_secret(&secret_key).map_err(unite_core::Error::from)?)
+        }
+    }
+}
+
+/// A key from the environment variable `<field>_env` names or the store entry `<field>_store`
+/// names
+async fn key(
+    field: &str,
+    env_name: &Option<String>,
+    stored: &Option<String>,
+    secrets: Option<&dyn SecretStore>,
+) -> Result<Zeroizing<String>> {
+    match (env_name, stored, secrets) {
+        (Some(name), None, _) => Ok(Zeroizing::new(env(name)?)),
+        (None, Some(name), Some(secrets)) => {
+            let value = secrets
+                .get(name)
+                .await?
+                .ok_or_else(|| Error::Config(format!("no secret named {name}")))?;
+            String::from_utf8(value.to_vec())
+                .map(Zeroizing::new)
+                .map_err(|_| Error::Config(format!("secret {name} is not text")))
+        }
+        (None, Some(name), None) => Err(Error::Config(format!(
+            "{name} names a secret, but no [secrets] store is configured"
+        ))),
+        _ => Err(Error::Config(format!(
+            "give exactly one of {field}_env and {field}_store"
+        ))),
+    }
+}
+
+fn env(name: &str) -> Result<String> {
+    std::env::var(name).map_err(|_| Error::Config(format!("{name} is not set")))
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8080))
+}
+
+fn default_confirmations() -> u64 {
+    1
+}
+
+fn default_hedge() -> usize {
+    2
+}
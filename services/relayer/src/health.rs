@@ -0,0 +1,30 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use unite_core::ChainAdapter;
+use unite_telemetry::{Health, Probe, Reading};
+
+/// Readiness of a chain's RPC, probed through its adapter's head
+pub struct ChainProbe(pub Arc<dyn ChainAdapter>);
+
+#[async_trait]
+impl Probe for ChainProbe {
+    fn name(&self) -> String {
+        self.0.chain().to_string()
+    }
+
+    async fn probe(&self) -> Result<Reading, String> {
+        let head = self.0.head().await.map_err(|error| error.to_string())?;
+        Ok(Reading::height(head))
+    }
+}
+
+/// Probes for the RPC of each of `adapters`
+pub fn chains<'a>(adapters: impl IntoIterator<Item = &'a Arc<dyn ChainAdapter>>) -> Health {
+    Health::new(
+        adapters
+            .into_iter()
+            .map(|adapter| Arc::new(ChainProbe(adapter.clone())) as Arc<dyn Probe>)
+            .collect(),
+    )
+}
@@ -0,0 +1,57 @@
+// Cross-Chain Relayer
+// The off-chain half of the protocol. Makers and resolvers announce the escrows they create
+// for an order; the relayer pairs the source and destination escrow by order ID and hashlock,
+// follows both through their chains' `ChainAdapter`s and checks that they lock the same
+// hashlock with the destination expiring first. Once a claim on either leg reveals the secret
+// it claims the other leg with it, and legs whose timelock passes unclaimed are refunded.
+// Each swap is a small state machine persisted after every change, so a restarted relayer
+// picks up every unsettled swap where it left off. With a secret store configured, signing
+// keys can be read from it and revealed secrets are kept there instead of in the swap files;
+// Stellar keys can also be split across FROST signer nodes, so no single host holds them.
+// Swap progress, phase latencies, submissions and RPC errors are exported to Prometheus, and
+// every log line carries the order ID of its swap and, for chain calls, the leg's escrow ID.
+// Swaps stuck in one phase, timelocks closing on unsettled legs and failed claims and refunds
+// are alerted to the configured Telegram and Discord chats. `/readyz` probes every chain's RPC.
+
+pub mod api;
+pub mod config;
+pub mod health;
+pub mod metrics;
+pub mod relayer;
+pub mod store;
+pub mod swap;
+
+pub use config::{validate_chains, validate_policy, ChainConfig, Config};
+pub use relayer::Relayer;
+pub use store::SwapStore;
+pub use swap::{Action, ActionKind, Announcement, Leg, Lock, Outcome, Phase, Policy, Role, Swap};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error("no adapter for chain {0}")]
+    UnknownChain(String),
+    #[error("unknown order {0}")]
+    UnknownOrder(String),
+    #[error("conflicting announcement: {0}")]
+    Conflict(&'static str),
+    #[error("swap already settled")]
+    Settled,
+    #[error(transparent)]
+    Alert(#[from] unite_alert::Error),
+    #[error(transparent)]
+    Chain(#[from] unite_core::Error),
+    #[error(transparent)]
+    Frost(#[from] unite_frost::Error),
+    #[error(transparent)]
+    Secrets(#[from] unite_secrets::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Load(#[from] unite_config::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
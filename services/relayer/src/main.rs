@@ -0,0 +1,64 @@
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tracing::{info, warn};
+use unite_alert::Alerter;
+use unite_relayer::{api, metrics, Config, Relayer, SwapStore};
+
+/// `unite-relayer [config.toml]`, logging through `RUST_LOG`
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "relayer.toml".to_string());
+    let config = Config::load(&path)?;
+    let _telemetry = unite_telemetry::init("unite-relayer", &config.tracing)?;
+    if let Some(listen) = config.metrics_listen {
+        metrics::install(listen)?;
+        info!("metrics on http://{listen}/metrics");
+    }
+
+    let secrets = match &config.secrets {
+        Some(secrets) => Some(secrets.open().await?),
+        None => None,
+    };
+    let mut chains = Vec::new();
+    for chain in &config.chains {
+        chains.push(chain.connect(secrets.as_deref()).await?);
+    }
+    let relayer = Relayer::new(
+        chains,
+        SwapStore::open(&config.store)?,
+        config.policy,
+        secrets,
+        Alerter::new("unite-relayer", &config.alerts)?,
+    );
+    info!(
+        "chains: {}",
+        relayer.chains().collect::<Vec<_>>().join(", ")
+    );
+    info!("resumed {} unsettled swaps", relayer.recover()?);
+    if let Some(secs) = config.reload_secs {
+        let relayer = relayer.clone();
+        unite_config::watch(
+            Config::layers(&path),
+            Duration::from_secs(secs),
+            move |reloaded: unite_config::Result<Config>| match reloaded {
+                Ok(config) => {
+                    relayer.set_policy(config.policy);
+                    info!("reloaded [policy]");
+                }
+                Err(error) => warn!("config not reloaded: {error}"),
+            },
+        );
+    }
+
+    let listener = TcpListener::bind(config.listen).await?;
+    info!("listening on {}", config.listen);
+    axum::serve(listener, api::router(relayer))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
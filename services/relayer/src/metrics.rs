@@ -0,0 +1,98 @@
+// Prometheus metrics. Recording goes through the `metrics` facade, which is a no-op until a
+// binary installs the exporter with `install`, so library users pay nothing for it.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+use metrics_exporter_prometheus::{Matcher, PrometheusBuilder};
+
+use crate::swap::{ActionKind, Phase};
+use crate::{Error, Result};
+
+/// Buckets for `*_seconds` histograms, from a fast RPC call to a swap left to expire
+const SECONDS_BUCKETS: &[f64] = &[
+    0.05, 0.25, 1.0, 5.0, 15.0, 60.0, 300.0, 900.0, 3_600.0, 14_400.0, 86_400.0,
+];
+
+/// Serve every metric recorded in this process on `http://{listen}/metrics`
+pub fn install(listen: SocketAddr) -> Result<()> {
+    PrometheusBuilder::new()
+        .with_http_listener(listen)
+        .set_buckets_for_metric(Matcher::Suffix("_seconds".to_string()), SECONDS_BUCKETS)
+        .and_then(|builder| builder.install())
+        .map_err(|error| Error::Config(format!("metrics: {error}")))?;
+    describe();
+    Ok(())
+}
+
+fn describe() {
+    describe_gauge!(
+        "unite_relayer_swaps_in_flight",
+        "Unsettled swaps being driven"
+    );
+    describe_counter!(
+        "unite_relayer_phase_transitions_total",
+        "Swaps entering each phase"
+    );
+    describe_histogram!(
+        "unite_relayer_phase_seconds",
+        "Time swaps spent in a phase before leaving it"
+    );
+    describe_counter!(
+        "unite_relayer_submissions_total",
+        "Claims and refunds sent, by chain, action and result"
+    );
+    describe_counter!(
+        "unite_relayer_rpc_errors_total",
+        "Failed chain calls, by chain and call"
+    );
+    describe_histogram!(
+        "unite_relayer_fee_bid",
+        "Chain's max fee per unit when a claim or refund was sent, in its smallest unit"
+    );
+}
+
+pub(crate) fn swap_started() {
+    gauge!("unite_relayer_swaps_in_flight").increment(1.0);
+}
+
+pub(crate) fn swap_stopped() {
+    gauge!("unite_relayer_swaps_in_flight").decrement(1.0);
+}
+
+/// A swap left `from` for `to` after `elapsed` in it
+pub(crate) fn phase_changed(from: &Phase, to: &Phase, elapsed: Duration) {
+    histogram!("unite_relayer_phase_seconds", "phase" => from.name()).record(elapsed);
+    counter!("unite_relayer_phase_transitions_total", "phase" => to.name()).increment(1);
+}
+
+pub(crate) fn submitted(chain: &str, action: ActionKind, ok: bool) {
+    let action = match action {
+        ActionKind::Claim => "claim",
+        ActionKind::Refund => "refund",
+    };
+    counter!(
+        "unite_relayer_submissions_total",
+        "chain" => chain.to_string(),
+        "action" => action,
+        "result" => if ok { "ok" } else { "error" },
+    )
+    .increment(1);
+    if !ok {
+        rpc_error(chain, action);
+    }
+}
+
+pub(crate) fn rpc_error(chain: &str, call: &'static str) {
+    counter!(
+        "unite_relayer_rpc_errors_total",
+        "chain" => chain.to_string(),
+        "call" => call,
+    )
+    .increment(1);
+}
+
+pub(crate) fn fee_bid(chain: &str, max_fee: u128) {
+    histogram!("unite_relayer_fee_bid", "chain" => chain.to_string()).record(max_fee as f64);
+}
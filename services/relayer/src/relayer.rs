@@ -0,0 +1,640 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures::stream::{self, BoxStream, SelectAll, StreamExt};
+use sha2::{Digest, Sha256};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{info, info_span, warn, Instrument, Span};
+use unite_alert::{Alerter, Severity};
+use unite_core::{ChainAdapter, ChainEvent, EscrowEvent, EscrowStatus, Secret};
+use unite_secrets::SecretStore;
+use unite_telemetry::Health;
+
+use crate::health;
+use crate::metrics;
+use crate::store::SwapStore;
+use crate::swap::{Action, ActionKind, Announcement, Leg, Outcome, Phase, Policy, Role, Swap};
+use crate::{Error, Result};
+
+type Reply = oneshot::Sender<Result<()>>;
+type LegEvents = BoxStream<'static, (Role, Option<unite_core::Result<ChainEvent>>)>;
+
+/// Drives every open swap through its phases, one task per swap
+///
+/// A swap's task owns its state: it follows both escrows through their chain adapters,
+/// claims the other leg once a claim reveals the secret, refunds legs whose timelock passed
+/// and saves the swap after every change, so a restarted relayer resumes each watch from
+/// the last height it applied. With a secret store, a swap's secret is kept there while the
+/// swap is open and left out of its file.
+///
+/// Claims and refunds are saved as intents before they are sent. One sent before, or left
+/// by a restart mid-send, is only sent again after its escrow's state, read from the chain,
+/// shows it has not landed; one that has is applied as if its event had arrived.
+///
+/// Each task runs in a `swap` span carrying the order ID, and chain calls for one leg in a
+/// `leg` span carrying its role, chain and escrow ID. A swap stuck in one phase past
+/// `stuck_secs`, a locked leg within `deadline_alert_secs` of its timelock and a claim or
+/// refund that fails to send raise alerts, keyed by swap and leg so each incident is sent
+/// once and resolved when it is over.
+pub struct Relayer {
+    chains: HashMap<String, Arc<dyn ChainAdapter>>,
+    store: SwapStore,
+    policy: RwLock<Policy>,
+    secrets: Option<Arc<dyn SecretStore>>,
+    alerts: Alerter,
+    running: Mutex<HashMap<String, mpsc::UnboundedSender<(Announcement, Reply)>>>,
+}
+
+impl Relayer {
+    pub fn new(
+        chains: Vec<Arc<dyn ChainAdapter>>,
+        store: SwapStore,
+        policy: Policy,
+        secrets: Option<Arc<dyn SecretStore>>,
+        alerts: Alerter,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            chains: chains
+                .into_iter()
+                .map(|adapter| (adapter.chain().to_string(), adapter))
+                .collect(),
+            store,
+            policy: RwLock::new(policy),
+            secrets,
+            alerts,
+            running: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub fn chains(&self) -> impl Iterator<Item = &str> {
+        self.chains.keys().map(String::as_str)
+    }
+
+    pub fn policy(&self) -> Policy {
+        self.policy.read().unwrap().clone()
+    }
+
+    /// Use `policy` from the next step of every swap on
+    pub fn set_policy(&self, policy: Policy) {
+        *self.policy.write().unwrap() = policy;
+    }
+
+    /// Probes for the RPC of every chain
+    pub fn health(&self) -> Health {
+        health::chains(self.chains.values())
+    }
+
+    /// Restart the tasks of swaps left unsettled by a previous run, returning how many
+    pub fn recover(self: &Arc<Self>) -> Result<usize> {
+        let mut running = self.running.lock().unwrap();
+        let mut resumed = 0;
+        for swap in self.store.all()? {
+            if !swap.is_settled() && !running.contains_key(&swap.order_id) {
+                self.spawn(&mut running, swap);
+                resumed += 1;
+            }
+        }
+        Ok(resumed)
+    }
+
+    /// Add an escrow to its order's swap, starting the swap if it is the first
+    #[tracing::instrument(
+        skip_all,
+        fields(
+            order_id = %announcement.order_id,
+            role = ?announcement.role,
+            chain = %announcement.chain,
+            escrow_id = %hex::encode(announcement.escrow_id),
+        ),
+    )]
+    pub async fn announce(self: &Arc<Self>, announcement: Announcement) -> Result<()> {
+        if !self.chains.contains_key(&announcement.chain) {
+            return Err(Error::UnknownChain(announcement.chain));
+        }
+        let task = {
+            let mut running = self.running.lock().unwrap();
+            match running.get(&announcement.order_id) {
+                Some(task) => task.clone(),
+                None => {
+                    let mut swap = match self.store.load(&announcement.order_id)? {
+                        Some(swap) if swap.is_settled() => return Err(Error::Settled),
+                        Some(swap) => swap,
+                        None => Swap::new(announcement.order_id.clone(), announcement.hashlock),
+                    };
+                    swap.announce(&announcement)?;
+                    self.save(&swap)?;
+                    self.spawn(&mut running, swap);
+                    return Ok(());
+                }
+            }
+        };
+        // The task only stops once the swap has settled
+        let (reply, response) = oneshot::channel();
+        task.send((announcement, reply))
+            .map_err(|_| Error::Settled)?;
+        response.await.map_err(|_| Error::Settled)?
+    }
+
+    pub fn swap(&self, order_id: &str) -> Result<Option<Swap>> {
+        self.store.load(order_id)
+    }
+
+    pub fn swaps(&self) -> Result<Vec<Swap>> {
+        self.store.all()
+    }
+
+    fn spawn(
+        self: &Arc<Self>,
+        running: &mut HashMap<String, mpsc::UnboundedSender<(Announcement, Reply)>>,
+        swap: Swap,
+    ) {
+        let (task, announcements) = mpsc::unbounded_channel();
+        running.insert(swap.order_id.clone(), task);
+        metrics::swap_started();
+        let span = info_span!(parent: None, "swap", order_id = %swap.order_id);
+        tokio::spawn(self.clone().drive(swap, announcements).instrument(span));
+    }
+
+    async fn drive(
+        self: Arc<Self>,
+        mut swap: Swap,
+        mut announcements: mpsc::UnboundedReceiver<(Announcement, Reply)>,
+    ) {
+        info!(phase = ?swap.phase, "driving");
+        let mut events = SelectAll::<LegEvents>::new();
+        let mut watching = HashSet::new();
+        let mut tick = tokio::time::interval(Duration::from_secs(self.policy().tick_secs));
+        self.restore_secret(&mut swap).await;
+        let mut stored_secret = None;
+        let mut phase_since = Instant::now();
+        loop {
+            let before = swap.clone();
+            self.watch(&swap, &mut events, &mut watching).await;
+            tokio::select! {
+                Some((announcement, reply)) = announcements.recv() => {
+                    let _ = reply.send(swap.announce(&announcement).map(|_| ()));
+                }
+                Some((role, item)) = events.next() => match item {
+                    Some(Ok(event)) => self.apply(&mut swap, role, event).await,
+                    Some(Err(error)) => {
+                        if let Some(leg) = swap.leg(role) {
+                            leg_span(role, leg).in_scope(|| warn!("watch: {error}"));
+                            metrics::rpc_error(&leg.chain, "watch_events");
+                        }
+                    }
+                    None => {
+                        watching.remove(&role);
+                    }
+                },
+                _ = tick.tick() => {}
+            }
+
+            // Swap files leave the secret to the secret store, so a claim's saved intent only
+            // holds once the secret is stored
+            let secret_kept = self.keep_secret(&swap, &mut stored_secret).await;
+            for action in swap.step(now(), &self.policy()) {
+                if action.kind == ActionKind::Claim && !secret_kept {
+                    continue;
+                }
+                self.execute(&mut swap, action).await;
+            }
+            self.keep_secret(&swap, &mut stored_secret).await;
+            if swap != before {
+                if swap.phase != before.phase {
+                    info!(phase = ?swap.phase, "phase changed");
+                    metrics::phase_changed(&before.phase, &swap.phase, phase_since.elapsed());
+                    phase_since = Instant::now();
+                }
+                swap.updated_at = now();
+                if let Err(error) = self.save(&swap) {
+                    warn!("saving failed: {error}");
+                }
+            }
+            if swap.is_settled() {
+                break;
+            }
+            self.check_alerts(&swap, phase_since);
+        }
+        self.alerts.resolve_all(&alert_key(&swap.order_id, ""));
+        self.forget_secret(&swap).await;
+        self.running.lock().unwrap().remove(&swap.order_id);
+        metrics::swap_stopped();
+    }
+
+    /// Alert a swap that has been in its phase since `phase_since` for longer than
+    /// `stuck_secs`, and locked legs whose timelock is within `deadline_alert_secs`, resolving
+    /// the alerts of incidents that are over
+    fn check_alerts(&self, swap: &Swap, phase_since: Instant) {
+        let policy = self.policy();
+        let order_id = &swap.order_id;
+        let stuck = alert_key(order_id, "stuck");
+        let elapsed = phase_since.elapsed().as_secs();
+        if elapsed >= policy.stuck_secs {
+            self.alerts.fire(
+                Severity::Warning,
+                stuck,
+                format!(
+                    "swap {order_id} has been {} for {} min",
+                    swap.phase.name(),
+                    elapsed / 60
+                ),
+            );
+        } else {
+            self.alerts.resolve(&stuck);
+        }
+
+        let now = now();
+        for role in [Role::Source, Role::Destination] {
+            let key = alert_key(order_id, &format!("deadline:{}", role.name()));
+            let closing = swap.leg(role).and_then(|leg| {
+                let timelock = leg.lock.as_ref().filter(|_| leg.is_locked())?.timelock;
+                let left = timelock.checked_sub(now).filter(|left| *left > 0)?;
+                (left <= policy.deadline_alert_secs).then_some((leg, left))
+            });
+            // Past the timelock the leg is refunded, which failed submissions report
+            let Some((leg, left)) = closing.filter(|_| swap.phase != Phase::Expired) else {
+                self.alerts.resolve(&key);
+                continue;
+            };
+            let (severity, waiting) = if swap.phase == Phase::Revealed {
+                (Severity::Critical, "the secret is out but it is unclaimed")
+            } else {
+                (Severity::Warning, "the secret is not out")
+            };
+            self.alerts.fire(
+                severity,
+                key,
+                format!(
+                    "swap {order_id}: the {} leg on {} expires in {} min and {waiting}",
+                    role.name(),
+                    leg.chain,
+                    left / 60
+                ),
+            );
+        }
+    }
+
+    /// Alert a claim or refund that failed to send, critical from its third try
+    fn submission_failed(&self, swap: &Swap, action: Action, reason: &str) {
+        let Some(leg) = swap.leg(action.role) else {
+            return;
+        };
+        let attempts = leg
+            .submitted
+            .as_ref()
+            .map_or(1, |submitted| submitted.attempts);
+        let severity = if attempts >= 3 {
+            Severity::Critical
+        } else {
+            Severity::Warning
+        };
+        self.alerts.fire(
+            severity,
+            alert_key(&swap.order_id, &format!("submit:{}", action.role.name())),
+            format!(
+                "swap {}: {:?} of the {} leg on {} failed (try {attempts}): {reason}",
+                swap.order_id,
+                action.kind,
+                action.role.name(),
+                leg.chain
+            ),
+        );
+    }
+
+    /// Open event streams for legs that are announced, unsettled and not yet watched
+    async fn watch(
+        &self,
+        swap: &Swap,
+        events: &mut SelectAll<LegEvents>,
+        watching: &mut HashSet<Role>,
+    ) {
+        for role in [Role::Source, Role::Destination] {
+            let Some(leg) = swap.leg(role) else {
+                continue;
+            };
+            if leg.outcome.is_some() || watching.contains(&role) {
+                continue;
+            }
+            let span = leg_span(role, leg);
+            let Some(adapter) = self.chains.get(&leg.chain) else {
+                span.in_scope(|| warn!("no adapter for the chain"));
+                continue;
+            };
+            match adapter
+                .watch_events(leg.escrow_id, leg.height)
+                .instrument(span.clone())
+                .await
+            {
+                Ok(stream) => {
+                    // A final `None` marks the stream's end, so it can be reopened if the leg
+                    // is still unsettled
+                    let stream = stream
+                        .map(move |item| (role, Some(item)))
+                        .chain(stream::once(async move { (role, None) }));
+                    events.push(stream.boxed());
+                    watching.insert(role);
+                }
+                Err(error) => {
+                    span.in_scope(|| warn!("watch: {error}"));
+                    metrics::rpc_error(&leg.chain, "watch_events");
+                }
+            }
+        }
+    }
+
+    async fn apply(&self, swap: &mut Swap, role: Role, mut event: ChainEvent) {
+        let Some(leg) = swap.leg(role) else {
+            return;
+        };
+        let Some(adapter) = self.chains.get(&leg.chain) else {
+            return;
+        };
+        let span = leg_span(role, leg);
+        if let EscrowEvent::Claimed {
+            secret: secret @ None,
+        } = &mut event.event
+        {
+            if !event.removed {
+                match adapter
+                    .reveal_secret(&event.escrow_id)
+                    .instrument(span.clone())
+                    .await
+                {
+                    Ok(revealed) => *secret = revealed,
+                    Err(error) => {
+                        span.in_scope(|| warn!("reading the secret: {error}"));
+                        metrics::rpc_error(adapter.chain(), "reveal_secret");
+                    }
+                }
+            }
+        }
+        let name = match &event.event {
+            EscrowEvent::Created { .. } => "created",
+            EscrowEvent::Claimed { .. } => "claimed",
+            EscrowEvent::Refunded => "refunded",
+        };
+        span.in_scope(|| {
+            info!(
+                event = name,
+                height = event.height,
+                removed = event.removed,
+                "escrow {name}"
+            )
+        });
+        swap.apply(role, &event);
+    }
+
+    /// Swap files leave the secret out when a secret store keeps it
+    fn save(&self, swap: &Swap) -> Result<()> {
+        if self.secrets.is_none() || swap.secret.is_none() {
+            return self.store.save(swap);
+        }
+        self.store.save(&Swap {
+            secret: None,
+            ..swap.clone()
+        })
+    }
+
+    /// Reload the secret of a swap that had revealed it before a restart, from the secret
+    /// store or, failing that, from the chain of a claimed leg
+    async fn restore_secret(&self, swap: &mut Swap) {
+        let Some(secrets) = &self.secrets else {
+            return;
+        };
+        if swap.secret.is_some() || swap.phase != Phase::Revealed {
+            return;
+        }
+        match secrets.get(&secret_name(&swap.order_id)).await {
+            Ok(Some(secret)) => {
+                swap.secret = Some(secret.to_vec());
+                return;
+            }
+            Ok(None) => {}
+            Err(error) => warn!("reading the secret: {error}"),
+        }
+        for role in [Role::Source, Role::Destination] {
+            let Some(leg) = swap.leg(role) else {
+                continue;
+            };
+            let Some(adapter) = self.chains.get(&leg.chain) else {
+                continue;
+            };
+            if leg.outcome != Some(Outcome::Claimed) {
+                continue;
+            }
+            let span = leg_span(role, leg);
+            match adapter
+                .reveal_secret(&leg.escrow_id)
+                .instrument(span.clone())
+                .await
+            {
+                Ok(Some(secret)) => {
+                    swap.secret = Some(secret);
+                    return;
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    span.in_scope(|| warn!("reading the secret: {error}"));
+                    metrics::rpc_error(&leg.chain, "reveal_secret");
+                }
+            }
+        }
+    }
+
+    /// Store the swap's secret if it is not the one `stored` last, returning whether it is
+    /// stored
+    async fn keep_secret(&self, swap: &Swap, stored: &mut Option<Secret>) -> bool {
+        if swap.secret == *stored {
+            return true;
+        }
+        let kept = self.store_secret(swap).await;
+        if kept {
+            stored.clone_from(&swap.secret);
+        }
+        kept
+    }
+
+    /// Keep the swap's secret in the secret store, returning whether it is there
+    async fn store_secret(&self, swap: &Swap) -> bool {
+        let (Some(secrets), Some(secret)) = (&self.secrets, &swap.secret) else {
+            return true;
+        };
+        match secrets.put(&secret_name(&swap.order_id), secret).await {
+            Ok(()) => true,
+            Err(error) => {
+                warn!("storing the secret: {error}");
+                false
+            }
+        }
+    }
+
+    /// A settled swap no longer needs its secret
+    async fn forget_secret(&self, swap: &Swap) {
+        let (Some(secrets), Some(_)) = (&self.secrets, &swap.secret) else {
+            return;
+        };
+        if let Err(error) = secrets.delete(&secret_name(&swap.order_id)).await {
+            warn!("deleting the secret: {error}");
+        }
+    }
+
+    async fn execute(&self, swap: &mut Swap, action: Action) {
+        let Some(leg) = swap.leg(action.role) else {
+            return;
+        };
+        let Some(adapter) = self.chains.get(&leg.chain).cloned() else {
+            return;
+        };
+        let span = leg_span(action.role, leg);
+        if leg.is_retry(action.kind) && !self.reconcile(swap, action, adapter.as_ref()).await {
+            return;
+        }
+        let Some(leg) = swap.leg(action.role) else {
+            return;
+        };
+        let policy = self.policy();
+        let held = match policy.max_fee.get(&leg.chain) {
+            Some(&max_fee) => match adapter.fee_estimate().instrument(span.clone()).await {
+                Ok(fee) if fee.max_fee > u128::from(max_fee) => {
+                    Some(format!("fee {} is above max_fee {max_fee}", fee.max_fee))
+                }
+                Ok(_) => None,
+                Err(error) => {
+                    metrics::rpc_error(&leg.chain, "fee_estimate");
+                    Some(format!("fee estimate failed: {error}"))
+                }
+            },
+            None => None,
+        };
+        let escrow_id = leg.escrow_id;
+        let chain = leg.chain.clone();
+        let Some(leg) = swap.leg_mut(action.role) else {
+            return;
+        };
+        leg.intend(action.kind, now(), &policy);
+        if let Some(reason) = held {
+            span.in_scope(|| warn!(action = ?action.kind, "{:?} held back: {reason}", action.kind));
+            leg.sent(Err(reason.clone()));
+            self.submission_failed(swap, action, &reason);
+            return;
+        }
+        if let Err(error) = self.save(swap) {
+            span.in_scope(|| warn!(action = ?action.kind, "saving the intent failed: {error}"));
+            if let Some(leg) = swap.leg_mut(action.role) {
+                leg.sent(Err(format!("saving the intent failed: {error}")));
+            }
+            return;
+        }
+        let result = match (action.kind, &swap.secret) {
+            (ActionKind::Claim, Some(secret)) => {
+                adapter
+                    .claim(&escrow_id, secret)
+                    .instrument(span.clone())
+                    .await
+            }
+            (ActionKind::Claim, None) => return,
+            (ActionKind::Refund, _) => adapter.refund(&escrow_id).instrument(span.clone()).await,
+        };
+        span.in_scope(|| match &result {
+            Ok(tx) => info!(action = ?action.kind, %tx, "{:?} sent", action.kind),
+            Err(error) => warn!(action = ?action.kind, "{:?} failed: {error}", action.kind),
+        });
+        metrics::submitted(&chain, action.kind, result.is_ok());
+        match &result {
+            Ok(_) => self.alerts.resolve(&alert_key(
+                &swap.order_id,
+                &format!("submit:{}", action.role.name()),
+            )),
+            Err(error) => self.submission_failed(swap, action, &error.to_string()),
+        }
+        if result.is_ok() {
+            match adapter.fee_estimate().await {
+                Ok(fee) => metrics::fee_bid(&chain, fee.max_fee),
+                Err(_) => metrics::rpc_error(&chain, "fee_estimate"),
+            }
+        }
+        if let Some(leg) = swap.leg_mut(action.role) {
+            leg.sent(result.map_err(|error| error.to_string()));
+        }
+    }
+
+    /// Check the chain before sending `action` again, returning whether it is still due
+    ///
+    /// An escrow already claimed or refunded is applied as its event would be; one the chain
+    /// cannot show, or whose state could not be read, backs off as if the send had failed.
+    async fn reconcile(&self, swap: &mut Swap, action: Action, adapter: &dyn ChainAdapter) -> bool {
+        let Some(leg) = swap.leg(action.role) else {
+            return false;
+        };
+        let span = leg_span(action.role, leg);
+        let status = adapter
+            .escrow_status(&leg.escrow_id)
+            .instrument(span.clone())
+            .await;
+        let event = match status {
+            Ok(EscrowStatus::Open) => return true,
+            Ok(EscrowStatus::Claimed) => EscrowEvent::Claimed { secret: None },
+            Ok(EscrowStatus::Refunded) => EscrowEvent::Refunded,
+            Ok(EscrowStatus::Missing) => {
+                self.back_off(swap, action, "escrow not found on chain".to_string());
+                return false;
+            }
+            Err(error) => {
+                metrics::rpc_error(adapter.chain(), "escrow_status");
+                self.back_off(swap, action, format!("reading the escrow failed: {error}"));
+                return false;
+            }
+        };
+        span.in_scope(|| info!(action = ?action.kind, "already settled on chain"));
+        let event = ChainEvent {
+            escrow_id: leg.escrow_id,
+            event,
+            height: leg.height,
+            tx: None,
+            removed: false,
+        };
+        self.apply(swap, action.role, event).await;
+        false
+    }
+
+    fn back_off(&self, swap: &mut Swap, action: Action, reason: String) {
+        let Some(leg) = swap.leg_mut(action.role) else {
+            return;
+        };
+        leg_span(action.role, leg)
+            .in_scope(|| warn!(action = ?action.kind, "{:?} held back: {reason}", action.kind));
+        leg.intend(action.kind, now(), &self.policy());
+        leg.sent(Err(reason));
+    }
+}
+
+/// Span for chain calls on one leg of the current swap
+fn leg_span(role: Role, leg: &Leg) -> Span {
+    info_span!(
+        "leg",
+        ?role,
+        chain = %leg.chain,
+        escrow_id = %hex::encode(leg.escrow_id),
+    )
+}
+
+/// Key of one incident of a swap; every key of the swap starts with that of `""`
+fn alert_key(order_id: &str, incident: &str) -> String {
+    format!("swap:{order_id}:{incident}")
+}
+
+/// Order IDs are free-form, so secrets are named by their hash
+fn secret_name(order_id: &str) -> String {
+    format!(
+        "preimages/{}",
+        hex::encode(Sha256::digest(order_id.as_bytes()))
+    )
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,60 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+
+use crate::swap::Swap;
+use crate::Result;
+
+/// Swaps persisted as one JSON file each, replaced atomically on every save so a crash
+/// leaves either the old or the new state on disk
+#[derive(Clone, Debug)]
+pub struct SwapStore {
+    dir: PathBuf,
+}
+
+impl SwapStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    pub fn save(&self, swap: &Swap) -> Result<()> {
+        let path = self.path(&swap.order_id);
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_vec_pretty(swap)?)?;
+        fs::rename(temporary, path)?;
+        Ok(())
+    }
+
+    pub fn load(&self, order_id: &str) -> Result<Option<Swap>> {
+        match fs::read(self.path(order_id)) {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn all(&self) -> Result<Vec<Swap>> {
+        let mut swaps = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                swaps.push(serde_json::from_slice(&fs::read(path)?)?);
+            }
+        }
+        Ok(swaps)
+    }
+
+    /// Order IDs are free-form, so files are named by their hash
+    fn path(&self, order_id: &str) -> PathBuf {
+        let name = hex::encode(Sha256::digest(order_id.as_bytes()));
+        self.dir.join(format!("{name}.json"))
+    }
+}
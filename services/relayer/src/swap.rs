@@ -0,0 +1,447 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use unite_core::{ChainEvent, EscrowEvent, EscrowId, Hashlock, Secret, TxId};
+
+use crate::{Error, Result};
+
+/// Which side of the order an escrow locks
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    /// The maker's funds, claimed by the resolver once the secret is out
+    Source,
+    /// The resolver's funds, claimed by the maker, which reveals the secret
+    Destination,
+}
+
+impl Role {
+    /// Name as serialized
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Source => "source",
+            Role::Destination => "destination",
+        }
+    }
+}
+
+/// An escrow a maker or resolver reports for an order
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Announcement {
+    pub order_id: String,
+    #[serde(with = "hex")]
+    pub hashlock: Hashlock,
+    pub role: Role,
+    /// Name the chain's adapter reports, e.g. `stellar` or `base`
+    pub chain: String,
+    #[serde(with = "hex")]
+    pub escrow_id: EscrowId,
+}
+
+/// Terms an escrow was created with, as its chain reports them
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lock {
+    pub amount: u128,
+    #[serde(with = "hex")]
+    pub hashlock: Hashlock,
+    pub timelock: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    Claimed,
+    Refunded,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActionKind {
+    Claim,
+    Refund,
+}
+
+/// A claim or refund the relayer is sending, kept until the leg's settlement event arrives
+///
+/// It is saved as an intent, with neither `tx` nor `error`, before it goes out, so a relayer
+/// restarted mid-send knows to check the chain before sending it again.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Submission {
+    pub action: ActionKind,
+    pub tx: Option<TxId>,
+    pub error: Option<String>,
+    /// Unix seconds
+    pub at: u64,
+    /// Times this action was sent or held back, setting how long the next try waits
+    #[serde(default)]
+    pub attempts: u32,
+    /// Unix seconds before which it is not tried again
+    #[serde(default)]
+    pub retry_at: u64,
+}
+
+/// One escrow of a swap
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Leg {
+    pub chain: String,
+    #[serde(with = "hex")]
+    pub escrow_id: EscrowId,
+    /// `None` until the creation is seen on chain
+    pub lock: Option<Lock>,
+    pub outcome: Option<Outcome>,
+    /// Transaction that claimed or refunded the escrow, whoever sent it
+    #[serde(default)]
+    pub settled_tx: Option<TxId>,
+    /// Height of the last event applied, where watching resumes after a restart
+    pub height: u64,
+    pub submitted: Option<Submission>,
+}
+
+impl Leg {
+    /// Created and not yet settled
+    pub fn is_locked(&self) -> bool {
+        self.lock.is_some() && self.outcome.is_none()
+    }
+
+    /// Whether `kind` was tried before and its settlement not seen since, so the chain may
+    /// already have it
+    pub fn is_retry(&self, kind: ActionKind) -> bool {
+        self.submitted
+            .as_ref()
+            .is_some_and(|submitted| submitted.action == kind)
+    }
+
+    /// Record the intent to send `kind`, backing off from `retry_secs` and doubling up to
+    /// `max_retry_secs` with each try of the same action
+    pub fn intend(&mut self, kind: ActionKind, now: u64, policy: &Policy) {
+        let attempts = match &self.submitted {
+            Some(submitted) if submitted.action == kind => submitted.attempts.saturating_add(1),
+            _ => 1,
+        };
+        let backoff = policy
+            .retry_secs
+            .saturating_mul(1 << (attempts - 1).min(16))
+            .min(policy.max_retry_secs);
+        self.submitted = Some(Submission {
+            action: kind,
+            tx: None,
+            error: None,
+            at: now,
+            attempts,
+            retry_at: now.saturating_add(backoff),
+        });
+    }
+
+    /// Record how the intended send went
+    pub fn sent(&mut self, result: std::result::Result<TxId, String>) {
+        if let Some(submitted) = &mut self.submitted {
+            match result {
+                Ok(tx) => submitted.tx = Some(tx),
+                Err(error) => submitted.error = Some(error),
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// Waiting for both escrows to be announced and created
+    Pending,
+    /// Both escrows created on matching terms; the maker can reveal the secret
+    Locked,
+    /// Both escrows created, but their terms do not match; the maker should not reveal
+    Mismatched(String),
+    /// The secret is public; claiming the legs still locked
+    Revealed,
+    /// A timelock passed without the secret; refunding the legs still locked
+    Expired,
+    /// Both legs claimed or refunded
+    Settled,
+}
+
+impl Phase {
+    /// Name without the mismatch reason, as serialized
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Pending => "pending",
+            Phase::Locked => "locked",
+            Phase::Mismatched(_) => "mismatched",
+            Phase::Revealed => "revealed",
+            Phase::Expired => "expired",
+            Phase::Settled => "settled",
+        }
+    }
+}
+
+/// Tunables for driving swaps; a running relayer takes new values on a config reload
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    /// How much earlier than the source escrow the destination escrow must expire, so the
+    /// resolver can still claim the source after the maker claims the destination at the
+    /// last moment
+    pub min_timelock_gap_secs: u64,
+    /// Wait before resending a claim or refund whose settlement has not been seen, doubling
+    /// with each resend
+    pub retry_secs: u64,
+    /// Longest wait between resends
+    pub max_retry_secs: u64,
+    /// How often swaps are re-checked for expired timelocks and due retries; a reload only
+    /// applies it to swaps started afterwards
+    pub tick_secs: u64,
+    /// Highest fee estimate, per chain, at which claims and refunds are sent, in the chain's
+    /// `FeeEstimate::max_fee` unit; above it they back off like a resend, so keep
+    /// it well clear of what a claim before the timelock is worth
+    pub max_fee: BTreeMap<String, u64>,
+    /// How long a swap may stay in one phase before it is alerted as stuck
+    pub stuck_secs: u64,
+    /// How close a locked leg's timelock may come while its swap has not settled before it
+    /// is alerted; critical once the secret is out and the leg still needs its claim
+    pub deadline_alert_secs: u64,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            min_timelock_gap_secs: 3_600,
+            retry_secs: 60,
+            max_retry_secs: 3_600,
+            tick_secs: 15,
+            max_fee: BTreeMap::new(),
+            stuck_secs: 3_600,
+            deadline_alert_secs: 1_800,
+        }
+    }
+}
+
+/// A claim or refund to send for one leg
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Action {
+    pub role: Role,
+    pub kind: ActionKind,
+}
+
+/// A source and destination escrow locked to the same hashlock for one order
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Swap {
+    pub order_id: String,
+    #[serde(with = "hex")]
+    pub hashlock: Hashlock,
+    pub source: Option<Leg>,
+    pub destination: Option<Leg>,
+    #[serde(default, with = "hex_option")]
+    pub secret: Option<Secret>,
+    pub phase: Phase,
+    /// Unix seconds
+    pub updated_at: u64,
+}
+
+impl Swap {
+    pub fn new(order_id: String, hashlock: Hashlock) -> Self {
+        Self {
+            order_id,
+            hashlock,
+            source: None,
+            destination: None,
+            secret: None,
+            phase: Phase::Pending,
+            updated_at: 0,
+        }
+    }
+
+    pub fn leg(&self, role: Role) -> Option<&Leg> {
+        match role {
+            Role::Source => self.source.as_ref(),
+            Role::Destination => self.destination.as_ref(),
+        }
+    }
+
+    pub fn leg_mut(&mut self, role: Role) -> Option<&mut Leg> {
+        match role {
+            Role::Source => self.source.as_mut(),
+            Role::Destination => self.destination.as_mut(),
+        }
+    }
+
+    /// Record an announced escrow, returning whether it is new; announcing the same escrow
+    /// again is a no-op, announcing another one for a taken role is a conflict
+    pub fn announce(&mut self, announcement: &Announcement) -> Result<bool> {
+        if announcement.order_id != self.order_id || announcement.hashlock != self.hashlock {
+            return Err(Error::Conflict("hashlock differs from the order's"));
+        }
+        let slot = match announcement.role {
+            Role::Source => &mut self.source,
+            Role::Destination => &mut self.destination,
+        };
+        match slot {
+            Some(leg)
+                if leg.chain == announcement.chain && leg.escrow_id == announcement.escrow_id =>
+            {
+                Ok(false)
+            }
+            Some(_) => Err(Error::Conflict("another escrow holds this role")),
+            None => {
+                *slot = Some(Leg {
+                    chain: announcement.chain.clone(),
+                    escrow_id: announcement.escrow_id,
+                    lock: None,
+                    outcome: None,
+                    settled_tx: None,
+                    height: 0,
+                    submitted: None,
+                });
+                Ok(true)
+            }
+        }
+    }
+
+    /// Apply a chain event of one leg; events replayed after a restart apply again harmlessly
+    pub fn apply(&mut self, role: Role, event: &ChainEvent) {
+        let Some(leg) = self.leg_mut(role) else {
+            return;
+        };
+        leg.height = leg.height.max(event.height);
+        match (&event.event, event.removed) {
+            (
+                EscrowEvent::Created {
+                    amount,
+                    hashlock,
+                    timelock,
+                },
+                false,
+            ) => {
+                leg.lock = Some(Lock {
+                    amount: *amount,
+                    hashlock: *hashlock,
+                    timelock: *timelock,
+                })
+            }
+            (EscrowEvent::Created { .. }, true) => leg.lock = None,
+            (EscrowEvent::Claimed { secret }, false) => {
+                leg.outcome = Some(Outcome::Claimed);
+                leg.settled_tx = event.tx.clone();
+                leg.submitted = None;
+                if let Some(secret) = secret {
+                    self.secret.get_or_insert_with(|| secret.clone());
+                }
+            }
+            (EscrowEvent::Refunded, false) => {
+                leg.outcome = Some(Outcome::Refunded);
+                leg.settled_tx = event.tx.clone();
+                leg.submitted = None;
+            }
+            // A reorg dropped the settlement; the secret, once seen, stays public
+            (EscrowEvent::Claimed { .. } | EscrowEvent::Refunded, true) => {
+                leg.outcome = None;
+                leg.settled_tx = None;
+            }
+        }
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.phase == Phase::Settled
+    }
+
+    /// Update the phase and list the claims and refunds due at `now`
+    ///
+    /// Locked legs are claimed once the secret is known and refunded once their timelock
+    /// passes; a leg with a recent submission waits until its `retry_at` for its settlement
+    /// event.
+    pub fn step(&mut self, now: u64, policy: &Policy) -> Vec<Action> {
+        let legs = [
+            (Role::Source, self.source.as_ref()),
+            (Role::Destination, self.destination.as_ref()),
+        ];
+        if legs
+            .iter()
+            .all(|(_, leg)| leg.is_some_and(|leg| leg.outcome.is_some()))
+        {
+            self.phase = Phase::Settled;
+            return Vec::new();
+        }
+
+        let mut actions = Vec::new();
+        let mut expired = false;
+        for (role, leg) in legs {
+            let Some(leg) = leg.filter(|leg| leg.is_locked()) else {
+                continue;
+            };
+            let timelock = leg.lock.as_ref().map_or(0, |lock| lock.timelock);
+            let kind = if now >= timelock {
+                expired = true;
+                ActionKind::Refund
+            } else if self.secret.is_some() {
+                ActionKind::Claim
+            } else {
+                continue;
+            };
+            let waiting = leg
+                .submitted
+                .as_ref()
+                .is_some_and(|submitted| submitted.action == kind && now < submitted.retry_at);
+            if !waiting {
+                actions.push(Action { role, kind });
+            }
+        }
+
+        self.phase = if self.secret.is_some() {
+            Phase::Revealed
+        } else if expired {
+            Phase::Expired
+        } else if let (Some(source), Some(destination)) = (
+            self.source.as_ref().and_then(|leg| leg.lock.as_ref()),
+            self.destination.as_ref().and_then(|leg| leg.lock.as_ref()),
+        ) {
+            match self.mismatch(source, destination, policy) {
+                Some(reason) => Phase::Mismatched(reason),
+                None => Phase::Locked,
+            }
+        } else {
+            Phase::Pending
+        };
+        actions
+    }
+
+    fn mismatch(&self, source: &Lock, destination: &Lock, policy: &Policy) -> Option<String> {
+        if source.hashlock != self.hashlock {
+            return Some("source hashlock differs from the order's".to_string());
+        }
+        if destination.hashlock != self.hashlock {
+            return Some("destination hashlock differs from the order's".to_string());
+        }
+        if destination
+            .timelock
+            .saturating_add(policy.min_timelock_gap_secs)
+            > source.timelock
+        {
+            return Some(format!(
+                "destination timelock {} is less than {}s before source timelock {}",
+                destination.timelock, policy.min_timelock_gap_secs, source.timelock
+            ));
+        }
+        None
+    }
+}
+
+mod hex_option {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        value: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(bytes) => serializer.serialize_some(&hex::encode(bytes)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| hex::decode(value).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::order::Asset;
+use crate::Result;
+
+/// Seconds in a UTC day, the span of one report
+pub const DAY_SECS: u64 = 86_400;
+
+/// How a fill ended for the resolver
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Settlement {
+    /// Either leg is still locked
+    Open,
+    /// The maker claimed the destination and the resolver the source
+    Completed,
+    /// The destination was refunded, so the fill cost only its fees
+    Unwound,
+    /// The maker claimed the destination but the source went back to the maker
+    Lost,
+}
+
+impl Settlement {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Completed => "completed",
+            Self::Unwound => "unwound",
+            Self::Lost => "lost",
+        }
+    }
+}
+
+/// What one fill cost and earned, amounts in the tokens' smallest units
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Booking {
+    pub order_id: String,
+    /// Unix seconds the destination escrow was locked
+    pub filled_at: u64,
+    pub source: Asset,
+    /// Source amount the resolver claims once the maker reveals the secret
+    pub received: u128,
+    pub destination: Asset,
+    /// Destination amount locked for the maker, the fill's cost basis
+    pub cost: u128,
+    /// Market price when the fill was booked, in destination units per source unit
+    pub price: f64,
+    /// Native currency of the destination chain held as the escrow's safety deposit until
+    /// the fill settles
+    pub safety_deposit: u128,
+    pub settlement: Settlement,
+    /// Unix seconds the swap was seen settled
+    pub settled_at: Option<u64>,
+    /// Native currency of the source chain paid to claim the source; `None` until settled
+    /// or where the chain's fees cannot be read
+    pub source_fee: Option<u128>,
+    /// Native currency of the destination chain paid to lock and, when unwound, refund the
+    /// destination; `None` like `source_fee`
+    pub destination_fee: Option<u128>,
+}
+
+impl Booking {
+    /// Value received at the booked price less the cost basis, in destination units, once
+    /// settled; fees are left out, being paid in the chains' native currencies
+    pub fn spread(&self) -> Option<i128> {
+        let cost = self.cost.min(i128::MAX as u128) as i128;
+        match self.settlement {
+            Settlement::Open => None,
+            Settlement::Completed => {
+                Some((self.received as f64 * self.price).round() as i128 - cost)
+            }
+            Settlement::Unwound => Some(0),
+            Settlement::Lost => Some(-cost),
+        }
+    }
+
+    /// `spread` in basis points of the cost basis
+    pub fn spread_bps(&self) -> Option<f64> {
+        let spread = self.spread()?;
+        (self.cost > 0).then(|| spread as f64 * 10_000.0 / self.cost as f64)
+    }
+
+    fn is_on(&self, day: u64) -> bool {
+        self.filled_at / DAY_SECS == day
+            || self
+                .settled_at
+                .is_some_and(|settled_at| settled_at / DAY_SECS == day)
+    }
+
+    fn settled_on(&self, day: u64) -> bool {
+        self.settled_at
+            .is_some_and(|settled_at| settled_at / DAY_SECS == day)
+    }
+}
+
+/// One asset's line in a day's inventory report
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Position {
+    /// Locked in fills still open, as cost basis or safety deposits
+    pub locked: u128,
+    /// Claimed from makers in fills settled that day
+    pub received: u128,
+    /// Paid to makers in fills settled that day
+    pub paid: u128,
+    /// Fees of fills settled that day, for a chain's native currency
+    pub fees: u128,
+    /// Held when the report was written, where the chain can read it
+    pub balance: Option<u128>,
+}
+
+/// Bookings persisted as one JSON file each, with the daily reports written next to them
+///
+/// Each report comes as two CSV files under `reports/`: `fills-<date>.csv` lists the fills
+/// made or settled that UTC day and those still open, `inventory-<date>.csv` each asset's
+/// position.
+#[derive(Clone, Debug)]
+pub struct Ledger {
+    dir: PathBuf,
+}
+
+impl Ledger {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        fs::create_dir_all(dir.as_ref().join(REPORTS))?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    pub fn save(&self, booking: &Booking) -> Result<()> {
+        let path = self.path(&booking.order_id);
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_vec_pretty(booking)?)?;
+        fs::rename(temporary, path)?;
+        Ok(())
+    }
+
+    pub fn load(&self, order_id: &str) -> Result<Option<Booking>> {
+        match fs::read(self.path(order_id)) {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn all(&self) -> Result<Vec<Booking>> {
+        let mut bookings = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                bookings.push(serde_json::from_slice(&fs::read(path)?)?);
+            }
+        }
+        Ok(bookings)
+    }
+
+    /// Whether the report of `day`, counted in days since the Unix epoch, was written
+    pub fn has_report(&self, day: u64) -> bool {
+        self.report_path("inventory", day).exists()
+    }
+
+    /// Write the report of `day` from the bookings, with `balances` read beforehand,
+    /// replacing an earlier one; returns the fills report's path
+    pub fn write_report(&self, day: u64, balances: &HashMap<Asset, u128>) -> Result<PathBuf> {
+        let mut bookings: Vec<_> = self
+            .all()?
+            .into_iter()
+            .filter(|booking| booking.is_on(day) || booking.settlement == Settlement::Open)
+            .collect();
+        bookings.sort_by(|a, b| (a.filled_at, &a.order_id).cmp(&(b.filled_at, &b.order_id)));
+
+        let mut fills = String::from(
+            "order_id,filled_at,settled_at,settlement,source_chain,source_token,received,\
+             destination_chain,destination_token,cost,price,safety_deposit,source_fee,\
+             destination_fee,spread,spread_bps\n",
+        );
+        for booking in &bookings {
+            let fields = [
+                field(&booking.order_id),
+                timestamp(booking.filled_at),
+                booking.settled_at.map(timestamp).unwrap_or_default(),
+                booking.settlement.as_str().to_string(),
+                field(&booking.source.chain),
+                field(booking.source.token.as_deref().unwrap_or("")),
+                booking.received.to_string(),
+                field(&booking.destination.chain),
+                field(booking.destination.token.as_deref().unwrap_or("")),
+                booking.cost.to_string(),
+                booking.price.to_string(),
+                booking.safety_deposit.to_string(),
+                optional(booking.source_fee),
+                optional(booking.destination_fee),
+                optional(booking.spread()),
+                booking
+                    .spread_bps()
+                    .map(|bps| format!("{bps:.2}"))
+                    .unwrap_or_default(),
+            ];
+            fills.push_str(&fields.join(","));
+            fills.push('\n');
+        }
+
+        let mut inventory = String::from("chain,token,locked,received,paid,fees,balance\n");
+        for (asset, position) in positions(&bookings, day, balances) {
+            let _ = writeln!(
+                inventory,
+                "{},{},{},{},{},{},{}",
+                field(&asset.chain),
+                field(asset.token.as_deref().unwrap_or("")),
+                position.locked,
+                position.received,
+                position.paid,
+                position.fees,
+                optional(position.balance)
+            );
+        }
+
+        let path = self.report_path("fills", day);
+        fs::write(&path, fills)?;
+        // Written last, since its presence marks the report done
+        fs::write(self.report_path("inventory", day), inventory)?;
+        Ok(path)
+    }
+
+    fn path(&self, order_id: &str) -> PathBuf {
+        let name = hex::encode(Sha256::digest(order_id.as_bytes()));
+        self.dir.join(format!("{name}.json"))
+    }
+
+    fn report_path(&self, kind: &str, day: u64) -> PathBuf {
+        self.dir
+            .join(REPORTS)
+            .join(format!("{kind}-{}.csv", date(day)))
+    }
+}
+
+const REPORTS: &str = "reports";
+
+/// Each asset's position on `day`, ordered by chain and token with the native currency first
+fn positions(
+    bookings: &[Booking],
+    day: u64,
+    balances: &HashMap<Asset, u128>,
+) -> Vec<(Asset, Position)> {
+    let mut positions = Positions::new();
+    for booking in bookings {
+        let (source, destination) = (&booking.source, &booking.destination);
+        if booking.settlement == Settlement::Open {
+            position(&mut positions, &destination.chain, &destination.token).locked += booking.cost;
+            position(&mut positions, &destination.chain, &None).locked += booking.safety_deposit;
+            continue;
+        }
+        if !booking.settled_on(day) {
+            continue;
+        }
+        if booking.settlement == Settlement::Completed {
+            position(&mut positions, &source.chain, &source.token).received += booking.received;
+        }
+        if let Settlement::Completed | Settlement::Lost = booking.settlement {
+            position(&mut positions, &destination.chain, &destination.token).paid += booking.cost;
+        }
+        position(&mut positions, &source.chain, &None).fees +=
+            booking.source_fee.unwrap_or_default();
+        position(&mut positions, &destination.chain, &None).fees +=
+            booking.destination_fee.unwrap_or_default();
+    }
+    for (asset, balance) in balances {
+        position(&mut positions, &asset.chain, &asset.token).balance = Some(*balance);
+    }
+    positions
+        .into_iter()
+        .map(|((chain, token), position)| (Asset { chain, token }, position))
+        .collect()
+}
+
+type Positions = BTreeMap<(String, Option<String>), Position>;
+
+fn position<'a>(
+    positions: &'a mut Positions,
+    chain: &str,
+    token: &Option<String>,
+) -> &'a mut Position {
+    positions
+        .entry((chain.to_string(), token.clone()))
+        .or_default()
+}
+
+/// A CSV field, quoted when it holds a separator, quote or line break
+fn field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn optional(value: Option<impl ToString>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// `YYYY-MM-DD` of a day counted since the Unix epoch, in the proleptic Gregorian calendar
+pub fn date(day: u64) -> String {
+    let days = day as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day_of_month = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day_of_month:02}")
+}
+
+/// RFC 3339 UTC time of Unix seconds
+fn timestamp(secs: u64) -> String {
+    let time = secs % DAY_SECS;
+    format!(
+        "{}T{:02}:{:02}:{:02}Z",
+        date(secs / DAY_SECS),
+        time / 3_600,
+        time / 60 % 60,
+        time % 60
+    )
+}
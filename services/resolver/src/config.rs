@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use unite_alert::AlertConfig;
+use unite_config::{Layers, Problems, Tokens, Validate};
+use unite_relayer::{validate_chains, validate_policy, ChainConfig, Policy};
+use unite_secrets::SecretsConfig;
+use unite_telemetry::TracingConfig;
+
+use crate::order::Asset;
+use crate::strategy::{InventoryLimit, Market, Spread};
+use crate::Result;
+
+/// Resolver settings, read from a TOML file with its environment's overlay and
+/// `UNITE_RESOLVER__*` overrides; chains are configured as for the relayer
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    /// URL returning the open orders
+    pub feed_url: String,
+    #[serde(default = "default_poll_secs")]
+    pub poll_secs: u64,
+    /// Address to serve Prometheus metrics on, at `/metrics`; off without it
+    pub metrics_listen: Option<SocketAddr>,
+    /// Address to serve `/healthz` and `/readyz` on; off without it
+    pub health_listen: Option<SocketAddr>,
+    /// Log format and OTLP export
+    #[serde(default)]
+    pub tracing: TracingConfig,
+    /// Directory fills, their bookings and reports, and the embedded relayer's swaps are
+    /// persisted in
+    pub store: PathBuf,
+    #[serde(default)]
+    pub policy: Policy,
+    /// How often to check the config files for edits and apply a changed `[policy]`,
+    /// `[strategy]`, `[tokens]` and `[[markets]]`; other changes wait for a restart. Off
+    /// without it
+    pub reload_secs: Option<u64>,
+    pub strategy: StrategyConfig,
+    /// Token symbols markets can use in place of addresses
+    #[serde(default)]
+    pub tokens: Tokens,
+    pub markets: Vec<Market>,
+    /// Where keys named by `*_store` fields are kept, and revealed secrets while swaps are open
+    pub secrets: Option<SecretsConfig>,
+    /// Chats failed fills, low balances and the embedded relayer's incidents are sent to
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    pub chains: Vec<ChainConfig>,
+}
+
+/// Settings of the built-in `Spread` strategy
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub struct StrategyConfig {
+    pub min_spread_bps: u32,
+    /// Spread held out for early in an auction; defaults to `min_spread_bps`
+    #[serde(default)]
+    pub target_spread_bps: u32,
+    /// Share of the auction, in basis points, after which `min_spread_bps` is accepted
+    #[serde(default)]
+    pub patience_bps: u32,
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::layers(path).load()?)
+    }
+
+    /// Where the config at `path` is read from, for watching it
+    pub fn layers(path: impl AsRef<Path>) -> Layers {
+        Layers::new(path.as_ref(), "UNITE_RESOLVER")
+    }
+
+    /// Markets with token symbols replaced by their addresses
+    pub fn markets(&self) -> Vec<Market> {
+        let resolve = |asset: &Asset| Asset {
+            chain: asset.chain.clone(),
+            token: asset
+                .token
+                .as_deref()
+                .map(|token| self.tokens.resolve(&asset.chain, token).to_string()),
+        };
+        self.markets
+            .iter()
+            .map(|market| Market {
+                source: resolve(&market.source),
+                destination: resolve(&market.destination),
+                ..market.clone()
+            })
+            .collect()
+    }
+
+    /// Each market's `min_balance` of its destination asset
+    pub fn min_balances(&self) -> HashMap<Asset, u128> {
+        self.markets()
+            .into_iter()
+            .filter_map(|market| Some((market.destination, market.min_balance?)))
+            .collect()
+    }
+
+    /// The built-in strategy, limited by each market's `max_inventory`
+    pub fn strategy(&self) -> InventoryLimit<Spread> {
+        let markets = self.markets();
+        let limits: HashMap<_, _> = markets
+            .iter()
+            .filter_map(|market| {
+                market
+                    .max_inventory
+                    .map(|limit| (market.destination.clone(), limit))
+            })
+            .collect();
+        InventoryLimit {
+            inner: Spread {
+                markets,
+                min_spread_bps: self.strategy.min_spread_bps,
+                target_spread_bps: self.strategy.target_spread_bps,
+                patience_bps: self.strategy.patience_bps,
+            },
+            limits,
+        }
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.poll_secs > 0, || {
+            "poll_secs must be positive".to_string()
+        });
+        validate_chains(&self.chains, problems);
+        validate_policy(&self.policy, &self.chains, problems);
+        problems.section("strategy", &self.strategy);
+        problems.section("alerts", &self.alerts);
+        for (index, market) in self.markets.iter().enumerate() {
+            for asset in [&market.source, &market.destination] {
+                problems.check(
+                    self.chains.iter().any(|chain| chain.name() == asset.chain),
+                    || format!("markets[{index}]: no chain named {}", asset.chain),
+                );
+                if let Some(token) = &asset.token {
+                    problems.check(
+                        !self.tokens.is_symbol(token) || self.tokens.has(&asset.chain, token),
+                        || {
+                            format!(
+                                "markets[{index}]: {token} has no address on {}",
+                                asset.chain
+                            )
+                        },
+                    );
+                }
+            }
+            problems.check(market.price.is_finite() && market.price > 0.0, || {
+                format!("markets[{index}]: price must be positive")
+            });
+        }
+    }
+}
+
+impl Validate for StrategyConfig {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.min_spread_bps < 10_000, || {
+            "min_spread_bps must be below 10000".to_string()
+        });
+        problems.check(
+            self.target_spread_bps == 0 || self.target_spread_bps >= self.min_spread_bps,
+            || "target_spread_bps must be at least min_spread_bps".to_string(),
+        );
+        problems.check(self.patience_bps <= 10_000, || {
+            "patience_bps must be at most 10000".to_string()
+        });
+    }
+}
+
+fn default_poll_secs() -> u64 {
+    5
+}
@@ -0,0 +1,44 @@
+use async_trait::async_trait;
+use unite_telemetry::{Probe, Reading};
+
+use crate::order::Order;
+use crate::Result;
+
+/// Order feed polled over HTTP; `GET` on the URL returns the open orders as a JSON array
+#[derive(Clone, Debug)]
+pub struct OrderFeed {
+    http: reqwest::Client,
+    url: String,
+}
+
+impl OrderFeed {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            url: url.into(),
+        }
+    }
+
+    pub async fn orders(&self) -> Result<Vec<Order>> {
+        Ok(self
+            .http
+            .get(&self.url)
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
+
+#[async_trait]
+impl Probe for OrderFeed {
+    fn name(&self) -> String {
+        "feed".to_string()
+    }
+
+    async fn probe(&self) -> std::result::Result<Reading, String> {
+        self.orders().await.map_err(|error| error.to_string())?;
+        Ok(Reading::default())
+    }
+}
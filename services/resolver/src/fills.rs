@@ -0,0 +1,72 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use unite_core::{EscrowId, TxId};
+
+use crate::order::Order;
+use crate::Result;
+
+/// A destination escrow the resolver locked for an order
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Fill {
+    pub order: Order,
+    pub amount: u128,
+    #[serde(with = "hex")]
+    pub escrow_id: EscrowId,
+    pub tx: TxId,
+    /// Unix seconds
+    pub at: u64,
+}
+
+/// Fills persisted as one JSON file each, so a restarted resolver never fills an order twice
+#[derive(Clone, Debug)]
+pub struct FillStore {
+    dir: PathBuf,
+}
+
+impl FillStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    pub fn save(&self, fill: &Fill) -> Result<()> {
+        let path = self.path(&fill.order.order_id);
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_vec_pretty(fill)?)?;
+        fs::rename(temporary, path)?;
+        Ok(())
+    }
+
+    pub fn load(&self, order_id: &str) -> Result<Option<Fill>> {
+        match fs::read(self.path(order_id)) {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn all(&self) -> Result<Vec<Fill>> {
+        let mut fills = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                fills.push(serde_json::from_slice(&fs::read(path)?)?);
+            }
+        }
+        Ok(fills)
+    }
+
+    fn path(&self, order_id: &str) -> PathBuf {
+        let name = hex::encode(Sha256::digest(order_id.as_bytes()));
+        self.dir.join(format!("{name}.json"))
+    }
+}
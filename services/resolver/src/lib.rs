@@ -0,0 +1,58 @@
+// Resolver Bot
+// Takes the other side of makers' orders. Orders come from the order feed with the maker's
+// source escrow already locked and a Dutch auction on what the maker wants on the destination
+// chain. A `Strategy` prices each order at the auction's current rate: the built-in one
+// fills when the spread against configured market prices clears a target that relaxes as the
+// auction runs, within per-asset inventory limits. Filling locks the destination escrow and
+// hands both legs to an embedded relayer, which claims the source once the maker's claim
+// reveals the secret and refunds the destination if the maker never claims. Decisions, fills,
+// fill latency and locked inventory are exported to Prometheus alongside the relayer's metrics.
+// Failed fills and destination balances below a market's `min_balance` are alerted, next to
+// the embedded relayer's alerts. `/readyz` probes every chain's RPC and the order feed. Each
+// fill is booked in a ledger with its cost basis, fees, safety deposit and, once settled,
+// realized spread; a CSV report of the fills and per-asset inventory is written every day.
+
+pub mod accounting;
+pub mod config;
+pub mod feed;
+pub mod fills;
+pub mod metrics;
+pub mod order;
+pub mod resolver;
+pub mod strategy;
+
+pub use accounting::{Booking, Ledger, Position, Settlement};
+pub use config::{Config, StrategyConfig};
+pub use feed::OrderFeed;
+pub use fills::{Fill, FillStore};
+pub use order::{Asset, Auction, AuctionPoint, Destination, Order, SourceEscrow};
+pub use resolver::Resolver;
+pub use strategy::{Decision, Inventory, InventoryLimit, Market, Spread, Strategy};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error("no adapter for chain {0}")]
+    UnknownChain(String),
+    #[error("strategy: {0}")]
+    Strategy(String),
+    #[error("source escrow: {0}")]
+    Source(String),
+    #[error("order feed: {0}")]
+    Feed(#[from] reqwest::Error),
+    #[error(transparent)]
+    Alert(#[from] unite_alert::Error),
+    #[error(transparent)]
+    Relayer(#[from] unite_relayer::Error),
+    #[error(transparent)]
+    Chain(#[from] unite_core::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Load(#[from] unite_config::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use tracing::{error, info, warn};
+use unite_alert::Alerter;
+use unite_relayer::{Relayer, SwapStore};
+use unite_resolver::{Config, FillStore, Ledger, OrderFeed, Resolver};
+
+/// `unite-resolver [config.toml]`, logging through `RUST_LOG`
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "resolver.toml".to_string());
+    let config = Config::load(&path)?;
+    let _telemetry = unite_telemetry::init("unite-resolver", &config.tracing)?;
+    if let Some(listen) = config.metrics_listen {
+        unite_relayer::metrics::install(listen)?;
+        unite_resolver::metrics::describe();
+        info!("metrics on http://{listen}/metrics");
+    }
+
+    let alerts = Alerter::new("unite-resolver", &config.alerts)?;
+    let secrets = match &config.secrets {
+        Some(secrets) => Some(secrets.open().await?),
+        None => None,
+    };
+    let mut chains = Vec::new();
+    for chain in &config.chains {
+        chains.push(chain.connect(secrets.as_deref()).await?);
+    }
+    let relayer = Relayer::new(
+        chains.clone(),
+        SwapStore::open(config.store.join("swaps"))?,
+        config.policy.clone(),
+        secrets,
+        alerts.clone(),
+    );
+    info!("resumed {} unsettled swaps", relayer.recover()?);
+    let feed = Arc::new(OrderFeed::new(&config.feed_url));
+    if let Some(listen) = config.health_listen {
+        let health = relayer.health().with(feed.clone());
+        tokio::spawn(async move {
+            if let Err(error) = unite_telemetry::health::serve(listen, health).await {
+                error!("health endpoint: {error}");
+            }
+        });
+        info!("health on http://{listen}/readyz");
+    }
+    let resolver = Arc::new(
+        Resolver::new(
+            chains,
+            relayer,
+            Box::new(config.strategy()),
+            FillStore::open(config.store.join("fills"))?,
+            Ledger::open(config.store.join("ledger"))?,
+            config.policy.clone(),
+        )
+        .with_alerts(alerts),
+    );
+    resolver.set_min_balances(config.min_balances());
+    resolver.set_markets(config.markets());
+    if let Some(secs) = config.reload_secs {
+        let resolver = resolver.clone();
+        unite_config::watch(
+            Config::layers(&path),
+            Duration::from_secs(secs),
+            move |reloaded: unite_config::Result<Config>| match reloaded {
+                Ok(config) => {
+                    resolver.set_strategy(Box::new(config.strategy()));
+                    resolver.set_min_balances(config.min_balances());
+                    resolver.set_markets(config.markets());
+                    resolver.set_policy(config.policy);
+                    info!("reloaded [policy], [strategy], [tokens] and markets");
+                }
+                Err(error) => warn!("config not reloaded: {error}"),
+            },
+        );
+    }
+
+    info!("polling {}", config.feed_url);
+    tokio::select! {
+        _ = resolver.run(&feed, Duration::from_secs(config.poll_secs)) => {}
+        _ = tokio::signal::ctrl_c() => {}
+    }
+    Ok(())
+}
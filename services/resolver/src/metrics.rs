@@ -0,0 +1,94 @@
+// Prometheus metrics, recorded through the `metrics` facade; the binary serves them with the
+// relayer's exporter, next to the embedded relayer's own.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use metrics::{counter, describe_counter, describe_gauge, describe_histogram, gauge, histogram};
+
+use crate::order::Asset;
+use crate::strategy::{Decision, Inventory};
+
+pub fn describe() {
+    describe_counter!(
+        "unite_resolver_decisions_total",
+        "Strategy decisions, one per open order and poll"
+    );
+    describe_counter!(
+        "unite_resolver_orders_total",
+        "Orders filled or skipped; each order is counted once"
+    );
+    describe_counter!(
+        "unite_resolver_fill_errors_total",
+        "Orders whose fill failed and will be retried"
+    );
+    describe_counter!(
+        "unite_resolver_feed_errors_total",
+        "Failed order feed polls"
+    );
+    describe_histogram!(
+        "unite_resolver_fill_seconds",
+        "Time from the strategy's fill decision to the destination escrow's creation"
+    );
+    describe_histogram!(
+        "unite_resolver_fee_bid",
+        "Chain's max fee per unit when a destination escrow was created, in its smallest unit"
+    );
+    describe_gauge!(
+        "unite_resolver_inventory",
+        "Destination asset locked in unsettled fills, in its smallest unit"
+    );
+}
+
+pub(crate) fn decided(decision: &Decision) {
+    let decision = match decision {
+        Decision::Fill { .. } => "fill",
+        Decision::Wait => "wait",
+        Decision::Skip(_) => "skip",
+    };
+    counter!("unite_resolver_decisions_total", "decision" => decision).increment(1);
+}
+
+pub(crate) fn filled(elapsed: Duration) {
+    counter!("unite_resolver_orders_total", "outcome" => "filled").increment(1);
+    histogram!("unite_resolver_fill_seconds").record(elapsed);
+}
+
+pub(crate) fn skipped() {
+    counter!("unite_resolver_orders_total", "outcome" => "skipped").increment(1);
+}
+
+pub(crate) fn fill_error() {
+    counter!("unite_resolver_fill_errors_total").increment(1);
+}
+
+pub(crate) fn feed_error() {
+    counter!("unite_resolver_feed_errors_total").increment(1);
+}
+
+pub(crate) fn fee_bid(chain: &str, max_fee: u128) {
+    histogram!("unite_resolver_fee_bid", "chain" => chain.to_string()).record(max_fee as f64);
+}
+
+/// Report each asset's locked amount; assets in `reported` that are no longer locked drop
+/// to zero
+pub(crate) fn inventory(inventory: &Inventory, reported: &mut HashSet<Asset>) {
+    let mut current = HashSet::new();
+    for (asset, amount) in inventory.iter() {
+        set_inventory(asset, amount as f64);
+        current.insert(asset.clone());
+    }
+    for asset in reported.difference(&current) {
+        set_inventory(asset, 0.0);
+    }
+    *reported = current;
+}
+
+fn set_inventory(asset: &Asset, amount: f64) {
+    gauge!(
+        "unite_resolver_inventory",
+        "chain" => asset.chain.clone(),
+        "token" => asset.token.clone().unwrap_or_default(),
+    )
+    .set(amount);
+}
@@ -0,0 +1,146 @@
+use serde::{Deserialize, Serialize};
+use unite_core::{EscrowId, Hashlock};
+
+/// Denominator of auction rate bumps (1e7 = +100%), as in the escrow contract
+pub const RATE_BUMP_BASE: u128 = 10_000_000;
+
+/// A token on one chain; `token` is `None` for the chain's native currency
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Asset {
+    pub chain: String,
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Point on a Dutch auction curve, `delay` seconds after the previous point
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuctionPoint {
+    pub delay: u64,
+    pub rate_bump: u32,
+}
+
+/// Dutch auction decaying the required taker amount from `initial_rate_bump` to zero
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Auction {
+    pub start_time: u64,
+    pub duration: u64,
+    pub initial_rate_bump: u32,
+    #[serde(default)]
+    pub points: Vec<AuctionPoint>,
+}
+
+impl Auction {
+    /// Rate bump at `now`, interpolated linearly between the points like the contract does
+    pub fn rate_bump(&self, now: u64) -> u32 {
+        let end_time = self.start_time.saturating_add(self.duration);
+        if now <= self.start_time {
+            return self.initial_rate_bump;
+        }
+        if now >= end_time {
+            return 0;
+        }
+
+        let mut point_time = self.start_time;
+        let mut rate_bump = self.initial_rate_bump as u64;
+        for point in &self.points {
+            let next_time = point_time.saturating_add(point.delay);
+            let next_bump = point.rate_bump as u64;
+            if now <= next_time {
+                return interpolate(point_time, rate_bump, next_time, next_bump, now);
+            }
+            point_time = next_time;
+            rate_bump = next_bump;
+        }
+        interpolate(point_time, rate_bump, end_time, 0, now)
+    }
+
+    /// Share of the auction elapsed at `now`, in basis points
+    pub fn progress_bps(&self, now: u64) -> u32 {
+        if self.duration == 0 || now >= self.start_time.saturating_add(self.duration) {
+            return 10_000;
+        }
+        let elapsed = now.saturating_sub(self.start_time) as u128;
+        (elapsed * 10_000 / self.duration as u128) as u32
+    }
+}
+
+fn interpolate(from_time: u64, from_bump: u64, to_time: u64, to_bump: u64, now: u64) -> u32 {
+    if to_time <= from_time {
+        return to_bump as u32;
+    }
+    let span = (to_time - from_time) as u128;
+    let weighted =
+        (now - from_time) as u128 * to_bump as u128 + (to_time - now) as u128 * from_bump as u128;
+    (weighted / span) as u32
+}
+
+/// The escrow the maker locked on the source chain, which the resolver claims
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceEscrow {
+    pub chain: String,
+    #[serde(with = "hex")]
+    pub escrow_id: EscrowId,
+    #[serde(default)]
+    pub token: Option<String>,
+    pub amount: u128,
+    pub timelock: u64,
+}
+
+/// Where the maker wants to be paid
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Destination {
+    pub chain: String,
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Maker's address on the destination chain, the destination escrow's receiver
+    pub receiver: String,
+}
+
+/// An open order from the feed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Order {
+    pub order_id: String,
+    #[serde(with = "hex")]
+    pub hashlock: Hashlock,
+    pub source: SourceEscrow,
+    pub destination: Destination,
+    /// Least the maker accepts on the destination chain, reached when the auction ends
+    pub taking_amount: u128,
+    /// `None` for a fixed `taking_amount`
+    #[serde(default)]
+    pub auction: Option<Auction>,
+}
+
+impl Order {
+    pub fn source_asset(&self) -> Asset {
+        Asset {
+            chain: self.source.chain.clone(),
+            token: self.source.token.clone(),
+        }
+    }
+
+    pub fn destination_asset(&self) -> Asset {
+        Asset {
+            chain: self.destination.chain.clone(),
+            token: self.destination.token.clone(),
+        }
+    }
+
+    /// Destination amount the auction requires at `now`
+    pub fn required_amount(&self, now: u64) -> u128 {
+        let rate_bump = self
+            .auction
+            .as_ref()
+            .map_or(0, |auction| auction.rate_bump(now));
+        self.taking_amount
+            .saturating_mul(RATE_BUMP_BASE + rate_bump as u128)
+            / RATE_BUMP_BASE
+    }
+
+    /// Share of the auction elapsed at `now`, in basis points; a fixed-price order is done
+    pub fn progress_bps(&self, now: u64) -> u32 {
+        self.auction
+            .as_ref()
+            .map_or(10_000, |auction| auction.progress_bps(now))
+    }
+}
@@ -0,0 +1,552 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use tracing::{info, info_span, warn, Instrument};
+use unite_alert::{Alerter, Severity};
+use unite_core::{ChainAdapter, EscrowEvent, EscrowParams, TxId};
+use unite_relayer::{Announcement, Outcome, Policy, Relayer, Role};
+
+use crate::accounting::{Booking, Ledger, Settlement, DAY_SECS};
+use crate::feed::OrderFeed;
+use crate::fills::{Fill, FillStore};
+use crate::metrics;
+use crate::order::{Asset, Order};
+use crate::strategy::{Decision, Inventory, Market, Strategy};
+use crate::{Error, Result};
+
+/// How long to wait for the source escrow's creation event before giving up on an order
+const SOURCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often destination balances are checked against the markets' `min_balance`
+const BALANCE_CHECK: Duration = Duration::from_secs(60);
+
+/// Fills orders the strategy accepts and leaves both legs to the embedded relayer
+///
+/// The relayer signs with the resolver's keys, so it claims the source escrow and refunds the
+/// destination escrow; the maker's side of each leg is left to the maker, and the relayer's
+/// attempts to settle it for them fail harmlessly until they do. Work on an order runs in an
+/// `order` span carrying its order ID, the same one the relayer's `swap` span carries.
+///
+/// Fills that fail on a chain, and destination balances below their market's `min_balance`,
+/// are alerted; each incident is sent once and resolved when the fill lands or the balance
+/// is topped up.
+///
+/// Every fill is booked in the ledger at its market's price and settled there once its swap
+/// settles, with the fees its transactions paid. After each UTC day its report is written, with
+/// the balances of the markets' assets at that moment.
+pub struct Resolver {
+    chains: HashMap<String, Arc<dyn ChainAdapter>>,
+    relayer: Arc<Relayer>,
+    strategy: RwLock<Box<dyn Strategy>>,
+    fills: FillStore,
+    ledger: Ledger,
+    markets: RwLock<Vec<Market>>,
+    policy: RwLock<Policy>,
+    skipped: Mutex<HashSet<String>>,
+    /// Assets with an inventory gauge, zeroed once nothing is locked in them
+    reported: Mutex<HashSet<Asset>>,
+    alerts: Alerter,
+    min_balances: RwLock<HashMap<Asset, u128>>,
+    balances_checked: Mutex<Option<Instant>>,
+}
+
+impl Resolver {
+    pub fn new(
+        chains: Vec<Arc<dyn ChainAdapter>>,
+        relayer: Arc<Relayer>,
+        strategy: Box<dyn Strategy>,
+        fills: FillStore,
+        ledger: Ledger,
+        policy: Policy,
+    ) -> Self {
+        Self {
+            chains: chains
+                .into_iter()
+                .map(|adapter| (adapter.chain().to_string(), adapter))
+                .collect(),
+            relayer,
+            strategy: RwLock::new(strategy),
+            fills,
+            ledger,
+            markets: RwLock::new(Vec::new()),
+            policy: RwLock::new(policy),
+            skipped: Mutex::new(HashSet::new()),
+            reported: Mutex::new(HashSet::new()),
+            alerts: Alerter::log_only("unite-resolver"),
+            min_balances: RwLock::new(HashMap::new()),
+            balances_checked: Mutex::new(None),
+        }
+    }
+
+    pub fn with_alerts(mut self, alerts: Alerter) -> Self {
+        self.alerts = alerts;
+        self
+    }
+
+    /// Alert when the resolver holds less than these of a destination asset, from the next
+    /// check on
+    pub fn set_min_balances(&self, min_balances: HashMap<Asset, u128>) {
+        *self.min_balances.write().unwrap() = min_balances;
+        *self.balances_checked.lock().unwrap() = None;
+    }
+
+    /// Book fills at these markets' prices and safety deposits, and report their assets'
+    /// balances, from the next fill on
+    pub fn set_markets(&self, markets: Vec<Market>) {
+        *self.markets.write().unwrap() = markets;
+    }
+
+    /// Price orders with `strategy` from the next poll on
+    pub fn set_strategy(&self, strategy: Box<dyn Strategy>) {
+        *self.strategy.write().unwrap() = strategy;
+    }
+
+    /// Use `policy` for the next orders, and in the embedded relayer
+    pub fn set_policy(&self, policy: Policy) {
+        self.relayer.set_policy(policy.clone());
+        *self.policy.write().unwrap() = policy;
+    }
+
+    /// Destination assets locked in fills whose swap has not settled
+    pub fn inventory(&self) -> Result<Inventory> {
+        let mut inventory = Inventory::default();
+        for fill in self.fills.all()? {
+            let settled = self
+                .relayer
+                .swap(&fill.order.order_id)?
+                .is_some_and(|swap| swap.is_settled());
+            if !settled {
+                inventory.lock(fill.order.destination_asset(), fill.amount);
+            }
+        }
+        Ok(inventory)
+    }
+
+    /// Poll the feed every `interval` and consider each open order
+    pub async fn run(&self, feed: &OrderFeed, interval: Duration) {
+        let mut tick = tokio::time::interval(interval);
+        loop {
+            tick.tick().await;
+            if let Err(error) = self.announce_fills().await {
+                warn!("announcing fills: {error}");
+            }
+            match self.inventory() {
+                Ok(inventory) => metrics::inventory(&inventory, &mut self.reported.lock().unwrap()),
+                Err(error) => warn!("inventory: {error}"),
+            }
+            self.check_balances().await;
+            if let Err(error) = self.account().await {
+                warn!("accounting: {error}");
+            }
+            self.close_day().await;
+            let orders = match feed.orders().await {
+                Ok(orders) => orders,
+                Err(error) => {
+                    warn!("{error}");
+                    metrics::feed_error();
+                    continue;
+                }
+            };
+            for order in orders {
+                let span = info_span!("order", order_id = %order.order_id);
+                let key = format!("fill:{}", order.order_id);
+                match self.consider(&order).instrument(span.clone()).await {
+                    Ok(Some(_)) => self.alerts.resolve(&key),
+                    Ok(None) => {}
+                    Err(error) => {
+                        span.in_scope(|| warn!("{error}"));
+                        metrics::fill_error();
+                        if let Error::Chain(_) | Error::Relayer(_) = error {
+                            self.alerts.fire(
+                                Severity::Warning,
+                                key,
+                                format!(
+                                    "filling order {} on {} failed: {error}",
+                                    order.order_id, order.destination.chain
+                                ),
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Ask the strategy about `order` and fill it if it says so, returning the new fill
+    pub async fn consider(&self, order: &Order) -> Result<Option<Fill>> {
+        if self.skipped.lock().unwrap().contains(&order.order_id)
+            || self.fills.load(&order.order_id)?.is_some()
+        {
+            return Ok(None);
+        }
+        match self.fill(order).await {
+            Err(error @ (Error::Source(_) | Error::UnknownChain(_))) => {
+                self.skip(order, &error.to_string());
+                Ok(None)
+            }
+            result => result,
+        }
+    }
+
+    /// Hand the legs of every fill to the relayer again if it is missing either of them,
+    /// e.g. after an announcement failed
+    pub async fn announce_fills(&self) -> Result<()> {
+        for fill in self.fills.all()? {
+            let announced = self
+                .relayer
+                .swap(&fill.order.order_id)?
+                .is_some_and(|swap| swap.source.is_some() && swap.destination.is_some());
+            if !announced {
+                self.announce(&fill)
+                    .instrument(info_span!("order", order_id = %fill.order.order_id))
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Book fills the ledger is missing and settle the bookings of swaps that settled; a
+    /// booking that fails to settle is tried again on the next call
+    pub async fn account(&self) -> Result<()> {
+        for fill in self.fills.all()? {
+            let booking = match self.ledger.load(&fill.order.order_id)? {
+                Some(booking) => booking,
+                None => self.book(&fill)?,
+            };
+            if booking.settlement == Settlement::Open {
+                let span = info_span!("order", order_id = %fill.order.order_id);
+                if let Err(error) = self.settle(&fill, booking).instrument(span.clone()).await {
+                    span.in_scope(|| warn!("settling the booking: {error}"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Book `fill` at its market's price, or at its own price without a market
+    fn book(&self, fill: &Fill) -> Result<Booking> {
+        let order = &fill.order;
+        let (source, destination) = (order.source_asset(), order.destination_asset());
+        let markets = self.markets.read().unwrap();
+        let market = markets
+            .iter()
+            .find(|market| market.source == source && market.destination == destination);
+        let booking = Booking {
+            order_id: order.order_id.clone(),
+            filled_at: fill.at,
+            source,
+            received: order.source.amount,
+            destination,
+            cost: fill.amount,
+            price: market.map_or(
+                fill.amount as f64 / order.source.amount.max(1) as f64,
+                |market| market.price,
+            ),
+            safety_deposit: market
+                .and_then(|market| market.safety_deposit)
+                .unwrap_or_default(),
+            settlement: Settlement::Open,
+            settled_at: None,
+            source_fee: None,
+            destination_fee: None,
+        };
+        self.ledger.save(&booking)?;
+        Ok(booking)
+    }
+
+    /// Close `booking` once both legs of its swap settled, reading the fees of the
+    /// resolver's transactions: the lock, the source claim and the destination refund
+    async fn settle(&self, fill: &Fill, mut booking: Booking) -> Result<()> {
+        let Some(swap) = self.relayer.swap(&fill.order.order_id)? else {
+            return Ok(());
+        };
+        let (Some(source), Some(destination)) = (&swap.source, &swap.destination) else {
+            return Ok(());
+        };
+        if !swap.is_settled() {
+            return Ok(());
+        }
+        booking.settlement = match (source.outcome, destination.outcome) {
+            (_, Some(Outcome::Refunded)) => Settlement::Unwound,
+            (Some(Outcome::Claimed), _) => Settlement::Completed,
+            _ => Settlement::Lost,
+        };
+        let mut source_txs = Vec::new();
+        if booking.settlement == Settlement::Completed {
+            source_txs.push(source.settled_tx.as_ref());
+        }
+        let mut destination_txs = vec![Some(&fill.tx)];
+        if booking.settlement == Settlement::Unwound {
+            destination_txs.push(destination.settled_tx.as_ref());
+        }
+        booking.source_fee = self.fees(&source.chain, &source_txs).await?;
+        booking.destination_fee = self.fees(&destination.chain, &destination_txs).await?;
+        booking.settled_at = Some(now());
+        self.ledger.save(&booking)?;
+        info!(
+            spread = booking.spread().unwrap_or_default(),
+            "booked as {}",
+            booking.settlement.as_str()
+        );
+        Ok(())
+    }
+
+    /// Total fee of `txs` on `chain`, `None` when any of them is unknown or unreadable
+    async fn fees(&self, chain: &str, txs: &[Option<&TxId>]) -> Result<Option<u128>> {
+        let adapter = self.adapter(chain)?;
+        let mut total = 0u128;
+        for tx in txs {
+            let Some(tx) = tx else {
+                return Ok(None);
+            };
+            match adapter.tx_fee(tx).await? {
+                Some(fee) => total = total.saturating_add(fee),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(total))
+    }
+
+    /// Write the ledger's report of the day before, unless it was written already
+    async fn close_day(&self) {
+        let Some(day) = (now() / DAY_SECS).checked_sub(1) else {
+            return;
+        };
+        if self.ledger.has_report(day) {
+            return;
+        }
+        let balances = self.balances().await;
+        match self.ledger.write_report(day, &balances) {
+            Ok(path) => info!("wrote {}", path.display()),
+            Err(error) => warn!("report: {error}"),
+        }
+    }
+
+    /// Balances of the markets' assets and their chains' native currencies, where readable
+    async fn balances(&self) -> HashMap<Asset, u128> {
+        let mut assets = HashSet::new();
+        for market in self.markets.read().unwrap().iter() {
+            for asset in [&market.source, &market.destination] {
+                assets.insert(asset.clone());
+                assets.insert(Asset {
+                    chain: asset.chain.clone(),
+                    token: None,
+                });
+            }
+        }
+        let mut balances = HashMap::new();
+        for asset in assets {
+            let Ok(adapter) = self.adapter(&asset.chain) else {
+                continue;
+            };
+            match adapter.balance(asset.token.as_deref()).await {
+                Ok(Some(balance)) => {
+                    balances.insert(asset, balance);
+                }
+                Ok(None) => {}
+                Err(error) => warn!("balance on {}: {error}", asset.chain),
+            }
+        }
+        balances
+    }
+
+    /// Compare each destination balance with its `min_balance`, at most every
+    /// `BALANCE_CHECK`
+    async fn check_balances(&self) {
+        {
+            let mut checked = self.balances_checked.lock().unwrap();
+            if checked.is_some_and(|checked| checked.elapsed() < BALANCE_CHECK) {
+                return;
+            }
+            *checked = Some(Instant::now());
+        }
+        let min_balances = self.min_balances.read().unwrap().clone();
+        for (asset, min_balance) in min_balances {
+            let Ok(adapter) = self.adapter(&asset.chain) else {
+                continue;
+            };
+            let name = match &asset.token {
+                Some(token) => format!("{token} on {}", asset.chain),
+                None => format!("the native currency on {}", asset.chain),
+            };
+            let key = format!(
+                "balance:{}:{}",
+                asset.chain,
+                asset.token.as_deref().unwrap_or("")
+            );
+            let balance = match adapter.balance(asset.token.as_deref()).await {
+                Ok(Some(balance)) => balance,
+                Ok(None) => {
+                    warn!("cannot read the balance of {name}");
+                    continue;
+                }
+                Err(error) => {
+                    warn!("balance of {name}: {error}");
+                    continue;
+                }
+            };
+            if balance >= min_balance {
+                self.alerts.resolve(&key);
+                continue;
+            }
+            let severity = if balance < min_balance / 2 {
+                Severity::Critical
+            } else {
+                Severity::Warning
+            };
+            self.alerts.fire(
+                severity,
+                key,
+                format!("balance of {name} is {balance}, below {min_balance}"),
+            );
+        }
+    }
+
+    async fn fill(&self, order: &Order) -> Result<Option<Fill>> {
+        let now = now();
+        let source = self.adapter(&order.source.chain)?;
+        let destination = self.adapter(&order.destination.chain)?;
+        // The maker needs the gap to claim the destination, and the resolver the same again
+        // to claim the source after the secret comes out
+        let gap = self.policy.read().unwrap().min_timelock_gap_secs;
+        let timelock = order.source.timelock.saturating_sub(gap);
+        if timelock <= now.saturating_add(gap) {
+            self.skip(order, "source timelock too close");
+            return Ok(None);
+        }
+
+        let inventory = self.inventory()?;
+        let decision = self.strategy.read().unwrap().decide(order, now, &inventory);
+        metrics::decided(&decision);
+        let started = Instant::now();
+        let amount = match decision {
+            Decision::Fill { amount } => amount,
+            Decision::Wait => return Ok(None),
+            Decision::Skip(reason) => {
+                self.skip(order, &reason);
+                return Ok(None);
+            }
+        };
+        if amount < order.required_amount(now) {
+            return Err(Error::Strategy(format!(
+                "strategy offered {amount}, below the required {}",
+                order.required_amount(now)
+            )));
+        }
+        verify_source(source.as_ref(), order).await?;
+
+        let params = EscrowParams {
+            order_id: order.order_id.clone(),
+            hashlock: order.hashlock,
+            receiver: order.destination.receiver.clone(),
+            token: order.destination.token.clone(),
+            amount,
+            timelock,
+        };
+        let (escrow_id, tx) = destination.create_escrow(&params).await?;
+        metrics::filled(started.elapsed());
+        match destination.fee_estimate().await {
+            Ok(fee) => metrics::fee_bid(destination.chain(), fee.max_fee),
+            Err(error) => warn!("{}: fee estimate: {error}", destination.chain()),
+        }
+        let fill = Fill {
+            order: order.clone(),
+            amount,
+            escrow_id,
+            tx,
+            at: now,
+        };
+        self.fills.save(&fill)?;
+        if let Err(error) = self.book(&fill) {
+            warn!("booking: {error}");
+        }
+        info!(
+            chain = %order.destination.chain,
+            escrow_id = %hex::encode(fill.escrow_id),
+            tx = %fill.tx,
+            "locked {amount} on {}",
+            order.destination.chain
+        );
+
+        if let Err(error) = self.announce(&fill).await {
+            warn!("announcing: {error}");
+        }
+        Ok(Some(fill))
+    }
+
+    async fn announce(&self, fill: &Fill) -> Result<()> {
+        let order = &fill.order;
+        for (role, chain, escrow_id) in [
+            (Role::Source, &order.source.chain, order.source.escrow_id),
+            (Role::Destination, &order.destination.chain, fill.escrow_id),
+        ] {
+            self.relayer
+                .announce(Announcement {
+                    order_id: order.order_id.clone(),
+                    hashlock: order.hashlock,
+                    role,
+                    chain: chain.clone(),
+                    escrow_id,
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    fn adapter(&self, chain: &str) -> Result<&Arc<dyn ChainAdapter>> {
+        self.chains
+            .get(chain)
+            .ok_or_else(|| Error::UnknownChain(chain.to_string()))
+    }
+
+    fn skip(&self, order: &Order, reason: &str) {
+        info!("skipped, {reason}");
+        if self.skipped.lock().unwrap().insert(order.order_id.clone()) {
+            metrics::skipped();
+        }
+    }
+}
+
+/// Check that the maker's escrow exists on the terms the order advertises
+async fn verify_source(adapter: &dyn ChainAdapter, order: &Order) -> Result<()> {
+    let mut events = adapter.watch_events(order.source.escrow_id, 0).await?;
+    let event = tokio::time::timeout(SOURCE_TIMEOUT, events.next())
+        .await
+        .map_err(|_| Error::Source("not created".to_string()))?
+        .ok_or_else(|| Error::Source("not created".to_string()))??;
+    match event.event {
+        EscrowEvent::Created {
+            amount,
+            hashlock,
+            timelock,
+        } if !event.removed => {
+            if hashlock != order.hashlock {
+                Err(Error::Source(
+                    "hashlock differs from the order's".to_string(),
+                ))
+            } else if amount < order.source.amount {
+                Err(Error::Source(format!(
+                    "locks {amount}, less than {}",
+                    order.source.amount
+                )))
+            } else if timelock != order.source.timelock {
+                Err(Error::Source(format!(
+                    "timelock {timelock} differs from {}",
+                    order.source.timelock
+                )))
+            } else {
+                Ok(())
+            }
+        }
+        _ => Err(Error::Source("already settled".to_string())),
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::order::{Asset, Order};
+
+/// What a strategy wants done with an order
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// Lock `amount` of the destination asset for the maker now
+    Fill { amount: u128 },
+    /// Look again at the next poll, e.g. until the auction has decayed further
+    Wait,
+    /// Never fill this order
+    Skip(String),
+}
+
+/// Pricing logic deciding which orders to fill and when
+///
+/// Called once per poll for every open order the resolver has not filled or skipped. A
+/// `Fill` must offer at least `order.required_amount(now)`, or the maker's claim will not
+/// pay out what the auction asks.
+pub trait Strategy: Send + Sync {
+    fn decide(&self, order: &Order, now: u64, inventory: &Inventory) -> Decision;
+}
+
+/// Destination assets locked in fills that have not settled yet
+#[derive(Clone, Debug, Default)]
+pub struct Inventory {
+    locked: HashMap<Asset, u128>,
+}
+
+impl Inventory {
+    pub fn locked(&self, asset: &Asset) -> u128 {
+        self.locked.get(asset).copied().unwrap_or_default()
+    }
+
+    pub fn lock(&mut self, asset: Asset, amount: u128) {
+        *self.locked.entry(asset).or_default() += amount;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Asset, u128)> {
+        self.locked.iter().map(|(asset, amount)| (asset, *amount))
+    }
+}
+
+/// A pair the resolver trades, priced in destination units per source unit (both in the
+/// tokens' smallest units)
+#[derive(Clone, Debug, Deserialize)]
+pub struct Market {
+    pub source: Asset,
+    pub destination: Asset,
+    pub price: f64,
+    /// Most of the destination asset locked across unsettled fills; TOML integers stop at
+    /// `i64`, so larger limits are written as strings
+    #[serde(default, deserialize_with = "amount")]
+    pub max_inventory: Option<u128>,
+    /// Least of the destination asset the resolver's account should hold; less is alerted,
+    /// critically below half. Written like `max_inventory`
+    #[serde(default, deserialize_with = "amount")]
+    pub min_balance: Option<u128>,
+    /// Native currency of the destination chain each fill's escrow holds as a safety deposit,
+    /// booked as locked until the fill settles. Written like `max_inventory`
+    #[serde(default, deserialize_with = "amount")]
+    pub safety_deposit: Option<u128>,
+}
+
+fn amount<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<u128>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Amount {
+        Integer(u64),
+        String(String),
+    }
+    match Amount::deserialize(deserializer)? {
+        Amount::Integer(amount) => Ok(Some(amount as u128)),
+        Amount::String(amount) => amount.parse().map(Some).map_err(serde::de::Error::custom),
+    }
+}
+
+/// Fills orders whose required amount leaves at least a target spread against the market
+/// price
+///
+/// Early in an auction only `target_spread_bps` is accepted; once `patience_bps` of the
+/// auction has elapsed the resolver settles for `min_spread_bps`, trading some margin for the
+/// chance that another resolver fills first.
+#[derive(Clone, Debug)]
+pub struct Spread {
+    pub markets: Vec<Market>,
+    pub min_spread_bps: u32,
+    pub target_spread_bps: u32,
+    pub patience_bps: u32,
+}
+
+impl Spread {
+    fn max_amount(fair: f64, spread_bps: u32) -> u128 {
+        (fair * (1.0 - spread_bps as f64 / 10_000.0)).max(0.0) as u128
+    }
+}
+
+impl Strategy for Spread {
+    fn decide(&self, order: &Order, now: u64, _inventory: &Inventory) -> Decision {
+        let (source, destination) = (order.source_asset(), order.destination_asset());
+        let Some(market) = self
+            .markets
+            .iter()
+            .find(|market| market.source == source && market.destination == destination)
+        else {
+            return Decision::Skip("no market for the pair".to_string());
+        };
+        let fair = order.source.amount as f64 * market.price;
+        let spread_bps = if order.progress_bps(now) < self.patience_bps {
+            self.target_spread_bps.max(self.min_spread_bps)
+        } else {
+            self.min_spread_bps
+        };
+
+        let required = order.required_amount(now);
+        if required <= Self::max_amount(fair, spread_bps) {
+            Decision::Fill { amount: required }
+        } else if order.taking_amount <= Self::max_amount(fair, self.min_spread_bps) {
+            Decision::Wait
+        } else {
+            Decision::Skip(format!(
+                "taking amount {} leaves less than {}bps against {fair:.0}",
+                order.taking_amount, self.min_spread_bps
+            ))
+        }
+    }
+}
+
+/// Holds back fills that would lock more of a destination asset than its limit allows;
+/// they are retried once earlier fills settle
+#[derive(Clone, Debug)]
+pub struct InventoryLimit<S> {
+    pub inner: S,
+    pub limits: HashMap<Asset, u128>,
+}
+
+impl<S: Strategy> Strategy for InventoryLimit<S> {
+    fn decide(&self, order: &Order, now: u64, inventory: &Inventory) -> Decision {
+        match self.inner.decide(order, now, inventory) {
+            Decision::Fill { amount } => {
+                let asset = order.destination_asset();
+                match self.limits.get(&asset) {
+                    Some(limit) if inventory.locked(&asset).saturating_add(amount) > *limit => {
+                        if amount > *limit {
+                            Decision::Skip(format!("{amount} exceeds the inventory limit"))
+                        } else {
+                            Decision::Wait
+                        }
+                    }
+                    _ => Decision::Fill { amount },
+                }
+            }
+            decision => decision,
+        }
+    }
+}
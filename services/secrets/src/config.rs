@@ -0,0 +1,88 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use serde::Deserialize;
+
+use crate::file::FileStore;
+use crate::kms::KmsStore;
+use crate::store::SecretStore;
+use crate::vault::VaultStore;
+use crate::{Error, Result};
+
+/// The `[secrets]` table of a service's config, picking the backend by `backend`; tokens and
+/// passphrases are read from the environment variables it names, never from the file
+#[derive(Clone, Debug, Deserialize)]
+#[serde(tag = "backend", rename_all = "kebab-case")]
+pub enum SecretsConfig {
+    /// HashiCorp Vault KV version 2 engine
+    Vault {
+        /// e.g. `https://vault.example.com:8200`
+        address: String,
+        #[serde(default = "default_token_env")]
+        token_env: String,
+        #[serde(default = "default_mount")]
+        mount: String,
+        /// Path under the mount that names are relative to, e.g. `unite/relayer`
+        #[serde(default)]
+        prefix: String,
+        namespace: Option<String>,
+    },
+    /// Values encrypted with an AWS KMS key, ciphertexts kept in `dir`
+    AwsKms {
+        /// Key ID, ARN or alias, e.g. `alias/unite`
+        key_id: String,
+        region: Option<String>,
+        dir: PathBuf,
+    },
+    /// Local file encrypted under a passphrase
+    File {
+        path: PathBuf,
+        passphrase_env: String,
+    },
+}
+
+impl SecretsConfig {
+    pub async fn open(&self) -> Result<Arc<dyn SecretStore>> {
+        Ok(match self {
+            SecretsConfig::Vault {
+                address,
+                token_env,
+                mount,
+                prefix,
+                namespace,
+            } => {
+                let store = VaultStore::new(
+                    address.as_str(),
+                    env(token_env)?,
+                    mount.as_str(),
+                    prefix.as_str(),
+                );
+                Arc::new(match namespace {
+                    Some(namespace) => store.with_namespace(namespace.as_str()),
+                    None => store,
+                })
+            }
+            SecretsConfig::AwsKms {
+                key_id,
+                region,
+                dir,
+            } => Arc::new(KmsStore::connect(key_id.as_str(), region.clone(), dir).await?),
+            SecretsConfig::File {
+                path,
+                passphrase_env,
+            } => Arc::new(FileStore::open(path, &env(passphrase_env)?)?),
+        })
+    }
+}
+
+fn env(name: &str) -> Result<String> {
+    std::env::var(name).map_err(|_| Error::Config(format!("{name} is not set")))
+}
+
+fn default_token_env() -> String {
+    "VAULT_TOKEN".to_string()
+}
+
+fn default_mount() -> String {
+    "secret".to_string()
+}
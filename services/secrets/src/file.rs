@@ -0,0 +1,197 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use async_trait::async_trait;
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{KeyInit, XChaCha20Poly1305, XNonce};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use zeroize::Zeroizing;
+
+use crate::store::{check_name, SecretStore, SecretValue};
+use crate::{Error, Result};
+
+/// Bound to every ciphertext, so files of another format or version fail to decrypt
+const ASSOCIATED_DATA: &[u8] = b"unite-secrets v1";
+
+/// Secrets in one local file, encrypted under a key derived from a passphrase
+///
+/// The key is derived with Argon2id and the whole set of secrets is sealed with
+/// XChaCha20-Poly1305 under a fresh random nonce on every write, then written atomically.
+/// The decrypted set is kept in memory while the store is open.
+pub struct FileStore {
+    path: PathBuf,
+    key: Zeroizing<[u8; 32]>,
+    kdf: Kdf,
+    secrets: Mutex<BTreeMap<String, SecretValue>>,
+}
+
+/// Argon2id parameters and salt, stored with the file so they can be raised for new files
+/// without breaking old ones
+#[derive(Clone, Debug, Serialize, Deserialize)]
+struct Kdf {
+    #[serde(with = "hex")]
+    salt: [u8; 16],
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Sealed {
+    version: u32,
+    kdf: Kdf,
+    #[serde(with = "hex")]
+    nonce: [u8; 24],
+    #[serde(with = "hex")]
+    ciphertext: Vec<u8>,
+}
+
+impl FileStore {
+    /// Open the file at `path`, which is created on the first `put` if it does not exist; a
+    /// wrong passphrase fails here rather than on the first read
+    pub fn open(path: impl AsRef<Path>, passphrase: &str) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let sealed = match fs::read(&path) {
+            Ok(json) => Some(serde_json::from_slice::<Sealed>(&json)?),
+            Err(error) if error.kind() == ErrorKind::NotFound => None,
+            Err(error) => return Err(error.into()),
+        };
+        let Some(sealed) = sealed else {
+            let mut salt = [0; 16];
+            rand::thread_rng().fill_bytes(&mut salt);
+            let kdf = Kdf {
+                salt,
+                memory_kib: Params::DEFAULT_M_COST,
+                iterations: Params::DEFAULT_T_COST,
+                parallelism: Params::DEFAULT_P_COST,
+            };
+            return Ok(Self {
+                path,
+                key: kdf.derive(passphrase)?,
+                kdf,
+                secrets: Mutex::new(BTreeMap::new()),
+            });
+        };
+        if sealed.version != 1 {
+            return Err(Error::Config(format!(
+                "{}: unsupported version {}",
+                path.display(),
+                sealed.version
+            )));
+        }
+        let key = sealed.kdf.derive(passphrase)?;
+        let plaintext = Zeroizing::new(
+            XChaCha20Poly1305::new(key.as_ref().into())
+                .decrypt(
+                    XNonce::from_slice(&sealed.nonce),
+                    Payload {
+                        msg: &sealed.ciphertext,
+                        aad: ASSOCIATED_DATA,
+                    },
+                )
+                .map_err(|_| Error::Decrypt)?,
+        );
+        let encoded: BTreeMap<String, Zeroizing<String>> = serde_json::from_slice(&plaintext)?;
+        let mut secrets = BTreeMap::new();
+        for (name, value) in encoded {
+            let value = hex::decode(value.as_str()).map_err(|_| Error::Decrypt)?;
+            secrets.insert(name, Zeroizing::new(value));
+        }
+        Ok(Self {
+            path,
+            key,
+            kdf: sealed.kdf,
+            secrets: Mutex::new(secrets),
+        })
+    }
+
+    fn save(&self, secrets: &BTreeMap<String, SecretValue>) -> Result<()> {
+        let encoded: BTreeMap<&str, Zeroizing<String>> = secrets
+            .iter()
+            .map(|(name, value)| (name.as_str(), Zeroizing::new(hex::encode(value.as_slice()))))
+            .collect();
+        let plaintext = Zeroizing::new(serde_json::to_vec(&encoded)?);
+        let mut nonce = [0; 24];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = XChaCha20Poly1305::new(self.key.as_ref().into())
+            .encrypt(
+                XNonce::from_slice(&nonce),
+                Payload {
+                    msg: &plaintext,
+                    aad: ASSOCIATED_DATA,
+                },
+            )
+            .map_err(|_| Error::Config("encryption failed".to_string()))?;
+        let sealed = Sealed {
+            version: 1,
+            kdf: self.kdf.clone(),
+            nonce,
+            ciphertext,
+        };
+        let temporary = self.path.with_extension("tmp");
+        fs::write(&temporary, serde_json::to_vec_pretty(&sealed)?)?;
+        fs::rename(temporary, &self.path)?;
+        Ok(())
+    }
+}
+
+impl Kdf {
+    fn derive(&self, passphrase: &str) -> Result<Zeroizing<[u8; 32]>> {
+        let params = Params::new(self.memory_kib, self.iterations, self.parallelism, Some(32))
+            .map_err(|error| Error::Config(format!("argon2 parameters: {error}")))?;
+        let mut key = Zeroizing::new([0; 32]);
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+            .hash_password_into(passphrase.as_bytes(), &self.salt, key.as_mut())
+            .map_err(|error| Error::Config(format!("argon2: {error}")))?;
+        Ok(key)
+    }
+}
+
+#[async_trait]
+impl SecretStore for FileStore {
+    fn backend(&self) -> &str {
+        "file"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<SecretValue>> {
+        check_name(name)?;
+        Ok(self.secrets.lock().await.get(name).cloned())
+    }
+
+    async fn put(&self, name: &str, value: &[u8]) -> Result<()> {
+        check_name(name)?;
+        let mut secrets = self.secrets.lock().await;
+        let previous = secrets.insert(name.to_string(), Zeroizing::new(value.to_vec()));
+        if let Err(error) = self.save(&secrets) {
+            // Keep memory in step with the file
+            match previous {
+                Some(previous) => secrets.insert(name.to_string(), previous),
+                None => secrets.remove(name),
+            };
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        check_name(name)?;
+        let mut secrets = self.secrets.lock().await;
+        let Some(previous) = secrets.remove(name) else {
+            return Ok(());
+        };
+        if let Err(error) = self.save(&secrets) {
+            secrets.insert(name.to_string(), previous);
+            return Err(error);
+        }
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        Ok(self.secrets.lock().await.keys().cloned().collect())
+    }
+}
@@ -0,0 +1,142 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use aws_sdk_kms::primitives::Blob;
+use zeroize::Zeroizing;
+
+use crate::store::{check_name, SecretStore, SecretValue};
+use crate::{Error, Result};
+
+/// Encryption context key binding each ciphertext to its secret's name, so a ciphertext
+/// copied to another name no longer decrypts
+const CONTEXT_KEY: &str = "unite:secret";
+
+/// Secrets encrypted with an AWS KMS key, the ciphertexts kept in a local directory
+///
+/// KMS holds no secrets itself: each value is encrypted under `key_id` and only the
+/// ciphertext touches the disk, so reading a secret needs both the directory and
+/// `kms:Decrypt` on the key. Values can be at most 4 KiB, which covers keys and preimages.
+#[derive(Clone, Debug)]
+pub struct KmsStore {
+    client: aws_sdk_kms::Client,
+    key_id: String,
+    dir: PathBuf,
+}
+
+impl KmsStore {
+    /// Credentials and region come from the usual AWS sources: the environment, shared
+    /// config files, or the instance or task role; `region` overrides the region found there
+    pub async fn connect(
+        key_id: impl Into<String>,
+        region: Option<String>,
+        dir: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let mut loader = aws_config::from_env();
+        if let Some(region) = region {
+            loader = loader.region(aws_config::Region::new(region));
+        }
+        let client = aws_sdk_kms::Client::new(&loader.load().await);
+        Self::new(client, key_id, dir)
+    }
+
+    pub fn new(
+        client: aws_sdk_kms::Client,
+        key_id: impl Into<String>,
+        dir: impl AsRef<Path>,
+    ) -> Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            client,
+            key_id: key_id.into(),
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    /// Names may contain `/`, so files are named by the name's hex
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(format!("{}.kms", hex::encode(name)))
+    }
+}
+
+#[async_trait]
+impl SecretStore for KmsStore {
+    fn backend(&self) -> &str {
+        "aws-kms"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<SecretValue>> {
+        check_name(name)?;
+        let ciphertext = match fs::read(self.path(name)) {
+            Ok(ciphertext) => ciphertext,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(error.into()),
+        };
+        let output = self
+            .client
+            .decrypt()
+            .key_id(&self.key_id)
+            .ciphertext_blob(Blob::new(ciphertext))
+            .encryption_context(CONTEXT_KEY, name)
+            .send()
+            .await
+            .map_err(|error| {
+                Error::Kms(format!("decrypting {name}: {}", error.into_service_error()))
+            })?;
+        let plaintext = output
+            .plaintext
+            .ok_or_else(|| Error::Kms(format!("decrypting {name} returned no plaintext")))?;
+        Ok(Some(Zeroizing::new(plaintext.into_inner())))
+    }
+
+    async fn put(&self, name: &str, value: &[u8]) -> Result<()> {
+        check_name(name)?;
+        let output = self
+            .client
+            .encrypt()
+            .key_id(&self.key_id)
+            .plaintext(Blob::new(value))
+            .encryption_context(CONTEXT_KEY, name)
+            .send()
+            .await
+            .map_err(|error| {
+                Error::Kms(format!("encrypting {name}: {}", error.into_service_error()))
+            })?;
+        let ciphertext = output
+            .ciphertext_blob
+            .ok_or_else(|| Error::Kms(format!("encrypting {name} returned no ciphertext")))?;
+        let path = self.path(name);
+        let temporary = path.with_extension("kms.tmp");
+        fs::write(&temporary, ciphertext.as_ref())?;
+        fs::rename(temporary, path)?;
+        Ok(())
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        check_name(name)?;
+        match fs::remove_file(self.path(name)) {
+            Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "kms") {
+                continue;
+            }
+            let name = path
+                .file_stem()
+                .and_then(|stem| hex::decode(stem.to_str()?).ok())
+                .and_then(|name| String::from_utf8(name).ok());
+            if let Some(name) = name {
+                names.push(name);
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
@@ -0,0 +1,42 @@
+// Secret Storage
+// Keeps signing keys and preimages out of the services' config files. `SecretStore` is a small
+// get/put/delete/list interface over named secrets, implemented by a HashiCorp Vault KV
+// engine, by AWS KMS (values encrypted under a KMS key, ciphertexts kept on disk) and by a
+// local file sealed under a passphrase. Services pick a backend with a `[secrets]` table and
+// refer to secrets by name; the `unite-secrets` binary manages the entries.
+
+pub mod config;
+pub mod file;
+pub mod kms;
+pub mod store;
+pub mod vault;
+
+pub use config::SecretsConfig;
+pub use file::FileStore;
+pub use kms::KmsStore;
+pub use store::{check_name, SecretStore, SecretValue};
+pub use vault::VaultStore;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error("invalid secret name {0:?}")]
+    InvalidName(String),
+    #[error("vault: {0}")]
+    Vault(String),
+    #[error("kms: {0}")]
+    Kms(String),
+    #[error("wrong passphrase or corrupted secrets file")]
+    Decrypt,
+    #[error(transparent)]
+    Http(#[from] reqwest::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Toml(#[from] toml::de::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use serde::Deserialize;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use unite_secrets::{Error, SecretsConfig};
+use zeroize::Zeroizing;
+
+/// Manage the secrets a service's `[secrets]` table points at
+#[derive(Debug, Parser)]
+#[command(name = "unite-secrets", version)]
+struct Cli {
+    /// Service config holding the `[secrets]` table
+    #[arg(long, short)]
+    config: PathBuf,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Store the value read from stdin, without its trailing newline
+    Put {
+        name: String,
+    },
+    /// Write a value to stdout
+    Get {
+        name: String,
+    },
+    Delete {
+        name: String,
+    },
+    /// Print every stored name
+    List,
+}
+
+#[derive(Deserialize)]
+struct ServiceConfig {
+    secrets: SecretsConfig,
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    match run(Cli::parse()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {error}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(cli: Cli) -> unite_secrets::Result<()> {
+    let config: ServiceConfig = toml::from_str(&std::fs::read_to_string(&cli.config)?)?;
+    let store = config.secrets.open().await?;
+    match cli.command {
+        Command::Put { name } => {
+            let mut value = Zeroizing::new(Vec::new());
+            tokio::io::stdin().read_to_end(&mut value).await?;
+            while value
+                .last()
+                .is_some_and(|byte| matches!(byte, b'\n' | b'\r'))
+            {
+                value.pop();
+            }
+            store.put(&name, &value).await
+        }
+        Command::Get { name } => {
+            let value = store
+                .get(&name)
+                .await?
+                .ok_or_else(|| Error::Config(format!("no secret named {name}")))?;
+            let mut stdout = tokio::io::stdout();
+            stdout.write_all(&value).await?;
+            stdout.flush().await?;
+            Ok(())
+        }
+        Command::Delete { name } => store.delete(&name).await,
+        Command::List => {
+            for name in store.list().await? {
+                println!("{name}");
+            }
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use zeroize::Zeroizing;
+
+use crate::{Error, Result};
+
+/// Secret bytes, wiped from memory when dropped
+pub type SecretValue = Zeroizing<Vec<u8>>;
+
+/// Named secrets, such as signing keys and preimages, kept outside config files
+/// Object safe, so services can hold whichever backend is configured as `Arc<dyn SecretStore>`
+#[async_trait]
+pub trait SecretStore: Send + Sync {
+    /// Backend name used in logs, e.g. `vault`
+    fn backend(&self) -> &str;
+
+    /// The secret stored under `name`, or `None` if there is none
+    async fn get(&self, name: &str) -> Result<Option<SecretValue>>;
+
+    /// Store `value` under `name`, replacing any previous value
+    async fn put(&self, name: &str, value: &[u8]) -> Result<()>;
+
+    /// Remove the secret under `name`; removing a missing one is not an error
+    async fn delete(&self, name: &str) -> Result<()>;
+
+    /// Names of every stored secret, sorted
+    async fn list(&self) -> Result<Vec<String>>;
+}
+
+/// Names are `/`-separated paths of letters, digits, `-`, `_` and `.`, e.g. `relayer/stellar`,
+/// so every backend can use them as keys, paths or file names
+pub fn check_name(name: &str) -> Result<()> {
+    let valid = !name.is_empty()
+        && name.split('/').all(|part| {
+            !part.is_empty()
+                && part != "."
+                && part != ".."
+                && part
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        });
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::InvalidName(name.to_string()))
+    }
+}
@@ -0,0 +1,190 @@
+use async_trait::async_trait;
+use reqwest::{Method, RequestBuilder, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+use zeroize::Zeroizing;
+
+use crate::store::{check_name, SecretStore, SecretValue};
+use crate::{Error, Result};
+
+/// Secrets in a HashiCorp Vault KV version 2 engine, one entry per secret under `prefix`
+///
+/// Text values are kept in the entry's `value` field, so keys can also be written from the
+/// Vault UI or CLI; other bytes go in `hex`.
+#[derive(Clone)]
+pub struct VaultStore {
+    http: reqwest::Client,
+    address: String,
+    token: Zeroizing<String>,
+    namespace: Option<String>,
+    mount: String,
+    prefix: String,
+}
+
+#[derive(Deserialize)]
+struct Response<T> {
+    data: T,
+}
+
+#[derive(Deserialize)]
+struct Entry {
+    data: Option<Map<String, Value>>,
+}
+
+#[derive(Deserialize)]
+struct Keys {
+    keys: Vec<String>,
+}
+
+impl VaultStore {
+    /// `address` is the server's URL, e.g. `https://vault.example.com:8200`, and `mount` the
+    /// path the KV engine is mounted at, e.g. `secret`
+    pub fn new(
+        address: impl Into<String>,
+        token: impl Into<String>,
+        mount: impl Into<String>,
+        prefix: impl Into<String>,
+    ) -> Self {
+        let prefix = prefix.into().trim_matches('/').to_string();
+        Self {
+            http: reqwest::Client::new(),
+            address: address.into().trim_end_matches('/').to_string(),
+            token: Zeroizing::new(token.into()),
+            namespace: None,
+            mount: mount.into().trim_matches('/').to_string(),
+            prefix: if prefix.is_empty() {
+                prefix
+            } else {
+                format!("{prefix}/")
+            },
+        }
+    }
+
+    /// Vault Enterprise namespace requests are made in
+    pub fn with_namespace(mut self, namespace: impl Into<String>) -> Self {
+        self.namespace = Some(namespace.into());
+        self
+    }
+
+    fn request(&self, method: Method, kind: &str, path: &str) -> RequestBuilder {
+        let url = format!(
+            "{}/v1/{}/{kind}/{}{path}",
+            self.address, self.mount, self.prefix
+        );
+        let request = self
+            .http
+            .request(method, url)
+            .header("X-Vault-Token", self.token.as_str());
+        match &self.namespace {
+            Some(namespace) => request.header("X-Vault-Namespace", namespace),
+            None => request,
+        }
+    }
+
+    /// Names under `folder` (empty or ending in `/`), descending into subfolders
+    async fn list_folder(&self, folder: String, names: &mut Vec<String>) -> Result<()> {
+        let response = self
+            .request(Method::GET, "metadata", &folder)
+            .query(&[("list", "true")])
+            .send()
+            .await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        let keys: Response<Keys> = check(response).await?.json().await?;
+        for key in keys.data.keys {
+            let name = format!("{folder}{key}");
+            if name.ends_with('/') {
+                Box::pin(self.list_folder(name, names)).await?;
+            } else {
+                names.push(name);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl SecretStore for VaultStore {
+    fn backend(&self) -> &str {
+        "vault"
+    }
+
+    async fn get(&self, name: &str) -> Result<Option<SecretValue>> {
+        check_name(name)?;
+        let response = self.request(Method::GET, "data", name).send().await?;
+        // Missing and deleted entries are both 404
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        let entry: Response<Entry> = check(response).await?.json().await?;
+        let Some(fields) = entry.data.data else {
+            return Ok(None);
+        };
+        match (fields.get("value"), fields.get("hex")) {
+            (Some(Value::String(value)), _) => Ok(Some(Zeroizing::new(value.as_bytes().to_vec()))),
+            (_, Some(Value::String(value))) => hex::decode(value)
+                .map(|bytes| Some(Zeroizing::new(bytes)))
+                .map_err(|_| Error::Vault(format!("{name}: hex is not hex"))),
+            _ => Err(Error::Vault(format!("{name} has neither value nor hex"))),
+        }
+    }
+
+    async fn put(&self, name: &str, value: &[u8]) -> Result<()> {
+        check_name(name)?;
+        let data = match std::str::from_utf8(value) {
+            Ok(text) => json!({ "value": text }),
+            Err(_) => json!({ "hex": hex::encode(value) }),
+        };
+        let response = self
+            .request(Method::POST, "data", name)
+            .json(&json!({ "data": data }))
+            .send()
+            .await?;
+        check(response).await?;
+        Ok(())
+    }
+
+    /// Removes every version of the entry, not only the latest
+    async fn delete(&self, name: &str) -> Result<()> {
+        check_name(name)?;
+        let response = self
+            .request(Method::DELETE, "metadata", name)
+            .send()
+            .await?;
+        if response.status() != StatusCode::NOT_FOUND {
+            check(response).await?;
+        }
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        self.list_folder(String::new(), &mut names).await?;
+        names.sort();
+        Ok(names)
+    }
+}
+
+/// Vault reports failures as `{"errors": [...]}` with an error status
+async fn check(response: reqwest::Response) -> Result<reqwest::Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    let body = response.text().await.unwrap_or_default();
+    let errors = serde_json::from_str::<Value>(&body)
+        .ok()
+        .and_then(|body| {
+            body.get("errors")?.as_array().map(|errors| {
+                errors
+                    .iter()
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            })
+        })
+        .filter(|errors| !errors.is_empty())
+        .unwrap_or(body);
+    Err(Error::Vault(format!("{status}: {errors}")))
+}
@@ -0,0 +1,148 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Serialize;
+use tokio::net::TcpListener;
+
+use crate::Result;
+
+/// Longest a probe may take before its dependency counts as down
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One dependency a service needs to do its work: a chain RPC, a database, a feed
+#[async_trait]
+pub trait Probe: Send + Sync {
+    /// Name in reports, e.g. `stellar` or `database`
+    fn name(&self) -> String;
+
+    /// Make a cheap call to the dependency, failing with why it is not usable
+    async fn probe(&self) -> std::result::Result<Reading, String>;
+}
+
+/// What a successful probe saw
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct Reading {
+    /// Newest block or ledger the dependency reported
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub height: Option<u64>,
+    /// Blocks or ledgers the dependency is behind where it should be
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lag: Option<u64>,
+}
+
+impl Reading {
+    pub fn height(height: u64) -> Self {
+        Self {
+            height: Some(height),
+            lag: None,
+        }
+    }
+}
+
+/// The body of `/readyz`
+#[derive(Clone, Debug, Serialize)]
+pub struct Report {
+    /// Whether every dependency is usable
+    pub ready: bool,
+    pub dependencies: Vec<Status>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Status {
+    pub name: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    #[serde(flatten)]
+    pub reading: Reading,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A service's dependencies, probed together on each readiness check
+#[derive(Clone, Default)]
+pub struct Health {
+    probes: Vec<Arc<dyn Probe>>,
+}
+
+impl Health {
+    pub fn new(probes: Vec<Arc<dyn Probe>>) -> Self {
+        Self { probes }
+    }
+
+    pub fn with(mut self, probe: Arc<dyn Probe>) -> Self {
+        self.probes.push(probe);
+        self
+    }
+
+    /// Probe every dependency concurrently
+    pub async fn check(&self) -> Report {
+        let dependencies =
+            futures::future::join_all(self.probes.iter().map(|probe| status(probe.as_ref()))).await;
+        Report {
+            ready: dependencies.iter().all(|status| status.ok),
+            dependencies,
+        }
+    }
+
+    /// `GET /healthz` answers 200 while the process serves requests, without probing anything;
+    /// `GET /readyz` probes every dependency and answers 200, or 503 if any is down, with the
+    /// `Report` as JSON
+    pub fn router(self) -> Router {
+        Router::new()
+            .route("/healthz", get(healthz))
+            .route("/readyz", get(readyz))
+            .with_state(Arc::new(self))
+    }
+}
+
+async fn status(probe: &dyn Probe) -> Status {
+    let started = Instant::now();
+    let result = match tokio::time::timeout(PROBE_TIMEOUT, probe.probe()).await {
+        Ok(result) => result,
+        Err(_) => Err(format!("no answer within {}s", PROBE_TIMEOUT.as_secs())),
+    };
+    let latency_ms = started.elapsed().as_millis() as u64;
+    match result {
+        Ok(reading) => Status {
+            name: probe.name(),
+            ok: true,
+            latency_ms,
+            reading,
+            error: None,
+        },
+        Err(error) => Status {
+            name: probe.name(),
+            ok: false,
+            latency_ms,
+            reading: Reading::default(),
+            error: Some(error),
+        },
+    }
+}
+
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+async fn readyz(State(health): State<Arc<Health>>) -> (StatusCode, Json<Report>) {
+    let report = health.check().await;
+    let status = if report.ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report))
+}
+
+/// Serve `health` on its own listener, for services without an HTTP API to mount it on
+pub async fn serve(listen: SocketAddr, health: Health) -> Result<()> {
+    let listener = TcpListener::bind(listen).await?;
+    axum::serve(listener, health.router()).await?;
+    Ok(())
+}
@@ -0,0 +1,27 @@
+// Telemetry
+// Tracing setup for the relayer, resolver and indexer. Each daemon opens spans keyed by the
+// swap they work on (`order_id`, and `escrow_id` per leg), so one swap can be followed through
+// every service's output. Events are filtered with `RUST_LOG` and written as text or JSON
+// lines; with an OTLP endpoint configured, spans are also exported to a collector such as
+// Jaeger or Tempo. Records from crates still using `log` are captured into the same spans.
+//
+// Health: each service serves `/healthz` for liveness and `/readyz`, which probes the chain
+// RPCs, databases and feeds it depends on and reports each one's status, latency and lag.
+
+pub mod health;
+pub mod subscriber;
+
+pub use health::{Health, Probe, Reading, Report, Status};
+pub use subscriber::{init, LogFormat, Telemetry, TracingConfig};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("io: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("otlp: {0}")]
+    Otlp(#[from] opentelemetry_otlp::ExporterBuildError),
+    #[error("tracing: {0}")]
+    Subscriber(#[from] tracing_subscriber::util::TryInitError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
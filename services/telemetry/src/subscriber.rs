@@ -0,0 +1,79 @@
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use opentelemetry_sdk::Resource;
+use serde::Deserialize;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
+
+use crate::Result;
+
+/// The `[tracing]` table of a service's config
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct TracingConfig {
+    #[serde(default)]
+    pub format: LogFormat,
+    /// OTLP/HTTP traces endpoint, e.g. `http://localhost:4318/v1/traces`; spans are only
+    /// written to the output without it
+    pub otlp_endpoint: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines, with the fields of enclosing spans
+    #[default]
+    Text,
+    /// One JSON object per event, with its span and the span's parents
+    Json,
+}
+
+/// Keeps the span exporter running; dropping it flushes the spans still queued
+pub struct Telemetry {
+    provider: Option<SdkTracerProvider>,
+}
+
+impl Drop for Telemetry {
+    fn drop(&mut self) {
+        if let Some(provider) = self.provider.take() {
+            let _ = provider.shutdown();
+        }
+    }
+}
+
+/// Install the global subscriber for `service`, filtered by `RUST_LOG` (default `info`)
+pub fn init(service: &'static str, config: &TracingConfig) -> Result<Telemetry> {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let output = match config.format {
+        LogFormat::Text => tracing_subscriber::fmt::layer().boxed(),
+        LogFormat::Json => tracing_subscriber::fmt::layer()
+            .json()
+            .with_current_span(true)
+            .with_span_list(true)
+            .boxed(),
+    };
+    let provider = match &config.otlp_endpoint {
+        Some(endpoint) => Some(
+            SdkTracerProvider::builder()
+                .with_batch_exporter(
+                    SpanExporter::builder()
+                        .with_http()
+                        .with_endpoint(endpoint)
+                        .build()?,
+                )
+                .with_resource(Resource::builder().with_service_name(service).build())
+                .build(),
+        ),
+        None => None,
+    };
+    let otlp = provider.as_ref().map(|provider| {
+        tracing_opentelemetry::layer().with_tracer(provider.tracer(service))
+    });
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(output)
+        .with(otlp)
+        .try_init()?;
+    Ok(Telemetry { provider })
+}
@@ -0,0 +1,65 @@
+use log::{error, info, warn};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{Error, Result};
+
+/// Where alerts go besides the log
+#[derive(Clone, Debug, Default, Deserialize)]
+pub struct AlertConfig {
+    /// Environment variable holding a webhook URL; alerts are posted to it as `{"text": ...}`,
+    /// which Slack and Mattermost incoming webhooks accept
+    pub webhook_url_env: Option<String>,
+}
+
+/// Reports on guarded escrows: always in the log, and on the webhook when one is set
+#[derive(Clone, Debug)]
+pub struct Alerter {
+    http: reqwest::Client,
+    webhook_url: Option<String>,
+}
+
+impl Alerter {
+    pub fn new(config: &AlertConfig) -> Result<Self> {
+        let webhook_url = match &config.webhook_url_env {
+            Some(name) => {
+                Some(std::env::var(name).map_err(|_| Error::Config(format!("{name} is not set")))?)
+            }
+            None => None,
+        };
+        Ok(Self {
+            http: reqwest::Client::new(),
+            webhook_url,
+        })
+    }
+
+    /// Something needs an operator, e.g. cancellations keep failing
+    pub async fn alert(&self, message: &str) {
+        error!("alert: {message}");
+        self.post(message).await;
+    }
+
+    /// Something the maker wants to hear about, e.g. funds came back
+    pub async fn notify(&self, message: &str) {
+        info!("{message}");
+        self.post(message).await;
+    }
+
+    /// A webhook that cannot be reached is only logged, so alerting never holds up a
+    /// cancellation
+    async fn post(&self, message: &str) {
+        let Some(url) = &self.webhook_url else {
+            return;
+        };
+        let result = self
+            .http
+            .post(url)
+            .json(&json!({ "text": format!("unite watchtower: {message}") }))
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        if let Err(error) = result {
+            warn!("alert webhook failed: {error}");
+        }
+    }
+}
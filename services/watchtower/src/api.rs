@@ -0,0 +1,60 @@
+use std::sync::Arc;
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use unite_core::EscrowId;
+
+use crate::guard::{Guard, Target};
+use crate::watchtower::Watchtower;
+use crate::Error;
+
+/// `POST /escrows` takes a `Target` to guard; `GET /escrows` and
+/// `GET /escrows/{chain}/{escrow_id}` return persisted guard state; `GET /healthz` and
+/// `GET /readyz` report liveness and chain RPC health
+pub fn router(watchtower: Arc<Watchtower>) -> Router {
+    let health = watchtower.health().router();
+    Router::new()
+        .route("/escrows", get(guards).post(protect))
+        .route("/escrows/{chain}/{escrow_id}", get(guard))
+        .with_state(watchtower)
+        .merge(health)
+}
+
+async fn protect(
+    State(watchtower): State<Arc<Watchtower>>,
+    Json(target): Json<Target>,
+) -> Result<(StatusCode, Json<Guard>), Error> {
+    Ok((StatusCode::ACCEPTED, Json(watchtower.protect(&target)?)))
+}
+
+async fn guards(State(watchtower): State<Arc<Watchtower>>) -> Result<Json<Vec<Guard>>, Error> {
+    Ok(Json(watchtower.guards()?))
+}
+
+async fn guard(
+    State(watchtower): State<Arc<Watchtower>>,
+    Path((chain, escrow_id)): Path<(String, String)>,
+) -> Result<Json<Guard>, Error> {
+    let id: EscrowId = hex::decode(&escrow_id)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| Error::InvalidEscrowId(escrow_id.clone()))?;
+    watchtower
+        .guard(&chain, &id)?
+        .map(Json)
+        .ok_or(Error::UnknownEscrow(escrow_id))
+}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            Error::UnknownEscrow(_) => StatusCode::NOT_FOUND,
+            Error::UnknownChain(_) | Error::InvalidEscrowId(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        (status, self.to_string()).into_response()
+    }
+}
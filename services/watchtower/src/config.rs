@@ -0,0 +1,80 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use unite_config::{Layers, Problems, Validate};
+use unite_relayer::{validate_chains, ChainConfig};
+use unite_secrets::SecretsConfig;
+
+use crate::alert::AlertConfig;
+use crate::guard::{Policy, Target};
+use crate::Result;
+
+/// Watchtower settings, read from a TOML file with its environment's overlay and
+/// `UNITE_WATCHTOWER__*` overrides; keys are read from the environment variables or secret
+/// store entries the file names, never from the file itself
+#[derive(Clone, Debug, Deserialize)]
+pub struct Config {
+    /// Address of the registration API
+    #[serde(default = "default_listen")]
+    pub listen: SocketAddr,
+    /// Directory guards are persisted in
+    pub store: PathBuf,
+    #[serde(default)]
+    pub policy: Policy,
+    /// How often to check the config files for edits and apply a changed `[policy]`; other
+    /// changes wait for a restart. Off without it
+    pub reload_secs: Option<u64>,
+    #[serde(default)]
+    pub alerts: AlertConfig,
+    /// Escrows guarded from startup, besides those registered through the API
+    #[serde(default)]
+    pub escrows: Vec<Target>,
+    /// Where keys named by `*_store` fields are kept
+    pub secrets: Option<SecretsConfig>,
+    /// Chains to guard escrows on, each signing as the escrows' sender, since only the sender
+    /// can cancel
+    pub chains: Vec<ChainConfig>,
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::layers(path).load()?)
+    }
+
+    /// Where the config at `path` is read from, for watching it
+    pub fn layers(path: impl AsRef<Path>) -> Layers {
+        Layers::new(path.as_ref(), "UNITE_WATCHTOWER")
+    }
+}
+
+impl Validate for Config {
+    fn validate(&self, problems: &mut Problems) {
+        validate_chains(&self.chains, problems);
+        problems.section("policy", &self.policy);
+        for (index, target) in self.escrows.iter().enumerate() {
+            problems.check(
+                self.chains.iter().any(|chain| chain.name() == target.chain),
+                || format!("escrows[{index}]: no chain named {}", target.chain),
+            );
+        }
+    }
+}
+
+impl Validate for Policy {
+    fn validate(&self, problems: &mut Problems) {
+        problems.check(self.tick_secs > 0, || {
+            "tick_secs must be positive".to_string()
+        });
+        problems.check(self.retry_secs > 0, || {
+            "retry_secs must be positive".to_string()
+        });
+        problems.check(self.max_retry_secs >= self.retry_secs, || {
+            "max_retry_secs must be at least retry_secs".to_string()
+        });
+    }
+}
+
+fn default_listen() -> SocketAddr {
+    SocketAddr::from(([127, 0, 0, 1], 8082))
+}
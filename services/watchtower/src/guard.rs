@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use unite_core::{ChainEvent, EscrowEvent, EscrowId, TxId};
+
+/// An escrow to guard, as configured or registered through the API
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Target {
+    /// Name the chain's adapter reports, e.g. `stellar` or `base`
+    pub chain: String,
+    #[serde(with = "hex")]
+    pub escrow_id: EscrowId,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// Not yet seen created on chain
+    Pending,
+    /// Created; cancelled once its timelock and the grace period pass
+    Locked,
+    /// Timelock passed; cancellations are being sent
+    Cancelling,
+    /// Refunded to the sender
+    Refunded,
+    /// Claimed by the receiver before it expired, so nothing is left to reclaim
+    Claimed,
+}
+
+/// Tunables for guarding escrows; a running watchtower takes new values on a config reload,
+/// except `tick_secs` for escrows already guarded
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct Policy {
+    /// Wait after the timelock before cancelling, since chain clocks trail the wall clock and
+    /// a cancellation sent before the chain sees the timelock pass is rejected
+    pub grace_secs: u64,
+    /// Wait for the refund event of a sent cancellation before sending another
+    pub confirm_secs: u64,
+    /// First wait after a failed cancellation, doubled after each further failure
+    pub retry_secs: u64,
+    /// Longest wait between failed cancellations
+    pub max_retry_secs: u64,
+    /// Consecutive failures after which every further failure raises an alert
+    pub alert_after: u32,
+    /// How often escrows are re-checked for expired timelocks and due retries
+    pub tick_secs: u64,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Self {
+            grace_secs: 30,
+            confirm_secs: 120,
+            retry_secs: 30,
+            max_retry_secs: 900,
+            alert_after: 3,
+            tick_secs: 15,
+        }
+    }
+}
+
+/// One guarded escrow and the watchtower's progress on it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Guard {
+    pub chain: String,
+    #[serde(with = "hex")]
+    pub escrow_id: EscrowId,
+    pub status: Status,
+    /// Unix seconds after which the escrow can be cancelled; `None` until it is seen created
+    pub timelock: Option<u64>,
+    /// Height of the last event applied, where watching resumes after a restart
+    pub height: u64,
+    /// Cancellations that failed since the last one that was accepted
+    pub failures: u32,
+    pub last_error: Option<String>,
+    /// Last accepted cancellation, kept until its refund event arrives
+    pub tx: Option<TxId>,
+    /// Unix seconds of the last cancellation sent
+    pub attempted_at: Option<u64>,
+    /// Unix seconds before which no cancellation is sent
+    pub retry_at: u64,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
+
+impl Guard {
+    pub fn new(target: &Target, now: u64) -> Self {
+        Self {
+            chain: target.chain.clone(),
+            escrow_id: target.escrow_id,
+            status: Status::Pending,
+            timelock: None,
+            height: 0,
+            failures: 0,
+            last_error: None,
+            tx: None,
+            attempted_at: None,
+            retry_at: 0,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn is_settled(&self) -> bool {
+        matches!(self.status, Status::Refunded | Status::Claimed)
+    }
+
+    /// Apply a chain event of the escrow; events replayed after a restart apply again harmlessly
+    pub fn apply(&mut self, event: &ChainEvent) {
+        self.height = self.height.max(event.height);
+        match (&event.event, event.removed) {
+            (EscrowEvent::Created { timelock, .. }, false) => {
+                self.timelock = Some(*timelock);
+                if self.status == Status::Pending {
+                    self.status = Status::Locked;
+                }
+            }
+            (EscrowEvent::Created { .. }, true) => {
+                self.timelock = None;
+                self.status = Status::Pending;
+            }
+            (EscrowEvent::Claimed { .. }, false) => self.settle(Status::Claimed),
+            (EscrowEvent::Refunded, false) => self.settle(Status::Refunded),
+            // A reorg dropped the settlement, so the escrow is open again
+            (EscrowEvent::Claimed { .. } | EscrowEvent::Refunded, true) => {
+                self.status = Status::Locked;
+            }
+        }
+    }
+
+    /// Update the status and tell whether a cancellation is due at `now`
+    ///
+    /// A cancellation is due once the timelock and grace period have passed, unless an
+    /// accepted one is still waiting `confirm_secs` for its refund event or a failed one is
+    /// backing off.
+    pub fn step(&mut self, now: u64, policy: &Policy) -> bool {
+        if self.is_settled() {
+            return false;
+        }
+        let Some(timelock) = self.timelock else {
+            return false;
+        };
+        if now < timelock.saturating_add(policy.grace_secs) {
+            return false;
+        }
+        self.status = Status::Cancelling;
+        if now < self.retry_at {
+            return false;
+        }
+        match (&self.tx, self.attempted_at) {
+            (Some(_), Some(at)) => now >= at.saturating_add(policy.confirm_secs),
+            _ => true,
+        }
+    }
+
+    /// Record a cancellation the chain accepted
+    pub fn accepted(&mut self, tx: TxId, now: u64) {
+        self.tx = Some(tx);
+        self.failures = 0;
+        self.last_error = None;
+        self.attempted_at = Some(now);
+        self.retry_at = 0;
+    }
+
+    /// Record a failed cancellation and back off before the next
+    pub fn failed(&mut self, error: String, now: u64, policy: &Policy) {
+        self.failures = self.failures.saturating_add(1);
+        self.last_error = Some(error);
+        self.attempted_at = Some(now);
+        let backoff = policy
+            .retry_secs
+            .saturating_mul(1 << (self.failures - 1).min(16))
+            .min(policy.max_retry_secs);
+        self.retry_at = now.saturating_add(backoff);
+    }
+
+    fn settle(&mut self, status: Status) {
+        self.status = status;
+        self.retry_at = 0;
+    }
+}
@@ -0,0 +1,43 @@
+// Watchtower
+// Reclaims funds for makers who go offline. It follows the escrows it is told to guard
+// (listed in its config or registered over HTTP) through their chains' `ChainAdapter`s, and
+// once an escrow's timelock passes unclaimed it cancels it as the sender, so the refund does
+// not wait for the maker to come back. Failed cancellations are retried with exponential
+// backoff and raise alerts when they keep failing. Each guard is persisted after every
+// change, so a restarted watchtower resumes every unsettled escrow where it left off.
+// `/readyz` probes every chain's RPC.
+
+pub mod alert;
+pub mod api;
+pub mod config;
+pub mod guard;
+pub mod store;
+pub mod watchtower;
+
+pub use alert::{AlertConfig, Alerter};
+pub use config::Config;
+pub use guard::{Guard, Policy, Status, Target};
+pub use store::GuardStore;
+pub use watchtower::Watchtower;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("config: {0}")]
+    Config(String),
+    #[error("no adapter for chain {0}")]
+    UnknownChain(String),
+    #[error("escrow {0} is not guarded")]
+    UnknownEscrow(String),
+    #[error("invalid escrow ID {0}")]
+    InvalidEscrowId(String),
+    #[error(transparent)]
+    Relayer(#[from] unite_relayer::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Load(#[from] unite_config::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
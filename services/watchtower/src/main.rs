@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use log::{info, warn};
+use tokio::net::TcpListener;
+use unite_watchtower::{api, Alerter, Config, GuardStore, Watchtower};
+
+/// `unite-watchtower [config.toml]`, logging through `RUST_LOG`
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "watchtower.toml".to_string());
+    let config = Config::load(&path)?;
+
+    let secrets = match &config.secrets {
+        Some(secrets) => Some(secrets.open().await?),
+        None => None,
+    };
+    let mut chains = Vec::new();
+    for chain in &config.chains {
+        chains.push(chain.connect(secrets.as_deref()).await?);
+    }
+    let watchtower = Watchtower::new(
+        chains,
+        GuardStore::open(&config.store)?,
+        config.policy,
+        Alerter::new(&config.alerts)?,
+    );
+    info!(
+        "chains: {}",
+        watchtower.chains().collect::<Vec<_>>().join(", ")
+    );
+    info!("resumed {} unsettled escrows", watchtower.recover()?);
+    if let Some(secs) = config.reload_secs {
+        let watchtower = watchtower.clone();
+        unite_config::watch(
+            Config::layers(&path),
+            Duration::from_secs(secs),
+            move |reloaded: unite_config::Result<Config>| match reloaded {
+                Ok(config) => {
+                    watchtower.set_policy(config.policy);
+                    info!("reloaded [policy]");
+                }
+                Err(error) => warn!("config not reloaded: {error}"),
+            },
+        );
+    }
+    for target in &config.escrows {
+        watchtower.protect(target)?;
+    }
+
+    let listener = TcpListener::bind(config.listen).await?;
+    info!("listening on {}", config.listen);
+    axum::serve(listener, api::router(watchtower))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await?;
+    Ok(())
+}
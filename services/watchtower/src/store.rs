@@ -0,0 +1,60 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use unite_core::EscrowId;
+
+use crate::guard::Guard;
+use crate::Result;
+
+/// Guards persisted as one JSON file each, replaced atomically on every save so a crash
+/// leaves either the old or the new state on disk
+#[derive(Clone, Debug)]
+pub struct GuardStore {
+    dir: PathBuf,
+}
+
+impl GuardStore {
+    pub fn open(dir: impl AsRef<Path>) -> Result<Self> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    pub fn save(&self, guard: &Guard) -> Result<()> {
+        let path = self.path(&guard.chain, &guard.escrow_id);
+        let temporary = path.with_extension("json.tmp");
+        fs::write(&temporary, serde_json::to_vec_pretty(guard)?)?;
+        fs::rename(temporary, path)?;
+        Ok(())
+    }
+
+    pub fn load(&self, chain: &str, escrow_id: &EscrowId) -> Result<Option<Guard>> {
+        match fs::read(self.path(chain, escrow_id)) {
+            Ok(json) => Ok(Some(serde_json::from_slice(&json)?)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.into()),
+        }
+    }
+
+    pub fn all(&self) -> Result<Vec<Guard>> {
+        let mut guards = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                guards.push(serde_json::from_slice(&fs::read(path)?)?);
+            }
+        }
+        Ok(guards)
+    }
+
+    /// Chain names come from the configured adapters, so they are safe in file names
+    fn path(&self, chain: &str, escrow_id: &EscrowId) -> PathBuf {
+        self.dir
+            .join(format!("{chain}-{}.json", hex::encode(escrow_id)))
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use futures::StreamExt;
+use log::{info, warn};
+use unite_core::{ChainAdapter, EscrowEvent, EscrowId, EventStream};
+use unite_relayer::health;
+use unite_telemetry::Health;
+
+use crate::alert::Alerter;
+use crate::guard::{Guard, Policy, Status, Target};
+use crate::store::GuardStore;
+use crate::{Error, Result};
+
+/// Guards escrows for makers who may be offline when they expire, one task per escrow
+///
+/// An escrow's task follows it through its chain adapter until it is claimed or refunded.
+/// Once its timelock and the grace period pass it sends cancellations, backing off after
+/// failures and alerting when they keep failing, and saves the guard after every change so
+/// a restarted watchtower resumes where it left off.
+pub struct Watchtower {
+    chains: HashMap<String, Arc<dyn ChainAdapter>>,
+    store: GuardStore,
+    policy: RwLock<Policy>,
+    alerter: Alerter,
+    running: Mutex<HashSet<(String, EscrowId)>>,
+}
+
+impl Watchtower {
+    pub fn new(
+        chains: Vec<Arc<dyn ChainAdapter>>,
+        store: GuardStore,
+        policy: Policy,
+        alerter: Alerter,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            chains: chains
+                .into_iter()
+                .map(|adapter| (adapter.chain().to_string(), adapter))
+                .collect(),
+            store,
+            policy: RwLock::new(policy),
+            alerter,
+            running: Mutex::new(HashSet::new()),
+        })
+    }
+
+    pub fn chains(&self) -> impl Iterator<Item = &str> {
+        self.chains.keys().map(String::as_str)
+    }
+
+    pub fn policy(&self) -> Policy {
+        *self.policy.read().unwrap()
+    }
+
+    /// Use `policy` from the next step of every guard on
+    pub fn set_policy(&self, policy: Policy) {
+        *self.policy.write().unwrap() = policy;
+    }
+
+    /// Probes for the RPC of every chain
+    pub fn health(&self) -> Health {
+        health::chains(self.chains.values())
+    }
+
+    /// Restart the tasks of escrows left unsettled by a previous run, returning how many
+    pub fn recover(self: &Arc<Self>) -> Result<usize> {
+        let mut running = self.running.lock().unwrap();
+        let mut resumed = 0;
+        for guard in self.store.all()? {
+            if guard.is_settled() || running.contains(&key(&guard)) {
+                continue;
+            }
+            if !self.chains.contains_key(&guard.chain) {
+                warn!("{}: no adapter for {}", label(&guard), guard.chain);
+                continue;
+            }
+            self.spawn(&mut running, guard);
+            resumed += 1;
+        }
+        Ok(resumed)
+    }
+
+    /// Start guarding an escrow; guarding one that is already guarded or settled changes
+    /// nothing and returns its current state
+    pub fn protect(self: &Arc<Self>, target: &Target) -> Result<Guard> {
+        if !self.chains.contains_key(&target.chain) {
+            return Err(Error::UnknownChain(target.chain.clone()));
+        }
+        let mut running = self.running.lock().unwrap();
+        if let Some(guard) = self.store.load(&target.chain, &target.escrow_id)? {
+            if !guard.is_settled() && !running.contains(&key(&guard)) {
+                self.spawn(&mut running, guard.clone());
+            }
+            return Ok(guard);
+        }
+        let guard = Guard::new(target, now());
+        self.store.save(&guard)?;
+        self.spawn(&mut running, guard.clone());
+        Ok(guard)
+    }
+
+    pub fn guard(&self, chain: &str, escrow_id: &EscrowId) -> Result<Option<Guard>> {
+        self.store.load(chain, escrow_id)
+    }
+
+    pub fn guards(&self) -> Result<Vec<Guard>> {
+        self.store.all()
+    }
+
+    fn spawn(self: &Arc<Self>, running: &mut HashSet<(String, EscrowId)>, guard: Guard) {
+        running.insert(key(&guard));
+        info!("{}: guarding ({:?})", label(&guard), guard.status);
+        tokio::spawn(self.clone().drive(guard));
+    }
+
+    async fn drive(self: Arc<Self>, mut guard: Guard) {
+        let adapter = self.chains[&guard.chain].clone();
+        let mut events: Option<EventStream> = None;
+        let mut tick = tokio::time::interval(Duration::from_secs(self.policy().tick_secs));
+        loop {
+            let before = guard.clone();
+            if events.is_none() {
+                match adapter.watch_events(guard.escrow_id, guard.height).await {
+                    Ok(stream) => events = Some(stream),
+                    Err(error) => warn!("{}: watch: {error}", label(&guard)),
+                }
+            }
+            let next = async {
+                match events.as_mut() {
+                    Some(stream) => stream.next().await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                item = next => match item {
+                    Some(Ok(event)) => {
+                        let name = match &event.event {
+                            EscrowEvent::Created { .. } => "created",
+                            EscrowEvent::Claimed { .. } => "claimed",
+                            EscrowEvent::Refunded => "refunded",
+                        };
+                        info!(
+                            "{}: {name} at {}{}",
+                            label(&guard),
+                            event.height,
+                            if event.removed { " (removed)" } else { "" }
+                        );
+                        guard.apply(&event);
+                    }
+                    Some(Err(error)) => warn!("{}: watch: {error}", label(&guard)),
+                    // The stream ended; it is reopened while the escrow is unsettled
+                    None => events = None,
+                },
+                _ = tick.tick() => {}
+            }
+
+            let due = guard.step(now(), &self.policy());
+            if guard.status != before.status {
+                self.report(&guard).await;
+            }
+            if due {
+                self.cancel(&mut guard).await;
+            }
+            if guard != before {
+                guard.updated_at = now();
+                if let Err(error) = self.store.save(&guard) {
+                    warn!("{}: saving failed: {error}", label(&guard));
+                }
+            }
+            if guard.is_settled() {
+                break;
+            }
+        }
+        self.running.lock().unwrap().remove(&key(&guard));
+    }
+
+    async fn cancel(&self, guard: &mut Guard) {
+        let adapter = &self.chains[&guard.chain];
+        if let (Some(tx), 0) = (&guard.tx, guard.failures) {
+            warn!(
+                "{}: no refund seen {}s after {tx}, cancelling again",
+                label(guard),
+                self.policy().confirm_secs
+            );
+        }
+        match adapter.refund(&guard.escrow_id).await {
+            Ok(tx) => {
+                info!("{}: cancellation sent in {tx}", label(guard));
+                guard.accepted(tx, now());
+            }
+            Err(error) => {
+                warn!("{}: cancellation failed: {error}", label(guard));
+                guard.failed(error.to_string(), now(), &self.policy());
+                if guard.failures >= self.policy().alert_after {
+                    self.alerter
+                        .alert(&format!(
+                            "{}: cancellation failed {} times in a row, last with: {error}",
+                            label(guard),
+                            guard.failures
+                        ))
+                        .await;
+                }
+            }
+        }
+    }
+
+    async fn report(&self, guard: &Guard) {
+        match guard.status {
+            Status::Refunded => {
+                self.alerter
+                    .notify(&format!("{}: refunded to the sender", label(guard)))
+                    .await
+            }
+            Status::Cancelling => info!("{}: timelock passed, cancelling", label(guard)),
+            status => info!("{}: {status:?}", label(guard)),
+        }
+    }
+}
+
+fn key(guard: &Guard) -> (String, EscrowId) {
+    (guard.chain.clone(), guard.escrow_id)
+}
+
+fn label(guard: &Guard) -> String {
+    format!("{} escrow {}", guard.chain, hex::encode(guard.escrow_id))
+}
+
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
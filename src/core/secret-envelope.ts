@@ -0,0 +1,93 @@
+// Secret envelopes attached to Stellar escrows with `attach_secret`
+//
+// Layout: ephemeral X25519 public key (32) || nonce (12) || ChaCha20-Poly1305 ciphertext + tag.
+// The key is HKDF-SHA256 over the X25519 shared secret and the escrow ID is the associated
+// data, so an envelope can only be opened by the resolver and only for the escrow it names.
+
+import {
+  createCipheriv,
+  createDecipheriv,
+  createPrivateKey,
+  createPublicKey,
+  diffieHellman,
+  generateKeyPairSync,
+  hkdfSync,
+  randomBytes,
+  KeyObject,
+} from 'crypto';
+
+const SPKI_PREFIX = Buffer.from('302a300506032b656e032100', 'hex');
+const PKCS8_PREFIX = Buffer.from('302e020100300506032b656e04220420', 'hex');
+const HKDF_INFO = Buffer.from('unite-secret-envelope-v1');
+const KEY_LEN = 32;
+const NONCE_LEN = 12;
+const TAG_LEN = 16;
+
+export interface X25519KeyPair {
+  publicKey: Buffer;
+  privateKey: Buffer;
+}
+
+/** Generate a resolver's X25519 key pair as raw 32-byte keys */
+export function generateResolverKeyPair(): X25519KeyPair {
+  const { publicKey, privateKey } = generateKeyPairSync('x25519');
+  return {
+    publicKey: rawPublicKey(publicKey),
+    privateKey: privateKey.export({ format: 'der', type: 'pkcs8' }).subarray(PKCS8_PREFIX.length),
+  };
+}
+
+/** Encrypt a 32-byte secret to the resolver's X25519 public key for the given escrow */
+export function encryptSecret(secret: Buffer, resolverPublicKey: Buffer, escrowId: Buffer): Buffer {
+  const ephemeral = generateKeyPairSync('x25519');
+  const ephemeralPublic = rawPublicKey(ephemeral.publicKey);
+  const shared = diffieHellman({
+    privateKey: ephemeral.privateKey,
+    publicKey: importPublicKey(resolverPublicKey),
+  });
+  const key = deriveKey(shared, ephemeralPublic, resolverPublicKey);
+
+  const nonce = randomBytes(NONCE_LEN);
+  const cipher = createCipheriv('chacha20-poly1305', key, nonce, { authTagLength: TAG_LEN });
+  cipher.setAAD(escrowId, { plaintextLength: secret.length });
+  const sealed = Buffer.concat([cipher.update(secret), cipher.final(), cipher.getAuthTag()]);
+
+  return Buffer.concat([ephemeralPublic, nonce, sealed]);
+}
+
+/** Open an envelope with the resolver's X25519 private key, throwing if it was tampered with */
+export function decryptSecret(envelope: Buffer, resolverPrivateKey: Buffer, escrowId: Buffer): Buffer {
+  if (envelope.length < KEY_LEN + NONCE_LEN + TAG_LEN) {
+    throw new Error('Secret envelope is too short');
+  }
+  const ephemeralPublic = envelope.subarray(0, KEY_LEN);
+  const nonce = envelope.subarray(KEY_LEN, KEY_LEN + NONCE_LEN);
+  const ciphertext = envelope.subarray(KEY_LEN + NONCE_LEN, envelope.length - TAG_LEN);
+  const tag = envelope.subarray(envelope.length - TAG_LEN);
+
+  const privateKey = importPrivateKey(resolverPrivateKey);
+  const shared = diffieHellman({ privateKey, publicKey: importPublicKey(ephemeralPublic) });
+  const key = deriveKey(shared, ephemeralPublic, rawPublicKey(createPublicKey(privateKey)));
+
+  const decipher = createDecipheriv('chacha20-poly1305', key, nonce, { authTagLength: TAG_LEN });
+  decipher.setAAD(escrowId, { plaintextLength: ciphertext.length });
+  decipher.setAuthTag(tag);
+  return Buffer.concat([decipher.update(ciphertext), decipher.final()]);
+}
+
+function deriveKey(shared: Buffer, ephemeralPublic: Buffer, resolverPublic: Buffer): Buffer {
+  const salt = Buffer.concat([ephemeralPublic, resolverPublic]);
+  return Buffer.from(hkdfSync('sha256', shared, salt, HKDF_INFO, KEY_LEN));
+}
+
+function rawPublicKey(key: KeyObject): Buffer {
+  return key.export({ format: 'der', type: 'spki' }).subarray(SPKI_PREFIX.length);
+}
+
+function importPublicKey(raw: Buffer): KeyObject {
+  return createPublicKey({ key: Buffer.concat([SPKI_PREFIX, raw]), format: 'der', type: 'spki' });
+}
+
+function importPrivateKey(raw: Buffer): KeyObject {
+  return createPrivateKey({ key: Buffer.concat([PKCS8_PREFIX, raw]), format: 'der', type: 'pkcs8' });
+}